@@ -0,0 +1,360 @@
+//! `#[derive(DynamoEntity)]`, which implements `ddb-simple`'s
+//! `DynamoEntity` trait for a struct with named fields.
+//!
+//! The struct names its table, and marks its key fields:
+//!
+//! ```ignore
+//! #[derive(Serialize, Deserialize, DynamoEntity)]
+//! #[dynamo(table = "products")]
+//! struct Product {
+//!     #[dynamo(partition_key)]
+//!     category: String,
+//!     #[dynamo(sort_key, rename = "product_name")]
+//!     name: String,
+//!     price: f64,
+//! }
+//! ```
+//!
+//! Field attributes:
+//!
+//! - `partition_key`: the table's partition key. Exactly one field has it.
+//! - `sort_key`: the table's sort key, if it has one.
+//! - `rename = "..."`: the attribute the field is stored as. Set it
+//!   whenever serde renames the field other than with a field-level
+//!   `#[serde(rename = "...")]`, which is read too.
+//! - `skip`: leaves the field out of the schema, for types the schema
+//!   can't describe.
+//!
+//! Every other field's type goes into the schema: strings are `String`,
+//! integers and floats `Number`, `bool` `Boolean`, vectors and sets of
+//! strings `StringList`, other vectors and sets `List`, and maps `Map`.
+//! `Option` fields are checked for a supported type but left out of the
+//! schema, since `None` is written as `NULL`. Keys must be strings or
+//! numbers. Anything else is a compile error.
+
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::quote;
+use syn::meta::ParseNestedMeta;
+use syn::{
+    parse_macro_input, Data, DeriveInput, Error, Expr, Field, Fields, GenericArgument, LitStr,
+    PathArguments, Result, Token, Type,
+};
+
+/// Implements `DynamoEntity`; see the crate documentation for the
+/// attributes it reads.
+#[proc_macro_derive(DynamoEntity, attributes(dynamo))]
+pub fn derive_dynamo_entity(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(&input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+/// The attribute types the schema records, named as `FieldType` variants.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    String,
+    Number,
+    Boolean,
+    StringList,
+    List,
+    Map,
+}
+
+/// What a field's type means for the schema.
+enum FieldKind {
+    /// Recorded in the schema as this type.
+    Typed(Kind),
+    /// Supported, but not recorded: an `Option`.
+    Optional,
+}
+
+/// A field as the derive sees it.
+struct EntityField<'a> {
+    field: &'a Field,
+    attribute: String,
+    partition_key: bool,
+    sort_key: bool,
+    skip: bool,
+}
+
+fn expand(input: &DeriveInput) -> Result<TokenStream2> {
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(Error::new_spanned(
+                    &input.ident,
+                    "DynamoEntity can only be derived for structs with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "DynamoEntity can only be derived for structs",
+            ))
+        }
+    };
+
+    let table = table_name(input)?;
+    let fields = fields
+        .iter()
+        .map(entity_field)
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut partition_key = None;
+    let mut sort_key = None;
+    let mut schema = Vec::new();
+    for field in &fields {
+        let ty = &field.field.ty;
+        for (is_key, slot, name) in [
+            (field.partition_key, &mut partition_key, "partition_key"),
+            (field.sort_key, &mut sort_key, "sort_key"),
+        ] {
+            if !is_key {
+                continue;
+            }
+            if slot.is_some() {
+                return Err(Error::new_spanned(
+                    field.field,
+                    format!("only one field can be the {name}"),
+                ));
+            }
+            if field.skip {
+                return Err(Error::new_spanned(
+                    field.field,
+                    format!("the {name} can't be skipped"),
+                ));
+            }
+            match field_kind(ty)? {
+                FieldKind::Typed(kind @ (Kind::String | Kind::Number)) => {
+                    *slot = Some((field, kind));
+                }
+                _ => {
+                    return Err(Error::new_spanned(
+                        ty,
+                        format!("the {name} must be a string or a number"),
+                    ))
+                }
+            }
+        }
+        if field.skip {
+            continue;
+        }
+        if let FieldKind::Typed(kind) = field_kind(ty)? {
+            schema.push((field.attribute.as_str(), kind));
+        }
+    }
+    let Some(partition_key) = partition_key else {
+        return Err(Error::new_spanned(
+            &input.ident,
+            "DynamoEntity needs a field marked #[dynamo(partition_key)]",
+        ));
+    };
+
+    let krate = quote!(::aws_dynamodb_testing);
+    let partition_key_name = &partition_key.0.attribute;
+    let partition_key_value = key_value(partition_key);
+    let (sort_key_name, sort_key_value) = match sort_key {
+        Some(sort_key) => {
+            let name = &sort_key.0.attribute;
+            let value = key_value(sort_key);
+            (quote!(Some(#name)), quote!(Some(#value)))
+        }
+        None => (quote!(None), quote!(None)),
+    };
+    let schema = schema.iter().map(|(name, kind)| {
+        let variant = syn::Ident::new(&format!("{kind:?}"), Span::call_site());
+        quote!(.add_field(#name, #krate::dynamodb::FieldType::#variant))
+    });
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics #krate::dynamodb::DynamoEntity for #name #ty_generics #where_clause {
+            fn table_name() -> &'static str {
+                #table
+            }
+
+            fn partition_key_name() -> &'static str {
+                #partition_key_name
+            }
+
+            fn sort_key_name() -> ::std::option::Option<&'static str> {
+                #sort_key_name
+            }
+
+            fn schema() -> #krate::dynamodb::Schema {
+                #krate::dynamodb::Schema::new() #(#schema)*
+            }
+
+            fn partition_key_value(&self) -> #krate::aws::AttributeValue {
+                #partition_key_value
+            }
+
+            fn sort_key_value(&self) -> ::std::option::Option<#krate::aws::AttributeValue> {
+                #sort_key_value
+            }
+        }
+    })
+}
+
+/// The `table` from the struct's `#[dynamo(...)]` attribute.
+fn table_name(input: &DeriveInput) -> Result<LitStr> {
+    let mut table = None;
+    for attr in input
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("dynamo"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("table") {
+                table = Some(meta.value()?.parse::<LitStr>()?);
+                Ok(())
+            } else {
+                Err(meta.error("unknown dynamo attribute; expected `table = \"...\"`"))
+            }
+        })?;
+    }
+    table.ok_or_else(|| {
+        Error::new_spanned(
+            &input.ident,
+            "DynamoEntity needs the table name: #[dynamo(table = \"...\")]",
+        )
+    })
+}
+
+/// Reads a field's `#[dynamo(...)]` and `#[serde(rename = "...")]`
+/// attributes.
+fn entity_field(field: &Field) -> Result<EntityField<'_>> {
+    let mut entity_field = EntityField {
+        field,
+        attribute: field
+            .ident
+            .as_ref()
+            .map(|ident| ident.to_string().trim_start_matches("r#").to_string())
+            .unwrap_or_default(),
+        partition_key: false,
+        sort_key: false,
+        skip: false,
+    };
+    let mut rename = None;
+    let mut serde_rename = None;
+    for attr in &field.attrs {
+        if attr.path().is_ident("dynamo") {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("partition_key") {
+                    entity_field.partition_key = true;
+                } else if meta.path.is_ident("sort_key") {
+                    entity_field.sort_key = true;
+                } else if meta.path.is_ident("skip") {
+                    entity_field.skip = true;
+                } else if meta.path.is_ident("rename") {
+                    rename = Some(meta.value()?.parse::<LitStr>()?.value());
+                } else {
+                    return Err(meta.error(
+                        "unknown dynamo attribute; expected `partition_key`, `sort_key`, \
+                         `rename = \"...\"` or `skip`",
+                    ));
+                }
+                Ok(())
+            })?;
+        } else if attr.path().is_ident("serde") {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("rename") && meta.input.peek(Token![=]) {
+                    serde_rename = Some(meta.value()?.parse::<LitStr>()?.value());
+                    Ok(())
+                } else if meta.path.is_ident("skip") || meta.path.is_ident("skip_serializing") {
+                    entity_field.skip = true;
+                    Ok(())
+                } else {
+                    skip_meta(meta)
+                }
+            })?;
+        }
+    }
+    if entity_field.partition_key && entity_field.sort_key {
+        return Err(Error::new_spanned(
+            field,
+            "a field can't be both the partition_key and the sort_key",
+        ));
+    }
+    if let Some(attribute) = rename.or(serde_rename) {
+        entity_field.attribute = attribute;
+    }
+    Ok(entity_field)
+}
+
+/// Consumes a `serde` attribute entry the derive doesn't care about.
+fn skip_meta(meta: ParseNestedMeta) -> Result<()> {
+    if meta.input.peek(Token![=]) {
+        meta.value()?.parse::<Expr>()?;
+    } else if meta.input.peek(syn::token::Paren) {
+        meta.parse_nested_meta(skip_meta)?;
+    }
+    Ok(())
+}
+
+/// What `ty` means for the schema, or an error if it isn't supported.
+fn field_kind(ty: &Type) -> Result<FieldKind> {
+    let unsupported = || {
+        Error::new_spanned(
+            ty,
+            "unsupported field type for DynamoEntity; mark the field #[dynamo(skip)] to leave \
+             it out of the schema",
+        )
+    };
+    let path = match ty {
+        Type::Reference(reference) => return field_kind(&reference.elem),
+        Type::Group(group) => return field_kind(&group.elem),
+        Type::Paren(paren) => return field_kind(&paren.elem),
+        Type::Path(path) if path.qself.is_none() => &path.path,
+        _ => return Err(unsupported()),
+    };
+    let segment = path.segments.last().ok_or_else(unsupported)?;
+    let first_argument = || match &segment.arguments {
+        PathArguments::AngleBracketed(arguments) => {
+            arguments.args.iter().find_map(|argument| match argument {
+                GenericArgument::Type(ty) => Some(ty),
+                _ => None,
+            })
+        }
+        _ => None,
+    };
+    let kind = match segment.ident.to_string().as_str() {
+        "String" | "str" | "char" => Kind::String,
+        "i8" | "i16" | "i32" | "i64" | "i128" | "isize" | "u8" | "u16" | "u32" | "u64" | "u128"
+        | "usize" | "f32" | "f64" => Kind::Number,
+        "bool" => Kind::Boolean,
+        "Vec" | "VecDeque" | "HashSet" | "BTreeSet" => {
+            let element = first_argument().ok_or_else(unsupported)?;
+            match field_kind(element)? {
+                FieldKind::Typed(Kind::String) => Kind::StringList,
+                _ => Kind::List,
+            }
+        }
+        "HashMap" | "BTreeMap" => Kind::Map,
+        "Option" => {
+            field_kind(first_argument().ok_or_else(unsupported)?)?;
+            return Ok(FieldKind::Optional);
+        }
+        _ => return Err(unsupported()),
+    };
+    Ok(FieldKind::Typed(kind))
+}
+
+/// The expression giving a key field's `AttributeValue`.
+fn key_value((field, kind): (&EntityField, Kind)) -> TokenStream2 {
+    let ident = &field.field.ident;
+    if kind == Kind::String {
+        quote!(::aws_dynamodb_testing::aws::AttributeValue::S(
+            ::std::string::ToString::to_string(&self.#ident)
+        ))
+    } else {
+        quote!(::aws_dynamodb_testing::aws::AttributeValue::N(
+            ::std::string::ToString::to_string(&self.#ident)
+        ))
+    }
+}
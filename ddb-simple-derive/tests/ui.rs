@@ -0,0 +1,8 @@
+//! The derive's compile errors, checked against `ui/*.stderr`. Regenerate
+//! those with `TRYBUILD=overwrite cargo test -p ddb-simple-derive`.
+
+#[test]
+fn ui() {
+    let cases = trybuild::TestCases::new();
+    cases.compile_fail("tests/ui/*.rs");
+}
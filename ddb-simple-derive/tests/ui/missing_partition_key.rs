@@ -0,0 +1,11 @@
+use ddb_simple_derive::DynamoEntity;
+
+#[derive(DynamoEntity)]
+#[dynamo(table = "products")]
+struct Product {
+    #[dynamo(sort_key)]
+    product_name: String,
+    price: f64,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use ddb_simple_derive::DynamoEntity;
+
+#[derive(DynamoEntity)]
+struct Product {
+    #[dynamo(partition_key)]
+    category: String,
+}
+
+fn main() {}
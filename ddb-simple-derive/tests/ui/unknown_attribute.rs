@@ -0,0 +1,10 @@
+use ddb_simple_derive::DynamoEntity;
+
+#[derive(DynamoEntity)]
+#[dynamo(table = "products")]
+struct Product {
+    #[dynamo(partition_key, index)]
+    category: String,
+}
+
+fn main() {}
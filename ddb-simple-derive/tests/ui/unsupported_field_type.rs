@@ -0,0 +1,16 @@
+use ddb_simple_derive::DynamoEntity;
+
+struct Dimensions {
+    width: f64,
+    height: f64,
+}
+
+#[derive(DynamoEntity)]
+#[dynamo(table = "products")]
+struct Product {
+    #[dynamo(partition_key)]
+    category: String,
+    dimensions: Dimensions,
+}
+
+fn main() {}
@@ -0,0 +1,42 @@
+//! The AWS SDK types this crate's API takes and returns, re-exported so
+//! callers don't need their own `aws-sdk-dynamodb` dependency, or have it
+//! drift to a version whose types don't match ours.
+//!
+//! Anything else from the SDK is under [`sdk`], the same version this crate
+//! is built with.
+//!
+//! ```no_run
+//! use aws_dynamodb_testing::aws::AttributeValue;
+//! use aws_dynamodb_testing::dynamodb::{DynamoDb, QueryFlexibleParams, LOCAL_ENDPOINT};
+//! use std::collections::HashMap;
+//!
+//! # async fn example() -> anyhow::Result<()> {
+//! let ddb = DynamoDb::new_local(LOCAL_ENDPOINT);
+//! let books = ddb
+//!     .query_flexible(QueryFlexibleParams {
+//!         table_name: "products",
+//!         key_condition_expression: "category = :category",
+//!         expression_attribute_values: Some(HashMap::from([(
+//!             ":category".to_string(),
+//!             AttributeValue::S("Books".to_string()),
+//!         )])),
+//!         ..Default::default()
+//!     })
+//!     .await?;
+//! println!("{} books", books.len());
+//! # Ok(())
+//! # }
+//! ```
+
+pub use aws_sdk_dynamodb as sdk;
+pub use aws_sdk_dynamodb::config::retry::RetryMode;
+pub use aws_sdk_dynamodb::operation::create_table::CreateTableOutput;
+pub use aws_sdk_dynamodb::primitives::Blob;
+pub use aws_sdk_dynamodb::types::{
+    AttributeValue, BackupStatus, BackupSummary, BillingMode, ConditionCheck, Delete,
+    ExportDescription, ExportFormat, ExportStatus, ImportStatus, ImportTableDescription,
+    IndexStatus, InputFormat, ProjectionType, Put, ReturnConsumedCapacity,
+    ReturnValuesOnConditionCheckFailure, ScalarAttributeType, Select, StreamViewType,
+    TableDescription, TableStatus, TransactWriteItem, Update,
+};
+pub use aws_sdk_dynamodb::Client;
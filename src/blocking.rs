@@ -0,0 +1,863 @@
+//! A synchronous wrapper around [`DynamoDb`], for tools that would rather
+//! not bring in an async runtime for a few calls.
+//!
+//! [`BlockingDynamoDb`] owns a current-thread Tokio runtime and blocks on
+//! it for each call. Its methods take the same arguments and return the
+//! same results as the [`DynamoDb`] methods of the same name, paginated
+//! and bulk helpers included.
+//!
+//! Calling it from inside an async runtime panics, as blocking there could
+//! stall or deadlock the runtime's other tasks; use [`DynamoDb`] directly
+//! in async code.
+//!
+//! ```no_run
+//! use aws_dynamodb_testing::blocking::BlockingDynamoDb;
+//! use aws_dynamodb_testing::dynamodb::{Item, LOCAL_ENDPOINT};
+//!
+//! fn main() -> anyhow::Result<()> {
+//!     let ddb = BlockingDynamoDb::new_local(LOCAL_ENDPOINT)?;
+//!     ddb.put_item("users", Item::new().set_string("user_id", "123"))?;
+//!     let key = Item::new().set_string("user_id", "123");
+//!     println!("{:?}", ddb.get_item("users", key)?);
+//!     Ok(())
+//! }
+//! ```
+
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::future::Future;
+use std::ops::ControlFlow;
+use std::time::Duration;
+use tokio::runtime::{Handle, Runtime};
+use tokio_util::sync::CancellationToken;
+
+use crate::aws::{
+    AttributeValue, CreateTableOutput, ExportFormat, InputFormat, StreamViewType, TransactWriteItem,
+};
+use crate::dynamodb::{
+    AuthInfo, BackupArn, BackupInfo, BulkResult, ConcurrentPutStats, DynamoDb, DynamoDbBuilder,
+    ExportInfo, Filter, FilterExpression, GetItemOptions, ImportInfo, Item, ItemCount, KeySchema,
+    Progress, QueryFlexibleParams, QueryOptions, QueryResult, ReadResult, ScanParams, ScanStats,
+    ScanSummary, ShardedKey, SortKeyCondition, SortKeyValue, Statement, StatementResult, Table,
+    TableInfo, UpdateBuilder, WriteResult,
+};
+#[cfg(feature = "serde")]
+use crate::dynamodb::{DynamoEntity, TypedItems};
+#[cfg(feature = "serde")]
+use serde::{de::DeserializeOwned, Serialize};
+
+/// The message `BlockingDynamoDb` panics with inside an async runtime.
+const IN_ASYNC_CONTEXT: &str = "BlockingDynamoDb can't be used from within an async runtime, \
+     where blocking would stall its other tasks; use DynamoDb there instead";
+
+/// A [`DynamoDb`] whose methods block until the call completes.
+///
+/// Each client has its own runtime and connection pool. The wrapped client
+/// is available from [`BlockingDynamoDb::inner`] for its non-async methods,
+/// such as [`DynamoDb::metrics`].
+#[derive(Debug)]
+pub struct BlockingDynamoDb {
+    ddb: DynamoDb,
+    runtime: Runtime,
+}
+
+impl BlockingDynamoDb {
+    /// Wraps `ddb`, giving it connections of its own so none are shared
+    /// with a runtime that may go away.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called from within an async runtime.
+    pub fn new(ddb: DynamoDb) -> Result<Self> {
+        Ok(Self {
+            runtime: runtime()?,
+            ddb: ddb.with_new_connections(),
+        })
+    }
+
+    /// Connects to DynamoDB Local, as [`DynamoDb::new_local`] does.
+    pub fn new_local(endpoint: &str) -> Result<Self> {
+        Ok(Self {
+            runtime: runtime()?,
+            ddb: DynamoDb::new_local(endpoint),
+        })
+    }
+
+    /// Connects to AWS as configured by the environment, as
+    /// `DynamoDb::new(&aws_config::load_from_env().await)` does.
+    pub fn from_env() -> Result<Self> {
+        let runtime = runtime()?;
+        let sdk_config = runtime.block_on(aws_config::load_from_env());
+        Ok(Self {
+            ddb: DynamoDb::new(&sdk_config),
+            runtime,
+        })
+    }
+
+    /// Builds the client with `builder`.
+    pub fn from_builder(builder: DynamoDbBuilder) -> Result<Self> {
+        let runtime = runtime()?;
+        let ddb = runtime.block_on(builder.build())?;
+        Ok(Self { ddb, runtime })
+    }
+
+    /// The async client underneath.
+    pub fn inner(&self) -> &DynamoDb {
+        &self.ddb
+    }
+
+    /// Runs `future` to completion on this client's runtime.
+    fn block_on<F: Future>(&self, future: F) -> F::Output {
+        assert!(Handle::try_current().is_err(), "{IN_ASYNC_CONTEXT}");
+        self.runtime.block_on(future)
+    }
+
+    pub fn check_auth(&self) -> Result<Option<AuthInfo>> {
+        self.block_on(self.ddb.check_auth())
+    }
+
+    pub fn create_table_if_not_exists(
+        &self,
+        table: &Table<'_>,
+    ) -> Result<Option<CreateTableOutput>> {
+        self.block_on(self.ddb.create_table_if_not_exists(table))
+    }
+
+    pub fn create_table_if_not_exists_and_wait(
+        &self,
+        table: &Table<'_>,
+        timeout: Duration,
+    ) -> Result<Option<CreateTableOutput>> {
+        self.block_on(self.ddb.create_table_if_not_exists_and_wait(table, timeout))
+    }
+
+    pub fn delete_table(&self, table_name: &str) -> Result<()> {
+        self.block_on(self.ddb.delete_table(table_name))
+    }
+
+    pub fn delete_table_if_exists(&self, table_name: &str) -> Result<bool> {
+        self.block_on(self.ddb.delete_table_if_exists(table_name))
+    }
+
+    pub fn delete_table_if_exists_and_wait(
+        &self,
+        table_name: &str,
+        timeout: Duration,
+    ) -> Result<bool> {
+        self.block_on(
+            self.ddb
+                .delete_table_if_exists_and_wait(table_name, timeout),
+        )
+    }
+
+    pub fn table_exists(&self, table_name: &str) -> Result<bool> {
+        self.block_on(self.ddb.table_exists(table_name))
+    }
+
+    pub fn list_tables(&self) -> Result<Vec<String>> {
+        self.block_on(self.ddb.list_tables())
+    }
+
+    pub fn list_tables_with_prefix(&self, prefix: &str) -> Result<Vec<String>> {
+        self.block_on(self.ddb.list_tables_with_prefix(prefix))
+    }
+
+    pub fn describe_table(
+        &self,
+        table_name: &str,
+    ) -> Result<aws_sdk_dynamodb::operation::describe_table::DescribeTableOutput> {
+        self.block_on(self.ddb.describe_table(table_name))
+    }
+
+    pub fn table_info(&self, table_name: &str) -> Result<TableInfo> {
+        self.block_on(self.ddb.table_info(table_name))
+    }
+
+    pub fn try_describe_table(&self, table_name: &str) -> Result<Option<TableInfo>> {
+        self.block_on(self.ddb.try_describe_table(table_name))
+    }
+
+    pub fn key_schema(&self, table_name: &str) -> Result<KeySchema> {
+        self.block_on(self.ddb.key_schema(table_name))
+    }
+
+    pub fn invalidate_key_schema(&self, table_name: &str) {
+        self.ddb.invalidate_key_schema(table_name)
+    }
+
+    pub fn load_table(
+        &self,
+        table_name: &str,
+        sample_size: Option<usize>,
+    ) -> Result<Table<'static>> {
+        self.block_on(self.ddb.load_table(table_name, sample_size))
+    }
+
+    pub fn create_gsi(
+        &self,
+        table: &Table<'_>,
+        index: &crate::dynamodb::GlobalSecondaryIndex<'_>,
+    ) -> Result<()> {
+        self.block_on(self.ddb.create_gsi(table, index))
+    }
+
+    pub fn delete_gsi(&self, table_name: &str, index_name: &str) -> Result<()> {
+        self.block_on(self.ddb.delete_gsi(table_name, index_name))
+    }
+
+    pub fn wait_for_index_active(
+        &self,
+        table_name: &str,
+        index_name: &str,
+        timeout: Duration,
+    ) -> Result<()> {
+        self.block_on(
+            self.ddb
+                .wait_for_index_active(table_name, index_name, timeout),
+        )
+    }
+
+    pub fn enable_stream(&self, table_name: &str, view_type: StreamViewType) -> Result<()> {
+        self.block_on(self.ddb.enable_stream(table_name, view_type))
+    }
+
+    pub fn get_stream_arn(&self, table_name: &str) -> Result<String> {
+        self.block_on(self.ddb.get_stream_arn(table_name))
+    }
+
+    pub fn create_backup(&self, table_name: &str, backup_name: &str) -> Result<BackupArn> {
+        self.block_on(self.ddb.create_backup(table_name, backup_name))
+    }
+
+    pub fn list_backups(&self, table_name: &str) -> Result<Vec<BackupInfo>> {
+        self.block_on(self.ddb.list_backups(table_name))
+    }
+
+    pub fn delete_backup(&self, backup_arn: &BackupArn) -> Result<()> {
+        self.block_on(self.ddb.delete_backup(backup_arn))
+    }
+
+    pub fn restore_table_from_backup(
+        &self,
+        backup_arn: &BackupArn,
+        new_table_name: &str,
+    ) -> Result<()> {
+        self.block_on(
+            self.ddb
+                .restore_table_from_backup(backup_arn, new_table_name),
+        )
+    }
+
+    pub fn restore_table_from_backup_and_wait(
+        &self,
+        backup_arn: &BackupArn,
+        new_table_name: &str,
+        timeout: Duration,
+    ) -> Result<()> {
+        self.block_on(self.ddb.restore_table_from_backup_and_wait(
+            backup_arn,
+            new_table_name,
+            timeout,
+        ))
+    }
+
+    pub fn export_to_s3(
+        &self,
+        table_name: &str,
+        bucket: &str,
+        prefix: &str,
+        format: ExportFormat,
+    ) -> Result<ExportInfo> {
+        self.block_on(self.ddb.export_to_s3(table_name, bucket, prefix, format))
+    }
+
+    pub fn describe_export(&self, export_arn: &str) -> Result<ExportInfo> {
+        self.block_on(self.ddb.describe_export(export_arn))
+    }
+
+    pub fn import_from_s3(
+        &self,
+        table: &Table<'_>,
+        bucket: &str,
+        prefix: &str,
+        format: InputFormat,
+    ) -> Result<ImportInfo> {
+        self.block_on(self.ddb.import_from_s3(table, bucket, prefix, format))
+    }
+
+    pub fn describe_import(&self, import_arn: &str) -> Result<ImportInfo> {
+        self.block_on(self.ddb.describe_import(import_arn))
+    }
+
+    pub fn wait_for_table_active(&self, table_name: &str, timeout: Duration) -> Result<()> {
+        self.block_on(self.ddb.wait_for_table_active(table_name, timeout))
+    }
+
+    pub fn wait_for_table_deleted(&self, table_name: &str, timeout: Duration) -> Result<()> {
+        self.block_on(self.ddb.wait_for_table_deleted(table_name, timeout))
+    }
+
+    pub fn put_item(&self, table_name: &str, item: impl Into<Item>) -> Result<()> {
+        self.block_on(self.ddb.put_item(table_name, item))
+    }
+
+    pub fn put_item_if_not_exists(&self, table: &Table<'_>, item: Item) -> Result<bool> {
+        self.block_on(self.ddb.put_item_if_not_exists(table, item))
+    }
+
+    pub fn put_item_if(
+        &self,
+        table_name: &str,
+        item: Item,
+        condition: &FilterExpression,
+        return_item: bool,
+    ) -> Result<()> {
+        self.block_on(
+            self.ddb
+                .put_item_if(table_name, item, condition, return_item),
+        )
+    }
+
+    pub fn put_item_with_capacity(&self, table_name: &str, item: Item) -> Result<WriteResult> {
+        self.block_on(self.ddb.put_item_with_capacity(table_name, item))
+    }
+
+    pub fn get_item(&self, table_name: &str, key: Item) -> Result<Option<Item>> {
+        self.block_on(self.ddb.get_item(table_name, key))
+    }
+
+    pub fn get_item_projected(
+        &self,
+        table_name: &str,
+        key: Item,
+        attributes: &[&str],
+    ) -> Result<Option<Item>> {
+        self.block_on(self.ddb.get_item_projected(table_name, key, attributes))
+    }
+
+    pub fn get_item_with(
+        &self,
+        table_name: &str,
+        key: Item,
+        options: GetItemOptions<'_>,
+    ) -> Result<Option<Item>> {
+        self.block_on(self.ddb.get_item_with(table_name, key, options))
+    }
+
+    pub fn get_item_with_capacity(
+        &self,
+        table_name: &str,
+        key: Item,
+    ) -> Result<ReadResult<Option<Item>>> {
+        self.block_on(self.ddb.get_item_with_capacity(table_name, key))
+    }
+
+    pub fn item_exists(
+        &self,
+        table: &Table<'_>,
+        key: Item,
+        consistent_read: Option<bool>,
+    ) -> Result<bool> {
+        self.block_on(self.ddb.item_exists(table, key, consistent_read))
+    }
+
+    #[cfg(feature = "serde")]
+    pub fn put_item_typed<T: Serialize>(&self, table_name: &str, value: &T) -> Result<()> {
+        self.block_on(self.ddb.put_item_typed(table_name, value))
+    }
+
+    #[cfg(feature = "serde")]
+    pub fn get_item_typed<T: DeserializeOwned>(
+        &self,
+        table_name: &str,
+        key: Item,
+    ) -> Result<Option<T>> {
+        self.block_on(self.ddb.get_item_typed(table_name, key))
+    }
+
+    #[cfg(feature = "serde")]
+    pub fn query_typed<T: DeserializeOwned>(
+        &self,
+        params: QueryFlexibleParams<'_>,
+    ) -> Result<Vec<T>> {
+        self.block_on(self.ddb.query_typed(params))
+    }
+
+    #[cfg(feature = "serde")]
+    pub fn query_typed_lenient<T: DeserializeOwned>(
+        &self,
+        params: QueryFlexibleParams<'_>,
+    ) -> Result<TypedItems<T>> {
+        self.block_on(self.ddb.query_typed_lenient(params))
+    }
+
+    #[cfg(feature = "serde")]
+    pub fn scan_typed<T: DeserializeOwned>(
+        &self,
+        table_name: &str,
+        filter: Option<Filter>,
+    ) -> Result<Vec<T>> {
+        self.block_on(self.ddb.scan_typed(table_name, filter))
+    }
+
+    #[cfg(feature = "serde")]
+    pub fn scan_typed_lenient<T: DeserializeOwned>(
+        &self,
+        table_name: &str,
+        filter: Option<Filter>,
+    ) -> Result<TypedItems<T>> {
+        self.block_on(self.ddb.scan_typed_lenient(table_name, filter))
+    }
+
+    #[cfg(feature = "serde")]
+    pub fn save<T: DynamoEntity>(&self, entity: &T) -> Result<()> {
+        self.block_on(self.ddb.save(entity))
+    }
+
+    #[cfg(feature = "serde")]
+    pub fn load<T: DynamoEntity>(&self, key: Item) -> Result<Option<T>> {
+        self.block_on(self.ddb.load(key))
+    }
+
+    #[cfg(feature = "serde")]
+    pub fn delete_entity<T: DynamoEntity>(&self, key: Item) -> Result<()> {
+        self.block_on(self.ddb.delete_entity::<T>(key))
+    }
+
+    #[cfg(feature = "serde")]
+    pub fn query_partition<T: DynamoEntity>(
+        &self,
+        partition_key: AttributeValue,
+    ) -> Result<Vec<T>> {
+        self.block_on(self.ddb.query_partition(partition_key))
+    }
+
+    pub fn update_item(&self, table_name: &str, key: Item, updates: Item) -> Result<()> {
+        self.block_on(self.ddb.update_item(table_name, key, updates))
+    }
+
+    pub fn update_table_item(&self, table: &Table<'_>, key: Item, updates: Item) -> Result<()> {
+        self.block_on(self.ddb.update_table_item(table, key, updates))
+    }
+
+    pub fn update_item_if(
+        &self,
+        table_name: &str,
+        key: Item,
+        updates: Item,
+        condition: &FilterExpression,
+        return_item: bool,
+    ) -> Result<()> {
+        self.block_on(
+            self.ddb
+                .update_item_if(table_name, key, updates, condition, return_item),
+        )
+    }
+
+    pub fn update_with(&self, table_name: &str, key: Item, updates: UpdateBuilder) -> Result<()> {
+        self.block_on(self.ddb.update_with(table_name, key, updates))
+    }
+
+    pub fn update_with_if(
+        &self,
+        table_name: &str,
+        key: Item,
+        updates: UpdateBuilder,
+        condition: &FilterExpression,
+        return_item: bool,
+    ) -> Result<()> {
+        self.block_on(
+            self.ddb
+                .update_with_if(table_name, key, updates, condition, return_item),
+        )
+    }
+
+    pub fn set_if_not_exists(
+        &self,
+        table_name: &str,
+        key: Item,
+        attribute: &str,
+        value: AttributeValue,
+    ) -> Result<()> {
+        self.block_on(
+            self.ddb
+                .set_if_not_exists(table_name, key, attribute, value),
+        )
+    }
+
+    pub fn append_to_list(
+        &self,
+        table_name: &str,
+        key: Item,
+        attribute: &str,
+        values: Vec<AttributeValue>,
+    ) -> Result<()> {
+        self.block_on(self.ddb.append_to_list(table_name, key, attribute, values))
+    }
+
+    pub fn set_list_element(
+        &self,
+        table_name: &str,
+        key: Item,
+        attribute: &str,
+        index: usize,
+        value: AttributeValue,
+    ) -> Result<()> {
+        self.block_on(
+            self.ddb
+                .set_list_element(table_name, key, attribute, index, value),
+        )
+    }
+
+    pub fn delete_item(&self, table_name: &str, key: Item) -> Result<()> {
+        self.block_on(self.ddb.delete_item(table_name, key))
+    }
+
+    pub fn delete_item_if(
+        &self,
+        table_name: &str,
+        key: Item,
+        condition: &FilterExpression,
+        return_item: bool,
+    ) -> Result<()> {
+        self.block_on(
+            self.ddb
+                .delete_item_if(table_name, key, condition, return_item),
+        )
+    }
+
+    pub fn transact_write(&self, operations: Vec<TransactWriteItem>) -> Result<()> {
+        self.block_on(self.ddb.transact_write(operations))
+    }
+
+    pub fn batch_put_items(
+        &self,
+        table_name: &str,
+        items: Vec<Item>,
+        on_progress: Option<&mut dyn FnMut(&Progress)>,
+    ) -> Result<usize> {
+        self.block_on(self.ddb.batch_put_items(table_name, items, on_progress))
+    }
+
+    pub fn batch_put_items_cancellable(
+        &self,
+        table_name: &str,
+        items: Vec<Item>,
+        on_progress: Option<&mut dyn FnMut(&Progress)>,
+        cancel: &CancellationToken,
+    ) -> Result<BulkResult<usize>> {
+        self.block_on(
+            self.ddb
+                .batch_put_items_cancellable(table_name, items, on_progress, cancel),
+        )
+    }
+
+    pub fn put_items_concurrent(
+        &self,
+        table_name: &str,
+        items: Vec<Item>,
+        concurrency: usize,
+    ) -> Result<ConcurrentPutStats> {
+        self.block_on(
+            self.ddb
+                .put_items_concurrent(table_name, items, concurrency),
+        )
+    }
+
+    pub fn scan_table(&self, table_name: &str) -> Result<Vec<HashMap<String, AttributeValue>>> {
+        self.block_on(self.ddb.scan_table(table_name))
+    }
+
+    pub fn scan(&self, params: ScanParams<'_>) -> Result<Vec<Item>> {
+        self.block_on(self.ddb.scan(params))
+    }
+
+    pub fn scan_all(
+        &self,
+        params: ScanParams<'_>,
+        max_items: Option<usize>,
+        on_progress: Option<&mut dyn FnMut(&Progress)>,
+    ) -> Result<Vec<Item>> {
+        self.block_on(self.ddb.scan_all(params, max_items, on_progress))
+    }
+
+    pub fn scan_all_cancellable(
+        &self,
+        params: ScanParams<'_>,
+        max_items: Option<usize>,
+        on_progress: Option<&mut dyn FnMut(&Progress)>,
+        cancel: &CancellationToken,
+    ) -> Result<BulkResult<Vec<Item>>> {
+        self.block_on(
+            self.ddb
+                .scan_all_cancellable(params, max_items, on_progress, cancel),
+        )
+    }
+
+    pub fn for_each_item(
+        &self,
+        params: ScanParams<'_>,
+        handler: impl FnMut(Item) -> Result<ControlFlow<()>>,
+    ) -> Result<ScanSummary> {
+        self.block_on(self.ddb.for_each_item(params, handler))
+    }
+
+    pub fn for_each_item_cancellable(
+        &self,
+        params: ScanParams<'_>,
+        on_progress: Option<&mut dyn FnMut(&Progress)>,
+        cancel: &CancellationToken,
+        handler: impl FnMut(Item) -> Result<ControlFlow<()>>,
+    ) -> Result<BulkResult<ScanSummary>> {
+        self.block_on(
+            self.ddb
+                .for_each_item_cancellable(params, on_progress, cancel, handler),
+        )
+    }
+
+    pub fn scan_with_capacity(&self, params: ScanParams<'_>) -> Result<ReadResult<Vec<Item>>> {
+        self.block_on(self.ddb.scan_with_capacity(params))
+    }
+
+    pub fn scan_rate_limited(
+        &self,
+        params: ScanParams<'_>,
+        target_rcu_per_sec: f64,
+        on_page: impl FnMut(&ScanStats),
+    ) -> Result<(Vec<Item>, ScanStats)> {
+        self.block_on(
+            self.ddb
+                .scan_rate_limited(params, target_rcu_per_sec, on_page),
+        )
+    }
+
+    pub fn scan_where_in(
+        &self,
+        table_name: &str,
+        attribute: &str,
+        values: Vec<AttributeValue>,
+        filter: Option<Filter>,
+    ) -> Result<Vec<Item>> {
+        self.block_on(
+            self.ddb
+                .scan_where_in(table_name, attribute, values, filter),
+        )
+    }
+
+    pub fn scan_where_contains(
+        &self,
+        table_name: &str,
+        attribute: &str,
+        value: AttributeValue,
+        filter: Option<Filter>,
+    ) -> Result<Vec<Item>> {
+        self.block_on(
+            self.ddb
+                .scan_where_contains(table_name, attribute, value, filter),
+        )
+    }
+
+    pub fn scan_count(
+        &self,
+        table_name: &str,
+        filter_expression: Option<&str>,
+        expression_attribute_names: Option<HashMap<String, String>>,
+        expression_attribute_values: Option<HashMap<String, AttributeValue>>,
+    ) -> Result<ItemCount> {
+        self.block_on(self.ddb.scan_count(
+            table_name,
+            filter_expression,
+            expression_attribute_names,
+            expression_attribute_values,
+        ))
+    }
+
+    pub fn query_flexible(&self, params: QueryFlexibleParams<'_>) -> Result<Vec<Item>> {
+        self.block_on(self.ddb.query_flexible(params))
+    }
+
+    pub fn query_flexible_detailed(
+        &self,
+        params: QueryFlexibleParams<'_>,
+        exclusive_start_key: Option<HashMap<String, AttributeValue>>,
+    ) -> Result<QueryResult> {
+        self.block_on(
+            self.ddb
+                .query_flexible_detailed(params, exclusive_start_key),
+        )
+    }
+
+    pub fn query_all(
+        &self,
+        params: QueryFlexibleParams<'_>,
+        max_items: Option<usize>,
+    ) -> Result<Vec<Item>> {
+        self.block_on(self.ddb.query_all(params, max_items))
+    }
+
+    pub fn query_all_shards(
+        &self,
+        table_name: &str,
+        partition_key_name: &str,
+        key: &ShardedKey,
+        sort_key: Option<(&str, SortKeyCondition)>,
+    ) -> Result<Vec<Item>> {
+        self.block_on(
+            self.ddb
+                .query_all_shards(table_name, partition_key_name, key, sort_key),
+        )
+    }
+
+    pub fn query_latest(
+        &self,
+        table: &Table<'_>,
+        pk_value: AttributeValue,
+        n: i32,
+    ) -> Result<Vec<Item>> {
+        self.block_on(self.ddb.query_latest(table, pk_value, n))
+    }
+
+    #[allow(clippy::type_complexity)]
+    pub fn query_paginated(
+        &self,
+        params: QueryFlexibleParams<'_>,
+        exclusive_start_key: Option<HashMap<String, AttributeValue>>,
+    ) -> Result<(Vec<Item>, Option<HashMap<String, AttributeValue>>)> {
+        self.block_on(self.ddb.query_paginated(params, exclusive_start_key))
+    }
+
+    pub fn query_paginated_with_token(
+        &self,
+        params: QueryFlexibleParams<'_>,
+        page_token: Option<&str>,
+        signing_key: Option<&[u8]>,
+    ) -> Result<(Vec<Item>, Option<String>)> {
+        self.block_on(
+            self.ddb
+                .query_paginated_with_token(params, page_token, signing_key),
+        )
+    }
+
+    pub fn query_flexible_with_capacity(
+        &self,
+        params: QueryFlexibleParams<'_>,
+    ) -> Result<ReadResult<Vec<Item>>> {
+        self.block_on(self.ddb.query_flexible_with_capacity(params))
+    }
+
+    pub fn query_count(&self, params: QueryFlexibleParams<'_>) -> Result<ItemCount> {
+        self.block_on(self.ddb.query_count(params))
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn query_simple(
+        &self,
+        table_name: &str,
+        partition_key: (&str, AttributeValue),
+        sort_key_condition: Option<(&str, String, SortKeyValue)>,
+        filter_expression: Option<&str>,
+        limit: Option<i32>,
+        expression_attribute_values: Option<HashMap<String, AttributeValue>>,
+        filter: Option<&FilterExpression>,
+    ) -> Result<Vec<Item>> {
+        self.block_on(self.ddb.query_simple(
+            table_name,
+            partition_key,
+            sort_key_condition,
+            filter_expression,
+            limit,
+            expression_attribute_values,
+            filter,
+        ))
+    }
+
+    pub fn query_items(
+        &self,
+        table_name: &str,
+        partition_key: (&str, AttributeValue),
+        sort_key_condition: Option<(&str, String, SortKeyValue)>,
+    ) -> Result<Vec<Item>> {
+        self.block_on(
+            self.ddb
+                .query_items(table_name, partition_key, sort_key_condition),
+        )
+    }
+
+    pub fn query_index(
+        &self,
+        table_name: &str,
+        index_name: &str,
+        partition_key: (&str, AttributeValue),
+        sort_key_condition: Option<(&str, String, SortKeyValue)>,
+        options: QueryOptions<'_>,
+    ) -> Result<Vec<Item>> {
+        self.block_on(self.ddb.query_index(
+            table_name,
+            index_name,
+            partition_key,
+            sort_key_condition,
+            options,
+        ))
+    }
+
+    pub fn query_begins_with(
+        &self,
+        table: &Table<'_>,
+        pk_value: AttributeValue,
+        prefix: &str,
+    ) -> Result<Vec<Item>> {
+        self.block_on(self.ddb.query_begins_with(table, pk_value, prefix))
+    }
+
+    pub fn query_between(
+        &self,
+        table: &Table<'_>,
+        pk_value: AttributeValue,
+        low: AttributeValue,
+        high: AttributeValue,
+    ) -> Result<Vec<Item>> {
+        self.block_on(self.ddb.query_between(table, pk_value, low, high))
+    }
+
+    #[allow(clippy::type_complexity)]
+    pub fn scan_paginated(
+        &self,
+        params: ScanParams<'_>,
+        exclusive_start_key: Option<HashMap<String, AttributeValue>>,
+    ) -> Result<(Vec<Item>, Option<HashMap<String, AttributeValue>>)> {
+        self.block_on(self.ddb.scan_paginated(params, exclusive_start_key))
+    }
+
+    pub fn scan_paginated_with_token(
+        &self,
+        params: ScanParams<'_>,
+        page_token: Option<&str>,
+        signing_key: Option<&[u8]>,
+    ) -> Result<(Vec<Item>, Option<String>)> {
+        self.block_on(
+            self.ddb
+                .scan_paginated_with_token(params, page_token, signing_key),
+        )
+    }
+
+    pub fn execute_statement(
+        &self,
+        statement: &Statement,
+        parameters: Vec<AttributeValue>,
+        next_token: Option<String>,
+    ) -> Result<StatementResult> {
+        self.block_on(
+            self.ddb
+                .execute_statement(statement, parameters, next_token),
+        )
+    }
+}
+
+/// A current-thread runtime for one client.
+fn runtime() -> Result<Runtime> {
+    assert!(Handle::try_current().is_err(), "{IN_ASYNC_CONTEXT}");
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .context("Failed to start a runtime for BlockingDynamoDb")
+}
@@ -1,59 +1,361 @@
-use crate::dynamodb::{DynamoDb, FieldType, Item, QueryFlexibleParams, Table};
+use crate::aws::{AttributeValue, ReturnConsumedCapacity, Select, StreamViewType};
+use crate::config::{Config, OutputFormat};
+use crate::constants::{CATEGORY_PARTITION_KEY, PRICE_ATTRIBUTE, PRODUCT_NAME_SORT_KEY};
+use crate::dynamodb::{
+    from_plain_json, is_local_endpoint, AuthInfo, BulkResult, BulkStatus, Capacity, DynamoDb,
+    DynamoDbApi, FieldType, GetItemOptions, GlobalSecondaryIndex, InMemoryCheckpoints, Item,
+    JsonFormat, KeyCondition, KeyConditionExpression, Metrics, MetricsSnapshot, Progress,
+    ProjectionExpression, QueryFlexibleParams, QueryResult, ScanParams, Schema, SortKeyCondition,
+    SortKeyValue, Statement, StreamRecord, StreamsDisabled, Table, TableCapacity, TableInfo,
+    TableNotFound, UpdateBuilder, WireLog, SORT_KEY_OPERATORS,
+};
+use crate::interrupt::Interrupt;
+use crate::item_editor::ItemEditor;
+use crate::line_reader::{InputClosed, LineReader, PromptCancelled};
 use anyhow::{anyhow, Result};
-use aws_sdk_dynamodb::types::AttributeValue;
+use aws_sdk_dynamodb::primitives::{DateTime, DateTimeFormat};
 use std::collections::HashMap;
+use std::fmt::Write as _;
 use std::io::{self, Write};
+use std::ops::ControlFlow;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use tokio_util::sync::CancellationToken;
 use tracing::info;
 
-/// Runs the command-line interface for interacting with a DynamoDB table.
+/// The commands `run` understands, as offered at the command prompt.
+const COMMANDS: &[&str] = &[
+    "info",
+    "put",
+    "get",
+    "update",
+    "edit",
+    "copy",
+    "delete",
+    "query",
+    "scan",
+    "list",
+    "tables",
+    "use",
+    "query_flexible",
+    "query_simple",
+    "scan_paginated",
+    "query_paginated",
+    "watch",
+    "sql",
+    "import",
+    "import_csv",
+    "export",
+    "export_csv",
+    "backup",
+    "restore",
+    "create_table",
+    "delete_table",
+    "config",
+    "whoami",
+    "stats",
+    "exit",
+];
+
+/// How many items `use` scans to infer a table's schema.
+const SCHEMA_SAMPLE_SIZE: usize = 100;
+
+/// How long `create_table` waits for a new table to become active.
+const TABLE_CREATE_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// How long `delete_table` waits for DynamoDB to finish removing a table.
+const TABLE_DELETE_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// How long `watch` waits for the table to become active again after
+/// enabling its stream.
+const STREAM_ENABLE_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// How long `restore` waits for the restored table to become active.
+const TABLE_RESTORE_TIMEOUT: Duration = Duration::from_secs(1800);
+
+/// The widest a column gets in item tables before values are truncated.
+const MAX_COLUMN_WIDTH: usize = 30;
+
+/// Builds the CLI's client for `config`: DynamoDB Local with dummy
+/// credentials if the endpoint is on `localhost`; else, with a profile, a
+/// [`DynamoDbBuilder`](crate::dynamodb::DynamoDbBuilder) reading only that
+/// profile; else AWS as configured by the environment. `config`'s endpoint
+/// and region apply to the last two.
+///
+/// The client records [`Metrics`] for `stats`, asks for consumed capacity
+/// unless `--no-capacity` was given, and logs the wire traffic if
+/// [`WireLog::from_env`] asks for it.
+///
+/// # Errors
+///
+/// Returns an error if the profile has no region and none was given.
+pub async fn connect(config: &Config) -> Result<DynamoDb> {
+    let endpoint_url = config.endpoint_url.value.as_deref();
+    let region = config.region.value.as_deref();
+    let ddb = match (endpoint_url, config.profile.value.as_deref()) {
+        (Some(endpoint_url), _) if is_local_endpoint(endpoint_url) => {
+            let ddb = DynamoDb::new_local(endpoint_url);
+            ddb.log_settings();
+            ddb
+        }
+        // The builder logs its client's settings itself.
+        (_, Some(profile)) => {
+            let mut builder = DynamoDb::builder().profile(profile);
+            if let Some(endpoint_url) = endpoint_url {
+                builder = builder.endpoint_url(endpoint_url);
+            }
+            if let Some(region) = region {
+                builder = builder.region(region);
+            }
+            builder.build().await?
+        }
+        (_, None) => {
+            let mut loader = aws_config::from_env();
+            if let Some(endpoint_url) = endpoint_url {
+                loader = loader.endpoint_url(endpoint_url);
+            }
+            if let Some(region) = region {
+                loader = loader.region(aws_config::Region::new(region.to_string()));
+            }
+            let ddb = DynamoDb::new(&loader.load().await);
+            ddb.log_settings();
+            ddb
+        }
+    }
+    .with_metrics(Arc::new(Metrics::new()));
+    let ddb = if config.capacity.value {
+        ddb.with_consumed_capacity()
+    } else {
+        ddb
+    };
+    // Wire logs go out at DEBUG, so they also need -v or RUST_LOG to show.
+    Ok(match WireLog::from_env() {
+        Some(wire_log) => ddb.with_wire_log(wire_log),
+        None => ddb,
+    })
+}
+
+/// Runs the command-line interface for interacting with DynamoDB tables.
 ///
 /// This function enters a loop that prompts the user for commands and executes them.
+/// Most commands operate on the active table, which `use` and `create_table` switch.
 /// The supported commands are:
 /// - info: Print table information
 /// - put: Add a new item to the table
-/// - get: Retrieve an item from the table
+/// - `get [--consistent] [--projection <attributes>]`: Retrieve an item from the table
 /// - update: Update an existing item in the table
+/// - edit: Edit an existing item as JSON in $EDITOR
+/// - copy: Copy an item to a new key, optionally changing other fields
 /// - delete: Delete an item from the table
 /// - query: Query items from the table
 /// - scan: Scan items from the table
 /// - list: List all items in the table
+/// - tables: List the tables in the account, optionally by name prefix
+/// - use <table_name>: Switch the active table to an existing table
 /// - query_flexible: Perform a flexible query operation with full control over all query parameters
 /// - query_simple: Provide a simplified interface for common query operations
 /// - scan_paginated: Enable users to perform a paginated scan operation on the table
+/// - query_paginated: Enable users to page through query results interactively
+/// - watch: Print the changes made to the table as they happen, until Enter
+///   or Ctrl-C is pressed
+/// - sql: Run a PartiQL statement with positional parameters
+/// - import: Batch write items from a JSON file
+/// - export: Write every item to a JSON Lines file
+/// - export_csv: Write every item to a CSV file
+/// - import_csv: Batch write rows from a CSV file
+/// - backup: Create an on-demand backup of the table
+/// - restore: Restore one of the table's backups into a new table
+/// - create_table: Create a new table and make it the active one
 /// - delete_table: Delete the DynamoDB table
+/// - config: Print the effective configuration and where each setting came from
+/// - whoami: Print the AWS identity the client is authenticated as
+/// - stats: Print the requests made this session, by operation, and the
+///   capacity consumed, by table
 /// - exit: Exit the program
 ///
 /// # Arguments
 ///
 /// * `ddb` - A reference to the DynamoDB client
-/// * `table` - A reference to the Table struct containing table information
+/// * `reader` - Where prompts read their input from
+/// * `editor` - Where `edit` opens items for editing
+/// * `interrupt` - Cancels the running command on Ctrl-C
+/// * `config` - The effective configuration, shown by `config`
+/// * `initial_table` - The table that is active until `use` or `create_table` switches it, if any
 ///
 /// # Returns
 ///
-/// Returns `Ok(())` if the function completes successfully, or an error if any operation fails.
-pub async fn run(ddb: &DynamoDb, table: &Table<'_>) -> Result<()> {
+/// Returns `Ok(())` once the input is closed or `exit` is entered. A command
+/// that fails has its error printed and the session carries on; only failing
+/// to list the tables at the start or to read a command ends it with an error.
+pub async fn run(
+    ddb: &impl DynamoDbApi,
+    reader: &mut dyn LineReader,
+    editor: &mut dyn ItemEditor,
+    interrupt: &Interrupt,
+    config: &Config,
+    initial_table: Option<&Table<'_>>,
+) -> Result<()> {
+    let output = config.output_format.value;
+    let mut switched_table: Option<Table<'static>> = None;
+    let mut completed_table = None;
+    reader.set_commands(COMMANDS);
+    reader.set_table_names(ddb.list_tables().await?);
     loop {
-        let command = prompt("Enter command (info/put/get/update/delete/query/scan/list/query_flexible/query_simple/scan_paginated/delete_table/exit): ", None)?;
-        match command.as_str() {
-            "info" => print_info(ddb, table).await?,
-            "put" => put_item(ddb, table).await?,
-            "get" => get_item(ddb, table).await?,
-            "update" => update_item(ddb, table).await?,
-            "delete" => delete_item(ddb, table).await?,
-            "query" => query_items(ddb, table).await?,
-            "scan" => scan_items(ddb, table).await?,
-            "list" => list_items(ddb, table).await?,
-            "query_flexible" => query_flexible_items(ddb, table).await?,
-            "query_simple" => query_simple_items(ddb, table).await?,
-            "scan_paginated" => scan_paginated_items(ddb, table).await?,
-            "delete_table" => delete_table(ddb, table).await?,
-            "exit" => break,
-            _ => println!("Unknown command. Please try again."),
+        let table = switched_table.as_ref().or(initial_table);
+        let table_name = table.map(|table| table.name().to_string());
+        if completed_table != table_name {
+            reader.set_field_names(table.map(field_names).unwrap_or_default());
+            completed_table = table_name;
+        }
+
+        let input = match reader.read_command(&format!(
+            "[{}] Enter command ({}): ",
+            table.map_or("no table", |table| table.name()),
+            COMMANDS.join("/")
+        )) {
+            Ok(input) => input,
+            Err(e) if e.is::<PromptCancelled>() => continue,
+            Err(e) if e.is::<InputClosed>() => break,
+            Err(e) => return Err(e),
+        };
+
+        let (command, argument) = parse_command(&input);
+        let cancel = interrupt.start();
+        let capacity_before = ddb.metrics().map(|metrics| metrics.total_capacity());
+        let result = match (command, table) {
+            ("tables", _) => list_tables(ddb, reader, table).await,
+            ("use", _) => use_table(ddb, reader, argument).await.map(|table| {
+                if let Some(table) = table {
+                    switched_table = Some(table);
+                }
+            }),
+            ("sql", _) => run_sql(ddb, reader, output).await,
+            ("create_table", _) => create_table(ddb, reader)
+                .await
+                .map(|table| switched_table = Some(table)),
+            ("config", _) => {
+                print!("{}", config);
+                Ok(())
+            }
+            ("whoami", _) => ddb
+                .check_auth()
+                .await
+                .map(|auth| print_identity(auth.as_ref())),
+            ("stats", _) => print_stats(ddb.metrics(), output),
+            ("exit", _) => break,
+            ("", _) => Ok(()),
+            (command, Some(table)) => run_table_command(
+                ddb, reader, editor, table, command, argument, output, &cancel,
+            )
+            .await
+            .map(|known| {
+                if !known {
+                    println!("Unknown command. Please try again.");
+                }
+            }),
+            (_, None) => {
+                println!(
+                    "No active table. Choose one with 'use <table_name>' or create one with 'create_table'."
+                );
+                Ok(())
+            }
+        };
+
+        // Ctrl-C abandons the command being entered; Ctrl-D ends the session.
+        // Any other failure is reported and the session carries on.
+        match result {
+            Err(e) if e.is::<PromptCancelled>() => println!("Cancelled."),
+            Err(e) if e.is::<InputClosed>() => break,
+            Err(e) => println!("Error: {:#}", e),
+            Ok(()) => {}
+        }
+        if config.capacity.value && output == OutputFormat::Table {
+            print_consumed(capacity_before, ddb.metrics());
+        }
+
+        if matches!(
+            command,
+            "tables" | "create_table" | "restore" | "delete_table"
+        ) {
+            match ddb.list_tables().await {
+                Ok(table_names) => reader.set_table_names(table_names),
+                Err(e) => println!("Couldn't refresh the table names to complete: {:#}", e),
+            }
         }
     }
     Ok(())
 }
 
+/// Runs a command that operates on the active table. Long-running ones stop
+/// early when `cancel` is cancelled.
+///
+/// Returns `false` if `command` isn't one of them.
+#[allow(clippy::too_many_arguments)]
+async fn run_table_command(
+    ddb: &impl DynamoDbApi,
+    reader: &mut dyn LineReader,
+    editor: &mut dyn ItemEditor,
+    table: &Table<'_>,
+    command: &str,
+    argument: Option<&str>,
+    output: OutputFormat,
+    cancel: &CancellationToken,
+) -> Result<bool> {
+    match command {
+        "info" => print_info(ddb, table).await?,
+        "put" => put_item(ddb, reader, table).await?,
+        "get" => get_item(ddb, reader, table, output, argument).await?,
+        "update" => update_item(ddb, reader, table).await?,
+        "edit" => edit_item(ddb, reader, editor, table).await?,
+        "copy" => copy_item(ddb, reader, table).await?,
+        "delete" => delete_item(ddb, reader, table).await?,
+        "query" => query_items(ddb, reader, table, output).await?,
+        "scan" => scan_items(ddb, reader, table, output, cancel).await?,
+        "list" => list_items(ddb, table, output, cancel).await?,
+        "query_flexible" => query_flexible_items(ddb, reader, table, output).await?,
+        "query_simple" => query_simple_items(ddb, reader, table, output).await?,
+        "scan_paginated" => scan_paginated_items(ddb, reader, table, output, cancel).await?,
+        "query_paginated" => query_paginated_items(ddb, reader, table, output).await?,
+        "watch" => watch_changes(ddb, reader, table, cancel).await?,
+        "import" => import_items(ddb, reader, table, cancel).await?,
+        "import_csv" => import_csv(ddb, reader, table, cancel).await?,
+        "export" => export_items(ddb, reader, table, cancel).await?,
+        "export_csv" => export_csv(ddb, reader, table, cancel).await?,
+        "backup" => backup_table(ddb, reader, table).await?,
+        "restore" => restore_table(ddb, reader, table).await?,
+        "delete_table" => delete_table(ddb, reader, table).await?,
+        _ => return Ok(false),
+    }
+    Ok(true)
+}
+
+/// The names offered for completion at value prompts: the table's keys and
+/// schema fields.
+fn field_names(table: &Table<'_>) -> Vec<String> {
+    let mut names: Vec<String> = std::iter::once(table.partition_key())
+        .chain(table.sort_key())
+        .map(str::to_string)
+        .chain(
+            table
+                .schema()
+                .into_iter()
+                .flat_map(|schema| schema.fields().keys().cloned()),
+        )
+        .collect();
+    names.sort();
+    names.dedup();
+    names
+}
+
+/// Splits a command line into the command and its argument, if any, e.g.
+/// `use products` into `("use", Some("products"))`.
+pub(crate) fn parse_command(input: &str) -> (&str, Option<&str>) {
+    match input.trim().split_once(char::is_whitespace) {
+        Some((command, argument)) => (command, Some(argument.trim())),
+        None => (input.trim(), None),
+    }
+}
+
 /// Prints detailed information about the DynamoDB table.
 ///
 /// This function retrieves and displays the following information:
@@ -61,9 +363,11 @@ pub async fn run(ddb: &DynamoDb, table: &Table<'_>) -> Result<()> {
 /// - Partition key
 /// - Sort key (if present)
 /// - Schema (if defined)
-/// - Item count
-/// - Table size in bytes
-/// - Table status
+/// - Item count and table size in bytes, as last refreshed by DynamoDB
+/// - Table status and billing mode
+/// - Global secondary indexes
+/// - Stream ARN (if streams are enabled)
+/// - Creation time
 ///
 /// # Arguments
 ///
@@ -73,72 +377,172 @@ pub async fn run(ddb: &DynamoDb, table: &Table<'_>) -> Result<()> {
 /// # Returns
 ///
 /// Returns `Ok(())` if the function completes successfully, or an error if any operation fails.
-async fn print_info(ddb: &DynamoDb, table: &Table<'_>) -> Result<()> {
-    let table_info = ddb.describe_table(table.name()).await?;
-    let items = ddb.scan_table(table.name()).await?;
+async fn print_info(ddb: &impl DynamoDbApi, table: &Table<'_>) -> Result<()> {
+    let info = ddb.table_info(table.name()).await?;
+    print!("{}", render_info(&info, table.schema())?);
+    Ok(())
+}
 
-    println!("\n--- Table Information ---");
-    println!("Table Name: {}", table.name());
-    println!("Partition Key: {}", table.partition_key());
-    if let Some(key) = table.sort_key() {
-        println!("Sort Key: {}", key);
+/// Renders the `info` command's output: the table's keys, size and status,
+/// followed by each secondary index with its keys, projection, item count
+/// and capacity.
+pub(crate) fn render_info(info: &TableInfo, schema: Option<&Schema>) -> Result<String> {
+    let mut out = String::new();
+    writeln!(out, "\n--- Table Information ---")?;
+    writeln!(out, "Table Name: {}", info.name)?;
+    writeln!(out, "Partition Key: {}", info.partition_key)?;
+    if let Some(key) = &info.sort_key {
+        writeln!(out, "Sort Key: {}", key)?;
     }
 
-    if let Some(schema) = table.schema() {
-        println!("Schema:");
+    if let Some(schema) = schema {
+        writeln!(out, "Schema:")?;
         for (field, field_type) in schema.fields() {
-            println!("  {}: {:?}", field, field_type);
+            writeln!(out, "  {}: {:?}", field, field_type)?;
         }
     }
 
-    let item_count = items.len();
-    let table_size_bytes: usize = items
-        .iter()
-        .flat_map(|item| item.values())
-        .map(|attr| {
-            attr.as_s()
-                .map(|s| s.len())
-                .unwrap_or_else(|_| attr.as_n().map_or(0, |n| n.len()))
-        })
-        .sum();
+    writeln!(out, "Item Count: {}", info.item_count)?;
+    writeln!(out, "Table Size (bytes): {}", info.size_bytes)?;
+    writeln!(out, "Table Status: {}", info.status)?;
+    writeln!(out, "Billing Mode: {}", info.billing_mode)?;
+    if !info.global_secondary_indexes.is_empty() {
+        writeln!(out, "Global Secondary Indexes:")?;
+    }
+    for index in &info.global_secondary_indexes {
+        writeln!(
+            out,
+            "  {} {} - {}",
+            index.name,
+            index_keys(&index.partition_key, index.sort_key.as_deref()),
+            index.status
+        )?;
+        writeln!(out, "    Projection: {}", index.projection_type)?;
+        writeln!(out, "    Item Count: {}", index.item_count)?;
+        writeln!(out, "    Capacity: {}", describe_capacity(index.capacity))?;
+    }
+    if !info.local_secondary_indexes.is_empty() {
+        writeln!(out, "Local Secondary Indexes:")?;
+    }
+    for index in &info.local_secondary_indexes {
+        writeln!(
+            out,
+            "  {} {}",
+            index.name,
+            index_keys(&index.partition_key, index.sort_key.as_deref())
+        )?;
+        writeln!(out, "    Projection: {}", index.projection_type)?;
+        writeln!(out, "    Item Count: {}", index.item_count)?;
+    }
+    if let Some(stream_arn) = &info.stream_arn {
+        writeln!(out, "Stream ARN: {}", stream_arn)?;
+    }
+    writeln!(
+        out,
+        "Created: {}",
+        DateTime::from(info.created_at).fmt(DateTimeFormat::DateTime)?
+    )?;
+    writeln!(out, "-------------------------\n")?;
+    Ok(out)
+}
 
-    println!("Item Count: {}", item_count);
-    println!("Table Size (bytes): {}", table_size_bytes);
-    println!(
-        "Table Status: {:?}",
-        table_info.table().unwrap().table_status()
-    );
-    println!("-------------------------\n");
-    Ok(())
+fn index_keys(partition_key: &str, sort_key: Option<&str>) -> String {
+    match sort_key {
+        Some(sort_key) => format!("({}, {})", partition_key, sort_key),
+        None => format!("({})", partition_key),
+    }
+}
+
+fn describe_capacity(capacity: Capacity) -> String {
+    match capacity {
+        Capacity::OnDemand => "on-demand".to_string(),
+        Capacity::Provisioned {
+            read_capacity_units,
+            write_capacity_units,
+        } => format!(
+            "{} read / {} write units",
+            read_capacity_units, write_capacity_units
+        ),
+    }
 }
 
 /// Adds a new item to the DynamoDB table.
 ///
 /// This function prompts the user to enter values for each field defined in the table's schema,
-/// creates a new Item, and adds it to the table.
+/// creates a new Item, and adds it to the table. Entering a JSON object at the first prompt
+/// puts that object as the item instead.
 ///
 /// # Arguments
 ///
 /// * `ddb` - A reference to the DynamoDB client
+/// * `reader` - Where prompts read their input from
 /// * `table` - A reference to the Table struct containing table information
 ///
 /// # Returns
 ///
 /// Returns `Ok(())` if the item is added successfully, or an error if the operation fails.
-async fn put_item(ddb: &DynamoDb, table: &Table<'_>) -> Result<()> {
+async fn put_item(
+    ddb: &impl DynamoDbApi,
+    reader: &mut dyn LineReader,
+    table: &Table<'_>,
+) -> Result<()> {
     let schema = table
         .schema()
         .ok_or_else(|| anyhow!("Table schema not defined"))?;
-    let item = schema
-        .fields()
-        .iter()
-        .fold(Item::new(), |item, (field_name, field_type)| {
-            let value = prompt(&format!("Enter {}: ", field_name), None).unwrap();
-            match field_type {
-                FieldType::String => item.set_string(field_name, value),
-                FieldType::Number => item.set_number(field_name, value.parse::<f64>().unwrap()),
-            }
-        });
+    let mut fields: Vec<_> = schema.fields().iter().collect();
+    fields.sort_by_key(|(field_name, _)| {
+        (
+            field_name.as_str() != table.partition_key(),
+            Some(field_name.as_str()) != table.sort_key(),
+            field_name.as_str(),
+        )
+    });
+
+    let mut item = Item::new();
+    for (index, (field_name, field_type)) in fields.into_iter().enumerate() {
+        // A map's own value starts with `{` too, so it can't double as the
+        // whole item.
+        let whole_item = index == 0 && !matches!(field_type, FieldType::Map);
+        let value = if whole_item {
+            prompt(
+                reader,
+                &format!("Enter {} (or the whole item as JSON)", field_name),
+                field_example(field_type),
+            )?
+        } else {
+            prompt(
+                reader,
+                &format!("Enter {}", field_name),
+                field_example(field_type),
+            )?
+        };
+        if whole_item && value.starts_with('{') {
+            let json_item = read_json_item(
+                value,
+                |message| prompt(reader, message, None),
+                |json| {
+                    let item = Item::from_json(&json)?;
+                    table.validate_item(&item)?;
+                    Ok(item)
+                },
+            )?;
+            let Some(json_item) = json_item else {
+                println!("Put cancelled.");
+                return Ok(());
+            };
+            item = json_item;
+            break;
+        }
+        let value =
+            reprompt_until_valid(reader, &format!("Enter {}", field_name), value, |input| {
+                coerce(field_type, input)
+            })?;
+        let Some(value) = value else {
+            println!("Put cancelled.");
+            return Ok(());
+        };
+        item.attributes.insert(field_name.clone(), value);
+    }
 
     ddb.put_item(table.name(), item).await?;
     info!("Item added successfully!");
@@ -148,47 +552,306 @@ async fn put_item(ddb: &DynamoDb, table: &Table<'_>) -> Result<()> {
 /// Retrieves an item from the DynamoDB table.
 ///
 /// This function prompts the user to enter the key values for the item,
-/// retrieves the item from the table, and displays it if found.
+/// retrieves the item from the table, and displays it if found. The read
+/// options come from the command's flags (`get --consistent --projection
+/// name,price`), or are prompted for when there are none; with a projection
+/// only the projected attributes are shown.
 ///
 /// # Arguments
 ///
 /// * `ddb` - A reference to the DynamoDB client
+/// * `reader` - Where prompts read their input from
 /// * `table` - A reference to the Table struct containing table information
+/// * `output` - How to print the items
+/// * `argument` - The flags given after `get`, if any
 ///
 /// # Returns
 ///
 /// Returns `Ok(())` if the operation completes successfully, or an error if it fails.
-async fn get_item(ddb: &DynamoDb, table: &Table<'_>) -> Result<()> {
-    let key = create_key_item(table)?;
-    match ddb.get_item(table.name(), key).await? {
-        Some(item) => println!("Item found: {:?}", item),
+async fn get_item(
+    ddb: &impl DynamoDbApi,
+    reader: &mut dyn LineReader,
+    table: &Table<'_>,
+    output: OutputFormat,
+    argument: Option<&str>,
+) -> Result<()> {
+    let args = match argument.map(parse_get_args).transpose() {
+        Ok(args) => args,
+        Err(e) => {
+            println!("{}", e);
+            return Ok(());
+        }
+    };
+    let key = create_key_item(reader, table)?;
+    let args = match args {
+        Some(args) => args,
+        None => GetArgs {
+            consistent_read: prompt_bool(reader, "Use a strongly consistent read?", false)?,
+            projection: prompt_optional(
+                reader,
+                "Enter attributes to project",
+                Some("name, status, price"),
+            )?
+            .map(|input| attribute_list(&input))
+            .filter(|attributes| !attributes.is_empty()),
+        },
+    };
+
+    let projection = args.projection.as_ref().and_then(|attributes| {
+        ProjectionExpression::new(&attributes.iter().map(String::as_str).collect::<Vec<_>>())
+    });
+    let options = GetItemOptions {
+        consistent_read: Some(args.consistent_read),
+        projection: projection.as_ref(),
+    };
+    match ddb.get_item_with(table.name(), key, options).await? {
+        Some(item) => print_items("Item found", &[item], &key_columns(table), output),
         None => println!("Item not found"),
     }
     Ok(())
 }
 
+/// The read options for `get`.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub(crate) struct GetArgs {
+    pub consistent_read: bool,
+    /// The attributes to return; every attribute if `None`.
+    pub projection: Option<Vec<String>>,
+}
+
+/// Parses the flags of `get`: `--consistent` and `--projection <attributes>`,
+/// where the attributes are comma-separated and may be given as
+/// `--projection=name,price`.
+pub(crate) fn parse_get_args(argument: &str) -> Result<GetArgs> {
+    let mut args = GetArgs::default();
+    let mut words = argument.split_whitespace().peekable();
+    while let Some(word) = words.next() {
+        let (flag, value) = match word.split_once('=') {
+            Some((flag, value)) => (flag, Some(value)),
+            None => (word, None),
+        };
+        match (flag, value) {
+            ("--consistent", None) => args.consistent_read = true,
+            ("--projection", value) => {
+                // The list may have spaces after its commas.
+                let mut list = value.unwrap_or_default().to_string();
+                while let Some(next) = words.next_if(|next| !next.starts_with("--")) {
+                    list.push(' ');
+                    list.push_str(next);
+                }
+                let attributes = attribute_list(&list);
+                if attributes.is_empty() {
+                    return Err(anyhow!("--projection needs a list of attributes"));
+                }
+                args.projection = Some(attributes);
+            }
+            _ => {
+                return Err(anyhow!(
+                "Unknown option '{}' for get, expected --consistent or --projection <attributes>",
+                word
+            ))
+            }
+        }
+    }
+    Ok(args)
+}
+
 /// Updates an existing item in the DynamoDB table.
 ///
 /// This function prompts the user to enter the key values for the item to update,
-/// then prompts for new values for each updateable field. It then sends an update
-/// request to DynamoDB with the new values.
+/// then asks which fields to update or remove. It then sends a single update
+/// request to DynamoDB with the new values and removals, or does nothing if no
+/// field was chosen.
 ///
 /// # Arguments
 ///
 /// * `ddb` - A reference to the DynamoDB client
+/// * `reader` - Where prompts read their input from
 /// * `table` - A reference to the Table struct containing table information
 ///
 /// # Returns
 ///
 /// Returns `Ok(())` if the item is updated successfully, or an error if the operation fails.
-async fn update_item(ddb: &DynamoDb, table: &Table<'_>) -> Result<()> {
-    let key = create_key_item(table)?;
-    let updates = create_update_item(table)?;
-    ddb.update_item(table.name(), key, updates).await?;
+async fn update_item(
+    ddb: &impl DynamoDbApi,
+    reader: &mut dyn LineReader,
+    table: &Table<'_>,
+) -> Result<()> {
+    let key = create_key_item(reader, table)?;
+    let changes = create_update_item(reader, table)?;
+    if changes.is_empty() {
+        println!("No fields selected, nothing to update.");
+        return Ok(());
+    }
+    ddb.update_with(table.name(), key, changes.into()).await?;
+    println!("Item updated successfully!");
+    Ok(())
+}
+
+/// Edits an existing item as JSON in the user's editor.
+///
+/// This function prompts for the item's key, opens the item as plain JSON in
+/// `editor`, and compares the saved JSON with the original. Changed and added
+/// attributes are set and deleted ones removed in a single update, after the
+/// changes are shown and confirmed. Aborting the editor, saving invalid JSON or
+/// declining the changes leaves the item untouched.
+///
+/// # Arguments
+///
+/// * `ddb` - A reference to the DynamoDB client
+/// * `reader` - Where prompts read their input from
+/// * `editor` - Where the item is edited
+/// * `table` - A reference to the Table struct containing table information
+///
+/// # Returns
+///
+/// Returns `Ok(())` if the edit is applied or abandoned, or an error if the operation fails.
+async fn edit_item(
+    ddb: &impl DynamoDbApi,
+    reader: &mut dyn LineReader,
+    editor: &mut dyn ItemEditor,
+    table: &Table<'_>,
+) -> Result<()> {
+    let key = create_key_item(reader, table)?;
+    let Some(item) = ddb.get_item(table.name(), key.clone()).await? else {
+        println!("Item not found.");
+        return Ok(());
+    };
+    let original = item.to_json(JsonFormat::Plain);
+
+    let Some(edited) = editor.edit(&serde_json::to_string_pretty(&original)?)? else {
+        println!("Edit aborted, item unchanged.");
+        return Ok(());
+    };
+    let changes = serde_json::from_str(&edited)
+        .map_err(|e| anyhow!("Invalid JSON: {}", e))
+        .and_then(|edited| diff_item_json(&original, &edited, table));
+    let changes = match changes {
+        Ok(changes) => changes,
+        Err(e) => {
+            println!("{}. Item unchanged.", e);
+            return Ok(());
+        }
+    };
+    if changes.is_empty() {
+        println!("No changes.");
+        return Ok(());
+    }
+
+    print!("{}", render_item_changes(&changes));
+    if !prompt_bool(reader, "Apply these changes?", false)? {
+        println!("Edit discarded, item unchanged.");
+        return Ok(());
+    }
+    ddb.update_with(table.name(), key, changes.into()).await?;
     println!("Item updated successfully!");
     Ok(())
 }
 
+/// Copies an item in the DynamoDB table to a new key.
+///
+/// This function prompts for the source item's key, fetches it, then prompts
+/// for the new key. If the table has a schema, other fields can be changed
+/// on the copy the same way as with `update`. The copy is only written if no
+/// item has the new key yet, and copying an item onto its own key is
+/// refused.
+///
+/// # Arguments
+///
+/// * `ddb` - A reference to the DynamoDB client
+/// * `reader` - Where prompts read their input from
+/// * `table` - A reference to the Table struct containing table information
+///
+/// # Returns
+///
+/// Returns `Ok(())` if the item is copied or the copy is refused, or an error if the operation fails.
+async fn copy_item(
+    ddb: &impl DynamoDbApi,
+    reader: &mut dyn LineReader,
+    table: &Table<'_>,
+) -> Result<()> {
+    let source_key = prompt_key(reader, table, "Enter source")?;
+    let Some(mut item) = ddb.get_item(table.name(), source_key.clone()).await? else {
+        println!("Item not found.");
+        return Ok(());
+    };
+
+    let new_key = prompt_key(reader, table, "Enter new")?;
+    if new_key.attributes == source_key.attributes {
+        println!("The new key is the same as the source item's; nothing copied.");
+        return Ok(());
+    }
+    item.attributes.extend(new_key.attributes);
+
+    if table.schema().is_some() && prompt_bool(reader, "Change other fields?", false)? {
+        let changes = create_update_item(reader, table)?;
+        item.attributes.extend(changes.set.attributes);
+        for name in &changes.remove {
+            item.attributes.remove(name);
+        }
+    }
+
+    if ddb.put_item_if_not_exists(table, item).await? {
+        println!("Item copied.");
+    } else {
+        println!("An item with that key already exists; nothing copied.");
+    }
+    Ok(())
+}
+
+/// Works out the changes that turn `original` into `edited`, both items in
+/// plain JSON.
+///
+/// Attributes are compared as JSON, so values that only change form in the
+/// round trip (`"1.50"` shown as `1.5`, sets shown as lists) aren't
+/// rewritten unless they were edited. Key attributes can't change, and set
+/// values must match the table's schema.
+pub(crate) fn diff_item_json(
+    original: &serde_json::Value,
+    edited: &serde_json::Value,
+    table: &Table<'_>,
+) -> Result<ItemChanges> {
+    let (Some(original), Some(edited)) = (original.as_object(), edited.as_object()) else {
+        return Err(anyhow!("The item must be a JSON object"));
+    };
+    for key in std::iter::once(table.partition_key()).chain(table.sort_key()) {
+        if original.get(key) != edited.get(key) {
+            return Err(anyhow!("Key attribute '{}' can't be edited", key));
+        }
+    }
+
+    let changed: serde_json::Map<_, _> = edited
+        .iter()
+        .filter(|(name, value)| original.get(*name) != Some(*value))
+        .map(|(name, value)| (name.clone(), value.clone()))
+        .collect();
+    let set = Item::from_json(&serde_json::Value::Object(changed))?;
+    table.validate_updates(&set)?;
+
+    let mut remove: Vec<String> = original
+        .keys()
+        .filter(|name| !edited.contains_key(*name))
+        .cloned()
+        .collect();
+    remove.sort();
+    Ok(ItemChanges { set, remove })
+}
+
+/// Renders changes as `SET` and `REMOVE` lines for confirmation.
+pub(crate) fn render_item_changes(changes: &ItemChanges) -> String {
+    let mut names: Vec<_> = changes.set.attributes.keys().collect();
+    names.sort();
+    let mut out = String::new();
+    for name in names {
+        let value = changes.set.get_json(name).unwrap_or_default();
+        let _ = writeln!(out, "SET {} = {}", name, value);
+    }
+    for name in &changes.remove {
+        let _ = writeln!(out, "REMOVE {}", name);
+    }
+    out
+}
+
 /// Deletes an item from the DynamoDB table.
 ///
 /// This function prompts the user to enter the key values for the item to delete,
@@ -197,13 +860,18 @@ async fn update_item(ddb: &DynamoDb, table: &Table<'_>) -> Result<()> {
 /// # Arguments
 ///
 /// * `ddb` - A reference to the DynamoDB client
+/// * `reader` - Where prompts read their input from
 /// * `table` - A reference to the Table struct containing table information
 ///
 /// # Returns
 ///
 /// Returns `Ok(())` if the item is deleted successfully, or an error if the operation fails.
-async fn delete_item(ddb: &DynamoDb, table: &Table<'_>) -> Result<()> {
-    let key = create_key_item(table)?;
+async fn delete_item(
+    ddb: &impl DynamoDbApi,
+    reader: &mut dyn LineReader,
+    table: &Table<'_>,
+) -> Result<()> {
+    let key = create_key_item(reader, table)?;
     ddb.delete_item(table.name(), key).await?;
     println!("Item deleted successfully!");
     Ok(())
@@ -211,63 +879,51 @@ async fn delete_item(ddb: &DynamoDb, table: &Table<'_>) -> Result<()> {
 
 /// Queries items from the DynamoDB table.
 ///
-/// This function prompts the user to enter query parameters and performs a query operation.
+/// This function prompts the user to enter query parameters and performs a query operation,
+/// fetching every page of results (up to the optional limit).
 ///
 /// # Arguments
 ///
 /// * `ddb` - A reference to the DynamoDB client
+/// * `reader` - Where prompts read their input from
 /// * `table` - A reference to the Table struct containing table information
+/// * `output` - How to print the items
 ///
 /// # Returns
 ///
 /// Returns `Ok(())` if the query completes successfully, or an error if the operation fails.
-async fn query_items(ddb: &DynamoDb, table: &Table<'_>) -> Result<()> {
-    let partition_key_name = table.partition_key();
-    let partition_key_value = prompt(&format!("Enter {} value: ", partition_key_name), None)?;
-
-    let mut key_condition_expression = "#pk = :pkval".to_string();
-    let mut expression_attribute_names =
-        HashMap::from([("#pk".to_string(), partition_key_name.to_string())]);
-    let mut expression_attribute_values =
-        HashMap::from([(":pkval".to_string(), AttributeValue::S(partition_key_value))]);
-
-    if let Some(sort_key) = table.sort_key() {
-        let sort_key_condition = prompt(
-            &format!(
-                "Enter condition for {} (e.g., '=', '>', '<', 'BETWEEN'): ",
-                sort_key
-            ),
-            None,
-        )?;
-        let sort_key_value = prompt(&format!("Enter value for {}: ", sort_key), None)?;
-
-        key_condition_expression.push_str(&format!(" AND #sk {} :skval", sort_key_condition));
-        expression_attribute_names.insert("#sk".to_string(), sort_key.to_string());
-        expression_attribute_values.insert(":skval".to_string(), AttributeValue::S(sort_key_value));
+async fn query_items(
+    ddb: &impl DynamoDbApi,
+    reader: &mut dyn LineReader,
+    table: &Table<'_>,
+    output: OutputFormat,
+) -> Result<()> {
+    let index = select_index(reader, table)?;
+    let (partition_key_name, sort_key) = match index {
+        Some(index) => (index.partition_key(), index.sort_key()),
+        None => (table.partition_key(), table.sort_key()),
+    };
+    let key_condition = read_key_condition(reader, table, partition_key_name, sort_key)?;
 
-        if sort_key_condition == "BETWEEN" {
-            let sort_key_value_2 = prompt(
-                &format!(
-                    "Enter second value for {} (for BETWEEN condition): ",
-                    sort_key
-                ),
-                None,
-            )?;
-            key_condition_expression.push_str(" AND :skval2");
-            expression_attribute_values
-                .insert(":skval2".to_string(), AttributeValue::S(sort_key_value_2));
-        }
-    }
+    let KeyConditionExpression {
+        expression: key_condition_expression,
+        names: mut expression_attribute_names,
+        values: mut expression_attribute_values,
+    } = key_condition.build()?;
 
-    let filter_expression = prompt_optional("Enter filter expression (optional): ", None)?;
+    let filter_expression = prompt_optional(reader, "Enter filter expression (optional): ", None)?;
     if filter_expression.is_some() {
-        let filter_attribute_names = get_expression_attribute_names()?;
-        let filter_attribute_values = get_expression_attribute_values()?;
+        let filter_attribute_names = get_expression_attribute_names(reader)?;
+        let filter_attribute_values = get_expression_attribute_values(reader)?;
         expression_attribute_names.extend(filter_attribute_names);
         expression_attribute_values.extend(filter_attribute_values);
     }
 
-    let limit = prompt_optional("Enter limit (optional): ", None)?.and_then(|s| s.parse().ok());
+    let Some(max_items) = prompt_limit(reader, "Enter limit (or press Enter for none)", None)?
+    else {
+        println!("Query cancelled.");
+        return Ok(());
+    };
 
     let params = QueryFlexibleParams {
         table_name: table.name(),
@@ -276,23 +932,112 @@ async fn query_items(ddb: &DynamoDb, table: &Table<'_>) -> Result<()> {
         expression_attribute_values: Some(expression_attribute_values),
         filter_expression: filter_expression.as_deref(),
         projection_expression: None,
-        limit,
+        limit: None,
         scan_index_forward: None,
-        index_name: None,
+        index_name: index.map(|index| index.name()),
+        ..Default::default()
     };
 
-    let items = ddb.query_flexible(params).await?;
+    let items = ddb.query_all(params, max_items).await?;
 
-    print_items(
-        "Query Results",
-        &items
-            .iter()
-            .map(|item| item.attributes.clone())
-            .collect::<Vec<_>>(),
-    );
+    print_items("Query Results", &items, &key_columns(table), output);
     Ok(())
 }
 
+/// Prompts for a partition key value and an optional sort key condition on
+/// `partition_key` and `sort_key`, the keys of the table or of one of its
+/// indexes.
+///
+/// Values are typed as the table's schema declares the attributes, so
+/// numeric keys are compared as numbers.
+pub(crate) fn read_key_condition(
+    reader: &mut dyn LineReader,
+    table: &Table<'_>,
+    partition_key: &str,
+    sort_key: Option<&str>,
+) -> Result<KeyCondition> {
+    let partition_key_value = prompt_key_value(
+        reader,
+        table,
+        partition_key,
+        &format!("Enter {} value", partition_key),
+    )?;
+    let mut key_condition = KeyCondition::partition(partition_key, partition_key_value);
+
+    if let Some(sort_key) = sort_key {
+        let sort_key_condition = prompt(
+            reader,
+            &format!(
+                "Enter condition for {} ({}, or press Enter for none): ",
+                sort_key,
+                SORT_KEY_OPERATORS.join(", ")
+            ),
+            None,
+        )?;
+
+        if !sort_key_condition.is_empty() {
+            let sort_key_value = prompt_key_value(
+                reader,
+                table,
+                sort_key,
+                &format!("Enter value for {}", sort_key),
+            )?;
+            let sort_key_value = if sort_key_condition.eq_ignore_ascii_case("BETWEEN") {
+                let sort_key_value_2 = prompt_key_value(
+                    reader,
+                    table,
+                    sort_key,
+                    &format!(
+                        "Enter second value for {} (for BETWEEN condition)",
+                        sort_key
+                    ),
+                )?;
+                SortKeyValue::Between(sort_key_value, sort_key_value_2)
+            } else {
+                sort_key_value.into()
+            };
+            key_condition = key_condition.and_sort(
+                sort_key,
+                SortKeyCondition::parse(&sort_key_condition, sort_key_value)?,
+            );
+        }
+    }
+    Ok(key_condition)
+}
+
+/// Lets the user pick one of the table's global secondary indexes.
+///
+/// Returns `None` without prompting when the table declares no indexes, or
+/// when the user chooses the base table.
+fn select_index<'t, 'a>(
+    reader: &mut dyn LineReader,
+    table: &'t Table<'a>,
+) -> Result<Option<&'t GlobalSecondaryIndex<'a>>> {
+    let indexes = table.global_secondary_indexes();
+    if indexes.is_empty() {
+        return Ok(None);
+    }
+
+    let names: Vec<_> = indexes.iter().map(|index| index.name()).collect();
+    let choice = prompt(
+        reader,
+        &format!(
+            "Enter index to query ({}, or press Enter for the base table)",
+            names.join(", ")
+        ),
+        None,
+    )?;
+    if choice.is_empty() {
+        return Ok(None);
+    }
+
+    indexes
+        .iter()
+        .find(|index| index.name() == choice)
+        .map(Some)
+        .ok_or_else(|| anyhow!("Unknown index '{}'", choice))
+}
+
 /// Scans items from the DynamoDB table.
 ///
 /// This function prompts the user to enter an optional filter expression and attribute values,
@@ -301,13 +1046,22 @@ async fn query_items(ddb: &DynamoDb, table: &Table<'_>) -> Result<()> {
 /// # Arguments
 ///
 /// * `ddb` - A reference to the DynamoDB client
+/// * `reader` - Where prompts read their input from
 /// * `table` - A reference to the Table struct containing table information
+/// * `output` - How to print the items
 ///
 /// # Returns
 ///
 /// Returns `Ok(())` if the scan completes successfully, or an error if the operation fails.
-async fn scan_items(ddb: &DynamoDb, table: &Table<'_>) -> Result<()> {
+async fn scan_items(
+    ddb: &impl DynamoDbApi,
+    reader: &mut dyn LineReader,
+    table: &Table<'_>,
+    output: OutputFormat,
+    cancel: &CancellationToken,
+) -> Result<()> {
     let filter_expression = prompt(
+        reader,
         "Enter filter expression (or press Enter for no filter, e.g., 'attribute_name > :value'): ",
         None,
     )?;
@@ -315,29 +1069,39 @@ async fn scan_items(ddb: &DynamoDb, table: &Table<'_>) -> Result<()> {
     let (expression_attribute_names, expression_attribute_values) = if !filter_expression.is_empty()
     {
         (
-            get_expression_attribute_names()?,
-            get_expression_attribute_values()?,
+            get_expression_attribute_names(reader)?,
+            get_expression_attribute_values(reader)?,
         )
     } else {
         (HashMap::new(), HashMap::new())
     };
 
-    let items = ddb
-        .scan(
-            table.name(),
-            Some(filter_expression),
-            Some(expression_attribute_names),
-            Some(expression_attribute_values),
+    let Some(max_items) = prompt_limit(reader, "Enter limit (or press Enter for none)", None)?
+    else {
+        println!("Scan cancelled.");
+        return Ok(());
+    };
+
+    let mut progress = ProgressLine::new("Scanned");
+    let result = ddb
+        .scan_all_cancellable(
+            ScanParams {
+                table_name: table.name(),
+                filter_expression: (!filter_expression.is_empty())
+                    .then_some(filter_expression.as_str()),
+                expression_attribute_names: Some(expression_attribute_names),
+                expression_attribute_values: Some(expression_attribute_values),
+                ..Default::default()
+            },
+            max_items,
+            Some(&mut |p: &Progress| progress.update(p)),
+            cancel,
         )
         .await?;
+    progress.finish();
 
-    print_items(
-        "Scan Results",
-        &items
-            .iter()
-            .map(|item| item.attributes.clone())
-            .collect::<Vec<_>>(),
-    );
+    print_items("Scan Results", &result.value, &key_columns(table), output);
+    report_cancelled("Scan", result.status, result.value.len());
     Ok(())
 }
 
@@ -352,21 +1116,71 @@ async fn scan_items(ddb: &DynamoDb, table: &Table<'_>) -> Result<()> {
 /// # Returns
 ///
 /// Returns a Result containing the created Item if successful, or an error if the operation fails.
-fn create_key_item(table: &Table<'_>) -> Result<Item> {
+pub(crate) fn create_key_item(reader: &mut dyn LineReader, table: &Table<'_>) -> Result<Item> {
+    prompt_key(reader, table, "Enter")
+}
+
+/// Prompts for each key attribute as "`action` <attribute>".
+fn prompt_key(reader: &mut dyn LineReader, table: &Table<'_>, action: &str) -> Result<Item> {
     let mut key = Item::new();
-    key = key.set_string(
-        table.partition_key(),
-        prompt(&format!("Enter {}: ", table.partition_key()), None)?,
-    );
-    if let Some(sort_key) = table.sort_key() {
-        key = key.set_string(sort_key, prompt(&format!("Enter {}: ", sort_key), None)?);
+    for attribute in std::iter::once(table.partition_key()).chain(table.sort_key()) {
+        let value = prompt_key_value(
+            reader,
+            table,
+            attribute,
+            &format!("{} {}", action, attribute),
+        )?;
+        key.attributes.insert(attribute.to_string(), value);
     }
     Ok(key)
 }
 
-/// Creates an Item containing the attributes to update for a DynamoDB operation.
+/// Prompts for a value of the key attribute `attribute`, typed as the
+/// table's schema declares it.
+///
+/// Invalid values are asked for again; giving up on the value cancels the
+/// command.
+fn prompt_key_value(
+    reader: &mut dyn LineReader,
+    table: &Table<'_>,
+    attribute: &str,
+    message: &str,
+) -> Result<AttributeValue> {
+    let field_type = table.field_type(attribute);
+    let input = prompt(reader, message, None)?;
+    reprompt_until_valid(reader, message, input, |input| coerce(&field_type, input))?
+        .ok_or_else(|| PromptCancelled.into())
+}
+
+/// The changes entered for an item: attributes to set and attributes to
+/// remove.
+#[derive(Debug, Default)]
+pub(crate) struct ItemChanges {
+    pub set: Item,
+    pub remove: Vec<String>,
+}
+
+impl ItemChanges {
+    pub fn is_empty(&self) -> bool {
+        self.set.attributes.is_empty() && self.remove.is_empty()
+    }
+}
+
+impl From<ItemChanges> for UpdateBuilder {
+    fn from(changes: ItemChanges) -> Self {
+        changes
+            .remove
+            .into_iter()
+            .fold(UpdateBuilder::from(&changes.set), UpdateBuilder::remove)
+    }
+}
+
+/// Prompts for the changes to make to an item.
 ///
-/// This function prompts the user to enter new values for each updateable field in the table schema.
+/// This function asks whether to update, remove or skip each non-key field in the table schema,
+/// in name order, and prompts for new values of updated fields. Removing a schema field asks for
+/// confirmation first. Entering a JSON object at the first prompt takes the changed fields from
+/// it instead; no changes means the JSON entry was cancelled.
 ///
 /// # Arguments
 ///
@@ -374,54 +1188,222 @@ fn create_key_item(table: &Table<'_>) -> Result<Item> {
 ///
 /// # Returns
 ///
-/// Returns a Result containing the created Item if successful, or an error if the operation fails.
-fn create_update_item(table: &Table<'_>) -> Result<Item> {
+/// Returns a Result containing the changes if successful, or an error if the operation fails.
+pub(crate) fn create_update_item(
+    reader: &mut dyn LineReader,
+    table: &Table<'_>,
+) -> Result<ItemChanges> {
     let schema = table
         .schema()
         .ok_or_else(|| anyhow!("Table schema not defined"))?;
-    let mut updates = Item::new();
-    for (field_name, field_type) in schema.fields() {
-        // Skip partition key and sort key fields
-        let is_not_partition_key = field_name != table.partition_key();
-        let is_not_sort_key = table
-            .sort_key()
-            .map_or(true, |sort_key| field_name != sort_key);
-        if is_not_partition_key
-            && is_not_sort_key
-            && prompt(&format!("Update {}? (y/n): ", field_name), None)?.to_lowercase() == "y"
-        {
-            let value = prompt(&format!("Enter new value for {}: ", field_name), None)?;
-            updates = match field_type {
-                FieldType::String => updates.set_string(field_name, value),
-                FieldType::Number => updates.set_number(field_name, value.parse::<f64>()?),
-            };
+    let mut fields: Vec<_> = schema
+        .fields()
+        .iter()
+        .filter(|(field_name, _)| !table.is_key_attribute(field_name))
+        .collect();
+    fields.sort_by_key(|(field_name, _)| field_name.as_str());
+
+    let mut changes = ItemChanges::default();
+    for (index, (field_name, field_type)) in fields.into_iter().enumerate() {
+        let answer = if index == 0 {
+            prompt(
+                reader,
+                &format!(
+                    "Update {}? (update/remove/skip, or the changed fields as JSON)",
+                    field_name
+                ),
+                Some("u/r/s"),
+            )?
+        } else {
+            prompt(
+                reader,
+                &format!("Update {}? (update/remove/skip)", field_name),
+                Some("u/r/s"),
+            )?
+        };
+        if index == 0 && answer.starts_with('{') {
+            let set = read_json_item(
+                answer,
+                |message| prompt(reader, message, None),
+                |json| {
+                    let updates = Item::from_json(&json)?;
+                    table.validate_updates(&updates)?;
+                    Ok(updates)
+                },
+            )?;
+            return Ok(ItemChanges {
+                set: set.unwrap_or_default(),
+                remove: Vec::new(),
+            });
+        }
+        match answer.to_lowercase().as_str() {
+            "u" | "update" | "y" => {
+                let value = prompt(
+                    reader,
+                    &format!("Enter new value for {}", field_name),
+                    field_example(field_type),
+                )?;
+                let value = reprompt_until_valid(
+                    reader,
+                    &format!("Enter new value for {}", field_name),
+                    value,
+                    |input| coerce(field_type, input),
+                )?;
+                let Some(value) = value else {
+                    println!("Update cancelled.");
+                    return Ok(ItemChanges::default());
+                };
+                changes.set.attributes.insert(field_name.clone(), value);
+            }
+            "r" | "remove" => {
+                println!(
+                    "Warning: '{}' is part of the table's schema; the item won't have it after the update.",
+                    field_name
+                );
+                if prompt_bool(reader, &format!("Remove {}?", field_name), false)? {
+                    changes.remove.push(field_name.clone());
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(changes)
+}
+
+/// Reads a JSON object that starts with `first_line`, reading further lines
+/// from `read_line` until its braces balance, and converts it with
+/// `convert`.
+///
+/// Invalid JSON, or an object `convert` rejects, is reported (with the
+/// position of a syntax error) and read again. Returns `None` if an empty
+/// line is entered instead.
+pub(crate) fn read_json_item(
+    first_line: String,
+    mut read_line: impl FnMut(&str) -> Result<String>,
+    convert: impl Fn(serde_json::Value) -> Result<Item>,
+) -> Result<Option<Item>> {
+    let mut text = first_line;
+    loop {
+        while !json_braces_balanced(&text) {
+            let line = read_line("...")?;
+            if line.is_empty() {
+                break;
+            }
+            text.push('\n');
+            text.push_str(&line);
+        }
+
+        let problem = match serde_json::from_str::<serde_json::Value>(&text) {
+            Ok(json) => match convert(json) {
+                Ok(item) => return Ok(Some(item)),
+                Err(e) => e.to_string(),
+            },
+            Err(e) => format!("Invalid JSON: {}", e),
+        };
+        println!("{}", problem);
+
+        text = read_line("Enter the JSON object again (or press Enter to cancel)")?;
+        if text.is_empty() {
+            return Ok(None);
+        }
+    }
+}
+
+/// Whether every `{` in `text` outside of strings has been closed.
+fn json_braces_balanced(text: &str) -> bool {
+    let mut depth = 0i32;
+    let mut in_string = false;
+    let mut escaped = false;
+    for c in text.chars() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if in_string => escaped = true,
+            '"' => in_string = !in_string,
+            '{' if !in_string => depth += 1,
+            '}' if !in_string => depth -= 1,
+            _ => {}
         }
     }
-    Ok(updates)
+    depth <= 0
 }
 
 /// Lists all items in the DynamoDB table.
 ///
-/// This function retrieves all items from the table using a scan operation and displays them.
+/// This function scans the table page by page and displays its items. JSON
+/// output is printed as each page arrives; the table view needs every item
+/// to size its columns, so it's printed once the scan finishes.
 ///
 /// # Arguments
 ///
 /// * `ddb` - A reference to the DynamoDB client
 /// * `table` - A reference to the Table struct containing table information
+/// * `output` - How to print the items
 ///
 /// # Returns
 ///
 /// Returns `Ok(())` if the operation completes successfully, or an error if it fails.
-async fn list_items(ddb: &DynamoDb, table: &Table<'_>) -> Result<()> {
-    let items = ddb.scan_table(table.name()).await?;
-    println!("\n--- Items in {} ---", table.name());
-    items.iter().for_each(|item| println!("{:?}", item));
-    println!("-------------------------\n");
+async fn list_items(
+    ddb: &impl DynamoDbApi,
+    table: &Table<'_>,
+    output: OutputFormat,
+    cancel: &CancellationToken,
+) -> Result<()> {
+    let mut progress = ProgressLine::new("Scanned");
+    let mut items = Vec::new();
+    let json_format = match output {
+        OutputFormat::Table => None,
+        OutputFormat::Json => Some(JsonFormat::Plain),
+        OutputFormat::DynamoDb => Some(JsonFormat::DynamoDb),
+    };
+    // The progress line would be interleaved with JSON lines on a terminal,
+    // so it's only shown for the table view.
+    let mut update = |p: &Progress| progress.update(p);
+    let on_progress: Option<&mut dyn FnMut(&Progress)> = match json_format {
+        Some(_) => None,
+        None => Some(&mut update),
+    };
+    let result = ddb
+        .for_each_item_cancellable(
+            ScanParams {
+                table_name: table.name(),
+                ..Default::default()
+            },
+            on_progress,
+            cancel,
+            &mut |item| {
+                match json_format {
+                    Some(format) => println!("{}", item.to_json(format)),
+                    None => items.push(item),
+                }
+                Ok(ControlFlow::Continue(()))
+            },
+        )
+        .await?;
+    progress.finish();
+    if json_format.is_none() {
+        print_items(
+            &format!("Items in {}", table.name()),
+            &items,
+            &key_columns(table),
+            output,
+        );
+    }
+    report_cancelled("Scan", result.status, result.value.items);
     Ok(())
 }
 
-/// Prompts the user for input and returns the entered string.
-///
+/// Tells the user when a cancelled scan's items are only the first pages.
+fn report_cancelled(operation: &str, status: BulkStatus, items: usize) {
+    if status == BulkStatus::Cancelled {
+        println!(
+            "{} cancelled; showing the first {} item(s) only.",
+            operation, items
+        );
+    }
+}
+
+/// Prompts the user for input and returns the entered string.
+///
 /// This function displays a message to the user, waits for input, and returns the entered string.
 ///
 /// # Arguments
@@ -431,73 +1413,314 @@ async fn list_items(ddb: &DynamoDb, table: &Table<'_>) -> Result<()> {
 /// # Returns
 ///
 /// Returns a Result containing the user's input as a String if successful, or an error if the operation fails.
-fn prompt(message: &str, example: Option<&str>) -> Result<String> {
+fn prompt(reader: &mut dyn LineReader, message: &str, example: Option<&str>) -> Result<String> {
     let full_message = if let Some(ex) = example {
         format!("{} (e.g., {}): ", message, ex)
     } else {
         format!("{}: ", message)
     };
-    print!("{}", full_message);
-    io::stdout().flush()?;
-    let mut input = String::new();
-    io::stdin().read_line(&mut input)?;
-    Ok(input.trim().to_string())
+    Ok(reader.read_line(&full_message)?.trim().to_string())
+}
+
+/// Converts what was typed for a field of type `field_type` into an
+/// attribute value.
+///
+/// Numbers are kept as typed, trimmed, so precision isn't lost. Booleans are
+/// y/n; sets and string lists are comma-separated, or a JSON array as
+/// `export_csv` writes them; other lists and maps are JSON.
+pub(crate) fn coerce(field_type: &FieldType, input: &str) -> Result<AttributeValue> {
+    match field_type {
+        FieldType::String => Ok(AttributeValue::S(input.to_string())),
+        FieldType::Number => Ok(AttributeValue::N(number(input)?)),
+        FieldType::Boolean => match input.trim().to_lowercase().as_str() {
+            "y" | "yes" | "true" => Ok(AttributeValue::Bool(true)),
+            "n" | "no" | "false" => Ok(AttributeValue::Bool(false)),
+            _ => Err(anyhow!("'{}' is not y or n", input)),
+        },
+        FieldType::StringSet => Ok(AttributeValue::Ss(set_values(input)?)),
+        FieldType::NumberSet => Ok(AttributeValue::Ns(
+            set_values(input)?
+                .iter()
+                .map(|n| number(n))
+                .collect::<Result<_>>()?,
+        )),
+        FieldType::StringList => Ok(AttributeValue::L(
+            list_values(input)?
+                .into_iter()
+                .map(AttributeValue::S)
+                .collect(),
+        )),
+        FieldType::List => match json_value(input)? {
+            list @ serde_json::Value::Array(_) => Ok(from_plain_json(&list)),
+            other => Err(anyhow!("Expected a JSON array, found {}", other)),
+        },
+        FieldType::Map => match json_value(input)? {
+            map @ serde_json::Value::Object(_) => Ok(from_plain_json(&map)),
+            other => Err(anyhow!("Expected a JSON object, found {}", other)),
+        },
+    }
+}
+
+fn number(input: &str) -> Result<String> {
+    let number = input.trim();
+    match number.parse::<f64>() {
+        Ok(parsed) if parsed.is_finite() => Ok(number.to_string()),
+        _ => Err(anyhow!("'{}' is not a number", input)),
+    }
+}
+
+/// Splits comma-separated values, or reads a JSON array of strings and
+/// numbers.
+fn list_values(input: &str) -> Result<Vec<String>> {
+    if !input.trim_start().starts_with('[') {
+        return Ok(input
+            .split(',')
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .map(String::from)
+            .collect());
+    }
+    let serde_json::Value::Array(values) = json_value(input)? else {
+        return Err(anyhow!("Expected a JSON array, found {}", input));
+    };
+    values
+        .into_iter()
+        .map(|value| match value {
+            serde_json::Value::String(s) => Ok(s),
+            serde_json::Value::Number(n) => Ok(n.to_string()),
+            other => Err(anyhow!("Expected a string or number, found {}", other)),
+        })
+        .collect()
+}
+
+/// Like [`list_values`], without duplicates. DynamoDB rejects empty sets.
+fn set_values(input: &str) -> Result<Vec<String>> {
+    let mut values = list_values(input)?;
+    let mut seen = std::collections::HashSet::new();
+    values.retain(|value| seen.insert(value.clone()));
+    if values.is_empty() {
+        return Err(anyhow!("A set needs at least one value"));
+    }
+    Ok(values)
+}
+
+fn json_value(input: &str) -> Result<serde_json::Value> {
+    serde_json::from_str(input.trim()).map_err(|e| anyhow!("'{}' is not valid JSON: {}", input, e))
+}
+
+/// How a value of `field_type` is typed at a prompt, shown as the prompt's
+/// example.
+fn field_example(field_type: &FieldType) -> Option<&'static str> {
+    match field_type {
+        FieldType::String | FieldType::Number => None,
+        FieldType::Boolean => Some("y/n"),
+        FieldType::StringSet | FieldType::StringList => Some("red, green, blue"),
+        FieldType::NumberSet => Some("1, 2.5, 10"),
+        FieldType::List => Some("[1, \"two\", {\"three\": 3}]"),
+        FieldType::Map => Some("{\"color\": \"red\", \"size\": 10}"),
+    }
+}
+
+/// Parses `input` with `parse`, prompting again with `message` for as long
+/// as it's rejected.
+///
+/// Each rejection is explained. Returns `None` if an empty line is entered
+/// after a rejection, so the user can give up on the value.
+fn reprompt_until_valid<T>(
+    reader: &mut dyn LineReader,
+    message: &str,
+    input: String,
+    parse: impl Fn(&str) -> Result<T>,
+) -> Result<Option<T>> {
+    let mut input = input;
+    loop {
+        match parse(&input) {
+            Ok(value) => return Ok(Some(value)),
+            Err(e) => println!("{}. Try again, or press Enter to abort.", e),
+        }
+        input = prompt(reader, message, None)?;
+        if input.is_empty() {
+            return Ok(None);
+        }
+    }
 }
 
-fn get_expression_attribute_names() -> Result<HashMap<String, String>> {
+/// Prompts for an optional limit, prompting again with `message` for as
+/// long as the input isn't a positive whole number that fits `T`.
+///
+/// Returns `Some(None)` if no limit was entered, and `None` if the user gave
+/// up after a rejection.
+fn prompt_limit<T: TryFrom<usize>>(
+    reader: &mut dyn LineReader,
+    message: &str,
+    example: Option<&str>,
+) -> Result<Option<Option<T>>> {
+    let input = prompt(reader, message, example)?;
+    reprompt_until_valid(reader, message, input, |input| {
+        if input.is_empty() {
+            return Ok(None);
+        }
+        input
+            .parse::<usize>()
+            .ok()
+            .filter(|limit| *limit > 0)
+            .and_then(|limit| T::try_from(limit).ok())
+            .map(Some)
+            .ok_or_else(|| anyhow!("'{}' is not a positive whole number", input))
+    })
+}
+
+fn get_expression_attribute_names(reader: &mut dyn LineReader) -> Result<HashMap<String, String>> {
     let mut names = HashMap::new();
     loop {
         let name = prompt(
+            reader,
             "Enter attribute name (or press Enter to finish)",
             Some("#n"),
         )?;
         if name.is_empty() {
             break;
         }
-        let placeholder = prompt("Enter attribute name placeholder", Some("#name"))?;
+        let placeholder = prompt(reader, "Enter attribute name placeholder", Some("#name"))?;
         names.insert(placeholder, name);
     }
     Ok(names)
 }
 
-fn get_expression_attribute_values() -> Result<HashMap<String, AttributeValue>> {
+fn get_expression_attribute_values(
+    reader: &mut dyn LineReader,
+) -> Result<HashMap<String, AttributeValue>> {
     let mut values = HashMap::new();
     loop {
         let placeholder = prompt(
+            reader,
             "Enter value placeholder (or press Enter to finish)",
             Some(":v"),
         )?;
         if placeholder.is_empty() {
             break;
         }
-        let value_type = prompt("Enter value type (S for string, N for number)", Some("S"))?;
-        let value = prompt("Enter value", Some("example_value"))?;
-        let attribute_value = match value_type.as_str() {
-            "S" => AttributeValue::S(value),
-            "N" => AttributeValue::N(value),
-            _ => return Err(anyhow!("Unsupported value type")),
-        };
-        values.insert(placeholder, attribute_value);
+        let value_type = prompt(
+            reader,
+            "Enter value type (S for string, N for number)",
+            Some("S"),
+        )?;
+        let value = prompt(reader, "Enter value", Some("example_value"))?;
+        values.insert(placeholder, attribute_value(&value_type, value)?);
     }
     Ok(values)
 }
 
+/// Wraps a prompted value in the attribute type named by `value_type`.
+fn attribute_value(value_type: &str, value: String) -> Result<AttributeValue> {
+    match value_type {
+        "S" => Ok(AttributeValue::S(value)),
+        "N" => Ok(AttributeValue::N(value)),
+        _ => Err(anyhow!("Unsupported value type")),
+    }
+}
+
+/// Prints the session's request metrics for `stats`, as JSON when the
+/// output format is JSON.
+fn print_stats(metrics: Option<MetricsSnapshot>, output: OutputFormat) -> Result<()> {
+    let Some(metrics) = metrics else {
+        println!("Metrics are not being collected.");
+        return Ok(());
+    };
+    match output {
+        OutputFormat::Table => println!("{metrics}"),
+        OutputFormat::Json | OutputFormat::DynamoDb => {
+            println!("{}", serde_json::to_string(&metrics)?)
+        }
+    }
+    Ok(())
+}
+
+/// Prints the footer giving the capacity a command consumed, e.g.
+/// `consumed: 3.5 RCU`, if it consumed any.
+fn print_consumed(before: Option<TableCapacity>, after: Option<MetricsSnapshot>) {
+    if let (Some(before), Some(after)) = (before, after) {
+        let consumed = after.total_capacity() - before;
+        if consumed.total() > 0.0 {
+            println!("consumed: {consumed}");
+        }
+    }
+}
+
+/// Prints who the client is authenticated as, for `whoami` and at startup.
+pub fn print_identity(auth: Option<&AuthInfo>) {
+    match auth {
+        Some(auth) => println!("Authenticated as {auth}"),
+        None => println!("Not using AWS credentials: this endpoint doesn't check them"),
+    }
+}
+
+/// Lists the tables in the account, optionally only those with a name
+/// prefix, marking the active table with `*`.
+async fn list_tables(
+    ddb: &impl DynamoDbApi,
+    reader: &mut dyn LineReader,
+    active_table: Option<&Table<'_>>,
+) -> Result<()> {
+    let prefix = prompt_optional(reader, "Enter table name prefix", Some("testing-"))?;
+    let table_names = match prefix {
+        Some(prefix) => ddb.list_tables_with_prefix(&prefix).await?,
+        None => ddb.list_tables().await?,
+    };
+
+    println!("\n--- Tables ---");
+    for name in &table_names {
+        let marker = if active_table.map(|table| table.name()) == Some(name.as_str()) {
+            "*"
+        } else {
+            " "
+        };
+        println!("{} {}", marker, name);
+    }
+    println!("{} table(s)", table_names.len());
+    println!("--------------\n");
+    Ok(())
+}
+
 /// Performs a flexible query operation on the DynamoDB table.
-async fn query_flexible_items(ddb: &DynamoDb, table: &Table<'_>) -> Result<()> {
-    let key_condition_expression =
-        prompt("Enter key condition expression", Some("partitionKey = :pk"))?;
-    let filter_expression = prompt_optional("Enter filter expression", Some("attribute > :value"))?;
-    let projection_expression =
-        prompt_optional("Enter projection expression", Some("attr1, attr2, attr3"))?;
+async fn query_flexible_items(
+    ddb: &impl DynamoDbApi,
+    reader: &mut dyn LineReader,
+    table: &Table<'_>,
+    output: OutputFormat,
+) -> Result<()> {
+    let key_condition_expression = prompt(
+        reader,
+        "Enter key condition expression",
+        Some("partitionKey = :pk"),
+    )?;
+    let filter_expression = prompt_optional(
+        reader,
+        "Enter filter expression",
+        Some("attribute > :value"),
+    )?;
+    let projection = prompt_projection(reader)?;
 
-    let expression_attribute_names = get_expression_attribute_names()?;
-    let expression_attribute_values = get_expression_attribute_values()?;
+    let expression_attribute_names = get_expression_attribute_names(reader)?;
+    let expression_attribute_values = get_expression_attribute_values(reader)?;
 
-    let limit = prompt_optional("Enter limit", Some("10"))?.and_then(|s| s.parse().ok());
+    let Some(limit) = prompt_limit(reader, "Enter limit (or press Enter for none)", Some("10"))?
+    else {
+        println!("Query cancelled.");
+        return Ok(());
+    };
 
-    let scan_index_forward = prompt_bool("Scan index forward?", true)?;
+    let scan_index_forward = prompt_bool(reader, "Scan index forward?", true)?;
 
-    let index_name = prompt_optional("Enter index name", Some("GSI1"))?;
+    let index_name = prompt_optional(reader, "Enter index name", Some("GSI1"))?;
+
+    let consistent_read = prompt_bool(reader, "Use strongly consistent reads?", false)?;
+    let select = prompt_choice::<Select>(reader, "Enter attributes to select", Select::values())?;
+    let return_consumed_capacity = prompt_choice::<ReturnConsumedCapacity>(
+        reader,
+        "Enter consumed capacity detail",
+        ReturnConsumedCapacity::values(),
+    )?;
 
     let params = QueryFlexibleParams {
         table_name: table.name(),
@@ -505,55 +1728,92 @@ async fn query_flexible_items(ddb: &DynamoDb, table: &Table<'_>) -> Result<()> {
         expression_attribute_names: Some(expression_attribute_names),
         expression_attribute_values: Some(expression_attribute_values),
         filter_expression: filter_expression.as_deref(),
-        projection_expression: projection_expression.as_deref(),
+        projection_expression: None,
         limit,
         scan_index_forward: Some(scan_index_forward),
         index_name: index_name.as_deref(),
+        consistent_read: Some(consistent_read),
+        select,
+        return_consumed_capacity,
+    };
+    let params = match &projection {
+        Some(projection) => params.with_projection(projection)?,
+        None => params,
     };
 
-    let items = ddb.query_flexible(params).await?;
+    let result = ddb.query_flexible_detailed(params, None).await?;
 
     print_items(
         "Query Flexible Results",
-        &items
-            .iter()
-            .map(|item| item.attributes.clone())
-            .collect::<Vec<_>>(),
+        &result.items,
+        &key_columns(table),
+        output,
     );
+    print_query_counts(&result);
     Ok(())
 }
 
 /// Performs a simple query operation on the DynamoDB table.
-async fn query_simple_items(ddb: &DynamoDb, table: &Table<'_>) -> Result<()> {
+async fn query_simple_items(
+    ddb: &impl DynamoDbApi,
+    reader: &mut dyn LineReader,
+    table: &Table<'_>,
+    output: OutputFormat,
+) -> Result<()> {
     let partition_key_name = table.partition_key();
-    let partition_key_value = prompt(
+    let partition_key_value = prompt_key_value(
+        reader,
+        table,
+        partition_key_name,
         &format!("Enter {} value", partition_key_name),
-        Some("example_value"),
     )?;
-    let partition_key = (partition_key_name, AttributeValue::S(partition_key_value));
+    let partition_key = (partition_key_name, partition_key_value);
 
-    let sort_key_condition = table.sort_key().map(|sort_key| {
-        let condition = prompt(
-            &format!(
-                "Enter condition for {} (e.g., '>', '<', '=', 'BETWEEN')",
-                sort_key
-            ),
-            Some(">="),
-        )
-        .unwrap();
-        let value = prompt(
-            &format!("Enter value for {}", sort_key),
-            Some("example_value"),
-        )
-        .unwrap();
-        (sort_key, condition, AttributeValue::S(value))
-    });
+    let sort_key_condition = table
+        .sort_key()
+        .map(|sort_key| -> Result<_> {
+            let condition = prompt(
+                reader,
+                &format!(
+                    "Enter condition for {} (e.g., '>', '<', '=', 'BETWEEN')",
+                    sort_key
+                ),
+                Some(">="),
+            )?;
+            let value = prompt_key_value(
+                reader,
+                table,
+                sort_key,
+                &format!("Enter value for {}", sort_key),
+            )?;
+            let value = if condition.trim().eq_ignore_ascii_case("BETWEEN") {
+                let high = prompt_key_value(
+                    reader,
+                    table,
+                    sort_key,
+                    &format!("Enter upper value for {}", sort_key),
+                )?;
+                SortKeyValue::Between(value, high)
+            } else {
+                value.into()
+            };
+            Ok((sort_key, condition, value))
+        })
+        .transpose()?;
 
-    let filter_expression = prompt_optional("Enter filter expression", Some("attribute > :value"))?;
+    let filter_expression = prompt_optional(
+        reader,
+        "Enter filter expression",
+        Some("attribute > :value"),
+    )?;
 
-    let limit = prompt_optional("Enter limit", Some("10"))?.and_then(|s| s.parse().ok());
+    let Some(limit) = prompt_limit(reader, "Enter limit (or press Enter for none)", Some("10"))?
+    else {
+        println!("Query cancelled.");
+        return Ok(());
+    };
 
-    let expression_attribute_values = get_expression_attribute_values()?;
+    let expression_attribute_values = get_expression_attribute_values(reader)?;
 
     let items = ddb
         .query_simple(
@@ -563,73 +1823,86 @@ async fn query_simple_items(ddb: &DynamoDb, table: &Table<'_>) -> Result<()> {
             filter_expression.as_deref(),
             limit,
             Some(expression_attribute_values),
+            None,
         )
         .await?;
 
-    print_items(
-        "Query Simple Results",
-        &items
-            .iter()
-            .map(|item| item.attributes.clone())
-            .collect::<Vec<_>>(),
-    );
+    print_items("Query Simple Results", &items, &key_columns(table), output);
     Ok(())
 }
 
 /// Performs a paginated scan operation on the DynamoDB table.
-async fn scan_paginated_items(ddb: &DynamoDb, table: &Table<'_>) -> Result<()> {
-    let filter_expression = prompt_optional("Enter filter expression", Some("attribute > :value"))?;
-    let projection_expression =
-        prompt_optional("Enter projection expression", Some("attr1, attr2, attr3"))?;
+///
+/// Cancelling `cancel` while a page is being read stops after that page.
+async fn scan_paginated_items(
+    ddb: &impl DynamoDbApi,
+    reader: &mut dyn LineReader,
+    table: &Table<'_>,
+    output: OutputFormat,
+    cancel: &CancellationToken,
+) -> Result<()> {
+    let filter_expression = prompt_optional(
+        reader,
+        "Enter filter expression",
+        Some("attribute > :value"),
+    )?;
+    let projection = prompt_projection(reader)?;
 
-    let expression_attribute_names = get_expression_attribute_names()?;
-    let expression_attribute_values = get_expression_attribute_values()?;
+    let expression_attribute_names = get_expression_attribute_names(reader)?;
+    let expression_attribute_values = get_expression_attribute_values(reader)?;
     let filter_expression = match filter_expression {
         Some(expr) if !expr.is_empty() => Some(expr),
         _ => None,
     };
 
-    let projection_expression = match projection_expression {
-        Some(expr) if !expr.is_empty() => Some(expr),
-        _ => None,
+    let Some(limit) = prompt_limit(reader, "Enter limit (or press Enter for none)", Some("10"))?
+    else {
+        println!("Scan cancelled.");
+        return Ok(());
     };
 
-    let limit = prompt("Enter limit (or press Enter for none)", Some("10"))?;
-    let limit = if limit.is_empty() {
-        None
-    } else {
-        Some(limit.parse()?)
-    };
+    let index_name = prompt_optional(reader, "Enter index name", Some("GSI1"))?;
+    let index_name = index_name.filter(|name| !name.is_empty());
+    let consistent_read = prompt_bool(reader, "Use strongly consistent reads?", false)?;
 
     let mut exclusive_start_key = None;
     let mut page_num = 1;
 
     loop {
+        let params = ScanParams {
+            table_name: table.name(),
+            index_name: index_name.as_deref(),
+            filter_expression: filter_expression.as_deref(),
+            projection_expression: None,
+            expression_attribute_names: Some(expression_attribute_names.clone()),
+            expression_attribute_values: Some(expression_attribute_values.clone()),
+            limit,
+            consistent_read: Some(consistent_read),
+        };
+        let params = match &projection {
+            Some(projection) => params.with_projection(projection)?,
+            None => params,
+        };
         let (items, last_evaluated_key) = ddb
-            .scan_paginated(
-                table.name(),
-                filter_expression.as_deref(),
-                projection_expression.as_deref(),
-                Some(expression_attribute_names.clone()),
-                Some(expression_attribute_values.clone()),
-                limit,
-                exclusive_start_key.clone(),
-            )
+            .scan_paginated(params, exclusive_start_key.clone())
             .await?;
 
         print_items(
             &format!("Scan Paginated Results (Page {})", page_num),
-            &items
-                .iter()
-                .map(|item| item.attributes.clone())
-                .collect::<Vec<_>>(),
+            &items,
+            &key_columns(table),
+            output,
         );
 
         if last_evaluated_key.is_none() {
             break;
         }
+        if cancel.is_cancelled() {
+            println!("Scan cancelled after page {}.", page_num);
+            break;
+        }
 
-        let continue_scan = prompt("Continue to next page? (y/n)", Some("y"))?;
+        let continue_scan = prompt(reader, "Continue to next page? (y/n)", Some("y"))?;
         if continue_scan.to_lowercase() != "y" {
             break;
         } else {
@@ -641,25 +1914,1167 @@ async fn scan_paginated_items(ddb: &DynamoDb, table: &Table<'_>) -> Result<()> {
     Ok(())
 }
 
-fn print_items(title: &str, items: &[HashMap<String, AttributeValue>]) {
-    println!("\n--- {} ---", title);
-    items.iter().for_each(|item| println!("{:?}", item));
-    println!("{}", "-".repeat(title.len() + 8));
+/// Performs a paginated query operation on the DynamoDB table.
+async fn query_paginated_items(
+    ddb: &impl DynamoDbApi,
+    reader: &mut dyn LineReader,
+    table: &Table<'_>,
+    output: OutputFormat,
+) -> Result<()> {
+    let key_condition_expression = prompt(
+        reader,
+        "Enter key condition expression",
+        Some("partitionKey = :pk"),
+    )?;
+    let filter_expression = prompt_optional(
+        reader,
+        "Enter filter expression",
+        Some("attribute > :value"),
+    )?;
+
+    let expression_attribute_names = get_expression_attribute_names(reader)?;
+    let expression_attribute_values = get_expression_attribute_values(reader)?;
+
+    let Some(limit) = prompt_limit(
+        reader,
+        "Enter page size (or press Enter for none)",
+        Some("10"),
+    )?
+    else {
+        println!("Query cancelled.");
+        return Ok(());
+    };
+
+    let mut exclusive_start_key = None;
+    let mut page_num = 1;
+
+    loop {
+        let params = QueryFlexibleParams {
+            table_name: table.name(),
+            key_condition_expression: &key_condition_expression,
+            expression_attribute_names: Some(expression_attribute_names.clone()),
+            expression_attribute_values: Some(expression_attribute_values.clone()),
+            filter_expression: filter_expression.as_deref(),
+            projection_expression: None,
+            limit,
+            scan_index_forward: None,
+            index_name: None,
+            ..Default::default()
+        };
+        let result = ddb
+            .query_flexible_detailed(params, exclusive_start_key.clone())
+            .await?;
+
+        print_items(
+            &format!("Query Paginated Results (Page {})", page_num),
+            &result.items,
+            &key_columns(table),
+            output,
+        );
+        print_query_counts(&result);
+        let last_evaluated_key = result.last_evaluated_key;
+
+        if last_evaluated_key.is_none() {
+            break;
+        }
+
+        let continue_query = prompt(reader, "Continue to next page? (y/n)", Some("y"))?;
+        if continue_query.to_lowercase() != "y" {
+            break;
+        } else {
+            exclusive_start_key = last_evaluated_key;
+            page_num += 1;
+        }
+    }
+
+    Ok(())
+}
+
+/// Runs a PartiQL statement typed at the prompt.
+///
+/// The statement may span several lines and ends at a line ending in `;`.
+/// A `SELECT` pages through its results like `scan_paginated`; an `UPDATE` or
+/// `DELETE` without a `WHERE` clause asks for confirmation before running.
+async fn run_sql(
+    ddb: &impl DynamoDbApi,
+    reader: &mut dyn LineReader,
+    output: OutputFormat,
+) -> Result<()> {
+    let Some(statement) = read_statement(|message| prompt(reader, message, None))? else {
+        println!("No statement entered.");
+        return Ok(());
+    };
+    let parameters = read_parameters(statement.parameter_count(), |message, example| {
+        prompt(reader, message, example)
+    })?;
+
+    if statement.is_unbounded_write()
+        && !prompt_bool(
+            reader,
+            &format!(
+                "{} without a WHERE clause can affect every item. Run it anyway?",
+                statement.kind()
+            ),
+            false,
+        )?
+    {
+        println!("Statement cancelled.");
+        return Ok(());
+    }
+
+    if statement.kind().is_write() {
+        ddb.execute_statement(&statement, parameters, None).await?;
+        // PartiQL writes in DynamoDB always target a single item.
+        println!("{} succeeded: 1 item affected", statement.kind());
+        return Ok(());
+    }
+
+    let mut next_token = None;
+    let mut page_num = 1;
+
+    loop {
+        let result = ddb
+            .execute_statement(&statement, parameters.clone(), next_token)
+            .await?;
+
+        print_items(
+            &format!("SQL Results (Page {})", page_num),
+            &result.items,
+            &[],
+            output,
+        );
+
+        if result.next_token.is_none() {
+            break;
+        }
+
+        let continue_select = prompt(reader, "Continue to next page? (y/n)", Some("y"))?;
+        if continue_select.to_lowercase() != "y" {
+            break;
+        } else {
+            next_token = result.next_token;
+            page_num += 1;
+        }
+    }
+
+    Ok(())
+}
+
+/// Reads a statement line by line from `read_line` until a line ends with `;`.
+///
+/// Returns `None` if the first line is empty.
+pub(crate) fn read_statement(
+    mut read_line: impl FnMut(&str) -> Result<String>,
+) -> Result<Option<Statement>> {
+    let mut text = String::new();
+    loop {
+        let message = if text.is_empty() {
+            "Enter PartiQL statement, ending with ';'"
+        } else {
+            "..."
+        };
+        let line = read_line(message)?;
+        if text.is_empty() && line.trim().is_empty() {
+            return Ok(None);
+        }
+
+        text.push_str(&line);
+        text.push('\n');
+        if line.trim_end().ends_with(';') {
+            return Statement::parse(&text).map(Some);
+        }
+    }
+}
+
+/// Reads the type and value of each of a statement's positional parameters
+/// from `read`, which is called like [`prompt`].
+pub(crate) fn read_parameters(
+    count: usize,
+    mut read: impl FnMut(&str, Option<&str>) -> Result<String>,
+) -> Result<Vec<AttributeValue>> {
+    (1..=count)
+        .map(|position| {
+            let value_type = read(
+                &format!(
+                    "Enter type of parameter {} (S for string, N for number)",
+                    position
+                ),
+                Some("S"),
+            )?;
+            let value = read(
+                &format!("Enter value of parameter {}", position),
+                Some("example_value"),
+            )?;
+            attribute_value(&value_type, value)
+        })
+        .collect()
+}
+
+/// Prints the changes made to the table as they happen, rendered with
+/// [`render_change`], until Enter or Ctrl-C is pressed.
+///
+/// If the table's stream isn't enabled, offers to enable it with new and old
+/// images, so changes can be shown attribute by attribute. If reading the
+/// stream fails, the error is printed as soon as it happens, and Enter
+/// returns to the prompt.
+async fn watch_changes(
+    ddb: &impl DynamoDbApi,
+    reader: &mut dyn LineReader,
+    table: &Table<'_>,
+    cancel: &CancellationToken,
+) -> Result<()> {
+    let cancel = cancel.child_token();
+    let subscribe = || {
+        ddb.subscribe_changes(
+            table.name(),
+            Arc::new(InMemoryCheckpoints::default()),
+            cancel.clone(),
+            Box::new(|record| println!("{}", render_change(&record))),
+        )
+    };
+    let subscription = match subscribe().await {
+        Err(e) if e.downcast_ref::<StreamsDisabled>().is_some() => {
+            println!("{}.", e);
+            if !prompt_bool(reader, "Enable a stream with new and old images?", true)? {
+                println!("Watch cancelled.");
+                return Ok(());
+            }
+            ddb.enable_stream(table.name(), StreamViewType::NewAndOldImages)
+                .await?;
+            ddb.wait_for_table_active(table.name(), STREAM_ENABLE_TIMEOUT)
+                .await?;
+            subscribe().await?
+        }
+        result => result?,
+    };
+
+    println!(
+        "Watching '{}' for changes. Press Enter or Ctrl-C to stop.",
+        table.name()
+    );
+    // Reading the line blocks this thread until Enter, so the subscription
+    // is awaited in a task of its own that reports a failure straight away.
+    let subscription = tokio::spawn(async move {
+        let result = subscription
+            .await
+            .map_err(anyhow::Error::from)
+            .and_then(|result| result);
+        if let Err(e) = &result {
+            println!("Error: {:#}\nPress Enter to return to the prompt.", e);
+        }
+        result
+    });
+    let stopped = reader.read_line("");
+    cancel.cancel();
+    // A failed subscription has been reported already.
+    if subscription.await?.is_ok() {
+        println!("Stopped watching '{}'.", table.name());
+    }
+    match stopped {
+        Err(e) if !e.is::<PromptCancelled>() => Err(e),
+        _ => Ok(()),
+    }
+}
+
+/// Batch writes the items in a JSON file to the table.
+///
+/// The file holds an array of items, or one item per line as written by
+/// `export`, either as plain JSON objects or in DynamoDB JSON. Records that don't convert or don't fit the table are
+/// skipped and listed at the end; malformed JSON aborts before anything is
+/// written.
+async fn import_items(
+    ddb: &impl DynamoDbApi,
+    reader: &mut dyn LineReader,
+    table: &Table<'_>,
+    cancel: &CancellationToken,
+) -> Result<()> {
+    let path = prompt(reader, "Enter path to JSON file", Some("items.json"))?;
+    let contents =
+        std::fs::read_to_string(&path).map_err(|e| anyhow!("Failed to read '{}': {}", path, e))?;
+    let import = parse_json_import(&contents, table)?;
+
+    let written = write_imported_items(ddb, table, import.items, cancel).await?;
+    println!(
+        "Imported {} item(s), rejected {}",
+        written,
+        import.rejected.len()
+    );
+    for rejected in &import.rejected {
+        println!("  Record {}: {}", rejected.record, rejected.reason);
+    }
+    Ok(())
+}
+
+/// Writes the table's items, optionally filtered, to a JSON Lines file.
+///
+/// Items are written a page at a time, so the whole table is never held in
+/// memory.
+async fn export_items(
+    ddb: &impl DynamoDbApi,
+    reader: &mut dyn LineReader,
+    table: &Table<'_>,
+    cancel: &CancellationToken,
+) -> Result<()> {
+    let path = prompt(
+        reader,
+        "Enter path for the export file",
+        Some(&format!("{}.jsonl", table.name())),
+    )?;
+    let format = prompt_optional(reader, "Enter --format (json/dynamodb)", Some("json"))?
+        .map(|format| format.parse())
+        .transpose()?
+        .unwrap_or(JsonFormat::Plain);
+    let filter_expression =
+        prompt_optional(reader, "Enter --filter expression", Some("price > :p"))?;
+    let (expression_attribute_names, expression_attribute_values) = match &filter_expression {
+        Some(_) => (
+            Some(get_expression_attribute_names(reader)?),
+            Some(get_expression_attribute_values(reader)?),
+        ),
+        None => (None, None),
+    };
+
+    let file =
+        std::fs::File::create(&path).map_err(|e| anyhow!("Failed to create '{}': {}", path, e))?;
+    let mut progress = ProgressLine::new("Exported");
+    let result = write_export(
+        ddb,
+        ScanParams {
+            table_name: table.name(),
+            filter_expression: filter_expression.as_deref(),
+            expression_attribute_names,
+            expression_attribute_values,
+            ..Default::default()
+        },
+        format,
+        io::BufWriter::new(file),
+        Some(&mut |p: &Progress| progress.update(p)),
+        cancel,
+    )
+    .await?;
+    progress.finish();
+    report_export(&result, &path);
+    Ok(())
+}
+
+/// Scans every page matching `params` and writes each item to `output` as
+/// one line of JSON, calling `on_progress`, if given, after every page.
+/// Returns the number of items written, which only covers the first pages
+/// if `cancel` was cancelled.
+pub(crate) async fn write_export(
+    ddb: &impl DynamoDbApi,
+    params: ScanParams<'_>,
+    format: JsonFormat,
+    mut output: impl Write,
+    on_progress: Option<&mut dyn FnMut(&Progress)>,
+    cancel: &CancellationToken,
+) -> Result<BulkResult<usize>> {
+    let result = ddb
+        .for_each_item_cancellable(params, on_progress, cancel, &mut |item| {
+            serde_json::to_writer(&mut output, &item.to_json(format))?;
+            output.write_all(b"\n")?;
+            Ok(ControlFlow::Continue(()))
+        })
+        .await?;
+    output.flush()?;
+    Ok(BulkResult {
+        value: result.value.items,
+        status: result.status,
+    })
+}
+
+/// Says how many items an export wrote, and whether it was cut short.
+fn report_export(result: &BulkResult<usize>, path: &str) {
+    match result.status {
+        BulkStatus::Complete => println!("Exported {} item(s) to '{}'", result.value, path),
+        BulkStatus::Cancelled => println!(
+            "Export cancelled; wrote the first {} item(s) to '{}'",
+            result.value, path
+        ),
+    }
+}
+
+/// Writes the table's items to a CSV file, one row per item.
+///
+/// Columns come from `--columns` when given, otherwise from the table schema,
+/// otherwise from every attribute name seen in the table.
+async fn export_csv(
+    ddb: &impl DynamoDbApi,
+    reader: &mut dyn LineReader,
+    table: &Table<'_>,
+    cancel: &CancellationToken,
+) -> Result<()> {
+    let path = prompt(
+        reader,
+        "Enter path for the CSV file",
+        Some(&format!("{}.csv", table.name())),
+    )?;
+    let columns = prompt_optional(
+        reader,
+        "Enter --columns to export, in order",
+        Some("name, price"),
+    )?
+    .map(|columns| {
+        columns
+            .split(',')
+            .map(str::trim)
+            .filter(|column| !column.is_empty())
+            .map(String::from)
+            .collect::<Vec<_>>()
+    });
+
+    let file =
+        std::fs::File::create(&path).map_err(|e| anyhow!("Failed to create '{}': {}", path, e))?;
+    let mut progress = ProgressLine::new("Exported");
+    let result = write_csv_export(
+        ddb,
+        table,
+        columns,
+        file,
+        Some(&mut |p: &Progress| progress.update(p)),
+        cancel,
+    )
+    .await?;
+    progress.finish();
+    report_export(&result, &path);
+    Ok(())
+}
+
+/// Scans the whole table and writes it to `output` as CSV, calling
+/// `on_progress`, if given, after every page. Returns the number of rows
+/// written, which only covers the first pages if `cancel` was cancelled.
+///
+/// With explicit `columns` or a table schema, rows are written a page at a
+/// time; otherwise the header depends on every item, so the table is read in
+/// full first.
+pub(crate) async fn write_csv_export(
+    ddb: &impl DynamoDbApi,
+    table: &Table<'_>,
+    columns: Option<Vec<String>>,
+    output: impl Write,
+    on_progress: Option<&mut dyn FnMut(&Progress)>,
+    cancel: &CancellationToken,
+) -> Result<BulkResult<usize>> {
+    let mut writer = csv::Writer::from_writer(output);
+    let params = ScanParams {
+        table_name: table.name(),
+        ..Default::default()
+    };
+
+    let result = match columns.or_else(|| table.schema().map(|_| csv_columns(table, &[]))) {
+        Some(columns) => {
+            writer.write_record(&columns)?;
+            let result = ddb
+                .for_each_item_cancellable(params, on_progress, cancel, &mut |item| {
+                    writer.write_record(csv_row(&item, &columns))?;
+                    Ok(ControlFlow::Continue(()))
+                })
+                .await?;
+            BulkResult {
+                value: result.value.items,
+                status: result.status,
+            }
+        }
+        None => {
+            let scan = ddb
+                .scan_all_cancellable(params, None, on_progress, cancel)
+                .await?;
+            let columns = csv_columns(table, &scan.value);
+            writer.write_record(&columns)?;
+            for item in &scan.value {
+                writer.write_record(csv_row(item, &columns))?;
+            }
+            BulkResult {
+                value: scan.value.len(),
+                status: scan.status,
+            }
+        }
+    };
+    writer.flush()?;
+    Ok(result)
+}
+
+/// Chooses CSV columns for `table`: the key attributes first, then the other
+/// schema fields, or, without a schema, every other attribute name found in
+/// `items`, alphabetically.
+pub(crate) fn csv_columns(table: &Table<'_>, items: &[Item]) -> Vec<String> {
+    let mut others: Vec<&str> = match table.schema() {
+        Some(schema) => schema.fields().keys().map(String::as_str).collect(),
+        None => items
+            .iter()
+            .flat_map(|item| item.attributes.keys().map(String::as_str))
+            .collect(),
+    };
+    others.retain(|name| !table.is_key_attribute(name));
+    others.sort_unstable();
+    others.dedup();
+
+    std::iter::once(table.partition_key())
+        .chain(table.sort_key())
+        .chain(others)
+        .map(String::from)
+        .collect()
+}
+
+/// Renders an item's values for `columns`, leaving missing attributes blank.
+///
+/// Strings and numbers are written as-is; maps, lists and sets as JSON.
+pub(crate) fn csv_row(item: &Item, columns: &[String]) -> Vec<String> {
+    columns
+        .iter()
+        .map(|column| match item.attributes.get(column) {
+            None | Some(AttributeValue::Null(_)) => String::new(),
+            Some(AttributeValue::S(s)) => s.clone(),
+            Some(AttributeValue::N(n)) => n.clone(),
+            Some(_) => match item.get_json(column) {
+                Some(serde_json::Value::String(s)) => s,
+                Some(value) => value.to_string(),
+                None => String::new(),
+            },
+        })
+        .collect()
+}
+
+/// Batch writes imported items, keeping a running count on one line.
+///
+/// Cancelling `cancel` stops before the next batch; the batches already
+/// written stay in the table.
+async fn write_imported_items(
+    ddb: &impl DynamoDbApi,
+    table: &Table<'_>,
+    items: Vec<Item>,
+    cancel: &CancellationToken,
+) -> Result<usize> {
+    let total = items.len();
+    println!("Importing {} item(s) into '{}'...", total, table.name());
+    let mut progress = ProgressLine::new("Written").with_total(total);
+    let result = ddb
+        .batch_put_items_cancellable(
+            table.name(),
+            items,
+            Some(&mut |p: &Progress| progress.update(p)),
+            cancel,
+        )
+        .await?;
+    progress.finish();
+    if result.status == BulkStatus::Cancelled {
+        println!(
+            "Import cancelled after {} of {} item(s).",
+            result.value, total
+        );
+    }
+    Ok(result.value)
+}
+
+/// Keeps a bulk operation's running totals on one line of stderr, so they
+/// never mix with items printed to stdout, e.g. with `--output json`.
+struct ProgressLine {
+    label: &'static str,
+    total: Option<usize>,
+    shown: bool,
+}
+
+impl ProgressLine {
+    fn new(label: &'static str) -> Self {
+        Self {
+            label,
+            total: None,
+            shown: false,
+        }
+    }
+
+    /// Shows the item count as "n of `total`".
+    fn with_total(mut self, total: usize) -> Self {
+        self.total = Some(total);
+        self
+    }
+
+    fn update(&mut self, progress: &Progress) {
+        let mut line = format!("\r{} {}", self.label, progress.items);
+        if let Some(total) = self.total {
+            let _ = write!(line, " of {total}");
+        }
+        let _ = write!(
+            line,
+            " item(s), {} page(s), {:.0} items/s",
+            progress.pages,
+            progress.items_per_sec()
+        );
+        if let Some(capacity) = progress.consumed_capacity {
+            let _ = write!(line, ", {capacity:.1} capacity units");
+        }
+        eprint!("{line}");
+        let _ = io::stderr().flush();
+        self.shown = true;
+    }
+
+    /// Ends the progress line, if anything was shown.
+    fn finish(self) {
+        if self.shown {
+            eprintln!();
+        }
+    }
+}
+
+/// Batch writes the rows of a CSV file to the table.
+///
+/// The header row names the attributes. Columns in the table schema are
+/// coerced to their declared type; other columns are imported as strings, or
+/// abort the import in strict mode. Rows that fail coercion are skipped and
+/// reported at the end, optionally to a file as well.
+async fn import_csv(
+    ddb: &impl DynamoDbApi,
+    reader: &mut dyn LineReader,
+    table: &Table<'_>,
+    cancel: &CancellationToken,
+) -> Result<()> {
+    let path = prompt(reader, "Enter path to CSV file", Some("items.csv"))?;
+    let strict = prompt_bool(
+        reader,
+        "Reject columns outside the table schema (--strict)?",
+        false,
+    )?;
+    let file =
+        std::fs::File::open(&path).map_err(|e| anyhow!("Failed to read '{}': {}", path, e))?;
+    let import = parse_csv_import(file, table, strict)?;
+
+    let written = write_imported_items(ddb, table, import.items, cancel).await?;
+    println!(
+        "Imported {} item(s), {} row error(s)",
+        written,
+        import.errors.len()
+    );
+    if import.errors.is_empty() {
+        return Ok(());
+    }
+
+    for error in &import.errors {
+        println!("  {}", error);
+    }
+    if let Some(report_path) = prompt_optional(
+        reader,
+        "Enter path to save the errors report",
+        Some("errors.csv"),
+    )? {
+        let mut writer = csv::Writer::from_path(&report_path)?;
+        writer.write_record(["row", "column", "reason"])?;
+        for error in &import.errors {
+            writer.write_record([
+                error.row.to_string().as_str(),
+                error.column.as_deref().unwrap_or_default(),
+                &error.reason,
+            ])?;
+        }
+        writer.flush()?;
+        println!("Errors report written to '{}'", report_path);
+    }
+    Ok(())
+}
+
+/// The items parsed from a CSV file and the rows that were skipped.
+#[derive(Debug)]
+pub(crate) struct CsvImport {
+    pub items: Vec<Item>,
+    pub errors: Vec<CsvRowError>,
+}
+
+/// Why a CSV row was skipped. `row` is the line number in the file, counting
+/// the header as line 1.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct CsvRowError {
+    pub row: u64,
+    pub column: Option<String>,
+    pub reason: String,
+}
+
+impl std::fmt::Display for CsvRowError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.column {
+            Some(column) => write!(f, "Row {}, column '{}': {}", self.row, column, self.reason),
+            None => write!(f, "Row {}: {}", self.row, self.reason),
+        }
+    }
+}
+
+/// Parses CSV rows into items for `table`.
+///
+/// Fails before reading any rows if the header is missing a key attribute,
+/// or, when `strict`, names a column outside the table schema. Empty cells
+/// are left out of the item.
+pub(crate) fn parse_csv_import(
+    input: impl io::Read,
+    table: &Table<'_>,
+    strict: bool,
+) -> Result<CsvImport> {
+    let mut reader = csv::Reader::from_reader(input);
+    let headers = reader
+        .headers()
+        .map_err(|e| anyhow!("Invalid CSV header: {}", e))?
+        .clone();
+
+    for key in std::iter::once(table.partition_key()).chain(table.sort_key()) {
+        if !headers.iter().any(|header| header == key) {
+            return Err(anyhow!("CSV has no column for key attribute '{}'", key));
+        }
+    }
+    let field_types: Vec<Option<&FieldType>> = headers
+        .iter()
+        .map(|header| {
+            table
+                .schema()
+                .and_then(|schema| schema.fields().get(header))
+        })
+        .collect();
+    if strict {
+        if let Some(unknown) = headers
+            .iter()
+            .zip(&field_types)
+            .find(|(header, field_type)| field_type.is_none() && !table.is_key_attribute(header))
+        {
+            return Err(anyhow!("Column '{}' is not in the table schema", unknown.0));
+        }
+    }
+
+    let mut import = CsvImport {
+        items: Vec::new(),
+        errors: Vec::new(),
+    };
+    for record in reader.records() {
+        let record = match record {
+            Ok(record) => record,
+            Err(e) => {
+                import.errors.push(CsvRowError {
+                    row: e.position().map_or(0, |position| position.line()),
+                    column: None,
+                    reason: e.to_string(),
+                });
+                continue;
+            }
+        };
+        let row = record.position().map_or(0, |position| position.line());
+
+        let mut item = Item::new();
+        let mut error = None;
+        for ((header, value), field_type) in headers.iter().zip(&record).zip(&field_types) {
+            if value.is_empty() {
+                if table.is_key_attribute(header) {
+                    error = Some((header, "key attribute is empty".to_string()));
+                    break;
+                }
+                continue;
+            }
+            let value = match coerce(field_type.unwrap_or(&FieldType::String), value) {
+                Ok(value) => value,
+                Err(e) => {
+                    error = Some((header, e.to_string()));
+                    break;
+                }
+            };
+            item.attributes.insert(header.to_string(), value);
+        }
+
+        match error {
+            Some((column, reason)) => import.errors.push(CsvRowError {
+                row,
+                column: Some(column.to_string()),
+                reason,
+            }),
+            None => import.items.push(item),
+        }
+    }
+    Ok(import)
+}
+
+/// The items parsed from an import file and the records that were skipped.
+#[derive(Debug)]
+pub(crate) struct ParsedImport {
+    pub items: Vec<Item>,
+    pub rejected: Vec<RejectedRecord>,
+}
+
+/// A record left out of an import, numbered from 1.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct RejectedRecord {
+    pub record: usize,
+    pub reason: String,
+}
+
+/// Parses a JSON array or JSON Lines file of items for `table`.
+///
+/// The format is detected from the first record. Fails only if the input
+/// isn't valid JSON; individual records that can't be converted or are
+/// rejected by [`Table::validate_item`] are collected in `rejected`.
+pub(crate) fn parse_json_import(contents: &str, table: &Table<'_>) -> Result<ParsedImport> {
+    let records: Vec<serde_json::Value> = if contents.trim_start().starts_with('[') {
+        serde_json::from_str(contents)
+    } else {
+        serde_json::Deserializer::from_str(contents)
+            .into_iter()
+            .collect()
+    }
+    .map_err(|e| anyhow!("Invalid JSON, nothing was imported: {}", e))?;
+    let format = records
+        .first()
+        .map(JsonFormat::detect)
+        .unwrap_or(JsonFormat::Plain);
+
+    let mut import = ParsedImport {
+        items: Vec::new(),
+        rejected: Vec::new(),
+    };
+    for (i, record) in records.into_iter().enumerate() {
+        let item = match format {
+            JsonFormat::Plain => Item::from_json(&record),
+            JsonFormat::DynamoDb => Item::from_dynamodb_json(record),
+        }
+        .and_then(|item| table.validate_item(&item).map(|_| item));
+        match item {
+            Ok(item) => import.items.push(item),
+            Err(e) => import.rejected.push(RejectedRecord {
+                record: i + 1,
+                reason: e.to_string(),
+            }),
+        }
+    }
+    Ok(import)
+}
+
+/// Renders a stream record as its event type and keys, followed by one line
+/// per changed attribute: `+` for added, `-` for removed and `~` for
+/// modified values. Key attributes and unchanged values are left out.
+pub(crate) fn render_change(record: &StreamRecord) -> String {
+    let mut key_names: Vec<_> = record.keys.attributes.keys().collect();
+    key_names.sort();
+    let keys = key_names
+        .iter()
+        .map(|name| format!("{}: {:?}", name, record.keys.attributes[*name]))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let mut rendered = format!("{} {{{}}}", record.event_type, keys);
+
+    let empty = HashMap::new();
+    let old = record
+        .old_image
+        .as_ref()
+        .map_or(&empty, |image| &image.attributes);
+    let new = record
+        .new_image
+        .as_ref()
+        .map_or(&empty, |image| &image.attributes);
+    let mut names: Vec<_> = old
+        .keys()
+        .chain(new.keys())
+        .filter(|name| !record.keys.attributes.contains_key(*name))
+        .collect();
+    names.sort();
+    names.dedup();
+
+    for name in names {
+        match (old.get(name), new.get(name)) {
+            (None, Some(value)) => rendered.push_str(&format!("\n  + {}: {:?}", name, value)),
+            (Some(value), None) => rendered.push_str(&format!("\n  - {}: {:?}", name, value)),
+            (Some(before), Some(after)) if before != after => {
+                rendered.push_str(&format!("\n  ~ {}: {:?} -> {:?}", name, before, after))
+            }
+            _ => {}
+        }
+    }
+    rendered
+}
+
+/// Prints items in the configured output format: a titled, column-aligned
+/// table, or one JSON object per line.
+fn print_items(title: &str, items: &[Item], key_columns: &[&str], output: OutputFormat) {
+    let json_format = match output {
+        OutputFormat::Table => {
+            println!("\n--- {} ---", title);
+            print!("{}", render_items(items, key_columns, MAX_COLUMN_WIDTH));
+            println!("{}", "-".repeat(title.len() + 8));
+            return;
+        }
+        OutputFormat::Json => JsonFormat::Plain,
+        OutputFormat::DynamoDb => JsonFormat::DynamoDb,
+    };
+    for item in items {
+        println!("{}", item.to_json(json_format));
+    }
+}
+
+/// The table's key attributes, partition key first.
+fn key_columns<'t>(table: &'t Table<'_>) -> Vec<&'t str> {
+    std::iter::once(table.partition_key())
+        .chain(table.sort_key())
+        .collect()
+}
+
+/// Renders items as a column-aligned text table.
+///
+/// There is one column per attribute name found in `items`: the
+/// `key_columns` first, then the rest alphabetically. Missing attributes are
+/// left blank, and values longer than `max_width` characters are cut short
+/// with an ellipsis.
+pub(crate) fn render_items(items: &[Item], key_columns: &[&str], max_width: usize) -> String {
+    if items.is_empty() {
+        return "(no items)\n".to_string();
+    }
+
+    let mut others: Vec<&str> = items
+        .iter()
+        .flat_map(|item| item.attributes.keys().map(String::as_str))
+        .filter(|name| !key_columns.contains(name))
+        .collect();
+    others.sort_unstable();
+    others.dedup();
+    let columns: Vec<&str> = key_columns
+        .iter()
+        .copied()
+        .filter(|key| items.iter().any(|item| item.attributes.contains_key(*key)))
+        .chain(others)
+        .collect();
+
+    let rows: Vec<Vec<String>> = items
+        .iter()
+        .map(|item| {
+            columns
+                .iter()
+                .map(|column| truncate(&display_value(item, column), max_width))
+                .collect()
+        })
+        .collect();
+    let widths: Vec<usize> = columns
+        .iter()
+        .enumerate()
+        .map(|(i, column)| {
+            rows.iter()
+                .map(|row| row[i].chars().count())
+                .chain([truncate(column, max_width).chars().count()])
+                .max()
+                .unwrap_or_default()
+        })
+        .collect();
+
+    let format_row = |cells: &[String]| {
+        let filled = cells
+            .iter()
+            .rposition(|cell| !cell.is_empty())
+            .map_or(0, |i| i + 1);
+        let line = cells[..filled]
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{:<width$}", cell, width = *width))
+            .collect::<Vec<_>>()
+            .join(" | ");
+        format!("{}\n", line.trim_end())
+    };
+    let header: Vec<String> = columns
+        .iter()
+        .map(|column| truncate(column, max_width))
+        .collect();
+    let separator = widths
+        .iter()
+        .map(|width| "-".repeat(*width))
+        .collect::<Vec<_>>()
+        .join("-+-");
+
+    let mut rendered = format_row(&header);
+    rendered.push_str(&separator);
+    rendered.push('\n');
+    for row in &rows {
+        rendered.push_str(&format_row(row));
+    }
+    rendered.push_str(&format!(
+        "({} item{})\n",
+        items.len(),
+        if items.len() == 1 { "" } else { "s" }
+    ));
+    rendered
+}
+
+/// Formats an item's attribute for a table cell, on a single line. Missing
+/// attributes are blank.
+fn display_value(item: &Item, column: &str) -> String {
+    let Some(value) = item.attributes.get(column) else {
+        return String::new();
+    };
+    let text = match value {
+        AttributeValue::S(s) => s.clone(),
+        AttributeValue::N(n) => n.clone(),
+        AttributeValue::Bool(b) => b.to_string(),
+        AttributeValue::Null(_) => "NULL".to_string(),
+        AttributeValue::B(b) => format!("<{} bytes>", b.as_ref().len()),
+        AttributeValue::Bs(set) => format!("<{} binary values>", set.len()),
+        AttributeValue::Ss(set) => format!("{{{}}}", set.join(", ")),
+        AttributeValue::Ns(set) => format!("{{{}}}", set.join(", ")),
+        _ => item
+            .get_json(column)
+            .map(|json| json.to_string())
+            .unwrap_or_default(),
+    };
+    text.replace('\n', "\\n")
 }
 
-fn prompt_optional(message: &str, example: Option<&str>) -> Result<Option<String>> {
-    let input = prompt(message, example)?;
+/// Cuts `text` to at most `max_width` characters, ending in `…` if shortened.
+fn truncate(text: &str, max_width: usize) -> String {
+    if text.chars().count() <= max_width {
+        return text.to_string();
+    }
+    let mut truncated: String = text.chars().take(max_width.saturating_sub(1)).collect();
+    truncated.push('…');
+    truncated
+}
+
+/// Prints how many items a query returned against how many it read.
+fn print_query_counts(result: &QueryResult) {
+    println!(
+        "Returned {} of {} scanned items",
+        result.count, result.scanned_count
+    );
+    if let Some(capacity) = result.consumed_capacity {
+        println!("Consumed {} read capacity units", capacity);
+    }
+}
+
+fn prompt_optional(
+    reader: &mut dyn LineReader,
+    message: &str,
+    example: Option<&str>,
+) -> Result<Option<String>> {
+    let input = prompt(reader, message, example)?;
     Ok(if input.is_empty() { None } else { Some(input) })
 }
 
-fn prompt_bool(message: &str, default: bool) -> Result<bool> {
+/// Prompts for a comma-separated list of attributes to project. A blank
+/// answer, or one with no names in it, means every attribute.
+///
+/// The names are aliased, so reserved words like `name` or `status` work as-is.
+fn prompt_projection(reader: &mut dyn LineReader) -> Result<Option<ProjectionExpression>> {
+    let input = prompt_optional(
+        reader,
+        "Enter attributes to project",
+        Some("name, status, price"),
+    )?;
+    Ok(input.and_then(|input| {
+        let attributes = attribute_list(&input);
+        ProjectionExpression::new(&attributes.iter().map(String::as_str).collect::<Vec<_>>())
+    }))
+}
+
+/// Splits a comma-separated list of attribute names.
+fn attribute_list(input: &str) -> Vec<String> {
+    input
+        .split(',')
+        .map(str::trim)
+        .filter(|attribute| !attribute.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+/// Prompts for one of an SDK enum's values, matched case-insensitively.
+///
+/// Returns `None` when the input is left empty.
+fn prompt_choice<T: for<'s> From<&'s str>>(
+    reader: &mut dyn LineReader,
+    message: &str,
+    values: &[&str],
+) -> Result<Option<T>> {
+    let Some(input) =
+        prompt_optional(reader, &format!("{} ({})", message, values.join("/")), None)?
+    else {
+        return Ok(None);
+    };
+    values
+        .iter()
+        .find(|value| value.eq_ignore_ascii_case(input.trim()))
+        .map(|value| T::from(value))
+        .map(Some)
+        .ok_or_else(|| {
+            anyhow!(
+                "Unsupported value '{}', expected one of: {}",
+                input,
+                values.join(", ")
+            )
+        })
+}
+
+fn prompt_bool(reader: &mut dyn LineReader, message: &str, default: bool) -> Result<bool> {
     let input = prompt(
+        reader,
         &format!("{} (y/n)", message),
         Some(if default { "y" } else { "n" }),
     )?;
     Ok(input.to_lowercase().starts_with('y') || (input.is_empty() && default))
 }
 
+/// Creates an on-demand backup of the table and prints its ARN.
+async fn backup_table(
+    ddb: &impl DynamoDbApi,
+    reader: &mut dyn LineReader,
+    table: &Table<'_>,
+) -> Result<()> {
+    let default_name = format!(
+        "{}-{}",
+        table.name(),
+        DateTime::from(SystemTime::now()).secs()
+    );
+    let backup_name = prompt(reader, "Enter backup name", Some(&default_name))?;
+    let backup_name = if backup_name.is_empty() {
+        default_name
+    } else {
+        backup_name
+    };
+
+    let arn = ddb.create_backup(table.name(), &backup_name).await?;
+    println!("Backup '{}' created: {}", backup_name, arn);
+    Ok(())
+}
+
+/// Restores one of the table's backups into a new table, waiting until the
+/// new table is active.
+async fn restore_table(
+    ddb: &impl DynamoDbApi,
+    reader: &mut dyn LineReader,
+    table: &Table<'_>,
+) -> Result<()> {
+    let backups = ddb.list_backups(table.name()).await?;
+    if backups.is_empty() {
+        println!("No backups found for table '{}'.", table.name());
+        return Ok(());
+    }
+
+    println!("\n--- Backups of {} ---", table.name());
+    for (i, backup) in backups.iter().enumerate() {
+        println!(
+            "{}: {} ({}, created {})",
+            i + 1,
+            backup.name,
+            backup.status,
+            DateTime::from(backup.created_at).fmt(DateTimeFormat::DateTime)?
+        );
+    }
+
+    let choice = prompt(reader, "Enter backup number", Some("1"))?;
+    let backup = choice
+        .parse::<usize>()
+        .ok()
+        .and_then(|n| n.checked_sub(1))
+        .and_then(|i| backups.get(i))
+        .ok_or_else(|| anyhow!("Invalid backup number '{}'", choice))?;
+    let new_table_name = prompt(
+        reader,
+        "Enter name for the restored table",
+        Some(&format!("{}-restored", table.name())),
+    )?;
+    if new_table_name.is_empty() {
+        return Err(anyhow!("A table name is required"));
+    }
+
+    println!("Restoring '{}' into '{}'...", backup.name, new_table_name);
+    ddb.restore_table_from_backup_and_wait(&backup.arn, &new_table_name, TABLE_RESTORE_TIMEOUT)
+        .await?;
+    println!("Table '{}' has been restored.", new_table_name);
+    Ok(())
+}
+
 /// Deletes the DynamoDB table.
 ///
 /// This function prompts the user for confirmation before deleting the table.
@@ -667,13 +3082,20 @@ fn prompt_bool(message: &str, default: bool) -> Result<bool> {
 /// # Arguments
 ///
 /// * `ddb` - A reference to the DynamoDB client
+/// * `reader` - Where prompts read their input from
 /// * `table` - A reference to the Table struct containing table information
 ///
 /// # Returns
 ///
-/// Returns `Ok(())` if the table is deleted successfully, or an error if the operation fails.
-async fn delete_table(ddb: &DynamoDb, table: &Table<'_>) -> Result<()> {
+/// Returns `Ok(())` once the table is fully deleted (or was already gone), or an error if the
+/// operation fails.
+async fn delete_table(
+    ddb: &impl DynamoDbApi,
+    reader: &mut dyn LineReader,
+    table: &Table<'_>,
+) -> Result<()> {
     let confirmation = prompt(
+        reader,
         &format!(
             "Are you sure you want to delete the table '{}'? This action cannot be undone. (y/n): ",
             table.name()
@@ -682,11 +3104,229 @@ async fn delete_table(ddb: &DynamoDb, table: &Table<'_>) -> Result<()> {
     )?;
 
     if confirmation.to_lowercase() == "y" {
-        ddb.delete_table(table.name()).await?;
-        println!("Table '{}' has been deleted.", table.name());
+        println!("Deleting table '{}'...", table.name());
+        if ddb
+            .delete_table_if_exists_and_wait(table.name(), TABLE_DELETE_TIMEOUT)
+            .await?
+        {
+            println!("Table '{}' has been deleted.", table.name());
+        } else {
+            println!("Table '{}' does not exist.", table.name());
+        }
     } else {
         println!("Table deletion cancelled.");
     }
 
     Ok(())
 }
+
+/// Switches to an existing table, named by the command's argument or
+/// prompted for.
+///
+/// The table's keys are loaded with `DescribeTable`; a schema for the other
+/// attributes can optionally be inferred from a sample of its items.
+///
+/// # Returns
+///
+/// Returns the table, which becomes the session's active table, or `None`
+/// if there's no table by that name, leaving the active table as it was.
+async fn use_table(
+    ddb: &impl DynamoDbApi,
+    reader: &mut dyn LineReader,
+    table_name: Option<&str>,
+) -> Result<Option<Table<'static>>> {
+    let table_name = match table_name {
+        Some(table_name) => table_name.to_string(),
+        None => prompt(reader, "Enter table name", Some("products"))?,
+    };
+    let sample_size = if prompt_bool(reader, "Infer a schema from a sample of items?", false)? {
+        Some(SCHEMA_SAMPLE_SIZE)
+    } else {
+        None
+    };
+
+    let table = match ddb.load_table(&table_name, sample_size).await {
+        Ok(table) => table,
+        Err(e) if e.downcast_ref::<TableNotFound>().is_some() => {
+            println!("{}. List the tables with 'tables'.", e);
+            return Ok(None);
+        }
+        Err(e) => return Err(e),
+    };
+    println!(
+        "Using table '{}' (partition key '{}'{})",
+        table.name(),
+        table.partition_key(),
+        table
+            .sort_key()
+            .map(|key| format!(", sort key '{}'", key))
+            .unwrap_or_default()
+    );
+    Ok(Some(table))
+}
+
+/// Prompts for a table definition, creates the table if it doesn't exist and
+/// waits for it to become active.
+///
+/// # Returns
+///
+/// Returns the new table, which becomes the session's active table.
+async fn create_table(
+    ddb: &impl DynamoDbApi,
+    reader: &mut dyn LineReader,
+) -> Result<Table<'static>> {
+    let table = read_table_definition(|message, example| prompt(reader, message, example))?;
+
+    println!("Creating table '{}'...", table.name());
+    if ddb.create_table_if_not_exists(&table).await?.is_none() {
+        println!("Table '{}' already exists; using it.", table.name());
+    }
+    ddb.wait_for_table_active(table.name(), TABLE_CREATE_TIMEOUT)
+        .await?;
+    println!("Table '{}' is active.", table.name());
+    Ok(table)
+}
+
+/// Reads a table's name, keys, billing mode and schema from `read`, which
+/// is called like [`prompt`].
+///
+/// Key types are recorded in the table's schema, along with any extra
+/// fields entered, since that is where table creation looks them up.
+pub(crate) fn read_table_definition(
+    mut read: impl FnMut(&str, Option<&str>) -> Result<String>,
+) -> Result<Table<'static>> {
+    let name = read_required(&mut read, "Enter table name", "products")?;
+    let partition_key = read_required(
+        &mut read,
+        "Enter partition key name",
+        CATEGORY_PARTITION_KEY,
+    )?;
+    let mut schema = Schema::new().add_field(
+        partition_key.as_str(),
+        key_type(&read_required(
+            &mut read,
+            "Enter partition key type (S for string, N for number)",
+            "S",
+        )?)?,
+    );
+
+    let sort_key = read(
+        "Enter sort key name (or press Enter for none)",
+        Some(PRODUCT_NAME_SORT_KEY),
+    )?;
+    let sort_key = if sort_key.is_empty() {
+        None
+    } else {
+        let sort_key_type = read_required(
+            &mut read,
+            "Enter sort key type (S for string, N for number)",
+            "S",
+        )?;
+        schema = schema.add_field(sort_key.as_str(), key_type(&sort_key_type)?);
+        Some(sort_key)
+    };
+
+    let billing_mode = read(
+        "Enter billing mode (on-demand/provisioned)",
+        Some("on-demand"),
+    )?;
+    let capacity = match billing_mode.to_lowercase().as_str() {
+        "" | "on-demand" | "ondemand" => Capacity::OnDemand,
+        "provisioned" => Capacity::Provisioned {
+            read_capacity_units: capacity_units(&read_required(
+                &mut read,
+                "Enter read capacity units",
+                "5",
+            )?)?,
+            write_capacity_units: capacity_units(&read_required(
+                &mut read,
+                "Enter write capacity units",
+                "5",
+            )?)?,
+        },
+        mode => {
+            return Err(anyhow!(
+                "Unsupported billing mode '{}', expected on-demand or provisioned",
+                mode
+            ))
+        }
+    };
+
+    loop {
+        let field = read(
+            "Enter schema field name (or press Enter to finish)",
+            Some(PRICE_ATTRIBUTE),
+        )?;
+        if field.is_empty() {
+            break;
+        }
+        let field_type = field_type(&read_required(
+            &mut read,
+            &format!(
+                "Enter type of '{}' (S, N, BOOL, SS, NS, STRING_LIST, L or M)",
+                field
+            ),
+            "N",
+        )?)?;
+        schema = schema.add_field(field, field_type);
+    }
+
+    Ok(Table::owned(name, partition_key, sort_key)
+        .with_schema(schema)
+        .with_capacity(capacity))
+}
+
+/// Calls `read` like [`prompt`], failing if the input is left empty.
+fn read_required(
+    read: &mut impl FnMut(&str, Option<&str>) -> Result<String>,
+    message: &str,
+    example: &str,
+) -> Result<String> {
+    let input = read(message, Some(example))?;
+    if input.is_empty() {
+        Err(anyhow!(
+            "{} is required",
+            message.trim_start_matches("Enter ")
+        ))
+    } else {
+        Ok(input)
+    }
+}
+
+/// Parses a schema field type: `S`/`string`, `N`/`number`, `BOOL`/`boolean`,
+/// `SS`/`string_set`, `NS`/`number_set`, `string_list`, `L`/`list` or
+/// `M`/`map`.
+pub(crate) fn field_type(input: &str) -> Result<FieldType> {
+    match input.to_lowercase().as_str() {
+        "s" | "string" => Ok(FieldType::String),
+        "n" | "number" => Ok(FieldType::Number),
+        "bool" | "boolean" => Ok(FieldType::Boolean),
+        "ss" | "string_set" => Ok(FieldType::StringSet),
+        "ns" | "number_set" => Ok(FieldType::NumberSet),
+        "string_list" => Ok(FieldType::StringList),
+        "l" | "list" => Ok(FieldType::List),
+        "m" | "map" => Ok(FieldType::Map),
+        _ => Err(anyhow!(
+            "Unsupported field type '{}', expected S, N, BOOL, SS, NS, STRING_LIST, L or M",
+            input
+        )),
+    }
+}
+
+/// Parses the type of a key attribute, which must be a string or number.
+fn key_type(input: &str) -> Result<FieldType> {
+    match field_type(input)? {
+        key_type @ (FieldType::String | FieldType::Number) => Ok(key_type),
+        other => Err(anyhow!("Key attributes must be S or N, not {:?}", other)),
+    }
+}
+
+fn capacity_units(input: &str) -> Result<i64> {
+    match input.parse::<i64>() {
+        Ok(units) if units > 0 => Ok(units),
+        _ => Err(anyhow!(
+            "Invalid capacity units '{}', expected a positive whole number",
+            input
+        )),
+    }
+}
@@ -0,0 +1,565 @@
+//! Startup settings for the CLI, read from a `ddb.toml` file, environment
+//! variables and command-line flags.
+//!
+//! Each setting is resolved on its own, taking the first of: a flag, an
+//! environment variable, the config file, the built-in default. The file is
+//! the one named by `--config`, else `./ddb.toml`, else
+//! `~/.config/ddb.toml`.
+//!
+//! An `endpoint_url` on `localhost` is taken to be DynamoDB Local, which is
+//! connected to with dummy credentials, so no AWS settings are needed.
+//! Otherwise a `region` or `profile` replaces `AWS_REGION` and `AWS_PROFILE`
+//! for this client, and with a profile nothing else is read from the
+//! environment.
+//!
+//! # Example
+//!
+//! ```toml
+//! [table]
+//! name = "products"
+//! partition_key = "category"
+//! sort_key = "product_name"
+//! prefix = "dev-"
+//!
+//! [schema]
+//! category = "string"
+//! product_name = "string"
+//! price = "number"
+//!
+//! [connection]
+//! endpoint_url = "http://localhost:8000"
+//! # region = "eu-west-1"
+//! # profile = "staging"
+//!
+//! [output]
+//! format = "table"
+//! ```
+
+use anyhow::{anyhow, Context, Result};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use crate::command_line::field_type;
+use crate::constants::{
+    CATEGORY_PARTITION_KEY, PRICE_ATTRIBUTE, PRODUCT_NAME_SORT_KEY, TABLE_NAME,
+};
+use crate::dynamodb::{FieldType, Schema, Table};
+
+/// The config file looked for in the working directory, then in
+/// `~/.config`.
+const CONFIG_FILE: &str = "ddb.toml";
+
+const TABLE_ENV: &str = "DDB_TABLE";
+const PARTITION_KEY_ENV: &str = "DDB_PARTITION_KEY";
+const SORT_KEY_ENV: &str = "DDB_SORT_KEY";
+const TABLE_PREFIX_ENV: &str = "DDB_TABLE_PREFIX";
+const OUTPUT_ENV: &str = "DDB_OUTPUT";
+/// The SDK's own variable, so an endpoint set for other AWS tools applies
+/// here too.
+const ENDPOINT_URL_ENV: &str = "AWS_ENDPOINT_URL";
+
+/// How the CLI prints items returned by gets, queries and scans.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputFormat {
+    /// An aligned table with the key columns first.
+    #[default]
+    Table,
+    /// One plain JSON object per line.
+    Json,
+    /// One DynamoDB JSON object per line.
+    DynamoDb,
+}
+
+impl FromStr for OutputFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "table" => Ok(Self::Table),
+            "json" => Ok(Self::Json),
+            "dynamodb" | "dynamodb-json" => Ok(Self::DynamoDb),
+            _ => Err(anyhow!(
+                "Unknown output format '{s}', expected table, json or dynamodb"
+            )),
+        }
+    }
+}
+
+impl fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Table => "table",
+            Self::Json => "json",
+            Self::DynamoDb => "dynamodb",
+        })
+    }
+}
+
+/// Where a setting's value came from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Source {
+    Default,
+    File(PathBuf),
+    Env(&'static str),
+    Flag(&'static str),
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Default => f.write_str("default"),
+            Self::File(path) => write!(f, "file {}", path.display()),
+            Self::Env(name) => write!(f, "env {name}"),
+            Self::Flag(name) => write!(f, "flag {name}"),
+        }
+    }
+}
+
+/// A resolved setting and where it came from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Setting<T> {
+    pub value: T,
+    pub source: Source,
+}
+
+impl<T> Setting<T> {
+    fn default(value: T) -> Self {
+        Self {
+            value,
+            source: Source::Default,
+        }
+    }
+
+    fn map<U>(self, f: impl FnOnce(T) -> U) -> Setting<U> {
+        Setting {
+            value: f(self.value),
+            source: self.source,
+        }
+    }
+}
+
+/// The CLI's effective settings.
+///
+/// An empty sort key, from any source, means the table has none.
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub table_name: Setting<String>,
+    pub partition_key: Setting<String>,
+    pub sort_key: Setting<Option<String>>,
+    /// Prepended to `table_name`, e.g. to keep `dev-` and `prod-` tables
+    /// apart.
+    pub table_prefix: Setting<Option<String>>,
+    pub schema: Setting<Schema>,
+    pub endpoint_url: Setting<Option<String>>,
+    /// The AWS region; without one the SDK's usual lookup applies.
+    pub region: Setting<Option<String>>,
+    /// The shared config profile to take credentials and region from.
+    pub profile: Setting<Option<String>>,
+    pub output_format: Setting<OutputFormat>,
+    /// Whether requests ask for the capacity they consume, which commands
+    /// then report; `--no-capacity` turns it off.
+    pub capacity: Setting<bool>,
+}
+
+impl Default for Config {
+    /// The demo products table, printed as a table, at the SDK's default
+    /// endpoint.
+    fn default() -> Self {
+        Self {
+            table_name: Setting::default(TABLE_NAME.to_string()),
+            partition_key: Setting::default(CATEGORY_PARTITION_KEY.to_string()),
+            sort_key: Setting::default(Some(PRODUCT_NAME_SORT_KEY.to_string())),
+            table_prefix: Setting::default(None),
+            schema: Setting::default(
+                Schema::new()
+                    .add_field(CATEGORY_PARTITION_KEY, FieldType::String)
+                    .add_field(PRODUCT_NAME_SORT_KEY, FieldType::String)
+                    .add_field(PRICE_ATTRIBUTE, FieldType::Number),
+            ),
+            endpoint_url: Setting::default(None),
+            region: Setting::default(None),
+            profile: Setting::default(None),
+            output_format: Setting::default(OutputFormat::Table),
+            capacity: Setting::default(true),
+        }
+    }
+}
+
+impl Config {
+    /// Loads the configuration for this process from its parsed flags,
+    /// environment and config file.
+    pub fn load(flags: &Flags) -> Result<Self> {
+        let file = match &flags.config {
+            Some(path) => Some(ConfigFile::read(path)?),
+            None => find_config_file()
+                .map(|path| ConfigFile::read(&path))
+                .transpose()?,
+        };
+        Self::resolve(file.as_ref(), |name| std::env::var(name).ok(), flags)
+    }
+
+    /// Layers `flags` over the environment (read through `env`) over `file`
+    /// over the defaults.
+    pub(crate) fn resolve(
+        file: Option<&ConfigFile>,
+        env: impl Fn(&str) -> Option<String>,
+        flags: &Flags,
+    ) -> Result<Self> {
+        let mut config = Self::default();
+        if let Some(file) = file {
+            file.apply(&mut config)?;
+        }
+
+        let env = |name: &'static str| {
+            env(name).map(|value| Setting {
+                value,
+                source: Source::Env(name),
+            })
+        };
+        let flag = |name: &'static str, value: &Option<String>| {
+            value.clone().map(|value| Setting {
+                value,
+                source: Source::Flag(name),
+            })
+        };
+        if let Some(setting) = flag("--table", &flags.table).or_else(|| env(TABLE_ENV)) {
+            config.table_name = setting;
+        }
+        if let Some(setting) =
+            flag("--partition-key", &flags.partition_key).or_else(|| env(PARTITION_KEY_ENV))
+        {
+            config.partition_key = setting;
+        }
+        if let Some(setting) = flag("--sort-key", &flags.sort_key).or_else(|| env(SORT_KEY_ENV)) {
+            config.sort_key = setting.map(non_empty);
+        }
+        if let Some(setting) =
+            flag("--table-prefix", &flags.table_prefix).or_else(|| env(TABLE_PREFIX_ENV))
+        {
+            config.table_prefix = setting.map(non_empty);
+        }
+        if let Some(setting) =
+            flag("--endpoint-url", &flags.endpoint_url).or_else(|| env(ENDPOINT_URL_ENV))
+        {
+            config.endpoint_url = setting.map(non_empty);
+        }
+        if let Some(setting) = flag("--region", &flags.region) {
+            config.region = setting.map(non_empty);
+        }
+        if let Some(setting) = flag("--profile", &flags.profile) {
+            config.profile = setting.map(non_empty);
+        }
+        if let Some(setting) = flag("--output", &flags.output).or_else(|| env(OUTPUT_ENV)) {
+            config.output_format = Setting {
+                value: setting
+                    .value
+                    .parse()
+                    .with_context(|| format!("Invalid output format from {}", setting.source))?,
+                source: setting.source,
+            };
+        }
+        if flags.no_capacity {
+            config.capacity = Setting {
+                value: false,
+                source: Source::Flag("--no-capacity"),
+            };
+        }
+        Ok(config)
+    }
+
+    /// The full name of the default table, with the prefix applied.
+    pub fn full_table_name(&self) -> String {
+        format!(
+            "{}{}",
+            self.table_prefix.value.as_deref().unwrap_or_default(),
+            self.table_name.value
+        )
+    }
+
+    /// The default table described by the configuration.
+    pub fn table(&self) -> Table<'static> {
+        Table::owned(
+            self.full_table_name(),
+            self.partition_key.value.clone(),
+            self.sort_key.value.clone(),
+        )
+        .with_schema(self.schema.value.clone())
+    }
+}
+
+fn non_empty(value: String) -> Option<String> {
+    Some(value).filter(|value| !value.is_empty())
+}
+
+impl fmt::Display for Config {
+    /// One `name = value (source)` line per setting, as shown by the
+    /// `config` command.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn optional(value: &Option<String>) -> String {
+            value
+                .as_deref()
+                .map_or_else(|| "(none)".to_string(), |value| format!("{value:?}"))
+        }
+
+        let mut fields: Vec<_> = self.schema.value.fields().iter().collect();
+        fields.sort_by_key(|(name, _)| name.as_str());
+        let schema = fields
+            .iter()
+            .map(|(name, field_type)| format!("{name}: {field_type:?}"))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        writeln!(
+            f,
+            "table = {:?} ({})",
+            self.table_name.value, self.table_name.source
+        )?;
+        writeln!(
+            f,
+            "table_prefix = {} ({})",
+            optional(&self.table_prefix.value),
+            self.table_prefix.source
+        )?;
+        writeln!(
+            f,
+            "partition_key = {:?} ({})",
+            self.partition_key.value, self.partition_key.source
+        )?;
+        writeln!(
+            f,
+            "sort_key = {} ({})",
+            optional(&self.sort_key.value),
+            self.sort_key.source
+        )?;
+        writeln!(f, "schema = {{{}}} ({})", schema, self.schema.source)?;
+        writeln!(
+            f,
+            "endpoint_url = {} ({})",
+            optional(&self.endpoint_url.value),
+            self.endpoint_url.source
+        )?;
+        writeln!(
+            f,
+            "region = {} ({})",
+            optional(&self.region.value),
+            self.region.source
+        )?;
+        writeln!(
+            f,
+            "profile = {} ({})",
+            optional(&self.profile.value),
+            self.profile.source
+        )?;
+        writeln!(
+            f,
+            "output = {} ({})",
+            self.output_format.value, self.output_format.source
+        )?;
+        writeln!(
+            f,
+            "capacity = {} ({})",
+            self.capacity.value, self.capacity.source
+        )
+    }
+}
+
+/// The command-line flags that override configured settings.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Flags {
+    pub config: Option<PathBuf>,
+    pub table: Option<String>,
+    pub partition_key: Option<String>,
+    pub sort_key: Option<String>,
+    pub table_prefix: Option<String>,
+    pub endpoint_url: Option<String>,
+    pub region: Option<String>,
+    pub profile: Option<String>,
+    pub output: Option<String>,
+    /// Set by `--no-capacity`.
+    pub no_capacity: bool,
+    /// How many times `-v`/`--verbose` was given; see
+    /// `logging::init_with_verbosity`.
+    pub verbose: u8,
+}
+
+impl Flags {
+    /// Parses `--flag value` and `--flag=value` arguments, `--no-capacity`,
+    /// and `-v` flags, which stack (`-vv` or `-v -v`).
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self> {
+        let mut flags = Self::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let (name, inline_value) = match arg.split_once('=') {
+                Some((name, value)) => (name.to_string(), Some(value.to_string())),
+                None => (arg, None),
+            };
+            let verbosity = match name.strip_prefix('-') {
+                Some("-verbose") => 1,
+                Some(vs) if !vs.is_empty() && vs.bytes().all(|b| b == b'v') => vs.len(),
+                _ => 0,
+            };
+            if verbosity > 0 && inline_value.is_none() {
+                flags.verbose = flags
+                    .verbose
+                    .saturating_add(u8::try_from(verbosity).unwrap_or(u8::MAX));
+                continue;
+            }
+            if name == "--no-capacity" && inline_value.is_none() {
+                flags.no_capacity = true;
+                continue;
+            }
+            let slot = match name.as_str() {
+                "--table" => &mut flags.table,
+                "--partition-key" => &mut flags.partition_key,
+                "--sort-key" => &mut flags.sort_key,
+                "--table-prefix" => &mut flags.table_prefix,
+                "--endpoint-url" => &mut flags.endpoint_url,
+                "--region" => &mut flags.region,
+                "--profile" => &mut flags.profile,
+                "--output" => &mut flags.output,
+                "--config" => {
+                    let value = inline_value
+                        .or_else(|| args.next())
+                        .ok_or_else(|| anyhow!("Missing value for --config"))?;
+                    flags.config = Some(PathBuf::from(value));
+                    continue;
+                }
+                _ => return Err(anyhow!("Unknown argument '{name}'")),
+            };
+            let value = inline_value
+                .or_else(|| args.next())
+                .ok_or_else(|| anyhow!("Missing value for {name}"))?;
+            *slot = Some(value);
+        }
+        Ok(flags)
+    }
+}
+
+/// The contents of a `ddb.toml` file.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigFile {
+    #[serde(skip)]
+    path: PathBuf,
+    #[serde(default)]
+    table: TableSection,
+    /// Field names to `string` or `number`.
+    #[serde(default)]
+    schema: Option<BTreeMap<String, String>>,
+    #[serde(default)]
+    connection: ConnectionSection,
+    #[serde(default)]
+    output: OutputSection,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TableSection {
+    name: Option<String>,
+    partition_key: Option<String>,
+    sort_key: Option<String>,
+    prefix: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ConnectionSection {
+    endpoint_url: Option<String>,
+    region: Option<String>,
+    profile: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct OutputSection {
+    format: Option<String>,
+}
+
+impl ConfigFile {
+    /// Reads and parses the config file at `path`.
+    pub fn read(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config file '{}'", path.display()))?;
+        Self::parse(&contents, path)
+    }
+
+    /// Parses `contents` as the config file at `path`. Errors name the file
+    /// and the offending line.
+    pub fn parse(contents: &str, path: &Path) -> Result<Self> {
+        let mut file: Self = toml::from_str(contents)
+            .map_err(|e| anyhow!("Invalid config file '{}': {}", path.display(), e))?;
+        file.path = path.to_path_buf();
+        Ok(file)
+    }
+
+    /// Sets every value the file gives on `config`.
+    fn apply(&self, config: &mut Config) -> Result<()> {
+        let setting = |value: &String| Setting {
+            value: value.clone(),
+            source: Source::File(self.path.clone()),
+        };
+
+        if let Some(name) = &self.table.name {
+            config.table_name = setting(name);
+        }
+        if let Some(key) = &self.table.partition_key {
+            config.partition_key = setting(key);
+        }
+        if let Some(key) = &self.table.sort_key {
+            config.sort_key = setting(key).map(non_empty);
+        }
+        if let Some(prefix) = &self.table.prefix {
+            config.table_prefix = setting(prefix).map(non_empty);
+        }
+        if let Some(url) = &self.connection.endpoint_url {
+            config.endpoint_url = setting(url).map(non_empty);
+        }
+        if let Some(region) = &self.connection.region {
+            config.region = setting(region).map(non_empty);
+        }
+        if let Some(profile) = &self.connection.profile {
+            config.profile = setting(profile).map(non_empty);
+        }
+        if let Some(format) = &self.output.format {
+            config.output_format = Setting {
+                value: format
+                    .parse()
+                    .with_context(|| format!("In config file '{}'", self.path.display()))?,
+                source: Source::File(self.path.clone()),
+            };
+        }
+        if let Some(fields) = &self.schema {
+            let schema = fields
+                .iter()
+                .try_fold(Schema::new(), |schema, (name, kind)| {
+                    let kind = field_type(kind).with_context(|| {
+                        format!(
+                            "Invalid type for schema field '{}' in config file '{}'",
+                            name,
+                            self.path.display()
+                        )
+                    })?;
+                    Ok::<_, anyhow::Error>(schema.add_field(name, kind))
+                })?;
+            config.schema = Setting {
+                value: schema,
+                source: Source::File(self.path.clone()),
+            };
+        }
+        Ok(())
+    }
+}
+
+/// `./ddb.toml` if it exists, else `~/.config/ddb.toml` if that does.
+fn find_config_file() -> Option<PathBuf> {
+    let local = PathBuf::from(CONFIG_FILE);
+    if local.is_file() {
+        return Some(local);
+    }
+    std::env::var_os("HOME")
+        .map(|home| PathBuf::from(home).join(".config").join(CONFIG_FILE))
+        .filter(|path| path.is_file())
+}
@@ -1,4 +1,16 @@
+//! The default table layout, shared by the CLI's config, its prompts and the
+//! tests.
+//!
+//! These are only defaults: [`Config`](crate::config::Config) lets each
+//! name be overridden by `ddb.toml`, a `DDB_*` environment variable or a
+//! flag.
+
+/// The default table, overridden by `DDB_TABLE` or `--table`.
 pub const TABLE_NAME: &str = "testing-products";
+/// The default partition key, overridden by `DDB_PARTITION_KEY` or
+/// `--partition-key`.
 pub const CATEGORY_PARTITION_KEY: &str = "category";
+/// The default sort key, overridden by `DDB_SORT_KEY` or `--sort-key`.
 pub const PRODUCT_NAME_SORT_KEY: &str = "product_name";
+/// The numeric attribute the default schema and examples use.
 pub const PRICE_ATTRIBUTE: &str = "price";
@@ -0,0 +1,390 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use aws_sdk_dynamodb::operation::create_table::CreateTableOutput;
+use aws_sdk_dynamodb::types::{AttributeValue, StreamViewType};
+use std::collections::HashMap;
+use std::ops::ControlFlow;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+
+use crate::dynamodb::{
+    AuthInfo, BackupArn, BackupInfo, BulkResult, CheckpointStore, DynamoDb, FilterExpression,
+    GetItemOptions, Item, Metrics, MetricsSnapshot, Progress, QueryFlexibleParams, QueryResult,
+    ScanParams, ScanSummary, SortKeyValue, Statement, StatementResult, StreamRecord, Table,
+    TableInfo, UpdateBuilder,
+};
+
+/// The operations the CLI performs against DynamoDB.
+///
+/// [`DynamoDb`] implements this by calling the service; an
+/// [`InMemoryDynamoDb`](crate::dynamodb::InMemoryDynamoDb) implements it
+/// without one, so commands can be exercised without credentials or a
+/// local endpoint. Each method behaves like the `DynamoDb` method of the
+/// same name.
+///
+/// The futures aren't `Send`, since the bulk operations borrow a progress
+/// callback across their pages.
+#[async_trait(?Send)]
+pub trait DynamoDbApi {
+    /// What the client has counted so far, if it collects metrics.
+    fn metrics(&self) -> Option<MetricsSnapshot>;
+
+    async fn check_auth(&self) -> Result<Option<AuthInfo>>;
+
+    async fn list_tables(&self) -> Result<Vec<String>>;
+
+    async fn list_tables_with_prefix(&self, prefix: &str) -> Result<Vec<String>>;
+
+    async fn table_exists(&self, table_name: &str) -> Result<bool>;
+
+    async fn create_table_if_not_exists(
+        &self,
+        table: &Table<'_>,
+    ) -> Result<Option<CreateTableOutput>>;
+
+    async fn delete_table_if_exists_and_wait(
+        &self,
+        table_name: &str,
+        timeout: Duration,
+    ) -> Result<bool>;
+
+    async fn wait_for_table_active(&self, table_name: &str, timeout: Duration) -> Result<()>;
+
+    async fn load_table(
+        &self,
+        table_name: &str,
+        sample_size: Option<usize>,
+    ) -> Result<Table<'static>>;
+
+    async fn table_info(&self, table_name: &str) -> Result<TableInfo>;
+
+    async fn try_describe_table(&self, table_name: &str) -> Result<Option<TableInfo>>;
+
+    async fn create_backup(&self, table_name: &str, backup_name: &str) -> Result<BackupArn>;
+
+    async fn list_backups(&self, table_name: &str) -> Result<Vec<BackupInfo>>;
+
+    async fn restore_table_from_backup_and_wait(
+        &self,
+        backup_arn: &BackupArn,
+        new_table_name: &str,
+        timeout: Duration,
+    ) -> Result<()>;
+
+    async fn enable_stream(&self, table_name: &str, view_type: StreamViewType) -> Result<()>;
+
+    async fn subscribe_changes(
+        &self,
+        table_name: &str,
+        checkpoints: Arc<dyn CheckpointStore>,
+        cancel: CancellationToken,
+        handler: Box<dyn Fn(StreamRecord) + Send>,
+    ) -> Result<JoinHandle<Result<()>>>;
+
+    async fn put_item(&self, table_name: &str, item: Item) -> Result<()>;
+
+    async fn put_item_if_not_exists(&self, table: &Table<'_>, item: Item) -> Result<bool>;
+
+    async fn get_item(&self, table_name: &str, key: Item) -> Result<Option<Item>>;
+
+    async fn get_item_with(
+        &self,
+        table_name: &str,
+        key: Item,
+        options: GetItemOptions<'_>,
+    ) -> Result<Option<Item>>;
+
+    async fn update_with(&self, table_name: &str, key: Item, updates: UpdateBuilder) -> Result<()>;
+
+    async fn delete_item(&self, table_name: &str, key: Item) -> Result<()>;
+
+    async fn batch_put_items_cancellable(
+        &self,
+        table_name: &str,
+        items: Vec<Item>,
+        on_progress: Option<&mut dyn for<'p> FnMut(&'p Progress)>,
+        cancel: &CancellationToken,
+    ) -> Result<BulkResult<usize>>;
+
+    async fn query_flexible(&self, params: QueryFlexibleParams<'_>) -> Result<Vec<Item>>;
+
+    async fn query_flexible_detailed(
+        &self,
+        params: QueryFlexibleParams<'_>,
+        exclusive_start_key: Option<HashMap<String, AttributeValue>>,
+    ) -> Result<QueryResult>;
+
+    async fn query_all(
+        &self,
+        params: QueryFlexibleParams<'_>,
+        max_items: Option<usize>,
+    ) -> Result<Vec<Item>>;
+
+    #[allow(clippy::too_many_arguments)]
+    async fn query_simple(
+        &self,
+        table_name: &str,
+        partition_key: (&str, AttributeValue),
+        sort_key_condition: Option<(&str, String, SortKeyValue)>,
+        filter_expression: Option<&str>,
+        limit: Option<i32>,
+        expression_attribute_values: Option<HashMap<String, AttributeValue>>,
+        filter: Option<&FilterExpression>,
+    ) -> Result<Vec<Item>>;
+
+    async fn scan_all(
+        &self,
+        params: ScanParams<'_>,
+        max_items: Option<usize>,
+        on_progress: Option<&mut dyn for<'p> FnMut(&'p Progress)>,
+    ) -> Result<Vec<Item>>;
+
+    async fn scan_all_cancellable(
+        &self,
+        params: ScanParams<'_>,
+        max_items: Option<usize>,
+        on_progress: Option<&mut dyn for<'p> FnMut(&'p Progress)>,
+        cancel: &CancellationToken,
+    ) -> Result<BulkResult<Vec<Item>>>;
+
+    async fn scan_paginated(
+        &self,
+        params: ScanParams<'_>,
+        exclusive_start_key: Option<HashMap<String, AttributeValue>>,
+    ) -> Result<(Vec<Item>, Option<HashMap<String, AttributeValue>>)>;
+
+    async fn for_each_item_cancellable(
+        &self,
+        params: ScanParams<'_>,
+        on_progress: Option<&mut dyn for<'p> FnMut(&'p Progress)>,
+        cancel: &CancellationToken,
+        handler: &mut dyn FnMut(Item) -> Result<ControlFlow<()>>,
+    ) -> Result<BulkResult<ScanSummary>>;
+
+    async fn execute_statement(
+        &self,
+        statement: &Statement,
+        parameters: Vec<AttributeValue>,
+        next_token: Option<String>,
+    ) -> Result<StatementResult>;
+}
+
+#[async_trait(?Send)]
+impl DynamoDbApi for DynamoDb {
+    fn metrics(&self) -> Option<MetricsSnapshot> {
+        DynamoDb::metrics(self).map(Metrics::snapshot)
+    }
+
+    async fn check_auth(&self) -> Result<Option<AuthInfo>> {
+        DynamoDb::check_auth(self).await
+    }
+
+    async fn list_tables(&self) -> Result<Vec<String>> {
+        DynamoDb::list_tables(self).await
+    }
+
+    async fn list_tables_with_prefix(&self, prefix: &str) -> Result<Vec<String>> {
+        DynamoDb::list_tables_with_prefix(self, prefix).await
+    }
+
+    async fn table_exists(&self, table_name: &str) -> Result<bool> {
+        DynamoDb::table_exists(self, table_name).await
+    }
+
+    async fn create_table_if_not_exists(
+        &self,
+        table: &Table<'_>,
+    ) -> Result<Option<CreateTableOutput>> {
+        DynamoDb::create_table_if_not_exists(self, table).await
+    }
+
+    async fn delete_table_if_exists_and_wait(
+        &self,
+        table_name: &str,
+        timeout: Duration,
+    ) -> Result<bool> {
+        DynamoDb::delete_table_if_exists_and_wait(self, table_name, timeout).await
+    }
+
+    async fn wait_for_table_active(&self, table_name: &str, timeout: Duration) -> Result<()> {
+        DynamoDb::wait_for_table_active(self, table_name, timeout).await
+    }
+
+    async fn load_table(
+        &self,
+        table_name: &str,
+        sample_size: Option<usize>,
+    ) -> Result<Table<'static>> {
+        DynamoDb::load_table(self, table_name, sample_size).await
+    }
+
+    async fn table_info(&self, table_name: &str) -> Result<TableInfo> {
+        DynamoDb::table_info(self, table_name).await
+    }
+
+    async fn try_describe_table(&self, table_name: &str) -> Result<Option<TableInfo>> {
+        DynamoDb::try_describe_table(self, table_name).await
+    }
+
+    async fn create_backup(&self, table_name: &str, backup_name: &str) -> Result<BackupArn> {
+        DynamoDb::create_backup(self, table_name, backup_name).await
+    }
+
+    async fn list_backups(&self, table_name: &str) -> Result<Vec<BackupInfo>> {
+        DynamoDb::list_backups(self, table_name).await
+    }
+
+    async fn restore_table_from_backup_and_wait(
+        &self,
+        backup_arn: &BackupArn,
+        new_table_name: &str,
+        timeout: Duration,
+    ) -> Result<()> {
+        DynamoDb::restore_table_from_backup_and_wait(self, backup_arn, new_table_name, timeout)
+            .await
+    }
+
+    async fn enable_stream(&self, table_name: &str, view_type: StreamViewType) -> Result<()> {
+        DynamoDb::enable_stream(self, table_name, view_type).await
+    }
+
+    async fn subscribe_changes(
+        &self,
+        table_name: &str,
+        checkpoints: Arc<dyn CheckpointStore>,
+        cancel: CancellationToken,
+        handler: Box<dyn Fn(StreamRecord) + Send>,
+    ) -> Result<JoinHandle<Result<()>>> {
+        DynamoDb::subscribe_changes(self, table_name, checkpoints, cancel, handler).await
+    }
+
+    async fn put_item(&self, table_name: &str, item: Item) -> Result<()> {
+        DynamoDb::put_item(self, table_name, item).await
+    }
+
+    async fn put_item_if_not_exists(&self, table: &Table<'_>, item: Item) -> Result<bool> {
+        DynamoDb::put_item_if_not_exists(self, table, item).await
+    }
+
+    async fn get_item(&self, table_name: &str, key: Item) -> Result<Option<Item>> {
+        DynamoDb::get_item(self, table_name, key).await
+    }
+
+    async fn get_item_with(
+        &self,
+        table_name: &str,
+        key: Item,
+        options: GetItemOptions<'_>,
+    ) -> Result<Option<Item>> {
+        DynamoDb::get_item_with(self, table_name, key, options).await
+    }
+
+    async fn update_with(&self, table_name: &str, key: Item, updates: UpdateBuilder) -> Result<()> {
+        DynamoDb::update_with(self, table_name, key, updates).await
+    }
+
+    async fn delete_item(&self, table_name: &str, key: Item) -> Result<()> {
+        DynamoDb::delete_item(self, table_name, key).await
+    }
+
+    async fn batch_put_items_cancellable(
+        &self,
+        table_name: &str,
+        items: Vec<Item>,
+        on_progress: Option<&mut dyn for<'p> FnMut(&'p Progress)>,
+        cancel: &CancellationToken,
+    ) -> Result<BulkResult<usize>> {
+        DynamoDb::batch_put_items_cancellable(self, table_name, items, on_progress, cancel).await
+    }
+
+    async fn query_flexible(&self, params: QueryFlexibleParams<'_>) -> Result<Vec<Item>> {
+        DynamoDb::query_flexible(self, params).await
+    }
+
+    async fn query_flexible_detailed(
+        &self,
+        params: QueryFlexibleParams<'_>,
+        exclusive_start_key: Option<HashMap<String, AttributeValue>>,
+    ) -> Result<QueryResult> {
+        DynamoDb::query_flexible_detailed(self, params, exclusive_start_key).await
+    }
+
+    async fn query_all(
+        &self,
+        params: QueryFlexibleParams<'_>,
+        max_items: Option<usize>,
+    ) -> Result<Vec<Item>> {
+        DynamoDb::query_all(self, params, max_items).await
+    }
+
+    async fn query_simple(
+        &self,
+        table_name: &str,
+        partition_key: (&str, AttributeValue),
+        sort_key_condition: Option<(&str, String, SortKeyValue)>,
+        filter_expression: Option<&str>,
+        limit: Option<i32>,
+        expression_attribute_values: Option<HashMap<String, AttributeValue>>,
+        filter: Option<&FilterExpression>,
+    ) -> Result<Vec<Item>> {
+        DynamoDb::query_simple(
+            self,
+            table_name,
+            partition_key,
+            sort_key_condition,
+            filter_expression,
+            limit,
+            expression_attribute_values,
+            filter,
+        )
+        .await
+    }
+
+    async fn scan_all(
+        &self,
+        params: ScanParams<'_>,
+        max_items: Option<usize>,
+        on_progress: Option<&mut dyn for<'p> FnMut(&'p Progress)>,
+    ) -> Result<Vec<Item>> {
+        DynamoDb::scan_all(self, params, max_items, on_progress).await
+    }
+
+    async fn scan_all_cancellable(
+        &self,
+        params: ScanParams<'_>,
+        max_items: Option<usize>,
+        on_progress: Option<&mut dyn for<'p> FnMut(&'p Progress)>,
+        cancel: &CancellationToken,
+    ) -> Result<BulkResult<Vec<Item>>> {
+        DynamoDb::scan_all_cancellable(self, params, max_items, on_progress, cancel).await
+    }
+
+    async fn scan_paginated(
+        &self,
+        params: ScanParams<'_>,
+        exclusive_start_key: Option<HashMap<String, AttributeValue>>,
+    ) -> Result<(Vec<Item>, Option<HashMap<String, AttributeValue>>)> {
+        DynamoDb::scan_paginated(self, params, exclusive_start_key).await
+    }
+
+    async fn for_each_item_cancellable(
+        &self,
+        params: ScanParams<'_>,
+        on_progress: Option<&mut dyn for<'p> FnMut(&'p Progress)>,
+        cancel: &CancellationToken,
+        handler: &mut dyn FnMut(Item) -> Result<ControlFlow<()>>,
+    ) -> Result<BulkResult<ScanSummary>> {
+        DynamoDb::for_each_item_cancellable(self, params, on_progress, cancel, handler).await
+    }
+
+    async fn execute_statement(
+        &self,
+        statement: &Statement,
+        parameters: Vec<AttributeValue>,
+        next_token: Option<String>,
+    ) -> Result<StatementResult> {
+        DynamoDb::execute_statement(self, statement, parameters, next_token).await
+    }
+}
@@ -0,0 +1,44 @@
+use anyhow::{anyhow, Result};
+use aws_sdk_sts::operation::get_caller_identity::GetCallerIdentityOutput;
+use std::fmt;
+
+/// Who a client is authenticated as, as reported by STS
+/// `GetCallerIdentity`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuthInfo {
+    pub account_id: String,
+    /// The IAM user or assumed role, e.g.
+    /// `arn:aws:sts::123456789012:assumed-role/Admin/alice`.
+    pub arn: String,
+    /// The unique ID of the user, or of the role and session.
+    pub user_id: String,
+}
+
+impl TryFrom<GetCallerIdentityOutput> for AuthInfo {
+    type Error = anyhow::Error;
+
+    fn try_from(identity: GetCallerIdentityOutput) -> Result<Self> {
+        let arn = identity
+            .arn
+            .ok_or_else(|| anyhow!("GetCallerIdentity returned no ARN"))?;
+        Ok(Self {
+            account_id: identity
+                .account
+                .ok_or_else(|| anyhow!("GetCallerIdentity returned no account for '{arn}'"))?,
+            user_id: identity
+                .user_id
+                .ok_or_else(|| anyhow!("GetCallerIdentity returned no user ID for '{arn}'"))?,
+            arn,
+        })
+    }
+}
+
+impl fmt::Display for AuthInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} (account {}, user ID {})",
+            self.arn, self.account_id, self.user_id
+        )
+    }
+}
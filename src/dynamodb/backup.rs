@@ -0,0 +1,65 @@
+use anyhow::{anyhow, Result};
+use aws_sdk_dynamodb::types::{BackupStatus, BackupSummary};
+use std::fmt;
+use std::time::SystemTime;
+
+/// The ARN identifying an on-demand backup.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BackupArn(String);
+
+impl BackupArn {
+    /// Wraps an ARN returned by DynamoDB or supplied by the user.
+    pub fn new(arn: impl Into<String>) -> Self {
+        Self(arn.into())
+    }
+
+    /// Returns the ARN as a string.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for BackupArn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// An on-demand backup as reported by `ListBackups`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BackupInfo {
+    pub arn: BackupArn,
+    pub name: String,
+    pub table_name: String,
+    pub status: BackupStatus,
+    pub created_at: SystemTime,
+    /// Size of the backup, once DynamoDB has measured it.
+    pub size_bytes: Option<i64>,
+}
+
+impl TryFrom<BackupSummary> for BackupInfo {
+    type Error = anyhow::Error;
+
+    fn try_from(backup: BackupSummary) -> Result<Self> {
+        let arn = backup
+            .backup_arn
+            .map(BackupArn)
+            .ok_or_else(|| anyhow!("Backup summary has no ARN"))?;
+        let created_at = backup
+            .backup_creation_date_time
+            .ok_or_else(|| anyhow!("Backup '{arn}' has no creation time"))?
+            .try_into()
+            .map_err(|e| anyhow!("Backup '{arn}' has an invalid creation time: {e}"))?;
+
+        Ok(Self {
+            name: backup.backup_name.unwrap_or_default(),
+            table_name: backup.table_name.unwrap_or_default(),
+            status: backup
+                .backup_status
+                .ok_or_else(|| anyhow!("Backup '{arn}' has no status"))?,
+            size_bytes: backup.backup_size_bytes,
+            created_at,
+            arn,
+        })
+    }
+}
@@ -0,0 +1,230 @@
+use anyhow::{anyhow, Result};
+use aws_config::meta::region::ProvideRegion;
+use aws_config::profile::{ProfileFileCredentialsProvider, ProfileFileRegionProvider};
+use aws_config::retry::{RetryConfig, RetryMode};
+use aws_config::timeout::TimeoutConfigBuilder;
+use aws_config::{BehaviorVersion, Region, SdkConfig};
+use aws_sdk_dynamodb::config::{Credentials, SharedCredentialsProvider};
+use std::time::Duration;
+
+use crate::dynamodb::client::{local_credentials, retry_policy, LOCAL_REGION};
+use crate::dynamodb::{is_local_endpoint, DynamoDb};
+
+/// Builds a [`DynamoDb`] from explicit settings, without reading `AWS_*`
+/// environment variables, so one process can hold clients for several
+/// accounts, regions or endpoints at once.
+///
+/// Credentials come from [`DynamoDbBuilder::credentials`], else the named
+/// [`DynamoDbBuilder::profile`] in `~/.aws/credentials` and
+/// `~/.aws/config`. The region comes from [`DynamoDbBuilder::region`], else
+/// the profile. An endpoint on `localhost` needs neither: like
+/// [`DynamoDb::new_local`], it falls back to dummy credentials and a fixed
+/// region.
+///
+/// Retries and timeouts the builder isn't given are the SDK's defaults.
+/// The settings a client ends up with are logged at INFO when it's built.
+///
+/// ```no_run
+/// use aws_dynamodb_testing::aws::RetryMode;
+/// use aws_dynamodb_testing::dynamodb::DynamoDb;
+/// use std::time::Duration;
+///
+/// # async fn example() -> anyhow::Result<()> {
+/// let prod = DynamoDb::builder()
+///     .profile("prod")
+///     .timeout(Duration::from_secs(5))
+///     .build()
+///     .await?;
+/// let staging = DynamoDb::builder()
+///     .region("eu-west-1")
+///     .credentials("AKIAEXAMPLE", "secret", None)
+///     .retry_policy(5, Duration::from_millis(100), Duration::from_secs(2))
+///     .build()
+///     .await?;
+/// // Tuned for a bulk load: back off client-side when throttled, keep
+/// // trying for longer, but give up quickly on a dead connection.
+/// let bulk = DynamoDb::builder()
+///     .profile("prod")
+///     .retry_mode(RetryMode::Adaptive)
+///     .max_attempts(10)
+///     .connect_timeout(Duration::from_secs(1))
+///     .read_timeout(Duration::from_secs(5))
+///     .build()
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct DynamoDbBuilder {
+    region: Option<String>,
+    endpoint_url: Option<String>,
+    credentials: Option<Credentials>,
+    profile: Option<String>,
+    timeout_config: TimeoutConfigBuilder,
+    retry_config: Option<RetryConfig>,
+    key_schema_ttl: Option<Duration>,
+}
+
+impl DynamoDbBuilder {
+    /// The AWS region to send requests to, e.g. `eu-west-1`.
+    pub fn region(mut self, region: impl Into<String>) -> Self {
+        self.region = Some(region.into());
+        self
+    }
+
+    /// Sends requests to `endpoint_url` instead of the region's DynamoDB
+    /// endpoint, e.g. DynamoDB Local at [`LOCAL_ENDPOINT`](super::LOCAL_ENDPOINT).
+    pub fn endpoint_url(mut self, endpoint_url: impl Into<String>) -> Self {
+        self.endpoint_url = Some(endpoint_url.into());
+        self
+    }
+
+    /// Signs requests with these static credentials, taking precedence over
+    /// a profile's.
+    pub fn credentials(
+        mut self,
+        access_key_id: impl Into<String>,
+        secret_access_key: impl Into<String>,
+        session_token: Option<String>,
+    ) -> Self {
+        self.credentials = Some(Credentials::new(
+            access_key_id,
+            secret_access_key,
+            session_token,
+            None,
+            "DynamoDbBuilder",
+        ));
+        self
+    }
+
+    /// Takes credentials, and the region unless one is set, from this
+    /// profile in the shared AWS config files.
+    pub fn profile(mut self, name: impl Into<String>) -> Self {
+        self.profile = Some(name.into());
+        self
+    }
+
+    /// Fails any call that takes longer than `timeout` in all, retries
+    /// included.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout_config = self.timeout_config.operation_timeout(timeout);
+        self
+    }
+
+    /// Gives up on opening a connection after `timeout`. The attempt can
+    /// then be retried.
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout_config = self.timeout_config.connect_timeout(timeout);
+        self
+    }
+
+    /// Gives up on a response after waiting `timeout` for its first byte.
+    /// The attempt can then be retried.
+    pub fn read_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout_config = self.timeout_config.read_timeout(timeout);
+        self
+    }
+
+    /// Retries in `mode`: [`RetryMode::Adaptive`] also slows requests down
+    /// client-side while DynamoDB is throttling them, which suits bulk
+    /// loads better than retrying at full speed.
+    pub fn retry_mode(mut self, mode: RetryMode) -> Self {
+        self.retry_config = Some(self.retry_config().with_retry_mode(mode));
+        self
+    }
+
+    /// Makes up to `max_attempts` attempts at each request, the first
+    /// included.
+    pub fn max_attempts(mut self, max_attempts: u32) -> Self {
+        self.retry_config = Some(self.retry_config().with_max_attempts(max_attempts.max(1)));
+        self
+    }
+
+    /// Retries as [`DynamoDb::with_retry_policy`] does.
+    pub fn retry_policy(
+        mut self,
+        max_attempts: u32,
+        base_delay: Duration,
+        max_delay: Duration,
+    ) -> Self {
+        self.retry_config = Some(retry_policy(max_attempts, base_delay, max_delay));
+        self
+    }
+
+    /// Makes every request once, as [`DynamoDb::without_retries`] does.
+    pub fn without_retries(mut self) -> Self {
+        self.retry_config = Some(RetryConfig::disabled());
+        self
+    }
+
+    /// Caches key schemas for `ttl`, as [`DynamoDb::with_key_schema_ttl`]
+    /// does.
+    pub fn key_schema_ttl(mut self, ttl: Duration) -> Self {
+        self.key_schema_ttl = Some(ttl);
+        self
+    }
+
+    /// Creates the client.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if neither credentials nor a profile were given, or
+    /// no region was given and the profile has none, unless the endpoint is
+    /// local.
+    pub async fn build(self) -> Result<DynamoDb> {
+        let local = self.endpoint_url.as_deref().is_some_and(is_local_endpoint);
+
+        let credentials = match (self.credentials, &self.profile) {
+            (Some(credentials), _) => SharedCredentialsProvider::new(credentials),
+            (None, Some(profile)) => SharedCredentialsProvider::new(
+                ProfileFileCredentialsProvider::builder()
+                    .profile_name(profile)
+                    .build(),
+            ),
+            (None, None) if local => SharedCredentialsProvider::new(local_credentials()),
+            (None, None) => {
+                return Err(anyhow!(
+                    "No credentials for DynamoDB: set credentials or a profile"
+                ))
+            }
+        };
+        let region = match (self.region, &self.profile) {
+            (Some(region), _) => Region::new(region),
+            (None, Some(profile)) => ProfileFileRegionProvider::builder()
+                .profile_name(profile)
+                .build()
+                .region()
+                .await
+                .ok_or_else(|| anyhow!("Profile '{profile}' has no region; set one"))?,
+            (None, None) if local => Region::new(LOCAL_REGION),
+            (None, None) => {
+                return Err(anyhow!("No region for DynamoDB: set a region or a profile"))
+            }
+        };
+
+        let mut config = SdkConfig::builder()
+            .behavior_version(BehaviorVersion::latest())
+            .region(region)
+            .credentials_provider(credentials);
+        if let Some(endpoint_url) = self.endpoint_url {
+            config = config.endpoint_url(endpoint_url);
+        }
+        config = config.timeout_config(self.timeout_config.build());
+        if let Some(retry_config) = self.retry_config {
+            config = config.retry_config(retry_config);
+        }
+        let mut ddb = DynamoDb::new(&config.build());
+        if let Some(ttl) = self.key_schema_ttl {
+            ddb = ddb.with_key_schema_ttl(ttl);
+        }
+        ddb.log_settings();
+        Ok(ddb)
+    }
+
+    /// The retry settings so far, starting from the SDK's standard ones.
+    fn retry_config(&self) -> RetryConfig {
+        self.retry_config
+            .clone()
+            .unwrap_or_else(RetryConfig::standard)
+    }
+}
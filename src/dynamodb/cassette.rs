@@ -0,0 +1,272 @@
+//! Recording and replaying DynamoDB HTTP traffic, so tests can run without
+//! DynamoDB, Docker or credentials.
+//!
+//! A cassette is a JSON file of request/response pairs. In record mode the
+//! client's real HTTP connector is wrapped, and every exchange is appended
+//! to the cassette as it completes. In replay mode no connection is made at
+//! all: each request is answered from the cassette, and a request it
+//! doesn't hold fails.
+//!
+//! Requests are matched on their signature: method, path, `X-Amz-Target`
+//! (the operation) and JSON body. Headers that change on every call, such
+//! as the date and the SigV4 `Authorization`, are ignored. When the same
+//! request was recorded more than once (e.g. a `GetItem` before and after
+//! a `PutItem`), the recorded responses are replayed in order, and the
+//! last one is repeated once they run out.
+
+use anyhow::{anyhow, Context, Result};
+use aws_sdk_dynamodb::config::http::{HttpRequest, HttpResponse};
+use aws_smithy_runtime_api::client::http::{
+    HttpClient, HttpConnector, HttpConnectorFuture, HttpConnectorSettings, SharedHttpClient,
+    SharedHttpConnector,
+};
+use aws_smithy_runtime_api::client::result::ConnectorError;
+use aws_smithy_runtime_api::client::runtime_components::RuntimeComponents;
+use aws_smithy_runtime_api::http::StatusCode;
+use aws_smithy_types::body::SdkBody;
+use aws_smithy_types::byte_stream::ByteStream;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// The recorded exchanges, in the order they happened.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Cassette {
+    interactions: Vec<Interaction>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Interaction {
+    request: RequestSignature,
+    response: RecordedResponse,
+}
+
+/// The parts of a request that identify it across runs.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct RequestSignature {
+    method: String,
+    path: String,
+    target: Option<String>,
+    /// The body as JSON, so key order doesn't matter, or as a string if it
+    /// isn't JSON.
+    body: Value,
+}
+
+impl RequestSignature {
+    fn of(request: &HttpRequest) -> Self {
+        let path = request
+            .uri()
+            .split_once("://")
+            .map_or(request.uri(), |(_, rest)| rest);
+        let path = path.find('/').map_or("/", |start| &path[start..]);
+        let body = request.body().bytes().unwrap_or_default();
+        Self {
+            method: request.method().to_string(),
+            path: path.to_string(),
+            target: request.headers().get("x-amz-target").map(str::to_string),
+            body: serde_json::from_slice(body)
+                .unwrap_or_else(|_| Value::String(String::from_utf8_lossy(body).into_owned())),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct RecordedResponse {
+    status: u16,
+    headers: BTreeMap<String, String>,
+    body: String,
+}
+
+impl RecordedResponse {
+    fn to_response(&self) -> Result<HttpResponse, ConnectorError> {
+        let status =
+            StatusCode::try_from(self.status).map_err(|e| ConnectorError::other(e.into(), None))?;
+        let mut response = HttpResponse::new(status, SdkBody::from(self.body.clone()));
+        for (name, value) in &self.headers {
+            response.headers_mut().insert(name.clone(), value.clone());
+        }
+        Ok(response)
+    }
+}
+
+/// Wraps `inner` so every exchange is written to the cassette at `path`,
+/// replacing whatever it held.
+pub(crate) fn recording_client(
+    inner: SharedHttpClient,
+    path: impl Into<PathBuf>,
+) -> Result<SharedHttpClient> {
+    let path = path.into();
+    let recorder = Recorder {
+        path,
+        cassette: Mutex::new(Cassette::default()),
+    };
+    recorder.save()?;
+    Ok(SharedHttpClient::new(RecordingClient {
+        inner,
+        recorder: Arc::new(recorder),
+    }))
+}
+
+/// A client that answers every request from the cassette at `path`.
+pub(crate) fn replaying_client(path: impl AsRef<Path>) -> Result<SharedHttpClient> {
+    let path = path.as_ref();
+    let contents = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read cassette {}", path.display()))?;
+    let cassette: Cassette = serde_json::from_str(&contents)
+        .with_context(|| format!("Failed to parse cassette {}", path.display()))?;
+    Ok(SharedHttpClient::new(ReplayingClient(Arc::new(Replay {
+        path: path.to_path_buf(),
+        interactions: cassette.interactions,
+        played: Mutex::new(Vec::new()),
+    }))))
+}
+
+#[derive(Debug)]
+struct Recorder {
+    path: PathBuf,
+    cassette: Mutex<Cassette>,
+}
+
+impl Recorder {
+    fn record(&self, interaction: Interaction) -> Result<()> {
+        self.cassette
+            .lock()
+            .map_err(|_| anyhow!("Cassette lock poisoned"))?
+            .interactions
+            .push(interaction);
+        self.save()
+    }
+
+    fn save(&self) -> Result<()> {
+        let cassette = self
+            .cassette
+            .lock()
+            .map_err(|_| anyhow!("Cassette lock poisoned"))?;
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(&self.path, serde_json::to_string_pretty(&*cassette)? + "\n")
+            .with_context(|| format!("Failed to write cassette {}", self.path.display()))
+    }
+}
+
+#[derive(Debug)]
+struct RecordingClient {
+    inner: SharedHttpClient,
+    recorder: Arc<Recorder>,
+}
+
+impl HttpClient for RecordingClient {
+    fn http_connector(
+        &self,
+        settings: &HttpConnectorSettings,
+        components: &RuntimeComponents,
+    ) -> SharedHttpConnector {
+        SharedHttpConnector::new(RecordingConnector {
+            inner: self.inner.http_connector(settings, components),
+            recorder: self.recorder.clone(),
+        })
+    }
+}
+
+#[derive(Debug)]
+struct RecordingConnector {
+    inner: SharedHttpConnector,
+    recorder: Arc<Recorder>,
+}
+
+impl HttpConnector for RecordingConnector {
+    fn call(&self, request: HttpRequest) -> HttpConnectorFuture {
+        let signature = RequestSignature::of(&request);
+        let inner = self.inner.clone();
+        let recorder = self.recorder.clone();
+        HttpConnectorFuture::new(async move {
+            let mut response = inner.call(request).await?;
+            // Read the whole body so it can be both saved and passed on.
+            let body = ByteStream::new(response.take_body())
+                .collect()
+                .await
+                .map_err(|e| ConnectorError::io(e.into()))?
+                .into_bytes();
+            let recorded = RecordedResponse {
+                status: response.status().as_u16(),
+                headers: response
+                    .headers()
+                    .iter()
+                    .map(|(name, value)| (name.to_string(), value.to_string()))
+                    .collect(),
+                body: String::from_utf8_lossy(&body).into_owned(),
+            };
+            recorder
+                .record(Interaction {
+                    request: signature,
+                    response: recorded,
+                })
+                .map_err(|e| ConnectorError::other(e.into(), None))?;
+            *response.body_mut() = SdkBody::from(body);
+            Ok(response)
+        })
+    }
+}
+
+#[derive(Debug)]
+struct Replay {
+    path: PathBuf,
+    interactions: Vec<Interaction>,
+    /// Indexes of the interactions already replayed.
+    played: Mutex<Vec<usize>>,
+}
+
+impl Replay {
+    fn respond(&self, request: &HttpRequest) -> Result<HttpResponse, ConnectorError> {
+        let signature = RequestSignature::of(request);
+        let mut played = self.played.lock().expect("replay lock poisoned");
+        let mut matching = self
+            .interactions
+            .iter()
+            .enumerate()
+            .filter(|(_, interaction)| interaction.request == signature)
+            .map(|(index, _)| index)
+            .peekable();
+        let Some(&first) = matching.peek() else {
+            return Err(ConnectorError::other(
+                format!(
+                    "No response recorded in {} for {} {}",
+                    self.path.display(),
+                    signature.target.as_deref().unwrap_or(&signature.method),
+                    signature.body
+                )
+                .into(),
+                None,
+            ));
+        };
+        let mut last = first;
+        let index = matching
+            .inspect(|index| last = *index)
+            .find(|index| !played.contains(index))
+            .unwrap_or(last);
+        played.push(index);
+        self.interactions[index].response.to_response()
+    }
+}
+
+#[derive(Debug, Clone)]
+struct ReplayingClient(Arc<Replay>);
+
+impl HttpClient for ReplayingClient {
+    fn http_connector(
+        &self,
+        _settings: &HttpConnectorSettings,
+        _components: &RuntimeComponents,
+    ) -> SharedHttpConnector {
+        SharedHttpConnector::new(self.clone())
+    }
+}
+
+impl HttpConnector for ReplayingClient {
+    fn call(&self, request: HttpRequest) -> HttpConnectorFuture {
+        HttpConnectorFuture::ready(self.0.respond(&request))
+    }
+}
@@ -1,16 +1,240 @@
-use anyhow::{anyhow, Result};
+#[cfg(feature = "serde")]
+use crate::dynamodb::DynamoEntity;
+use anyhow::{anyhow, Context, Result};
+use aws_sdk_dynamodb::error::{ProvideErrorMetadata, SdkError};
 use aws_sdk_dynamodb::{
-    operation::{create_table::CreateTableOutput, scan::ScanOutput},
+    config::{retry::RetryConfig, Credentials, SharedCredentialsProvider},
+    operation::{
+        create_backup::builders::CreateBackupFluentBuilder,
+        create_table::{builders::CreateTableFluentBuilder, CreateTableOutput},
+        delete_item::DeleteItemError,
+        execute_statement::builders::ExecuteStatementFluentBuilder,
+        export_table_to_point_in_time::builders::ExportTableToPointInTimeFluentBuilder,
+        get_item::builders::GetItemFluentBuilder,
+        import_table::builders::ImportTableFluentBuilder,
+        list_backups::builders::ListBackupsFluentBuilder,
+        put_item::{builders::PutItemFluentBuilder, PutItemError},
+        query::builders::QueryFluentBuilder,
+        restore_table_from_backup::builders::RestoreTableFromBackupFluentBuilder,
+        scan::builders::ScanFluentBuilder,
+        scan::ScanOutput,
+        transact_write_items::TransactWriteItemsError,
+        update_item::UpdateItemError,
+        update_table::builders::UpdateTableFluentBuilder,
+    },
     types::{
-        AttributeDefinition, AttributeValue, BillingMode, KeySchemaElement, KeyType,
-        ScalarAttributeType,
+        AttributeDefinition, AttributeValue, BackupTypeFilter, BillingMode, ConsumedCapacity,
+        CreateGlobalSecondaryIndexAction, DeleteGlobalSecondaryIndexAction, ExportFormat,
+        GlobalSecondaryIndex, GlobalSecondaryIndexUpdate, IndexStatus, InputFormat,
+        KeySchemaElement, KeyType, Projection, ProjectionType, ProvisionedThroughput, PutRequest,
+        ReturnConsumedCapacity, ReturnValuesOnConditionCheckFailure, S3BucketSource,
+        ScalarAttributeType, Select, StreamSpecification, StreamViewType, TableCreationParameters,
+        TableDescription, TableStatus, TransactWriteItem, WriteRequest,
     },
     Client,
 };
+#[cfg(feature = "serde")]
+use serde::de::DeserializeOwned;
+#[cfg(feature = "serde")]
+use serde::Serialize;
 use std::collections::HashMap;
-use tracing::{error, info};
+use std::ops::ControlFlow;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::task::{self, JoinError, JoinHandle, JoinSet};
+use tokio::time::sleep;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug_span, field::Empty, info, instrument, Instrument, Span};
+
+use crate::dynamodb::key_schema::{KeySchemaCache, KEY_SCHEMA_TTL};
+use crate::dynamodb::metrics::{Metrics, MetricsRecorder, ReturnTotalCapacity};
+use crate::dynamodb::retry::{is_transient, RetryTracing};
+use crate::dynamodb::stream::run_subscription;
+use crate::dynamodb::table_info::key_attributes;
+use crate::dynamodb::wire_log::WireLog;
+use crate::dynamodb::{
+    AuthInfo, BackupArn, BackupInfo, Capacity, CheckpointStore, ConditionFailed, DynamoDbBuilder,
+    ExportInfo, ExpressionRejected, FieldType, Filter, FilterExpression, ImportInfo,
+    InMemoryCheckpoints, Item, KeyCondition, KeyConditionExpression, KeySchema, PageToken,
+    PointInTimeRecoveryDisabled, ProjectionExpression, RequestFailed, Schema, ShardedKey,
+    SortKeyCondition, SortKeyValue, Statement, StreamReader, StreamRecord, StreamsDisabled, Table,
+    TableInfo, TableNotFound, TransactionCancelled, UpdateBuilder,
+};
+
+/// How often the table waiters re-check the table's status.
+const TABLE_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// The most items a single `BatchWriteItem` request accepts.
+pub(crate) const BATCH_WRITE_SIZE: usize = 25;
+
+/// How many times `batch_put_items` retries unprocessed items.
+const BATCH_WRITE_MAX_RETRIES: u32 = 8;
+
+/// The delay before the first retry of unprocessed items, doubled each time.
+const BATCH_WRITE_RETRY_DELAY: Duration = Duration::from_millis(50);
+
+/// Where DynamoDB Local listens by default.
+pub const LOCAL_ENDPOINT: &str = "http://localhost:8000";
+
+/// The region `DynamoDb::new_local` uses. DynamoDB Local accepts any region
+/// and credentials, but keeps separate tables for each access key and
+/// region unless it's started with `-sharedDb`, so they're fixed.
+pub(crate) const LOCAL_REGION: &str = "us-east-1";
+const LOCAL_ACCESS_KEY: &str = "dummy";
+
+/// The dummy credentials local clients sign with; see [`LOCAL_REGION`].
+pub(crate) fn local_credentials() -> Credentials {
+    Credentials::new(
+        LOCAL_ACCESS_KEY,
+        LOCAL_ACCESS_KEY,
+        None,
+        None,
+        "dynamodb-local",
+    )
+}
+
+/// The SDK retry settings for [`DynamoDb::with_retry_policy`].
+pub(crate) fn retry_policy(
+    max_attempts: u32,
+    base_delay: Duration,
+    max_delay: Duration,
+) -> RetryConfig {
+    RetryConfig::standard()
+        .with_max_attempts(max_attempts.max(1))
+        .with_initial_backoff(base_delay)
+        .with_max_backoff(max_delay)
+}
+
+/// Wraps the SDK error of a failed request in [`RequestFailed`], naming the
+/// operation and, where there is one, the table and item key. If the error
+/// says the table doesn't exist, [`TableNotFound`] is attached on top.
+///
+/// Every request the client sends maps its error through this, directly or
+/// through one of the helpers below.
+pub(crate) fn request_failed<E>(
+    table_name: Option<&str>,
+    key: Option<HashMap<String, AttributeValue>>,
+) -> impl FnOnce(SdkError<E>) -> anyhow::Error + '_
+where
+    E: ProvideErrorMetadata + std::error::Error + Send + Sync + 'static,
+{
+    move |e| {
+        let code = e.code().map(str::to_string);
+        let transient = is_transient(&e);
+        // Backup operations say `TableNotFoundException`.
+        let missing = matches!(
+            code.as_deref(),
+            Some("ResourceNotFoundException" | "TableNotFoundException")
+        );
+        let e = anyhow::Error::new(e).context(RequestFailed::new::<E>(
+            table_name,
+            key.as_ref(),
+            code.as_deref(),
+            transient,
+        ));
+        match table_name {
+            Some(table_name) if missing => e.context(TableNotFound {
+                table_name: table_name.to_string(),
+            }),
+            _ => e,
+        }
+    }
+}
+
+/// Maps the error of a request on `table_name`, reporting a missing table
+/// as [`TableNotFound`].
+fn table_not_found<E>(table_name: &str) -> impl FnOnce(SdkError<E>) -> anyhow::Error + '_
+where
+    E: ProvideErrorMetadata + std::error::Error + Send + Sync + 'static,
+{
+    request_failed(Some(table_name), None)
+}
+
+/// Maps the error of a request on the item with `key` in `table_name`.
+fn item_request_failed<'a, E>(
+    table_name: &'a str,
+    key: &Item,
+) -> impl FnOnce(SdkError<E>) -> anyhow::Error + 'a
+where
+    E: ProvideErrorMetadata + std::error::Error + Send + Sync + 'static,
+{
+    request_failed(Some(table_name), Some(key.attributes.clone()))
+}
+
+/// Like [`request_failed`], but also attaches the request's expressions,
+/// built by `expressions`, as [`ExpressionRejected`] when DynamoDB answers
+/// with a `ValidationException`.
+fn expression_rejected<'a, E>(
+    table_name: &'a str,
+    key: Option<HashMap<String, AttributeValue>>,
+    expressions: impl FnOnce() -> ExpressionRejected + 'a,
+) -> impl FnOnce(SdkError<E>) -> anyhow::Error + 'a
+where
+    E: ProvideErrorMetadata + std::error::Error + Send + Sync + 'static,
+{
+    move |e| {
+        let invalid = e.code() == Some("ValidationException");
+        let e = request_failed(Some(table_name), key)(e);
+        if invalid {
+            e.context(expressions())
+        } else {
+            e
+        }
+    }
+}
+
+/// Like [`request_failed`], but reports a false condition as
+/// [`ConditionFailed`], with the item DynamoDB returned, if any.
+fn condition_failed<E>(
+    table_name: &str,
+    key: Option<HashMap<String, AttributeValue>>,
+    item: Option<Option<HashMap<String, AttributeValue>>>,
+) -> impl FnOnce(SdkError<E>) -> anyhow::Error + '_
+where
+    E: ProvideErrorMetadata + std::error::Error + Send + Sync + 'static,
+{
+    move |e| {
+        let e = request_failed(Some(table_name), key)(e);
+        match item {
+            Some(item) => e.context(ConditionFailed {
+                table_name: table_name.to_string(),
+                item: item.map(|attributes| Item { attributes }),
+            }),
+            None => e,
+        }
+    }
+}
+
+/// Asks for the existing item back when a condition fails, if wanted.
+fn return_item_on_failure(return_item: bool) -> Option<ReturnValuesOnConditionCheckFailure> {
+    return_item.then_some(ReturnValuesOnConditionCheckFailure::AllOld)
+}
+
+/// Builds the SDK client for `sdk_config`, with retries traced.
+fn sdk_client(sdk_config: &aws_config::SdkConfig) -> Client {
+    Client::from_conf(
+        aws_sdk_dynamodb::config::Builder::from(sdk_config)
+            .interceptor(RetryTracing)
+            .build(),
+    )
+}
 
-use crate::dynamodb::{Item, Table};
+/// Whether `endpoint` is a URL on this machine (`localhost` or a loopback
+/// address), as DynamoDB Local's is.
+pub fn is_local_endpoint(endpoint: &str) -> bool {
+    let authority = endpoint
+        .split_once("://")
+        .map_or(endpoint, |(_, rest)| rest);
+    let authority = authority.split(['/', '?']).next().unwrap_or_default();
+    let host = match authority.strip_prefix('[') {
+        Some(ipv6) => ipv6.split(']').next().unwrap_or_default(),
+        None => authority.split(':').next().unwrap_or_default(),
+    };
+    host.eq_ignore_ascii_case("localhost")
+        || host
+            .parse::<std::net::IpAddr>()
+            .is_ok_and(|ip| ip.is_loopback())
+}
 
 /// DynamoDB client wrapper for high-level operations.
 ///
@@ -45,9 +269,10 @@ use crate::dynamodb::{Item, Table};
 ///
 /// # Example
 ///
-/// ```rust
+/// ```rust,no_run
 /// use aws_config::load_from_env;
-/// use dynamodb::{DynamoDb, Table, Item};
+/// use aws_dynamodb_testing::dynamodb::{DynamoDb, Item, Table};
+/// use aws_dynamodb_testing::aws::AttributeValue;
 ///
 /// #[tokio::main]
 /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -60,22 +285,50 @@ use crate::dynamodb::{Item, Table};
 ///
 ///     // Put an item
 ///     let item = Item::new()
-///         .set("user_id", "123")
-///         .set("email", "user@example.com")
-///         .set("name", "John Doe");
+///         .set_string("user_id", "123")
+///         .set_string("email", "user@example.com")
+///         .set_string("name", "John Doe");
 ///     client.put_item("users", item).await?;
 ///
 ///     // Query items
 ///     let items = client.query_items(
 ///         "users",
 ///         ("user_id", AttributeValue::S("123".to_string())),
-///         None
+///         None,
 ///     ).await?;
 ///
 ///     Ok(())
 /// }
 /// ```
 ///
+/// # Sharing a client
+///
+/// `DynamoDb` is cheap to clone: clones share the SDK client's connection
+/// pool, credentials cache and configuration, and any [`Metrics`] set by
+/// [`DynamoDb::with_metrics`], so a clone can be moved into each task that
+/// fans out work instead of wrapping the client in an `Arc`.
+///
+/// ```no_run
+/// use aws_dynamodb_testing::dynamodb::{DynamoDb, Item, LOCAL_ENDPOINT};
+///
+/// # async fn example() -> anyhow::Result<()> {
+/// let ddb = DynamoDb::new_local(LOCAL_ENDPOINT);
+/// let tasks: Vec<_> = (0..4)
+///     .map(|n| {
+///         let ddb = ddb.clone();
+///         tokio::spawn(async move {
+///             ddb.put_item("users", Item::new().set_string("user_id", n.to_string()))
+///                 .await
+///         })
+///     })
+///     .collect();
+/// for task in tasks {
+///     task.await??;
+/// }
+/// # Ok(())
+/// # }
+/// ```
+///
 /// # Performance Considerations
 ///
 /// - Use `query` instead of `scan` when possible for better performance
@@ -86,32 +339,367 @@ use crate::dynamodb::{Item, Table};
 ///
 /// Most methods return `Result<T, anyhow::Error>`, allowing for flexible error handling.
 /// Use the `?` operator or match on the `Result` to handle potential errors.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct DynamoDb {
     client: Client,
+    /// The endpoint the client was configured with, if any. Stream readers
+    /// send their requests there too.
+    endpoint_url: Option<String>,
+    /// The client's credentials, which the DynamoDB Streams client signs
+    /// with as well.
+    credentials: Option<SharedCredentialsProvider>,
+    /// Answers `check_auth`'s "who am I" question. `None` for clients made
+    /// by `from_client`, whose credentials can't be read back out.
+    sts: Option<aws_sdk_sts::Client>,
+    /// The endpoint, if it's DynamoDB Local on this machine.
+    local_endpoint: Option<String>,
+    /// Counts every request, when set by `with_metrics`.
+    metrics: Option<Arc<Metrics>>,
+    /// Shared by clones, so a table is only described once between them.
+    key_schemas: Arc<KeySchemaCache>,
 }
 
 impl DynamoDb {
     /// Creates a new `DynamoDb` instance.
     pub fn new(sdk_config: &aws_config::SdkConfig) -> Self {
         Self {
-            client: Client::new(sdk_config),
+            client: sdk_client(sdk_config),
+            endpoint_url: sdk_config.endpoint_url().map(str::to_string),
+            credentials: sdk_config.credentials_provider(),
+            sts: Some(aws_sdk_sts::Client::new(sdk_config)),
+            local_endpoint: sdk_config
+                .endpoint_url()
+                .filter(|endpoint| is_local_endpoint(endpoint))
+                .map(str::to_string),
+            metrics: None,
+            key_schemas: Arc::new(KeySchemaCache::new(KEY_SCHEMA_TTL)),
         }
     }
 
-    /// Verifies authentication by attempting to list tables.
-    pub async fn check_auth(&self) -> Result<()> {
-        self.client.list_tables().send().await.map_err(|e| {
-            error!("Authentication failed: {}", e);
-            anyhow!("Authentication failed")
-        })?;
-        info!("Authentication successful");
-        Ok(())
+    /// Starts a [`DynamoDbBuilder`], for a client configured from explicit
+    /// settings rather than the environment.
+    pub fn builder() -> DynamoDbBuilder {
+        DynamoDbBuilder::default()
+    }
+
+    /// Creates a client for DynamoDB Local at `endpoint` (usually
+    /// [`LOCAL_ENDPOINT`]), with dummy credentials and a fixed region so no
+    /// AWS environment variables or `.env` are needed.
+    pub fn new_local(endpoint: &str) -> Self {
+        let sdk_config = aws_config::SdkConfig::builder()
+            .behavior_version(aws_config::BehaviorVersion::latest())
+            .endpoint_url(endpoint)
+            .region(aws_config::Region::new(LOCAL_REGION))
+            .credentials_provider(SharedCredentialsProvider::new(local_credentials()))
+            .build();
+        Self {
+            client: sdk_client(&sdk_config),
+            endpoint_url: Some(endpoint.to_string()),
+            credentials: sdk_config.credentials_provider(),
+            sts: Some(aws_sdk_sts::Client::new(&sdk_config)),
+            local_endpoint: Some(endpoint.to_string()),
+            metrics: None,
+            key_schemas: Arc::new(KeySchemaCache::new(KEY_SCHEMA_TTL)),
+        }
+    }
+
+    /// Wraps a client built elsewhere as given: [`DynamoDb::client`]
+    /// returns that same client, with its settings, interceptors and
+    /// connection pool.
+    ///
+    /// The SDK doesn't let a client's endpoint or credentials be read back,
+    /// so a wrapped client differs from one made by [`DynamoDb::new`]:
+    ///
+    /// - [`DynamoDb::check_auth`] only checks the connection and returns
+    ///   `Ok(None)`, as there's no STS client to ask who the credentials
+    ///   belong to.
+    /// - [`DynamoDb::is_local`] is `false`, whatever the endpoint.
+    /// - [`DynamoDb::stream_reader`] and [`DynamoDb::subscribe_changes`]
+    ///   take their credentials and endpoint from the environment.
+    /// - Retries aren't traced: that needs an interceptor of this crate's,
+    ///   and adding one means building a new client from the given one's
+    ///   settings, as the `with_*` methods that add interceptors do.
+    pub fn from_client(client: Client) -> Self {
+        Self {
+            client,
+            endpoint_url: None,
+            credentials: None,
+            sts: None,
+            local_endpoint: None,
+            metrics: None,
+            key_schemas: Arc::new(KeySchemaCache::new(KEY_SCHEMA_TTL)),
+        }
+    }
+
+    /// The SDK client underneath, for operations this crate doesn't wrap.
+    ///
+    /// Requests made with it go through the same connections and
+    /// interceptors, so they count towards [`DynamoDb::with_metrics`] and
+    /// show up in the wire log.
+    pub fn client(&self) -> &Client {
+        &self.client
+    }
+
+    /// Logs the region, retry and timeout settings the client was configured
+    /// with. Unset settings are logged as `None`: the SDK's defaults apply.
+    pub(crate) fn log_settings(&self) {
+        let config = self.client.config();
+        let retry = config.retry_config();
+        let timeouts = config.timeout_config();
+        info!(
+            region = ?config.region().map(|region| region.as_ref()),
+            retry_mode = ?retry.map(RetryConfig::mode),
+            max_attempts = ?retry.map(RetryConfig::max_attempts),
+            connect_timeout = ?timeouts.and_then(|timeouts| timeouts.connect_timeout()),
+            read_timeout = ?timeouts.and_then(|timeouts| timeouts.read_timeout()),
+            operation_timeout = ?timeouts.and_then(|timeouts| timeouts.operation_timeout()),
+            "DynamoDB client settings"
+        );
+    }
+
+    /// Returns `true` if the client talks to DynamoDB Local (or another
+    /// server on this machine) rather than AWS.
+    pub fn is_local(&self) -> bool {
+        self.local_endpoint.is_some()
+    }
+
+    /// Returns a client that also records every request and response to a
+    /// cassette file at `path`, for [`DynamoDb::with_replay`] to play back.
+    /// The file is overwritten.
+    #[cfg(any(test, feature = "test-util"))]
+    pub fn with_recording(self, path: impl Into<std::path::PathBuf>) -> Result<Self> {
+        use aws_smithy_http_client::tls::{rustls_provider::CryptoMode, Provider};
+
+        let inner = self.client.config().http_client().unwrap_or_else(|| {
+            aws_smithy_http_client::Builder::new()
+                .tls_provider(Provider::Rustls(CryptoMode::AwsLc))
+                .build_https()
+        });
+        let http_client = crate::dynamodb::cassette::recording_client(inner, path)?;
+        Ok(self.with_http_client(http_client))
+    }
+
+    /// Returns a client that answers every request from the cassette at
+    /// `path` instead of the network, failing requests it has no recording
+    /// of. Credentials and region are still needed to sign requests, but
+    /// nothing checks them; [`DynamoDb::new_local`]'s will do.
+    #[cfg(any(test, feature = "test-util"))]
+    pub fn with_replay(self, path: impl AsRef<std::path::Path>) -> Result<Self> {
+        let http_client = crate::dynamodb::cassette::replaying_client(path)?;
+        Ok(self.with_http_client(http_client))
+    }
+
+    /// Returns a client that retries throttled requests and server errors
+    /// until `max_attempts` attempts (counting the first, and at least 1)
+    /// have been made. Before each retry it waits a random time of up to
+    /// `base_delay` doubled per retry, capped at `max_delay` ("full
+    /// jitter").
+    ///
+    /// Errors another attempt wouldn't fix, such as a `ValidationException`
+    /// or a failed condition, are returned at once. Without a policy the
+    /// SDK's standard one applies: 3 attempts from a 1 second base delay,
+    /// or as `AWS_MAX_ATTEMPTS` says. Retries are logged with their attempt
+    /// number.
+    pub fn with_retry_policy(
+        self,
+        max_attempts: u32,
+        base_delay: Duration,
+        max_delay: Duration,
+    ) -> Self {
+        self.with_retry_config(retry_policy(max_attempts, base_delay, max_delay))
+    }
+
+    /// Returns a client that makes every request once, returning throttling
+    /// and server errors to the caller.
+    pub fn without_retries(self) -> Self {
+        self.with_retry_config(RetryConfig::disabled())
+    }
+
+    /// Returns a client that records the operation, latency, items,
+    /// consumed capacity and outcome of every request in `metrics`.
+    ///
+    /// Capacity is only known for requests that ask DynamoDB to return it.
+    pub fn with_metrics(self, metrics: Arc<Metrics>) -> Self {
+        let config = self
+            .client
+            .config()
+            .to_builder()
+            .interceptor(MetricsRecorder(metrics.clone()))
+            .build();
+        Self {
+            client: Client::from_conf(config),
+            metrics: Some(metrics),
+            ..self
+        }
+    }
+
+    /// Returns a client that logs every request and response at DEBUG, with
+    /// credentials redacted, when `enabled`; see [`WireLog`].
+    pub fn with_wire_logging(self, enabled: bool) -> Self {
+        if enabled {
+            self.with_wire_log(WireLog::new())
+        } else {
+            self
+        }
+    }
+
+    /// Returns a client that logs its requests and responses as `wire_log`
+    /// says.
+    pub fn with_wire_log(self, wire_log: WireLog) -> Self {
+        let config = self
+            .client
+            .config()
+            .to_builder()
+            .interceptor(wire_log)
+            .build();
+        Self {
+            client: Client::from_conf(config),
+            ..self
+        }
+    }
+
+    /// Returns a client that asks DynamoDB how much capacity each read and
+    /// write consumed, so [`Metrics`] can add it up by table. Requests that
+    /// already ask keep their own setting.
+    ///
+    /// DynamoDB reports capacity at no cost, but it makes every response a
+    /// little larger.
+    pub fn with_consumed_capacity(self) -> Self {
+        let config = self
+            .client
+            .config()
+            .to_builder()
+            .interceptor(ReturnTotalCapacity)
+            .build();
+        Self {
+            client: Client::from_conf(config),
+            ..self
+        }
+    }
+
+    /// Returns a client that trusts a cached key schema for `ttl` before
+    /// describing the table again. The new client starts with an empty
+    /// cache.
+    pub fn with_key_schema_ttl(self, ttl: Duration) -> Self {
+        Self {
+            key_schemas: Arc::new(KeySchemaCache::new(ttl)),
+            ..self
+        }
+    }
+
+    /// The metrics set by [`DynamoDb::with_metrics`], if any.
+    pub fn metrics(&self) -> Option<&Metrics> {
+        self.metrics.as_deref()
+    }
+
+    fn with_retry_config(self, retry_config: RetryConfig) -> Self {
+        let config = self
+            .client
+            .config()
+            .to_builder()
+            .retry_config(retry_config)
+            .build();
+        Self {
+            client: Client::from_conf(config),
+            ..self
+        }
+    }
+
+    #[cfg(any(test, feature = "test-util"))]
+    fn with_http_client(self, http_client: aws_sdk_dynamodb::config::SharedHttpClient) -> Self {
+        let config = self
+            .client
+            .config()
+            .to_builder()
+            .http_client(http_client.clone())
+            .build();
+        let sts = self.sts.map(|sts| {
+            aws_sdk_sts::Client::from_conf(
+                sts.config().to_builder().http_client(http_client).build(),
+            )
+        });
+        Self {
+            client: Client::from_conf(config),
+            sts,
+            ..self
+        }
+    }
+
+    /// Returns a client with the same settings but its own connections, for
+    /// use on another runtime than the one this client's connections run on.
+    #[cfg(any(test, feature = "blocking"))]
+    pub(crate) fn with_new_connections(&self) -> Self {
+        Self {
+            client: Client::from_conf(self.client.config().clone()),
+            endpoint_url: self.endpoint_url.clone(),
+            credentials: self.credentials.clone(),
+            sts: self
+                .sts
+                .as_ref()
+                .map(|sts| aws_sdk_sts::Client::from_conf(sts.config().clone())),
+            local_endpoint: self.local_endpoint.clone(),
+            metrics: self.metrics.clone(),
+            key_schemas: self.key_schemas.clone(),
+        }
+    }
+
+    /// Returns who the client is authenticated as, from STS
+    /// `GetCallerIdentity`, after checking that it can also list tables.
+    ///
+    /// DynamoDB Local accepts any credentials and has no STS, so for it
+    /// only the connection is checked and `None` is returned. The same goes
+    /// for a client from [`DynamoDb::from_client`], which has no STS client
+    /// to ask. Errors keep
+    /// the SDK error as their source, so an expired session token or a
+    /// skewed clock shows up as such.
+    #[instrument(skip_all, fields(local = self.local_endpoint.is_some()))]
+    pub async fn check_auth(&self) -> Result<Option<AuthInfo>> {
+        if let Some(endpoint) = &self.local_endpoint {
+            self.client
+                .list_tables()
+                .limit(1)
+                .send()
+                .await
+                .map_err(request_failed(None, None))
+                .with_context(|| {
+                    format!("Couldn't reach DynamoDB Local at {endpoint}; is it running?")
+                })?;
+            info!("Connected to DynamoDB Local at {endpoint}");
+            return Ok(None);
+        }
+
+        let Some(sts) = &self.sts else {
+            self.client
+                .list_tables()
+                .limit(1)
+                .send()
+                .await
+                .map_err(request_failed(None, None))
+                .context("Couldn't list tables")?;
+            return Ok(None);
+        };
+        let auth = AuthInfo::try_from(
+            sts.get_caller_identity()
+                .send()
+                .await
+                .map_err(request_failed(None, None))
+                .context("Authentication failed")?,
+        )?;
+        self.client
+            .list_tables()
+            .limit(1)
+            .send()
+            .await
+            .map_err(request_failed(None, None))
+            .with_context(|| format!("Authenticated as {}, but couldn't list tables", auth.arn))?;
+        info!("Authenticated as {auth}");
+        Ok(Some(auth))
     }
 
     // --- Table Operations ---
 
     /// Creates a table if it doesn't exist.
+    #[instrument(skip_all, fields(table_name = %table.name(), operation = "CreateTable"))]
     pub async fn create_table_if_not_exists(
         &self,
         table: &Table<'_>,
@@ -121,61 +709,157 @@ impl DynamoDb {
             return Ok(None);
         }
 
-        let mut attribute_definitions = vec![AttributeDefinition::builder()
-            .attribute_name(table.partition_key())
-            .attribute_type(ScalarAttributeType::S)
-            .build()?];
-
-        let mut key_schema = vec![KeySchemaElement::builder()
-            .attribute_name(table.partition_key())
-            .key_type(KeyType::Hash)
-            .build()?];
+        let output = self
+            .create_table_request(table)?
+            .send()
+            .await
+            .map_err(request_failed(Some(table.name()), None))?;
+        Ok(Some(output))
+    }
 
-        if let Some(sort_key) = table.sort_key() {
-            attribute_definitions.push(
-                AttributeDefinition::builder()
-                    .attribute_name(sort_key)
-                    .attribute_type(ScalarAttributeType::S)
-                    .build()?,
-            );
-            key_schema.push(
-                KeySchemaElement::builder()
-                    .attribute_name(sort_key)
-                    .key_type(KeyType::Range)
-                    .build()?,
-            );
-        }
+    /// Builds the `CreateTable` request for `table`, with its keys, indexes
+    /// and capacity.
+    pub(crate) fn create_table_request(
+        &self,
+        table: &Table<'_>,
+    ) -> Result<CreateTableFluentBuilder> {
+        let definition = TableDefinition::new(table)?;
 
-        let output = self
+        Ok(self
             .client
             .create_table()
             .table_name(table.name())
-            .billing_mode(BillingMode::PayPerRequest)
-            .set_attribute_definitions(Some(attribute_definitions))
-            .set_key_schema(Some(key_schema))
-            .send()
-            .await?;
-        Ok(Some(output))
+            .billing_mode(definition.billing_mode)
+            .set_provisioned_throughput(definition.provisioned_throughput)
+            .set_attribute_definitions(Some(definition.attribute_definitions))
+            .set_key_schema(Some(definition.key_schema))
+            .set_global_secondary_indexes(definition.global_secondary_indexes))
     }
 
-    /// Deletes a table if it exists.
+    /// Creates a table if it doesn't exist, then waits up to `timeout` for it
+    /// to become active.
+    #[instrument(skip_all, fields(table_name = %table.name()))]
+    pub async fn create_table_if_not_exists_and_wait(
+        &self,
+        table: &Table<'_>,
+        timeout: Duration,
+    ) -> Result<Option<CreateTableOutput>> {
+        let output = self.create_table_if_not_exists(table).await?;
+        self.wait_for_table_active(table.name(), timeout).await?;
+        Ok(output)
+    }
+
+    /// Deletes a table, failing if it doesn't exist.
+    ///
+    /// Deletion continues in the background after this returns; see
+    /// [`DynamoDb::delete_table_if_exists_and_wait`].
+    #[instrument(skip_all, fields(table_name = %table_name, operation = "DeleteTable"))]
     pub async fn delete_table(&self, table_name: &str) -> Result<()> {
         self.client
             .delete_table()
             .table_name(table_name)
             .send()
-            .await?;
+            .await
+            .map_err(table_not_found(table_name))?;
+        self.invalidate_key_schema(table_name);
         info!("Table '{table_name}' deleted");
         Ok(())
     }
 
+    /// Deletes a table if it exists.
+    ///
+    /// Returns `false` if there was no table to delete.
+    #[instrument(skip_all, fields(table_name = %table_name, operation = "DeleteTable"))]
+    pub async fn delete_table_if_exists(&self, table_name: &str) -> Result<bool> {
+        match self
+            .client
+            .delete_table()
+            .table_name(table_name)
+            .send()
+            .await
+        {
+            Ok(_) => {
+                self.invalidate_key_schema(table_name);
+                info!("Table '{table_name}' deleted");
+                Ok(true)
+            }
+            Err(e)
+                if e.as_service_error()
+                    .is_some_and(|e| e.is_resource_not_found_exception()) =>
+            {
+                info!("Table '{table_name}' does not exist");
+                Ok(false)
+            }
+            Err(e) => Err(table_not_found(table_name)(e)),
+        }
+    }
+
+    /// Deletes a table if it exists, then waits up to `timeout` for it to be
+    /// fully removed, so the name can be reused straight away.
+    ///
+    /// Returns `false` if there was no table to delete.
+    #[instrument(skip_all, fields(table_name = %table_name))]
+    pub async fn delete_table_if_exists_and_wait(
+        &self,
+        table_name: &str,
+        timeout: Duration,
+    ) -> Result<bool> {
+        let deleted = self.delete_table_if_exists(table_name).await?;
+        self.wait_for_table_deleted(table_name, timeout).await?;
+        Ok(deleted)
+    }
+
     /// Checks if a table exists.
+    ///
+    /// Only a `ResourceNotFoundException` means the table is missing; any
+    /// other error (throttling, bad credentials, ...) is returned as-is.
+    #[instrument(skip_all, fields(table_name = %table_name))]
     pub async fn table_exists(&self, table_name: &str) -> Result<bool> {
-        let tables = self.client.list_tables().send().await?;
-        Ok(tables.table_names().contains(&table_name.to_string()))
+        Ok(self.try_describe_table(table_name).await?.is_some())
+    }
+
+    /// Lists the names of every table, following `LastEvaluatedTableName`
+    /// across pages.
+    #[instrument(skip_all, fields(operation = "ListTables", count = Empty))]
+    pub async fn list_tables(&self) -> Result<Vec<String>> {
+        let mut table_names = Vec::new();
+        let mut exclusive_start_table_name = None;
+
+        for number in 1.. {
+            let page = page_span(number);
+            let response = self
+                .client
+                .list_tables()
+                .set_exclusive_start_table_name(exclusive_start_table_name)
+                .send()
+                .instrument(page.clone())
+                .await
+                .map_err(request_failed(None, None))?;
+
+            let names = response.table_names.unwrap_or_default();
+            page.record("count", names.len());
+            table_names.extend(names);
+
+            exclusive_start_table_name = response.last_evaluated_table_name;
+            if exclusive_start_table_name.is_none() {
+                break;
+            }
+        }
+
+        Span::current().record("count", table_names.len());
+        Ok(table_names)
+    }
+
+    /// Lists the names of the tables starting with `prefix`.
+    #[instrument(skip_all, fields(prefix = %prefix))]
+    pub async fn list_tables_with_prefix(&self, prefix: &str) -> Result<Vec<String>> {
+        let mut table_names = self.list_tables().await?;
+        table_names.retain(|name| name.starts_with(prefix));
+        Ok(table_names)
     }
 
     /// Retrieves table description.
+    #[instrument(skip_all, fields(table_name = %table_name, operation = "DescribeTable"))]
     pub async fn describe_table(
         &self,
         table_name: &str,
@@ -185,147 +869,2184 @@ impl DynamoDb {
             .table_name(table_name)
             .send()
             .await
-            .map_err(Into::into)
+            .map_err(table_not_found(table_name))
     }
 
-    // --- Item Operations ---
-
-    /// Puts an item into a DynamoDB table.
-    pub async fn put_item(&self, table_name: &str, item: Item) -> Result<()> {
-        self.client
-            .put_item()
-            .table_name(table_name)
-            .set_item(Some(item.attributes))
-            .send()
-            .await?;
-
-        info!("Item added to '{table_name}'");
-        Ok(())
+    /// Retrieves a table's description as a [`TableInfo`], failing with
+    /// [`TableNotFound`] if the table doesn't exist.
+    #[instrument(skip_all, fields(table_name = %table_name))]
+    pub async fn table_info(&self, table_name: &str) -> Result<TableInfo> {
+        self.try_describe_table(table_name).await?.ok_or_else(|| {
+            TableNotFound {
+                table_name: table_name.to_string(),
+            }
+            .into()
+        })
     }
 
-    /// Gets an item from a DynamoDB table.
-    pub async fn get_item(&self, table_name: &str, key: Item) -> Result<Option<Item>> {
-        let response = self
-            .client
-            .get_item()
-            .table_name(table_name)
-            .set_key(Some(key.attributes))
-            .send()
-            .await?;
+    /// Retrieves a table's description as a [`TableInfo`], or `None` if the
+    /// table doesn't exist.
+    #[instrument(skip_all, fields(table_name = %table_name))]
+    pub async fn try_describe_table(&self, table_name: &str) -> Result<Option<TableInfo>> {
+        self.describe_table_if_exists(table_name)
+            .await?
+            .map(TableInfo::try_from)
+            .transpose()
+    }
 
-        Ok(response.item.map(|attrs| Item { attributes: attrs }))
+    /// The names of a table's key attributes.
+    ///
+    /// Key schemas are cached for five minutes, or as long as
+    /// [`DynamoDb::with_key_schema_ttl`] says, so only the first lookup of a
+    /// table sends a `DescribeTable`; lookups made while it's in flight wait
+    /// for it. Clones share the cache. Deleting a table through this client
+    /// forgets its schema; use [`DynamoDb::invalidate_key_schema`] when it's
+    /// replaced some other way.
+    #[instrument(skip_all, fields(table_name = %table_name))]
+    pub async fn key_schema(&self, table_name: &str) -> Result<KeySchema> {
+        self.key_schemas
+            .get(table_name, || async {
+                let description = self.describe_table(table_name).await?;
+                let table = description
+                    .table()
+                    .ok_or_else(|| anyhow!("Table '{table_name}' has no description"))?;
+                let (partition_key, sort_key) =
+                    key_attributes(table_name, Some(table.key_schema()))?;
+                Ok(KeySchema {
+                    partition_key,
+                    sort_key,
+                })
+            })
+            .await
     }
 
-    /// Updates an item in a DynamoDB table.
-    pub async fn update_item(&self, table_name: &str, key: Item, updates: Item) -> Result<()> {
-        let mut update_expression = String::new();
-        let mut expression_attribute_names = HashMap::new();
-        let mut expression_attribute_values = HashMap::new();
+    /// Forgets the cached key schema of `table_name`, so the next
+    /// [`DynamoDb::key_schema`] describes the table again.
+    pub fn invalidate_key_schema(&self, table_name: &str) {
+        self.key_schemas.invalidate(table_name);
+    }
 
-        for (i, (attr_name, attr_value)) in updates.attributes.iter().enumerate() {
-            let placeholder = format!("#attr{}", i);
-            let value_placeholder = format!(":val{}", i);
+    /// Loads an existing table as a [`Table`], for tables that weren't
+    /// declared in code.
+    ///
+    /// The keys and capacity come from `DescribeTable`, with the key types
+    /// recorded in the table's schema. Given a `sample_size`, the schema is
+    /// also inferred from that many scanned items (see [`Schema::infer`]).
+    /// Global secondary indexes aren't loaded.
+    #[instrument(skip_all, fields(table_name = %table_name, sample_size = ?sample_size))]
+    pub async fn load_table(
+        &self,
+        table_name: &str,
+        sample_size: Option<usize>,
+    ) -> Result<Table<'static>> {
+        let description = self
+            .describe_table_if_exists(table_name)
+            .await?
+            .ok_or_else(|| TableNotFound {
+                table_name: table_name.to_string(),
+            })?;
+        let info = TableInfo::try_from(description.clone())?;
 
-            if i > 0 {
-                update_expression.push_str(", ");
+        let mut schema = match sample_size {
+            Some(sample_size) => {
+                let params = ScanParams {
+                    table_name,
+                    limit: Some(i32::try_from(sample_size).unwrap_or(i32::MAX)),
+                    ..Default::default()
+                };
+                Schema::infer(&self.scan_all(params, Some(sample_size), None).await?)
             }
-            update_expression.push_str(&format!("{} = {}", placeholder, value_placeholder));
-
-            expression_attribute_names.insert(placeholder, attr_name.clone());
-            expression_attribute_values.insert(value_placeholder, attr_value.clone());
+            None => Schema::new(),
+        };
+        for definition in description.attribute_definitions() {
+            let field_type = match definition.attribute_type() {
+                ScalarAttributeType::N => FieldType::Number,
+                ScalarAttributeType::S => FieldType::String,
+                _ => continue,
+            };
+            schema = schema.add_field(definition.attribute_name(), field_type);
         }
 
+        let capacity = match (&info.billing_mode, description.provisioned_throughput()) {
+            (BillingMode::Provisioned, Some(throughput)) => Capacity::Provisioned {
+                read_capacity_units: throughput.read_capacity_units().unwrap_or_default(),
+                write_capacity_units: throughput.write_capacity_units().unwrap_or_default(),
+            },
+            _ => Capacity::OnDemand,
+        };
+
+        Ok(Table::owned(info.name, info.partition_key, info.sort_key)
+            .with_schema(schema)
+            .with_capacity(capacity))
+    }
+
+    /// Adds a global secondary index to an existing table.
+    ///
+    /// Key attribute types come from `table`'s schema, as in
+    /// [`DynamoDb::create_table_if_not_exists`], and all attributes are
+    /// projected. DynamoDB backfills the index in the background; use
+    /// [`DynamoDb::wait_for_index_active`] before querying it.
+    #[instrument(
+        skip_all,
+        fields(table_name = %table.name(), index_name = %index.name(), operation = "UpdateTable")
+    )]
+    pub async fn create_gsi(
+        &self,
+        table: &Table<'_>,
+        index: &crate::dynamodb::GlobalSecondaryIndex<'_>,
+    ) -> Result<()> {
+        let mut attribute_definitions = Vec::new();
+        let definition = index_definition(
+            table,
+            index,
+            &mut attribute_definitions,
+            provisioned_throughput(table.capacity())?,
+        )?;
+
         self.client
-            .update_item()
-            .table_name(table_name)
-            .set_key(Some(key.attributes))
-            .update_expression(format!("SET {}", update_expression))
-            .set_expression_attribute_names(Some(expression_attribute_names))
-            .set_expression_attribute_values(Some(expression_attribute_values))
+            .update_table()
+            .table_name(table.name())
+            .set_attribute_definitions(Some(attribute_definitions))
+            .global_secondary_index_updates(
+                GlobalSecondaryIndexUpdate::builder()
+                    .create(
+                        CreateGlobalSecondaryIndexAction::builder()
+                            .index_name(definition.index_name)
+                            .set_key_schema(Some(definition.key_schema))
+                            .set_projection(definition.projection)
+                            .set_provisioned_throughput(definition.provisioned_throughput)
+                            .build()?,
+                    )
+                    .build(),
+            )
             .send()
-            .await?;
-
-        info!("Item updated in '{table_name}'");
+            .await
+            .map_err(table_not_found(table.name()))?;
+        info!(
+            "Creating index '{}' on table '{}'",
+            index.name(),
+            table.name()
+        );
         Ok(())
     }
 
-    /// Deletes an item from a DynamoDB table.
-    pub async fn delete_item(&self, table_name: &str, key: Item) -> Result<()> {
+    /// Removes a global secondary index from a table.
+    #[instrument(
+        skip_all,
+        fields(table_name = %table_name, index_name = %index_name, operation = "UpdateTable")
+    )]
+    pub async fn delete_gsi(&self, table_name: &str, index_name: &str) -> Result<()> {
         self.client
-            .delete_item()
+            .update_table()
             .table_name(table_name)
-            .set_key(Some(key.attributes))
+            .global_secondary_index_updates(
+                GlobalSecondaryIndexUpdate::builder()
+                    .delete(
+                        DeleteGlobalSecondaryIndexAction::builder()
+                            .index_name(index_name)
+                            .build()?,
+                    )
+                    .build(),
+            )
             .send()
-            .await?;
-
-        info!("Item deleted from '{table_name}'");
+            .await
+            .map_err(table_not_found(table_name))?;
+        info!("Deleting index '{index_name}' from table '{table_name}'");
         Ok(())
     }
 
-    // --- Query and Scan Operations ---
-
-    /// Scans a table for items.
-    pub async fn scan_table(
+    /// Waits up to `timeout` for a global secondary index to report `ACTIVE`,
+    /// i.e. for its backfill to finish.
+    ///
+    /// An index that isn't listed yet is assumed to still be on its way.
+    #[instrument(skip_all, fields(table_name = %table_name, index_name = %index_name))]
+    pub async fn wait_for_index_active(
         &self,
         table_name: &str,
-    ) -> Result<Vec<HashMap<String, AttributeValue>>> {
+        index_name: &str,
+        timeout: Duration,
+    ) -> Result<()> {
+        let goal = format!("finish building index '{index_name}'");
+        self.wait_for_table(table_name, timeout, &goal, |table| {
+            table
+                .map(TableDescription::global_secondary_indexes)
+                .unwrap_or_default()
+                .iter()
+                .any(|index| {
+                    index.index_name() == Some(index_name)
+                        && index.index_status() == Some(&IndexStatus::Active)
+                })
+        })
+        .await
+    }
+
+    // --- Stream Operations ---
+
+    /// Turns on the table's stream with the given view type.
+    ///
+    /// DynamoDB creates a new stream (with a new ARN) each time streams are
+    /// enabled; records from a previous stream aren't carried over.
+    #[instrument(
+        skip_all,
+        fields(table_name = %table_name, view_type = %view_type, operation = "UpdateTable")
+    )]
+    pub async fn enable_stream(&self, table_name: &str, view_type: StreamViewType) -> Result<()> {
+        self.enable_stream_request(table_name, view_type.clone())?
+            .send()
+            .await
+            .map_err(table_not_found(table_name))?;
+        info!("Enabled {view_type} stream on table '{table_name}'");
+        Ok(())
+    }
+
+    /// Builds the `UpdateTable` request that turns on a table's stream.
+    pub(crate) fn enable_stream_request(
+        &self,
+        table_name: &str,
+        view_type: StreamViewType,
+    ) -> Result<UpdateTableFluentBuilder> {
+        Ok(self
+            .client
+            .update_table()
+            .table_name(table_name)
+            .stream_specification(
+                StreamSpecification::builder()
+                    .stream_enabled(true)
+                    .stream_view_type(view_type)
+                    .build()?,
+            ))
+    }
+
+    /// Returns the ARN of the table's latest stream, failing with
+    /// [`StreamsDisabled`] if the table has no stream enabled.
+    #[instrument(skip_all, fields(table_name = %table_name))]
+    pub async fn get_stream_arn(&self, table_name: &str) -> Result<String> {
+        let table = self.table_info(table_name).await?;
+        table.stream_arn.ok_or_else(|| {
+            StreamsDisabled {
+                table_name: table_name.to_string(),
+            }
+            .into()
+        })
+    }
+
+    /// Opens a [`StreamReader`] on the table's latest stream, which reads
+    /// the changes made from now on. Fails with [`StreamsDisabled`] if the
+    /// table has no stream enabled.
+    #[instrument(skip_all, fields(table_name = %table_name))]
+    pub async fn stream_reader(&self, table_name: &str) -> Result<StreamReader> {
+        self.open_stream_reader(table_name, &InMemoryCheckpoints::default())
+            .await
+    }
+
+    /// Opens a reader on the table's latest stream, resuming each shard after
+    /// its checkpoint in `checkpoints`.
+    async fn open_stream_reader(
+        &self,
+        table_name: &str,
+        checkpoints: &dyn CheckpointStore,
+    ) -> Result<StreamReader> {
+        let stream_arn = self.get_stream_arn(table_name).await?;
+        StreamReader::open(self.streams_client().await, stream_arn, checkpoints).await
+    }
+
+    /// Calls `handler` with every change made to the table from now on,
+    /// from a background task, until `cancel` is cancelled.
+    ///
+    /// All of the stream's shards are polled, including those that split off
+    /// while the subscription runs; the changes to any one item reach the
+    /// handler in order. After the handler returns for a record, its
+    /// sequence number is saved in `checkpoints`, and a subscription started
+    /// with the same checkpoints resumes after it. Delivery is
+    /// at-least-once (see [`CheckpointStore`]), so handlers should be
+    /// idempotent.
+    ///
+    /// Fails straight away with [`StreamsDisabled`] if the table has no
+    /// stream enabled. The returned task ends with `Ok` once cancelled, or
+    /// with the error that stopped it. Throttling and server errors are
+    /// retried by the Streams client first, like any other request.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use aws_dynamodb_testing::dynamodb::{DynamoDb, InMemoryCheckpoints};
+    /// # use std::sync::Arc;
+    /// # use tokio_util::sync::CancellationToken;
+    /// # async fn example(client: &DynamoDb) -> anyhow::Result<()> {
+    /// let cancel = CancellationToken::new();
+    /// let subscription = client
+    ///     .subscribe_changes(
+    ///         "orders",
+    ///         Arc::new(InMemoryCheckpoints::default()),
+    ///         cancel.clone(),
+    ///         |record| println!("{} {:?}", record.event_type, record.keys),
+    ///     )
+    ///     .await?;
+    /// // ...
+    /// cancel.cancel();
+    /// subscription.await??;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[instrument(skip_all, fields(table_name = %table_name))]
+    pub async fn subscribe_changes(
+        &self,
+        table_name: &str,
+        checkpoints: Arc<dyn CheckpointStore>,
+        cancel: CancellationToken,
+        handler: impl Fn(StreamRecord) + Send + 'static,
+    ) -> Result<JoinHandle<Result<()>>> {
+        // Boxed: the reader's future nests deep enough to overflow the
+        // compiler's layout depth limit when inlined here.
+        let reader = Box::pin(self.open_stream_reader(table_name, checkpoints.as_ref())).await?;
+        Ok(tokio::spawn(
+            run_subscription(reader, checkpoints, cancel, handler).in_current_span(),
+        ))
+    }
+
+    /// Builds a DynamoDB Streams client that shares this client's region,
+    /// endpoint, credentials, HTTP client, retry and timeout settings and
+    /// interceptors.
+    ///
+    /// The endpoint and credentials of a client from
+    /// [`DynamoDb::from_client`] can't be read back, so for it they're
+    /// loaded from the environment, as `aws_config` loads them.
+    async fn streams_client(&self) -> aws_sdk_dynamodbstreams::Client {
+        let config = self.client.config();
+        let mut builder = match &self.credentials {
+            Some(credentials) => {
+                let mut builder = aws_sdk_dynamodbstreams::Config::builder()
+                    .credentials_provider(credentials.clone());
+                builder.set_endpoint_url(self.endpoint_url.clone());
+                builder
+            }
+            None => aws_sdk_dynamodbstreams::config::Builder::from(
+                &aws_config::defaults(aws_config::BehaviorVersion::latest())
+                    .region(config.region().cloned())
+                    .load()
+                    .await,
+            ),
+        };
+        builder
+            .set_region(config.region().cloned())
+            .set_http_client(config.http_client())
+            .set_retry_config(config.retry_config().cloned())
+            .set_timeout_config(config.timeout_config().cloned())
+            .set_sleep_impl(config.sleep_impl())
+            .set_time_source(config.time_source());
+        for interceptor in config.interceptors() {
+            builder.push_interceptor(interceptor);
+        }
+        aws_sdk_dynamodbstreams::Client::from_conf(builder.build())
+    }
+
+    // --- Backup Operations ---
+
+    /// Creates an on-demand backup of a table.
+    #[instrument(
+        skip_all,
+        fields(table_name = %table_name, backup_name = %backup_name, operation = "CreateBackup")
+    )]
+    pub async fn create_backup(&self, table_name: &str, backup_name: &str) -> Result<BackupArn> {
+        let response = self
+            .create_backup_request(table_name, backup_name)
+            .send()
+            .await
+            .map_err(table_not_found(table_name))?;
+        let arn = response
+            .backup_details
+            .map(|details| BackupArn::new(details.backup_arn))
+            .ok_or_else(|| anyhow!("DynamoDB returned no details for backup '{backup_name}'"))?;
+
+        info!("Backup '{backup_name}' of '{table_name}' created: {arn}");
+        Ok(arn)
+    }
+
+    /// Builds the `CreateBackup` request.
+    pub(crate) fn create_backup_request(
+        &self,
+        table_name: &str,
+        backup_name: &str,
+    ) -> CreateBackupFluentBuilder {
+        self.client
+            .create_backup()
+            .table_name(table_name)
+            .backup_name(backup_name)
+    }
+
+    /// Lists the on-demand backups of a table, following
+    /// `LastEvaluatedBackupArn` across pages.
+    #[instrument(
+        skip_all,
+        fields(table_name = %table_name, operation = "ListBackups", count = Empty)
+    )]
+    pub async fn list_backups(&self, table_name: &str) -> Result<Vec<BackupInfo>> {
+        let mut backups = Vec::new();
+        let mut exclusive_start_backup_arn = None;
+
+        for number in 1.. {
+            let page = page_span(number);
+            let response = self
+                .list_backups_request(table_name)
+                .set_exclusive_start_backup_arn(exclusive_start_backup_arn)
+                .send()
+                .instrument(page.clone())
+                .await
+                .map_err(table_not_found(table_name))?;
+
+            let summaries = response.backup_summaries.unwrap_or_default();
+            page.record("count", summaries.len());
+            for summary in summaries {
+                backups.push(BackupInfo::try_from(summary)?);
+            }
+
+            exclusive_start_backup_arn = response.last_evaluated_backup_arn;
+            if exclusive_start_backup_arn.is_none() {
+                break;
+            }
+        }
+
+        Span::current().record("count", backups.len());
+        Ok(backups)
+    }
+
+    /// Builds the first-page `ListBackups` request for on-demand backups of a table.
+    pub(crate) fn list_backups_request(&self, table_name: &str) -> ListBackupsFluentBuilder {
+        self.client
+            .list_backups()
+            .table_name(table_name)
+            .backup_type(BackupTypeFilter::User)
+    }
+
+    /// Deletes an on-demand backup.
+    #[instrument(skip_all, fields(backup_arn = %backup_arn, operation = "DeleteBackup"))]
+    pub async fn delete_backup(&self, backup_arn: &BackupArn) -> Result<()> {
+        self.client
+            .delete_backup()
+            .backup_arn(backup_arn.as_str())
+            .send()
+            .await
+            .map_err(request_failed(None, None))?;
+        info!("Backup '{backup_arn}' deleted");
+        Ok(())
+    }
+
+    /// Starts restoring a backup into a new table.
+    ///
+    /// The new table is unusable until the restore finishes; see
+    /// [`DynamoDb::restore_table_from_backup_and_wait`].
+    #[instrument(
+        skip_all,
+        fields(
+            backup_arn = %backup_arn,
+            new_table_name = %new_table_name,
+            operation = "RestoreTableFromBackup",
+        )
+    )]
+    pub async fn restore_table_from_backup(
+        &self,
+        backup_arn: &BackupArn,
+        new_table_name: &str,
+    ) -> Result<()> {
+        self.restore_table_request(backup_arn, new_table_name)
+            .send()
+            .await
+            .map_err(request_failed(Some(new_table_name), None))?;
+        info!("Restoring backup '{backup_arn}' into '{new_table_name}'");
+        Ok(())
+    }
+
+    /// Restores a backup into a new table and waits up to `timeout` for the
+    /// table to become active.
+    #[instrument(skip_all, fields(backup_arn = %backup_arn, new_table_name = %new_table_name))]
+    pub async fn restore_table_from_backup_and_wait(
+        &self,
+        backup_arn: &BackupArn,
+        new_table_name: &str,
+        timeout: Duration,
+    ) -> Result<()> {
+        self.restore_table_from_backup(backup_arn, new_table_name)
+            .await?;
+        self.wait_for_table_active(new_table_name, timeout).await
+    }
+
+    /// Builds the `RestoreTableFromBackup` request.
+    pub(crate) fn restore_table_request(
+        &self,
+        backup_arn: &BackupArn,
+        new_table_name: &str,
+    ) -> RestoreTableFromBackupFluentBuilder {
+        self.client
+            .restore_table_from_backup()
+            .backup_arn(backup_arn.as_str())
+            .target_table_name(new_table_name)
+    }
+
+    // --- S3 Export and Import Operations ---
+
+    /// Starts exporting a table's current state to `s3://{bucket}/{prefix}`.
+    ///
+    /// Exports are read from the table's point-in-time recovery backups, so
+    /// this checks that PITR is enabled first and fails with
+    /// [`PointInTimeRecoveryDisabled`] if it isn't. The export runs in the
+    /// background; poll it with [`DynamoDb::describe_export`].
+    #[instrument(
+        skip_all,
+        fields(
+            table_name = %table_name,
+            bucket = %bucket,
+            prefix = %prefix,
+            operation = "ExportTableToPointInTime",
+        )
+    )]
+    pub async fn export_to_s3(
+        &self,
+        table_name: &str,
+        bucket: &str,
+        prefix: &str,
+        format: ExportFormat,
+    ) -> Result<ExportInfo> {
+        let backups = self
+            .client
+            .describe_continuous_backups()
+            .table_name(table_name)
+            .send()
+            .await
+            .map_err(table_not_found(table_name))?;
+        PointInTimeRecoveryDisabled::check(
+            table_name,
+            backups.continuous_backups_description.as_ref(),
+        )?;
+
+        let table_arn = self
+            .describe_table(table_name)
+            .await?
+            .table
+            .and_then(|table| table.table_arn)
+            .ok_or_else(|| anyhow!("DynamoDB returned no ARN for table '{table_name}'"))?;
+
+        let response = match self
+            .export_request(&table_arn, bucket, prefix, format)
+            .send()
+            .await
+        {
+            Ok(response) => response,
+            // PITR can still be switched off between the check and the export.
+            Err(e)
+                if e.as_service_error()
+                    .is_some_and(|e| e.is_point_in_time_recovery_unavailable_exception()) =>
+            {
+                return Err(PointInTimeRecoveryDisabled {
+                    table_name: table_name.to_string(),
+                }
+                .into());
+            }
+            Err(e) => return Err(table_not_found(table_name)(e)),
+        };
+        let export = ExportInfo::try_from(
+            response
+                .export_description
+                .ok_or_else(|| anyhow!("DynamoDB returned no export description"))?,
+        )?;
+
+        info!(
+            "Exporting '{table_name}' to s3://{bucket}/{prefix}: {} ({})",
+            export.arn, export.status
+        );
+        Ok(export)
+    }
+
+    /// Builds the `ExportTableToPointInTime` request.
+    pub(crate) fn export_request(
+        &self,
+        table_arn: &str,
+        bucket: &str,
+        prefix: &str,
+        format: ExportFormat,
+    ) -> ExportTableToPointInTimeFluentBuilder {
+        self.client
+            .export_table_to_point_in_time()
+            .table_arn(table_arn)
+            .s3_bucket(bucket)
+            .s3_prefix(prefix)
+            .export_format(format)
+    }
+
+    /// Reports the progress of an export started by [`DynamoDb::export_to_s3`].
+    #[instrument(skip_all, fields(export_arn = %export_arn, operation = "DescribeExport"))]
+    pub async fn describe_export(&self, export_arn: &str) -> Result<ExportInfo> {
+        let response = self
+            .client
+            .describe_export()
+            .export_arn(export_arn)
+            .send()
+            .await
+            .map_err(request_failed(None, None))?;
+        ExportInfo::try_from(
+            response
+                .export_description
+                .ok_or_else(|| anyhow!("DynamoDB returned no description for '{export_arn}'"))?,
+        )
+    }
+
+    /// Starts importing the data under `s3://{bucket}/{prefix}` into a new
+    /// table shaped like `table`.
+    ///
+    /// `ImportTable` always creates the table, so it must not exist yet. The
+    /// import runs in the background; poll it with
+    /// [`DynamoDb::describe_import`].
+    #[instrument(
+        skip_all,
+        fields(
+            table_name = %table.name(),
+            bucket = %bucket,
+            prefix = %prefix,
+            operation = "ImportTable",
+        )
+    )]
+    pub async fn import_from_s3(
+        &self,
+        table: &Table<'_>,
+        bucket: &str,
+        prefix: &str,
+        format: InputFormat,
+    ) -> Result<ImportInfo> {
+        let response = self
+            .import_request(table, bucket, prefix, format)?
+            .send()
+            .await
+            .map_err(request_failed(Some(table.name()), None))?;
+        let import = ImportInfo::try_from(
+            response
+                .import_table_description
+                .ok_or_else(|| anyhow!("DynamoDB returned no import description"))?,
+        )?;
+
+        info!(
+            "Importing s3://{bucket}/{prefix} into '{}': {} ({})",
+            table.name(),
+            import.arn,
+            import.status
+        );
+        Ok(import)
+    }
+
+    /// Builds the `ImportTable` request, creating the table with its declared
+    /// capacity.
+    pub(crate) fn import_request(
+        &self,
+        table: &Table<'_>,
+        bucket: &str,
+        prefix: &str,
+        format: InputFormat,
+    ) -> Result<ImportTableFluentBuilder> {
+        let definition = TableDefinition::new(table)?;
+        let table_creation_parameters = TableCreationParameters::builder()
+            .table_name(table.name())
+            .billing_mode(definition.billing_mode)
+            .set_provisioned_throughput(definition.provisioned_throughput)
+            .set_attribute_definitions(Some(definition.attribute_definitions))
+            .set_key_schema(Some(definition.key_schema))
+            .set_global_secondary_indexes(definition.global_secondary_indexes)
+            .build()?;
+        let s3_bucket_source = S3BucketSource::builder()
+            .s3_bucket(bucket)
+            .s3_key_prefix(prefix)
+            .build()?;
+
+        Ok(self
+            .client
+            .import_table()
+            .s3_bucket_source(s3_bucket_source)
+            .input_format(format)
+            .table_creation_parameters(table_creation_parameters))
+    }
+
+    /// Reports the progress of an import started by [`DynamoDb::import_from_s3`].
+    #[instrument(skip_all, fields(import_arn = %import_arn, operation = "DescribeImport"))]
+    pub async fn describe_import(&self, import_arn: &str) -> Result<ImportInfo> {
+        let response = self
+            .client
+            .describe_import()
+            .import_arn(import_arn)
+            .send()
+            .await
+            .map_err(request_failed(None, None))?;
+        ImportInfo::try_from(
+            response
+                .import_table_description
+                .ok_or_else(|| anyhow!("DynamoDB returned no description for '{import_arn}'"))?,
+        )
+    }
+
+    /// Describes a table, returning `None` if it doesn't exist.
+    #[instrument(skip_all, fields(table_name = %table_name, operation = "DescribeTable"))]
+    async fn describe_table_if_exists(&self, table_name: &str) -> Result<Option<TableDescription>> {
+        match self
+            .client
+            .describe_table()
+            .table_name(table_name)
+            .send()
+            .await
+        {
+            Ok(output) => Ok(output.table),
+            Err(e)
+                if e.as_service_error()
+                    .is_some_and(|e| e.is_resource_not_found_exception()) =>
+            {
+                Ok(None)
+            }
+            Err(e) => Err(table_not_found(table_name)(e)),
+        }
+    }
+
+    /// Waits up to `timeout` for a table to report `ACTIVE`.
+    ///
+    /// A table that doesn't exist yet is assumed to still be on its way, so a
+    /// missing table only fails once the timeout runs out.
+    #[instrument(skip_all, fields(table_name = %table_name))]
+    pub async fn wait_for_table_active(&self, table_name: &str, timeout: Duration) -> Result<()> {
+        self.wait_for_table(table_name, timeout, "become active", |table| {
+            table.and_then(TableDescription::table_status) == Some(&TableStatus::Active)
+        })
+        .await
+    }
+
+    /// Waits up to `timeout` for a table to disappear.
+    #[instrument(skip_all, fields(table_name = %table_name))]
+    pub async fn wait_for_table_deleted(&self, table_name: &str, timeout: Duration) -> Result<()> {
+        self.wait_for_table(table_name, timeout, "be deleted", |table| table.is_none())
+            .await
+    }
+
+    /// Polls the table description every [`TABLE_POLL_INTERVAL`] until `done`
+    /// accepts it or `timeout` elapses.
+    #[instrument(skip_all, fields(table_name = %table_name, goal = %goal))]
+    async fn wait_for_table(
+        &self,
+        table_name: &str,
+        timeout: Duration,
+        goal: &str,
+        done: impl Fn(Option<&TableDescription>) -> bool,
+    ) -> Result<()> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let table = self.describe_table_if_exists(table_name).await?;
+            if done(table.as_ref()) {
+                return Ok(());
+            }
+
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return Err(anyhow!(
+                    "Timed out after {timeout:?} waiting for table '{table_name}' to {goal}"
+                ));
+            }
+            sleep(TABLE_POLL_INTERVAL.min(remaining)).await;
+        }
+    }
+
+    // --- Item Operations ---
+
+    /// Puts an item into a DynamoDB table.
+    ///
+    /// Anything that converts into an [`Item`] will do, such as a raw
+    /// attribute map or, with the `serde` feature, a `serde_dynamo::Item`.
+    #[instrument(
+        skip_all,
+        fields(
+            table_name = %table_name,
+            operation = "PutItem",
+            item_attributes = Empty,
+        )
+    )]
+    pub async fn put_item(&self, table_name: &str, item: impl Into<Item>) -> Result<()> {
+        let item = item.into();
+        Span::current().record("item_attributes", item.attributes.len());
+        self.put_item_request(table_name, item, false)
+            .send()
+            .await
+            .map_err(table_not_found(table_name))?;
+
+        info!("Item added to '{table_name}'");
+        Ok(())
+    }
+
+    /// Puts an item unless the table already has one with the same key.
+    ///
+    /// Returns `false`, leaving the existing item untouched, if the key is
+    /// taken.
+    #[instrument(
+        skip_all,
+        fields(
+            table_name = %table.name(),
+            operation = "PutItem",
+            item_attributes = item.attributes.len(),
+        )
+    )]
+    pub async fn put_item_if_not_exists(&self, table: &Table<'_>, item: Item) -> Result<bool> {
+        let result = self
+            .put_item_request(table.name(), item, false)
+            .condition_expression("attribute_not_exists(#pk)")
+            .expression_attribute_names("#pk", table.partition_key())
+            .send()
+            .await;
+
+        match result {
+            Ok(_) => {
+                info!("Item added to '{}'", table.name());
+                Ok(true)
+            }
+            Err(e)
+                if e.as_service_error()
+                    .is_some_and(|e| e.is_conditional_check_failed_exception()) =>
+            {
+                Ok(false)
+            }
+            Err(e) => Err(table_not_found(table.name())(e)),
+        }
+    }
+
+    /// Puts an item if `condition` holds for the item it would replace.
+    ///
+    /// Fails with [`ConditionFailed`] if the condition is false; with
+    /// `return_item`, the error holds the item that was found.
+    #[instrument(
+        skip_all,
+        fields(
+            table_name = %table_name,
+            operation = "PutItem",
+            item_attributes = item.attributes.len(),
+            condition = %condition.expression,
+        )
+    )]
+    pub async fn put_item_if(
+        &self,
+        table_name: &str,
+        item: Item,
+        condition: &FilterExpression,
+        return_item: bool,
+    ) -> Result<()> {
+        self.put_item_request(table_name, item, false)
+            .condition_expression(&condition.expression)
+            .set_expression_attribute_names(Some(condition.names.clone()))
+            .set_expression_attribute_values(
+                Some(condition.values.clone()).filter(|values| !values.is_empty()),
+            )
+            .set_return_values_on_condition_check_failure(return_item_on_failure(return_item))
+            .send()
+            .await
+            .map_err(|e| {
+                let item = match e.as_service_error() {
+                    Some(PutItemError::ConditionalCheckFailedException(failed)) => {
+                        Some(failed.item.clone())
+                    }
+                    _ => None,
+                };
+                condition_failed(table_name, None, item)(e)
+            })?;
+
+        info!("Item added to '{table_name}'");
+        Ok(())
+    }
+
+    /// Puts an item and reports the write capacity it consumed.
+    #[instrument(
+        skip_all,
+        fields(
+            table_name = %table_name,
+            operation = "PutItem",
+            item_attributes = item.attributes.len(),
+        )
+    )]
+    pub async fn put_item_with_capacity(
+        &self,
+        table_name: &str,
+        item: Item,
+    ) -> Result<WriteResult> {
+        let response = self
+            .put_item_request(table_name, item, true)
+            .send()
+            .await
+            .map_err(table_not_found(table_name))?;
+
+        info!("Item added to '{table_name}'");
+        Ok(WriteResult {
+            consumed_capacity: capacity_units(response.consumed_capacity.as_ref()),
+        })
+    }
+
+    /// Builds the `PutItem` request shared by the put helpers.
+    pub(crate) fn put_item_request(
+        &self,
+        table_name: &str,
+        item: Item,
+        return_capacity: bool,
+    ) -> PutItemFluentBuilder {
+        self.client
+            .put_item()
+            .table_name(table_name)
+            .set_item(Some(item.attributes))
+            .set_return_consumed_capacity(return_consumed_capacity(return_capacity))
+    }
+
+    /// Gets an item from a DynamoDB table.
+    #[instrument(
+        skip_all,
+        fields(
+            table_name = %table_name,
+            operation = "GetItem",
+            key_attributes = key.attributes.len(),
+            found = Empty,
+        )
+    )]
+    pub async fn get_item(&self, table_name: &str, key: Item) -> Result<Option<Item>> {
+        let failed = item_request_failed(table_name, &key);
+        let response = self
+            .get_item_request(table_name, key, false)
+            .send()
+            .await
+            .map_err(failed)?;
+
+        Span::current().record("found", response.item.is_some());
+        Ok(response.item.map(|attrs| Item { attributes: attrs }))
+    }
+
+    /// Gets only the listed attributes of an item, or all of them if
+    /// `attributes` is empty.
+    ///
+    /// Attribute names are aliased, so reserved words like `name` can be projected.
+    #[instrument(
+        skip_all,
+        fields(
+            table_name = %table_name,
+            key_attributes = key.attributes.len(),
+            projected = attributes.len(),
+        )
+    )]
+    pub async fn get_item_projected(
+        &self,
+        table_name: &str,
+        key: Item,
+        attributes: &[&str],
+    ) -> Result<Option<Item>> {
+        let projection = ProjectionExpression::new(attributes);
+        self.get_item_with(
+            table_name,
+            key,
+            GetItemOptions {
+                projection: projection.as_ref(),
+                ..Default::default()
+            },
+        )
+        .await
+    }
+
+    /// Gets an item with the read consistency and projection in `options`.
+    #[instrument(
+        skip_all,
+        fields(
+            table_name = %table_name,
+            operation = "GetItem",
+            key_attributes = key.attributes.len(),
+            found = Empty,
+        )
+    )]
+    pub async fn get_item_with(
+        &self,
+        table_name: &str,
+        key: Item,
+        options: GetItemOptions<'_>,
+    ) -> Result<Option<Item>> {
+        let failed = item_request_failed(table_name, &key);
+        let mut request = self
+            .get_item_request(table_name, key, false)
+            .set_consistent_read(options.consistent_read);
+        if let Some(projection) = options.projection {
+            request = request
+                .projection_expression(&projection.expression)
+                .set_expression_attribute_names(Some(projection.names.clone()));
+        }
+        let response = request.send().await.map_err(failed)?;
+
+        Span::current().record("found", response.item.is_some());
+        Ok(response.item.map(|attrs| Item { attributes: attrs }))
+    }
+
+    /// Gets an item and reports the read capacity it consumed.
+    #[instrument(
+        skip_all,
+        fields(
+            table_name = %table_name,
+            operation = "GetItem",
+            key_attributes = key.attributes.len(),
+            found = Empty,
+        )
+    )]
+    pub async fn get_item_with_capacity(
+        &self,
+        table_name: &str,
+        key: Item,
+    ) -> Result<ReadResult<Option<Item>>> {
+        let failed = item_request_failed(table_name, &key);
+        let response = self
+            .get_item_request(table_name, key, true)
+            .send()
+            .await
+            .map_err(failed)?;
+
+        Span::current().record("found", response.item.is_some());
+        Ok(ReadResult {
+            value: response.item.map(|attrs| Item { attributes: attrs }),
+            consumed_capacity: capacity_units(response.consumed_capacity.as_ref()),
+        })
+    }
+
+    /// Builds the `GetItem` request shared by the get helpers.
+    pub(crate) fn get_item_request(
+        &self,
+        table_name: &str,
+        key: Item,
+        return_capacity: bool,
+    ) -> GetItemFluentBuilder {
+        self.client
+            .get_item()
+            .table_name(table_name)
+            .set_key(Some(key.attributes))
+            .set_return_consumed_capacity(return_consumed_capacity(return_capacity))
+    }
+
+    /// Checks whether an item with the given key exists.
+    ///
+    /// Only the partition key is projected, so the response stays small no
+    /// matter how large the item is.
+    #[instrument(
+        skip_all,
+        fields(
+            table_name = %table.name(),
+            operation = "GetItem",
+            key_attributes = key.attributes.len(),
+        )
+    )]
+    pub async fn item_exists(
+        &self,
+        table: &Table<'_>,
+        key: Item,
+        consistent_read: Option<bool>,
+    ) -> Result<bool> {
+        let failed = item_request_failed(table.name(), &key);
+        let response = self
+            .client
+            .get_item()
+            .table_name(table.name())
+            .set_key(Some(key.attributes))
+            .projection_expression("#pk")
+            .expression_attribute_names("#pk", table.partition_key())
+            .set_consistent_read(consistent_read)
+            .send()
+            .await
+            .map_err(failed)?;
+
+        Ok(response.item.is_some())
+    }
+}
+
+/// Conversions to and from your own types, with the `serde` feature.
+#[cfg(feature = "serde")]
+impl DynamoDb {
+    /// Puts a serializable value into a DynamoDB table as an item.
+    #[instrument(skip_all, fields(table_name = %table_name))]
+    pub async fn put_item_typed<T: Serialize>(&self, table_name: &str, value: &T) -> Result<()> {
+        self.put_item(table_name, Item::from_typed(value)?).await
+    }
+
+    /// Gets an item from a DynamoDB table and converts it into a typed value.
+    #[instrument(skip_all, fields(table_name = %table_name, key_attributes = key.attributes.len()))]
+    pub async fn get_item_typed<T: DeserializeOwned>(
+        &self,
+        table_name: &str,
+        key: Item,
+    ) -> Result<Option<T>> {
+        self.get_item(table_name, key)
+            .await?
+            .map(Item::into_typed)
+            .transpose()
+    }
+
+    /// Queries every page and converts each item into a typed value.
+    ///
+    /// Fails on the first item that doesn't convert; the error names the
+    /// item's key. Use [`DynamoDb::query_typed_lenient`] to keep the good rows.
+    #[instrument(skip_all, fields(table_name = %params.table_name))]
+    pub async fn query_typed<T: DeserializeOwned>(
+        &self,
+        params: QueryFlexibleParams<'_>,
+    ) -> Result<Vec<T>> {
+        self.query_typed_lenient(params).await?.into_result()
+    }
+
+    /// Queries every page and converts each item into a typed value,
+    /// collecting conversion failures instead of stopping at the first one.
+    #[instrument(skip_all, fields(table_name = %params.table_name))]
+    pub async fn query_typed_lenient<T: DeserializeOwned>(
+        &self,
+        params: QueryFlexibleParams<'_>,
+    ) -> Result<TypedItems<T>> {
+        let table_name = params.table_name;
+        let items = self.query_all(params, None).await?;
+        self.convert_items(table_name, items).await
+    }
+
+    /// Scans a table, optionally filtered, and converts each item into a
+    /// typed value.
+    ///
+    /// Fails on the first item that doesn't convert; the error names the
+    /// item's key. Use [`DynamoDb::scan_typed_lenient`] to keep the good rows.
+    #[instrument(skip_all, fields(table_name = %table_name))]
+    pub async fn scan_typed<T: DeserializeOwned>(
+        &self,
+        table_name: &str,
+        filter: Option<Filter>,
+    ) -> Result<Vec<T>> {
+        self.scan_typed_lenient(table_name, filter)
+            .await?
+            .into_result()
+    }
+
+    /// Scans a table, optionally filtered, and converts each item into a
+    /// typed value, collecting conversion failures instead of stopping at the
+    /// first one.
+    #[instrument(skip_all, fields(table_name = %table_name))]
+    pub async fn scan_typed_lenient<T: DeserializeOwned>(
+        &self,
+        table_name: &str,
+        filter: Option<Filter>,
+    ) -> Result<TypedItems<T>> {
+        let filter = filter.map(Filter::build);
+        let params = ScanParams {
+            table_name,
+            ..Default::default()
+        };
+        let params = match &filter {
+            Some(filter) => params.with_filter(filter)?,
+            None => params,
+        };
+        let items = self.scan_all(params, None, None).await?;
+        self.convert_items(table_name, items).await
+    }
+
+    /// Writes `entity` to its table, replacing any item with the same key.
+    ///
+    /// The item is checked against [`DynamoEntity::table`] first, so a value
+    /// missing a key attribute or with the wrong type for a schema field is
+    /// rejected before anything is sent.
+    #[instrument(skip_all, fields(table_name = %T::table_name()))]
+    pub async fn save<T: DynamoEntity>(&self, entity: &T) -> Result<()> {
+        let item = Item::from_typed(entity)?;
+        T::table().validate_item(&item)?;
+        self.put_item(T::table_name(), item).await
+    }
+
+    /// Reads the `T` with `key` (see [`DynamoEntity::key_of`]).
+    ///
+    /// Returns `Ok(None)` if there is no such item. A missing table is a
+    /// [`TableNotFound`] error.
+    #[instrument(skip_all, fields(table_name = %T::table_name()))]
+    pub async fn load<T: DynamoEntity>(&self, key: Item) -> Result<Option<T>> {
+        self.get_item_typed(T::table_name(), key).await
+    }
+
+    /// Deletes the `T` with `key`. Deleting an item that doesn't exist
+    /// succeeds.
+    #[instrument(skip_all, fields(table_name = %T::table_name()))]
+    pub async fn delete_entity<T: DynamoEntity>(&self, key: Item) -> Result<()> {
+        self.delete_item(T::table_name(), key).await
+    }
+
+    /// Reads every `T` in the partition `partition_key`, in sort key order.
+    #[instrument(skip_all, fields(table_name = %T::table_name()))]
+    pub async fn query_partition<T: DynamoEntity>(
+        &self,
+        partition_key: AttributeValue,
+    ) -> Result<Vec<T>> {
+        self.query_typed(QueryFlexibleParams {
+            table_name: T::table_name(),
+            key_condition_expression: "#pk = :pk",
+            expression_attribute_names: Some(HashMap::from([(
+                "#pk".to_string(),
+                T::partition_key_name().to_string(),
+            )])),
+            expression_attribute_values: Some(HashMap::from([(":pk".to_string(), partition_key)])),
+            ..Default::default()
+        })
+        .await
+    }
+
+    /// Converts items one by one, recording the key of every item that fails.
+    ///
+    /// The table's key schema is only looked up once something fails.
+    #[instrument(skip_all, fields(table_name = %table_name, items = items.len()))]
+    async fn convert_items<T: DeserializeOwned>(
+        &self,
+        table_name: &str,
+        items: Vec<Item>,
+    ) -> Result<TypedItems<T>> {
+        let mut converted = TypedItems {
+            values: Vec::with_capacity(items.len()),
+            errors: Vec::new(),
+        };
+        let mut key_names: Option<Vec<String>> = None;
+
+        for item in items {
+            let attributes = item.attributes.clone();
+            match item.into_typed() {
+                Ok(value) => converted.values.push(value),
+                Err(error) => {
+                    if key_names.is_none() {
+                        key_names = Some(self.key_attribute_names(table_name).await?);
+                    }
+                    let key = key_names
+                        .iter()
+                        .flatten()
+                        .filter_map(|name| {
+                            attributes
+                                .get(name)
+                                .map(|value| (name.clone(), value.clone()))
+                        })
+                        .collect();
+                    converted.errors.push(ItemConversionError {
+                        key,
+                        message: error.to_string(),
+                    });
+                }
+            }
+        }
+
+        Ok(converted)
+    }
+
+    /// Returns the names of the table's partition and sort key attributes.
+    async fn key_attribute_names(&self, table_name: &str) -> Result<Vec<String>> {
+        let key_schema = self.key_schema(table_name).await?;
+        Ok(key_schema.names().map(str::to_string).collect())
+    }
+}
+
+impl DynamoDb {
+    /// Updates an item in a DynamoDB table.
+    ///
+    /// An empty `updates` item is a successful no-op: no request is sent.
+    #[instrument(
+        skip_all,
+        fields(
+            table_name = %table_name,
+            key_attributes = key.attributes.len(),
+            update_attributes = updates.attributes.len(),
+        )
+    )]
+    pub async fn update_item(&self, table_name: &str, key: Item, updates: Item) -> Result<()> {
+        self.update_with(table_name, key, UpdateBuilder::from(&updates))
+            .await
+    }
+
+    /// Updates an item, rejecting updates that touch the table's key attributes.
+    ///
+    /// DynamoDB refuses to modify key attributes; checking up front gives an
+    /// error that names the offending attribute instead of a service error.
+    #[instrument(
+        skip_all,
+        fields(
+            table_name = %table.name(),
+            key_attributes = key.attributes.len(),
+            update_attributes = updates.attributes.len(),
+        )
+    )]
+    pub async fn update_table_item(
+        &self,
+        table: &Table<'_>,
+        key: Item,
+        updates: Item,
+    ) -> Result<()> {
+        if let Some(attribute) = updates
+            .attributes
+            .keys()
+            .find(|attribute| table.is_key_attribute(attribute))
+        {
+            return Err(anyhow!(
+                "Cannot update key attribute '{attribute}' of table '{}'",
+                table.name()
+            ));
+        }
+
+        self.update_item(table.name(), key, updates).await
+    }
+
+    /// Applies the actions collected in an `UpdateBuilder` to an item.
+    ///
+    /// An empty builder is a successful no-op: no request is sent.
+    #[instrument(
+        skip_all,
+        fields(
+            table_name = %table_name,
+            operation = "UpdateItem",
+            key_attributes = key.attributes.len(),
+        )
+    )]
+    pub async fn update_with(
+        &self,
+        table_name: &str,
+        key: Item,
+        updates: UpdateBuilder,
+    ) -> Result<()> {
+        let Some(update) = updates.build() else {
+            info!("No updates for item in '{table_name}', skipping");
+            return Ok(());
+        };
+
+        let attributes = key.attributes.clone();
+        let rejected = || ExpressionRejected {
+            update_expression: Some(update.expression.clone()),
+            ..ExpressionRejected::new(table_name, Some(&update.names), Some(&update.values))
+        };
+        self.client
+            .update_item()
+            .table_name(table_name)
+            .set_key(Some(key.attributes))
+            .update_expression(update.expression.clone())
+            .set_expression_attribute_names(Some(update.names.clone()))
+            // DynamoDB rejects an empty map, as sent by a remove-only update.
+            .set_expression_attribute_values(
+                Some(update.values.clone()).filter(|values| !values.is_empty()),
+            )
+            .send()
+            .await
+            .map_err(expression_rejected(table_name, Some(attributes), rejected))?;
+
+        info!("Item updated in '{table_name}'");
+        Ok(())
+    }
+
+    /// Updates an item if `condition` holds for it.
+    ///
+    /// Fails with [`ConditionFailed`] if the condition is false; with
+    /// `return_item`, the error holds the item that was found. An empty
+    /// `updates` item is a successful no-op: no request is sent and the
+    /// condition isn't checked.
+    #[instrument(
+        skip_all,
+        fields(
+            table_name = %table_name,
+            key_attributes = key.attributes.len(),
+            update_attributes = updates.attributes.len(),
+            condition = %condition.expression,
+        )
+    )]
+    pub async fn update_item_if(
+        &self,
+        table_name: &str,
+        key: Item,
+        updates: Item,
+        condition: &FilterExpression,
+        return_item: bool,
+    ) -> Result<()> {
+        self.update_with_if(
+            table_name,
+            key,
+            UpdateBuilder::from(&updates),
+            condition,
+            return_item,
+        )
+        .await
+    }
+
+    /// Applies the actions collected in an `UpdateBuilder` to an item if
+    /// `condition` holds for it.
+    ///
+    /// Fails with [`ConditionFailed`] if the condition is false; with
+    /// `return_item`, the error holds the item that was found. An empty
+    /// builder is a successful no-op: no request is sent and the condition
+    /// isn't checked.
+    #[instrument(
+        skip_all,
+        fields(
+            table_name = %table_name,
+            operation = "UpdateItem",
+            key_attributes = key.attributes.len(),
+            condition = %condition.expression,
+        )
+    )]
+    pub async fn update_with_if(
+        &self,
+        table_name: &str,
+        key: Item,
+        updates: UpdateBuilder,
+        condition: &FilterExpression,
+        return_item: bool,
+    ) -> Result<()> {
+        let Some(update) = updates.build() else {
+            info!("No updates for item in '{table_name}', skipping");
+            return Ok(());
+        };
+
+        let mut names = update.names.clone();
+        let mut values = update.values.clone();
+        condition.merge_into(&mut names, &mut values)?;
+        let attributes = key.attributes.clone();
+        self.client
+            .update_item()
+            .table_name(table_name)
+            .set_key(Some(key.attributes))
+            .update_expression(update.expression)
+            .condition_expression(&condition.expression)
+            .set_expression_attribute_names(Some(names))
+            .set_expression_attribute_values(Some(values).filter(|values| !values.is_empty()))
+            .set_return_values_on_condition_check_failure(return_item_on_failure(return_item))
+            .send()
+            .await
+            .map_err(|e| {
+                let item = match e.as_service_error() {
+                    Some(UpdateItemError::ConditionalCheckFailedException(failed)) => {
+                        Some(failed.item.clone())
+                    }
+                    _ => None,
+                };
+                condition_failed(table_name, Some(attributes), item)(e)
+            })?;
+
+        info!("Item updated in '{table_name}'");
+        Ok(())
+    }
+
+    /// Sets an attribute only if the item doesn't already have a value for it.
+    ///
+    /// Use `update_with` and `UpdateBuilder::set_if_not_exists` to combine this
+    /// with other `SET` actions in one request.
+    #[instrument(
+        skip_all,
+        fields(
+            table_name = %table_name,
+            key_attributes = key.attributes.len(),
+            attribute = %attribute,
+        )
+    )]
+    pub async fn set_if_not_exists(
+        &self,
+        table_name: &str,
+        key: Item,
+        attribute: &str,
+        value: AttributeValue,
+    ) -> Result<()> {
+        self.update_with(
+            table_name,
+            key,
+            UpdateBuilder::new().set_if_not_exists(attribute, value),
+        )
+        .await
+    }
+
+    /// Appends values to the end of a list attribute.
+    ///
+    /// The list is created if the attribute doesn't exist yet, so the first
+    /// append behaves like a plain set.
+    #[instrument(
+        skip_all,
+        fields(
+            table_name = %table_name,
+            operation = "UpdateItem",
+            key_attributes = key.attributes.len(),
+            attribute = %attribute,
+            values = values.len(),
+        )
+    )]
+    pub async fn append_to_list(
+        &self,
+        table_name: &str,
+        key: Item,
+        attribute: &str,
+        values: Vec<AttributeValue>,
+    ) -> Result<()> {
+        let failed = item_request_failed(table_name, &key);
+        self.client
+            .update_item()
+            .table_name(table_name)
+            .set_key(Some(key.attributes))
+            .update_expression("SET #attr = list_append(if_not_exists(#attr, :empty), :vals)")
+            .expression_attribute_names("#attr", attribute)
+            .expression_attribute_values(":empty", AttributeValue::L(Vec::new()))
+            .expression_attribute_values(":vals", AttributeValue::L(values))
+            .send()
+            .await
+            .map_err(failed)?;
+
+        info!("Appended to list '{attribute}' in '{table_name}'");
+        Ok(())
+    }
+
+    /// Replaces a single element of a list attribute by index.
+    ///
+    /// DynamoDB rejects the update if the index is past the end of the list.
+    #[instrument(
+        skip_all,
+        fields(
+            table_name = %table_name,
+            operation = "UpdateItem",
+            key_attributes = key.attributes.len(),
+            attribute = %attribute,
+            index,
+        )
+    )]
+    pub async fn set_list_element(
+        &self,
+        table_name: &str,
+        key: Item,
+        attribute: &str,
+        index: usize,
+        value: AttributeValue,
+    ) -> Result<()> {
+        let failed = item_request_failed(table_name, &key);
+        self.client
+            .update_item()
+            .table_name(table_name)
+            .set_key(Some(key.attributes))
+            .update_expression(format!("SET #attr[{index}] = :val"))
+            .expression_attribute_names("#attr", attribute)
+            .expression_attribute_values(":val", value)
+            .send()
+            .await
+            .map_err(failed)?;
+
+        info!("Set element {index} of list '{attribute}' in '{table_name}'");
+        Ok(())
+    }
+
+    /// Deletes an item from a DynamoDB table.
+    #[instrument(
+        skip_all,
+        fields(
+            table_name = %table_name,
+            operation = "DeleteItem",
+            key_attributes = key.attributes.len(),
+        )
+    )]
+    pub async fn delete_item(&self, table_name: &str, key: Item) -> Result<()> {
+        let failed = item_request_failed(table_name, &key);
+        self.client
+            .delete_item()
+            .table_name(table_name)
+            .set_key(Some(key.attributes))
+            .send()
+            .await
+            .map_err(failed)?;
+
+        info!("Item deleted from '{table_name}'");
+        Ok(())
+    }
+
+    /// Deletes an item if `condition` holds for it.
+    ///
+    /// Fails with [`ConditionFailed`] if the condition is false; with
+    /// `return_item`, the error holds the item that was found.
+    #[instrument(
+        skip_all,
+        fields(
+            table_name = %table_name,
+            operation = "DeleteItem",
+            key_attributes = key.attributes.len(),
+            condition = %condition.expression,
+        )
+    )]
+    pub async fn delete_item_if(
+        &self,
+        table_name: &str,
+        key: Item,
+        condition: &FilterExpression,
+        return_item: bool,
+    ) -> Result<()> {
+        let attributes = key.attributes.clone();
+        self.client
+            .delete_item()
+            .table_name(table_name)
+            .set_key(Some(key.attributes))
+            .condition_expression(&condition.expression)
+            .set_expression_attribute_names(Some(condition.names.clone()))
+            .set_expression_attribute_values(
+                Some(condition.values.clone()).filter(|values| !values.is_empty()),
+            )
+            .set_return_values_on_condition_check_failure(return_item_on_failure(return_item))
+            .send()
+            .await
+            .map_err(|e| {
+                let item = match e.as_service_error() {
+                    Some(DeleteItemError::ConditionalCheckFailedException(failed)) => {
+                        Some(failed.item.clone())
+                    }
+                    _ => None,
+                };
+                condition_failed(table_name, Some(attributes), item)(e)
+            })?;
+
+        info!("Item deleted from '{table_name}'");
+        Ok(())
+    }
+
+    /// Applies `operations` with `TransactWriteItems`: all of them or none.
+    ///
+    /// A cancelled transaction fails with [`TransactionCancelled`], which
+    /// says which operations failed and why.
+    #[instrument(skip_all, fields(operation = "TransactWriteItems", operations = operations.len()))]
+    pub async fn transact_write(&self, operations: Vec<TransactWriteItem>) -> Result<()> {
+        let count = operations.len();
+        self.client
+            .transact_write_items()
+            .set_transact_items(Some(operations))
+            .send()
+            .await
+            .map_err(|e| {
+                let cancelled = match e.as_service_error() {
+                    Some(TransactWriteItemsError::TransactionCanceledException(cancelled)) => {
+                        Some(TransactionCancelled::new(cancelled.cancellation_reasons()))
+                    }
+                    _ => None,
+                };
+                let e = request_failed(None, None)(e);
+                match cancelled {
+                    Some(cancelled) => e.context(cancelled),
+                    None => e,
+                }
+            })?;
+
+        info!("Transaction of {count} operations written");
+        Ok(())
+    }
+
+    /// Writes items with `BatchWriteItem`, `BATCH_WRITE_SIZE` (25) per request,
+    /// calling `on_progress`, if given, with the running totals after each
+    /// request.
+    ///
+    /// Items DynamoDB leaves unprocessed (usually because of throttling) are
+    /// retried with exponential backoff; the call fails if some are still
+    /// unprocessed after `BATCH_WRITE_MAX_RETRIES` (8) retries. Returns the
+    /// number of items written.
+    #[instrument(skip_all, fields(table_name = %table_name, items = items.len()))]
+    pub async fn batch_put_items(
+        &self,
+        table_name: &str,
+        items: Vec<Item>,
+        on_progress: Option<&mut dyn FnMut(&Progress)>,
+    ) -> Result<usize> {
+        let result = self
+            .batch_put_items_cancellable(table_name, items, on_progress, &CancellationToken::new())
+            .await?;
+        Ok(result.value)
+    }
+
+    /// Like [`DynamoDb::batch_put_items`], but stops before the next request
+    /// once `cancel` is cancelled. The items written so far stay written.
+    #[instrument(
+        skip_all,
+        fields(
+            table_name = %table_name,
+            operation = "BatchWriteItem",
+            items = items.len(),
+            count = Empty,
+        )
+    )]
+    pub async fn batch_put_items_cancellable(
+        &self,
+        table_name: &str,
+        items: Vec<Item>,
+        mut on_progress: Option<&mut dyn FnMut(&Progress)>,
+        cancel: &CancellationToken,
+    ) -> Result<BulkResult<usize>> {
+        let start = Instant::now();
+        let mut progress = Progress::default();
+        let mut status = BulkStatus::Complete;
+        let mut items = items.into_iter().peekable();
+
+        while items.peek().is_some() {
+            if cancel.is_cancelled() {
+                status = BulkStatus::Cancelled;
+                break;
+            }
+
+            let chunk = items
+                .by_ref()
+                .take(BATCH_WRITE_SIZE)
+                .map(put_request)
+                .collect::<Result<Vec<_>>>()?;
+            let chunk_size = chunk.len();
+            let batch = debug_span!("batch", number = progress.pages + 1, items = chunk_size);
+            self.write_batch(
+                table_name,
+                chunk,
+                on_progress.is_some(),
+                &mut progress.consumed_capacity,
+            )
+            .instrument(batch)
+            .await
+            .map_err(|failed| failed.error)?;
+
+            progress.record_page(chunk_size, start);
+            if let Some(on_progress) = on_progress.as_mut() {
+                on_progress(&progress);
+            }
+        }
+
+        let written = progress.items;
+        Span::current().record("count", written);
+        info!("Batch wrote {written} items to '{table_name}'");
+        Ok(BulkResult {
+            value: written,
+            status,
+        })
+    }
+
+    /// Writes `items` in batches of up to 25, with up to `concurrency`
+    /// batch requests in flight at once.
+    ///
+    /// Each batch retries its own unprocessed items with backoff, as
+    /// [`DynamoDb::batch_put_items`] does, and the batches may finish in any
+    /// order. A batch that fails doesn't stop the others: its unwritten items
+    /// are returned in [`ConcurrentPutStats::failed`], so every item is
+    /// either counted in `written` or listed there. If a batch's task
+    /// panics, all of its items are listed, though some may have been
+    /// written.
+    #[instrument(
+        skip_all,
+        fields(
+            table_name = %table_name,
+            operation = "BatchWriteItem",
+            items = items.len(),
+            concurrency,
+            count = Empty,
+        )
+    )]
+    pub async fn put_items_concurrent(
+        &self,
+        table_name: &str,
+        items: Vec<Item>,
+        concurrency: usize,
+    ) -> Result<ConcurrentPutStats> {
+        if concurrency == 0 {
+            return Err(anyhow!("concurrency must be at least 1"));
+        }
+        let start = Instant::now();
+        // Converted up front, so a bad item fails the call before any batch
+        // is sent rather than abandoning the ones in flight.
+        let requests = items
+            .into_iter()
+            .map(put_request)
+            .collect::<Result<Vec<_>>>()?;
+        let mut requests = requests.into_iter().peekable();
+        let mut stats = ConcurrentPutStats::default();
+        let mut batches = JoinSet::new();
+        // The requests of each batch in flight, to report if its task panics.
+        let mut in_flight = HashMap::new();
+
+        for number in 1.. {
+            if requests.peek().is_none() {
+                break;
+            }
+            if batches.len() == concurrency {
+                if let Some(joined) = batches.join_next_with_id().await {
+                    stats.record_batch(&mut in_flight, joined);
+                }
+            }
+            let chunk: Vec<_> = requests.by_ref().take(BATCH_WRITE_SIZE).collect();
+            let ddb = self.clone();
+            let table_name = table_name.to_string();
+            let batch = debug_span!("batch", number, items = chunk.len());
+            let task = batches.spawn(
+                {
+                    let chunk = chunk.clone();
+                    async move {
+                        let mut consumed_capacity = None;
+                        ddb.write_batch(&table_name, chunk, false, &mut consumed_capacity)
+                            .await
+                    }
+                }
+                .instrument(batch),
+            );
+            in_flight.insert(task.id(), chunk);
+        }
+        while let Some(joined) = batches.join_next_with_id().await {
+            stats.record_batch(&mut in_flight, joined);
+        }
+        stats.elapsed = start.elapsed();
+
+        Span::current().record("count", stats.written);
+        info!(
+            "Batch wrote {} items to '{table_name}', {} failed",
+            stats.written,
+            stats.failed_items()
+        );
+        Ok(stats)
+    }
+
+    /// Sends one `BatchWriteItem` request, then retries whatever comes back
+    /// unprocessed with exponential backoff. Returns how many requests it
+    /// took, adding the capacity they consumed to `consumed_capacity`.
+    async fn write_batch(
+        &self,
+        table_name: &str,
+        requests: Vec<WriteRequest>,
+        return_capacity: bool,
+        consumed_capacity: &mut Option<f64>,
+    ) -> std::result::Result<usize, FailedBatch> {
+        let chunk_size = requests.len();
+        let mut pending = requests;
+        let mut delay = BATCH_WRITE_RETRY_DELAY;
+        for attempt in 0.. {
+            let response = match self
+                .client
+                .batch_write_item()
+                .request_items(table_name, pending.clone())
+                .set_return_consumed_capacity(return_consumed_capacity(return_capacity))
+                .send()
+                .await
+                .map_err(table_not_found(table_name))
+            {
+                Ok(response) => response,
+                Err(error) => return Err(FailedBatch::new(pending, error, attempt as usize + 1)),
+            };
+            for consumed in response.consumed_capacity() {
+                *consumed_capacity = add_capacity(*consumed_capacity, Some(consumed));
+            }
+            pending = response
+                .unprocessed_items
+                .and_then(|mut unprocessed| unprocessed.remove(table_name))
+                .unwrap_or_default();
+            if pending.is_empty() {
+                return Ok(attempt as usize + 1);
+            }
+            if attempt == BATCH_WRITE_MAX_RETRIES {
+                let error = anyhow!(
+                    "{} of {chunk_size} items were still unprocessed after {BATCH_WRITE_MAX_RETRIES} retries",
+                    pending.len()
+                );
+                return Err(FailedBatch::new(pending, error, attempt as usize + 1));
+            }
+            sleep(delay).await;
+            delay *= 2;
+        }
+        unreachable!("the retry loop always returns")
+    }
+
+    // --- Query and Scan Operations ---
+
+    /// Scans a table for items.
+    #[instrument(skip_all, fields(table_name = %table_name, operation = "Scan", count = Empty))]
+    pub async fn scan_table(
+        &self,
+        table_name: &str,
+    ) -> Result<Vec<HashMap<String, AttributeValue>>> {
         let mut items = Vec::new();
         let mut last_evaluated_key = None;
 
+        for number in 1.. {
+            let mut scan = self.client.scan().table_name(table_name);
+
+            if let Some(key) = last_evaluated_key {
+                scan = scan.set_exclusive_start_key(Some(key));
+            }
+
+            let page = page_span(number);
+            let response: ScanOutput = scan
+                .send()
+                .instrument(page.clone())
+                .await
+                .map_err(table_not_found(table_name))?;
+            page.record("count", response.count);
+
+            if let Some(new_items) = response.items {
+                items.extend(new_items);
+            }
+
+            last_evaluated_key = response.last_evaluated_key;
+
+            if last_evaluated_key.is_none() {
+                break;
+            }
+        }
+
+        Span::current().record("count", items.len());
+        Ok(items)
+    }
+
+    /// Performs a scan operation on a DynamoDB table.
+    ///
+    /// Follows `LastEvaluatedKey` and returns every matching item.
+    #[instrument(skip_all, fields(table_name = %params.table_name))]
+    pub async fn scan(&self, params: ScanParams<'_>) -> Result<Vec<Item>> {
+        self.scan_all(params, None, None).await
+    }
+
+    /// Performs a scan, following `LastEvaluatedKey` across pages.
+    ///
+    /// `params.limit` still sets the page size, while `max_items` caps the total
+    /// number of items returned; the final page is trimmed to fit. If given,
+    /// `on_progress` is called with the running totals after every page.
+    #[instrument(skip_all, fields(table_name = %params.table_name, max_items = ?max_items))]
+    pub async fn scan_all(
+        &self,
+        params: ScanParams<'_>,
+        max_items: Option<usize>,
+        on_progress: Option<&mut dyn FnMut(&Progress)>,
+    ) -> Result<Vec<Item>> {
+        let (result, _) = self
+            .scan_all_pages(&params, max_items, false, on_progress, None)
+            .await?;
+        Ok(result.value)
+    }
+
+    /// Like [`DynamoDb::scan_all`], but stops before requesting the next page
+    /// once `cancel` is cancelled, returning the items read so far.
+    #[instrument(skip_all, fields(table_name = %params.table_name, max_items = ?max_items))]
+    pub async fn scan_all_cancellable(
+        &self,
+        params: ScanParams<'_>,
+        max_items: Option<usize>,
+        on_progress: Option<&mut dyn FnMut(&Progress)>,
+        cancel: &CancellationToken,
+    ) -> Result<BulkResult<Vec<Item>>> {
+        let (result, status) = self
+            .scan_all_pages(&params, max_items, false, on_progress, Some(cancel))
+            .await?;
+        Ok(BulkResult {
+            value: result.value,
+            status,
+        })
+    }
+
+    /// Scans the items matching `params`, handing each to `handler` as its
+    /// page arrives, so no more than one page is held in memory.
+    ///
+    /// `handler` can return [`ControlFlow::Break`] to stop the scan; the rest
+    /// of that page is dropped and no further pages are requested. An error
+    /// from `handler` stops the scan too, and is returned.
+    ///
+    /// ```no_run
+    /// use aws_dynamodb_testing::dynamodb::{DynamoDb, ScanParams, LOCAL_ENDPOINT};
+    /// use std::ops::ControlFlow;
+    ///
+    /// # async fn example() -> anyhow::Result<()> {
+    /// let ddb = DynamoDb::new_local(LOCAL_ENDPOINT);
+    /// let mut expensive = 0;
+    /// let summary = ddb
+    ///     .for_each_item(
+    ///         ScanParams {
+    ///             table_name: "products",
+    ///             ..Default::default()
+    ///         },
+    ///         |item| {
+    ///             if item.get_number("price").is_some_and(|price| price > 100.0) {
+    ///                 expensive += 1;
+    ///             }
+    ///             Ok(ControlFlow::Continue(()))
+    ///         },
+    ///     )
+    ///     .await?;
+    /// println!("{expensive} of {} products are expensive", summary.items);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[instrument(skip_all, fields(table_name = %params.table_name))]
+    pub async fn for_each_item(
+        &self,
+        params: ScanParams<'_>,
+        handler: impl FnMut(Item) -> Result<ControlFlow<()>>,
+    ) -> Result<ScanSummary> {
+        let result = self
+            .for_each_item_cancellable(params, None, &CancellationToken::new(), handler)
+            .await?;
+        Ok(result.value)
+    }
+
+    /// Like [`DynamoDb::for_each_item`], but stops before requesting the next
+    /// page once `cancel` is cancelled, and calls `on_progress`, if given,
+    /// after every page.
+    #[instrument(
+        skip_all,
+        fields(table_name = %params.table_name, operation = "Scan", count = Empty)
+    )]
+    pub async fn for_each_item_cancellable(
+        &self,
+        params: ScanParams<'_>,
+        mut on_progress: Option<&mut dyn FnMut(&Progress)>,
+        cancel: &CancellationToken,
+        mut handler: impl FnMut(Item) -> Result<ControlFlow<()>>,
+    ) -> Result<BulkResult<ScanSummary>> {
+        self.check_scan_consistency(&params).await?;
+
+        let start = Instant::now();
+        let mut progress = Progress::default();
+        let mut stopped_early = false;
+        let mut status = BulkStatus::Complete;
+        let mut last_evaluated_key = None;
+
         loop {
-            let mut scan = self.client.scan().table_name(table_name);
+            let page = page_span(progress.pages + 1);
+            let response = self
+                .scan_request(&params, on_progress.is_some())
+                .set_exclusive_start_key(last_evaluated_key)
+                .send()
+                .instrument(page.clone())
+                .await
+                .map_err(expression_rejected(params.table_name, None, || {
+                    params.rejected()
+                }))?;
+            page.record("count", response.count);
 
-            if let Some(key) = last_evaluated_key {
-                scan = scan.set_exclusive_start_key(Some(key));
+            let mut handled = 0;
+            for attributes in response.items.unwrap_or_default() {
+                handled += 1;
+                if handler(Item { attributes })?.is_break() {
+                    stopped_early = true;
+                    break;
+                }
             }
-
-            let response: ScanOutput = scan.send().await?;
-
-            if let Some(new_items) = response.items {
-                items.extend(new_items);
+            progress.consumed_capacity = add_capacity(
+                progress.consumed_capacity,
+                response.consumed_capacity.as_ref(),
+            );
+            progress.record_page(handled, start);
+            if let Some(on_progress) = on_progress.as_mut() {
+                on_progress(&progress);
             }
 
             last_evaluated_key = response.last_evaluated_key;
-
-            if last_evaluated_key.is_none() {
+            if stopped_early || last_evaluated_key.is_none() {
+                break;
+            }
+            if cancel.is_cancelled() {
+                status = BulkStatus::Cancelled;
                 break;
             }
         }
 
-        Ok(items)
+        Span::current().record("count", progress.items);
+        Ok(BulkResult {
+            value: ScanSummary {
+                pages: progress.pages,
+                items: progress.items,
+                stopped_early,
+            },
+            status,
+        })
     }
 
-    /// Performs a scan operation on a DynamoDB table.
-    pub async fn scan(
+    /// Scans a table and reports the read capacity consumed across all pages.
+    #[instrument(skip_all, fields(table_name = %params.table_name))]
+    pub async fn scan_with_capacity(
         &self,
-        table_name: &str,
-        filter_expression: Option<String>,
-        expression_attribute_names: Option<HashMap<String, String>>,
-        expression_attribute_values: Option<HashMap<String, AttributeValue>>,
-    ) -> Result<Vec<Item>> {
+        params: ScanParams<'_>,
+    ) -> Result<ReadResult<Vec<Item>>> {
+        let (result, _) = self.scan_all_pages(&params, None, true, None, None).await?;
+        Ok(result)
+    }
+
+    /// Scans every page while holding average read consumption at or below
+    /// `target_rcu_per_sec`.
+    ///
+    /// Each page reports the capacity it consumed; before requesting the next
+    /// one the scan sleeps long enough for the running average to fall back to
+    /// the target. `on_page` is called with the running totals after every
+    /// page, and the final totals are returned alongside the items.
+    #[instrument(
+        skip_all,
+        fields(
+            table_name = %params.table_name,
+            operation = "Scan",
+            target_rcu_per_sec,
+            count = Empty,
+        )
+    )]
+    pub async fn scan_rate_limited(
+        &self,
+        params: ScanParams<'_>,
+        target_rcu_per_sec: f64,
+        mut on_page: impl FnMut(&ScanStats),
+    ) -> Result<(Vec<Item>, ScanStats)> {
+        check_rcu_target(target_rcu_per_sec)?;
+        self.check_scan_consistency(&params).await?;
+
+        let start = Instant::now();
         let mut items = Vec::new();
+        let mut stats = ScanStats::default();
         let mut last_evaluated_key = None;
 
         loop {
-            let mut scan = self.client.scan().table_name(table_name);
+            let span = page_span(stats.pages + 1);
+            let response = self
+                .scan_request(&params, true)
+                .set_exclusive_start_key(last_evaluated_key)
+                .send()
+                .instrument(span.clone())
+                .await
+                .map_err(expression_rejected(params.table_name, None, || {
+                    params.rejected()
+                }))?;
+            span.record("count", response.count);
 
-            if let Some(filter) = &filter_expression {
-                scan = scan.filter_expression(filter);
-            }
+            let page = response.items.unwrap_or_default();
+            stats.pages += 1;
+            stats.items += page.len();
+            stats.consumed_capacity +=
+                capacity_units(response.consumed_capacity.as_ref()).unwrap_or_default();
+            items.extend(page.into_iter().map(|attrs| Item { attributes: attrs }));
 
-            if let Some(names) = &expression_attribute_names {
-                scan = scan.set_expression_attribute_names(Some(names.clone()));
+            last_evaluated_key = response.last_evaluated_key;
+            if last_evaluated_key.is_some() {
+                stats.elapsed = start.elapsed();
+                sleep(stats.delay_for_target(target_rcu_per_sec)?).await;
             }
+            stats.elapsed = start.elapsed();
+            on_page(&stats);
 
-            if let Some(values) = &expression_attribute_values {
-                scan = scan.set_expression_attribute_values(Some(values.clone()));
+            if last_evaluated_key.is_none() {
+                break;
             }
+        }
 
-            if let Some(key) = last_evaluated_key {
-                scan = scan.set_exclusive_start_key(Some(key));
-            }
+        Span::current().record("count", items.len());
+        Ok((items, stats))
+    }
+
+    /// Follows `LastEvaluatedKey` until the scan is exhausted, `max_items`
+    /// have been collected or `cancel` is cancelled.
+    #[instrument(
+        skip_all,
+        fields(table_name = %params.table_name, operation = "Scan", count = Empty)
+    )]
+    async fn scan_all_pages(
+        &self,
+        params: &ScanParams<'_>,
+        max_items: Option<usize>,
+        return_capacity: bool,
+        mut on_progress: Option<&mut dyn FnMut(&Progress)>,
+        cancel: Option<&CancellationToken>,
+    ) -> Result<(ReadResult<Vec<Item>>, BulkStatus)> {
+        self.check_scan_consistency(params).await?;
+
+        let start = Instant::now();
+        let mut items = Vec::new();
+        let mut consumed_capacity = None;
+        let mut pages = 0;
+        let mut status = BulkStatus::Complete;
+        let mut last_evaluated_key = None;
 
-            let response = scan.send().await?;
+        loop {
+            let page = page_span(pages + 1);
+            let response = self
+                .scan_request(params, return_capacity || on_progress.is_some())
+                .set_exclusive_start_key(last_evaluated_key)
+                .send()
+                .instrument(page.clone())
+                .await
+                .map_err(expression_rejected(params.table_name, None, || {
+                    params.rejected()
+                }))?;
+            page.record("count", response.count);
 
             if let Some(new_items) = response.items {
                 items.extend(
@@ -335,14 +3056,210 @@ impl DynamoDb {
                 );
             }
 
+            consumed_capacity =
+                add_capacity(consumed_capacity, response.consumed_capacity.as_ref());
+
+            if let Some(max_items) = max_items {
+                if items.len() >= max_items {
+                    items.truncate(max_items);
+                }
+            }
+
+            pages += 1;
+            if let Some(on_progress) = on_progress.as_mut() {
+                on_progress(&Progress {
+                    pages,
+                    items: items.len(),
+                    elapsed: start.elapsed(),
+                    consumed_capacity,
+                });
+            }
+
+            if max_items.is_some_and(|max_items| items.len() >= max_items) {
+                break;
+            }
+
             last_evaluated_key = response.last_evaluated_key;
+            if last_evaluated_key.is_none() {
+                break;
+            }
+            if cancel.is_some_and(CancellationToken::is_cancelled) {
+                info!(
+                    "Scan of '{}' cancelled after {pages} pages",
+                    params.table_name
+                );
+                status = BulkStatus::Cancelled;
+                break;
+            }
+        }
+
+        Span::current().record("count", items.len());
+        Ok((
+            ReadResult {
+                value: items,
+                consumed_capacity,
+            },
+            status,
+        ))
+    }
+
+    /// Builds a single-page `Scan` request.
+    pub(crate) fn scan_request(
+        &self,
+        params: &ScanParams<'_>,
+        return_capacity: bool,
+    ) -> ScanFluentBuilder {
+        self.client
+            .scan()
+            .table_name(params.table_name)
+            .set_index_name(params.index_name.map(str::to_string))
+            .set_filter_expression(params.filter_expression.map(str::to_string))
+            .set_projection_expression(params.projection_expression.map(str::to_string))
+            .set_expression_attribute_names(params.expression_attribute_names.clone())
+            .set_expression_attribute_values(params.expression_attribute_values.clone())
+            .set_limit(params.limit)
+            .set_consistent_read(params.consistent_read)
+            .set_return_consumed_capacity(return_consumed_capacity(return_capacity))
+    }
+
+    /// Rejects strongly consistent scans of a global secondary index.
+    ///
+    /// DynamoDB only supports eventually consistent reads on GSIs, so this is
+    /// caught before sending the scan rather than surfacing a validation error.
+    #[instrument(
+        skip_all,
+        fields(table_name = %params.table_name, index_name = ?params.index_name)
+    )]
+    async fn check_scan_consistency(&self, params: &ScanParams<'_>) -> Result<()> {
+        let (Some(index_name), Some(true)) = (params.index_name, params.consistent_read) else {
+            return Ok(());
+        };
+
+        let description = self.describe_table(params.table_name).await?;
+        let is_global = description
+            .table()
+            .map(|t| t.global_secondary_indexes())
+            .unwrap_or_default()
+            .iter()
+            .any(|gsi| gsi.index_name() == Some(index_name));
+
+        if is_global {
+            return Err(anyhow!(
+                "Consistent reads are not supported on global secondary index '{index_name}'"
+            ));
+        }
+        Ok(())
+    }
+
+    /// Scans for items whose `attribute` equals one of `values`.
+    ///
+    /// Renders `attribute IN (:f0, :f1, ...)`, ANDed with `filter` when given,
+    /// and follows every page. DynamoDB allows 1 to 100 values.
+    #[instrument(
+        skip_all,
+        fields(table_name = %table_name, attribute = %attribute, values = values.len())
+    )]
+    pub async fn scan_where_in(
+        &self,
+        table_name: &str,
+        attribute: &str,
+        values: Vec<AttributeValue>,
+        filter: Option<Filter>,
+    ) -> Result<Vec<Item>> {
+        if !(1..=100).contains(&values.len()) {
+            return Err(anyhow!(
+                "IN filters take between 1 and 100 values, got {}",
+                values.len()
+            ));
+        }
+        self.scan_where(table_name, Filter::is_in(attribute, values), filter)
+            .await
+    }
+
+    /// Scans for items whose `attribute` contains `value`, either as a
+    /// substring or as a member of a set or list.
+    ///
+    /// ANDed with `filter` when given, and follows every page.
+    #[instrument(skip_all, fields(table_name = %table_name, attribute = %attribute))]
+    pub async fn scan_where_contains(
+        &self,
+        table_name: &str,
+        attribute: &str,
+        value: AttributeValue,
+        filter: Option<Filter>,
+    ) -> Result<Vec<Item>> {
+        self.scan_where(table_name, Filter::contains(attribute, value), filter)
+            .await
+    }
+
+    /// Scans every page with `condition`, optionally ANDed with `filter`.
+    #[instrument(skip_all, fields(table_name = %table_name))]
+    async fn scan_where(
+        &self,
+        table_name: &str,
+        condition: Filter,
+        filter: Option<Filter>,
+    ) -> Result<Vec<Item>> {
+        let condition = match filter {
+            Some(filter) => condition.and(filter),
+            None => condition,
+        }
+        .build();
+        let params = ScanParams {
+            table_name,
+            ..Default::default()
+        }
+        .with_filter(&condition)?;
 
+        self.scan_all(params, None, None).await
+    }
+
+    /// Counts the items in a table, optionally filtered, without fetching them.
+    ///
+    /// Pages through the whole table and sums the per-page counts.
+    #[instrument(skip_all, fields(table_name = %table_name, operation = "Scan", count = Empty))]
+    pub async fn scan_count(
+        &self,
+        table_name: &str,
+        filter_expression: Option<&str>,
+        expression_attribute_names: Option<HashMap<String, String>>,
+        expression_attribute_values: Option<HashMap<String, AttributeValue>>,
+    ) -> Result<ItemCount> {
+        let params = ScanParams {
+            table_name,
+            filter_expression,
+            expression_attribute_names,
+            expression_attribute_values,
+            ..Default::default()
+        };
+        let mut total = ItemCount::default();
+        let mut last_evaluated_key = None;
+
+        for number in 1.. {
+            let page = page_span(number);
+            let response = self
+                .scan_request(&params, false)
+                .select(Select::Count)
+                .set_exclusive_start_key(last_evaluated_key)
+                .send()
+                .instrument(page.clone())
+                .await
+                .map_err(expression_rejected(params.table_name, None, || {
+                    params.rejected()
+                }))?;
+
+            page.record("count", response.count);
+            total.count += i64::from(response.count);
+            total.scanned_count += i64::from(response.scanned_count);
+
+            last_evaluated_key = response.last_evaluated_key;
             if last_evaluated_key.is_none() {
                 break;
             }
         }
 
-        Ok(items)
+        Span::current().record("count", total.count);
+        Ok(total)
     }
 
     /// Performs a flexible query operation on a DynamoDB table.
@@ -350,17 +3267,15 @@ impl DynamoDb {
     /// This method provides full control over the query operation, allowing you to specify
     /// all major query parameters.
     ///
+    /// Only a single `Query` request is issued, so at most one page (up to 1 MB of data)
+    /// is returned. Use `query_all` to follow `LastEvaluatedKey` through every page.
+    ///
     /// # Arguments
     ///
-    /// * `table_name` - The name of the table to query.
-    /// * `key_condition_expression` - A string representing the key condition expression.
-    /// * `filter_expression` - An optional filter expression to apply to the results.
-    /// * `projection_expression` - An optional projection expression to limit the attributes returned.
-    /// * `expression_attribute_names` - A map of expression attribute names used in the key condition and filter expressions.
-    /// * `expression_attribute_values` - A map of expression attribute values used in the key condition and filter expressions.
-    /// * `limit` - An optional limit on the number of items to evaluate.
-    /// * `scan_index_forward` - An optional boolean to specify the scan direction.
-    /// * `index_name` - An optional name of a secondary index to query.
+    /// * `params` - The table and key condition expression to query, with the
+    ///   optional filter and projection expressions, the attribute names and
+    ///   values they use, a limit on the number of items to evaluate, the scan
+    ///   direction and a secondary index to query.
     ///
     /// # Returns
     ///
@@ -368,65 +3283,394 @@ impl DynamoDb {
     ///
     /// # Example
     ///
-    /// ```rust
-    /// let items = client.query_flexible(
-    ///     "users",
-    ///     "#pk = :pkval AND #sk BETWEEN :start AND :end",
-    ///     Some("age > :min_age"),
-    ///     Some("user_id, username, email"),
-    ///     Some(HashMap::from([
+    /// ```rust,no_run
+    /// # use aws_dynamodb_testing::dynamodb::{DynamoDb, QueryFlexibleParams};
+    /// # use aws_dynamodb_testing::aws::AttributeValue;
+    /// # use std::collections::HashMap;
+    /// # async fn example(client: &DynamoDb) -> anyhow::Result<()> {
+    /// let items = client.query_flexible(QueryFlexibleParams {
+    ///     table_name: "users",
+    ///     key_condition_expression: "#pk = :pkval AND #sk BETWEEN :start AND :end",
+    ///     filter_expression: Some("age > :min_age"),
+    ///     projection_expression: Some("user_id, username, email"),
+    ///     expression_attribute_names: Some(HashMap::from([
     ///         ("#pk".to_string(), "user_id".to_string()),
     ///         ("#sk".to_string(), "created_at".to_string()),
     ///     ])),
-    ///     Some(HashMap::from([
+    ///     expression_attribute_values: Some(HashMap::from([
     ///         (":pkval".to_string(), AttributeValue::S("123".to_string())),
     ///         (":start".to_string(), AttributeValue::S("2023-01-01".to_string())),
     ///         (":end".to_string(), AttributeValue::S("2023-12-31".to_string())),
     ///         (":min_age".to_string(), AttributeValue::N("18".to_string())),
     ///     ])),
-    ///     Some(50),
-    ///     Some(false),
-    ///     Some("user_index")
-    /// ).await?;
+    ///     limit: Some(50),
+    ///     scan_index_forward: Some(false),
+    ///     index_name: Some("user_index"),
+    ///     ..Default::default()
+    /// }).await?;
+    /// # Ok(())
+    /// # }
     /// ```
-
+    #[instrument(
+        skip_all,
+        fields(table_name = %params.table_name, index_name = ?params.index_name)
+    )]
     pub async fn query_flexible(&self, params: QueryFlexibleParams<'_>) -> Result<Vec<Item>> {
-        let mut query = self
-            .client
-            .query()
-            .table_name(params.table_name)
-            .key_condition_expression(params.key_condition_expression)
-            .set_expression_attribute_names(params.expression_attribute_names)
-            .set_expression_attribute_values(params.expression_attribute_values);
+        Ok(self.query_flexible_detailed(params, None).await?.items)
+    }
 
-        if let Some(filter) = params.filter_expression {
-            query = query.filter_expression(filter);
-        }
+    /// Performs a single-page flexible query and keeps the response metadata.
+    ///
+    /// Alongside the items, the result reports how many items DynamoDB
+    /// evaluated before the filter applied, the key to resume from, and the
+    /// read capacity consumed if `params.return_consumed_capacity` asks for
+    /// it.
+    #[instrument(
+        skip_all,
+        fields(
+            table_name = %params.table_name,
+            index_name = ?params.index_name,
+            operation = "Query",
+            count = Empty,
+        )
+    )]
+    pub async fn query_flexible_detailed(
+        &self,
+        params: QueryFlexibleParams<'_>,
+        exclusive_start_key: Option<HashMap<String, AttributeValue>>,
+    ) -> Result<QueryResult> {
+        let response = self
+            .query_request(&params, false)
+            .set_exclusive_start_key(exclusive_start_key)
+            .send()
+            .await
+            .map_err(expression_rejected(params.table_name, None, || {
+                params.rejected()
+            }))?;
 
-        if let Some(projection) = params.projection_expression {
-            query = query.projection_expression(projection);
-        }
+        Span::current().record("count", response.count);
+        Ok(QueryResult {
+            count: response.count,
+            scanned_count: response.scanned_count,
+            consumed_capacity: capacity_units(response.consumed_capacity.as_ref()),
+            last_evaluated_key: response.last_evaluated_key,
+            items: response
+                .items
+                .unwrap_or_default()
+                .into_iter()
+                .map(|attrs| Item { attributes: attrs })
+                .collect(),
+        })
+    }
+
+    /// Performs a flexible query, following `LastEvaluatedKey` across pages.
+    ///
+    /// `params.limit` still sets the page size, while `max_items` caps the total
+    /// number of items returned; the final page is trimmed to fit.
+    #[instrument(
+        skip_all,
+        fields(
+            table_name = %params.table_name,
+            index_name = ?params.index_name,
+            operation = "Query",
+            max_items = ?max_items,
+            count = Empty,
+        )
+    )]
+    pub async fn query_all(
+        &self,
+        params: QueryFlexibleParams<'_>,
+        max_items: Option<usize>,
+    ) -> Result<Vec<Item>> {
+        let mut items = Vec::new();
+        let mut last_evaluated_key = None;
+
+        for number in 1.. {
+            let page = page_span(number);
+            let response = self
+                .query_request(&params, false)
+                .set_exclusive_start_key(last_evaluated_key)
+                .send()
+                .instrument(page.clone())
+                .await
+                .map_err(expression_rejected(params.table_name, None, || {
+                    params.rejected()
+                }))?;
+            page.record("count", response.count);
+
+            items.extend(
+                response
+                    .items
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|attrs| Item { attributes: attrs }),
+            );
+
+            if let Some(max_items) = max_items {
+                if items.len() >= max_items {
+                    items.truncate(max_items);
+                    break;
+                }
+            }
 
-        if let Some(limit_val) = params.limit {
-            query = query.limit(limit_val);
+            last_evaluated_key = response.last_evaluated_key;
+            if last_evaluated_key.is_none() {
+                break;
+            }
         }
 
-        if let Some(forward) = params.scan_index_forward {
-            query = query.scan_index_forward(forward);
+        Span::current().record("count", items.len());
+        Ok(items)
+    }
+
+    /// Queries every shard of a [`ShardedKey`] concurrently and returns
+    /// their items together, shard by shard.
+    ///
+    /// `partition_key_name` is the attribute holding the shard values, and
+    /// `sort_key`, if given, narrows every shard's query the same way. Each
+    /// shard's items are in sort key order, but the merged list isn't. This
+    /// always sends at least one query per shard; see [`ShardedKey`] for
+    /// that tradeoff.
+    #[instrument(
+        skip_all,
+        fields(
+            table_name = %table_name,
+            base = %key.base(),
+            shards = key.shard_count(),
+            count = Empty,
+        )
+    )]
+    pub async fn query_all_shards(
+        &self,
+        table_name: &str,
+        partition_key_name: &str,
+        key: &ShardedKey,
+        sort_key: Option<(&str, SortKeyCondition)>,
+    ) -> Result<Vec<Item>> {
+        let mut queries = JoinSet::new();
+        for (shard, value) in key.shards().enumerate() {
+            let mut condition =
+                KeyCondition::partition(partition_key_name, AttributeValue::S(value));
+            if let Some((name, sort_condition)) = &sort_key {
+                condition = condition.and_sort(*name, sort_condition.clone());
+            }
+            let condition = condition.build()?;
+            let ddb = self.clone();
+            let table_name = table_name.to_string();
+            queries.spawn(
+                async move {
+                    let params = QueryFlexibleParams::from_key_condition(&table_name, &condition);
+                    (shard, ddb.query_all(params, None).await)
+                }
+                .instrument(Span::current()),
+            );
         }
 
-        if let Some(index) = params.index_name {
-            query = query.index_name(index);
+        let mut shards = vec![Vec::new(); key.shard_count() as usize];
+        while let Some(joined) = queries.join_next().await {
+            let (shard, items) = joined?;
+            shards[shard] = items?;
         }
+        let items: Vec<Item> = shards.into_iter().flatten().collect();
+        Span::current().record("count", items.len());
+        Ok(items)
+    }
 
-        let response = query.send().await?;
+    /// Returns the `n` items with the highest sort keys in a partition, newest first.
+    ///
+    /// Uses the table's configured partition key, reads in descending sort key
+    /// order and keeps paging until `n` items have been collected.
+    #[instrument(skip_all, fields(table_name = %table.name(), n))]
+    pub async fn query_latest(
+        &self,
+        table: &Table<'_>,
+        pk_value: AttributeValue,
+        n: i32,
+    ) -> Result<Vec<Item>> {
+        let max_items = usize::try_from(n)
+            .ok()
+            .filter(|&n| n > 0)
+            .ok_or_else(|| anyhow!("query_latest needs a positive item count, got {n}"))?;
+        let key_condition = KeyCondition::partition(table.partition_key(), pk_value).build()?;
+
+        self.query_all(
+            QueryFlexibleParams {
+                limit: Some(n),
+                scan_index_forward: Some(false),
+                ..QueryFlexibleParams::from_key_condition(table.name(), &key_condition)
+            },
+            Some(max_items),
+        )
+        .await
+    }
+
+    /// Performs a single-page query starting after `exclusive_start_key`.
+    ///
+    /// Returns the page of items along with the `LastEvaluatedKey` to pass in
+    /// for the next page, or `None` once the results are exhausted.
+    #[allow(clippy::type_complexity)]
+    #[instrument(
+        skip_all,
+        fields(
+            table_name = %params.table_name,
+            index_name = ?params.index_name,
+            operation = "Query",
+            count = Empty,
+        )
+    )]
+    pub async fn query_paginated(
+        &self,
+        params: QueryFlexibleParams<'_>,
+        exclusive_start_key: Option<HashMap<String, AttributeValue>>,
+    ) -> Result<(Vec<Item>, Option<HashMap<String, AttributeValue>>)> {
+        let response = self
+            .query_request(&params, false)
+            .set_exclusive_start_key(exclusive_start_key)
+            .send()
+            .await
+            .map_err(expression_rejected(params.table_name, None, || {
+                params.rejected()
+            }))?;
 
-        Ok(response
+        let items = response
             .items
             .unwrap_or_default()
             .into_iter()
             .map(|attrs| Item { attributes: attrs })
-            .collect())
+            .collect();
+
+        Span::current().record("count", response.count);
+        Ok((items, response.last_evaluated_key))
+    }
+
+    /// Performs a single-page query, taking and returning an opaque
+    /// [`PageToken`] instead of a raw `LastEvaluatedKey`.
+    ///
+    /// When `signing_key` is given, the incoming token must carry a matching
+    /// signature and the returned token is signed with the same key.
+    #[instrument(skip_all, fields(table_name = %params.table_name))]
+    pub async fn query_paginated_with_token(
+        &self,
+        params: QueryFlexibleParams<'_>,
+        page_token: Option<&str>,
+        signing_key: Option<&[u8]>,
+    ) -> Result<(Vec<Item>, Option<String>)> {
+        let exclusive_start_key = page_token
+            .map(|token| PageToken::decode_with(token, signing_key))
+            .transpose()?;
+        let (items, last_key) = self.query_paginated(params, exclusive_start_key).await?;
+        let next_token = last_key
+            .map(|key| PageToken::encode_with(&key, signing_key))
+            .transpose()?;
+
+        Ok((items, next_token))
+    }
+
+    /// Performs a flexible query and reports the read capacity it consumed.
+    #[instrument(
+        skip_all,
+        fields(
+            table_name = %params.table_name,
+            index_name = ?params.index_name,
+            operation = "Query",
+            count = Empty,
+        )
+    )]
+    pub async fn query_flexible_with_capacity(
+        &self,
+        params: QueryFlexibleParams<'_>,
+    ) -> Result<ReadResult<Vec<Item>>> {
+        let response =
+            self.query_request(&params, true)
+                .send()
+                .await
+                .map_err(expression_rejected(params.table_name, None, || {
+                    params.rejected()
+                }))?;
+
+        Span::current().record("count", response.count);
+        Ok(ReadResult {
+            value: response
+                .items
+                .unwrap_or_default()
+                .into_iter()
+                .map(|attrs| Item { attributes: attrs })
+                .collect(),
+            consumed_capacity: capacity_units(response.consumed_capacity.as_ref()),
+        })
+    }
+
+    /// Builds a single-page `Query` request from `QueryFlexibleParams`.
+    pub(crate) fn query_request(
+        &self,
+        params: &QueryFlexibleParams<'_>,
+        return_capacity: bool,
+    ) -> QueryFluentBuilder {
+        self.client
+            .query()
+            .table_name(params.table_name)
+            .key_condition_expression(params.key_condition_expression)
+            .set_expression_attribute_names(params.expression_attribute_names.clone())
+            .set_expression_attribute_values(params.expression_attribute_values.clone())
+            .set_filter_expression(params.filter_expression.map(str::to_string))
+            .set_projection_expression(params.projection_expression.map(str::to_string))
+            .set_limit(params.limit)
+            .set_scan_index_forward(params.scan_index_forward)
+            .set_index_name(params.index_name.map(str::to_string))
+            .set_consistent_read(params.consistent_read)
+            .set_select(params.select.clone())
+            .set_return_consumed_capacity(
+                params
+                    .return_consumed_capacity
+                    .clone()
+                    .or_else(|| return_consumed_capacity(return_capacity)),
+            )
+    }
+
+    /// Counts the items matched by a query without fetching them.
+    ///
+    /// Follows `LastEvaluatedKey` across pages and sums the per-page counts,
+    /// since a filter only ever sees one page at a time. `params.limit` still
+    /// applies per page.
+    #[instrument(
+        skip_all,
+        fields(
+            table_name = %params.table_name,
+            index_name = ?params.index_name,
+            operation = "Query",
+            count = Empty,
+        )
+    )]
+    pub async fn query_count(&self, params: QueryFlexibleParams<'_>) -> Result<ItemCount> {
+        let mut total = ItemCount::default();
+        let mut last_evaluated_key = None;
+
+        for number in 1.. {
+            let page = page_span(number);
+            let response = self
+                .query_request(&params, false)
+                .set_projection_expression(None)
+                .select(Select::Count)
+                .set_exclusive_start_key(last_evaluated_key)
+                .send()
+                .instrument(page.clone())
+                .await
+                .map_err(expression_rejected(params.table_name, None, || {
+                    params.rejected()
+                }))?;
+
+            page.record("count", response.count);
+            total.count += i64::from(response.count);
+            total.scanned_count += i64::from(response.scanned_count);
+
+            last_evaluated_key = response.last_evaluated_key;
+            if last_evaluated_key.is_none() {
+                break;
+            }
+        }
+
+        Span::current().record("count", total.count);
+        Ok(total)
     }
 
     /// Performs a simple query operation on a DynamoDB table.
@@ -439,8 +3683,11 @@ impl DynamoDb {
     /// * `table_name` - The name of the table to query.
     /// * `partition_key` - A tuple containing the partition key name and value.
     /// * `sort_key_condition` - An optional tuple containing the sort key name, condition, and value.
+    ///   `BETWEEN` takes a [`SortKeyValue::Between`] pair; every other condition takes a single value.
     /// * `filter_expression` - An optional filter expression to apply to the results.
     /// * `limit` - An optional limit on the number of items to evaluate.
+    /// * `expression_attribute_values` - Values referenced by `filter_expression`.
+    /// * `filter` - An optional built [`Filter`]; combined with `filter_expression` via `AND`.
     ///
     /// # Returns
     ///
@@ -448,63 +3695,177 @@ impl DynamoDb {
     ///
     /// # Example
     ///
-    /// ```rust
+    /// ```rust,no_run
+    /// # use aws_dynamodb_testing::dynamodb::{DynamoDb, Filter};
+    /// # use aws_dynamodb_testing::aws::AttributeValue;
+    /// # async fn example(client: &DynamoDb) -> anyhow::Result<()> {
     /// let items = client.query_simple(
     ///     "users",
     ///     ("user_id", AttributeValue::S("123".to_string())),
-    ///     Some(("created_at", ">", AttributeValue::S("2023-01-01".to_string()))),
-    ///     Some("age > :min_age"),
-    ///     Some(10)
+    ///     Some(("created_at", ">".to_string(), AttributeValue::S("2023-01-01".to_string()).into())),
+    ///     None,
+    ///     Some(10),
+    ///     None,
+    ///     Some(&Filter::gt("age", AttributeValue::N("18".to_string())).build()),
     /// ).await?;
+    /// # Ok(())
+    /// # }
     /// ```
+    #[allow(clippy::too_many_arguments)]
+    #[instrument(skip_all, fields(table_name = %table_name, limit = ?limit))]
     pub async fn query_simple(
         &self,
-        table_name: &str,
-        partition_key: (&str, AttributeValue),
-        sort_key_condition: Option<(&str, String, AttributeValue)>,
-        filter_expression: Option<&str>,
-        limit: Option<i32>,
-        expression_attribute_values: Option<HashMap<String, AttributeValue>>,
+        table_name: &str,
+        partition_key: (&str, AttributeValue),
+        sort_key_condition: Option<(&str, String, SortKeyValue)>,
+        filter_expression: Option<&str>,
+        limit: Option<i32>,
+        expression_attribute_values: Option<HashMap<String, AttributeValue>>,
+        filter: Option<&FilterExpression>,
+    ) -> Result<Vec<Item>> {
+        let query = SimpleQuery::new(
+            partition_key,
+            sort_key_condition,
+            filter_expression,
+            expression_attribute_values,
+            filter,
+        )?;
+        self.query_flexible(query.params(table_name, limit)).await
+    }
+
+    /// Queries every item with `partition_key`, optionally narrowed by a
+    /// sort key condition as in [`DynamoDb::query_simple`].
+    ///
+    /// Follows `LastEvaluatedKey` through every page, so partitions larger
+    /// than one 1 MB page come back whole.
+    #[instrument(skip_all, fields(table_name = %table_name, operation = "Query", count = Empty))]
+    pub async fn query_items(
+        &self,
+        table_name: &str,
+        partition_key: (&str, AttributeValue),
+        sort_key_condition: Option<(&str, String, SortKeyValue)>,
+    ) -> Result<Vec<Item>> {
+        let query = SimpleQuery::new(partition_key, sort_key_condition, None, None, None)?;
+        let items = self.query_all(query.params(table_name, None), None).await?;
+        Span::current().record("count", items.len());
+        Ok(items)
+    }
+
+    /// Queries a secondary index by its partition key and an optional sort key condition.
+    ///
+    /// Mirrors [`DynamoDb::query_simple`], but targets `index_name` and takes its
+    /// remaining settings from `options`. Placeholders used in the filter must
+    /// not clash with the generated `#pk`/`#sk`, `:pkval`/`:skval` and `:lo`/`:hi`;
+    /// binding one of them to something else is an error naming it.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use aws_dynamodb_testing::dynamodb::{DynamoDb, QueryOptions};
+    /// # use aws_dynamodb_testing::aws::AttributeValue;
+    /// # async fn example(client: &DynamoDb) -> anyhow::Result<()> {
+    /// let items = client.query_index(
+    ///     "products",
+    ///     "brand-index",
+    ///     ("brand", AttributeValue::S("Acme".to_string())),
+    ///     None,
+    ///     QueryOptions::default(),
+    /// ).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[instrument(skip_all, fields(table_name = %table_name, index_name = %index_name))]
+    pub async fn query_index(
+        &self,
+        table_name: &str,
+        index_name: &str,
+        partition_key: (&str, AttributeValue),
+        sort_key_condition: Option<(&str, String, SortKeyValue)>,
+        options: QueryOptions<'_>,
+    ) -> Result<Vec<Item>> {
+        let key_condition = simple_key_condition(partition_key, sort_key_condition)?;
+        let mut names = options.expression_attribute_names.unwrap_or_default();
+        let mut values = options.expression_attribute_values.unwrap_or_default();
+        key_condition.merge_into(&mut names, &mut values)?;
+
+        self.query_flexible(QueryFlexibleParams {
+            table_name,
+            key_condition_expression: &key_condition.expression,
+            expression_attribute_names: Some(names),
+            expression_attribute_values: Some(values),
+            filter_expression: options.filter_expression,
+            projection_expression: options.projection_expression,
+            limit: options.limit,
+            scan_index_forward: options.scan_index_forward,
+            index_name: Some(index_name),
+            ..Default::default()
+        })
+        .await
+    }
+
+    /// Queries every item in a partition whose sort key starts with `prefix`.
+    ///
+    /// Uses the table's configured keys and follows all pages.
+    #[instrument(skip_all, fields(table_name = %table.name(), prefix = %prefix))]
+    pub async fn query_begins_with(
+        &self,
+        table: &Table<'_>,
+        pk_value: AttributeValue,
+        prefix: &str,
+    ) -> Result<Vec<Item>> {
+        let condition = SortKeyCondition::BeginsWith(AttributeValue::S(prefix.to_string()));
+        self.query_sort_key_condition(table, pk_value, condition)
+            .await
+    }
+
+    /// Queries every item in a partition whose sort key lies between `low` and
+    /// `high`, inclusive.
+    ///
+    /// Uses the table's configured keys and follows all pages.
+    #[instrument(skip_all, fields(table_name = %table.name()))]
+    pub async fn query_between(
+        &self,
+        table: &Table<'_>,
+        pk_value: AttributeValue,
+        low: AttributeValue,
+        high: AttributeValue,
+    ) -> Result<Vec<Item>> {
+        let condition = SortKeyCondition::Between(low, high);
+        self.query_sort_key_condition(table, pk_value, condition)
+            .await
+    }
+
+    /// Runs a paginated query on the table's partition key plus one sort key condition.
+    #[instrument(skip_all, fields(table_name = %table.name()))]
+    async fn query_sort_key_condition(
+        &self,
+        table: &Table<'_>,
+        pk_value: AttributeValue,
+        condition: SortKeyCondition,
     ) -> Result<Vec<Item>> {
-        let mut key_condition_expression = "#pk = :pkval".to_string();
-        let mut expression_attribute_names =
-            HashMap::from([("#pk".to_string(), partition_key.0.to_string())]);
-        let mut expression_attribute_values = expression_attribute_values.unwrap_or_default();
-        expression_attribute_values.insert(":pkval".to_string(), partition_key.1);
+        let sort_key = table
+            .sort_key()
+            .ok_or_else(|| anyhow!("Table '{}' has no sort key", table.name()))?;
 
-        if let Some((sort_key, condition, value)) = sort_key_condition {
-            key_condition_expression.push_str(&format!(" AND #sk {} :skval", condition));
-            expression_attribute_names.insert("#sk".to_string(), sort_key.to_string());
-            expression_attribute_values.insert(":skval".to_string(), value);
-        }
+        let key_condition = KeyCondition::partition(table.partition_key(), pk_value)
+            .and_sort(sort_key, condition)
+            .build()?;
 
-        self.query_flexible(QueryFlexibleParams {
-            table_name,
-            key_condition_expression: &key_condition_expression,
-            expression_attribute_names: Some(expression_attribute_names),
-            expression_attribute_values: Some(expression_attribute_values),
-            filter_expression,
-            projection_expression: None,
-            limit,
-            scan_index_forward: None,
-            index_name: None,
-        })
+        self.query_all(
+            QueryFlexibleParams::from_key_condition(table.name(), &key_condition),
+            None,
+        )
         .await
     }
 
     /// Performs a scan operation on a DynamoDB table with pagination.
     ///
-    /// This method allows for scanning a table with support for filtering, projection,
-    /// and pagination.
+    /// This method allows for scanning a table or index with support for filtering,
+    /// projection, consistent reads and pagination.
     ///
     /// # Arguments
     ///
-    /// * `table_name` - The name of the table to scan.
-    /// * `filter_expression` - An optional filter expression to apply to the results.
-    /// * `projection_expression` - An optional projection expression to limit the attributes returned.
-    /// * `expression_attribute_names` - A map of expression attribute names used in the filter expression.
-    /// * `expression_attribute_values` - A map of expression attribute values used in the filter expression.
-    /// * `limit` - An optional limit on the number of items to evaluate.
+    /// * `params` - The scan parameters; `limit` caps the number of items evaluated per page.
     /// * `exclusive_start_key` - An optional exclusive start key for pagination.
     ///
     /// # Returns
@@ -514,39 +3875,55 @@ impl DynamoDb {
     ///
     /// # Example
     ///
-    /// ```rust
+    /// ```rust,no_run
+    /// # use aws_dynamodb_testing::dynamodb::{DynamoDb, ScanParams};
+    /// # use aws_dynamodb_testing::aws::AttributeValue;
+    /// # use std::collections::HashMap;
+    /// # async fn example(client: &DynamoDb) -> anyhow::Result<()> {
     /// let (items, last_key) = client.scan_paginated(
-    ///     "users",
-    ///     Some("age > :min_age"),
-    ///     Some("user_id, username, email"),
-    ///     Some(HashMap::new()),
-    ///     Some(HashMap::from([(":min_age".to_string(), AttributeValue::N("18".to_string()))])),
-    ///     Some(100),
-    ///     None
+    ///     ScanParams {
+    ///         table_name: "users",
+    ///         filter_expression: Some("age > :min_age"),
+    ///         projection_expression: Some("user_id, username, email"),
+    ///         expression_attribute_values: Some(HashMap::from([(
+    ///             ":min_age".to_string(),
+    ///             AttributeValue::N("18".to_string()),
+    ///         )])),
+    ///         limit: Some(100),
+    ///         ..Default::default()
+    ///     },
+    ///     None,
     /// ).await?;
+    /// # Ok(())
+    /// # }
     /// ```
+    #[allow(clippy::type_complexity)]
+    #[instrument(
+        skip_all,
+        fields(
+            table_name = %params.table_name,
+            index_name = ?params.index_name,
+            operation = "Scan",
+            count = Empty,
+        )
+    )]
     pub async fn scan_paginated(
         &self,
-        table_name: &str,
-        filter_expression: Option<&str>,
-        projection_expression: Option<&str>,
-        expression_attribute_names: Option<HashMap<String, String>>,
-        expression_attribute_values: Option<HashMap<String, AttributeValue>>,
-        limit: Option<i32>,
+        params: ScanParams<'_>,
         exclusive_start_key: Option<HashMap<String, AttributeValue>>,
     ) -> Result<(Vec<Item>, Option<HashMap<String, AttributeValue>>)> {
+        self.check_scan_consistency(&params).await?;
+
         let scan = self
-            .client
-            .scan()
-            .table_name(table_name)
-            .set_filter_expression(filter_expression.map(|s| s.to_string()))
-            .set_projection_expression(projection_expression.map(|s| s.to_string()))
-            .set_expression_attribute_names(expression_attribute_names)
-            .set_expression_attribute_values(expression_attribute_values)
-            .set_limit(limit)
+            .scan_request(&params, false)
             .set_exclusive_start_key(exclusive_start_key);
 
-        let response = scan.send().await?;
+        let response =
+            scan.send()
+                .await
+                .map_err(expression_rejected(params.table_name, None, || {
+                    params.rejected()
+                }))?;
 
         let items = response
             .items
@@ -555,10 +3932,710 @@ impl DynamoDb {
             .map(|attrs| Item { attributes: attrs })
             .collect();
 
+        Span::current().record("count", response.count);
         Ok((items, response.last_evaluated_key))
     }
+
+    /// Performs a single-page scan, taking and returning an opaque
+    /// [`PageToken`] instead of a raw `LastEvaluatedKey`.
+    ///
+    /// See [`DynamoDb::query_paginated_with_token`] for how `signing_key` is
+    /// applied.
+    #[instrument(skip_all, fields(table_name = %params.table_name))]
+    pub async fn scan_paginated_with_token(
+        &self,
+        params: ScanParams<'_>,
+        page_token: Option<&str>,
+        signing_key: Option<&[u8]>,
+    ) -> Result<(Vec<Item>, Option<String>)> {
+        let exclusive_start_key = page_token
+            .map(|token| PageToken::decode_with(token, signing_key))
+            .transpose()?;
+        let (items, last_key) = self.scan_paginated(params, exclusive_start_key).await?;
+        let next_token = last_key
+            .map(|key| PageToken::encode_with(&key, signing_key))
+            .transpose()?;
+
+        Ok((items, next_token))
+    }
+
+    // --- PartiQL Operations ---
+
+    /// Runs one page of a PartiQL statement with positional `?` parameters.
+    ///
+    /// Pass the previous page's `next_token` to continue a `SELECT`; writes
+    /// always complete in a single call and return no items.
+    #[instrument(
+        skip_all,
+        fields(
+            operation = "ExecuteStatement",
+            kind = ?statement.kind(),
+            parameters = parameters.len(),
+            count = Empty,
+        )
+    )]
+    pub async fn execute_statement(
+        &self,
+        statement: &Statement,
+        parameters: Vec<AttributeValue>,
+        next_token: Option<String>,
+    ) -> Result<StatementResult> {
+        if parameters.len() != statement.parameter_count() {
+            return Err(anyhow!(
+                "Statement has {} parameter(s) but {} value(s) were given",
+                statement.parameter_count(),
+                parameters.len()
+            ));
+        }
+
+        let response = self
+            .execute_statement_request(statement, parameters)
+            .set_next_token(next_token)
+            .send()
+            .await
+            .map_err(request_failed(None, None))?;
+
+        Span::current().record("count", response.items().len());
+        Ok(StatementResult {
+            items: response
+                .items
+                .unwrap_or_default()
+                .into_iter()
+                .map(|attrs| Item { attributes: attrs })
+                .collect(),
+            next_token: response.next_token,
+        })
+    }
+
+    /// Builds the first-page `ExecuteStatement` request.
+    pub(crate) fn execute_statement_request(
+        &self,
+        statement: &Statement,
+        parameters: Vec<AttributeValue>,
+    ) -> ExecuteStatementFluentBuilder {
+        self.client
+            .execute_statement()
+            .statement(statement.text())
+            .set_parameters((!parameters.is_empty()).then_some(parameters))
+    }
+}
+
+/// A child span for one page of a paginated request, with the number of
+/// items the page returned recorded as `count` once it arrives.
+fn page_span(number: usize) -> Span {
+    debug_span!("page", number, count = Empty)
+}
+
+/// Result of a write operation along with the capacity it consumed.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct WriteResult {
+    /// Write capacity units consumed, if DynamoDB reported them.
+    pub consumed_capacity: Option<f64>,
+}
+
+/// How a cancellable bulk operation ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BulkStatus {
+    /// Every page or batch was processed.
+    Complete,
+    /// The operation was cancelled between pages or batches, so the result
+    /// only covers the ones processed before that.
+    Cancelled,
+}
+
+/// Result of a cancellable bulk operation, which may be partial.
+#[derive(Debug, Clone)]
+pub struct BulkResult<T> {
+    pub value: T,
+    pub status: BulkStatus,
+}
+
+/// Totals from [`DynamoDb::put_items_concurrent`].
+#[derive(Debug, Default)]
+pub struct ConcurrentPutStats {
+    /// Items written.
+    pub written: usize,
+    /// `BatchWriteItem` requests sent, including retries of unprocessed
+    /// items.
+    pub requests: usize,
+    /// Time the whole load took.
+    pub elapsed: Duration,
+    /// The batches that didn't finish, with the items they didn't write.
+    pub failed: Vec<FailedBatch>,
+}
+
+impl ConcurrentPutStats {
+    /// How many items weren't written.
+    pub fn failed_items(&self) -> usize {
+        self.failed.iter().map(|batch| batch.items.len()).sum()
+    }
+
+    /// Counts a finished batch, taking its requests out of `in_flight`. A
+    /// batch whose task panicked has all of its items listed as failed.
+    fn record_batch(
+        &mut self,
+        in_flight: &mut HashMap<task::Id, Vec<WriteRequest>>,
+        joined: std::result::Result<(task::Id, std::result::Result<usize, FailedBatch>), JoinError>,
+    ) {
+        match joined {
+            Ok((id, Ok(requests))) => {
+                self.written += in_flight.remove(&id).map_or(0, |chunk| chunk.len());
+                self.requests += requests;
+            }
+            Ok((id, Err(failed))) => {
+                let chunk_size = in_flight.remove(&id).map_or(0, |chunk| chunk.len());
+                self.written += chunk_size - failed.items.len();
+                self.requests += failed.requests;
+                self.failed.push(failed);
+            }
+            Err(e) => {
+                // How many requests it sent went down with the task.
+                let chunk = in_flight.remove(&e.id()).unwrap_or_default();
+                let error = anyhow!("Batch task failed: {e}");
+                self.failed.push(FailedBatch::new(chunk, error, 0));
+            }
+        }
+    }
+}
+
+/// A batch write that gave up, either on an error or with items still
+/// unprocessed after every retry.
+#[derive(Debug)]
+pub struct FailedBatch {
+    /// The items that weren't written.
+    pub items: Vec<Item>,
+    /// Why the batch gave up.
+    pub error: anyhow::Error,
+    /// Requests the batch sent before giving up.
+    pub requests: usize,
+}
+
+impl FailedBatch {
+    fn new(pending: Vec<WriteRequest>, error: anyhow::Error, requests: usize) -> Self {
+        let items = pending
+            .into_iter()
+            .filter_map(|request| request.put_request)
+            .map(|put| Item::from(put.item))
+            .collect();
+        FailedBatch {
+            items,
+            error,
+            requests,
+        }
+    }
+}
+
+/// Result of a read operation along with the capacity it consumed.
+#[derive(Debug, Clone)]
+pub struct ReadResult<T> {
+    pub value: T,
+    /// Read capacity units consumed, if DynamoDB reported them.
+    pub consumed_capacity: Option<f64>,
+}
+
+/// Typed values converted from a batch of items, along with the items that
+/// failed to convert.
+#[derive(Debug, Clone)]
+pub struct TypedItems<T> {
+    pub values: Vec<T>,
+    pub errors: Vec<ItemConversionError>,
+}
+
+impl<T> TypedItems<T> {
+    /// Returns the values, or the first conversion error if any item failed.
+    pub fn into_result(self) -> Result<Vec<T>> {
+        match self.errors.into_iter().next() {
+            Some(error) => Err(anyhow!(error)),
+            None => Ok(self.values),
+        }
+    }
+}
+
+/// An item that couldn't be converted into a typed value.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ItemConversionError {
+    /// The key attributes of the failing item.
+    pub key: HashMap<String, AttributeValue>,
+    pub message: String,
+}
+
+impl std::fmt::Display for ItemConversionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut key: Vec<_> = self
+            .key
+            .iter()
+            .map(|(name, value)| format!("{name}={value:?}"))
+            .collect();
+        key.sort();
+        write!(f, "Item with key {{{}}}: {}", key.join(", "), self.message)
+    }
+}
+
+impl std::error::Error for ItemConversionError {}
+
+/// A page of query results along with the response metadata.
+///
+/// `scanned_count` is the number of items DynamoDB evaluated before the filter
+/// applied; a large gap to `count` means the filter discards most of what the
+/// key condition reads.
+#[derive(Debug, Clone)]
+pub struct QueryResult {
+    pub items: Vec<Item>,
+    pub count: i32,
+    pub scanned_count: i32,
+    pub last_evaluated_key: Option<HashMap<String, AttributeValue>>,
+    /// Read capacity units consumed, if the query asked for them.
+    pub consumed_capacity: Option<f64>,
+}
+
+/// A page of results from a PartiQL statement.
+#[derive(Debug, Clone)]
+pub struct StatementResult {
+    pub items: Vec<Item>,
+    /// Set when a `SELECT` has more pages to read.
+    pub next_token: Option<String>,
+}
+
+/// Builds the key condition for the `(name, operator, value)` style query helpers.
+fn simple_key_condition(
+    partition_key: (&str, AttributeValue),
+    sort_key_condition: Option<(&str, String, SortKeyValue)>,
+) -> Result<KeyConditionExpression> {
+    let mut key_condition = KeyCondition::partition(partition_key.0, partition_key.1);
+    if let Some((sort_key, condition, value)) = sort_key_condition {
+        key_condition =
+            key_condition.and_sort(sort_key, SortKeyCondition::parse(&condition, value)?);
+    }
+    key_condition.build()
+}
+
+/// The expressions [`DynamoDb::query_simple`] sends, with the key condition,
+/// filter and caller's placeholders merged into one set of maps.
+pub(crate) struct SimpleQuery {
+    key_condition: String,
+    names: HashMap<String, String>,
+    values: HashMap<String, AttributeValue>,
+    filter_expression: Option<String>,
+}
+
+impl SimpleQuery {
+    pub(crate) fn new(
+        partition_key: (&str, AttributeValue),
+        sort_key_condition: Option<(&str, String, SortKeyValue)>,
+        filter_expression: Option<&str>,
+        expression_attribute_values: Option<HashMap<String, AttributeValue>>,
+        filter: Option<&FilterExpression>,
+    ) -> Result<Self> {
+        let key_condition = simple_key_condition(partition_key, sort_key_condition)?;
+        let mut names = HashMap::new();
+        let mut values = expression_attribute_values.unwrap_or_default();
+        key_condition.merge_into(&mut names, &mut values)?;
+
+        let filter_expression = match filter {
+            None => filter_expression.map(str::to_string),
+            Some(filter) => {
+                filter.merge_into(&mut names, &mut values)?;
+                Some(match filter_expression {
+                    Some(raw) => format!("({raw}) AND {}", filter.expression),
+                    None => filter.expression.clone(),
+                })
+            }
+        };
+
+        Ok(Self {
+            key_condition: key_condition.expression,
+            names,
+            values,
+            filter_expression,
+        })
+    }
+
+    pub(crate) fn params<'a>(
+        &'a self,
+        table_name: &'a str,
+        limit: Option<i32>,
+    ) -> QueryFlexibleParams<'a> {
+        QueryFlexibleParams {
+            table_name,
+            key_condition_expression: &self.key_condition,
+            expression_attribute_names: Some(self.names.clone()),
+            expression_attribute_values: Some(self.values.clone()),
+            filter_expression: self.filter_expression.as_deref(),
+            limit,
+            ..Default::default()
+        }
+    }
+}
+
+/// The key attributes, key schema, indexes and billing of a [`Table`], in
+/// the shape the `CreateTable` and `ImportTable` requests expect.
+struct TableDefinition {
+    attribute_definitions: Vec<AttributeDefinition>,
+    key_schema: Vec<KeySchemaElement>,
+    /// `None` rather than empty, since DynamoDB rejects an empty index list.
+    global_secondary_indexes: Option<Vec<GlobalSecondaryIndex>>,
+    billing_mode: BillingMode,
+    /// Only set for provisioned tables.
+    provisioned_throughput: Option<ProvisionedThroughput>,
+}
+
+impl TableDefinition {
+    fn new(table: &Table<'_>) -> Result<Self> {
+        let provisioned_throughput = provisioned_throughput(table.capacity())?;
+
+        let mut attribute_definitions = vec![AttributeDefinition::builder()
+            .attribute_name(table.partition_key())
+            .attribute_type(key_attribute_type(table, table.partition_key()))
+            .build()?];
+
+        let mut key_schema = vec![KeySchemaElement::builder()
+            .attribute_name(table.partition_key())
+            .key_type(KeyType::Hash)
+            .build()?];
+
+        if let Some(sort_key) = table.sort_key() {
+            attribute_definitions.push(
+                AttributeDefinition::builder()
+                    .attribute_name(sort_key)
+                    .attribute_type(key_attribute_type(table, sort_key))
+                    .build()?,
+            );
+            key_schema.push(
+                KeySchemaElement::builder()
+                    .attribute_name(sort_key)
+                    .key_type(KeyType::Range)
+                    .build()?,
+            );
+        }
+
+        let global_secondary_indexes = table
+            .global_secondary_indexes()
+            .iter()
+            .map(|index| {
+                index_definition(
+                    table,
+                    index,
+                    &mut attribute_definitions,
+                    provisioned_throughput.clone(),
+                )
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self {
+            attribute_definitions,
+            key_schema,
+            global_secondary_indexes: (!global_secondary_indexes.is_empty())
+                .then_some(global_secondary_indexes),
+            billing_mode: match table.capacity() {
+                Capacity::OnDemand => BillingMode::PayPerRequest,
+                Capacity::Provisioned { .. } => BillingMode::Provisioned,
+            },
+            provisioned_throughput,
+        })
+    }
+}
+
+/// The throughput to request for a table, or each of its indexes, under
+/// `capacity`.
+fn provisioned_throughput(capacity: Capacity) -> Result<Option<ProvisionedThroughput>> {
+    match capacity {
+        Capacity::OnDemand => Ok(None),
+        Capacity::Provisioned {
+            read_capacity_units,
+            write_capacity_units,
+        } => Ok(Some(
+            ProvisionedThroughput::builder()
+                .read_capacity_units(read_capacity_units)
+                .write_capacity_units(write_capacity_units)
+                .build()?,
+        )),
+    }
+}
+
+/// Builds the SDK definition of a global secondary index on `table`.
+///
+/// Definitions for the index's key attributes are appended to
+/// `attribute_definitions` unless already present. Indexes on provisioned
+/// tables need their own `provisioned_throughput`.
+fn index_definition(
+    table: &Table<'_>,
+    index: &crate::dynamodb::GlobalSecondaryIndex<'_>,
+    attribute_definitions: &mut Vec<AttributeDefinition>,
+    provisioned_throughput: Option<ProvisionedThroughput>,
+) -> Result<GlobalSecondaryIndex> {
+    let mut key_schema = Vec::new();
+    let index_keys = [
+        (Some(index.partition_key()), KeyType::Hash),
+        (index.sort_key(), KeyType::Range),
+    ];
+    for (attribute, key_type) in index_keys {
+        let Some(attribute) = attribute else {
+            continue;
+        };
+        if !attribute_definitions
+            .iter()
+            .any(|d| d.attribute_name() == attribute)
+        {
+            attribute_definitions.push(
+                AttributeDefinition::builder()
+                    .attribute_name(attribute)
+                    .attribute_type(key_attribute_type(table, attribute))
+                    .build()?,
+            );
+        }
+        key_schema.push(
+            KeySchemaElement::builder()
+                .attribute_name(attribute)
+                .key_type(key_type)
+                .build()?,
+        );
+    }
+
+    Ok(GlobalSecondaryIndex::builder()
+        .index_name(index.name())
+        .set_key_schema(Some(key_schema))
+        .projection(
+            Projection::builder()
+                .projection_type(ProjectionType::All)
+                .build(),
+        )
+        .set_provisioned_throughput(provisioned_throughput)
+        .build()?)
+}
+
+/// Picks the DynamoDB type for a key attribute from the table's schema.
+///
+/// Keys declared as `FieldType::Number` become `N`; everything else is `S`.
+fn key_attribute_type(table: &Table<'_>, attribute: &str) -> ScalarAttributeType {
+    match table.field_type(attribute) {
+        FieldType::Number => ScalarAttributeType::N,
+        _ => ScalarAttributeType::S,
+    }
+}
+
+/// Maps the capacity flag onto the SDK request parameter.
+fn return_consumed_capacity(enabled: bool) -> Option<ReturnConsumedCapacity> {
+    enabled.then_some(ReturnConsumedCapacity::Total)
+}
+
+/// Extracts the total capacity units from a response.
+fn capacity_units(consumed: Option<&ConsumedCapacity>) -> Option<f64> {
+    consumed.and_then(|c| c.capacity_units())
+}
+
+/// The `BatchWriteItem` request that puts `item`.
+fn put_request(item: Item) -> Result<WriteRequest> {
+    Ok(WriteRequest::builder()
+        .put_request(
+            PutRequest::builder()
+                .set_item(Some(item.attributes))
+                .build()?,
+        )
+        .build())
+}
+
+/// Adds one page's capacity to a running total.
+fn add_capacity(total: Option<f64>, page: Option<&ConsumedCapacity>) -> Option<f64> {
+    match (total, capacity_units(page)) {
+        (None, None) => None,
+        (total, page) => Some(total.unwrap_or_default() + page.unwrap_or_default()),
+    }
+}
+
+/// Running totals reported to the progress callbacks of the bulk operations
+/// ([`DynamoDb::scan_all`] and [`DynamoDb::batch_put_items`]).
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Progress {
+    /// Requests made so far: scan pages or batch writes.
+    pub pages: usize,
+    /// Items read or written so far.
+    pub items: usize,
+    /// Time since the operation started.
+    pub elapsed: Duration,
+    /// Capacity units consumed so far, if DynamoDB reported any.
+    pub consumed_capacity: Option<f64>,
+}
+
+impl Progress {
+    /// Counts one more request that handled `items` items.
+    pub(crate) fn record_page(&mut self, items: usize, start: Instant) {
+        self.pages += 1;
+        self.items += items;
+        self.elapsed = start.elapsed();
+    }
+
+    /// Average throughput so far.
+    pub fn items_per_sec(&self) -> f64 {
+        let seconds = self.elapsed.as_secs_f64();
+        if seconds > 0.0 {
+            self.items as f64 / seconds
+        } else {
+            0.0
+        }
+    }
+}
+
+/// What [`DynamoDb::for_each_item`] got through.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ScanSummary {
+    /// Scan pages requested.
+    pub pages: usize,
+    /// Items handed to the handler, including the one that stopped the scan.
+    pub items: usize,
+    /// Whether the handler stopped the scan.
+    pub stopped_early: bool,
+}
+
+/// Running totals for a rate-limited scan.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct ScanStats {
+    pub pages: usize,
+    pub items: usize,
+    /// Time since the scan started, including any pauses.
+    pub elapsed: Duration,
+    /// Read capacity units consumed so far.
+    pub consumed_capacity: f64,
+}
+
+impl ScanStats {
+    /// Average read capacity consumed per second so far.
+    pub fn average_rcu_per_sec(&self) -> f64 {
+        let seconds = self.elapsed.as_secs_f64();
+        if seconds > 0.0 {
+            self.consumed_capacity / seconds
+        } else {
+            0.0
+        }
+    }
+
+    /// How long to pause so the average falls to `target_rcu_per_sec`.
+    ///
+    /// Consuming `consumed_capacity` units at the target rate takes
+    /// `consumed_capacity / target` seconds; the pause is whatever of that
+    /// hasn't already elapsed.
+    ///
+    /// Fails if the target isn't a positive number, or is so small that the
+    /// pause wouldn't fit in a `Duration`.
+    pub fn delay_for_target(&self, target_rcu_per_sec: f64) -> Result<Duration> {
+        check_rcu_target(target_rcu_per_sec)?;
+        let budget = Duration::try_from_secs_f64(self.consumed_capacity / target_rcu_per_sec)
+            .map_err(|_| {
+                anyhow!("Target read capacity {target_rcu_per_sec} is too small to pace a scan at")
+            })?;
+        Ok(budget.saturating_sub(self.elapsed))
+    }
+}
+
+/// Rejects a read capacity target that's zero, negative, infinite or NaN.
+fn check_rcu_target(target_rcu_per_sec: f64) -> Result<()> {
+    if !(target_rcu_per_sec.is_finite() && target_rcu_per_sec > 0.0) {
+        return Err(anyhow!(
+            "Target read capacity must be a positive number, got {target_rcu_per_sec}"
+        ));
+    }
+    Ok(())
+}
+
+/// Item totals returned by the counting operations.
+///
+/// `count` is the number of items that matched after filtering, while
+/// `scanned_count` is the number DynamoDB evaluated before the filter applied.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ItemCount {
+    pub count: i64,
+    pub scanned_count: i64,
+}
+
+/// Parameters for the scan operations.
+///
+/// Only `table_name` is required; everything else defaults to `None`.
+/// `consistent_read` is rejected when `index_name` names a global secondary index.
+#[derive(Debug, Default, Clone)]
+pub struct ScanParams<'a> {
+    pub table_name: &'a str,
+    pub index_name: Option<&'a str>,
+    pub filter_expression: Option<&'a str>,
+    pub projection_expression: Option<&'a str>,
+    pub expression_attribute_names: Option<HashMap<String, String>>,
+    pub expression_attribute_values: Option<HashMap<String, AttributeValue>>,
+    pub limit: Option<i32>,
+    pub consistent_read: Option<bool>,
+}
+
+impl<'a> ScanParams<'a> {
+    /// The scan's expressions, for reporting a `ValidationException`.
+    fn rejected(&self) -> ExpressionRejected {
+        ExpressionRejected {
+            filter_expression: self.filter_expression.map(str::to_string),
+            projection_expression: self.projection_expression.map(str::to_string),
+            ..ExpressionRejected::new(
+                self.table_name,
+                self.expression_attribute_names.as_ref(),
+                self.expression_attribute_values.as_ref(),
+            )
+        }
+    }
+
+    /// Applies an aliased [`ProjectionExpression`], merging its placeholders
+    /// into the expression attribute names.
+    ///
+    /// Fails if a projection expression is already set or a placeholder collides.
+    pub fn with_projection(mut self, projection: &'a ProjectionExpression) -> Result<Self> {
+        if self.projection_expression.is_some() {
+            return Err(anyhow!("Scan already has a projection expression"));
+        }
+        let mut names = self.expression_attribute_names.unwrap_or_default();
+        projection.merge_into(&mut names)?;
+
+        self.projection_expression = Some(&projection.expression);
+        self.expression_attribute_names = Some(names);
+        Ok(self)
+    }
+
+    /// Applies a built [`Filter`], merging its placeholders into the
+    /// expression maps.
+    ///
+    /// Fails if a filter expression is already set or a placeholder collides.
+    pub fn with_filter(mut self, filter: &'a FilterExpression) -> Result<Self> {
+        if self.filter_expression.is_some() {
+            return Err(anyhow!("Scan already has a filter expression"));
+        }
+        let mut names = self.expression_attribute_names.unwrap_or_default();
+        let mut values = self.expression_attribute_values.unwrap_or_default();
+        filter.merge_into(&mut names, &mut values)?;
+
+        self.filter_expression = Some(&filter.expression);
+        self.expression_attribute_names = Some(names);
+        self.expression_attribute_values = Some(values);
+        Ok(self)
+    }
+}
+
+/// Optional settings for [`DynamoDb::get_item_with`].
+#[derive(Debug, Default, Clone)]
+pub struct GetItemOptions<'a> {
+    /// Strongly consistent read; DynamoDB defaults to eventually consistent.
+    pub consistent_read: Option<bool>,
+    /// Which attributes to return; every attribute if `None`.
+    pub projection: Option<&'a ProjectionExpression>,
+}
+
+/// Optional settings for the convenience query methods.
+#[derive(Debug, Default, Clone)]
+pub struct QueryOptions<'a> {
+    pub filter_expression: Option<&'a str>,
+    pub projection_expression: Option<&'a str>,
+    pub expression_attribute_names: Option<HashMap<String, String>>,
+    pub expression_attribute_values: Option<HashMap<String, AttributeValue>>,
+    pub limit: Option<i32>,
+    pub scan_index_forward: Option<bool>,
 }
 
+/// Parameters for the flexible query operations.
+///
+/// Only `table_name` and `key_condition_expression` are required; everything
+/// else defaults to `None`, which leaves the setting to DynamoDB.
+#[derive(Debug, Default, Clone)]
 pub struct QueryFlexibleParams<'a> {
     pub table_name: &'a str,
     pub key_condition_expression: &'a str,
@@ -569,4 +4646,78 @@ pub struct QueryFlexibleParams<'a> {
     pub limit: Option<i32>,
     pub scan_index_forward: Option<bool>,
     pub index_name: Option<&'a str>,
+    /// Strongly consistent reads; not supported on global secondary indexes.
+    pub consistent_read: Option<bool>,
+    /// Which attributes to return, or `Select::Count` for just the count.
+    pub select: Option<Select>,
+    /// Capacity reporting to request, overriding the method's own choice
+    /// (e.g. `Indexes` for a per-index breakdown).
+    pub return_consumed_capacity: Option<ReturnConsumedCapacity>,
+}
+
+impl<'a> QueryFlexibleParams<'a> {
+    /// The query's expressions, for reporting a `ValidationException`.
+    fn rejected(&self) -> ExpressionRejected {
+        ExpressionRejected {
+            key_condition_expression: Some(self.key_condition_expression.to_string()),
+            filter_expression: self.filter_expression.map(str::to_string),
+            projection_expression: self.projection_expression.map(str::to_string),
+            ..ExpressionRejected::new(
+                self.table_name,
+                self.expression_attribute_names.as_ref(),
+                self.expression_attribute_values.as_ref(),
+            )
+        }
+    }
+
+    /// Applies an aliased [`ProjectionExpression`], merging its placeholders
+    /// into the expression attribute names.
+    ///
+    /// Fails if a projection expression is already set or a placeholder collides.
+    pub fn with_projection(mut self, projection: &'a ProjectionExpression) -> Result<Self> {
+        if self.projection_expression.is_some() {
+            return Err(anyhow!("Query already has a projection expression"));
+        }
+        let mut names = self.expression_attribute_names.unwrap_or_default();
+        projection.merge_into(&mut names)?;
+
+        self.projection_expression = Some(&projection.expression);
+        self.expression_attribute_names = Some(names);
+        Ok(self)
+    }
+
+    /// Applies a built [`Filter`], merging its placeholders into the
+    /// expression maps.
+    ///
+    /// Fails if a filter expression is already set or a placeholder collides.
+    pub fn with_filter(mut self, filter: &'a FilterExpression) -> Result<Self> {
+        if self.filter_expression.is_some() {
+            return Err(anyhow!("Query already has a filter expression"));
+        }
+        let mut names = self.expression_attribute_names.unwrap_or_default();
+        let mut values = self.expression_attribute_values.unwrap_or_default();
+        filter.merge_into(&mut names, &mut values)?;
+
+        self.filter_expression = Some(&filter.expression);
+        self.expression_attribute_names = Some(names);
+        self.expression_attribute_values = Some(values);
+        Ok(self)
+    }
+
+    /// Creates query parameters from a built [`KeyCondition`], leaving every
+    /// other setting unset.
+    pub fn from_key_condition(
+        table_name: &'a str,
+        key_condition: &'a KeyConditionExpression,
+    ) -> Self {
+        Self {
+            table_name,
+            key_condition_expression: &key_condition.expression,
+            expression_attribute_names: Some(key_condition.names.clone()),
+            expression_attribute_values: Some(key_condition.values.clone()),
+            filter_expression: None,
+            projection_expression: None,
+            ..Default::default()
+        }
+    }
 }
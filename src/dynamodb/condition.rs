@@ -0,0 +1,131 @@
+use aws_sdk_dynamodb::types::CancellationReason;
+use std::fmt;
+
+use crate::dynamodb::Item;
+
+/// The code DynamoDB gives an operation whose condition was false.
+const CONDITIONAL_CHECK_FAILED: &str = "ConditionalCheckFailed";
+
+/// Returned when a conditional put or delete finds its condition false.
+///
+/// `item` holds the item as DynamoDB found it, when the request asked for
+/// it to be returned.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConditionFailed {
+    pub table_name: String,
+    pub item: Option<Item>,
+}
+
+impl fmt::Display for ConditionFailed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "The condition on table '{}' was not met",
+            self.table_name
+        )
+    }
+}
+
+impl std::error::Error for ConditionFailed {}
+
+/// Returned when DynamoDB cancels a write transaction.
+///
+/// Both lists have one entry per operation, in request order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TransactionCancelled {
+    /// Why each operation failed, e.g. `ConditionalCheckFailed`, or `None`
+    /// for operations that would have succeeded.
+    pub codes: Vec<Option<String>>,
+    /// The item each failed condition found, for operations that asked for
+    /// it to be returned.
+    pub items: Vec<Option<Item>>,
+}
+
+impl TransactionCancelled {
+    pub(crate) fn new(reasons: &[CancellationReason]) -> Self {
+        Self {
+            // Operations that didn't fail have the code "None".
+            codes: reasons
+                .iter()
+                .map(|reason| {
+                    reason
+                        .code()
+                        .filter(|code| *code != "None")
+                        .map(str::to_string)
+                })
+                .collect(),
+            items: reasons
+                .iter()
+                .map(|reason| {
+                    reason.item().map(|attributes| Item {
+                        attributes: attributes.clone(),
+                    })
+                })
+                .collect(),
+        }
+    }
+
+    /// The indexes of the operations whose conditions were false.
+    pub fn failed_conditions(&self) -> Vec<usize> {
+        self.codes
+            .iter()
+            .enumerate()
+            .filter(|(_, code)| code.as_deref() == Some(CONDITIONAL_CHECK_FAILED))
+            .map(|(index, _)| index)
+            .collect()
+    }
+}
+
+impl fmt::Display for TransactionCancelled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let failures: Vec<_> = self
+            .codes
+            .iter()
+            .enumerate()
+            .filter_map(|(index, code)| Some(format!("operation {index}: {}", code.as_deref()?)))
+            .collect();
+        write!(f, "Transaction cancelled ({})", failures.join(", "))
+    }
+}
+
+impl std::error::Error for TransactionCancelled {}
+
+/// Questions callers ask of a failed write, whichever operation it was.
+///
+/// Implemented for `anyhow::Error`, so `error.is_condition_failure()` works
+/// on anything the client returns.
+pub trait DynamoError {
+    /// Whether the write failed because a condition was false, either its
+    /// own or, in a transaction, any operation's.
+    fn is_condition_failure(&self) -> bool;
+
+    /// The indexes of the transaction operations whose conditions were
+    /// false; empty if this isn't a cancelled transaction.
+    fn failed_conditions(&self) -> Vec<usize>;
+
+    /// The item a failed condition found, if the request asked for it. For
+    /// a transaction, the first operation that returned one.
+    fn condition_failure_item(&self) -> Option<&Item>;
+}
+
+impl DynamoError for anyhow::Error {
+    fn is_condition_failure(&self) -> bool {
+        self.downcast_ref::<ConditionFailed>().is_some() || !self.failed_conditions().is_empty()
+    }
+
+    fn failed_conditions(&self) -> Vec<usize> {
+        self.downcast_ref::<TransactionCancelled>()
+            .map(TransactionCancelled::failed_conditions)
+            .unwrap_or_default()
+    }
+
+    fn condition_failure_item(&self) -> Option<&Item> {
+        if let Some(failed) = self.downcast_ref::<ConditionFailed>() {
+            return failed.item.as_ref();
+        }
+        self.downcast_ref::<TransactionCancelled>()?
+            .items
+            .iter()
+            .find_map(Option::as_ref)
+    }
+}
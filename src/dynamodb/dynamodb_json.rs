@@ -0,0 +1,219 @@
+use anyhow::{anyhow, Result};
+use aws_sdk_dynamodb::{primitives::Blob, types::AttributeValue};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::str::FromStr;
+
+/// The type descriptors DynamoDB JSON wraps each value in.
+const TYPE_DESCRIPTORS: [&str; 10] = ["S", "N", "B", "BOOL", "NULL", "M", "L", "SS", "NS", "BS"];
+
+/// How items are written in a JSON file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JsonFormat {
+    /// Ordinary JSON objects: `{"id": "123", "price": 9.5}`.
+    Plain,
+    /// DynamoDB JSON, with every value wrapped in its type:
+    /// `{"id": {"S": "123"}, "price": {"N": "9.5"}}`.
+    DynamoDb,
+}
+
+impl JsonFormat {
+    /// Guesses the format of an item from its shape.
+    pub fn detect(item: &Value) -> Self {
+        match item {
+            Value::Object(object) if is_dynamodb_json(object) => Self::DynamoDb,
+            _ => Self::Plain,
+        }
+    }
+}
+
+impl FromStr for JsonFormat {
+    type Err = anyhow::Error;
+
+    /// Parses `json` or `dynamodb`, as accepted by the CLI's `--format`.
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "json" | "plain" => Ok(Self::Plain),
+            "dynamodb" | "dynamodb-json" => Ok(Self::DynamoDb),
+            _ => Err(anyhow!(
+                "Unknown JSON format '{s}', expected json or dynamodb"
+            )),
+        }
+    }
+}
+
+/// Whether every attribute of `object` is a single-key `{"<type>": value}`
+/// wrapper, i.e. the object is an item in DynamoDB JSON rather than plain
+/// JSON.
+fn is_dynamodb_json(object: &Map<String, Value>) -> bool {
+    !object.is_empty()
+        && object.values().all(|value| match value {
+            Value::Object(typed) => {
+                typed.len() == 1
+                    && typed
+                        .keys()
+                        .all(|kind| TYPE_DESCRIPTORS.contains(&kind.as_str()))
+            }
+            _ => false,
+        })
+}
+
+/// Converts a DynamoDB JSON value (`{"S": "abc"}`, `{"N": "1.5"}`, ...) into
+/// an attribute value. Binary values are base64-encoded, as in the AWS CLI.
+pub(crate) fn to_attribute_value(value: Value) -> Result<AttributeValue> {
+    let Value::Object(typed) = value else {
+        return Err(anyhow!(
+            "Expected a {{\"<type>\": value}} object, found {value}"
+        ));
+    };
+    let mut typed = typed.into_iter();
+    let (Some((kind, value)), None) = (typed.next(), typed.next()) else {
+        return Err(anyhow!("Expected exactly one type descriptor per value"));
+    };
+
+    Ok(match (kind.as_str(), value) {
+        ("S", Value::String(s)) => AttributeValue::S(s),
+        ("N", Value::String(n)) => AttributeValue::N(number(n)?),
+        ("B", Value::String(b)) => AttributeValue::B(binary(&b)?),
+        ("BOOL", Value::Bool(b)) => AttributeValue::Bool(b),
+        ("NULL", Value::Bool(true)) => AttributeValue::Null(true),
+        ("M", Value::Object(map)) => AttributeValue::M(
+            map.into_iter()
+                .map(|(name, value)| Ok((name, to_attribute_value(value)?)))
+                .collect::<Result<_>>()?,
+        ),
+        ("L", Value::Array(list)) => AttributeValue::L(
+            list.into_iter()
+                .map(to_attribute_value)
+                .collect::<Result<_>>()?,
+        ),
+        ("SS", Value::Array(set)) => AttributeValue::Ss(strings(set)?),
+        ("NS", Value::Array(set)) => AttributeValue::Ns(
+            strings(set)?
+                .into_iter()
+                .map(number)
+                .collect::<Result<_>>()?,
+        ),
+        ("BS", Value::Array(set)) => AttributeValue::Bs(
+            strings(set)?
+                .iter()
+                .map(|b| binary(b))
+                .collect::<Result<_>>()?,
+        ),
+        (kind, value) => return Err(anyhow!("Invalid {kind} value {value}")),
+    })
+}
+
+fn number(n: String) -> Result<String> {
+    match n.parse::<f64>() {
+        Ok(_) => Ok(n),
+        Err(_) => Err(anyhow!("Invalid number '{n}'")),
+    }
+}
+
+fn binary(b: &str) -> Result<Blob> {
+    STANDARD
+        .decode(b)
+        .map(Blob::new)
+        .map_err(|e| anyhow!("Invalid base64 binary value: {e}"))
+}
+
+fn strings(values: Vec<Value>) -> Result<Vec<String>> {
+    values
+        .into_iter()
+        .map(|value| match value {
+            Value::String(s) => Ok(s),
+            value => Err(anyhow!("Expected a string in set, found {value}")),
+        })
+        .collect()
+}
+
+/// Converts an attribute value into DynamoDB JSON, the inverse of
+/// [`to_attribute_value`].
+pub(crate) fn from_attribute_value(value: &AttributeValue) -> Value {
+    let (kind, value) = match value {
+        AttributeValue::S(s) => ("S", Value::from(s.as_str())),
+        AttributeValue::N(n) => ("N", Value::from(n.as_str())),
+        AttributeValue::B(b) => ("B", Value::from(STANDARD.encode(b.as_ref()))),
+        AttributeValue::Bool(b) => ("BOOL", Value::from(*b)),
+        AttributeValue::Null(_) => ("NULL", Value::from(true)),
+        AttributeValue::M(map) => ("M", Value::Object(to_object(map, from_attribute_value))),
+        AttributeValue::L(list) => ("L", list.iter().map(from_attribute_value).collect()),
+        AttributeValue::Ss(set) => ("SS", set.iter().map(String::as_str).collect()),
+        AttributeValue::Ns(set) => ("NS", set.iter().map(String::as_str).collect()),
+        AttributeValue::Bs(set) => (
+            "BS",
+            set.iter().map(|b| STANDARD.encode(b.as_ref())).collect(),
+        ),
+        _ => ("NULL", Value::from(true)),
+    };
+    Value::Object(Map::from_iter([(kind.to_string(), value)]))
+}
+
+/// Converts plain JSON into an attribute value, the same way as
+/// [`Item::from_json`](crate::dynamodb::Item::from_json): numbers become `N`,
+/// strings `S`, arrays `L` and objects `M`.
+pub(crate) fn from_plain_json(value: &Value) -> AttributeValue {
+    match value {
+        Value::Null => AttributeValue::Null(true),
+        Value::Bool(b) => AttributeValue::Bool(*b),
+        // Floats are written as Rust writes them, so `1e20` stays digits.
+        Value::Number(n) => AttributeValue::N(match n.as_f64() {
+            Some(f) if n.is_f64() => f.to_string(),
+            _ => n.to_string(),
+        }),
+        Value::String(s) => AttributeValue::S(s.clone()),
+        Value::Array(values) => AttributeValue::L(values.iter().map(from_plain_json).collect()),
+        Value::Object(object) => AttributeValue::M(
+            object
+                .iter()
+                .map(|(name, value)| (name.clone(), from_plain_json(value)))
+                .collect(),
+        ),
+    }
+}
+
+/// Converts an attribute value into plain JSON.
+///
+/// This loses some type information: sets become arrays, binary values
+/// base64 strings, and numbers are re-encoded as JSON numbers (so `"1.50"`
+/// comes back as `1.5`).
+pub(crate) fn to_plain_json(value: &AttributeValue) -> Value {
+    match value {
+        AttributeValue::S(s) => Value::from(s.as_str()),
+        AttributeValue::N(n) => plain_number(n),
+        AttributeValue::B(b) => Value::from(STANDARD.encode(b.as_ref())),
+        AttributeValue::Bool(b) => Value::from(*b),
+        AttributeValue::M(map) => Value::Object(to_object(map, to_plain_json)),
+        AttributeValue::L(list) => list.iter().map(to_plain_json).collect(),
+        AttributeValue::Ss(set) => set.iter().map(String::as_str).collect(),
+        AttributeValue::Ns(set) => set.iter().map(|n| plain_number(n)).collect(),
+        AttributeValue::Bs(set) => set.iter().map(|b| STANDARD.encode(b.as_ref())).collect(),
+        _ => Value::Null,
+    }
+}
+
+/// Converts every attribute of an item-like map with `convert`.
+pub(crate) fn to_object(
+    attributes: &HashMap<String, AttributeValue>,
+    convert: fn(&AttributeValue) -> Value,
+) -> Map<String, Value> {
+    attributes
+        .iter()
+        .map(|(name, value)| (name.clone(), convert(value)))
+        .collect()
+}
+
+/// Integers stay integers; anything else goes through `f64`. A number JSON
+/// can't represent is kept as a string.
+fn plain_number(n: &str) -> Value {
+    if let Ok(i) = n.parse::<i64>() {
+        return Value::from(i);
+    }
+    n.parse::<f64>()
+        .ok()
+        .and_then(serde_json::Number::from_f64)
+        .map_or_else(|| Value::from(n), Value::Number)
+}
@@ -0,0 +1,116 @@
+use aws_sdk_dynamodb::types::AttributeValue;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::HashMap;
+
+use crate::dynamodb::{Item, Schema, Table};
+
+/// A type stored as items in one DynamoDB table, so it can be saved, loaded
+/// and queried without naming the table or building keys by hand.
+///
+/// The item is the type's serde serialization, as with
+/// [`Item::from_typed`]; the key attributes must be among its fields. Only
+/// the table, key names and key values need implementing, and with the
+/// `derive` feature `#[derive(DynamoEntity)]` writes those from
+/// `#[dynamo(table = "...")]`, `#[dynamo(partition_key)]` and
+/// `#[dynamo(sort_key)]` attributes, with a schema from the field types:
+///
+/// ```ignore
+/// #[derive(Serialize, Deserialize, DynamoEntity)]
+/// #[dynamo(table = "users")]
+/// struct User {
+///     #[dynamo(partition_key)]
+///     user_id: String,
+///     name: String,
+/// }
+/// ```
+///
+/// By hand:
+///
+/// ```no_run
+/// use aws_dynamodb_testing::aws::AttributeValue;
+/// use aws_dynamodb_testing::dynamodb::{DynamoDb, DynamoEntity, LOCAL_ENDPOINT};
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Serialize, Deserialize)]
+/// struct User {
+///     user_id: String,
+///     name: String,
+/// }
+///
+/// impl DynamoEntity for User {
+///     fn table_name() -> &'static str {
+///         "users"
+///     }
+///
+///     fn partition_key_name() -> &'static str {
+///         "user_id"
+///     }
+///
+///     fn partition_key_value(&self) -> AttributeValue {
+///         AttributeValue::S(self.user_id.clone())
+///     }
+/// }
+///
+/// # async fn example() -> anyhow::Result<()> {
+/// let ddb = DynamoDb::new_local(LOCAL_ENDPOINT);
+/// let user = User { user_id: "123".into(), name: "John Doe".into() };
+/// ddb.save(&user).await?;
+/// let key = User::key_of(AttributeValue::S("123".into()), None);
+/// let loaded: Option<User> = ddb.load::<User>(key).await?;
+/// # Ok(())
+/// # }
+/// ```
+pub trait DynamoEntity: Serialize + DeserializeOwned {
+    /// The table items of this type are stored in.
+    fn table_name() -> &'static str;
+
+    /// The name of the table's partition key attribute.
+    fn partition_key_name() -> &'static str;
+
+    /// The name of the table's sort key attribute, if it has one.
+    fn sort_key_name() -> Option<&'static str> {
+        None
+    }
+
+    /// The attribute types saved items are checked against. Empty by
+    /// default, which checks nothing beyond the keys.
+    fn schema() -> Schema {
+        Schema::new()
+    }
+
+    /// This value's partition key.
+    fn partition_key_value(&self) -> AttributeValue;
+
+    /// This value's sort key, if the table has one.
+    fn sort_key_value(&self) -> Option<AttributeValue> {
+        None
+    }
+
+    /// The table, with its keys and schema, e.g. for
+    /// [`DynamoDb::create_table_if_not_exists`](crate::dynamodb::DynamoDb::create_table_if_not_exists).
+    fn table() -> Table<'static> {
+        Table::new(
+            Self::table_name(),
+            Self::partition_key_name(),
+            Self::sort_key_name(),
+        )
+        .with_schema(Self::schema())
+    }
+
+    /// The key item for a partition key and, if the table has one, a sort
+    /// key.
+    fn key_of(partition_key: AttributeValue, sort_key: Option<AttributeValue>) -> Item {
+        let mut attributes =
+            HashMap::from([(Self::partition_key_name().to_string(), partition_key)]);
+        if let (Some(name), Some(value)) = (Self::sort_key_name(), sort_key) {
+            attributes.insert(name.to_string(), value);
+        }
+        Item::from(attributes)
+    }
+
+    /// This value's key item.
+    fn key(&self) -> Item {
+        Self::key_of(self.partition_key_value(), self.sort_key_value())
+    }
+}
@@ -0,0 +1,769 @@
+use anyhow::{anyhow, Result};
+use aws_sdk_dynamodb::types::AttributeValue;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+/// A parsed condition, filter or key condition expression, with its
+/// placeholders already substituted.
+///
+/// Supports comparisons, `BETWEEN`, `IN`, `AND`/`OR`/`NOT`, parentheses and
+/// the `attribute_exists`, `attribute_not_exists`, `attribute_type`,
+/// `begins_with`, `contains` and `size` functions over nested paths.
+#[derive(Debug, Clone)]
+pub(crate) enum Condition {
+    And(Box<Condition>, Box<Condition>),
+    Or(Box<Condition>, Box<Condition>),
+    Not(Box<Condition>),
+    Compare(Operand, Comparator, Operand),
+    Between(Operand, Operand, Operand),
+    In(Operand, Vec<Operand>),
+    Function(Function, Path, Option<Operand>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Comparator {
+    Eq,
+    Ne,
+    Lt,
+    LtEq,
+    Gt,
+    GtEq,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Function {
+    AttributeExists,
+    AttributeNotExists,
+    AttributeType,
+    BeginsWith,
+    Contains,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) enum Operand {
+    Path(Path),
+    Value(AttributeValue),
+    Size(Path),
+}
+
+/// A document path such as `address.city` or `tags[0]`.
+pub(crate) type Path = Vec<PathElement>;
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum PathElement {
+    Attribute(String),
+    Index(usize),
+}
+
+/// The right-hand side of a `SET` action.
+#[derive(Debug, Clone)]
+enum SetValue {
+    Operand(Operand),
+    IfNotExists(Path, Operand),
+    ListAppend(Operand, Operand),
+}
+
+#[derive(Debug)]
+enum UpdateAction {
+    Set(Path, SetValue),
+    Remove(Path),
+}
+
+impl Condition {
+    /// Parses `expression`, resolving `#name` and `:value` placeholders.
+    pub(crate) fn parse(
+        expression: &str,
+        names: &HashMap<String, String>,
+        values: &HashMap<String, AttributeValue>,
+    ) -> Result<Self> {
+        let mut parser = Parser::new(expression, names, values)?;
+        let condition = parser.condition()?;
+        parser.expect_end()?;
+        Ok(condition)
+    }
+
+    /// Returns `true` if the item satisfies the condition.
+    pub(crate) fn matches(&self, item: &HashMap<String, AttributeValue>) -> bool {
+        match self {
+            Self::And(left, right) => left.matches(item) && right.matches(item),
+            Self::Or(left, right) => left.matches(item) || right.matches(item),
+            Self::Not(condition) => !condition.matches(item),
+            Self::Compare(left, comparator, right) => {
+                match (left.resolve(item), right.resolve(item)) {
+                    (Some(left), Some(right)) => comparator.holds(&left, &right),
+                    _ => false,
+                }
+            }
+            Self::Between(operand, low, high) => {
+                match (operand.resolve(item), low.resolve(item), high.resolve(item)) {
+                    (Some(value), Some(low), Some(high)) => {
+                        Comparator::GtEq.holds(&value, &low)
+                            && Comparator::LtEq.holds(&value, &high)
+                    }
+                    _ => false,
+                }
+            }
+            Self::In(operand, candidates) => operand.resolve(item).is_some_and(|value| {
+                candidates.iter().any(|candidate| {
+                    candidate
+                        .resolve(item)
+                        .is_some_and(|candidate| values_equal(&value, &candidate))
+                })
+            }),
+            Self::Function(function, path, argument) => {
+                let value = resolve_path(item, path);
+                let argument = argument
+                    .as_ref()
+                    .and_then(|argument| argument.resolve(item));
+                function.holds(value, argument.as_ref())
+            }
+        }
+    }
+}
+
+impl Comparator {
+    fn holds(self, left: &AttributeValue, right: &AttributeValue) -> bool {
+        match self {
+            Self::Eq => values_equal(left, right),
+            Self::Ne => !values_equal(left, right),
+            Self::Lt => compare_values(left, right) == Some(Ordering::Less),
+            Self::LtEq => matches!(
+                compare_values(left, right),
+                Some(Ordering::Less | Ordering::Equal)
+            ),
+            Self::Gt => compare_values(left, right) == Some(Ordering::Greater),
+            Self::GtEq => matches!(
+                compare_values(left, right),
+                Some(Ordering::Greater | Ordering::Equal)
+            ),
+        }
+    }
+}
+
+impl Function {
+    fn holds(self, value: Option<&AttributeValue>, argument: Option<&AttributeValue>) -> bool {
+        match (self, value, argument) {
+            (Self::AttributeExists, value, _) => value.is_some(),
+            (Self::AttributeNotExists, value, _) => value.is_none(),
+            (Self::AttributeType, Some(value), Some(AttributeValue::S(type_code))) => {
+                type_code_of(value) == type_code
+            }
+            (Self::BeginsWith, Some(AttributeValue::S(value)), Some(AttributeValue::S(prefix))) => {
+                value.starts_with(prefix.as_str())
+            }
+            (Self::BeginsWith, Some(AttributeValue::B(value)), Some(AttributeValue::B(prefix))) => {
+                value.as_ref().starts_with(prefix.as_ref())
+            }
+            (Self::Contains, Some(AttributeValue::S(value)), Some(AttributeValue::S(part))) => {
+                value.contains(part.as_str())
+            }
+            (Self::Contains, Some(AttributeValue::Ss(set)), Some(AttributeValue::S(member))) => {
+                set.contains(member)
+            }
+            (
+                Self::Contains,
+                Some(AttributeValue::Ns(set)),
+                Some(member @ AttributeValue::N(_)),
+            ) => set
+                .iter()
+                .any(|n| values_equal(&AttributeValue::N(n.clone()), member)),
+            (Self::Contains, Some(AttributeValue::Bs(set)), Some(AttributeValue::B(member))) => {
+                set.contains(member)
+            }
+            (Self::Contains, Some(AttributeValue::L(list)), Some(member)) => {
+                list.iter().any(|element| values_equal(element, member))
+            }
+            _ => false,
+        }
+    }
+}
+
+impl Operand {
+    fn resolve(&self, item: &HashMap<String, AttributeValue>) -> Option<AttributeValue> {
+        match self {
+            Self::Path(path) => resolve_path(item, path).cloned(),
+            Self::Value(value) => Some(value.clone()),
+            Self::Size(path) => {
+                let size = match resolve_path(item, path)? {
+                    AttributeValue::S(s) => s.len(),
+                    AttributeValue::B(b) => b.as_ref().len(),
+                    AttributeValue::Ss(set) => set.len(),
+                    AttributeValue::Ns(set) => set.len(),
+                    AttributeValue::Bs(set) => set.len(),
+                    AttributeValue::L(list) => list.len(),
+                    AttributeValue::M(map) => map.len(),
+                    _ => return None,
+                };
+                Some(AttributeValue::N(size.to_string()))
+            }
+        }
+    }
+}
+
+/// Applies an update expression's `SET` and `REMOVE` actions to an item.
+///
+/// Every `SET` value is read from the item as it was before the update, as
+/// DynamoDB does. `ADD`, `DELETE` and arithmetic aren't supported.
+pub(crate) fn apply_update(
+    expression: &str,
+    names: &HashMap<String, String>,
+    values: &HashMap<String, AttributeValue>,
+    item: &mut HashMap<String, AttributeValue>,
+) -> Result<()> {
+    let mut parser = Parser::new(expression, names, values)?;
+    let actions = parser.update_actions()?;
+
+    let original = item.clone();
+    for action in actions {
+        match action {
+            UpdateAction::Set(path, value) => {
+                let value = match value {
+                    SetValue::Operand(operand) => operand.resolve(&original),
+                    SetValue::IfNotExists(existing, fallback) => resolve_path(&original, &existing)
+                        .cloned()
+                        .or_else(|| fallback.resolve(&original)),
+                    SetValue::ListAppend(first, second) => {
+                        match (first.resolve(&original), second.resolve(&original)) {
+                            (
+                                Some(AttributeValue::L(mut first)),
+                                Some(AttributeValue::L(second)),
+                            ) => {
+                                first.extend(second);
+                                Some(AttributeValue::L(first))
+                            }
+                            _ => return Err(anyhow!("list_append needs two lists")),
+                        }
+                    }
+                };
+                let value = value.ok_or_else(|| {
+                    anyhow!("The update expression refers to an attribute that doesn't exist")
+                })?;
+                set_path(item, &path, value)?;
+            }
+            UpdateAction::Remove(path) => remove_path(item, &path),
+        }
+    }
+    Ok(())
+}
+
+/// Parses a projection expression into the paths it selects.
+pub(crate) fn parse_projection(
+    expression: &str,
+    names: &HashMap<String, String>,
+) -> Result<Vec<Path>> {
+    let values = HashMap::new();
+    let mut parser = Parser::new(expression, names, &values)?;
+    let mut paths = vec![parser.path()?];
+    while parser.eat(&Token::Comma) {
+        paths.push(parser.path()?);
+    }
+    parser.expect_end()?;
+    Ok(paths)
+}
+
+/// Keeps only the projected paths of an item. A nested path keeps its
+/// top-level attribute whole.
+pub(crate) fn project(
+    item: &HashMap<String, AttributeValue>,
+    paths: &[Path],
+) -> HashMap<String, AttributeValue> {
+    item.iter()
+        .filter(|(name, _)| {
+            paths
+                .iter()
+                .any(|path| path.first() == Some(&PathElement::Attribute(name.to_string())))
+        })
+        .map(|(name, value)| (name.clone(), value.clone()))
+        .collect()
+}
+
+/// Orders two numbers, strings or binaries; other types (or a mix) don't
+/// compare.
+pub(crate) fn compare_values(left: &AttributeValue, right: &AttributeValue) -> Option<Ordering> {
+    match (left, right) {
+        (AttributeValue::N(left), AttributeValue::N(right)) => {
+            let left: f64 = left.parse().ok()?;
+            let right: f64 = right.parse().ok()?;
+            left.partial_cmp(&right)
+        }
+        (AttributeValue::S(left), AttributeValue::S(right)) => Some(left.cmp(right)),
+        (AttributeValue::B(left), AttributeValue::B(right)) => {
+            Some(left.as_ref().cmp(right.as_ref()))
+        }
+        _ => None,
+    }
+}
+
+fn values_equal(left: &AttributeValue, right: &AttributeValue) -> bool {
+    compare_values(left, right).map_or(left == right, |ordering| ordering == Ordering::Equal)
+}
+
+fn type_code_of(value: &AttributeValue) -> &'static str {
+    match value {
+        AttributeValue::S(_) => "S",
+        AttributeValue::N(_) => "N",
+        AttributeValue::B(_) => "B",
+        AttributeValue::Bool(_) => "BOOL",
+        AttributeValue::Null(_) => "NULL",
+        AttributeValue::Ss(_) => "SS",
+        AttributeValue::Ns(_) => "NS",
+        AttributeValue::Bs(_) => "BS",
+        AttributeValue::L(_) => "L",
+        AttributeValue::M(_) => "M",
+        _ => "",
+    }
+}
+
+fn resolve_path<'a>(
+    item: &'a HashMap<String, AttributeValue>,
+    path: &[PathElement],
+) -> Option<&'a AttributeValue> {
+    let (PathElement::Attribute(first), rest) = path.split_first()? else {
+        return None;
+    };
+    rest.iter()
+        .try_fold(item.get(first)?, |value, element| match (element, value) {
+            (PathElement::Attribute(name), AttributeValue::M(map)) => map.get(name),
+            (PathElement::Index(index), AttributeValue::L(list)) => list.get(*index),
+            _ => None,
+        })
+}
+
+fn set_path(
+    item: &mut HashMap<String, AttributeValue>,
+    path: &[PathElement],
+    value: AttributeValue,
+) -> Result<()> {
+    let Some((PathElement::Attribute(first), rest)) = path.split_first() else {
+        return Err(anyhow!("A document path must start with an attribute name"));
+    };
+    if rest.is_empty() {
+        item.insert(first.clone(), value);
+        return Ok(());
+    }
+    let parent = item
+        .get_mut(first)
+        .ok_or_else(|| anyhow!("The document path '{first}' doesn't exist"))?;
+    set_nested(parent, rest, value)
+}
+
+fn set_nested(
+    parent: &mut AttributeValue,
+    path: &[PathElement],
+    value: AttributeValue,
+) -> Result<()> {
+    let (element, rest) = path.split_first().expect("nested paths aren't empty");
+    let child = match (element, parent) {
+        (PathElement::Attribute(name), AttributeValue::M(map)) => {
+            if rest.is_empty() {
+                map.insert(name.clone(), value);
+                return Ok(());
+            }
+            map.get_mut(name)
+        }
+        (PathElement::Index(index), AttributeValue::L(list)) => {
+            if rest.is_empty() {
+                match list.get_mut(*index) {
+                    Some(existing) => *existing = value,
+                    None => list.push(value),
+                }
+                return Ok(());
+            }
+            list.get_mut(*index)
+        }
+        _ => None,
+    };
+    let child = child.ok_or_else(|| anyhow!("The document path doesn't exist"))?;
+    set_nested(child, rest, value)
+}
+
+fn remove_path(item: &mut HashMap<String, AttributeValue>, path: &[PathElement]) {
+    let Some((PathElement::Attribute(first), rest)) = path.split_first() else {
+        return;
+    };
+    if rest.is_empty() {
+        item.remove(first);
+    } else if let Some(parent) = item.get_mut(first) {
+        remove_nested(parent, rest);
+    }
+}
+
+fn remove_nested(parent: &mut AttributeValue, path: &[PathElement]) {
+    let Some((element, rest)) = path.split_first() else {
+        return;
+    };
+    match (element, parent) {
+        (PathElement::Attribute(name), AttributeValue::M(map)) if rest.is_empty() => {
+            map.remove(name);
+        }
+        (PathElement::Index(index), AttributeValue::L(list))
+            if rest.is_empty() && *index < list.len() =>
+        {
+            list.remove(*index);
+        }
+        (PathElement::Index(_), AttributeValue::L(_)) if rest.is_empty() => {}
+        (PathElement::Attribute(name), AttributeValue::M(map)) => {
+            if let Some(child) = map.get_mut(name) {
+                remove_nested(child, rest);
+            }
+        }
+        (PathElement::Index(index), AttributeValue::L(list)) => {
+            if let Some(child) = list.get_mut(*index) {
+                remove_nested(child, rest);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word(String),
+    Name(String),
+    Value(String),
+    Number(usize),
+    Comparator(Comparator),
+    LeftParen,
+    RightParen,
+    LeftBracket,
+    RightBracket,
+    Comma,
+    Dot,
+}
+
+fn tokenize(expression: &str) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = expression.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        let word = |chars: &mut std::iter::Peekable<std::str::Chars>| {
+            let mut word = String::new();
+            while let Some(&c) = chars.peek() {
+                if !(c.is_ascii_alphanumeric() || c == '_') {
+                    break;
+                }
+                word.push(c);
+                chars.next();
+            }
+            word
+        };
+
+        let token = match c {
+            c if c.is_whitespace() => {
+                chars.next();
+                continue;
+            }
+            '(' => Token::LeftParen,
+            ')' => Token::RightParen,
+            '[' => Token::LeftBracket,
+            ']' => Token::RightBracket,
+            ',' => Token::Comma,
+            '.' => Token::Dot,
+            '=' => Token::Comparator(Comparator::Eq),
+            '<' | '>' => {
+                chars.next();
+                let comparator = match (c, chars.peek()) {
+                    ('<', Some('=')) => Comparator::LtEq,
+                    ('<', Some('>')) => Comparator::Ne,
+                    ('>', Some('=')) => Comparator::GtEq,
+                    ('<', _) => Comparator::Lt,
+                    _ => Comparator::Gt,
+                };
+                if matches!(
+                    comparator,
+                    Comparator::LtEq | Comparator::Ne | Comparator::GtEq
+                ) {
+                    chars.next();
+                }
+                tokens.push(Token::Comparator(comparator));
+                continue;
+            }
+            '#' | ':' => {
+                chars.next();
+                let name = word(&mut chars);
+                if name.is_empty() {
+                    return Err(anyhow!("Expected a placeholder name after '{c}'"));
+                }
+                tokens.push(if c == '#' {
+                    Token::Name(format!("#{name}"))
+                } else {
+                    Token::Value(format!(":{name}"))
+                });
+                continue;
+            }
+            c if c.is_ascii_digit() => {
+                let digits = word(&mut chars);
+                let number = digits
+                    .parse()
+                    .map_err(|_| anyhow!("Invalid list index '{digits}'"))?;
+                tokens.push(Token::Number(number));
+                continue;
+            }
+            c if c.is_ascii_alphabetic() || c == '_' => {
+                tokens.push(Token::Word(word(&mut chars)));
+                continue;
+            }
+            c => return Err(anyhow!("Unsupported character '{c}' in expression")),
+        };
+        chars.next();
+        tokens.push(token);
+    }
+    Ok(tokens)
+}
+
+struct Parser<'a> {
+    tokens: Vec<Token>,
+    position: usize,
+    names: &'a HashMap<String, String>,
+    values: &'a HashMap<String, AttributeValue>,
+}
+
+impl<'a> Parser<'a> {
+    fn new(
+        expression: &str,
+        names: &'a HashMap<String, String>,
+        values: &'a HashMap<String, AttributeValue>,
+    ) -> Result<Self> {
+        Ok(Self {
+            tokens: tokenize(expression)?,
+            position: 0,
+            names,
+            values,
+        })
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn eat(&mut self, token: &Token) -> bool {
+        if self.peek() == Some(token) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        match self.peek() {
+            Some(Token::Word(word)) if word.eq_ignore_ascii_case(keyword) => {
+                self.position += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn expect(&mut self, token: Token) -> Result<()> {
+        match self.next() {
+            Some(next) if next == token => Ok(()),
+            next => Err(anyhow!("Expected {token:?}, found {next:?}")),
+        }
+    }
+
+    fn expect_end(&self) -> Result<()> {
+        match self.peek() {
+            None => Ok(()),
+            Some(token) => Err(anyhow!("Unexpected {token:?} in expression")),
+        }
+    }
+
+    fn condition(&mut self) -> Result<Condition> {
+        let mut condition = self.conjunction()?;
+        while self.eat_keyword("OR") {
+            condition = Condition::Or(Box::new(condition), Box::new(self.conjunction()?));
+        }
+        Ok(condition)
+    }
+
+    fn conjunction(&mut self) -> Result<Condition> {
+        let mut condition = self.negation()?;
+        while self.eat_keyword("AND") {
+            condition = Condition::And(Box::new(condition), Box::new(self.negation()?));
+        }
+        Ok(condition)
+    }
+
+    fn negation(&mut self) -> Result<Condition> {
+        if self.eat_keyword("NOT") {
+            return Ok(Condition::Not(Box::new(self.negation()?)));
+        }
+        self.primary()
+    }
+
+    fn primary(&mut self) -> Result<Condition> {
+        if self.eat(&Token::LeftParen) {
+            let condition = self.condition()?;
+            self.expect(Token::RightParen)?;
+            return Ok(condition);
+        }
+
+        if let Some(function) = self.function_name() {
+            self.position += 1;
+            self.expect(Token::LeftParen)?;
+            let path = self.path()?;
+            let argument = match function {
+                Function::AttributeExists | Function::AttributeNotExists => None,
+                _ => {
+                    self.expect(Token::Comma)?;
+                    Some(self.operand()?)
+                }
+            };
+            self.expect(Token::RightParen)?;
+            return Ok(Condition::Function(function, path, argument));
+        }
+
+        let operand = self.operand()?;
+        if self.eat_keyword("BETWEEN") {
+            let low = self.operand()?;
+            if !self.eat_keyword("AND") {
+                return Err(anyhow!("Expected AND in BETWEEN"));
+            }
+            return Ok(Condition::Between(operand, low, self.operand()?));
+        }
+        if self.eat_keyword("IN") {
+            self.expect(Token::LeftParen)?;
+            let mut candidates = vec![self.operand()?];
+            while self.eat(&Token::Comma) {
+                candidates.push(self.operand()?);
+            }
+            self.expect(Token::RightParen)?;
+            return Ok(Condition::In(operand, candidates));
+        }
+        match self.next() {
+            Some(Token::Comparator(comparator)) => {
+                Ok(Condition::Compare(operand, comparator, self.operand()?))
+            }
+            token => Err(anyhow!("Expected a comparison, found {token:?}")),
+        }
+    }
+
+    /// The condition function named by the next token, if it's followed by
+    /// a parenthesis.
+    fn function_name(&self) -> Option<Function> {
+        let Some(Token::Word(word)) = self.peek() else {
+            return None;
+        };
+        if self.tokens.get(self.position + 1) != Some(&Token::LeftParen) {
+            return None;
+        }
+        match word.as_str() {
+            "attribute_exists" => Some(Function::AttributeExists),
+            "attribute_not_exists" => Some(Function::AttributeNotExists),
+            "attribute_type" => Some(Function::AttributeType),
+            "begins_with" => Some(Function::BeginsWith),
+            "contains" => Some(Function::Contains),
+            _ => None,
+        }
+    }
+
+    fn operand(&mut self) -> Result<Operand> {
+        match self.peek() {
+            Some(Token::Value(placeholder)) => {
+                let value = self
+                    .values
+                    .get(placeholder)
+                    .cloned()
+                    .ok_or_else(|| anyhow!("Value {placeholder} is not defined"))?;
+                self.position += 1;
+                Ok(Operand::Value(value))
+            }
+            Some(Token::Word(word))
+                if word == "size"
+                    && self.tokens.get(self.position + 1) == Some(&Token::LeftParen) =>
+            {
+                self.position += 2;
+                let path = self.path()?;
+                self.expect(Token::RightParen)?;
+                Ok(Operand::Size(path))
+            }
+            _ => Ok(Operand::Path(self.path()?)),
+        }
+    }
+
+    fn path(&mut self) -> Result<Path> {
+        let mut path = vec![PathElement::Attribute(self.attribute_name()?)];
+        loop {
+            if self.eat(&Token::Dot) {
+                path.push(PathElement::Attribute(self.attribute_name()?));
+            } else if self.eat(&Token::LeftBracket) {
+                match self.next() {
+                    Some(Token::Number(index)) => path.push(PathElement::Index(index)),
+                    token => return Err(anyhow!("Expected a list index, found {token:?}")),
+                }
+                self.expect(Token::RightBracket)?;
+            } else {
+                return Ok(path);
+            }
+        }
+    }
+
+    fn attribute_name(&mut self) -> Result<String> {
+        match self.next() {
+            Some(Token::Word(word)) => Ok(word),
+            Some(Token::Name(placeholder)) => self
+                .names
+                .get(&placeholder)
+                .cloned()
+                .ok_or_else(|| anyhow!("Name {placeholder} is not defined")),
+            token => Err(anyhow!("Expected an attribute name, found {token:?}")),
+        }
+    }
+
+    fn update_actions(&mut self) -> Result<Vec<UpdateAction>> {
+        let mut actions = Vec::new();
+        while let Some(token) = self.next() {
+            let clause = match token {
+                Token::Word(word) => word.to_ascii_uppercase(),
+                token => return Err(anyhow!("Expected SET or REMOVE, found {token:?}")),
+            };
+            loop {
+                match clause.as_str() {
+                    "SET" => {
+                        let path = self.path()?;
+                        self.expect(Token::Comparator(Comparator::Eq))?;
+                        actions.push(UpdateAction::Set(path, self.set_value()?));
+                    }
+                    "REMOVE" => actions.push(UpdateAction::Remove(self.path()?)),
+                    _ => return Err(anyhow!("Unsupported update clause '{clause}'")),
+                }
+                if !self.eat(&Token::Comma) {
+                    break;
+                }
+            }
+        }
+        if actions.is_empty() {
+            return Err(anyhow!("The update expression is empty"));
+        }
+        Ok(actions)
+    }
+
+    fn set_value(&mut self) -> Result<SetValue> {
+        let function = match (self.peek(), self.tokens.get(self.position + 1)) {
+            (Some(Token::Word(word)), Some(Token::LeftParen)) => word.clone(),
+            _ => return Ok(SetValue::Operand(self.operand()?)),
+        };
+        self.position += 2;
+        let value = match function.as_str() {
+            "if_not_exists" => {
+                let path = self.path()?;
+                self.expect(Token::Comma)?;
+                SetValue::IfNotExists(path, self.operand()?)
+            }
+            "list_append" => {
+                let first = self.operand()?;
+                self.expect(Token::Comma)?;
+                SetValue::ListAppend(first, self.operand()?)
+            }
+            _ => return Err(anyhow!("Unsupported update function '{function}'")),
+        };
+        self.expect(Token::RightParen)?;
+        Ok(value)
+    }
+}
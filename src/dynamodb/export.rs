@@ -0,0 +1,120 @@
+use anyhow::{anyhow, Result};
+use aws_sdk_dynamodb::types::{
+    ContinuousBackupsDescription, ExportDescription, ExportFormat, ExportStatus, ImportStatus,
+    ImportTableDescription, PointInTimeRecoveryStatus,
+};
+use std::fmt;
+
+/// Returned by `DynamoDb::export_to_s3` when the table can't be exported
+/// because point-in-time recovery is turned off.
+///
+/// Exports read from the table's continuous backups, so PITR has to be
+/// enabled first (`UpdateContinuousBackups`, or "Point-in-time recovery" in
+/// the console's Backups tab). Callers can detect it with
+/// `error.downcast_ref::<PointInTimeRecoveryDisabled>()`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PointInTimeRecoveryDisabled {
+    pub table_name: String,
+}
+
+impl fmt::Display for PointInTimeRecoveryDisabled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Point-in-time recovery is not enabled on table '{}'; enable it before exporting to S3",
+            self.table_name
+        )
+    }
+}
+
+impl std::error::Error for PointInTimeRecoveryDisabled {}
+
+impl PointInTimeRecoveryDisabled {
+    /// Fails with this error unless `backups` shows PITR as enabled.
+    pub(crate) fn check(
+        table_name: &str,
+        backups: Option<&ContinuousBackupsDescription>,
+    ) -> Result<()> {
+        let enabled = backups
+            .and_then(|backups| backups.point_in_time_recovery_description())
+            .and_then(|pitr| pitr.point_in_time_recovery_status())
+            == Some(&PointInTimeRecoveryStatus::Enabled);
+        if enabled {
+            Ok(())
+        } else {
+            Err(Self {
+                table_name: table_name.to_string(),
+            }
+            .into())
+        }
+    }
+}
+
+/// The state of an export to S3.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExportInfo {
+    pub arn: String,
+    pub status: ExportStatus,
+    pub table_arn: Option<String>,
+    pub s3_bucket: Option<String>,
+    pub s3_prefix: Option<String>,
+    pub format: Option<ExportFormat>,
+    /// Number of items exported, once the export has completed.
+    pub item_count: Option<i64>,
+    pub failure_message: Option<String>,
+}
+
+impl TryFrom<ExportDescription> for ExportInfo {
+    type Error = anyhow::Error;
+
+    fn try_from(export: ExportDescription) -> Result<Self> {
+        let arn = export
+            .export_arn
+            .ok_or_else(|| anyhow!("Export description has no ARN"))?;
+
+        Ok(Self {
+            status: export
+                .export_status
+                .ok_or_else(|| anyhow!("Export '{arn}' has no status"))?,
+            table_arn: export.table_arn,
+            s3_bucket: export.s3_bucket,
+            s3_prefix: export.s3_prefix,
+            format: export.export_format,
+            item_count: export.item_count,
+            failure_message: export.failure_message,
+            arn,
+        })
+    }
+}
+
+/// The state of an import from S3 into a new table.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportInfo {
+    pub arn: String,
+    pub status: ImportStatus,
+    pub table_arn: Option<String>,
+    pub imported_item_count: i64,
+    pub error_count: i64,
+    pub failure_message: Option<String>,
+}
+
+impl TryFrom<ImportTableDescription> for ImportInfo {
+    type Error = anyhow::Error;
+
+    fn try_from(import: ImportTableDescription) -> Result<Self> {
+        let arn = import
+            .import_arn
+            .ok_or_else(|| anyhow!("Import description has no ARN"))?;
+
+        Ok(Self {
+            status: import
+                .import_status
+                .ok_or_else(|| anyhow!("Import '{arn}' has no status"))?,
+            table_arn: import.table_arn,
+            imported_item_count: import.imported_item_count,
+            error_count: import.error_count,
+            failure_message: import.failure_message,
+            arn,
+        })
+    }
+}
@@ -0,0 +1,89 @@
+use aws_sdk_dynamodb::types::AttributeValue;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+/// Returned when DynamoDB rejects a request's expressions with a
+/// `ValidationException`.
+///
+/// Carries the expressions as sent and their attribute names, so the error
+/// shows what DynamoDB was looking at. Values are redacted to their type and
+/// length, since they may hold user data. Callers can detect it with
+/// `error.downcast_ref::<ExpressionRejected>()`; the SDK error, with
+/// DynamoDB's own message, is kept as its source.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExpressionRejected {
+    pub table_name: String,
+    pub key_condition_expression: Option<String>,
+    pub filter_expression: Option<String>,
+    pub projection_expression: Option<String>,
+    pub update_expression: Option<String>,
+    /// The expression attribute names, sorted by placeholder.
+    pub names: BTreeMap<String, String>,
+    /// The expression attribute values, each as its type and length, e.g.
+    /// `S (5 chars)`.
+    pub values: BTreeMap<String, String>,
+}
+
+impl ExpressionRejected {
+    pub(crate) fn new(
+        table_name: &str,
+        names: Option<&HashMap<String, String>>,
+        values: Option<&HashMap<String, AttributeValue>>,
+    ) -> Self {
+        Self {
+            table_name: table_name.to_string(),
+            names: names
+                .into_iter()
+                .flatten()
+                .map(|(placeholder, name)| (placeholder.clone(), name.clone()))
+                .collect(),
+            values: values
+                .into_iter()
+                .flatten()
+                .map(|(placeholder, value)| (placeholder.clone(), redact(value)))
+                .collect(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Describes a value by its type and size only.
+pub(crate) fn redact(value: &AttributeValue) -> String {
+    match value {
+        AttributeValue::S(s) => format!("S ({} chars)", s.chars().count()),
+        AttributeValue::N(n) => format!("N ({} chars)", n.len()),
+        AttributeValue::B(b) => format!("B ({} bytes)", b.as_ref().len()),
+        AttributeValue::Bool(_) => "BOOL".to_string(),
+        AttributeValue::Null(_) => "NULL".to_string(),
+        AttributeValue::Ss(set) => format!("SS ({} elements)", set.len()),
+        AttributeValue::Ns(set) => format!("NS ({} elements)", set.len()),
+        AttributeValue::Bs(set) => format!("BS ({} elements)", set.len()),
+        AttributeValue::L(list) => format!("L ({} elements)", list.len()),
+        AttributeValue::M(map) => format!("M ({} entries)", map.len()),
+        _ => "unknown type".to_string(),
+    }
+}
+
+impl fmt::Display for ExpressionRejected {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "DynamoDB rejected the expressions for '{}':",
+            self.table_name
+        )?;
+        let expressions = [
+            ("key condition", &self.key_condition_expression),
+            ("filter", &self.filter_expression),
+            ("projection", &self.projection_expression),
+            ("update", &self.update_expression),
+        ];
+        for (kind, expression) in expressions {
+            if let Some(expression) = expression {
+                write!(f, " {kind} `{expression}`;")?;
+            }
+        }
+        write!(f, " names {:?}; values {:?}", self.names, self.values)
+    }
+}
+
+impl std::error::Error for ExpressionRejected {}
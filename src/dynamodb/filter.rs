@@ -0,0 +1,329 @@
+use anyhow::{anyhow, Result};
+use aws_sdk_dynamodb::types::AttributeValue;
+use std::collections::HashMap;
+use std::ops::Not;
+
+/// Builds a filter expression for queries and scans.
+///
+/// Attribute names and values are bound to `#fN` / `:fN` placeholders, which
+/// never clash with the `#pk` / `#sk` placeholders of a [`KeyCondition`].
+/// Conditions combine with [`Filter::and`], [`Filter::or`] and `!`.
+///
+/// # Example
+///
+/// ```
+/// use aws_dynamodb_testing::dynamodb::Filter;
+/// use aws_dynamodb_testing::aws::AttributeValue;
+///
+/// let filter = Filter::gt("price", AttributeValue::N("100".to_string()))
+///     .and(Filter::attribute_exists("discount").or(Filter::size("tags").gt(2)))
+///     .build();
+/// // (#f0 > :f0 AND (attribute_exists(#f1) OR size(#f2) > :f1))
+/// ```
+///
+/// [`KeyCondition`]: crate::dynamodb::KeyCondition
+#[derive(Debug, Clone)]
+pub struct Filter(Node);
+
+#[derive(Debug, Clone)]
+enum Node {
+    Compare(Operand, &'static str, AttributeValue),
+    In(String, Vec<AttributeValue>),
+    Between(Operand, AttributeValue, AttributeValue),
+    Function(&'static str, String, Option<AttributeValue>),
+    And(Box<Filter>, Box<Filter>),
+    Or(Box<Filter>, Box<Filter>),
+    Not(Box<Filter>),
+}
+
+#[derive(Debug, Clone)]
+enum Operand {
+    Attribute(String),
+    Size(String),
+}
+
+/// The `size(attribute)` operand, compared against a number.
+#[derive(Debug, Clone)]
+pub struct Size(String);
+
+/// A rendered filter expression along with its placeholder maps.
+#[derive(Debug, Clone)]
+pub struct FilterExpression {
+    pub expression: String,
+    pub names: HashMap<String, String>,
+    pub values: HashMap<String, AttributeValue>,
+}
+
+impl Filter {
+    /// `attribute = value`
+    pub fn eq(attribute: impl Into<String>, value: AttributeValue) -> Self {
+        Self::compare(attribute, "=", value)
+    }
+
+    /// `attribute <> value`
+    pub fn ne(attribute: impl Into<String>, value: AttributeValue) -> Self {
+        Self::compare(attribute, "<>", value)
+    }
+
+    /// `attribute < value`
+    pub fn lt(attribute: impl Into<String>, value: AttributeValue) -> Self {
+        Self::compare(attribute, "<", value)
+    }
+
+    /// `attribute <= value`
+    pub fn lt_eq(attribute: impl Into<String>, value: AttributeValue) -> Self {
+        Self::compare(attribute, "<=", value)
+    }
+
+    /// `attribute > value`
+    pub fn gt(attribute: impl Into<String>, value: AttributeValue) -> Self {
+        Self::compare(attribute, ">", value)
+    }
+
+    /// `attribute >= value`
+    pub fn gt_eq(attribute: impl Into<String>, value: AttributeValue) -> Self {
+        Self::compare(attribute, ">=", value)
+    }
+
+    /// `attribute BETWEEN low AND high`, inclusive on both ends.
+    pub fn between(
+        attribute: impl Into<String>,
+        low: AttributeValue,
+        high: AttributeValue,
+    ) -> Self {
+        Self(Node::Between(
+            Operand::Attribute(attribute.into()),
+            low,
+            high,
+        ))
+    }
+
+    /// `attribute IN (:v0, :v1, ...)`
+    ///
+    /// DynamoDB accepts between 1 and 100 values; other lengths are rejected
+    /// by the service when the request is sent.
+    pub fn is_in(attribute: impl Into<String>, values: Vec<AttributeValue>) -> Self {
+        Self(Node::In(attribute.into(), values))
+    }
+
+    /// `begins_with(attribute, prefix)`
+    pub fn begins_with(attribute: impl Into<String>, prefix: impl Into<String>) -> Self {
+        Self::function(
+            "begins_with",
+            attribute,
+            Some(AttributeValue::S(prefix.into())),
+        )
+    }
+
+    /// `contains(attribute, value)`, matching a substring or a set/list member.
+    pub fn contains(attribute: impl Into<String>, value: AttributeValue) -> Self {
+        Self::function("contains", attribute, Some(value))
+    }
+
+    /// `attribute_exists(attribute)`
+    pub fn attribute_exists(attribute: impl Into<String>) -> Self {
+        Self::function("attribute_exists", attribute, None)
+    }
+
+    /// `attribute_not_exists(attribute)`
+    pub fn attribute_not_exists(attribute: impl Into<String>) -> Self {
+        Self::function("attribute_not_exists", attribute, None)
+    }
+
+    /// Starts a comparison on `size(attribute)`.
+    pub fn size(attribute: impl Into<String>) -> Size {
+        Size(attribute.into())
+    }
+
+    /// Matches items that satisfy both filters.
+    pub fn and(self, other: Filter) -> Self {
+        Self(Node::And(Box::new(self), Box::new(other)))
+    }
+
+    /// Matches items that satisfy either filter.
+    pub fn or(self, other: Filter) -> Self {
+        Self(Node::Or(Box::new(self), Box::new(other)))
+    }
+
+    /// Renders the filter expression along with its placeholder maps.
+    pub fn build(self) -> FilterExpression {
+        let mut renderer = Renderer::default();
+        let expression = renderer.render(self);
+        FilterExpression {
+            expression,
+            names: renderer.names,
+            values: renderer.values,
+        }
+    }
+
+    fn compare(attribute: impl Into<String>, op: &'static str, value: AttributeValue) -> Self {
+        Self(Node::Compare(
+            Operand::Attribute(attribute.into()),
+            op,
+            value,
+        ))
+    }
+
+    fn function(
+        name: &'static str,
+        attribute: impl Into<String>,
+        value: Option<AttributeValue>,
+    ) -> Self {
+        Self(Node::Function(name, attribute.into(), value))
+    }
+}
+
+impl Not for Filter {
+    type Output = Filter;
+
+    /// Matches items that don't satisfy the filter.
+    fn not(self) -> Self::Output {
+        Self(Node::Not(Box::new(self)))
+    }
+}
+
+impl Size {
+    /// `size(attribute) = n`
+    pub fn eq(self, n: usize) -> Filter {
+        self.compare("=", n)
+    }
+
+    /// `size(attribute) <> n`
+    pub fn ne(self, n: usize) -> Filter {
+        self.compare("<>", n)
+    }
+
+    /// `size(attribute) < n`
+    pub fn lt(self, n: usize) -> Filter {
+        self.compare("<", n)
+    }
+
+    /// `size(attribute) <= n`
+    pub fn lt_eq(self, n: usize) -> Filter {
+        self.compare("<=", n)
+    }
+
+    /// `size(attribute) > n`
+    pub fn gt(self, n: usize) -> Filter {
+        self.compare(">", n)
+    }
+
+    /// `size(attribute) >= n`
+    pub fn gt_eq(self, n: usize) -> Filter {
+        self.compare(">=", n)
+    }
+
+    fn compare(self, op: &'static str, n: usize) -> Filter {
+        Filter(Node::Compare(
+            Operand::Size(self.0),
+            op,
+            AttributeValue::N(n.to_string()),
+        ))
+    }
+}
+
+impl FilterExpression {
+    /// Merges the filter's placeholders into existing expression maps.
+    ///
+    /// Fails if a placeholder is already bound to something else, rather than
+    /// silently changing the meaning of the other expression.
+    pub(crate) fn merge_into(
+        &self,
+        names: &mut HashMap<String, String>,
+        values: &mut HashMap<String, AttributeValue>,
+    ) -> Result<()> {
+        for (placeholder, name) in &self.names {
+            if names
+                .get(placeholder)
+                .is_some_and(|existing| existing != name)
+            {
+                return Err(anyhow!(
+                    "Filter placeholder '{placeholder}' is already used for another attribute"
+                ));
+            }
+        }
+        for (placeholder, value) in &self.values {
+            if values
+                .get(placeholder)
+                .is_some_and(|existing| existing != value)
+            {
+                return Err(anyhow!(
+                    "Filter placeholder '{placeholder}' is already used for another value"
+                ));
+            }
+        }
+
+        names.extend(self.names.clone());
+        values.extend(self.values.clone());
+        Ok(())
+    }
+}
+
+/// Assigns placeholders while walking the filter tree.
+#[derive(Default)]
+struct Renderer {
+    names: HashMap<String, String>,
+    values: HashMap<String, AttributeValue>,
+    aliases: HashMap<String, String>,
+}
+
+impl Renderer {
+    fn render(&mut self, filter: Filter) -> String {
+        match filter.0 {
+            Node::Compare(operand, op, value) => {
+                let operand = self.operand(operand);
+                format!("{operand} {op} {}", self.value(value))
+            }
+            Node::In(attribute, values) => {
+                let name = self.name(attribute);
+                let placeholders: Vec<_> = values.into_iter().map(|v| self.value(v)).collect();
+                format!("{name} IN ({})", placeholders.join(", "))
+            }
+            Node::Between(operand, low, high) => {
+                let operand = self.operand(operand);
+                let low = self.value(low);
+                format!("{operand} BETWEEN {low} AND {}", self.value(high))
+            }
+            Node::Function(function, attribute, value) => {
+                let name = self.name(attribute);
+                match value {
+                    Some(value) => format!("{function}({name}, {})", self.value(value)),
+                    None => format!("{function}({name})"),
+                }
+            }
+            Node::And(left, right) => {
+                let left = self.render(*left);
+                format!("({left} AND {})", self.render(*right))
+            }
+            Node::Or(left, right) => {
+                let left = self.render(*left);
+                format!("({left} OR {})", self.render(*right))
+            }
+            Node::Not(inner) => format!("NOT ({})", self.render(*inner)),
+        }
+    }
+
+    fn operand(&mut self, operand: Operand) -> String {
+        match operand {
+            Operand::Attribute(attribute) => self.name(attribute),
+            Operand::Size(attribute) => format!("size({})", self.name(attribute)),
+        }
+    }
+
+    /// Reuses one placeholder per distinct attribute name.
+    fn name(&mut self, attribute: String) -> String {
+        if let Some(placeholder) = self.aliases.get(&attribute) {
+            return placeholder.clone();
+        }
+        let placeholder = format!("#f{}", self.names.len());
+        self.names.insert(placeholder.clone(), attribute.clone());
+        self.aliases.insert(attribute, placeholder.clone());
+        placeholder
+    }
+
+    fn value(&mut self, value: AttributeValue) -> String {
+        let placeholder = format!(":f{}", self.values.len());
+        self.values.insert(placeholder.clone(), value);
+        placeholder
+    }
+}
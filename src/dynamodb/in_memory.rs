@@ -0,0 +1,855 @@
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use aws_sdk_dynamodb::operation::create_table::CreateTableOutput;
+use aws_sdk_dynamodb::types::{
+    AttributeValue, BillingMode, IndexStatus, ProjectionType, Select, StreamViewType, TableStatus,
+};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::ops::ControlFlow;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant, SystemTime};
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+
+use crate::dynamodb::api::DynamoDbApi;
+use crate::dynamodb::client::{SimpleQuery, BATCH_WRITE_SIZE};
+use crate::dynamodb::evaluate::{self, compare_values, Condition, Path};
+use crate::dynamodb::{
+    AuthInfo, BackupArn, BackupInfo, BulkResult, BulkStatus, Capacity, CheckpointStore,
+    FilterExpression, GetItemOptions, IndexInfo, Item, MetricsSnapshot, Progress,
+    QueryFlexibleParams, QueryResult, ScanParams, ScanSummary, Schema, SortKeyValue, Statement,
+    StatementResult, StreamRecord, Table, TableInfo, TableNotFound, UpdateBuilder,
+};
+
+/// A [`DynamoDbApi`] that keeps its tables in memory, for running commands
+/// without credentials or a DynamoDB endpoint.
+///
+/// Items are stored by their key attributes. Key condition, filter,
+/// projection and update expressions are evaluated in process: the
+/// comparisons, `BETWEEN`, `IN`, `AND`/`OR`/`NOT` and the condition
+/// functions are supported, while `ADD`/`DELETE` updates and arithmetic are
+/// rejected. Reads are always consistent and report no consumed capacity.
+/// Backups, streams and PartiQL statements aren't supported.
+///
+/// # Example
+///
+/// ```
+/// use aws_dynamodb_testing::dynamodb::{DynamoDbApi, InMemoryDynamoDb, Item, Table};
+///
+/// # #[tokio::main]
+/// # async fn main() -> anyhow::Result<()> {
+/// let ddb = InMemoryDynamoDb::new();
+/// ddb.create_table_if_not_exists(&Table::new("users", "user_id", None)).await?;
+/// ddb.put_item("users", Item::new().set_string("user_id", "123")).await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Default)]
+pub struct InMemoryDynamoDb {
+    tables: Mutex<HashMap<String, MemoryTable>>,
+}
+
+#[derive(Debug)]
+struct MemoryTable {
+    partition_key: String,
+    sort_key: Option<String>,
+    schema: Option<Schema>,
+    capacity: Capacity,
+    indexes: Vec<MemoryIndex>,
+    created_at: SystemTime,
+    items: HashMap<ItemKey, Item>,
+}
+
+#[derive(Debug)]
+struct MemoryIndex {
+    name: String,
+    partition_key: String,
+    sort_key: Option<String>,
+}
+
+/// The partition and sort key values of an item, rendered so equal keys
+/// (e.g. the numbers `1` and `1.0`) compare equal.
+type ItemKey = (String, Option<String>);
+
+/// One page of a query or scan, before it's shaped into the caller's result.
+struct Page {
+    items: Vec<Item>,
+    count: usize,
+    scanned_count: usize,
+    last_evaluated_key: Option<HashMap<String, AttributeValue>>,
+}
+
+/// A query or scan request with its expressions parsed.
+struct ReadRequest<'a> {
+    table_name: &'a str,
+    index_name: Option<&'a str>,
+    key_condition: Option<Condition>,
+    filter: Option<Condition>,
+    projection: Option<Vec<Path>>,
+    forward: bool,
+    limit: Option<i32>,
+    count_only: bool,
+}
+
+impl InMemoryDynamoDb {
+    /// Creates a store with no tables.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn tables(&self) -> Result<MutexGuard<'_, HashMap<String, MemoryTable>>> {
+        self.tables
+            .lock()
+            .map_err(|_| anyhow!("In-memory table store lock poisoned"))
+    }
+
+    fn read_page(
+        &self,
+        request: &ReadRequest<'_>,
+        exclusive_start_key: Option<&HashMap<String, AttributeValue>>,
+    ) -> Result<Page> {
+        let tables = self.tables()?;
+        let table = table(&tables, request.table_name)?;
+        let key_names = table.key_names(request.index_name)?;
+
+        let mut candidates: Vec<&Item> = table
+            .items
+            .values()
+            .filter(|item| {
+                key_names
+                    .iter()
+                    .all(|key| item.attributes.contains_key(key.as_str()))
+            })
+            .filter(|item| {
+                request
+                    .key_condition
+                    .as_ref()
+                    .is_none_or(|condition| condition.matches(&item.attributes))
+            })
+            .collect();
+        candidates.sort_by(|a, b| compare_keys(&a.attributes, &b.attributes, &key_names));
+        if !request.forward {
+            candidates.reverse();
+        }
+
+        if let Some(start) = exclusive_start_key {
+            let after = if request.forward {
+                Ordering::Greater
+            } else {
+                Ordering::Less
+            };
+            candidates.retain(|item| compare_keys(&item.attributes, start, &key_names) == after);
+        }
+
+        let limit = request
+            .limit
+            .map_or(candidates.len(), |limit| limit.max(0) as usize);
+        let evaluated = &candidates[..limit.min(candidates.len())];
+        let last_evaluated_key = (evaluated.len() < candidates.len())
+            .then(|| evaluated.last())
+            .flatten()
+            .map(|item| {
+                key_names
+                    .iter()
+                    .filter_map(|key| {
+                        item.attributes
+                            .get(key.as_str())
+                            .map(|value| (key.clone(), value.clone()))
+                    })
+                    .collect()
+            });
+
+        let matched: Vec<&Item> = evaluated
+            .iter()
+            .copied()
+            .filter(|item| {
+                request
+                    .filter
+                    .as_ref()
+                    .is_none_or(|filter| filter.matches(&item.attributes))
+            })
+            .collect();
+        let count = matched.len();
+        let items = if request.count_only {
+            Vec::new()
+        } else {
+            matched
+                .into_iter()
+                .map(|item| match &request.projection {
+                    Some(paths) => Item {
+                        attributes: evaluate::project(&item.attributes, paths),
+                    },
+                    None => item.clone(),
+                })
+                .collect()
+        };
+
+        Ok(Page {
+            items,
+            count,
+            scanned_count: evaluated.len(),
+            last_evaluated_key,
+        })
+    }
+
+    /// Reads every page of a scan, like [`DynamoDb::scan_all_cancellable`](crate::dynamodb::DynamoDb::scan_all_cancellable).
+    fn scan_pages(
+        &self,
+        params: &ScanParams<'_>,
+        max_items: Option<usize>,
+        mut on_progress: Option<&mut dyn FnMut(&Progress)>,
+        cancel: Option<&CancellationToken>,
+    ) -> Result<BulkResult<Vec<Item>>> {
+        let request = scan_request(params)?;
+        let start = Instant::now();
+        let mut progress = Progress::default();
+        let mut items = Vec::new();
+        let mut last_evaluated_key = None;
+
+        loop {
+            let page = self.read_page(&request, last_evaluated_key.as_ref())?;
+            let page_items = page.items.len();
+            items.extend(page.items);
+            if let Some(max_items) = max_items {
+                items.truncate(max_items);
+            }
+            progress.record_page(page_items, start);
+            progress.items = items.len();
+            if let Some(on_progress) = on_progress.as_mut() {
+                on_progress(&progress);
+            }
+
+            if max_items.is_some_and(|max_items| items.len() >= max_items) {
+                break;
+            }
+            last_evaluated_key = page.last_evaluated_key;
+            if last_evaluated_key.is_none() {
+                break;
+            }
+            if cancel.is_some_and(CancellationToken::is_cancelled) {
+                return Ok(BulkResult {
+                    value: items,
+                    status: BulkStatus::Cancelled,
+                });
+            }
+        }
+
+        Ok(BulkResult {
+            value: items,
+            status: BulkStatus::Complete,
+        })
+    }
+}
+
+impl MemoryTable {
+    fn new(table: &Table<'_>) -> Self {
+        Self {
+            partition_key: table.partition_key().to_string(),
+            sort_key: table.sort_key().map(str::to_string),
+            schema: table.schema().cloned(),
+            capacity: table.capacity(),
+            indexes: table
+                .global_secondary_indexes()
+                .iter()
+                .map(|index| MemoryIndex {
+                    name: index.name().to_string(),
+                    partition_key: index.partition_key().to_string(),
+                    sort_key: index.sort_key().map(str::to_string),
+                })
+                .collect(),
+            created_at: SystemTime::now(),
+            items: HashMap::new(),
+        }
+    }
+
+    /// Returns the key attributes items are ordered by: the index keys (if
+    /// reading an index) followed by the table keys.
+    fn key_names(&self, index_name: Option<&str>) -> Result<Vec<String>> {
+        let mut names = Vec::new();
+        if let Some(index_name) = index_name {
+            let index = self
+                .indexes
+                .iter()
+                .find(|index| index.name == index_name)
+                .ok_or_else(|| {
+                    anyhow!("The table does not have the specified index: {index_name}")
+                })?;
+            names.push(index.partition_key.clone());
+            names.extend(index.sort_key.clone());
+        }
+        for key in std::iter::once(&self.partition_key).chain(&self.sort_key) {
+            if !names.contains(key) {
+                names.push(key.clone());
+            }
+        }
+        Ok(names)
+    }
+
+    /// Returns the storage key of an item (or key), which must have the
+    /// table's key attributes.
+    fn item_key(&self, attributes: &HashMap<String, AttributeValue>) -> Result<ItemKey> {
+        let part = |name: &str| {
+            attributes
+                .get(name)
+                .ok_or_else(|| anyhow!("One of the required keys was not given a value: {name}"))
+                .and_then(key_part)
+        };
+        Ok((
+            part(&self.partition_key)?,
+            self.sort_key.as_deref().map(part).transpose()?,
+        ))
+    }
+
+    fn info(&self, name: &str) -> TableInfo {
+        let count_with = |keys: &[&String]| {
+            self.items
+                .values()
+                .filter(|item| {
+                    keys.iter()
+                        .all(|key| item.attributes.contains_key(key.as_str()))
+                })
+                .count() as i64
+        };
+        TableInfo {
+            name: name.to_string(),
+            status: TableStatus::Active,
+            partition_key: self.partition_key.clone(),
+            sort_key: self.sort_key.clone(),
+            item_count: self.items.len() as i64,
+            size_bytes: 0,
+            billing_mode: match self.capacity {
+                Capacity::OnDemand => BillingMode::PayPerRequest,
+                Capacity::Provisioned { .. } => BillingMode::Provisioned,
+            },
+            global_secondary_indexes: self
+                .indexes
+                .iter()
+                .map(|index| IndexInfo {
+                    name: index.name.clone(),
+                    status: IndexStatus::Active,
+                    partition_key: index.partition_key.clone(),
+                    sort_key: index.sort_key.clone(),
+                    projection_type: ProjectionType::All,
+                    capacity: self.capacity,
+                    item_count: count_with(
+                        &std::iter::once(&index.partition_key)
+                            .chain(&index.sort_key)
+                            .collect::<Vec<_>>(),
+                    ),
+                    size_bytes: 0,
+                })
+                .collect(),
+            local_secondary_indexes: Vec::new(),
+            stream_arn: None,
+            created_at: self.created_at,
+        }
+    }
+}
+
+#[async_trait(?Send)]
+impl DynamoDbApi for InMemoryDynamoDb {
+    /// No requests are sent, so there's nothing to count.
+    fn metrics(&self) -> Option<MetricsSnapshot> {
+        None
+    }
+
+    /// There are no credentials to check, and no identity to report.
+    async fn check_auth(&self) -> Result<Option<AuthInfo>> {
+        Ok(None)
+    }
+
+    async fn list_tables(&self) -> Result<Vec<String>> {
+        let mut names: Vec<String> = self.tables()?.keys().cloned().collect();
+        names.sort();
+        Ok(names)
+    }
+
+    async fn list_tables_with_prefix(&self, prefix: &str) -> Result<Vec<String>> {
+        let mut names = self.list_tables().await?;
+        names.retain(|name| name.starts_with(prefix));
+        Ok(names)
+    }
+
+    async fn table_exists(&self, table_name: &str) -> Result<bool> {
+        Ok(self.tables()?.contains_key(table_name))
+    }
+
+    async fn create_table_if_not_exists(
+        &self,
+        table: &Table<'_>,
+    ) -> Result<Option<CreateTableOutput>> {
+        let mut tables = self.tables()?;
+        if tables.contains_key(table.name()) {
+            return Ok(None);
+        }
+        tables.insert(table.name().to_string(), MemoryTable::new(table));
+        Ok(Some(CreateTableOutput::builder().build()))
+    }
+
+    async fn delete_table_if_exists_and_wait(
+        &self,
+        table_name: &str,
+        _timeout: Duration,
+    ) -> Result<bool> {
+        Ok(self.tables()?.remove(table_name).is_some())
+    }
+
+    async fn wait_for_table_active(&self, table_name: &str, _timeout: Duration) -> Result<()> {
+        let tables = self.tables()?;
+        table(&tables, table_name).map(|_| ())
+    }
+
+    async fn load_table(
+        &self,
+        table_name: &str,
+        sample_size: Option<usize>,
+    ) -> Result<Table<'static>> {
+        let tables = self.tables()?;
+        let table = table(&tables, table_name)?;
+
+        let schema = match (&table.schema, sample_size) {
+            (Some(schema), _) => schema.clone(),
+            (None, Some(sample_size)) => {
+                let mut items: Vec<&Item> = table.items.values().collect();
+                let key_names = table.key_names(None)?;
+                items.sort_by(|a, b| compare_keys(&a.attributes, &b.attributes, &key_names));
+                let sample: Vec<Item> = items.into_iter().take(sample_size).cloned().collect();
+                Schema::infer(&sample)
+            }
+            (None, None) => Schema::new(),
+        };
+
+        // Index definitions borrow their names, so a `Table<'static>` can't
+        // carry them; like `DynamoDb::load_table`, the loaded table has none.
+        Ok(Table::owned(
+            table_name.to_string(),
+            table.partition_key.clone(),
+            table.sort_key.clone(),
+        )
+        .with_schema(schema)
+        .with_capacity(table.capacity))
+    }
+
+    async fn table_info(&self, table_name: &str) -> Result<TableInfo> {
+        let tables = self.tables()?;
+        Ok(table(&tables, table_name)?.info(table_name))
+    }
+
+    async fn try_describe_table(&self, table_name: &str) -> Result<Option<TableInfo>> {
+        let tables = self.tables()?;
+        Ok(tables.get(table_name).map(|table| table.info(table_name)))
+    }
+
+    async fn create_backup(&self, _table_name: &str, _backup_name: &str) -> Result<BackupArn> {
+        Err(unsupported("Backups"))
+    }
+
+    async fn list_backups(&self, _table_name: &str) -> Result<Vec<BackupInfo>> {
+        Err(unsupported("Backups"))
+    }
+
+    async fn restore_table_from_backup_and_wait(
+        &self,
+        _backup_arn: &BackupArn,
+        _new_table_name: &str,
+        _timeout: Duration,
+    ) -> Result<()> {
+        Err(unsupported("Backups"))
+    }
+
+    async fn enable_stream(&self, _table_name: &str, _view_type: StreamViewType) -> Result<()> {
+        Err(unsupported("Streams"))
+    }
+
+    async fn subscribe_changes(
+        &self,
+        _table_name: &str,
+        _checkpoints: Arc<dyn CheckpointStore>,
+        _cancel: CancellationToken,
+        _handler: Box<dyn Fn(StreamRecord) + Send>,
+    ) -> Result<JoinHandle<Result<()>>> {
+        Err(unsupported("Streams"))
+    }
+
+    async fn put_item(&self, table_name: &str, item: Item) -> Result<()> {
+        let mut tables = self.tables()?;
+        let table = table_mut(&mut tables, table_name)?;
+        let key = table.item_key(&item.attributes)?;
+        table.items.insert(key, item);
+        Ok(())
+    }
+
+    async fn put_item_if_not_exists(&self, table: &Table<'_>, item: Item) -> Result<bool> {
+        let mut tables = self.tables()?;
+        let stored = table_mut(&mut tables, table.name())?;
+        let key = stored.item_key(&item.attributes)?;
+        if stored.items.contains_key(&key) {
+            return Ok(false);
+        }
+        stored.items.insert(key, item);
+        Ok(true)
+    }
+
+    async fn get_item(&self, table_name: &str, key: Item) -> Result<Option<Item>> {
+        self.get_item_with(table_name, key, GetItemOptions::default())
+            .await
+    }
+
+    async fn get_item_with(
+        &self,
+        table_name: &str,
+        key: Item,
+        options: GetItemOptions<'_>,
+    ) -> Result<Option<Item>> {
+        let projection = options
+            .projection
+            .map(|projection| evaluate::parse_projection(&projection.expression, &projection.names))
+            .transpose()?;
+
+        let tables = self.tables()?;
+        let table = table(&tables, table_name)?;
+        let item = table.items.get(&table.item_key(&key.attributes)?);
+        Ok(item.map(|item| match &projection {
+            Some(paths) => Item {
+                attributes: evaluate::project(&item.attributes, paths),
+            },
+            None => item.clone(),
+        }))
+    }
+
+    async fn update_with(&self, table_name: &str, key: Item, updates: UpdateBuilder) -> Result<()> {
+        let Some(update) = updates.build() else {
+            return Ok(());
+        };
+
+        let mut tables = self.tables()?;
+        let table = table_mut(&mut tables, table_name)?;
+        let item_key = table.item_key(&key.attributes)?;
+        let mut attributes = table
+            .items
+            .get(&item_key)
+            .map_or(key.attributes, |item| item.attributes.clone());
+        evaluate::apply_update(
+            &update.expression,
+            &update.names,
+            &update.values,
+            &mut attributes,
+        )?;
+        if table.item_key(&attributes)? != item_key {
+            return Err(anyhow!(
+                "Cannot update attribute: this attribute is part of the key"
+            ));
+        }
+
+        table.items.insert(item_key, Item { attributes });
+        Ok(())
+    }
+
+    async fn delete_item(&self, table_name: &str, key: Item) -> Result<()> {
+        let mut tables = self.tables()?;
+        let table = table_mut(&mut tables, table_name)?;
+        let key = table.item_key(&key.attributes)?;
+        table.items.remove(&key);
+        Ok(())
+    }
+
+    async fn batch_put_items_cancellable(
+        &self,
+        table_name: &str,
+        items: Vec<Item>,
+        mut on_progress: Option<&mut dyn for<'p> FnMut(&'p Progress)>,
+        cancel: &CancellationToken,
+    ) -> Result<BulkResult<usize>> {
+        let start = Instant::now();
+        let mut progress = Progress::default();
+        let mut status = BulkStatus::Complete;
+
+        for chunk in items.chunks(BATCH_WRITE_SIZE) {
+            if cancel.is_cancelled() {
+                status = BulkStatus::Cancelled;
+                break;
+            }
+            for item in chunk {
+                self.put_item(table_name, item.clone()).await?;
+            }
+            progress.record_page(chunk.len(), start);
+            if let Some(on_progress) = on_progress.as_mut() {
+                on_progress(&progress);
+            }
+        }
+
+        Ok(BulkResult {
+            value: progress.items,
+            status,
+        })
+    }
+
+    async fn query_flexible(&self, params: QueryFlexibleParams<'_>) -> Result<Vec<Item>> {
+        Ok(self.query_flexible_detailed(params, None).await?.items)
+    }
+
+    async fn query_flexible_detailed(
+        &self,
+        params: QueryFlexibleParams<'_>,
+        exclusive_start_key: Option<HashMap<String, AttributeValue>>,
+    ) -> Result<QueryResult> {
+        let page = self.read_page(&query_request(&params)?, exclusive_start_key.as_ref())?;
+        Ok(QueryResult {
+            items: page.items,
+            count: page.count as i32,
+            scanned_count: page.scanned_count as i32,
+            last_evaluated_key: page.last_evaluated_key,
+            consumed_capacity: None,
+        })
+    }
+
+    async fn query_all(
+        &self,
+        params: QueryFlexibleParams<'_>,
+        max_items: Option<usize>,
+    ) -> Result<Vec<Item>> {
+        let request = query_request(&params)?;
+        let mut items = Vec::new();
+        let mut last_evaluated_key = None;
+
+        loop {
+            let page = self.read_page(&request, last_evaluated_key.as_ref())?;
+            items.extend(page.items);
+            if let Some(max_items) = max_items {
+                if items.len() >= max_items {
+                    items.truncate(max_items);
+                    break;
+                }
+            }
+            last_evaluated_key = page.last_evaluated_key;
+            if last_evaluated_key.is_none() {
+                break;
+            }
+        }
+        Ok(items)
+    }
+
+    async fn query_simple(
+        &self,
+        table_name: &str,
+        partition_key: (&str, AttributeValue),
+        sort_key_condition: Option<(&str, String, SortKeyValue)>,
+        filter_expression: Option<&str>,
+        limit: Option<i32>,
+        expression_attribute_values: Option<HashMap<String, AttributeValue>>,
+        filter: Option<&FilterExpression>,
+    ) -> Result<Vec<Item>> {
+        let query = SimpleQuery::new(
+            partition_key,
+            sort_key_condition,
+            filter_expression,
+            expression_attribute_values,
+            filter,
+        )?;
+        self.query_flexible(query.params(table_name, limit)).await
+    }
+
+    async fn scan_all(
+        &self,
+        params: ScanParams<'_>,
+        max_items: Option<usize>,
+        on_progress: Option<&mut dyn for<'p> FnMut(&'p Progress)>,
+    ) -> Result<Vec<Item>> {
+        Ok(self
+            .scan_pages(&params, max_items, on_progress, None)?
+            .value)
+    }
+
+    async fn scan_all_cancellable(
+        &self,
+        params: ScanParams<'_>,
+        max_items: Option<usize>,
+        on_progress: Option<&mut dyn for<'p> FnMut(&'p Progress)>,
+        cancel: &CancellationToken,
+    ) -> Result<BulkResult<Vec<Item>>> {
+        self.scan_pages(&params, max_items, on_progress, Some(cancel))
+    }
+
+    async fn scan_paginated(
+        &self,
+        params: ScanParams<'_>,
+        exclusive_start_key: Option<HashMap<String, AttributeValue>>,
+    ) -> Result<(Vec<Item>, Option<HashMap<String, AttributeValue>>)> {
+        let page = self.read_page(&scan_request(&params)?, exclusive_start_key.as_ref())?;
+        Ok((page.items, page.last_evaluated_key))
+    }
+
+    async fn for_each_item_cancellable(
+        &self,
+        params: ScanParams<'_>,
+        mut on_progress: Option<&mut dyn for<'p> FnMut(&'p Progress)>,
+        cancel: &CancellationToken,
+        handler: &mut dyn FnMut(Item) -> Result<ControlFlow<()>>,
+    ) -> Result<BulkResult<ScanSummary>> {
+        let request = scan_request(&params)?;
+        let start = Instant::now();
+        let mut progress = Progress::default();
+        let mut stopped_early = false;
+        let mut status = BulkStatus::Complete;
+        let mut last_evaluated_key = None;
+
+        loop {
+            let page = self.read_page(&request, last_evaluated_key.as_ref())?;
+            let mut handled = 0;
+            for item in page.items {
+                handled += 1;
+                if handler(item)?.is_break() {
+                    stopped_early = true;
+                    break;
+                }
+            }
+            progress.record_page(handled, start);
+            if let Some(on_progress) = on_progress.as_mut() {
+                on_progress(&progress);
+            }
+
+            last_evaluated_key = page.last_evaluated_key;
+            if stopped_early || last_evaluated_key.is_none() {
+                break;
+            }
+            if cancel.is_cancelled() {
+                status = BulkStatus::Cancelled;
+                break;
+            }
+        }
+
+        Ok(BulkResult {
+            value: ScanSummary {
+                pages: progress.pages,
+                items: progress.items,
+                stopped_early,
+            },
+            status,
+        })
+    }
+
+    async fn execute_statement(
+        &self,
+        _statement: &Statement,
+        _parameters: Vec<AttributeValue>,
+        _next_token: Option<String>,
+    ) -> Result<StatementResult> {
+        Err(unsupported("PartiQL statements"))
+    }
+}
+
+fn table<'a>(tables: &'a HashMap<String, MemoryTable>, name: &str) -> Result<&'a MemoryTable> {
+    tables.get(name).ok_or_else(|| not_found(name))
+}
+
+fn table_mut<'a>(
+    tables: &'a mut HashMap<String, MemoryTable>,
+    name: &str,
+) -> Result<&'a mut MemoryTable> {
+    tables.get_mut(name).ok_or_else(|| not_found(name))
+}
+
+fn not_found(name: &str) -> anyhow::Error {
+    TableNotFound {
+        table_name: name.to_string(),
+    }
+    .into()
+}
+
+fn unsupported(operation: &str) -> anyhow::Error {
+    anyhow!("{operation} aren't supported by InMemoryDynamoDb")
+}
+
+/// Renders a key attribute value for [`ItemKey`]. Keys must be strings,
+/// numbers or binaries.
+fn key_part(value: &AttributeValue) -> Result<String> {
+    match value {
+        AttributeValue::S(s) => Ok(format!("S:{s}")),
+        AttributeValue::N(n) => {
+            let n: f64 = n
+                .parse()
+                .map_err(|_| anyhow!("Invalid number key value '{n}'"))?;
+            Ok(format!("N:{n}"))
+        }
+        AttributeValue::B(b) => Ok(format!("B:{:?}", b.as_ref())),
+        _ => Err(anyhow!(
+            "Key attributes must be strings, numbers or binaries"
+        )),
+    }
+}
+
+/// Orders items by the given key attributes, in order. Items missing one
+/// sort before items that have it.
+fn compare_keys(
+    a: &HashMap<String, AttributeValue>,
+    b: &HashMap<String, AttributeValue>,
+    key_names: &[String],
+) -> Ordering {
+    key_names
+        .iter()
+        .map(|key| match (a.get(key), b.get(key)) {
+            (Some(a), Some(b)) => compare_values(a, b).unwrap_or(Ordering::Equal),
+            (a, b) => a.is_some().cmp(&b.is_some()),
+        })
+        .find(|ordering| ordering.is_ne())
+        .unwrap_or(Ordering::Equal)
+}
+
+fn query_request<'a>(params: &QueryFlexibleParams<'a>) -> Result<ReadRequest<'a>> {
+    let names = params
+        .expression_attribute_names
+        .clone()
+        .unwrap_or_default();
+    let values = params
+        .expression_attribute_values
+        .clone()
+        .unwrap_or_default();
+    Ok(ReadRequest {
+        table_name: params.table_name,
+        index_name: params.index_name,
+        key_condition: Some(Condition::parse(
+            params.key_condition_expression,
+            &names,
+            &values,
+        )?),
+        filter: params
+            .filter_expression
+            .map(|filter| Condition::parse(filter, &names, &values))
+            .transpose()?,
+        projection: params
+            .projection_expression
+            .map(|projection| evaluate::parse_projection(projection, &names))
+            .transpose()?,
+        forward: params.scan_index_forward.unwrap_or(true),
+        limit: params.limit,
+        count_only: params.select == Some(Select::Count),
+    })
+}
+
+fn scan_request<'a>(params: &ScanParams<'a>) -> Result<ReadRequest<'a>> {
+    let names = params
+        .expression_attribute_names
+        .clone()
+        .unwrap_or_default();
+    let values = params
+        .expression_attribute_values
+        .clone()
+        .unwrap_or_default();
+    Ok(ReadRequest {
+        table_name: params.table_name,
+        index_name: params.index_name,
+        key_condition: None,
+        filter: params
+            .filter_expression
+            .map(|filter| Condition::parse(filter, &names, &values))
+            .transpose()?,
+        projection: params
+            .projection_expression
+            .map(|projection| evaluate::parse_projection(projection, &names))
+            .transpose()?,
+        forward: true,
+        limit: params.limit,
+        count_only: false,
+    })
+}
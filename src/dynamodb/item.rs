@@ -1,6 +1,13 @@
+use anyhow::{anyhow, Result};
 use aws_sdk_dynamodb::types::AttributeValue;
+#[cfg(feature = "serde")]
+use serde::de::DeserializeOwned;
+#[cfg(feature = "serde")]
+use serde::Serialize;
 use std::collections::HashMap;
 
+use crate::dynamodb::{dynamodb_json, JsonFormat, ShardedKey};
+
 /// Represents a DynamoDB item with various attribute types.
 ///
 /// In DynamoDB, an item is a collection of attributes, each with a name and a value.
@@ -24,18 +31,30 @@ use std::collections::HashMap;
 /// # Example
 ///
 /// ```
-/// use dynamodb::Item;
+/// use aws_dynamodb_testing::dynamodb::Item;
 ///
 /// let item = Item::new()
 ///     .set_string("user_id", "12345")
 ///     .set_string("username", "johndoe")
 ///     .set_number("age", 30.0);
 /// ```
+///
+/// # Equality
+///
+/// Items are equal when they have the same attributes with the same values.
+/// Numbers compare by value, so `1.50`, `1.5` and `15e-1` are equal, and
+/// sets compare regardless of order, as DynamoDB treats them.
 #[derive(Default, Debug, Clone)]
 pub struct Item {
     pub(crate) attributes: HashMap<String, AttributeValue>,
 }
 
+impl PartialEq for Item {
+    fn eq(&self, other: &Self) -> bool {
+        maps_equal(&self.attributes, &other.attributes)
+    }
+}
+
 impl Item {
     /// Creates a new empty `Item`.
     pub fn new() -> Self {
@@ -68,10 +87,25 @@ impl Item {
         self
     }
 
+    /// Sets the partition key `key` to one of `sharded`'s shards: the one
+    /// `discriminator` hashes to, or a random one without it. See
+    /// [`ShardedKey`] for when sharding pays off.
+    pub fn set_sharded_partition_key(
+        self,
+        key: impl Into<String>,
+        sharded: &ShardedKey,
+        discriminator: Option<&str>,
+    ) -> Self {
+        let shard = match discriminator {
+            Some(discriminator) => sharded.shard_for(discriminator),
+            None => sharded.random_shard(),
+        };
+        self.set_string(key, shard)
+    }
+
     /// Gets the value of an attribute as a string.
     ///
     /// Returns `None` if the attribute doesn't exist or is not a string.
-    #[allow(dead_code)]
     pub fn get_string(&self, key: &str) -> Option<&String> {
         self.attributes.get(key).and_then(|av| av.as_s().ok())
     }
@@ -79,11 +113,237 @@ impl Item {
     /// Gets the value of an attribute as a number (f64).
     ///
     /// Returns `None` if the attribute doesn't exist, is not a number, or can't be parsed as f64.
-    #[allow(dead_code)]
     pub fn get_number(&self, key: &str) -> Option<f64> {
         self.attributes
             .get(key)
             .and_then(|av| av.as_n().ok())
             .and_then(|n| n.parse().ok())
     }
+
+    /// Converts any serializable value into an `Item`.
+    ///
+    /// The value must serialize to a map (e.g. a struct), since DynamoDB items
+    /// are collections of named attributes.
+    #[cfg(feature = "serde")]
+    pub fn from_typed<T: Serialize>(value: &T) -> Result<Self> {
+        let attributes = serde_dynamo::to_item(value)
+            .map_err(|e| anyhow!("Failed to convert value to item: {e}"))?;
+        Ok(Self { attributes })
+    }
+
+    /// Converts the `Item` into a typed value.
+    ///
+    /// On failure the error names the attribute that couldn't be converted.
+    #[cfg(feature = "serde")]
+    pub fn into_typed<T: DeserializeOwned>(self) -> Result<T> {
+        let item: serde_dynamo::Item = self.into();
+        let deserializer = serde_dynamo::Deserializer::from_attribute_value(
+            serde_dynamo::AttributeValue::M(item.into()),
+        );
+        serde_path_to_error::deserialize(deserializer)
+            .map_err(|e| anyhow!("Failed to convert attribute '{}': {}", e.path(), e.inner()))
+    }
+
+    /// Converts a plain JSON object into an `Item`.
+    ///
+    /// Numbers become `N`, strings `S`, arrays `L` and nested objects `M`.
+    pub fn from_json(value: &serde_json::Value) -> Result<Self> {
+        let serde_json::Value::Object(object) = value else {
+            return Err(anyhow!("Expected a JSON object, found {value}"));
+        };
+        let attributes = object
+            .iter()
+            .map(|(name, value)| (name.clone(), dynamodb_json::from_plain_json(value)))
+            .collect();
+        Ok(Self { attributes })
+    }
+
+    /// Converts an object in DynamoDB JSON (`{"id": {"S": "123"}}`), as
+    /// written by the AWS CLI and S3 exports, into an `Item`.
+    pub fn from_dynamodb_json(value: serde_json::Value) -> Result<Self> {
+        let serde_json::Value::Object(object) = value else {
+            return Err(anyhow!("Expected a JSON object, found {value}"));
+        };
+        let attributes = object
+            .into_iter()
+            .map(|(name, value)| {
+                let value = dynamodb_json::to_attribute_value(value)
+                    .map_err(|e| anyhow!("Attribute '{name}': {e}"))?;
+                Ok((name, value))
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self { attributes })
+    }
+
+    /// Converts the `Item` into JSON in the given format.
+    ///
+    /// Plain JSON is lossy for sets, binary values and number formatting;
+    /// DynamoDB JSON round-trips exactly through [`Item::from_dynamodb_json`].
+    pub fn to_json(&self, format: JsonFormat) -> serde_json::Value {
+        let convert = match format {
+            JsonFormat::Plain => dynamodb_json::to_plain_json,
+            JsonFormat::DynamoDb => dynamodb_json::from_attribute_value,
+        };
+        serde_json::Value::Object(dynamodb_json::to_object(&self.attributes, convert))
+    }
+
+    /// Gets the value of an attribute as plain JSON, with the same lossy
+    /// conversion as [`Item::to_json`].
+    ///
+    /// Returns `None` if the attribute doesn't exist.
+    pub fn get_json(&self, key: &str) -> Option<serde_json::Value> {
+        self.attributes.get(key).map(dynamodb_json::to_plain_json)
+    }
+
+    /// Gets the value of an attribute as a list.
+    ///
+    /// Returns `None` if the attribute doesn't exist or is not a list.
+    pub fn get_list(&self, key: &str) -> Option<&Vec<AttributeValue>> {
+        self.attributes.get(key).and_then(|av| av.as_l().ok())
+    }
+}
+
+/// Wraps a raw attribute map, as the SDK returns and accepts items.
+///
+/// ```
+/// use aws_dynamodb_testing::dynamodb::Item;
+/// use aws_dynamodb_testing::aws::AttributeValue;
+/// use std::collections::HashMap;
+///
+/// let attributes = HashMap::from([("id".to_string(), AttributeValue::S("k1".into()))]);
+/// let item = Item::from(attributes);
+/// assert_eq!(item.get_string("id").map(String::as_str), Some("k1"));
+/// ```
+impl From<HashMap<String, AttributeValue>> for Item {
+    fn from(attributes: HashMap<String, AttributeValue>) -> Self {
+        Self { attributes }
+    }
+}
+
+/// Converts an item serialized with `serde_dynamo`, e.g. by
+/// `serde_dynamo::to_item`. Numbers stay the strings they were written as
+/// and binary values stay bytes, so nothing is lost.
+#[cfg(feature = "serde")]
+impl From<serde_dynamo::Item> for Item {
+    fn from(item: serde_dynamo::Item) -> Self {
+        Self {
+            attributes: item.into(),
+        }
+    }
+}
+
+/// Converts into `serde_dynamo`'s item, e.g. for `serde_dynamo::from_item`.
+#[cfg(feature = "serde")]
+impl From<Item> for serde_dynamo::Item {
+    fn from(item: Item) -> Self {
+        item.attributes.into()
+    }
+}
+
+/// Converts a `serde_dynamo` map (`M`) value, e.g. from
+/// `serde_dynamo::to_attribute_value`. Any other kind of value is an error.
+#[cfg(feature = "serde")]
+impl TryFrom<serde_dynamo::AttributeValue> for Item {
+    type Error = anyhow::Error;
+
+    fn try_from(value: serde_dynamo::AttributeValue) -> Result<Self> {
+        let serde_dynamo::AttributeValue::M(attributes) = value else {
+            return Err(anyhow!(
+                "Expected a map attribute value for an item, found {value:?}"
+            ));
+        };
+        Ok(Self {
+            attributes: attributes
+                .into_iter()
+                .map(|(name, value)| (name, value.into()))
+                .collect(),
+        })
+    }
+}
+
+/// Converts into a `serde_dynamo` map (`M`) value.
+#[cfg(feature = "serde")]
+impl From<Item> for serde_dynamo::AttributeValue {
+    fn from(item: Item) -> Self {
+        serde_dynamo::AttributeValue::M(
+            item.attributes
+                .into_iter()
+                .map(|(name, value)| (name, value.into()))
+                .collect(),
+        )
+    }
+}
+
+fn maps_equal(a: &HashMap<String, AttributeValue>, b: &HashMap<String, AttributeValue>) -> bool {
+    a.len() == b.len()
+        && a.iter()
+            .all(|(name, value)| b.get(name).is_some_and(|other| values_equal(value, other)))
+}
+
+/// Compares attribute values the way [`Item`]'s `PartialEq` does.
+fn values_equal(a: &AttributeValue, b: &AttributeValue) -> bool {
+    use AttributeValue::*;
+
+    /// Sets are equal when they hold the same elements in any order.
+    fn sets_equal<T: Ord>(a: impl Iterator<Item = T>, b: impl Iterator<Item = T>) -> bool {
+        let mut a: Vec<_> = a.collect();
+        let mut b: Vec<_> = b.collect();
+        a.sort();
+        b.sort();
+        a == b
+    }
+
+    match (a, b) {
+        (N(a), N(b)) => {
+            a == b || normalize_number(a).is_some_and(|a| Some(a) == normalize_number(b))
+        }
+        (Ss(a), Ss(b)) => sets_equal(a.iter(), b.iter()),
+        (Ns(a), Ns(b)) => sets_equal(
+            a.iter().map(|n| normalize_number(n).ok_or(n)),
+            b.iter().map(|n| normalize_number(n).ok_or(n)),
+        ),
+        (Bs(a), Bs(b)) => sets_equal(a.iter().map(|b| b.as_ref()), b.iter().map(|b| b.as_ref())),
+        (L(a), L(b)) => a.len() == b.len() && a.iter().zip(b).all(|(a, b)| values_equal(a, b)),
+        (M(a), M(b)) => maps_equal(a, b),
+        (a, b) => a == b,
+    }
+}
+
+/// A number in a canonical form: whether it's negative, its significant
+/// digits without leading or trailing zeros, and the exponent that goes
+/// with them. Zero has no digits and is never negative. Returns `None` if
+/// `n` isn't a decimal number.
+///
+/// Unlike parsing to `f64`, this keeps every digit, so numbers that differ
+/// only past `f64` precision still compare unequal.
+fn normalize_number(n: &str) -> Option<(bool, String, i64)> {
+    let n = n.trim();
+    let (negative, n) = match n.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, n.strip_prefix('+').unwrap_or(n)),
+    };
+    let (mantissa, exponent) = match n.split_once(['e', 'E']) {
+        Some((mantissa, exponent)) => (mantissa, exponent.parse::<i64>().ok()?),
+        None => (n, 0),
+    };
+    let (integer, fraction) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    if integer.is_empty() && fraction.is_empty()
+        || !integer
+            .chars()
+            .chain(fraction.chars())
+            .all(|c| c.is_ascii_digit())
+    {
+        return None;
+    }
+
+    // The value is 0.<digits> * 10^exponent once leading zeros are gone.
+    let digits = format!("{integer}{fraction}");
+    let exponent = exponent.checked_add(integer.len() as i64)?;
+    let trimmed = digits.trim_start_matches('0');
+    let exponent = exponent - (digits.len() - trimmed.len()) as i64;
+    let trimmed = trimmed.trim_end_matches('0');
+    if trimmed.is_empty() {
+        return Some((false, String::new(), 0));
+    }
+    Some((negative, trimmed.to_string(), exponent))
 }
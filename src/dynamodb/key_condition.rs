@@ -0,0 +1,253 @@
+use anyhow::{anyhow, Result};
+use aws_sdk_dynamodb::types::AttributeValue;
+use std::collections::HashMap;
+
+/// The comparisons DynamoDB allows on a sort key in a key condition.
+pub const SORT_KEY_OPERATORS: [&str; 7] = ["=", "<", "<=", ">", ">=", "BETWEEN", "begins_with"];
+
+/// Builds a key condition expression for a query.
+///
+/// Attribute names are always referenced through `#pk` / `#sk` aliases, so
+/// reserved words such as `name` or `timestamp` can be used as keys. Values
+/// are bound to `:pkval`, `:skval`, or `:lo` / `:hi` for `BETWEEN`.
+///
+/// # Example
+///
+/// ```
+/// use aws_dynamodb_testing::dynamodb::{KeyCondition, QueryFlexibleParams};
+/// use aws_dynamodb_testing::aws::AttributeValue;
+///
+/// let key_condition = KeyCondition::partition("category", AttributeValue::S("Electronics".to_string()))
+///     .and_sort_begins_with("product_name", "Smart")
+///     .build()?;
+/// let params = QueryFlexibleParams::from_key_condition("products", &key_condition);
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct KeyCondition {
+    partition_key: String,
+    partition_value: AttributeValue,
+    sort_conditions: Vec<(String, SortKeyCondition)>,
+}
+
+/// A single condition on the sort key.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SortKeyCondition {
+    Eq(AttributeValue),
+    Lt(AttributeValue),
+    LtEq(AttributeValue),
+    Gt(AttributeValue),
+    GtEq(AttributeValue),
+    Between(AttributeValue, AttributeValue),
+    BeginsWith(AttributeValue),
+}
+
+/// The value side of a sort key condition given as an operator string.
+///
+/// `BETWEEN` compares against an inclusive range; every other operator takes a
+/// single value.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SortKeyValue {
+    Single(AttributeValue),
+    Between(AttributeValue, AttributeValue),
+}
+
+impl From<AttributeValue> for SortKeyValue {
+    fn from(value: AttributeValue) -> Self {
+        Self::Single(value)
+    }
+}
+
+/// A rendered key condition expression along with its placeholder maps.
+#[derive(Debug, Clone)]
+pub struct KeyConditionExpression {
+    pub expression: String,
+    pub names: HashMap<String, String>,
+    pub values: HashMap<String, AttributeValue>,
+}
+
+impl KeyConditionExpression {
+    /// Merges the key condition's placeholders into the caller's expression
+    /// maps.
+    ///
+    /// Fails if the caller already bound one of them to something else,
+    /// rather than silently making their expression compare against a key.
+    pub(crate) fn merge_into(
+        &self,
+        names: &mut HashMap<String, String>,
+        values: &mut HashMap<String, AttributeValue>,
+    ) -> Result<()> {
+        for (placeholder, name) in &self.names {
+            if names
+                .get(placeholder)
+                .is_some_and(|existing| existing != name)
+            {
+                return Err(anyhow!(
+                    "Key condition placeholder '{placeholder}' is already used for another attribute"
+                ));
+            }
+        }
+        for (placeholder, value) in &self.values {
+            if values
+                .get(placeholder)
+                .is_some_and(|existing| existing != value)
+            {
+                return Err(anyhow!(
+                    "Key condition placeholder '{placeholder}' is already used for another value"
+                ));
+            }
+        }
+
+        names.extend(self.names.clone());
+        values.extend(self.values.clone());
+        Ok(())
+    }
+}
+
+impl SortKeyCondition {
+    /// Parses an operator such as `>=` or `begins_with` (case-insensitive).
+    ///
+    /// The operator must be one of [`SORT_KEY_OPERATORS`], so user input is
+    /// never interpolated into the expression verbatim.
+    pub fn parse(condition: &str, value: SortKeyValue) -> Result<Self> {
+        let condition = condition.trim();
+        if !SORT_KEY_OPERATORS
+            .iter()
+            .any(|op| op.eq_ignore_ascii_case(condition))
+        {
+            return Err(anyhow!(
+                "Unsupported sort key condition '{condition}', expected one of: {}",
+                SORT_KEY_OPERATORS.join(", ")
+            ));
+        }
+
+        match (condition.to_ascii_uppercase().as_str(), value) {
+            ("BETWEEN", SortKeyValue::Between(low, high)) => Ok(Self::Between(low, high)),
+            ("BETWEEN", SortKeyValue::Single(_)) => {
+                Err(anyhow!("BETWEEN requires a low and a high value"))
+            }
+            (_, SortKeyValue::Between(..)) => Err(anyhow!(
+                "Sort key condition '{condition}' takes a single value"
+            )),
+            ("=", SortKeyValue::Single(value)) => Ok(Self::Eq(value)),
+            ("<", SortKeyValue::Single(value)) => Ok(Self::Lt(value)),
+            ("<=", SortKeyValue::Single(value)) => Ok(Self::LtEq(value)),
+            (">", SortKeyValue::Single(value)) => Ok(Self::Gt(value)),
+            (">=", SortKeyValue::Single(value)) => Ok(Self::GtEq(value)),
+            (_, SortKeyValue::Single(value)) => Ok(Self::BeginsWith(value)),
+        }
+    }
+
+    /// Renders the condition against `#sk`, registering its values.
+    fn render(self, values: &mut HashMap<String, AttributeValue>) -> String {
+        let (comparator, value) = match self {
+            Self::Between(low, high) => {
+                values.insert(":lo".to_string(), low);
+                values.insert(":hi".to_string(), high);
+                return "#sk BETWEEN :lo AND :hi".to_string();
+            }
+            Self::BeginsWith(value) => {
+                values.insert(":skval".to_string(), value);
+                return "begins_with(#sk, :skval)".to_string();
+            }
+            Self::Eq(value) => ("=", value),
+            Self::Lt(value) => ("<", value),
+            Self::LtEq(value) => ("<=", value),
+            Self::Gt(value) => (">", value),
+            Self::GtEq(value) => (">=", value),
+        };
+        values.insert(":skval".to_string(), value);
+        format!("#sk {comparator} :skval")
+    }
+}
+
+impl KeyCondition {
+    /// Starts a key condition matching a single partition key value.
+    pub fn partition(name: impl Into<String>, value: AttributeValue) -> Self {
+        Self {
+            partition_key: name.into(),
+            partition_value: value,
+            sort_conditions: Vec::new(),
+        }
+    }
+
+    /// Adds a condition on the sort key.
+    ///
+    /// A query can constrain the sort key at most once; adding a second
+    /// condition makes [`KeyCondition::build`] fail.
+    pub fn and_sort(mut self, name: impl Into<String>, condition: SortKeyCondition) -> Self {
+        self.sort_conditions.push((name.into(), condition));
+        self
+    }
+
+    /// Adds `#sk = :skval`.
+    pub fn and_sort_eq(self, name: impl Into<String>, value: AttributeValue) -> Self {
+        self.and_sort(name, SortKeyCondition::Eq(value))
+    }
+
+    /// Adds `#sk < :skval`.
+    pub fn and_sort_lt(self, name: impl Into<String>, value: AttributeValue) -> Self {
+        self.and_sort(name, SortKeyCondition::Lt(value))
+    }
+
+    /// Adds `#sk <= :skval`.
+    pub fn and_sort_lt_eq(self, name: impl Into<String>, value: AttributeValue) -> Self {
+        self.and_sort(name, SortKeyCondition::LtEq(value))
+    }
+
+    /// Adds `#sk > :skval`.
+    pub fn and_sort_gt(self, name: impl Into<String>, value: AttributeValue) -> Self {
+        self.and_sort(name, SortKeyCondition::Gt(value))
+    }
+
+    /// Adds `#sk >= :skval`.
+    pub fn and_sort_gt_eq(self, name: impl Into<String>, value: AttributeValue) -> Self {
+        self.and_sort(name, SortKeyCondition::GtEq(value))
+    }
+
+    /// Adds `#sk BETWEEN :lo AND :hi`, inclusive on both ends.
+    pub fn and_sort_between(
+        self,
+        name: impl Into<String>,
+        low: AttributeValue,
+        high: AttributeValue,
+    ) -> Self {
+        self.and_sort(name, SortKeyCondition::Between(low, high))
+    }
+
+    /// Adds `begins_with(#sk, :skval)` for a string prefix.
+    pub fn and_sort_begins_with(self, name: impl Into<String>, prefix: impl Into<String>) -> Self {
+        self.and_sort(
+            name,
+            SortKeyCondition::BeginsWith(AttributeValue::S(prefix.into())),
+        )
+    }
+
+    /// Renders the key condition expression along with its placeholder maps.
+    ///
+    /// Fails if more than one sort key condition was added.
+    pub fn build(self) -> Result<KeyConditionExpression> {
+        if self.sort_conditions.len() > 1 {
+            return Err(anyhow!(
+                "A key condition can constrain the sort key only once, got {} conditions",
+                self.sort_conditions.len()
+            ));
+        }
+
+        let mut expression = "#pk = :pkval".to_string();
+        let mut names = HashMap::from([("#pk".to_string(), self.partition_key)]);
+        let mut values = HashMap::from([(":pkval".to_string(), self.partition_value)]);
+
+        if let Some((sort_key, condition)) = self.sort_conditions.into_iter().next() {
+            names.insert("#sk".to_string(), sort_key);
+            expression.push_str(" AND ");
+            expression.push_str(&condition.render(&mut values));
+        }
+
+        Ok(KeyConditionExpression {
+            expression,
+            names,
+            values,
+        })
+    }
+}
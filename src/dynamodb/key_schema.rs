@@ -0,0 +1,92 @@
+use anyhow::Result;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+use tokio::sync::OnceCell;
+
+/// How long a table's key schema is trusted before it's described again.
+pub(crate) const KEY_SCHEMA_TTL: Duration = Duration::from_secs(300);
+
+/// The names of a table's key attributes, as returned by
+/// [`DynamoDb::key_schema`](crate::dynamodb::DynamoDb::key_schema).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeySchema {
+    pub partition_key: String,
+    pub sort_key: Option<String>,
+}
+
+impl KeySchema {
+    /// The key attribute names, partition key first.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        std::iter::once(self.partition_key.as_str()).chain(self.sort_key.as_deref())
+    }
+}
+
+/// Key schemas by table name, each described at most once per `ttl`.
+///
+/// Every table has its own cell, so callers that miss at the same time wait
+/// for a single `DescribeTable` rather than each sending one. A failed
+/// lookup isn't cached.
+#[derive(Debug)]
+pub(crate) struct KeySchemaCache {
+    ttl: Duration,
+    tables: Mutex<HashMap<String, Arc<OnceCell<Cached>>>>,
+}
+
+#[derive(Debug)]
+struct Cached {
+    schema: KeySchema,
+    fetched: Instant,
+}
+
+impl KeySchemaCache {
+    pub(crate) fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            tables: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// The cached schema for `table_name`, or the one `describe` returns if
+    /// there's none or it's older than the TTL.
+    pub(crate) async fn get<F, Fut>(&self, table_name: &str, describe: F) -> Result<KeySchema>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<KeySchema>>,
+    {
+        let cell = {
+            let mut tables = self.lock();
+            let cell = tables.entry(table_name.to_string()).or_default();
+            if cell
+                .get()
+                .is_some_and(|cached| cached.fetched.elapsed() >= self.ttl)
+            {
+                *cell = Arc::default();
+            }
+            cell.clone()
+        };
+        let cached = cell
+            .get_or_try_init(|| async {
+                Ok::<_, anyhow::Error>(Cached {
+                    schema: describe().await?,
+                    fetched: Instant::now(),
+                })
+            })
+            .await?;
+        Ok(cached.schema.clone())
+    }
+
+    /// Forgets `table_name`'s schema, so the next lookup describes it again.
+    pub(crate) fn invalidate(&self, table_name: &str) {
+        self.lock().remove(table_name);
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<String, Arc<OnceCell<Cached>>>> {
+        // Every change to the map is a single insert, replace or remove, so a
+        // lock poisoned by a panicking holder still guards a usable map.
+        self.tables
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
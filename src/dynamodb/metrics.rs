@@ -0,0 +1,644 @@
+use aws_sdk_dynamodb::config::interceptors::{
+    BeforeSerializationInterceptorContextMut, BeforeSerializationInterceptorContextRef,
+    FinalizerInterceptorContextRef,
+};
+use aws_sdk_dynamodb::config::{ConfigBag, Intercept, RuntimeComponents};
+use aws_sdk_dynamodb::error::BoxError;
+use aws_sdk_dynamodb::operation::{
+    batch_get_item::{BatchGetItemInput, BatchGetItemOutput},
+    batch_write_item::{BatchWriteItemInput, BatchWriteItemOutput},
+    delete_item::{DeleteItemInput, DeleteItemOutput},
+    execute_statement::{ExecuteStatementInput, ExecuteStatementOutput},
+    get_item::{GetItemInput, GetItemOutput},
+    put_item::{PutItemInput, PutItemOutput},
+    query::{QueryInput, QueryOutput},
+    scan::{ScanInput, ScanOutput},
+    transact_get_items::{TransactGetItemsInput, TransactGetItemsOutput},
+    transact_write_items::{TransactWriteItemsInput, TransactWriteItemsOutput},
+    update_item::{UpdateItemInput, UpdateItemOutput},
+};
+use aws_sdk_dynamodb::types::{ConsumedCapacity, ReturnConsumedCapacity};
+use aws_smithy_runtime_api::client::interceptors::context::{Input, Output};
+use aws_smithy_runtime_api::client::orchestrator::Metadata;
+use aws_smithy_runtime_api::client::retries::RequestAttempts;
+use aws_smithy_types::config_bag::{Storable, StoreReplace};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::ops::Sub;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::dynamodb::Statement;
+
+/// Upper bounds, in milliseconds, of the latency histogram buckets. Slower
+/// requests land in a final, unbounded bucket.
+pub const LATENCY_BUCKETS_MS: &[u64] = &[5, 10, 25, 50, 100, 250, 500, 1000, 2500];
+
+/// Per-operation counters for every request a client sends.
+///
+/// Attach one with [`DynamoDb::with_metrics`](super::DynamoDb::with_metrics)
+/// and read it with [`Metrics::snapshot`]. Requests are counted once however
+/// many times the SDK retried them; the retries are counted separately.
+///
+/// The capacity each request consumed is also added up by table, for the
+/// requests DynamoDB reports it for (see
+/// [`DynamoDb::with_consumed_capacity`](super::DynamoDb::with_consumed_capacity)).
+#[derive(Debug, Default)]
+pub struct Metrics {
+    operations: Mutex<BTreeMap<String, OperationStats>>,
+    capacity: Mutex<CapacityStats>,
+}
+
+#[derive(Debug, Default)]
+struct CapacityStats {
+    tables: BTreeMap<String, TableCapacity>,
+    costliest: Option<CostliestRequest>,
+}
+
+/// Whether a request's capacity is counted as reads or writes, when
+/// DynamoDB reports only the total.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum CapacityKind {
+    Read,
+    Write,
+}
+
+impl Storable for CapacityKind {
+    type Storer = StoreReplace<Self>;
+}
+
+#[derive(Debug, Default)]
+struct OperationStats {
+    calls: u64,
+    retries: u64,
+    items: u64,
+    consumed_capacity: Option<f64>,
+    errors: BTreeMap<String, u64>,
+    total_latency: Duration,
+    max_latency: Duration,
+    latency_buckets: [u64; LATENCY_BUCKETS_MS.len() + 1],
+}
+
+/// What one finished request adds to its operation's counters.
+struct Sample<'a> {
+    operation: &'a str,
+    latency: Duration,
+    attempts: u32,
+    items: u64,
+    kind: CapacityKind,
+    consumed_capacity: Vec<ConsumedCapacity>,
+    error: Option<String>,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn record(&self, sample: Sample<'_>) {
+        self.record_capacity(sample.operation, sample.kind, &sample.consumed_capacity);
+        let mut operations = self.operations.lock().unwrap_or_else(|e| e.into_inner());
+        let stats = operations.entry(sample.operation.to_string()).or_default();
+        stats.calls += 1;
+        stats.retries += u64::from(sample.attempts.saturating_sub(1));
+        stats.items += sample.items;
+        if let Some(units) = sample
+            .consumed_capacity
+            .iter()
+            .filter_map(ConsumedCapacity::capacity_units)
+            .reduce(|a, b| a + b)
+        {
+            *stats.consumed_capacity.get_or_insert(0.0) += units;
+        }
+        if let Some(error) = sample.error {
+            *stats.errors.entry(error).or_default() += 1;
+        }
+        stats.total_latency += sample.latency;
+        stats.max_latency = stats.max_latency.max(sample.latency);
+        let millis = sample.latency.as_millis();
+        let bucket = LATENCY_BUCKETS_MS
+            .iter()
+            .position(|&bound| millis <= u128::from(bound))
+            .unwrap_or(LATENCY_BUCKETS_MS.len());
+        stats.latency_buckets[bucket] += 1;
+    }
+
+    /// Adds the capacity one `operation` request consumed, as reported per
+    /// table in `consumed`, to its tables' totals, and keeps the request if
+    /// it's the most expensive so far.
+    ///
+    /// Units DynamoDB didn't split into reads and writes count as `kind`.
+    pub(crate) fn record_capacity(
+        &self,
+        operation: &str,
+        kind: CapacityKind,
+        consumed: &[ConsumedCapacity],
+    ) {
+        if consumed.is_empty() {
+            return;
+        }
+        let mut stats = self.capacity.lock().unwrap_or_else(|e| e.into_inner());
+        let mut request = TableCapacity::default();
+        let mut tables = Vec::new();
+        for consumed in consumed {
+            let units = TableCapacity::consumed(consumed, kind);
+            let table = consumed.table_name().unwrap_or("unknown").to_string();
+            let total = stats.tables.entry(table.clone()).or_default();
+            total.read_units += units.read_units;
+            total.write_units += units.write_units;
+            request.read_units += units.read_units;
+            request.write_units += units.write_units;
+            tables.push(table);
+        }
+        if stats
+            .costliest
+            .as_ref()
+            .is_none_or(|costliest| request.total() > costliest.capacity.total())
+        {
+            stats.costliest = Some(CostliestRequest {
+                operation: operation.to_string(),
+                tables,
+                capacity: request,
+            });
+        }
+    }
+
+    /// A copy of the counters so far.
+    pub fn snapshot(&self) -> MetricsSnapshot {
+        let operations = self.operations.lock().unwrap_or_else(|e| e.into_inner());
+        let capacity = self.capacity.lock().unwrap_or_else(|e| e.into_inner());
+        MetricsSnapshot {
+            operations: operations
+                .iter()
+                .map(|(name, stats)| (name.clone(), OperationSnapshot::new(stats)))
+                .collect(),
+            tables: capacity.tables.clone(),
+            costliest: capacity.costliest.clone(),
+        }
+    }
+}
+
+/// The counters of a [`Metrics`] at one moment, by operation name (e.g.
+/// `GetItem`).
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct MetricsSnapshot {
+    pub operations: BTreeMap<String, OperationSnapshot>,
+    /// Capacity consumed so far, by table name.
+    pub tables: BTreeMap<String, TableCapacity>,
+    /// The request that consumed the most capacity, if any reported it.
+    pub costliest: Option<CostliestRequest>,
+}
+
+impl MetricsSnapshot {
+    /// The capacity consumed across every table.
+    pub fn total_capacity(&self) -> TableCapacity {
+        self.tables
+            .values()
+            .fold(TableCapacity::default(), |total, table| TableCapacity {
+                read_units: total.read_units + table.read_units,
+                write_units: total.write_units + table.write_units,
+            })
+    }
+}
+
+/// Read and write capacity units consumed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct TableCapacity {
+    pub read_units: f64,
+    pub write_units: f64,
+}
+
+impl TableCapacity {
+    /// The units in `consumed`, split into reads and writes if DynamoDB
+    /// did, else all counted as `kind`.
+    fn consumed(consumed: &ConsumedCapacity, kind: CapacityKind) -> Self {
+        match (
+            consumed.read_capacity_units(),
+            consumed.write_capacity_units(),
+        ) {
+            (None, None) => {
+                let units = consumed.capacity_units().unwrap_or_default();
+                match kind {
+                    CapacityKind::Read => Self {
+                        read_units: units,
+                        write_units: 0.0,
+                    },
+                    CapacityKind::Write => Self {
+                        read_units: 0.0,
+                        write_units: units,
+                    },
+                }
+            }
+            (read, write) => Self {
+                read_units: read.unwrap_or_default(),
+                write_units: write.unwrap_or_default(),
+            },
+        }
+    }
+
+    pub fn total(&self) -> f64 {
+        self.read_units + self.write_units
+    }
+}
+
+impl Sub for TableCapacity {
+    type Output = Self;
+
+    fn sub(self, earlier: Self) -> Self {
+        Self {
+            read_units: self.read_units - earlier.read_units,
+            write_units: self.write_units - earlier.write_units,
+        }
+    }
+}
+
+impl fmt::Display for TableCapacity {
+    /// `3.5 RCU`, `1.0 WCU` or `3.5 RCU, 1.0 WCU`, leaving out a kind
+    /// none were consumed of.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.read_units > 0.0, self.write_units > 0.0) {
+            (true, true) => write!(f, "{:.1} RCU, {:.1} WCU", self.read_units, self.write_units),
+            (false, true) => write!(f, "{:.1} WCU", self.write_units),
+            _ => write!(f, "{:.1} RCU", self.read_units),
+        }
+    }
+}
+
+/// The single request that consumed the most capacity.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CostliestRequest {
+    /// The operation name, e.g. `Scan`.
+    pub operation: String,
+    /// The tables the request consumed capacity on.
+    pub tables: Vec<String>,
+    pub capacity: TableCapacity,
+}
+
+/// The counters for one operation.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct OperationSnapshot {
+    /// Requests made, successful or not.
+    pub calls: u64,
+    /// Extra attempts the SDK made for throttled or failed requests.
+    pub retries: u64,
+    /// Items read or written by the successful requests.
+    pub items: u64,
+    /// Capacity units consumed, for requests that asked DynamoDB for them.
+    pub consumed_capacity: Option<f64>,
+    /// Failed requests by error code, e.g. `ResourceNotFoundException`, or
+    /// by kind (`Timeout`, `Connection`) when DynamoDB never answered.
+    pub errors: BTreeMap<String, u64>,
+    pub total_latency_ms: f64,
+    pub max_latency_ms: f64,
+    /// How many requests fell in each latency bucket.
+    pub latency_buckets: Vec<LatencyBucket>,
+}
+
+/// Requests that took at most `le_ms` milliseconds (and more than the
+/// previous bucket's bound); `le_ms` is `None` for the last bucket.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct LatencyBucket {
+    pub le_ms: Option<u64>,
+    pub count: u64,
+}
+
+impl OperationSnapshot {
+    fn new(stats: &OperationStats) -> Self {
+        Self {
+            calls: stats.calls,
+            retries: stats.retries,
+            items: stats.items,
+            consumed_capacity: stats.consumed_capacity,
+            errors: stats.errors.clone(),
+            total_latency_ms: millis(stats.total_latency),
+            max_latency_ms: millis(stats.max_latency),
+            latency_buckets: stats
+                .latency_buckets
+                .iter()
+                .enumerate()
+                .map(|(index, &count)| LatencyBucket {
+                    le_ms: LATENCY_BUCKETS_MS.get(index).copied(),
+                    count,
+                })
+                .collect(),
+        }
+    }
+
+    /// The number of failed requests.
+    pub fn error_count(&self) -> u64 {
+        self.errors.values().sum()
+    }
+
+    pub fn mean_latency_ms(&self) -> f64 {
+        if self.calls == 0 {
+            0.0
+        } else {
+            self.total_latency_ms / self.calls as f64
+        }
+    }
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+impl fmt::Display for MetricsSnapshot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.operations.is_empty() {
+            return write!(f, "No requests made yet");
+        }
+        writeln!(
+            f,
+            "{:<20} {:>7} {:>7} {:>7} {:>9} {:>10} {:>10} {:>10}",
+            "Operation", "Calls", "Errors", "Retries", "Items", "Capacity", "Mean ms", "Max ms"
+        )?;
+        for (name, op) in &self.operations {
+            let capacity = op
+                .consumed_capacity
+                .map_or_else(|| "-".to_string(), |units| format!("{units:.1}"));
+            writeln!(
+                f,
+                "{:<20} {:>7} {:>7} {:>7} {:>9} {:>10} {:>10.1} {:>10.1}",
+                name,
+                op.calls,
+                op.error_count(),
+                op.retries,
+                op.items,
+                capacity,
+                op.mean_latency_ms(),
+                op.max_latency_ms
+            )?;
+            for (code, count) in &op.errors {
+                writeln!(f, "  {code}: {count}")?;
+            }
+        }
+        if !self.tables.is_empty() {
+            writeln!(f)?;
+            writeln!(f, "{:<30} {:>10} {:>10}", "Table", "RCU", "WCU")?;
+            for (name, table) in &self.tables {
+                writeln!(
+                    f,
+                    "{:<30} {:>10.1} {:>10.1}",
+                    name, table.read_units, table.write_units
+                )?;
+            }
+        }
+        if let Some(costliest) = &self.costliest {
+            writeln!(
+                f,
+                "Most expensive request: {} on {} ({})",
+                costliest.operation,
+                costliest.tables.join(", "),
+                costliest.capacity
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// When the request started, kept in the request's config bag.
+#[derive(Debug, Clone, Copy)]
+struct Started(Instant);
+
+impl Storable for Started {
+    type Storer = StoreReplace<Self>;
+}
+
+/// How many items a batch or transaction write asked for, kept in the
+/// request's config bag since the output only lists what wasn't written.
+#[derive(Debug, Clone, Copy)]
+struct RequestedItems(u64);
+
+impl Storable for RequestedItems {
+    type Storer = StoreReplace<Self>;
+}
+
+/// Feeds every request a client makes into a [`Metrics`].
+#[derive(Debug)]
+pub(crate) struct MetricsRecorder(pub(crate) Arc<Metrics>);
+
+/// Asks DynamoDB to report the total capacity each request consumes, for
+/// the requests that didn't ask already.
+#[derive(Debug)]
+pub(crate) struct ReturnTotalCapacity;
+
+impl Intercept for ReturnTotalCapacity {
+    fn name(&self) -> &'static str {
+        "ReturnTotalCapacity"
+    }
+
+    fn modify_before_serialization(
+        &self,
+        context: &mut BeforeSerializationInterceptorContextMut<'_>,
+        _runtime_components: &RuntimeComponents,
+        _cfg: &mut ConfigBag,
+    ) -> Result<(), BoxError> {
+        fn request<T>(
+            input: &mut Input,
+            field: impl FnOnce(&mut T) -> &mut Option<ReturnConsumedCapacity>,
+        ) -> bool
+        where
+            T: fmt::Debug + Send + Sync + 'static,
+        {
+            input
+                .downcast_mut::<T>()
+                .map(|input| field(input).get_or_insert(ReturnConsumedCapacity::Total))
+                .is_some()
+        }
+
+        let input = context.input_mut();
+        let _ = request(input, |i: &mut GetItemInput| {
+            &mut i.return_consumed_capacity
+        }) || request(input, |i: &mut PutItemInput| {
+            &mut i.return_consumed_capacity
+        }) || request(input, |i: &mut UpdateItemInput| {
+            &mut i.return_consumed_capacity
+        }) || request(input, |i: &mut DeleteItemInput| {
+            &mut i.return_consumed_capacity
+        }) || request(input, |i: &mut QueryInput| &mut i.return_consumed_capacity)
+            || request(input, |i: &mut ScanInput| &mut i.return_consumed_capacity)
+            || request(input, |i: &mut BatchGetItemInput| {
+                &mut i.return_consumed_capacity
+            })
+            || request(input, |i: &mut BatchWriteItemInput| {
+                &mut i.return_consumed_capacity
+            })
+            || request(input, |i: &mut TransactGetItemsInput| {
+                &mut i.return_consumed_capacity
+            })
+            || request(input, |i: &mut TransactWriteItemsInput| {
+                &mut i.return_consumed_capacity
+            })
+            || request(input, |i: &mut ExecuteStatementInput| {
+                &mut i.return_consumed_capacity
+            });
+        Ok(())
+    }
+}
+
+/// Whether `input` reads, for the operations that only read, or writes.
+/// PartiQL statements read if they're `SELECT`s.
+fn capacity_kind(input: &Input) -> CapacityKind {
+    let reads = input.downcast_ref::<GetItemInput>().is_some()
+        || input.downcast_ref::<QueryInput>().is_some()
+        || input.downcast_ref::<ScanInput>().is_some()
+        || input.downcast_ref::<BatchGetItemInput>().is_some()
+        || input.downcast_ref::<TransactGetItemsInput>().is_some()
+        || input
+            .downcast_ref::<ExecuteStatementInput>()
+            .and_then(ExecuteStatementInput::statement)
+            .and_then(|statement| Statement::parse(statement).ok())
+            .is_some_and(|statement| !statement.kind().is_write());
+    if reads {
+        CapacityKind::Read
+    } else {
+        CapacityKind::Write
+    }
+}
+
+impl Intercept for MetricsRecorder {
+    fn name(&self) -> &'static str {
+        "MetricsRecorder"
+    }
+
+    fn read_before_execution(
+        &self,
+        context: &BeforeSerializationInterceptorContextRef<'_>,
+        cfg: &mut ConfigBag,
+    ) -> Result<(), BoxError> {
+        let input = context.input();
+        let requested = if let Some(batch) = input.downcast_ref::<BatchWriteItemInput>() {
+            batch
+                .request_items()
+                .map(|tables| tables.values().map(Vec::len).sum::<usize>())
+        } else {
+            input
+                .downcast_ref::<TransactWriteItemsInput>()
+                .map(|transaction| transaction.transact_items().len())
+        };
+        let state = cfg.interceptor_state();
+        state.store_put(Started(Instant::now()));
+        state.store_put(capacity_kind(input));
+        if let Some(requested) = requested {
+            state.store_put(RequestedItems(requested as u64));
+        }
+        Ok(())
+    }
+
+    fn read_after_execution(
+        &self,
+        context: &FinalizerInterceptorContextRef<'_>,
+        _runtime_components: &RuntimeComponents,
+        cfg: &mut ConfigBag,
+    ) -> Result<(), BoxError> {
+        let (items, consumed_capacity, error) = match context.output_or_error() {
+            Some(Ok(output)) => {
+                let requested = cfg.load::<RequestedItems>().map_or(0, |r| r.0);
+                let (items, capacity) = items_and_capacity(output, requested);
+                (items, capacity, None)
+            }
+            Some(Err(error)) => {
+                let class = if error.is_timeout_error() {
+                    "Timeout".to_string()
+                } else if error.is_connector_error() {
+                    "Connection".to_string()
+                } else {
+                    context
+                        .response()
+                        .and_then(error_code)
+                        .unwrap_or_else(|| "Other".to_string())
+                };
+                (0, Vec::new(), Some(class))
+            }
+            None => (0, Vec::new(), Some("Other".to_string())),
+        };
+        self.0.record(Sample {
+            operation: cfg.load::<Metadata>().map_or("unknown", Metadata::name),
+            latency: cfg
+                .load::<Started>()
+                .map_or(Duration::ZERO, |started| started.0.elapsed()),
+            attempts: cfg
+                .load::<RequestAttempts>()
+                .map_or(1, |attempts| attempts.attempts()),
+            items,
+            kind: cfg
+                .load::<CapacityKind>()
+                .copied()
+                .unwrap_or(CapacityKind::Write),
+            consumed_capacity,
+            error,
+        });
+        Ok(())
+    }
+}
+
+/// The items a successful request read or wrote and the capacity it
+/// consumed on each table, for the operations that report them.
+fn items_and_capacity(output: &Output, requested: u64) -> (u64, Vec<ConsumedCapacity>) {
+    fn single(consumed: Option<&ConsumedCapacity>) -> Vec<ConsumedCapacity> {
+        consumed.into_iter().cloned().collect()
+    }
+
+    if let Some(out) = output.downcast_ref::<GetItemOutput>() {
+        (
+            u64::from(out.item().is_some()),
+            single(out.consumed_capacity()),
+        )
+    } else if let Some(out) = output.downcast_ref::<PutItemOutput>() {
+        (1, single(out.consumed_capacity()))
+    } else if let Some(out) = output.downcast_ref::<UpdateItemOutput>() {
+        (1, single(out.consumed_capacity()))
+    } else if let Some(out) = output.downcast_ref::<DeleteItemOutput>() {
+        (1, single(out.consumed_capacity()))
+    } else if let Some(out) = output.downcast_ref::<QueryOutput>() {
+        (out.count().max(0) as u64, single(out.consumed_capacity()))
+    } else if let Some(out) = output.downcast_ref::<ScanOutput>() {
+        (out.count().max(0) as u64, single(out.consumed_capacity()))
+    } else if let Some(out) = output.downcast_ref::<BatchGetItemOutput>() {
+        let items = out
+            .responses()
+            .map_or(0, |tables| tables.values().map(Vec::len).sum::<usize>());
+        (items as u64, out.consumed_capacity().to_vec())
+    } else if let Some(out) = output.downcast_ref::<BatchWriteItemOutput>() {
+        let unprocessed = out
+            .unprocessed_items()
+            .map_or(0, |tables| tables.values().map(Vec::len).sum::<usize>());
+        (
+            requested.saturating_sub(unprocessed as u64),
+            out.consumed_capacity().to_vec(),
+        )
+    } else if let Some(out) = output.downcast_ref::<TransactWriteItemsOutput>() {
+        (requested, out.consumed_capacity().to_vec())
+    } else if let Some(out) = output.downcast_ref::<TransactGetItemsOutput>() {
+        (
+            out.responses().len() as u64,
+            out.consumed_capacity().to_vec(),
+        )
+    } else if let Some(out) = output.downcast_ref::<ExecuteStatementOutput>() {
+        (out.items().len() as u64, single(out.consumed_capacity()))
+    } else {
+        (0, Vec::new())
+    }
+}
+
+/// The error code of a failed response, e.g. `ResourceNotFoundException`,
+/// from its `__type` field (or header), falling back to the HTTP status.
+fn error_code(response: &aws_smithy_runtime_api::http::Response) -> Option<String> {
+    let from_header = response
+        .headers()
+        .get("x-amzn-errortype")
+        .map(str::to_string);
+    let from_body = || {
+        let body: serde_json::Value = serde_json::from_slice(response.body().bytes()?).ok()?;
+        body.get("__type")?.as_str().map(str::to_string)
+    };
+    let code = from_header.or_else(from_body).map(|code| {
+        // Both forms look like `com.amazonaws.dynamodb.v20120810#Code`,
+        // sometimes with `:url` details after the code.
+        let code = code.rsplit('#').next().unwrap_or_default();
+        code.split(':').next().unwrap_or_default().to_string()
+    });
+    code.filter(|code| !code.is_empty())
+        .or_else(|| Some(format!("HTTP {}", response.status().as_u16())))
+}
@@ -5,9 +5,44 @@
 //! ## Components
 //!
 //! - `DynamoDb`: A client wrapper for performing DynamoDB operations.
+//! - `DynamoDbBuilder`: Configures a `DynamoDb` from explicit settings
+//!   instead of the environment (`DynamoDb::builder`).
+//! - `DynamoDbApi`: The operations the CLI needs, implemented by `DynamoDb`.
+//! - `DynamoEntity`: Maps a serde type to its table and key, for
+//!   `DynamoDb::save`, `load`, `delete_entity` and `query_partition`; with
+//!   the `derive` feature, `#[derive(DynamoEntity)]` implements it.
+//! - `InMemoryDynamoDb`: A `DynamoDbApi` backed by in-memory tables, for tests.
+//! - `AuthInfo`: Who a client is authenticated as, from STS.
+//! - `BackupArn` / `BackupInfo`: Identify and describe on-demand backups.
+//! - `cassette`: Records DynamoDB HTTP traffic and replays it offline (tests
+//!   and the `test-util` feature).
+//! - `ConditionFailed` / `TransactionCancelled`: Errors for conditional writes
+//!   whose conditions were false; `DynamoError` asks any error about them.
+//! - `ExpressionRejected`: The error for expressions DynamoDB refuses, with
+//!   the expressions attached.
+//! - `ExportInfo` / `ImportInfo`: Track table exports to and imports from S3.
 //! - `Item`: Represents a DynamoDB item with various attribute types.
+//! - `Metrics`: Per-operation request counts, latencies, items, capacity and
+//!   errors, and capacity per table (`DynamoDb::with_metrics` collects them).
+//! - `JsonFormat`: Plain or DynamoDB JSON, for importing and exporting items.
+//! - `PageToken`: Encodes pagination keys as opaque, optionally signed cursors.
+//! - `ProjectionExpression`: Aliases projected attributes so reserved words are safe.
+//! - `RequestFailed`: Names the operation, table and key of any failed request.
+//! - `retry`: Traces the retries of throttled and failed requests
+//!   (`DynamoDb::with_retry_policy` sets how many).
 //! - `Schema`: Defines the structure of a DynamoDB table.
+//! - `Statement`: A PartiQL statement checked for its kind and `?` parameters.
+//! - `StreamReader` / `StreamRecord`: Read the changes made to a table from
+//!   its stream (`DynamoDb::stream_reader`), or have them delivered in the
+//!   background with `DynamoDb::subscribe_changes`.
 //! - `Table`: Represents a DynamoDB table configuration.
+//! - `TableInfo`: A table's live description as reported by DynamoDB.
+//! - `TableNotFound`: The error for requests that name a missing table.
+//! - `Filter`: Builds filter expressions from typed conditions.
+//! - `KeyCondition`: Builds query key condition expressions with safe placeholders.
+//! - `UpdateBuilder`: Composes update expressions from individual actions.
+//! - `WireLog`: Logs raw requests and responses at DEBUG, credentials redacted
+//!   (`DynamoDb::with_wire_logging` turns it on).
 //!
 //! ## Usage
 //!
@@ -21,17 +56,23 @@
 //! - `AWS_SESSION_TOKEN`: If you're using temporary credentials.
 //! - `AWS_ENDPOINT_URL`: For using a custom endpoint (e.g., for local development).
 //!
+//! DynamoDB Local needs none of these: `DynamoDb::new_local` connects with
+//! dummy credentials and a fixed region.
+//!
+//! `DynamoDb::builder` doesn't read them either: it takes the region,
+//! credentials or profile, endpoint, timeout and retries as arguments, so
+//! clients for different accounts can live side by side.
+//!
 //! ## Example
 //!
-//! ```rust
+//! ```rust,no_run
 //! use aws_config::load_from_env;
-//! use dynamodb::{DynamoDb, Table, Item, Schema, FieldType};
+//! use aws_dynamodb_testing::dynamodb::{DynamoDb, FieldType, Item, Schema, Table};
 //!
 //! #[tokio::main]
 //! async fn main() -> Result<(), Box<dyn std::error::Error>> {
 //!     // Load AWS configuration from environment variables
 //!     let config = load_from_env().await;
-//!     
 //!     // Create a DynamoDB client
 //!     let client = DynamoDb::new(&config);
 //!
@@ -64,12 +105,84 @@
 //! This module simplifies DynamoDB operations and provides a more Rust-idiomatic interface
 //! to work with DynamoDB tables and items.
 
+mod api;
+mod auth;
+mod backup;
+mod builder;
+#[cfg(any(test, feature = "test-util"))]
+mod cassette;
 mod client;
+mod condition;
+mod dynamodb_json;
+#[cfg(feature = "serde")]
+mod entity;
+mod evaluate;
+mod export;
+mod expression_error;
+mod filter;
+mod in_memory;
 mod item;
+mod key_condition;
+mod key_schema;
+mod metrics;
+mod page_token;
+mod projection;
+mod request_error;
+mod retry;
 mod schema;
+mod sharding;
+mod statement;
+mod stream;
 mod table;
+mod table_info;
+mod update;
+mod wire_log;
 
-pub use client::{DynamoDb, QueryFlexibleParams};
+pub use api::DynamoDbApi;
+pub use auth::AuthInfo;
+pub use backup::{BackupArn, BackupInfo};
+pub use builder::DynamoDbBuilder;
+pub use client::{is_local_endpoint, LOCAL_ENDPOINT};
+pub use client::{
+    BulkResult, BulkStatus, ConcurrentPutStats, FailedBatch, ItemConversionError, ItemCount,
+    Progress, QueryOptions, ReadResult, ScanStats, ScanSummary, StatementResult, TypedItems,
+    WriteResult,
+};
+pub use client::{DynamoDb, GetItemOptions, QueryFlexibleParams, QueryResult, ScanParams};
+pub use condition::{ConditionFailed, DynamoError, TransactionCancelled};
+#[cfg(feature = "derive")]
+pub use ddb_simple_derive::DynamoEntity;
+#[cfg(feature = "cli")]
+pub(crate) use dynamodb_json::from_plain_json;
+pub use dynamodb_json::JsonFormat;
+#[cfg(feature = "serde")]
+pub use entity::DynamoEntity;
+pub use export::{ExportInfo, ImportInfo, PointInTimeRecoveryDisabled};
+pub use expression_error::ExpressionRejected;
+pub use filter::{Filter, FilterExpression, Size};
+pub use in_memory::InMemoryDynamoDb;
 pub use item::Item;
+pub use key_condition::{
+    KeyCondition, KeyConditionExpression, SortKeyCondition, SortKeyValue, SORT_KEY_OPERATORS,
+};
+pub use key_schema::KeySchema;
+#[cfg(test)]
+pub(crate) use metrics::CapacityKind;
+pub use metrics::{
+    CostliestRequest, LatencyBucket, Metrics, MetricsSnapshot, OperationSnapshot, TableCapacity,
+};
+pub use page_token::PageToken;
+pub use projection::ProjectionExpression;
+pub use request_error::RequestFailed;
+pub use retry::is_transient;
 pub use schema::{FieldType, Schema};
-pub use table::Table;
+pub use sharding::{ShardedKey, SHARD_SEPARATOR};
+pub use statement::{Statement, StatementKind};
+pub use stream::{
+    CheckpointStore, InMemoryCheckpoints, StreamEventType, StreamReader, StreamRecord,
+    StreamsDisabled,
+};
+pub use table::{Capacity, GlobalSecondaryIndex, Table};
+pub use table_info::{IndexInfo, LocalIndexInfo, TableInfo, TableNotFound};
+pub use update::UpdateBuilder;
+pub use wire_log::{WireLog, WIRE_LOG_ENV, WIRE_LOG_MAX_VALUE_ENV};
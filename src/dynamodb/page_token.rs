@@ -0,0 +1,159 @@
+use anyhow::{anyhow, Result};
+use aws_sdk_dynamodb::{primitives::Blob, types::AttributeValue};
+use base64::engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD};
+use base64::Engine;
+use hmac::{Hmac, Mac};
+use serde_json::{json, Map, Value};
+use sha2::Sha256;
+use std::collections::HashMap;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Converts a `LastEvaluatedKey` to and from an opaque, URL-safe cursor.
+///
+/// The key is serialized in DynamoDB JSON (`{"id": {"S": "123"}}`) and
+/// base64url-encoded. Signed tokens append an HMAC-SHA256 of the payload, so a
+/// client can't hand back a cursor the server didn't issue.
+///
+/// # Example
+///
+/// ```
+/// use aws_dynamodb_testing::dynamodb::PageToken;
+/// # use aws_dynamodb_testing::aws::AttributeValue;
+/// # use std::collections::HashMap;
+/// # let last_evaluated_key =
+/// #     HashMap::from([("id".to_string(), AttributeValue::S("42".to_string()))]);
+///
+/// let token = PageToken::encode_signed(&last_evaluated_key, b"server secret")?;
+/// let key = PageToken::decode_signed(&token, b"server secret")?;
+/// # assert_eq!(key, last_evaluated_key);
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+pub struct PageToken;
+
+impl PageToken {
+    /// Encodes a key as an unsigned token.
+    pub fn encode(key: &HashMap<String, AttributeValue>) -> Result<String> {
+        Self::encode_with(key, None)
+    }
+
+    /// Decodes an unsigned token back into a key.
+    pub fn decode(token: &str) -> Result<HashMap<String, AttributeValue>> {
+        Self::decode_with(token, None)
+    }
+
+    /// Encodes a key as a token signed with `secret`.
+    pub fn encode_signed(key: &HashMap<String, AttributeValue>, secret: &[u8]) -> Result<String> {
+        Self::encode_with(key, Some(secret))
+    }
+
+    /// Decodes a token signed with `secret`, rejecting it if the signature
+    /// is missing or doesn't match.
+    pub fn decode_signed(token: &str, secret: &[u8]) -> Result<HashMap<String, AttributeValue>> {
+        Self::decode_with(token, Some(secret))
+    }
+
+    /// Encodes a key, signing it when a secret is given.
+    pub(crate) fn encode_with(
+        key: &HashMap<String, AttributeValue>,
+        secret: Option<&[u8]>,
+    ) -> Result<String> {
+        let attributes = key
+            .iter()
+            .map(|(name, value)| Ok((name.clone(), to_dynamodb_json(name, value)?)))
+            .collect::<Result<Map<_, _>>>()?;
+        let payload = serde_json::to_vec(&Value::Object(attributes))?;
+        let encoded = URL_SAFE_NO_PAD.encode(&payload);
+
+        Ok(match secret {
+            Some(secret) => {
+                let signature = signer(secret)?.chain_update(&payload).finalize();
+                format!(
+                    "{encoded}.{}",
+                    URL_SAFE_NO_PAD.encode(signature.into_bytes())
+                )
+            }
+            None => encoded,
+        })
+    }
+
+    /// Decodes a token, verifying its signature when a secret is given.
+    pub(crate) fn decode_with(
+        token: &str,
+        secret: Option<&[u8]>,
+    ) -> Result<HashMap<String, AttributeValue>> {
+        let (encoded, signature) = match token.split_once('.') {
+            Some((encoded, signature)) => (encoded, Some(signature)),
+            None => (token, None),
+        };
+        let payload = URL_SAFE_NO_PAD
+            .decode(encoded)
+            .map_err(|e| anyhow!("Invalid page token: {e}"))?;
+
+        match (secret, signature) {
+            (Some(secret), Some(signature)) => {
+                let signature = URL_SAFE_NO_PAD
+                    .decode(signature)
+                    .map_err(|e| anyhow!("Invalid page token signature: {e}"))?;
+                signer(secret)?
+                    .chain_update(&payload)
+                    .verify_slice(&signature)
+                    .map_err(|_| anyhow!("Page token signature does not match"))?;
+            }
+            (Some(_), None) => return Err(anyhow!("Page token is not signed")),
+            (None, Some(_)) => {
+                return Err(anyhow!("Page token is signed but no signing key was given"))
+            }
+            (None, None) => {}
+        }
+
+        let Value::Object(attributes) = serde_json::from_slice(&payload)
+            .map_err(|e| anyhow!("Invalid page token payload: {e}"))?
+        else {
+            return Err(anyhow!("Invalid page token payload: expected an object"));
+        };
+        attributes
+            .into_iter()
+            .map(|(name, value)| {
+                let value = from_dynamodb_json(&name, value)?;
+                Ok((name, value))
+            })
+            .collect()
+    }
+}
+
+fn signer(secret: &[u8]) -> Result<HmacSha256> {
+    HmacSha256::new_from_slice(secret).map_err(|e| anyhow!("Invalid signing key: {e}"))
+}
+
+/// Key attributes can only be strings, numbers or binary.
+fn to_dynamodb_json(name: &str, value: &AttributeValue) -> Result<Value> {
+    Ok(match value {
+        AttributeValue::S(s) => json!({ "S": s }),
+        AttributeValue::N(n) => json!({ "N": n }),
+        AttributeValue::B(b) => json!({ "B": STANDARD.encode(b.as_ref()) }),
+        _ => {
+            return Err(anyhow!(
+                "Key attribute '{name}' must be a string, number or binary"
+            ))
+        }
+    })
+}
+
+fn from_dynamodb_json(name: &str, value: Value) -> Result<AttributeValue> {
+    let invalid = || anyhow!("Invalid page token value for '{name}'");
+    let Value::Object(typed) = value else {
+        return Err(invalid());
+    };
+    let mut typed = typed.into_iter();
+    let (Some((kind, Value::String(value))), None) = (typed.next(), typed.next()) else {
+        return Err(invalid());
+    };
+
+    Ok(match kind.as_str() {
+        "S" => AttributeValue::S(value),
+        "N" => AttributeValue::N(value),
+        "B" => AttributeValue::B(Blob::new(STANDARD.decode(value).map_err(|_| invalid())?)),
+        _ => return Err(invalid()),
+    })
+}
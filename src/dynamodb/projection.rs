@@ -0,0 +1,80 @@
+use anyhow::{anyhow, Result};
+use std::collections::HashMap;
+
+/// A projection expression with every attribute name aliased.
+///
+/// DynamoDB rejects reserved words such as `name`, `status` or `size` in a raw
+/// projection, so each path segment is replaced by a `#pN` placeholder.
+/// Nested paths (`address.city`) and list elements (`tags[0]`) are supported.
+///
+/// # Example
+///
+/// ```
+/// use aws_dynamodb_testing::dynamodb::ProjectionExpression;
+///
+/// let projection = ProjectionExpression::new(&["name", "status", "address.city"]).unwrap();
+/// assert_eq!(projection.expression, "#p0, #p1, #p2.#p3");
+///
+/// // Projecting nothing means no projection: every attribute is returned.
+/// assert!(ProjectionExpression::new(&[]).is_none());
+/// ```
+#[derive(Debug, Clone)]
+pub struct ProjectionExpression {
+    pub expression: String,
+    pub names: HashMap<String, String>,
+}
+
+impl ProjectionExpression {
+    /// Builds the aliased projection for the given attribute paths, or
+    /// `None` if there are none, as DynamoDB rejects an empty projection.
+    pub fn new(attributes: &[&str]) -> Option<Self> {
+        if attributes.is_empty() {
+            return None;
+        }
+        let mut names = HashMap::new();
+        let mut aliases: HashMap<&str, String> = HashMap::new();
+
+        let paths: Vec<String> = attributes
+            .iter()
+            .map(|path| {
+                path.split('.')
+                    .map(|segment| {
+                        let (name, index) = segment
+                            .find('[')
+                            .map_or((segment, ""), |i| segment.split_at(i));
+                        let alias = aliases.entry(name).or_insert_with(|| {
+                            let alias = format!("#p{}", names.len());
+                            names.insert(alias.clone(), name.to_string());
+                            alias
+                        });
+                        format!("{alias}{index}")
+                    })
+                    .collect::<Vec<_>>()
+                    .join(".")
+            })
+            .collect();
+
+        Some(Self {
+            expression: paths.join(", "),
+            names,
+        })
+    }
+
+    /// Merges the projection's placeholders into an existing names map.
+    ///
+    /// Fails if a placeholder is already bound to a different attribute.
+    pub(crate) fn merge_into(&self, names: &mut HashMap<String, String>) -> Result<()> {
+        for (placeholder, name) in &self.names {
+            if names
+                .get(placeholder)
+                .is_some_and(|existing| existing != name)
+            {
+                return Err(anyhow!(
+                    "Projection placeholder '{placeholder}' is already used for another attribute"
+                ));
+            }
+        }
+        names.extend(self.names.clone());
+        Ok(())
+    }
+}
@@ -0,0 +1,77 @@
+use aws_sdk_dynamodb::types::AttributeValue;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+use crate::dynamodb::expression_error::redact;
+
+/// Context attached to every error the client gets back from AWS, saying
+/// which request failed.
+///
+/// Callers can read it with `error.downcast_ref::<RequestFailed>()`; the SDK
+/// error is kept as its source. More specific errors, such as
+/// [`TableNotFound`](crate::dynamodb::TableNotFound), may be attached on
+/// top of it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestFailed {
+    /// The API operation, e.g. `PutItem`.
+    pub operation: &'static str,
+    pub table_name: Option<String>,
+    /// The item's key, each value redacted to its type and length, for
+    /// requests on a single item.
+    pub key: Option<BTreeMap<String, String>>,
+    /// The error code AWS returned, e.g. `ResourceNotFoundException`, if
+    /// the request got as far as a response.
+    pub code: Option<String>,
+    /// Whether sending the request again might succeed; see
+    /// [`is_transient`](crate::dynamodb::is_transient).
+    pub transient: bool,
+}
+
+impl RequestFailed {
+    /// Describes a failed request whose SDK error type is `E`, e.g.
+    /// `PutItemError` for `PutItem`.
+    pub(crate) fn new<E>(
+        table_name: Option<&str>,
+        key: Option<&HashMap<String, AttributeValue>>,
+        code: Option<&str>,
+        transient: bool,
+    ) -> Self {
+        Self {
+            operation: operation_name::<E>(),
+            table_name: table_name.map(str::to_string),
+            key: key.map(|key| {
+                key.iter()
+                    .map(|(name, value)| (name.clone(), redact(value)))
+                    .collect()
+            }),
+            code: code.map(str::to_string),
+            transient,
+        }
+    }
+}
+
+/// The operation an SDK error type belongs to, from its name.
+fn operation_name<E>() -> &'static str {
+    let name = std::any::type_name::<E>();
+    let name = name.rsplit("::").next().unwrap_or(name);
+    name.strip_suffix("Error").unwrap_or(name)
+}
+
+impl fmt::Display for RequestFailed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.operation)?;
+        if let Some(table_name) = &self.table_name {
+            write!(f, " on table '{table_name}'")?;
+        }
+        if let Some(key) = &self.key {
+            write!(f, " for key {key:?}")?;
+        }
+        write!(f, " failed")?;
+        if let Some(code) = &self.code {
+            write!(f, " with {code}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for RequestFailed {}
@@ -0,0 +1,99 @@
+use aws_sdk_dynamodb::config::interceptors::{
+    BeforeTransmitInterceptorContextRef, FinalizerInterceptorContextRef,
+};
+use aws_sdk_dynamodb::config::{ConfigBag, Intercept, RuntimeComponents};
+use aws_sdk_dynamodb::error::{BoxError, ProvideErrorMetadata, SdkError};
+use aws_smithy_runtime_api::client::orchestrator::Metadata;
+use aws_smithy_runtime_api::client::retries::RequestAttempts;
+use tracing::{info, warn};
+
+/// Error codes AWS uses when a request was throttled.
+const THROTTLING_CODES: &[&str] = &[
+    "ThrottlingException",
+    "Throttling",
+    "ProvisionedThroughputExceededException",
+    "RequestLimitExceeded",
+    "TooManyRequestsException",
+    "TransactionInProgressException",
+];
+
+/// Whether a failed request is worth sending again: it was throttled, the
+/// service had a 5xx error, or it timed out or never got an answer.
+///
+/// Anything else, such as a `ValidationException` or a missing table, will
+/// fail the same way every time.
+pub fn is_transient<E: ProvideErrorMetadata>(error: &SdkError<E>) -> bool {
+    match error {
+        SdkError::TimeoutError(_) | SdkError::ResponseError(_) => true,
+        SdkError::DispatchFailure(failure) => failure.is_timeout() || failure.is_io(),
+        SdkError::ServiceError(service) => {
+            let status = service.raw().status();
+            status.is_server_error()
+                || status.as_u16() == 429
+                || service
+                    .err()
+                    .code()
+                    .is_some_and(|code| THROTTLING_CODES.contains(&code))
+        }
+        _ => false,
+    }
+}
+
+/// Logs each retry the SDK makes and how the request finally went, so
+/// throttling shows up in traces rather than only as a slow call.
+#[derive(Debug)]
+pub(crate) struct RetryTracing;
+
+impl Intercept for RetryTracing {
+    fn name(&self) -> &'static str {
+        "RetryTracing"
+    }
+
+    fn read_before_attempt(
+        &self,
+        _context: &BeforeTransmitInterceptorContextRef<'_>,
+        _runtime_components: &RuntimeComponents,
+        cfg: &mut ConfigBag,
+    ) -> Result<(), BoxError> {
+        let attempt = attempts(cfg);
+        if attempt > 1 {
+            info!(
+                operation = operation(cfg),
+                attempt, "Retrying DynamoDB request"
+            );
+        }
+        Ok(())
+    }
+
+    fn read_after_execution(
+        &self,
+        context: &FinalizerInterceptorContextRef<'_>,
+        _runtime_components: &RuntimeComponents,
+        cfg: &mut ConfigBag,
+    ) -> Result<(), BoxError> {
+        let attempts = attempts(cfg);
+        if attempts > 1 {
+            let operation = operation(cfg);
+            match context.output_or_error() {
+                Some(Ok(_)) => info!(
+                    operation,
+                    attempts, "DynamoDB request succeeded after retrying"
+                ),
+                _ => warn!(
+                    operation,
+                    attempts, "DynamoDB request failed after retrying"
+                ),
+            }
+        }
+        Ok(())
+    }
+}
+
+fn attempts(cfg: &ConfigBag) -> u32 {
+    cfg.load::<RequestAttempts>()
+        .map_or(1, |attempts| attempts.attempts())
+}
+
+fn operation(cfg: &ConfigBag) -> &str {
+    cfg.load::<Metadata>().map_or("unknown", Metadata::name)
+}
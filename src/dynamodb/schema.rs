@@ -1,5 +1,8 @@
+use aws_sdk_dynamodb::types::AttributeValue;
 use std::collections::HashMap;
 
+use crate::dynamodb::Item;
+
 /// Represents the schema of a DynamoDB table.
 ///
 /// In DynamoDB, a schema defines the structure of items in a table.
@@ -30,14 +33,14 @@ use std::collections::HashMap;
 /// # Example
 ///
 /// ```
-/// use dynamodb::{Schema, FieldType};
+/// use aws_dynamodb_testing::dynamodb::{FieldType, Schema};
 ///
 /// let schema = Schema::new()
 ///     .add_field("user_id", FieldType::String)
 ///     .add_field("timestamp", FieldType::Number)
 ///     .add_field("message", FieldType::String);
 /// ```
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct Schema {
     fields: HashMap<String, FieldType>,
 }
@@ -52,7 +55,18 @@ pub enum FieldType {
     String,
     /// Represents a number field.
     Number,
-    // Add more types as needed
+    /// A boolean (`BOOL`), entered as y/n.
+    Boolean,
+    /// A string set (`SS`), entered as comma-separated values.
+    StringSet,
+    /// A number set (`NS`), entered as comma-separated values.
+    NumberSet,
+    /// A list of strings (`L`), entered as comma-separated values.
+    StringList,
+    /// A list of any values (`L`), entered as a JSON array.
+    List,
+    /// A map (`M`), entered as a JSON object.
+    Map,
 }
 
 impl Schema {
@@ -73,4 +87,38 @@ impl Schema {
     pub fn fields(&self) -> &HashMap<String, FieldType> {
         &self.fields
     }
+
+    /// Infers a schema from a sample of items.
+    ///
+    /// Attributes that are a string in every item that has them become
+    /// `FieldType::String`, and likewise for numbers. Attributes of any other
+    /// type, or of different types across items, are left out.
+    pub fn infer(items: &[Item]) -> Self {
+        let mut fields: HashMap<String, Option<FieldType>> = HashMap::new();
+        for item in items {
+            for (name, value) in &item.attributes {
+                let field_type = match value {
+                    AttributeValue::S(_) => Some(FieldType::String),
+                    AttributeValue::N(_) => Some(FieldType::Number),
+                    _ => None,
+                };
+                let inferred = fields.entry(name.clone()).or_insert(field_type.clone());
+                let agrees = matches!(
+                    (&*inferred, &field_type),
+                    (Some(FieldType::String), Some(FieldType::String))
+                        | (Some(FieldType::Number), Some(FieldType::Number))
+                );
+                if !agrees {
+                    *inferred = None;
+                }
+            }
+        }
+
+        Self {
+            fields: fields
+                .into_iter()
+                .filter_map(|(name, field_type)| Some((name, field_type?)))
+                .collect(),
+        }
+    }
 }
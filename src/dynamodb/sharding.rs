@@ -0,0 +1,90 @@
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+
+/// Separates a sharded key's base value from its shard number.
+pub const SHARD_SEPARATOR: char = '#';
+
+/// A partition key value spread over several partitions, for keys written
+/// too often for one partition's throughput.
+///
+/// Each write goes to one of `shard_count` values, `base#0` to
+/// `base#{shard_count - 1}`, either the shard a discriminator such as the
+/// sort key hashes to, so rewriting an item finds it again, or a random
+/// one. A single hot key then spreads its writes over that many partitions.
+///
+/// The cost is on the read side: the items under the base value can only
+/// be read with one query per shard, which
+/// [`DynamoDb::query_all_shards`](crate::dynamodb::DynamoDb::query_all_shards)
+/// runs concurrently. Every read of the key costs at least `shard_count`
+/// requests, and an item written to a random shard can only be fetched by
+/// querying all of them. Pick the smallest count that stops the
+/// throttling.
+///
+/// ```
+/// use aws_dynamodb_testing::dynamodb::{Item, ShardedKey};
+///
+/// let counters = ShardedKey::new("global", 8);
+/// let item = Item::new()
+///     .set_sharded_partition_key("pk", &counters, Some("page-views"))
+///     .set_string("sk", "page-views");
+/// assert!(item.get_string("pk").unwrap().starts_with("global#"));
+/// assert_eq!(counters.shards().count(), 8);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShardedKey {
+    base: String,
+    shard_count: u32,
+}
+
+impl ShardedKey {
+    /// Spreads `base_value` over `shard_count` shards.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `shard_count` is 0.
+    pub fn new(base_value: impl Into<String>, shard_count: u32) -> Self {
+        assert!(shard_count > 0, "a sharded key needs at least one shard");
+        Self {
+            base: base_value.into(),
+            shard_count,
+        }
+    }
+
+    /// The value the shards are made from.
+    pub fn base(&self) -> &str {
+        &self.base
+    }
+
+    pub fn shard_count(&self) -> u32 {
+        self.shard_count
+    }
+
+    /// The key value of shard `n`, wrapping around past the last shard.
+    pub fn shard(&self, n: u32) -> String {
+        format!("{}{SHARD_SEPARATOR}{}", self.base, n % self.shard_count)
+    }
+
+    /// Every shard's key value, in shard order.
+    pub fn shards(&self) -> impl Iterator<Item = String> + '_ {
+        (0..self.shard_count).map(|n| self.shard(n))
+    }
+
+    /// The shard `discriminator` always lands on.
+    ///
+    /// The hash is FNV-1a, so the shard stays the same across processes
+    /// and releases.
+    pub fn shard_for(&self, discriminator: &str) -> String {
+        let hash = discriminator
+            .bytes()
+            .fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+                (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+            });
+        self.shard((hash % u64::from(self.shard_count)) as u32)
+    }
+
+    /// A shard picked at random.
+    pub fn random_shard(&self) -> String {
+        let random = RandomState::new().build_hasher().finish();
+        self.shard((random % u64::from(self.shard_count)) as u32)
+    }
+}
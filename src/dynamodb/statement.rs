@@ -0,0 +1,136 @@
+use anyhow::{anyhow, Result};
+use std::fmt;
+
+/// The kind of PartiQL statement, taken from its leading keyword.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatementKind {
+    Select,
+    Insert,
+    Update,
+    Delete,
+}
+
+impl StatementKind {
+    /// Whether the statement modifies the table.
+    pub fn is_write(self) -> bool {
+        self != Self::Select
+    }
+}
+
+impl fmt::Display for StatementKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Select => "SELECT",
+            Self::Insert => "INSERT",
+            Self::Update => "UPDATE",
+            Self::Delete => "DELETE",
+        })
+    }
+}
+
+/// A single PartiQL statement, checked just enough to run it safely.
+///
+/// Parsing only looks at the shape DynamoDB cares about before sending: the
+/// leading keyword, the `?` placeholders that need positional parameters and
+/// whether a `WHERE` clause is present. Quoted strings (`'...'`) and quoted
+/// identifiers (`"..."`) are skipped, so a `?` or `;` inside them doesn't
+/// count. DynamoDB itself validates everything else.
+///
+/// # Example
+///
+/// ```
+/// use aws_dynamodb_testing::dynamodb::{Statement, StatementKind};
+///
+/// let statement = Statement::parse("SELECT * FROM \"products\" WHERE category = ?;")?;
+/// assert_eq!(statement.kind(), StatementKind::Select);
+/// assert_eq!(statement.parameter_count(), 1);
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Statement {
+    text: String,
+    kind: StatementKind,
+    parameter_count: usize,
+    has_where: bool,
+}
+
+impl Statement {
+    /// Parses a statement, ignoring surrounding whitespace and one trailing `;`.
+    pub fn parse(input: &str) -> Result<Self> {
+        let text = input.trim();
+        let text = text.strip_suffix(';').unwrap_or(text).trim_end();
+        if text.is_empty() {
+            return Err(anyhow!("Statement is empty"));
+        }
+
+        let mut words = Vec::new();
+        let mut parameter_count = 0;
+        let mut word = String::new();
+        let mut quote = None;
+        for c in text.chars() {
+            match quote {
+                Some(q) if c == q => quote = None,
+                Some(_) => {}
+                None if c == '\'' || c == '"' => quote = Some(c),
+                None if c == ';' => {
+                    return Err(anyhow!("Only one statement can be run at a time"));
+                }
+                None if c.is_alphanumeric() || c == '_' => {
+                    word.push(c);
+                    continue;
+                }
+                None if c == '?' => parameter_count += 1,
+                None => {}
+            }
+            if !word.is_empty() {
+                words.push(std::mem::take(&mut word));
+            }
+        }
+        if let Some(q) = quote {
+            return Err(anyhow!("Unterminated {q} quote in statement"));
+        }
+        if !word.is_empty() {
+            words.push(word);
+        }
+
+        let keyword = words.first().map(String::as_str).unwrap_or_default();
+        let kind = match keyword.to_ascii_uppercase().as_str() {
+            "SELECT" => StatementKind::Select,
+            "INSERT" => StatementKind::Insert,
+            "UPDATE" => StatementKind::Update,
+            "DELETE" => StatementKind::Delete,
+            _ => {
+                return Err(anyhow!(
+                    "Unsupported statement '{keyword}', expected SELECT, INSERT, UPDATE or DELETE"
+                ))
+            }
+        };
+
+        Ok(Self {
+            text: text.to_string(),
+            kind,
+            parameter_count,
+            has_where: words.iter().any(|word| word.eq_ignore_ascii_case("WHERE")),
+        })
+    }
+
+    /// The statement text, without the trailing `;`.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn kind(&self) -> StatementKind {
+        self.kind
+    }
+
+    /// The number of `?` placeholders the statement needs values for.
+    pub fn parameter_count(&self) -> usize {
+        self.parameter_count
+    }
+
+    /// Whether the statement updates or deletes without a `WHERE` clause,
+    /// i.e. could touch every item in the table.
+    pub fn is_unbounded_write(&self) -> bool {
+        matches!(self.kind, StatementKind::Update | StatementKind::Delete) && !self.has_where
+    }
+}
@@ -0,0 +1,558 @@
+use anyhow::{anyhow, bail, Context, Result};
+use aws_sdk_dynamodb::types::AttributeValue;
+use aws_sdk_dynamodbstreams::types::{
+    AttributeValue as StreamAttributeValue, Record, Shard, ShardIteratorType,
+};
+use aws_sdk_dynamodbstreams::Client as StreamsClient;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio_util::sync::CancellationToken;
+use tracing::{info, warn};
+
+use crate::dynamodb::client::request_failed;
+use crate::dynamodb::Item;
+
+/// How often [`StreamReader::poll_records`] lists the stream's shards
+/// again, to find the ones that split off since.
+const SHARD_REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+
+/// How long a change subscriber waits before polling again after finding
+/// no new records.
+const SUBSCRIBER_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Returned when a stream is needed but the table doesn't have one enabled.
+///
+/// Callers can detect it with `error.downcast_ref::<StreamsDisabled>()` and
+/// offer to turn streams on with `DynamoDb::enable_stream`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StreamsDisabled {
+    pub table_name: String,
+}
+
+impl fmt::Display for StreamsDisabled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Streams are not enabled on table '{}'", self.table_name)
+    }
+}
+
+impl std::error::Error for StreamsDisabled {}
+
+/// The kind of change a stream record describes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StreamEventType {
+    Insert,
+    Modify,
+    Remove,
+}
+
+impl StreamEventType {
+    /// Parses the `eventName` of a stream record.
+    pub fn parse(event_name: &str) -> Result<Self> {
+        match event_name {
+            "INSERT" => Ok(Self::Insert),
+            "MODIFY" => Ok(Self::Modify),
+            "REMOVE" => Ok(Self::Remove),
+            _ => Err(anyhow!("Unknown stream event type '{event_name}'")),
+        }
+    }
+}
+
+impl fmt::Display for StreamEventType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Insert => "INSERT",
+            Self::Modify => "MODIFY",
+            Self::Remove => "REMOVE",
+        })
+    }
+}
+
+/// A single change read from a table's stream.
+///
+/// Which images are present depends on the stream's view type: `KEYS_ONLY`
+/// streams carry neither, and an `INSERT` never has an old image nor a
+/// `REMOVE` a new one.
+#[derive(Debug, Clone)]
+pub struct StreamRecord {
+    pub event_type: StreamEventType,
+    pub keys: Item,
+    pub new_image: Option<Item>,
+    pub old_image: Option<Item>,
+    /// Orders records within a shard.
+    pub sequence_number: String,
+}
+
+impl TryFrom<Record> for StreamRecord {
+    type Error = anyhow::Error;
+
+    fn try_from(record: Record) -> Result<Self> {
+        let event_name = record
+            .event_name
+            .context("Stream record has no event name")?;
+        let change = record.dynamodb.context("Stream record has no change")?;
+        Ok(Self {
+            event_type: StreamEventType::parse(event_name.as_str())?,
+            keys: stream_item(change.keys.unwrap_or_default())?,
+            new_image: change.new_image.map(stream_item).transpose()?,
+            old_image: change.old_image.map(stream_item).transpose()?,
+            sequence_number: change
+                .sequence_number
+                .context("Stream record has no sequence number")?,
+        })
+    }
+}
+
+/// Converts an image from a stream record, whose attribute values are the
+/// Streams SDK's own copy of the DynamoDB type.
+fn stream_item(image: HashMap<String, StreamAttributeValue>) -> Result<Item> {
+    let attributes = image
+        .into_iter()
+        .map(|(name, value)| Ok((name, stream_attribute_value(value)?)))
+        .collect::<Result<_>>()?;
+    Ok(Item { attributes })
+}
+
+fn stream_attribute_value(value: StreamAttributeValue) -> Result<AttributeValue> {
+    Ok(match value {
+        StreamAttributeValue::S(s) => AttributeValue::S(s),
+        StreamAttributeValue::N(n) => AttributeValue::N(n),
+        StreamAttributeValue::B(b) => AttributeValue::B(b),
+        StreamAttributeValue::Ss(ss) => AttributeValue::Ss(ss),
+        StreamAttributeValue::Ns(ns) => AttributeValue::Ns(ns),
+        StreamAttributeValue::Bs(bs) => AttributeValue::Bs(bs),
+        StreamAttributeValue::Bool(b) => AttributeValue::Bool(b),
+        StreamAttributeValue::Null(null) => AttributeValue::Null(null),
+        StreamAttributeValue::M(map) => AttributeValue::M(stream_item(map)?.attributes),
+        StreamAttributeValue::L(list) => AttributeValue::L(
+            list.into_iter()
+                .map(stream_attribute_value)
+                .collect::<Result<_>>()?,
+        ),
+        value => bail!("Unsupported attribute value in stream record: {value:?}"),
+    })
+}
+
+/// Reads the changes made to a table from its stream.
+///
+/// Made by [`DynamoDb::stream_reader`](crate::dynamodb::DynamoDb::stream_reader),
+/// it starts at the end of every open shard, so it sees the changes made
+/// after it was created. Each [`StreamReader::poll_records`] returns the
+/// records that arrived since the last one, from every shard.
+///
+/// A shard's records come back in order, and the shards that split off a
+/// shard are only read once it has been read to its end, so the changes to
+/// any one item are seen in the order they were made. Changes to different
+/// items may be interleaved.
+///
+/// Shard iterators expire after 15 minutes. An expired one is acquired
+/// again just after the last record read from its shard, so nothing is
+/// skipped while the records are retained (24 hours); if no record was
+/// read from the shard yet, reading resumes at its newest record.
+///
+/// If reading one shard fails, the records already read from the others in
+/// that poll aren't lost: the next poll returns them first.
+#[derive(Debug)]
+pub struct StreamReader {
+    client: StreamsClient,
+    stream_arn: String,
+    /// The shards still being read, parents before their children.
+    shards: Vec<ShardCursor>,
+    /// Shards read to their end, or closed before the reader started.
+    finished: HashSet<String>,
+    /// Records read by a poll that then failed, returned by the next one.
+    pending: Vec<(String, Vec<StreamRecord>)>,
+    /// When the shards were last listed; `None` if they must be listed
+    /// again before the next read.
+    refreshed_at: Option<Instant>,
+}
+
+/// How far a [`StreamReader`] has read a shard.
+#[derive(Debug)]
+struct ShardCursor {
+    shard_id: String,
+    parent_shard_id: Option<String>,
+    /// Where the next records are read from; `None` until one is first
+    /// needed, or after it expired.
+    iterator: Option<String>,
+    /// Where a new iterator starts: just after the last record read, or
+    /// where reading the shard began if none was.
+    resume_at: ShardStart,
+}
+
+/// Where to start reading a shard.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum ShardStart {
+    /// After the newest record, so only later changes are read.
+    Latest,
+    /// At the oldest record still retained.
+    TrimHorizon,
+    /// Just after the record with this sequence number.
+    After(String),
+}
+
+impl StreamReader {
+    /// Lists the stream's shards and starts reading each just after its
+    /// checkpoint in `checkpoints`, or, for open shards without one, at
+    /// their newest record. Closed shards without a checkpoint are skipped.
+    pub(crate) async fn open(
+        client: StreamsClient,
+        stream_arn: String,
+        checkpoints: &dyn CheckpointStore,
+    ) -> Result<Self> {
+        let mut shards = Vec::new();
+        let mut finished = HashSet::new();
+        for shard in describe_stream(&client, &stream_arn).await? {
+            let shard_id = shard.shard_id.clone().unwrap_or_default();
+            let resume_at = match checkpoints.load(&shard_id)? {
+                Some(sequence_number) => ShardStart::After(sequence_number),
+                None if is_closed(&shard) => {
+                    finished.insert(shard_id);
+                    continue;
+                }
+                None => ShardStart::Latest,
+            };
+            let mut cursor = ShardCursor {
+                shard_id,
+                parent_shard_id: shard.parent_shard_id,
+                iterator: None,
+                resume_at,
+            };
+            // Acquired now rather than at the first poll, so changes made
+            // in between aren't missed.
+            cursor.iterator = Some(acquire_iterator(&client, &stream_arn, &mut cursor).await?);
+            shards.push(cursor);
+        }
+        Ok(Self {
+            client,
+            stream_arn,
+            shards,
+            finished,
+            pending: Vec::new(),
+            refreshed_at: Some(Instant::now()),
+        })
+    }
+
+    /// The ARN of the stream being read.
+    pub fn stream_arn(&self) -> &str {
+        &self.stream_arn
+    }
+
+    /// Reads the records written to every shard since the last call, or
+    /// since the reader was created.
+    ///
+    /// Returns an empty list when nothing changed; callers polling in a
+    /// loop should wait a moment between calls. If a shard can't be read,
+    /// the error is returned, and the records read from the other shards
+    /// come back with the next call.
+    pub async fn poll_records(&mut self) -> Result<Vec<StreamRecord>> {
+        Ok(self
+            .poll_shards()
+            .await?
+            .into_iter()
+            .flat_map(|(_, records)| records)
+            .collect())
+    }
+
+    /// Like [`StreamReader::poll_records`], but keeps the records grouped
+    /// by the shard they came from, for checkpointing.
+    pub(crate) async fn poll_shards(&mut self) -> Result<Vec<(String, Vec<StreamRecord>)>> {
+        let mut shards = std::mem::take(&mut self.pending);
+        if let Err(e) = self.read_ready_shards(&mut shards).await {
+            // The cursors have moved past these records, so keep them.
+            self.pending = shards;
+            return Err(e);
+        }
+        Ok(shards)
+    }
+
+    /// Takes the records kept by a poll that failed, which the next poll
+    /// would have returned.
+    pub(crate) fn take_pending(&mut self) -> Vec<(String, Vec<StreamRecord>)> {
+        std::mem::take(&mut self.pending)
+    }
+
+    /// Reads a page from every shard whose parent is done into `shards`,
+    /// listing the shards again first if it's been a while.
+    async fn read_ready_shards(
+        &mut self,
+        shards: &mut Vec<(String, Vec<StreamRecord>)>,
+    ) -> Result<()> {
+        if self
+            .refreshed_at
+            .is_none_or(|at| at.elapsed() >= SHARD_REFRESH_INTERVAL)
+        {
+            self.refresh_shards().await?;
+        }
+
+        let mut index = 0;
+        while index < self.shards.len() {
+            if !self.is_ready(&self.shards[index]) {
+                index += 1;
+                continue;
+            }
+            let mut records = Vec::new();
+            let ended = self.read_shard(index, &mut records).await?;
+            if !records.is_empty() {
+                shards.push((self.shards[index].shard_id.clone(), records));
+            }
+            if ended {
+                let shard = self.shards.remove(index);
+                self.finished.insert(shard.shard_id);
+                // Its children are listed by now, and are read in this
+                // same poll, or the next one if listing them fails.
+                self.refreshed_at = None;
+                self.refresh_shards().await?;
+            } else {
+                index += 1;
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether `shard`'s parent, if it had one, has been read to its end.
+    fn is_ready(&self, shard: &ShardCursor) -> bool {
+        shard
+            .parent_shard_id
+            .as_ref()
+            .is_none_or(|parent| !self.shards.iter().any(|s| &s.shard_id == parent))
+    }
+
+    /// Reads the next page of the shard at `index` into `records`. Returns
+    /// whether the shard has been read to its end.
+    async fn read_shard(&mut self, index: usize, records: &mut Vec<StreamRecord>) -> Result<bool> {
+        let (client, stream_arn) = (&self.client, &self.stream_arn);
+        let cursor = &mut self.shards[index];
+
+        let iterator = match cursor.iterator.take() {
+            Some(iterator) => iterator,
+            None => acquire_iterator(client, stream_arn, cursor).await?,
+        };
+        let page = match client.get_records().shard_iterator(&iterator).send().await {
+            Ok(page) => page,
+            Err(e)
+                if e.as_service_error()
+                    .is_some_and(|e| e.is_expired_iterator_exception()) =>
+            {
+                let iterator = acquire_iterator(client, stream_arn, cursor).await?;
+                client
+                    .get_records()
+                    .shard_iterator(iterator)
+                    .send()
+                    .await
+                    .map_err(request_failed(None, None))?
+            }
+            Err(e) => {
+                cursor.iterator = Some(iterator);
+                return Err(request_failed(None, None)(e));
+            }
+        };
+
+        let page_records = page
+            .records
+            .unwrap_or_default()
+            .into_iter()
+            .map(StreamRecord::try_from)
+            .collect::<Result<Vec<_>>>()?;
+        if let Some(last) = page_records.last() {
+            cursor.resume_at = ShardStart::After(last.sequence_number.clone());
+        }
+        records.extend(page_records);
+        cursor.iterator = page.next_shard_iterator;
+        Ok(cursor.iterator.is_none())
+    }
+
+    /// Adds the shards that split off since the stream was last listed.
+    /// They're new, so they're read from their oldest record.
+    async fn refresh_shards(&mut self) -> Result<()> {
+        for shard in describe_stream(&self.client, &self.stream_arn).await? {
+            let shard_id = shard.shard_id.unwrap_or_default();
+            let known = self.finished.contains(&shard_id)
+                || self.shards.iter().any(|s| s.shard_id == shard_id);
+            if !known {
+                self.shards.push(ShardCursor {
+                    shard_id,
+                    parent_shard_id: shard.parent_shard_id,
+                    iterator: None,
+                    resume_at: ShardStart::TrimHorizon,
+                });
+            }
+        }
+        self.refreshed_at = Some(Instant::now());
+        Ok(())
+    }
+}
+
+/// Lists every shard of the stream, parents before their children.
+async fn describe_stream(client: &StreamsClient, stream_arn: &str) -> Result<Vec<Shard>> {
+    let mut shards = Vec::new();
+    let mut start_shard_id = None;
+    loop {
+        let description = client
+            .describe_stream()
+            .stream_arn(stream_arn)
+            .set_exclusive_start_shard_id(start_shard_id)
+            .send()
+            .await
+            .map_err(request_failed(None, None))?
+            .stream_description
+            .context("DescribeStream returned no stream description")?;
+        shards.extend(description.shards.unwrap_or_default());
+        match description.last_evaluated_shard_id {
+            Some(shard_id) => start_shard_id = Some(shard_id),
+            None => return Ok(shards),
+        }
+    }
+}
+
+/// Whether `shard` has stopped taking new records; it's read to its end
+/// and then replaced by its children.
+fn is_closed(shard: &Shard) -> bool {
+    shard
+        .sequence_number_range
+        .as_ref()
+        .is_some_and(|range| range.ending_sequence_number.is_some())
+}
+
+/// Acquires an iterator where `cursor` resumes. If the records after the
+/// last one read have been trimmed from the stream, reading resumes at the
+/// oldest record still retained.
+async fn acquire_iterator(
+    client: &StreamsClient,
+    stream_arn: &str,
+    cursor: &mut ShardCursor,
+) -> Result<String> {
+    let request = |start: &ShardStart| {
+        let (iterator_type, sequence_number) = match start {
+            ShardStart::Latest => (ShardIteratorType::Latest, None),
+            ShardStart::TrimHorizon => (ShardIteratorType::TrimHorizon, None),
+            ShardStart::After(sequence_number) => (
+                ShardIteratorType::AfterSequenceNumber,
+                Some(sequence_number.clone()),
+            ),
+        };
+        client
+            .get_shard_iterator()
+            .stream_arn(stream_arn)
+            .shard_id(&cursor.shard_id)
+            .shard_iterator_type(iterator_type)
+            .set_sequence_number(sequence_number)
+            .send()
+    };
+    let output = match request(&cursor.resume_at).await {
+        Err(e)
+            if e.as_service_error()
+                .is_some_and(|e| e.is_trimmed_data_access_exception()) =>
+        {
+            warn!(
+                shard_id = %cursor.shard_id,
+                "Records after the last one read were trimmed; resuming at the oldest retained record"
+            );
+            let output = request(&ShardStart::TrimHorizon)
+                .await
+                .map_err(request_failed(None, None))?;
+            cursor.resume_at = ShardStart::TrimHorizon;
+            output
+        }
+        result => result.map_err(request_failed(None, None))?,
+    };
+    output
+        .shard_iterator
+        .ok_or_else(|| anyhow!("Shard '{}' has no records left to read", cursor.shard_id))
+}
+
+/// Remembers how far a change subscriber (see
+/// [`DynamoDb::subscribe_changes`](crate::dynamodb::DynamoDb::subscribe_changes))
+/// has read in each shard.
+///
+/// Change delivery is at-least-once: a subscriber saves a shard's sequence
+/// number only after its handler has returned for that record, so a crash
+/// between the two replays the record on restart. Handlers should be
+/// idempotent.
+///
+/// Implement this to persist checkpoints (to a file, another table, ...);
+/// [`InMemoryCheckpoints`] keeps them for the life of the process only.
+pub trait CheckpointStore: Send + Sync {
+    /// Returns the last sequence number processed in `shard_id`, if any.
+    fn load(&self, shard_id: &str) -> Result<Option<String>>;
+
+    /// Records `sequence_number` as processed in `shard_id`.
+    fn save(&self, shard_id: &str, sequence_number: &str) -> Result<()>;
+}
+
+/// A [`CheckpointStore`] that keeps checkpoints in memory.
+#[derive(Debug, Default)]
+pub struct InMemoryCheckpoints {
+    sequence_numbers: Mutex<HashMap<String, String>>,
+}
+
+impl CheckpointStore for InMemoryCheckpoints {
+    fn load(&self, shard_id: &str) -> Result<Option<String>> {
+        Ok(self
+            .sequence_numbers
+            .lock()
+            .map_err(|_| anyhow!("Checkpoint store lock poisoned"))?
+            .get(shard_id)
+            .cloned())
+    }
+
+    fn save(&self, shard_id: &str, sequence_number: &str) -> Result<()> {
+        self.sequence_numbers
+            .lock()
+            .map_err(|_| anyhow!("Checkpoint store lock poisoned"))?
+            .insert(shard_id.to_string(), sequence_number.to_string());
+        Ok(())
+    }
+}
+
+/// Polls `reader` until `cancel` fires, passing each record to `handler`
+/// and then checkpointing it. A failed poll ends the subscription with
+/// its error, once the records it read from other shards are handled.
+pub(crate) async fn run_subscription(
+    mut reader: StreamReader,
+    checkpoints: Arc<dyn CheckpointStore>,
+    cancel: CancellationToken,
+    handler: impl Fn(StreamRecord),
+) -> Result<()> {
+    loop {
+        let polled = tokio::select! {
+            _ = cancel.cancelled() => break,
+            polled = reader.poll_shards() => polled,
+        };
+        let shards = match polled {
+            Ok(shards) => shards,
+            Err(e) => {
+                deliver(reader.take_pending(), checkpoints.as_ref(), &handler)?;
+                return Err(e);
+            }
+        };
+
+        if shards.is_empty() {
+            tokio::select! {
+                _ = cancel.cancelled() => break,
+                _ = tokio::time::sleep(SUBSCRIBER_POLL_INTERVAL) => continue,
+            }
+        }
+        deliver(shards, checkpoints.as_ref(), &handler)?;
+    }
+    info!(stream_arn = %reader.stream_arn(), "Change subscription cancelled");
+    Ok(())
+}
+
+/// Passes each record to `handler`, saving its shard's checkpoint once the
+/// handler has returned.
+fn deliver(
+    shards: Vec<(String, Vec<StreamRecord>)>,
+    checkpoints: &dyn CheckpointStore,
+    handler: &impl Fn(StreamRecord),
+) -> Result<()> {
+    for (shard_id, records) in shards {
+        for record in records {
+            let sequence_number = record.sequence_number.clone();
+            handler(record);
+            checkpoints.save(&shard_id, &sequence_number)?;
+        }
+    }
+    Ok(())
+}
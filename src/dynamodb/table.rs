@@ -1,4 +1,8 @@
-use crate::dynamodb::Schema;
+use anyhow::{anyhow, Result};
+use aws_sdk_dynamodb::types::AttributeValue;
+use std::borrow::Cow;
+
+use crate::dynamodb::{FieldType, Item, Schema};
 
 /// DynamoDB table configuration.
 ///
@@ -28,7 +32,7 @@ use crate::dynamodb::Schema;
 /// # Example
 ///
 /// ```
-/// use dynamodb::{Table, Schema, FieldType};
+/// use aws_dynamodb_testing::dynamodb::{FieldType, Schema, Table};
 ///
 /// let schema = Schema::new()
 ///     .add_field("user_id", FieldType::String)
@@ -40,10 +44,65 @@ use crate::dynamodb::Schema;
 /// ```
 #[derive(Debug)]
 pub struct Table<'a> {
+    name: Cow<'a, str>,
+    partition_key: Cow<'a, str>,
+    sort_key: Option<Cow<'a, str>>,
+    schema: Option<Schema>,
+    capacity: Capacity,
+    global_secondary_indexes: Vec<GlobalSecondaryIndex<'a>>,
+}
+
+/// How a table's read and write throughput is billed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Capacity {
+    /// Pay per request; DynamoDB scales with the workload.
+    #[default]
+    OnDemand,
+    /// A fixed number of read and write capacity units per second, shared by
+    /// the table and each of its global secondary indexes.
+    Provisioned {
+        read_capacity_units: i64,
+        write_capacity_units: i64,
+    },
+}
+
+/// A global secondary index declared on a [`Table`].
+///
+/// Like the table's own keys, index key attributes are created as strings
+/// unless the table schema declares them as numbers. All attributes are
+/// projected into the index.
+#[derive(Debug, Clone, Copy)]
+pub struct GlobalSecondaryIndex<'a> {
     name: &'a str,
     partition_key: &'a str,
     sort_key: Option<&'a str>,
-    schema: Option<Schema>,
+}
+
+impl<'a> GlobalSecondaryIndex<'a> {
+    /// Creates an index definition, for declaring on a [`Table`] or adding to
+    /// an existing table with `DynamoDb::create_gsi`.
+    pub fn new(name: &'a str, partition_key: &'a str, sort_key: Option<&'a str>) -> Self {
+        Self {
+            name,
+            partition_key,
+            sort_key,
+        }
+    }
+
+    /// Returns the name of the index.
+    pub fn name(&self) -> &str {
+        self.name
+    }
+
+    /// Returns the partition key of the index.
+    pub fn partition_key(&self) -> &str {
+        self.partition_key
+    }
+
+    /// Returns the sort key of the index, if any.
+    pub fn sort_key(&self) -> Option<&str> {
+        self.sort_key
+    }
 }
 
 impl<'a> Table<'a> {
@@ -60,26 +119,46 @@ impl<'a> Table<'a> {
     /// A new `Table` instance with the specified configuration.
     pub fn new(name: &'a str, partition_key: &'a str, sort_key: Option<&'a str>) -> Self {
         Self {
-            name,
-            partition_key,
-            sort_key,
+            name: Cow::Borrowed(name),
+            partition_key: Cow::Borrowed(partition_key),
+            sort_key: sort_key.map(Cow::Borrowed),
             schema: None,
+            capacity: Capacity::OnDemand,
+            global_secondary_indexes: Vec::new(),
+        }
+    }
+
+    /// Creates a `Table` that owns its names, for tables only known at
+    /// runtime (e.g. entered in the CLI).
+    pub fn owned(name: String, partition_key: String, sort_key: Option<String>) -> Table<'static> {
+        Table {
+            name: Cow::Owned(name),
+            partition_key: Cow::Owned(partition_key),
+            sort_key: sort_key.map(Cow::Owned),
+            schema: None,
+            capacity: Capacity::OnDemand,
+            global_secondary_indexes: Vec::new(),
         }
     }
 
     /// Returns the name of the table.
     pub fn name(&self) -> &str {
-        self.name
+        &self.name
     }
 
     /// Returns the partition key of the table.
     pub fn partition_key(&self) -> &str {
-        self.partition_key
+        &self.partition_key
     }
 
     /// Returns the sort key of the table, if any.
     pub fn sort_key(&self) -> Option<&str> {
-        self.sort_key
+        self.sort_key.as_deref()
+    }
+
+    /// Returns `true` if the attribute is the partition key or the sort key.
+    pub fn is_key_attribute(&self, attribute: &str) -> bool {
+        attribute == self.partition_key() || self.sort_key() == Some(attribute)
     }
 
     /// Sets the schema for the table and returns the modified `Table`.
@@ -100,4 +179,99 @@ impl<'a> Table<'a> {
     pub fn schema(&self) -> Option<&Schema> {
         self.schema.as_ref()
     }
+
+    /// Returns the type declared for `attribute` in the schema, or
+    /// `FieldType::String` for attributes it doesn't declare.
+    pub fn field_type(&self, attribute: &str) -> FieldType {
+        self.schema
+            .as_ref()
+            .and_then(|schema| schema.fields().get(attribute))
+            .cloned()
+            .unwrap_or(FieldType::String)
+    }
+
+    /// Sets how the table is billed when `DynamoDb::create_table_if_not_exists`
+    /// creates it. Tables are on-demand by default.
+    pub fn with_capacity(mut self, capacity: Capacity) -> Self {
+        self.capacity = capacity;
+        self
+    }
+
+    /// Returns how the table is billed.
+    pub fn capacity(&self) -> Capacity {
+        self.capacity
+    }
+
+    /// Declares a global secondary index and returns the modified `Table`.
+    ///
+    /// Declared indexes are created along with the table by
+    /// `DynamoDb::create_table_if_not_exists`.
+    pub fn with_global_secondary_index(
+        mut self,
+        name: &'a str,
+        partition_key: &'a str,
+        sort_key: Option<&'a str>,
+    ) -> Self {
+        self.global_secondary_indexes
+            .push(GlobalSecondaryIndex::new(name, partition_key, sort_key));
+        self
+    }
+
+    /// Returns the global secondary indexes declared on the table.
+    pub fn global_secondary_indexes(&self) -> &[GlobalSecondaryIndex<'a>] {
+        &self.global_secondary_indexes
+    }
+
+    /// Checks that an item can be written to the table: it must have the key
+    /// attributes, and attributes declared in the schema must have the
+    /// declared type. Attributes outside the schema are allowed.
+    pub fn validate_item(&self, item: &Item) -> Result<()> {
+        for key in std::iter::once(self.partition_key()).chain(self.sort_key()) {
+            if !item.attributes.contains_key(key) {
+                return Err(anyhow!("Missing key attribute '{key}'"));
+            }
+        }
+
+        self.check_field_types(item)
+    }
+
+    /// Checks that an item of changed attributes can be applied to the
+    /// table: keys can't be updated, and attributes declared in the schema
+    /// must have the declared type.
+    pub fn validate_updates(&self, updates: &Item) -> Result<()> {
+        if let Some(key) = updates
+            .attributes
+            .keys()
+            .find(|name| self.is_key_attribute(name))
+        {
+            return Err(anyhow!("Key attribute '{key}' can't be updated"));
+        }
+        self.check_field_types(updates)
+    }
+
+    fn check_field_types(&self, item: &Item) -> Result<()> {
+        let Some(schema) = &self.schema else {
+            return Ok(());
+        };
+        for (name, field_type) in schema.fields() {
+            let matches = matches!(
+                (item.attributes.get(name), field_type),
+                (None, _)
+                    | (Some(AttributeValue::S(_)), FieldType::String)
+                    | (Some(AttributeValue::N(_)), FieldType::Number)
+                    | (Some(AttributeValue::Bool(_)), FieldType::Boolean)
+                    | (Some(AttributeValue::Ss(_)), FieldType::StringSet)
+                    | (Some(AttributeValue::Ns(_)), FieldType::NumberSet)
+                    | (
+                        Some(AttributeValue::L(_)),
+                        FieldType::StringList | FieldType::List
+                    )
+                    | (Some(AttributeValue::M(_)), FieldType::Map)
+            );
+            if !matches {
+                return Err(anyhow!("Attribute '{name}' must be a {field_type:?}"));
+            }
+        }
+        Ok(())
+    }
 }
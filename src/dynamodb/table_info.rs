@@ -0,0 +1,217 @@
+use anyhow::{anyhow, Result};
+use aws_sdk_dynamodb::types::{
+    BillingMode, GlobalSecondaryIndexDescription, IndexStatus, KeySchemaElement, KeyType,
+    LocalSecondaryIndexDescription, Projection, ProjectionType, ProvisionedThroughputDescription,
+    TableDescription, TableStatus,
+};
+use std::fmt;
+use std::time::SystemTime;
+
+use crate::dynamodb::Capacity;
+
+/// A table's live description, as reported by `DescribeTable`.
+///
+/// Unlike the SDK's `TableDescription`, every field that DynamoDB always
+/// returns is a plain value; only genuinely optional settings (the sort key,
+/// the stream) are `Option`s.
+///
+/// `item_count` and `size_bytes` are maintained by DynamoDB and refreshed
+/// roughly every six hours, so they lag recent writes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TableInfo {
+    pub name: String,
+    pub status: TableStatus,
+    pub partition_key: String,
+    pub sort_key: Option<String>,
+    pub item_count: i64,
+    pub size_bytes: i64,
+    pub billing_mode: BillingMode,
+    pub global_secondary_indexes: Vec<IndexInfo>,
+    pub local_secondary_indexes: Vec<LocalIndexInfo>,
+    pub stream_arn: Option<String>,
+    pub created_at: SystemTime,
+}
+
+/// Returned when a request names a table that doesn't exist.
+///
+/// Callers can detect it with `error.downcast_ref::<TableNotFound>()`
+/// rather than matching on messages. When it comes from DynamoDB, the SDK
+/// error is kept as its source.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TableNotFound {
+    pub table_name: String,
+}
+
+impl fmt::Display for TableNotFound {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Table '{}' does not exist", self.table_name)
+    }
+}
+
+impl std::error::Error for TableNotFound {}
+
+/// A global secondary index as reported by `DescribeTable`.
+///
+/// `capacity` is the index's own throughput; indexes on on-demand tables
+/// report none and show as `Capacity::OnDemand`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexInfo {
+    pub name: String,
+    pub status: IndexStatus,
+    pub partition_key: String,
+    pub sort_key: Option<String>,
+    pub projection_type: ProjectionType,
+    pub capacity: Capacity,
+    pub item_count: i64,
+    pub size_bytes: i64,
+}
+
+/// A local secondary index as reported by `DescribeTable`.
+///
+/// Local indexes share the table's partition key and throughput, and have
+/// no status of their own.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LocalIndexInfo {
+    pub name: String,
+    pub partition_key: String,
+    pub sort_key: Option<String>,
+    pub projection_type: ProjectionType,
+    pub item_count: i64,
+    pub size_bytes: i64,
+}
+
+impl TryFrom<TableDescription> for TableInfo {
+    type Error = anyhow::Error;
+
+    fn try_from(table: TableDescription) -> Result<Self> {
+        let name = table
+            .table_name
+            .ok_or_else(|| anyhow!("Table description has no name"))?;
+        let (partition_key, sort_key) = key_attributes(&name, table.key_schema.as_deref())?;
+        let created_at = table
+            .creation_date_time
+            .ok_or_else(|| anyhow!("Table '{name}' has no creation time"))?
+            .try_into()
+            .map_err(|e| anyhow!("Table '{name}' has an invalid creation time: {e}"))?;
+        let global_secondary_indexes = table
+            .global_secondary_indexes
+            .unwrap_or_default()
+            .into_iter()
+            .map(IndexInfo::try_from)
+            .collect::<Result<_>>()?;
+        let local_secondary_indexes = table
+            .local_secondary_indexes
+            .unwrap_or_default()
+            .into_iter()
+            .map(LocalIndexInfo::try_from)
+            .collect::<Result<_>>()?;
+
+        Ok(Self {
+            status: table
+                .table_status
+                .ok_or_else(|| anyhow!("Table '{name}' has no status"))?,
+            partition_key,
+            sort_key,
+            item_count: table.item_count.unwrap_or_default(),
+            size_bytes: table.table_size_bytes.unwrap_or_default(),
+            // DynamoDB omits the billing summary for tables that have only
+            // ever been provisioned.
+            billing_mode: table
+                .billing_mode_summary
+                .and_then(|summary| summary.billing_mode)
+                .unwrap_or(BillingMode::Provisioned),
+            global_secondary_indexes,
+            local_secondary_indexes,
+            stream_arn: table.latest_stream_arn,
+            created_at,
+            name,
+        })
+    }
+}
+
+impl TryFrom<GlobalSecondaryIndexDescription> for IndexInfo {
+    type Error = anyhow::Error;
+
+    fn try_from(index: GlobalSecondaryIndexDescription) -> Result<Self> {
+        let name = index
+            .index_name
+            .ok_or_else(|| anyhow!("Index description has no name"))?;
+        let (partition_key, sort_key) = key_attributes(&name, index.key_schema.as_deref())?;
+
+        Ok(Self {
+            status: index
+                .index_status
+                .ok_or_else(|| anyhow!("Index '{name}' has no status"))?,
+            partition_key,
+            sort_key,
+            projection_type: projection_type(&name, index.projection.as_ref())?,
+            capacity: index_capacity(index.provisioned_throughput.as_ref()),
+            item_count: index.item_count.unwrap_or_default(),
+            size_bytes: index.index_size_bytes.unwrap_or_default(),
+            name,
+        })
+    }
+}
+
+impl TryFrom<LocalSecondaryIndexDescription> for LocalIndexInfo {
+    type Error = anyhow::Error;
+
+    fn try_from(index: LocalSecondaryIndexDescription) -> Result<Self> {
+        let name = index
+            .index_name
+            .ok_or_else(|| anyhow!("Index description has no name"))?;
+        let (partition_key, sort_key) = key_attributes(&name, index.key_schema.as_deref())?;
+
+        Ok(Self {
+            partition_key,
+            sort_key,
+            projection_type: projection_type(&name, index.projection.as_ref())?,
+            item_count: index.item_count.unwrap_or_default(),
+            size_bytes: index.index_size_bytes.unwrap_or_default(),
+            name,
+        })
+    }
+}
+
+fn projection_type(index_name: &str, projection: Option<&Projection>) -> Result<ProjectionType> {
+    projection
+        .and_then(|projection| projection.projection_type())
+        .cloned()
+        .ok_or_else(|| anyhow!("Index '{index_name}' has no projection type"))
+}
+
+/// DynamoDB reports zero read and write units for indexes on on-demand
+/// tables.
+fn index_capacity(throughput: Option<&ProvisionedThroughputDescription>) -> Capacity {
+    let units = |units: Option<i64>| units.filter(|units| *units > 0);
+    match throughput.map(|throughput| {
+        (
+            units(throughput.read_capacity_units()),
+            units(throughput.write_capacity_units()),
+        )
+    }) {
+        Some((Some(read_capacity_units), Some(write_capacity_units))) => Capacity::Provisioned {
+            read_capacity_units,
+            write_capacity_units,
+        },
+        _ => Capacity::OnDemand,
+    }
+}
+
+/// Splits a key schema into its partition and optional sort key attributes.
+pub(crate) fn key_attributes(
+    owner: &str,
+    key_schema: Option<&[KeySchemaElement]>,
+) -> Result<(String, Option<String>)> {
+    let key_schema = key_schema.unwrap_or_default();
+    let attribute = |key_type: KeyType| {
+        key_schema
+            .iter()
+            .find(|element| element.key_type() == &key_type)
+            .map(|element| element.attribute_name().to_string())
+    };
+
+    let partition_key = attribute(KeyType::Hash)
+        .ok_or_else(|| anyhow!("'{owner}' has no partition key in its key schema"))?;
+    Ok((partition_key, attribute(KeyType::Range)))
+}
@@ -0,0 +1,127 @@
+use aws_sdk_dynamodb::types::AttributeValue;
+use std::collections::HashMap;
+
+use crate::dynamodb::Item;
+
+/// Builds a DynamoDB update expression from individual actions.
+///
+/// Each action gets its own `#attrN` / `:valN` placeholders, so plain sets,
+/// conditional defaults and removals can be freely combined in a single
+/// `UpdateItem` call.
+///
+/// # Example
+///
+/// ```
+/// use aws_dynamodb_testing::dynamodb::UpdateBuilder;
+/// use aws_dynamodb_testing::aws::AttributeValue;
+///
+/// let updates = UpdateBuilder::new()
+///     .set_if_not_exists("created_at", AttributeValue::S("2024-01-01".to_string()))
+///     .set("updated_at", AttributeValue::S("2024-06-01".to_string()))
+///     .remove("discount");
+/// ```
+#[derive(Default, Debug, Clone)]
+pub struct UpdateBuilder {
+    set_clauses: Vec<String>,
+    remove_clauses: Vec<String>,
+    expression_attribute_names: HashMap<String, String>,
+    expression_attribute_values: HashMap<String, AttributeValue>,
+}
+
+/// The rendered parts of an update, ready to hand to the SDK request builder.
+pub(crate) struct UpdateExpression {
+    pub(crate) expression: String,
+    pub(crate) names: HashMap<String, String>,
+    pub(crate) values: HashMap<String, AttributeValue>,
+}
+
+impl UpdateBuilder {
+    /// Creates a new empty `UpdateBuilder`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets an attribute to a value, overwriting any existing value.
+    pub fn set(mut self, attribute: impl Into<String>, value: AttributeValue) -> Self {
+        let (name, val) = self.placeholders(attribute.into(), value);
+        self.set_clauses.push(format!("{name} = {val}"));
+        self
+    }
+
+    /// Sets an attribute only if the item doesn't already have it.
+    ///
+    /// Renders as `SET #a = if_not_exists(#a, :v)`.
+    pub fn set_if_not_exists(
+        mut self,
+        attribute: impl Into<String>,
+        value: AttributeValue,
+    ) -> Self {
+        let (name, val) = self.placeholders(attribute.into(), value);
+        self.set_clauses
+            .push(format!("{name} = if_not_exists({name}, {val})"));
+        self
+    }
+
+    /// Removes an attribute from the item. Removing an attribute the item
+    /// doesn't have is not an error.
+    pub fn remove(mut self, attribute: impl Into<String>) -> Self {
+        let name = self.name_placeholder(attribute.into());
+        self.remove_clauses.push(name);
+        self
+    }
+
+    /// Registers the name and value placeholders for the next action.
+    fn placeholders(&mut self, attribute: String, value: AttributeValue) -> (String, String) {
+        let i = self.expression_attribute_names.len();
+        let val = format!(":val{i}");
+        self.expression_attribute_values.insert(val.clone(), value);
+        (self.name_placeholder(attribute), val)
+    }
+
+    fn name_placeholder(&mut self, attribute: String) -> String {
+        let name = format!("#attr{}", self.expression_attribute_names.len());
+        self.expression_attribute_names
+            .insert(name.clone(), attribute);
+        name
+    }
+
+    /// Returns `true` if no actions have been added.
+    pub fn is_empty(&self) -> bool {
+        self.set_clauses.is_empty() && self.remove_clauses.is_empty()
+    }
+
+    /// Renders the update expression along with its placeholder maps.
+    ///
+    /// Returns `None` when there is nothing to update, since DynamoDB rejects
+    /// an empty update expression.
+    pub(crate) fn build(self) -> Option<UpdateExpression> {
+        if self.is_empty() {
+            return None;
+        }
+
+        let mut clauses = Vec::new();
+        if !self.set_clauses.is_empty() {
+            clauses.push(format!("SET {}", self.set_clauses.join(", ")));
+        }
+        if !self.remove_clauses.is_empty() {
+            clauses.push(format!("REMOVE {}", self.remove_clauses.join(", ")));
+        }
+
+        Some(UpdateExpression {
+            expression: clauses.join(" "),
+            names: self.expression_attribute_names,
+            values: self.expression_attribute_values,
+        })
+    }
+}
+
+impl From<&Item> for UpdateBuilder {
+    fn from(updates: &Item) -> Self {
+        updates
+            .attributes
+            .iter()
+            .fold(Self::new(), |builder, (name, value)| {
+                builder.set(name, value.clone())
+            })
+    }
+}
@@ -0,0 +1,175 @@
+use aws_sdk_dynamodb::config::interceptors::{
+    AfterDeserializationInterceptorContextRef, BeforeTransmitInterceptorContextRef,
+};
+use aws_sdk_dynamodb::config::{ConfigBag, Intercept, RuntimeComponents};
+use aws_sdk_dynamodb::error::BoxError;
+use serde_json::Value;
+use tracing::debug;
+
+/// Turns wire logging on for the CLI when set to anything but `0`, `false`,
+/// `off` or an empty string.
+pub const WIRE_LOG_ENV: &str = "DDB_WIRE_LOG";
+/// Truncates logged attribute values to this many characters.
+pub const WIRE_LOG_MAX_VALUE_ENV: &str = "DDB_WIRE_LOG_MAX_VALUE";
+
+/// Headers whose values are credentials, logged as `REDACTED`.
+const REDACTED_HEADERS: &[&str] = &["authorization", "x-amz-security-token"];
+const REDACTED: &str = "REDACTED";
+
+/// Logs every HTTP request a client sends and every response it gets back,
+/// at DEBUG: the method, URI, `x-amz-target` operation, headers and JSON
+/// body of the request, and the status and body of the response.
+///
+/// The `Authorization` and `X-Amz-Security-Token` headers are always
+/// redacted. Attribute values (`S`, `N` and `B`, and the members of sets)
+/// can be cut short with [`WireLog::truncate_values`], so large items don't
+/// flood the log. Each retry is logged as a request of its own.
+///
+/// Attach one with [`DynamoDb::with_wire_log`](super::DynamoDb::with_wire_log).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WireLog {
+    max_value_len: Option<usize>,
+}
+
+impl WireLog {
+    /// Logs requests and responses with their values in full.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cuts attribute values longer than `max_len` characters down to
+    /// `max_len`, noting how long they were.
+    pub fn truncate_values(self, max_len: usize) -> Self {
+        Self {
+            max_value_len: Some(max_len),
+        }
+    }
+
+    /// The wire log asked for by [`WIRE_LOG_ENV`] and
+    /// [`WIRE_LOG_MAX_VALUE_ENV`], if any.
+    ///
+    /// A maximum that isn't a number is ignored.
+    pub fn from_env() -> Option<Self> {
+        Self::from_vars(|name| std::env::var(name).ok())
+    }
+
+    /// Like [`WireLog::from_env`], reading variables through `env`.
+    pub(crate) fn from_vars(env: impl Fn(&str) -> Option<String>) -> Option<Self> {
+        let enabled = env(WIRE_LOG_ENV)?;
+        if matches!(
+            enabled.trim().to_ascii_lowercase().as_str(),
+            "" | "0" | "false" | "off"
+        ) {
+            return None;
+        }
+        Some(Self {
+            max_value_len: env(WIRE_LOG_MAX_VALUE_ENV).and_then(|max| max.trim().parse().ok()),
+        })
+    }
+
+    /// `body` as it's logged: JSON with long attribute values truncated, or
+    /// the raw text if it isn't JSON.
+    fn format_body(&self, body: &[u8]) -> String {
+        if body.is_empty() {
+            return String::new();
+        }
+        match serde_json::from_slice::<Value>(body) {
+            Ok(mut json) => {
+                if let Some(max_len) = self.max_value_len {
+                    truncate_attribute_values(&mut json, max_len);
+                }
+                json.to_string()
+            }
+            Err(_) => String::from_utf8_lossy(body).into_owned(),
+        }
+    }
+}
+
+impl Intercept for WireLog {
+    fn name(&self) -> &'static str {
+        "WireLog"
+    }
+
+    fn read_after_signing(
+        &self,
+        context: &BeforeTransmitInterceptorContextRef<'_>,
+        _runtime_components: &RuntimeComponents,
+        _cfg: &mut ConfigBag,
+    ) -> Result<(), BoxError> {
+        let request = context.request();
+        let headers = request
+            .headers()
+            .iter()
+            .map(|(name, value)| {
+                let redact = REDACTED_HEADERS
+                    .iter()
+                    .any(|redacted| name.eq_ignore_ascii_case(redacted));
+                format!("{name}: {}", if redact { REDACTED } else { value })
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        debug!(
+            method = request.method(),
+            uri = request.uri(),
+            operation = request.headers().get("x-amz-target").unwrap_or_default(),
+            headers,
+            body = self.format_body(request.body().bytes().unwrap_or_default()),
+            "DynamoDB request"
+        );
+        Ok(())
+    }
+
+    fn read_after_deserialization(
+        &self,
+        context: &AfterDeserializationInterceptorContextRef<'_>,
+        _runtime_components: &RuntimeComponents,
+        _cfg: &mut ConfigBag,
+    ) -> Result<(), BoxError> {
+        let response = context.response();
+        debug!(
+            status = response.status().as_u16(),
+            body = self.format_body(response.body().bytes().unwrap_or_default()),
+            "DynamoDB response"
+        );
+        Ok(())
+    }
+}
+
+/// Shortens every attribute value in `json` longer than `max_len`
+/// characters, wherever it's nested: `{"S": ...}`, `{"N": ...}`,
+/// `{"B": ...}` and the members of `SS`, `NS` and `BS` sets.
+fn truncate_attribute_values(json: &mut Value, max_len: usize) {
+    match json {
+        Value::Object(object) => {
+            for (name, value) in object.iter_mut() {
+                match (name.as_str(), value) {
+                    ("S" | "N" | "B", Value::String(text)) => truncate(text, max_len),
+                    ("SS" | "NS" | "BS", Value::Array(members)) => {
+                        for member in members {
+                            if let Value::String(text) = member {
+                                truncate(text, max_len);
+                            }
+                        }
+                    }
+                    (_, value) => truncate_attribute_values(value, max_len),
+                }
+            }
+        }
+        Value::Array(values) => {
+            for value in values {
+                truncate_attribute_values(value, max_len);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn truncate(text: &mut String, max_len: usize) {
+    let len = text.chars().count();
+    if len > max_len {
+        *text = format!(
+            "{}... ({len} chars)",
+            text.chars().take(max_len).collect::<String>()
+        );
+    }
+}
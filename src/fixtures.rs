@@ -0,0 +1,156 @@
+//! Test data loaded from JSON files.
+//!
+//! Fixture files live in the crate's `fixtures/` directory, as a JSON array
+//! or JSON Lines of items in plain JSON (`{"id": "a", "price": 12.5}`).
+//! Relative paths passed to [`load_fixture`] and
+//! [`assert_table_matches_fixture`] are resolved against that directory.
+//!
+//! To add a fixture, write the items to a new file in `fixtures/`, one
+//! object per item with at least the table's key attributes, and load it
+//! against a [`Table`] whose schema describes them. Records that don't
+//! match the schema fail the load rather than being skipped.
+
+use anyhow::{anyhow, Context, Result};
+use aws_sdk_dynamodb::types::AttributeValue;
+use serde_json::{json, Map, Number, Value};
+use std::path::{Path, PathBuf};
+use tokio_util::sync::CancellationToken;
+
+use crate::dynamodb::{DynamoDbApi, Item, ScanParams, Table};
+
+/// Resolves a fixture path against the `fixtures/` directory.
+pub(crate) fn fixture_path(path: impl AsRef<Path>) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("fixtures")
+        .join(path)
+}
+
+/// Reads the items in a fixture file, checked against `table`'s schema.
+pub(crate) fn read_fixture(table: &Table<'_>, path: impl AsRef<Path>) -> Result<Vec<Item>> {
+    let path = fixture_path(path);
+    let contents = std::fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read fixture {}", path.display()))?;
+    let records: Vec<Value> = if contents.trim_start().starts_with('[') {
+        serde_json::from_str(&contents)
+    } else {
+        serde_json::Deserializer::from_str(&contents)
+            .into_iter()
+            .collect()
+    }
+    .with_context(|| format!("Failed to parse fixture {}", path.display()))?;
+    let mut items = Vec::with_capacity(records.len());
+    for (i, record) in records.iter().enumerate() {
+        let item = Item::from_json(record)
+            .and_then(|item| table.validate_item(&item).map(|_| item))
+            .map_err(|e| {
+                anyhow!(
+                    "Fixture {} record {} doesn't fit table '{}': {}",
+                    path.display(),
+                    i + 1,
+                    table.name(),
+                    e
+                )
+            })?;
+        items.push(item);
+    }
+    Ok(items)
+}
+
+/// Writes the items in a fixture file to `table`, returning how many were
+/// written.
+pub(crate) async fn load_fixture(
+    ddb: &impl DynamoDbApi,
+    table: &Table<'_>,
+    path: impl AsRef<Path>,
+) -> Result<usize> {
+    let items = read_fixture(table, path)?;
+    let written = ddb
+        .batch_put_items_cancellable(table.name(), items, None, &CancellationToken::new())
+        .await?;
+    Ok(written.value)
+}
+
+/// Asserts that `table` holds exactly the items in a fixture file.
+///
+/// Items are compared by value, so attribute order and how numbers are
+/// written (`100` vs `100.0`) don't matter, and neither does scan order.
+pub(crate) async fn assert_table_matches_fixture(
+    ddb: &impl DynamoDbApi,
+    table: &Table<'_>,
+    path: impl AsRef<Path>,
+) -> Result<()> {
+    let path = path.as_ref();
+    let expected = canonical_items(&read_fixture(table, path)?);
+    let actual = canonical_items(
+        &ddb.scan_all(
+            ScanParams {
+                table_name: table.name(),
+                consistent_read: Some(true),
+                ..Default::default()
+            },
+            None,
+            None,
+        )
+        .await?,
+    );
+
+    let missing: Vec<_> = expected.iter().filter(|i| !actual.contains(i)).collect();
+    let unexpected: Vec<_> = actual.iter().filter(|i| !expected.contains(i)).collect();
+    assert!(
+        missing.is_empty() && unexpected.is_empty(),
+        "Table '{}' doesn't match fixture {}\nmissing: {missing:#?}\nunexpected: {unexpected:#?}",
+        table.name(),
+        path.display(),
+    );
+    Ok(())
+}
+
+/// Renders items so equal values compare equal, sorted for stable output.
+fn canonical_items(items: &[Item]) -> Vec<Value> {
+    let mut canonical: Vec<Value> = items
+        .iter()
+        .map(|item| canonical_map(item.attributes.iter()))
+        .collect();
+    canonical.sort_by_key(Value::to_string);
+    canonical
+}
+
+fn canonical_map<'a>(attributes: impl Iterator<Item = (&'a String, &'a AttributeValue)>) -> Value {
+    Value::Object(
+        attributes
+            .map(|(name, value)| (name.clone(), canonical(value)))
+            .collect::<Map<_, _>>(),
+    )
+}
+
+/// Maps an attribute value onto JSON, with numbers parsed and sets sorted.
+/// Sets are tagged so they don't compare equal to lists.
+fn canonical(value: &AttributeValue) -> Value {
+    let number = |n: &String| {
+        n.parse::<f64>()
+            .ok()
+            .and_then(Number::from_f64)
+            .map_or_else(|| Value::String(n.clone()), Value::Number)
+    };
+    let set = |mut values: Vec<Value>| {
+        values.sort_by_key(Value::to_string);
+        json!({ "set": values })
+    };
+
+    match value {
+        AttributeValue::S(s) => Value::String(s.clone()),
+        AttributeValue::N(n) => number(n),
+        AttributeValue::Bool(b) => Value::Bool(*b),
+        AttributeValue::Null(_) => Value::Null,
+        AttributeValue::B(b) => json!({ "binary": b.as_ref() }),
+        AttributeValue::Ss(values) => set(values.iter().cloned().map(Value::String).collect()),
+        AttributeValue::Ns(values) => set(values.iter().map(number).collect()),
+        AttributeValue::Bs(values) => set(values
+            .iter()
+            .map(|b| json!({ "binary": b.as_ref() }))
+            .collect()),
+        AttributeValue::L(values) => Value::Array(values.iter().map(canonical).collect()),
+        AttributeValue::M(map) => canonical_map(map.iter()),
+        other => Value::String(format!("{other:?}")),
+    }
+}
@@ -0,0 +1,57 @@
+use std::sync::{Arc, Mutex};
+use tokio_util::sync::CancellationToken;
+
+/// The exit status for a process ended by Ctrl-C (128 + SIGINT).
+const INTERRUPTED_EXIT_CODE: i32 = 130;
+
+/// Turns Ctrl-C into cancellation of the command that's running.
+///
+/// At a prompt, Ctrl-C is read as a key and cancels the prompt (see
+/// [`PromptCancelled`](crate::line_reader::PromptCancelled)). While a
+/// command runs, the first Ctrl-C cancels the token handed out by
+/// [`Interrupt::start`], which the bulk operations check between pages; a
+/// second Ctrl-C before the command finishes exits.
+#[derive(Debug, Clone, Default)]
+pub struct Interrupt {
+    current: Arc<Mutex<CancellationToken>>,
+}
+
+impl Interrupt {
+    /// Installs a Ctrl-C handler for the rest of the process. Without one,
+    /// [`Interrupt::start`] hands out tokens that are never cancelled.
+    pub fn install() -> Self {
+        let interrupt = Self::default();
+        let handler = interrupt.clone();
+        tokio::spawn(async move {
+            while tokio::signal::ctrl_c().await.is_ok() {
+                handler.interrupt();
+            }
+        });
+        interrupt
+    }
+
+    /// Starts a command, returning the token the next Ctrl-C cancels.
+    pub fn start(&self) -> CancellationToken {
+        let token = CancellationToken::new();
+        *self.lock() = token.clone();
+        token
+    }
+
+    fn interrupt(&self) {
+        let token = self.lock().clone();
+        if token.is_cancelled() {
+            eprintln!();
+            std::process::exit(INTERRUPTED_EXIT_CODE);
+        }
+        token.cancel();
+        eprintln!("\nCancelling... press Ctrl-C again to exit.");
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, CancellationToken> {
+        // The token is only ever replaced whole, so a poisoned lock still
+        // holds a usable one.
+        self.current
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
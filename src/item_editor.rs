@@ -0,0 +1,75 @@
+use anyhow::{anyhow, Context, Result};
+use std::path::PathBuf;
+use std::process::Command;
+
+/// Lets the user edit a document, such as an item as JSON.
+///
+/// The CLI's `edit` command opens the user's editor through
+/// [`ExternalEditor`]; tests implement this with canned edits, or use a
+/// closure.
+pub trait ItemEditor {
+    /// Returns `contents` as the user saved it, or `None` if they aborted
+    /// the edit.
+    fn edit(&mut self, contents: &str) -> Result<Option<String>>;
+}
+
+impl<F: FnMut(&str) -> Result<Option<String>>> ItemEditor for F {
+    fn edit(&mut self, contents: &str) -> Result<Option<String>> {
+        self(contents)
+    }
+}
+
+/// Edits in the program named by `$VISUAL` or `$EDITOR`, falling back to
+/// `vi` (`notepad` on Windows).
+///
+/// The contents go through a temporary `.json` file that's deleted
+/// afterwards. Quitting the editor with a non-zero status, e.g. `:cq` in
+/// vi, aborts the edit.
+#[derive(Debug, Default)]
+pub struct ExternalEditor;
+
+impl ExternalEditor {
+    fn command() -> String {
+        std::env::var("VISUAL")
+            .or_else(|_| std::env::var("EDITOR"))
+            .ok()
+            .filter(|editor| !editor.trim().is_empty())
+            .unwrap_or_else(|| if cfg!(windows) { "notepad" } else { "vi" }.to_string())
+    }
+}
+
+impl ItemEditor for ExternalEditor {
+    fn edit(&mut self, contents: &str) -> Result<Option<String>> {
+        let path = std::env::temp_dir().join(format!("ddb-edit-{}.json", std::process::id()));
+        std::fs::write(&path, contents)
+            .with_context(|| format!("Failed to write '{}'", path.display()))?;
+        let _cleanup = RemoveOnDrop(path.clone());
+
+        // `$EDITOR` may carry arguments, e.g. `code --wait`.
+        let command = Self::command();
+        let mut words = command.split_whitespace();
+        let program = words
+            .next()
+            .ok_or_else(|| anyhow!("No editor configured"))?;
+        let status = Command::new(program)
+            .args(words)
+            .arg(&path)
+            .status()
+            .with_context(|| format!("Failed to start editor '{command}'"))?;
+        if !status.success() {
+            return Ok(None);
+        }
+
+        let edited = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read '{}'", path.display()))?;
+        Ok(Some(edited))
+    }
+}
+
+struct RemoveOnDrop(PathBuf);
+
+impl Drop for RemoveOnDrop {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
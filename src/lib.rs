@@ -0,0 +1,81 @@
+//! A high-level client for Amazon DynamoDB, and the interactive CLI built
+//! on it.
+//!
+//! The [`dynamodb`] module is the library proper: [`dynamodb::DynamoDb`]
+//! wraps the AWS SDK client, and [`dynamodb::Item`], [`dynamodb::Schema`]
+//! and [`dynamodb::Table`] describe what it stores. [`utils`] has the retry
+//! and timeout helpers and [`logging`] sets up `tracing` output. [`aws`]
+//! re-exports the SDK types the API uses, so callers needn't depend on
+//! `aws-sdk-dynamodb` themselves.
+//!
+//! The remaining modules make up the `ddb-simple` command-line tool, whose
+//! `main` only loads its configuration and hands over to
+//! [`command_line::run`].
+//!
+//! # Features
+//!
+//! All on by default:
+//!
+//! - `client`: the [`dynamodb`], [`aws`] and [`utils`] modules.
+//! - `serde`: conversions between items and your own `Serialize` and
+//!   `Deserialize` types.
+//! - `cli`: the command-line tool's modules and the `ddb-simple` binary.
+//! - `logging`: the [`logging`] module's `tracing-subscriber` setup.
+//!
+//! Off by default:
+//!
+//! - `derive`: `#[derive(DynamoEntity)]`, from the `ddb-simple-derive`
+//!   crate.
+//! - `blocking`: `blocking::BlockingDynamoDb`, a synchronous client.
+//! - `test-util`: recording and replaying DynamoDB traffic, and the
+//!   `testdata` module's generator of fake items.
+//! - `otel`: exporting spans over OTLP.
+//!
+//! A Lambda that only talks to DynamoDB can build with
+//! `default-features = false, features = ["client"]`.
+//!
+//! ```no_run
+//! use aws_dynamodb_testing::dynamodb::{DynamoDb, Item, LOCAL_ENDPOINT};
+//!
+//! # async fn example() -> anyhow::Result<()> {
+//! let ddb = DynamoDb::new_local(LOCAL_ENDPOINT);
+//! ddb.put_item("users", Item::new().set_string("user_id", "123"))
+//!     .await?;
+//! # Ok(())
+//! # }
+//! ```
+
+#[cfg(feature = "client")]
+pub mod aws;
+// Lets `#[derive(DynamoEntity)]` output, which names this crate, compile
+// inside it too.
+extern crate self as aws_dynamodb_testing;
+
+#[cfg(all(feature = "client", any(feature = "blocking", test)))]
+pub mod blocking;
+#[cfg(feature = "cli")]
+pub mod command_line;
+#[cfg(feature = "cli")]
+pub mod config;
+#[cfg(any(feature = "cli", test))]
+mod constants;
+#[cfg(feature = "client")]
+pub mod dynamodb;
+#[cfg(all(test, feature = "client"))]
+mod fixtures;
+#[cfg(feature = "cli")]
+pub mod interrupt;
+#[cfg(feature = "cli")]
+pub mod item_editor;
+#[cfg(feature = "cli")]
+pub mod line_reader;
+#[cfg(feature = "logging")]
+pub mod logging;
+#[cfg(all(test, feature = "client"))]
+mod test_support;
+#[cfg(all(feature = "client", any(feature = "test-util", test)))]
+pub mod testdata;
+#[cfg(all(test, feature = "client"))]
+mod tests;
+#[cfg(feature = "client")]
+pub mod utils;
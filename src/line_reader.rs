@@ -0,0 +1,233 @@
+use anyhow::Result;
+use rustyline::{
+    completion::Completer, error::ReadlineError, highlight::Highlighter, hint::Hinter,
+    history::DefaultHistory, validate::Validator, Context, Helper,
+};
+use std::fmt;
+use std::path::PathBuf;
+use tracing::warn;
+
+/// The file in the home directory that prompt history is kept in.
+const HISTORY_FILE: &str = ".ddb_history";
+
+/// Returned when the user presses Ctrl-C at a prompt.
+///
+/// The command loop catches it and goes back to the command prompt, so the
+/// command being entered is abandoned but the session carries on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PromptCancelled;
+
+impl fmt::Display for PromptCancelled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Prompt cancelled")
+    }
+}
+
+impl std::error::Error for PromptCancelled {}
+
+/// Returned when input ends, on Ctrl-D or at the end of piped input.
+///
+/// The command loop exits cleanly when it sees it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InputClosed;
+
+impl fmt::Display for InputClosed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Input closed")
+    }
+}
+
+impl std::error::Error for InputClosed {}
+
+/// A source of lines for the CLI's prompts.
+///
+/// The CLI reads from the terminal through [`Editor`]; tests implement this
+/// with scripted input.
+pub trait LineReader {
+    /// Shows `prompt` and reads one line, without the trailing newline.
+    ///
+    /// Fails with [`PromptCancelled`] or [`InputClosed`] when the user
+    /// cancels the prompt or input ends.
+    fn read_line(&mut self, prompt: &str) -> Result<String>;
+
+    /// Reads a line at the command prompt, where command names are
+    /// completed rather than field names.
+    fn read_command(&mut self, prompt: &str) -> Result<String> {
+        self.read_line(prompt)
+    }
+
+    /// Replaces the command names offered for completion.
+    fn set_commands(&mut self, _commands: &[&str]) {}
+
+    /// Replaces the table names offered after `use`.
+    fn set_table_names(&mut self, _table_names: Vec<String>) {}
+
+    /// Replaces the field names offered at value prompts, e.g. when the
+    /// active table changes.
+    fn set_field_names(&mut self, _field_names: Vec<String>) {}
+}
+
+/// Completes what's being typed at a prompt: command names at the start of
+/// the command prompt, table names after `use`, and the active table's
+/// field names anywhere else.
+#[derive(Debug, Clone, Default)]
+pub struct PromptCompleter {
+    commands: Vec<String>,
+    table_names: Vec<String>,
+    field_names: Vec<String>,
+    at_command_prompt: bool,
+}
+
+impl PromptCompleter {
+    pub fn set_commands(&mut self, commands: &[&str]) {
+        self.commands = commands.iter().map(|command| command.to_string()).collect();
+    }
+
+    pub fn set_table_names(&mut self, table_names: Vec<String>) {
+        self.table_names = table_names;
+    }
+
+    pub fn set_field_names(&mut self, field_names: Vec<String>) {
+        self.field_names = field_names;
+    }
+
+    /// Sets whether the line being edited is at the command prompt.
+    pub fn set_at_command_prompt(&mut self, at_command_prompt: bool) {
+        self.at_command_prompt = at_command_prompt;
+    }
+
+    /// Returns where the word before `pos` in `line` starts, and the sorted
+    /// candidates for completing it.
+    pub fn candidates(&self, line: &str, pos: usize) -> (usize, Vec<String>) {
+        let before = &line[..pos];
+        // Field names show up inside expressions, so punctuation ends a word too.
+        let start = before
+            .rfind(|c: char| c.is_whitespace() || ",()=<>:#".contains(c))
+            .map_or(0, |i| i + 1);
+        let word = &before[start..];
+
+        let names = if !self.at_command_prompt {
+            &self.field_names
+        } else if before[..start].trim().is_empty() {
+            &self.commands
+        } else if before.split_whitespace().next() == Some("use") {
+            &self.table_names
+        } else {
+            return (start, Vec::new());
+        };
+
+        let mut candidates: Vec<String> = names
+            .iter()
+            .filter(|name| name.starts_with(word))
+            .cloned()
+            .collect();
+        candidates.sort();
+        candidates.dedup();
+        (start, candidates)
+    }
+}
+
+impl Completer for PromptCompleter {
+    type Candidate = String;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<String>)> {
+        Ok(self.candidates(line, pos))
+    }
+}
+
+impl Hinter for PromptCompleter {
+    type Hint = String;
+}
+
+impl Highlighter for PromptCompleter {}
+
+impl Validator for PromptCompleter {}
+
+impl Helper for PromptCompleter {}
+
+/// Reads prompt input with line editing, up-arrow history and tab
+/// completion.
+///
+/// History is loaded from `~/.ddb_history` when the editor is created and
+/// saved back when it's dropped.
+pub struct Editor {
+    editor: rustyline::Editor<PromptCompleter, DefaultHistory>,
+    history_path: Option<PathBuf>,
+}
+
+impl Editor {
+    /// Creates an editor with the history from previous sessions.
+    pub fn new() -> Result<Self> {
+        let mut editor = rustyline::Editor::new()?;
+        editor.set_helper(Some(PromptCompleter::default()));
+        let history_path =
+            std::env::var_os("HOME").map(|home| PathBuf::from(home).join(HISTORY_FILE));
+        if let Some(path) = &history_path {
+            // There's no history file until the first session ends.
+            let _ = editor.load_history(path);
+        }
+        Ok(Self {
+            editor,
+            history_path,
+        })
+    }
+}
+
+impl LineReader for Editor {
+    fn read_line(&mut self, prompt: &str) -> Result<String> {
+        match self.editor.readline(prompt) {
+            Ok(line) => {
+                if !line.trim().is_empty() {
+                    self.editor.add_history_entry(line.as_str())?;
+                }
+                Ok(line)
+            }
+            Err(ReadlineError::Interrupted) => Err(PromptCancelled.into()),
+            Err(ReadlineError::Eof) => Err(InputClosed.into()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn read_command(&mut self, prompt: &str) -> Result<String> {
+        self.completer().set_at_command_prompt(true);
+        let line = self.read_line(prompt);
+        self.completer().set_at_command_prompt(false);
+        line
+    }
+
+    fn set_commands(&mut self, commands: &[&str]) {
+        self.completer().set_commands(commands);
+    }
+
+    fn set_table_names(&mut self, table_names: Vec<String>) {
+        self.completer().set_table_names(table_names);
+    }
+
+    fn set_field_names(&mut self, field_names: Vec<String>) {
+        self.completer().set_field_names(field_names);
+    }
+}
+
+impl Editor {
+    fn completer(&mut self) -> &mut PromptCompleter {
+        // Set in `Editor::new` and never removed.
+        self.editor
+            .helper_mut()
+            .expect("Editor is created with a completer")
+    }
+}
+
+impl Drop for Editor {
+    fn drop(&mut self) {
+        if let Some(path) = &self.history_path {
+            if let Err(e) = self.editor.save_history(path) {
+                warn!("Failed to save history to '{}': {}", path.display(), e);
+            }
+        }
+    }
+}
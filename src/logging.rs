@@ -1,21 +1,177 @@
 //! Initializes application logging using `tracing` and `tracing_subscriber`.
+//!
+//! By default the filter comes from `RUST_LOG`, in `EnvFilter` syntax, e.g.
+//! `RUST_LOG=debug,aws_sigv4=trace` to see how requests are signed, or
+//! `RUST_LOG=error` to keep scripts quiet. Without it, [`DEFAULT_FILTER`]
+//! applies.
+//!
+//! The DynamoDB client opens a span per call, named after the method and
+//! carrying its table, operation and result count, so log lines show which
+//! call they belong to. Verbose logging also reports each span's elapsed
+//! time when it closes.
+//!
+//! With `DDB_WIRE_LOG=1` (and `DDB_WIRE_LOG_MAX_VALUE` to shorten attribute
+//! values), each raw request and response is logged at DEBUG too, with
+//! credentials redacted.
+//!
+//! With the `otel` feature, `init_with_telemetry` also exports those spans
+//! over OTLP.
 
 use anyhow::Result;
-use tracing::Level;
-use tracing_subscriber::FmtSubscriber;
+use tracing::{Level, Subscriber};
+use tracing_subscriber::fmt::format::FmtSpan;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::{EnvFilter, Layer};
 
-/// Sets up global logging with INFO level, line numbers, and file names.
+/// The filter used when `RUST_LOG` isn't set: this crate at INFO, and the
+/// chattier AWS and HTTP crates only when something goes wrong.
+pub const DEFAULT_FILTER: &str = "info,aws_config=warn,hyper=warn";
+
+/// Sets up global logging filtered by `RUST_LOG`, or [`DEFAULT_FILTER`]
+/// if it isn't set, with line numbers and file names.
+///
+/// Does nothing if a global subscriber is already set.
 ///
 /// # Errors
 ///
-/// Returns an error if setting the global default subscriber fails.
+/// Returns an error if `RUST_LOG` isn't a valid filter.
 pub fn init() -> Result<()> {
-    let subscriber = FmtSubscriber::builder()
-        .with_max_level(Level::INFO)
+    init_with_filter(env_filter()?, FmtSpan::NONE)
+}
+
+/// Sets up global logging at `level` for every crate, ignoring `RUST_LOG`,
+/// with an event giving each span's elapsed time as it closes.
+///
+/// Does nothing if a global subscriber is already set.
+pub fn init_with_level(level: Level) -> Result<()> {
+    init_with_filter(EnvFilter::new(level.to_string()), FmtSpan::CLOSE)
+}
+
+/// Sets up logging for the CLI's `-v` count: none defers to [`init`], one
+/// logs at DEBUG and two or more at TRACE.
+pub fn init_with_verbosity(verbosity: u8) -> Result<()> {
+    let (filter, span_events) = verbosity_filter(verbosity)?;
+    init_with_filter(filter, span_events)
+}
+
+/// The filter and span events for a `-v` count.
+fn verbosity_filter(verbosity: u8) -> Result<(EnvFilter, FmtSpan)> {
+    Ok(match verbosity {
+        0 => (env_filter()?, FmtSpan::NONE),
+        1 => (EnvFilter::new(Level::DEBUG.to_string()), FmtSpan::CLOSE),
+        _ => (EnvFilter::new(Level::TRACE.to_string()), FmtSpan::CLOSE),
+    })
+}
+
+/// `RUST_LOG`, or [`DEFAULT_FILTER`] if it isn't set.
+fn env_filter() -> Result<EnvFilter> {
+    Ok(match std::env::var(EnvFilter::DEFAULT_ENV) {
+        Ok(directives) => EnvFilter::try_new(directives)?,
+        Err(_) => EnvFilter::new(DEFAULT_FILTER),
+    })
+}
+
+/// Formats events for the terminal, with file names and line numbers.
+fn fmt_layer<S>(span_events: FmtSpan) -> impl Layer<S>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    tracing_subscriber::fmt::layer()
+        .with_span_events(span_events)
         .with_line_number(true)
         .with_file(true)
         .with_target(false)
-        .finish();
-    tracing::subscriber::set_global_default(subscriber)?;
+}
+
+fn init_with_filter(filter: EnvFilter, span_events: FmtSpan) -> Result<()> {
+    let subscriber = tracing_subscriber::registry()
+        .with(filter)
+        .with(fmt_layer(span_events));
+    // Only fails if a subscriber is already set, which is fine: the first
+    // one stays in charge.
+    let _ = tracing::subscriber::set_global_default(subscriber);
     Ok(())
 }
+
+/// The service name spans are exported under, unless `OTEL_SERVICE_NAME`
+/// says otherwise.
+#[cfg(feature = "otel")]
+const SERVICE_NAME: &str = "ddb-simple";
+
+/// Sets up logging as [`init_with_verbosity`] does, and also exports every
+/// span the filter lets through to an OTLP collector.
+///
+/// The exporter is configured by the standard environment variables, e.g.
+/// `OTEL_EXPORTER_OTLP_ENDPOINT` (default `http://localhost:4318`),
+/// `OTEL_EXPORTER_OTLP_TRACES_ENDPOINT` and `OTEL_SERVICE_NAME`. Spans are
+/// sent in batches from a background thread; keep the returned
+/// [`Telemetry`] alive until exit, since dropping it flushes the last batch.
+///
+/// Does nothing if a global subscriber is already set.
+#[cfg(feature = "otel")]
+pub fn init_with_telemetry(verbosity: u8) -> Result<Telemetry> {
+    use opentelemetry_sdk::{trace::SdkTracerProvider, Resource};
+
+    let exporter = opentelemetry_otlp::SpanExporter::builder()
+        .with_http()
+        .build()?;
+    let mut resource = Resource::builder();
+    if std::env::var_os("OTEL_SERVICE_NAME").is_none() {
+        resource = resource.with_service_name(SERVICE_NAME);
+    }
+    let provider = SdkTracerProvider::builder()
+        .with_batch_exporter(exporter)
+        .with_resource(resource.build())
+        .build();
+
+    let (filter, span_events) = verbosity_filter(verbosity)?;
+    let subscriber = tracing_subscriber::registry()
+        .with(filter)
+        .with(fmt_layer(span_events))
+        .with(telemetry_layer(&provider));
+    let _ = tracing::subscriber::set_global_default(subscriber);
+    Ok(Telemetry { provider })
+}
+
+/// Crates the OTLP exporter sends spans with. Their own spans are left
+/// out, or every export would produce more spans to export.
+#[cfg(feature = "otel")]
+const EXPORTER_TARGETS: &[&str] = &["opentelemetry", "hyper", "reqwest", "h2", "tower"];
+
+/// Turns spans into OpenTelemetry spans, with their fields as attributes,
+/// and hands them to `provider`'s exporters.
+#[cfg(feature = "otel")]
+pub(crate) fn telemetry_layer<S>(
+    provider: &opentelemetry_sdk::trace::SdkTracerProvider,
+) -> impl Layer<S>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    use opentelemetry::trace::TracerProvider as _;
+
+    tracing_opentelemetry::layer()
+        .with_tracer(provider.tracer(SERVICE_NAME))
+        .with_filter(tracing_subscriber::filter::filter_fn(|metadata| {
+            !EXPORTER_TARGETS
+                .iter()
+                .any(|target| metadata.target().starts_with(target))
+        }))
+}
+
+/// Keeps span export running; dropping it flushes the spans not yet sent
+/// and shuts the exporter down.
+#[cfg(feature = "otel")]
+#[must_use = "spans are flushed when the Telemetry is dropped"]
+pub struct Telemetry {
+    provider: opentelemetry_sdk::trace::SdkTracerProvider,
+}
+
+#[cfg(feature = "otel")]
+impl Drop for Telemetry {
+    fn drop(&mut self) {
+        if let Err(e) = self.provider.shutdown() {
+            eprintln!("Failed to flush telemetry: {e}");
+        }
+    }
+}
@@ -1,40 +1,38 @@
-mod command_line;
-mod constants;
-mod dynamodb;
-mod logging;
-#[cfg(test)]
-mod tests;
-mod utils;
-
 use anyhow::Result;
-use constants::{CATEGORY_PARTITION_KEY, PRICE_ATTRIBUTE, PRODUCT_NAME_SORT_KEY, TABLE_NAME};
-use dynamodb::{FieldType, Schema, Table};
+use aws_dynamodb_testing::{command_line, config, interrupt, item_editor, line_reader, logging};
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    logging::init()?;
     dotenv::dotenv().ok();
+    let flags = config::Flags::parse(std::env::args().skip(1))?;
+    // Flushes the spans not yet exported when main returns.
+    #[cfg(feature = "otel")]
+    let _telemetry = logging::init_with_telemetry(flags.verbose)?;
+    #[cfg(not(feature = "otel"))]
+    logging::init_with_verbosity(flags.verbose)?;
 
-    let sdk_config = aws_config::load_from_env().await;
-    let ddb = dynamodb::DynamoDb::new(&sdk_config);
-
-    ddb.check_auth().await?;
+    let config = config::Config::load(&flags)?;
 
-    let schema = Schema::new()
-        .add_field(CATEGORY_PARTITION_KEY, FieldType::String)
-        .add_field(PRODUCT_NAME_SORT_KEY, FieldType::String)
-        .add_field(PRICE_ATTRIBUTE, FieldType::Number);
+    let ddb = command_line::connect(&config).await?;
+    if ddb.is_local() {
+        tracing::info!("Using DynamoDB Local; AWS credentials and region are ignored");
+    }
 
-    let table = Table::new(
-        TABLE_NAME,
-        CATEGORY_PARTITION_KEY,
-        Some(PRODUCT_NAME_SORT_KEY),
-    )
-    .with_schema(schema);
+    command_line::print_identity(ddb.check_auth().await?.as_ref());
 
+    let table = config.table();
     ddb.create_table_if_not_exists(&table).await?;
 
-    command_line::run(&ddb, &table).await?;
+    let mut editor = line_reader::Editor::new()?;
+    command_line::run(
+        &ddb,
+        &mut editor,
+        &mut item_editor::ExternalEditor,
+        &interrupt::Interrupt::install(),
+        &config,
+        Some(&table),
+    )
+    .await?;
 
     Ok(())
 }
@@ -0,0 +1,257 @@
+//! Harness for tests that need a DynamoDB to talk to.
+//!
+//! Tests come in three tiers, so `cargo test` passes on a machine with no
+//! network:
+//!
+//! - Unit tests (items, schemas, expression builders, request building,
+//!   `InMemoryDynamoDb`, replayed cassettes) need nothing and always run.
+//! - Local tests call [`requires_local`] first. It starts DynamoDB Local
+//!   in a container with testcontainers and returns a client for it, so
+//!   they need neither a hand-started DynamoDB Local nor a `.env`. The
+//!   container is removed when the fixture is dropped. To use an instance
+//!   that's already running instead (e.g. a CI service container), set
+//!   `DYNAMODB_LOCAL_ENDPOINT` to its URL.
+//! - AWS tests call [`requires_aws`] first. It builds a client from the
+//!   environment (and `.env`), as the CLI does.
+//!
+//! When what a tier needs isn't there, its helper says why on stderr and
+//! returns `None`, and the test returns early: skipped, not failed.
+//!
+//! [`TestTableGuard`] empties (or deletes) a test's table when it's
+//! dropped, so a test that fails part-way doesn't leave rows behind for
+//! the next run.
+
+use anyhow::{Context, Result};
+use dotenv::dotenv;
+use std::ops::Deref;
+use std::path::PathBuf;
+use std::time::Duration;
+use testcontainers::bollard::Docker;
+use testcontainers::core::{IntoContainerPort, WaitFor};
+use testcontainers::runners::AsyncRunner;
+use testcontainers::{ContainerAsync, GenericImage};
+
+use crate::dynamodb::{DynamoDb, Item, ScanParams, Table};
+
+const IMAGE: &str = "amazon/dynamodb-local";
+const TAG: &str = "latest";
+const PORT: u16 = 8000;
+
+/// The last line DynamoDB Local prints before it accepts requests.
+const READY_MESSAGE: &str = "CorsParams";
+
+/// Names an already running DynamoDB Local to use instead of a container.
+const ENDPOINT_VAR: &str = "DYNAMODB_LOCAL_ENDPOINT";
+
+/// Any of these means AWS credentials are configured in the environment.
+const CREDENTIAL_VARS: &[&str] = &[
+    "AWS_ACCESS_KEY_ID",
+    "AWS_PROFILE",
+    "AWS_WEB_IDENTITY_TOKEN_FILE",
+    "AWS_CONTAINER_CREDENTIALS_RELATIVE_URI",
+    "AWS_CONTAINER_CREDENTIALS_FULL_URI",
+];
+
+/// How long a guard waits for its table to be deleted.
+const DELETE_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// A client for a DynamoDB Local that lives as long as `container`.
+pub(crate) struct DynamoDbLocal {
+    pub(crate) ddb: DynamoDb,
+    pub(crate) endpoint: String,
+    pub(crate) container: LocalContainer,
+}
+
+/// Keeps the DynamoDB Local container running until dropped. Empty when
+/// the fixture uses `DYNAMODB_LOCAL_ENDPOINT` instead.
+pub(crate) struct LocalContainer(#[allow(dead_code)] Option<ContainerAsync<GenericImage>>);
+
+/// Starts DynamoDB Local for `test_name`, or returns `None` (after saying
+/// why) if Docker isn't available.
+pub(crate) async fn requires_local(test_name: &str) -> Result<Option<DynamoDbLocal>> {
+    if let Ok(endpoint) = std::env::var(ENDPOINT_VAR) {
+        return Ok(Some(DynamoDbLocal {
+            ddb: DynamoDb::new_local(&endpoint),
+            endpoint,
+            container: LocalContainer(None),
+        }));
+    }
+
+    if let Err(e) = docker_available().await {
+        eprintln!(
+            "Skipping '{test_name}': Docker isn't available ({e:#}). \
+             Start Docker, or set {ENDPOINT_VAR} to a running DynamoDB Local."
+        );
+        return Ok(None);
+    }
+
+    let container = GenericImage::new(IMAGE, TAG)
+        .with_exposed_port(PORT.tcp())
+        .with_wait_for(WaitFor::message_on_stdout(READY_MESSAGE))
+        .start()
+        .await
+        .context("Failed to start DynamoDB Local")?;
+    let host = container.get_host().await?;
+    let port = container.get_host_port_ipv4(PORT).await?;
+
+    let endpoint = format!("http://{host}:{port}");
+    Ok(Some(DynamoDbLocal {
+        ddb: DynamoDb::new_local(&endpoint),
+        endpoint,
+        container: LocalContainer(Some(container)),
+    }))
+}
+
+/// Builds a client from the environment for `test_name`, or returns
+/// `None` (after saying why) if no credentials or region are configured.
+///
+/// Credentials are looked for in the environment and the shared config
+/// files only; instance and SSO credentials that would need a network
+/// call to find count as missing unless `AWS_PROFILE` names them.
+pub(crate) async fn requires_aws(test_name: &str) -> Result<Option<DynamoDb>> {
+    dotenv().ok();
+    let skip = |reason: &str| {
+        eprintln!(
+            "Skipping '{test_name}': {reason}. Set AWS credentials and AWS_REGION \
+             (e.g. in .env), or point AWS_ENDPOINT_URL at DynamoDB Local with dummy ones."
+        );
+        Ok(None)
+    };
+
+    if !credentials_configured() {
+        return skip("no AWS credentials are configured");
+    }
+    let sdk_config = aws_config::load_from_env().await;
+    if sdk_config.region().is_none() {
+        return skip("no AWS region is configured");
+    }
+    Ok(Some(DynamoDb::new(&sdk_config)))
+}
+
+fn credentials_configured() -> bool {
+    let home = std::env::var_os("HOME").map(PathBuf::from);
+    let shared_file = |var: &str, default: &str| {
+        std::env::var_os(var)
+            .map(PathBuf::from)
+            .or_else(|| home.as_ref().map(|home| home.join(default)))
+            .is_some_and(|path| path.exists())
+    };
+    CREDENTIAL_VARS
+        .iter()
+        .any(|var| std::env::var_os(var).is_some())
+        || shared_file("AWS_SHARED_CREDENTIALS_FILE", ".aws/credentials")
+        || shared_file("AWS_CONFIG_FILE", ".aws/config")
+}
+
+async fn docker_available() -> Result<()> {
+    let docker = Docker::connect_with_defaults()?;
+    docker.ping().await?;
+    Ok(())
+}
+
+/// What a [`TestTableGuard`] does to its table when dropped.
+#[derive(Debug, Clone, Copy)]
+enum Cleanup {
+    /// Delete every item, keeping the table.
+    Truncate,
+    /// Delete the table.
+    Delete,
+}
+
+/// A test's client, which cleans up its table when dropped, including
+/// when the test panics.
+///
+/// Derefs to [`DynamoDb`], so tests use it as the client. Dropping blocks
+/// until the cleanup is done. It runs on a thread of its own with a new
+/// client, since the test's runtime may be the thread that's dropping the
+/// guard. Cleanup is best effort: failures are printed, not raised, so
+/// they don't mask the test's own result.
+pub(crate) struct TestTableGuard {
+    ddb: DynamoDb,
+    table: Table<'static>,
+    cleanup: Cleanup,
+}
+
+impl TestTableGuard {
+    /// Guards `table`, deleting its items when dropped.
+    pub(crate) fn new(ddb: DynamoDb, table: Table<'static>) -> Self {
+        Self {
+            ddb,
+            table,
+            cleanup: Cleanup::Truncate,
+        }
+    }
+
+    /// Deletes the whole table when dropped instead, for tables the test
+    /// created for itself.
+    pub(crate) fn delete_on_drop(mut self) -> Self {
+        self.cleanup = Cleanup::Delete;
+        self
+    }
+}
+
+impl Deref for TestTableGuard {
+    type Target = DynamoDb;
+
+    fn deref(&self) -> &DynamoDb {
+        &self.ddb
+    }
+}
+
+impl Drop for TestTableGuard {
+    fn drop(&mut self) {
+        let ddb = self.ddb.with_new_connections();
+        let table = &self.table;
+        let cleanup = self.cleanup;
+        let outcome = std::thread::scope(|scope| {
+            scope
+                .spawn(move || {
+                    tokio::runtime::Builder::new_current_thread()
+                        .enable_all()
+                        .build()?
+                        .block_on(clean_up(&ddb, table, cleanup))
+                })
+                .join()
+        });
+        match outcome {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => eprintln!("Failed to clean up table '{}': {e:#}", table.name()),
+            Err(_) => eprintln!("Cleaning up table '{}' panicked", table.name()),
+        }
+    }
+}
+
+async fn clean_up(ddb: &DynamoDb, table: &Table<'_>, cleanup: Cleanup) -> Result<()> {
+    match cleanup {
+        Cleanup::Delete => {
+            ddb.delete_table_if_exists_and_wait(table.name(), DELETE_TIMEOUT)
+                .await?;
+        }
+        Cleanup::Truncate if ddb.table_exists(table.name()).await? => {
+            let items = ddb
+                .scan_all(
+                    ScanParams {
+                        table_name: table.name(),
+                        consistent_read: Some(true),
+                        ..Default::default()
+                    },
+                    None,
+                    None,
+                )
+                .await?;
+            for item in items {
+                let mut key = Item::new();
+                for name in std::iter::once(table.partition_key()).chain(table.sort_key()) {
+                    let value = item
+                        .attributes
+                        .get(name)
+                        .with_context(|| format!("Item is missing key attribute '{name}'"))?;
+                    key.attributes.insert(name.to_string(), value.clone());
+                }
+                ddb.delete_item(table.name(), key).await?;
+            }
+        }
+        Cleanup::Truncate => {}
+    }
+    Ok(())
+}
@@ -0,0 +1,382 @@
+//! Deterministic fake items for tests.
+//!
+//! [`ItemGenerator`] fills a table with pseudo-random items that fit its
+//! schema. The same seed always produces the same items, so a failing test
+//! can be rerun exactly. Every generated item has the key attributes and
+//! every field in the schema, with the declared type, and no two items share
+//! a primary key.
+//!
+//! ```
+//! use aws_dynamodb_testing::dynamodb::{FieldType, Schema, Table};
+//! use aws_dynamodb_testing::testdata::ItemGenerator;
+//!
+//! let schema = Schema::new().add_field("price", FieldType::Number);
+//! let table = Table::new("products", "category", Some("product_name"));
+//! let items = ItemGenerator::new(schema, &table)
+//!     .with_seed(42)
+//!     .with_partition_values(vec!["Books", "Games"])
+//!     .with_number_range("price", 1, 500)
+//!     .generate(100);
+//! assert_eq!(items.len(), 100);
+//! ```
+
+use aws_sdk_dynamodb::types::AttributeValue;
+use std::collections::{BTreeSet, HashMap};
+
+use crate::dynamodb::{FieldType, Item, Schema, Table};
+
+/// Category names weighted roughly by how many products each would hold,
+/// for partition keys that should look like a real catalogue.
+pub const CATEGORIES: &[(&str, u32)] = &[
+    ("Electronics", 8),
+    ("Books", 6),
+    ("Clothing", 5),
+    ("Home", 4),
+    ("Toys", 3),
+    ("Garden", 2),
+    ("Music", 1),
+];
+
+const ADJECTIVES: &[&str] = &[
+    "Compact", "Deluxe", "Classic", "Wireless", "Organic", "Vintage", "Smart", "Portable",
+];
+
+const NOUNS: &[&str] = &[
+    "Lamp",
+    "Speaker",
+    "Notebook",
+    "Jacket",
+    "Kettle",
+    "Puzzle",
+    "Planter",
+    "Headphones",
+];
+
+/// Numbers fall in this range unless `with_number_range` says otherwise.
+const DEFAULT_NUMBER_RANGE: (i64, i64) = (0, 1000);
+
+/// Numeric sort keys count up from here, like epoch-second timestamps.
+const FIRST_TIMESTAMP: i64 = 1_700_000_000;
+
+/// Strings to pick from, each drawn in proportion to its weight.
+#[derive(Debug, Clone)]
+pub struct WeightedPool {
+    values: Vec<(String, u32)>,
+    total: u64,
+}
+
+impl WeightedPool {
+    /// Creates a pool from `(value, weight)` pairs. Values with a weight of
+    /// zero are never drawn.
+    ///
+    /// # Panics
+    ///
+    /// If no value has a non-zero weight, as there'd be nothing to draw.
+    pub fn new<S: Into<String>>(values: impl IntoIterator<Item = (S, u32)>) -> Self {
+        let values: Vec<_> = values
+            .into_iter()
+            .map(|(value, weight)| (value.into(), weight))
+            .collect();
+        let total = values.iter().map(|(_, weight)| u64::from(*weight)).sum();
+        assert!(
+            total > 0,
+            "A weighted pool needs a value with a non-zero weight"
+        );
+        Self { values, total }
+    }
+
+    /// Creates a pool in which every value is equally likely.
+    ///
+    /// # Panics
+    ///
+    /// If `values` is empty.
+    pub fn uniform<S: Into<String>>(values: impl IntoIterator<Item = S>) -> Self {
+        Self::new(values.into_iter().map(|value| (value, 1)))
+    }
+
+    /// Returns whether `value` can be drawn from the pool.
+    pub fn contains(&self, value: &str) -> bool {
+        self.values
+            .iter()
+            .any(|(candidate, weight)| candidate == value && *weight > 0)
+    }
+
+    fn pick(&self, rng: &mut Rng) -> &str {
+        let mut target = rng.below(self.total);
+        for (value, weight) in &self.values {
+            let weight = u64::from(*weight);
+            if target < weight {
+                return value;
+            }
+            target -= weight;
+        }
+        unreachable!("target is below the total weight")
+    }
+
+    /// The drawable values, in a seed-determined order.
+    fn shuffled(&self, rng: &mut Rng) -> Vec<&str> {
+        let mut values: Vec<&str> = self
+            .values
+            .iter()
+            .filter(|(_, weight)| *weight > 0)
+            .map(|(value, _)| value.as_str())
+            .collect();
+        for i in (1..values.len()).rev() {
+            values.swap(i, rng.below(i as u64 + 1) as usize);
+        }
+        values
+    }
+}
+
+/// Generates items for a table from its schema.
+///
+/// Fields are drawn from a string pool or number range when one is set for
+/// them, and from built-in word lists and `0..=1000` otherwise. Partition
+/// keys default to [`CATEGORIES`] when the table has a sort key; sort keys
+/// (or, without one, partition keys) are made unique per item.
+#[derive(Debug, Clone)]
+pub struct ItemGenerator<'t> {
+    schema: Schema,
+    table: &'t Table<'t>,
+    seed: u64,
+    partition_values: Option<WeightedPool>,
+    string_pools: HashMap<String, WeightedPool>,
+    number_ranges: HashMap<String, (i64, i64)>,
+}
+
+impl<'t> ItemGenerator<'t> {
+    /// Creates a generator for `table`'s keys and `schema`'s fields.
+    pub fn new(schema: Schema, table: &'t Table<'t>) -> Self {
+        Self {
+            schema,
+            table,
+            seed: 0,
+            partition_values: None,
+            string_pools: HashMap::new(),
+            number_ranges: HashMap::new(),
+        }
+    }
+
+    /// Sets the seed. Generators with the same settings and seed produce
+    /// the same items.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Draws partition keys evenly from `values`.
+    ///
+    /// # Panics
+    ///
+    /// If `values` is empty.
+    pub fn with_partition_values<S: Into<String>>(self, values: Vec<S>) -> Self {
+        self.with_weighted_partition_values(WeightedPool::uniform(values))
+    }
+
+    /// Draws partition keys from a weighted pool, such as [`CATEGORIES`].
+    pub fn with_weighted_partition_values(mut self, pool: WeightedPool) -> Self {
+        self.partition_values = Some(pool);
+        self
+    }
+
+    /// Draws a string field's values from `pool`.
+    pub fn with_string_pool(mut self, field: impl Into<String>, pool: WeightedPool) -> Self {
+        self.string_pools.insert(field.into(), pool);
+        self
+    }
+
+    /// Keeps a number field's values (and number set elements) within
+    /// `min..=max`.
+    ///
+    /// # Panics
+    ///
+    /// If `min` is greater than `max`.
+    pub fn with_number_range(mut self, field: impl Into<String>, min: i64, max: i64) -> Self {
+        assert!(min <= max, "Empty number range {min}..={max}");
+        self.number_ranges.insert(field.into(), (min, max));
+        self
+    }
+
+    /// Generates `n` items.
+    ///
+    /// # Panics
+    ///
+    /// If the table has no sort key and `n` is more than the number of
+    /// partition values, since the partition keys couldn't all be unique.
+    pub fn generate(&self, n: usize) -> Vec<Item> {
+        let mut rng = Rng::new(self.seed);
+        let partition_key = self.table.partition_key();
+        let sort_key = self.table.sort_key();
+
+        // Schema fields live in a HashMap; sort them so values are drawn in
+        // the same order every run.
+        let mut fields: Vec<_> = self
+            .schema
+            .fields()
+            .iter()
+            .filter(|(name, _)| !self.table.is_key_attribute(name))
+            .collect();
+        fields.sort_by_key(|(name, _)| *name);
+
+        let partition_values = self
+            .partition_values
+            .clone()
+            .or_else(|| sort_key.map(|_| WeightedPool::new(CATEGORIES.iter().copied())));
+        // Without a sort key each item needs its own partition key, so draw
+        // pool values without replacement.
+        let distinct_partitions = match (&partition_values, sort_key) {
+            (Some(pool), None) => {
+                let values = pool.shuffled(&mut rng);
+                assert!(
+                    n <= values.len(),
+                    "Table '{}' has no sort key, so {n} items need {n} partition values, \
+                     but only {} were given",
+                    self.table.name(),
+                    values.len()
+                );
+                Some(values)
+            }
+            _ => None,
+        };
+
+        let mut timestamp = FIRST_TIMESTAMP;
+        let mut unique_key = |rng: &mut Rng, name: &str, index: usize| match self.field_type(name) {
+            FieldType::Number => {
+                timestamp += rng.between(1, 60);
+                AttributeValue::N(timestamp.to_string())
+            }
+            _ => AttributeValue::S(format!("{} {index:05}", product_name(rng))),
+        };
+
+        (0..n)
+            .map(|index| {
+                let mut item = Item::new();
+                let partition = match (&distinct_partitions, &partition_values) {
+                    (Some(values), _) => key_value(self.field_type(partition_key), values[index]),
+                    (None, Some(pool)) => {
+                        key_value(self.field_type(partition_key), pool.pick(&mut rng))
+                    }
+                    (None, None) => unique_key(&mut rng, partition_key, index),
+                };
+                item.attributes.insert(partition_key.to_string(), partition);
+                if let Some(sort_key) = sort_key {
+                    let sort = unique_key(&mut rng, sort_key, index);
+                    item.attributes.insert(sort_key.to_string(), sort);
+                }
+
+                for (name, field_type) in &fields {
+                    let value = self.value(&mut rng, name, field_type);
+                    item.attributes.insert(name.to_string(), value);
+                }
+                item
+            })
+            .collect()
+    }
+
+    fn field_type(&self, name: &str) -> FieldType {
+        self.schema
+            .fields()
+            .get(name)
+            .cloned()
+            .unwrap_or(FieldType::String)
+    }
+
+    fn value(&self, rng: &mut Rng, name: &str, field_type: &FieldType) -> AttributeValue {
+        let string = |rng: &mut Rng| match self.string_pools.get(name) {
+            Some(pool) => pool.pick(rng).to_string(),
+            None => product_name(rng),
+        };
+        let (min, max) = self
+            .number_ranges
+            .get(name)
+            .copied()
+            .unwrap_or(DEFAULT_NUMBER_RANGE);
+        let number = |rng: &mut Rng| rng.between(min, max).to_string();
+
+        match field_type {
+            FieldType::String => AttributeValue::S(string(rng)),
+            FieldType::Number => AttributeValue::N(number(rng)),
+            FieldType::Boolean => AttributeValue::Bool(rng.below(2) == 1),
+            // Sets can't be empty or hold duplicates.
+            FieldType::StringSet => {
+                let values: BTreeSet<_> = (0..rng.between(1, 3)).map(|_| string(rng)).collect();
+                AttributeValue::Ss(values.into_iter().collect())
+            }
+            FieldType::NumberSet => {
+                let values: BTreeSet<_> = (0..rng.between(1, 3))
+                    .map(|_| rng.between(min, max))
+                    .collect();
+                AttributeValue::Ns(values.iter().map(i64::to_string).collect())
+            }
+            FieldType::StringList => AttributeValue::L(
+                (0..rng.between(0, 3))
+                    .map(|_| AttributeValue::S(string(rng)))
+                    .collect(),
+            ),
+            FieldType::List => AttributeValue::L(
+                (0..rng.between(0, 3))
+                    .map(|_| match rng.below(2) {
+                        0 => AttributeValue::S(string(rng)),
+                        _ => AttributeValue::N(number(rng)),
+                    })
+                    .collect(),
+            ),
+            FieldType::Map => AttributeValue::M(HashMap::from([
+                ("label".to_string(), AttributeValue::S(string(rng))),
+                ("rank".to_string(), AttributeValue::N(number(rng))),
+            ])),
+        }
+    }
+}
+
+/// Renders a pool value as a key of the declared type.
+fn key_value(field_type: FieldType, value: &str) -> AttributeValue {
+    match field_type {
+        FieldType::Number => AttributeValue::N(value.to_string()),
+        _ => AttributeValue::S(value.to_string()),
+    }
+}
+
+fn product_name(rng: &mut Rng) -> String {
+    format!(
+        "{} {}",
+        ADJECTIVES[rng.below(ADJECTIVES.len() as u64) as usize],
+        NOUNS[rng.below(NOUNS.len() as u64) as usize]
+    )
+}
+
+/// SplitMix64: small, fast, and the same on every platform, which is all
+/// test data needs.
+#[derive(Debug, Clone)]
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// A number in `0..bound`.
+    fn below(&mut self, bound: u64) -> u64 {
+        self.next() % bound
+    }
+
+    /// A number in `min..=max`.
+    fn between(&mut self, min: i64, max: i64) -> i64 {
+        let span = max.abs_diff(min) + 1;
+        // A full-width range wraps to zero; any value fits.
+        let offset = if span == 0 {
+            self.next()
+        } else {
+            self.below(span)
+        };
+        min.wrapping_add(offset as i64)
+    }
+}
@@ -7,10 +7,20 @@
 //! - Authentication and table description
 //! - Item, Schema, and Table struct operations
 //! - Complex queries
+//! - `InMemoryDynamoDb`, and CLI commands run against it without credentials
 //!
 //! # Setup
 //!
-//! These tests require a running DynamoDB instance and proper AWS credentials.
+//! The tests fall into three tiers (see `test_support`), and `cargo test`
+//! passes with no network: tests whose tier isn't available print why
+//! and are skipped.
+//!
+//! - Unit tests need nothing. `test_replay_basic_crud` replays recorded
+//!   traffic from `fixtures/cassettes/` instead of calling DynamoDB.
+//! - Local tests (the CRUD and query tests) start their own DynamoDB Local
+//!   in Docker, or use the one `DYNAMODB_LOCAL_ENDPOINT` points at.
+//! - AWS tests (everything run with `run_test`) need credentials and a
+//!   region, and use whatever endpoint those point at.
 //!
 //! ## Credentials
 //!
@@ -22,7 +32,8 @@
 //! AWS_REGION=your_preferred_region
 //! ```
 //!
-//! For local testing with DynamoDB Local, you can use dummy values and set:
+//! To run the AWS tests against DynamoDB Local instead, use dummy values
+//! and set:
 //!
 //! ```
 //! AWS_ENDPOINT_URL=http://localhost:8000
@@ -37,29 +48,134 @@
 //!
 //! The table is created at the start of relevant tests if it doesn't exist.
 //!
+//! Seed data for some tests lives in `fixtures/`; see `fixtures/README.md` for
+//! how to add more.
+//!
 //! # Running Tests
 //!
 //! To run these tests, use:
 //!
 //! ```
-//! cargo test --test integration
+//! cargo test
 //! ```
 //!
+//! Pass `--nocapture` to see which tests were skipped and why.
+//!
 //! Note: These tests may incur AWS charges if run against a real DynamoDB instance.
 
+#[cfg(feature = "serde")]
+use crate::dynamodb::DynamoEntity;
+use crate::{
+    aws::{
+        AttributeValue, BackupStatus, BillingMode, Blob, ConditionCheck, ExportFormat,
+        ExportStatus, ImportStatus, IndexStatus, InputFormat, Put, ReturnConsumedCapacity,
+        ReturnValuesOnConditionCheckFailure, Select, StreamViewType, TransactWriteItem,
+    },
+    blocking::BlockingDynamoDb,
+    constants::{CATEGORY_PARTITION_KEY, PRICE_ATTRIBUTE, PRODUCT_NAME_SORT_KEY, TABLE_NAME},
+    dynamodb::{
+        AuthInfo, BackupArn, CheckpointStore, ConditionFailed, DynamoDb, DynamoDbApi, DynamoError,
+        ExpressionRejected, FieldType, Filter, GetItemOptions, GlobalSecondaryIndex,
+        InMemoryCheckpoints, InMemoryDynamoDb, Item, ItemCount, JsonFormat, KeyCondition,
+        KeySchema, Metrics, PageToken, PointInTimeRecoveryDisabled, Progress, ProjectionExpression,
+        QueryFlexibleParams, QueryOptions, RequestFailed, ScanParams, ScanStats, Schema,
+        ShardedKey, SortKeyCondition, SortKeyValue, Statement, StatementKind, StreamEventType,
+        StreamReader, StreamRecord, StreamsDisabled, Table, TableCapacity, TableNotFound,
+        UpdateBuilder, LOCAL_ENDPOINT,
+    },
+    fixtures::{assert_table_matches_fixture, fixture_path, load_fixture, read_fixture},
+    test_support::{requires_aws, requires_local, DynamoDbLocal, TestTableGuard},
+    testdata::{ItemGenerator, WeightedPool, CATEGORIES},
+    utils::RetryConfig,
+};
+#[cfg(feature = "cli")]
 use crate::{
-    constants::{CATEGORY_PARTITION_KEY, PRICE_ATTRIBUTE, PRODUCT_NAME_SORT_KEY},
-    dynamodb::{DynamoDb, FieldType, Item, Schema, Table},
+    aws::{ProjectionType, TableStatus},
+    command_line::{self, GetArgs},
+    config::{Config, ConfigFile, Flags, OutputFormat, Source},
+    dynamodb::{BulkStatus, Capacity},
+    interrupt::Interrupt,
+    item_editor::ItemEditor,
+    line_reader::{InputClosed, LineReader, PromptCancelled, PromptCompleter},
 };
 use anyhow::Result;
-use aws_sdk_dynamodb::types::AttributeValue;
-use dotenv::dotenv;
-use std::collections::HashMap;
+use aws_sdk_dynamodb::types::{
+    BackupTypeFilter, ContinuousBackupsDescription, ContinuousBackupsStatus,
+    PointInTimeRecoveryDescription, PointInTimeRecoveryStatus,
+};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "cli")]
+use std::collections::VecDeque;
+use std::collections::{BTreeMap, HashMap};
+use std::ops::ControlFlow;
 use std::time::Instant;
 use tokio::time::Duration;
+use tokio_util::sync::CancellationToken;
 use tracing::{error, info, instrument};
 
-const TEST_TABLE_NAME: &str = "testing-products";
+const TEST_TABLE_NAME: &str = TABLE_NAME;
+/// How long tests wait for a table to be created or deleted.
+const TABLE_WAIT_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Typed view of a row in the test table.
+#[cfg(feature = "serde")]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Product {
+    category: String,
+    product_name: String,
+    price: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    discount: Option<f64>,
+}
+
+/// The test table's rows with the entity derived instead of written out,
+/// and the sort key under another field name.
+#[cfg(feature = "serde")]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ddb_simple_derive::DynamoEntity)]
+#[dynamo(table = "testing-products")]
+struct DerivedProduct {
+    #[dynamo(partition_key)]
+    category: String,
+    #[dynamo(sort_key)]
+    #[serde(rename = "product_name")]
+    name: String,
+    price: f64,
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    discount: Option<f64>,
+}
+
+#[cfg(feature = "serde")]
+impl DynamoEntity for Product {
+    fn table_name() -> &'static str {
+        TEST_TABLE_NAME
+    }
+
+    fn partition_key_name() -> &'static str {
+        CATEGORY_PARTITION_KEY
+    }
+
+    fn sort_key_name() -> Option<&'static str> {
+        Some(PRODUCT_NAME_SORT_KEY)
+    }
+
+    fn schema() -> Schema {
+        Schema::new()
+            .add_field(CATEGORY_PARTITION_KEY, FieldType::String)
+            .add_field(PRODUCT_NAME_SORT_KEY, FieldType::String)
+            .add_field(PRICE_ATTRIBUTE, FieldType::Number)
+    }
+
+    fn partition_key_value(&self) -> AttributeValue {
+        AttributeValue::S(self.category.clone())
+    }
+
+    fn sort_key_value(&self) -> Option<AttributeValue> {
+        Some(AttributeValue::S(self.product_name.clone()))
+    }
+}
 
 #[instrument]
 async fn setup_test_table(ddb: &DynamoDb) -> Result<Table<'static>> {
@@ -79,10 +195,15 @@ async fn setup_test_table(ddb: &DynamoDb) -> Result<Table<'static>> {
     );
 
     if !ddb.table_exists(TEST_TABLE_NAME).await? {
-        match crate::utils::retry_with_backoff(
+        // Only throttling and service errors are worth waiting out; a bad
+        // table definition should fail straight away. Retries stop at the
+        // table wait timeout so a struggling endpoint can't stall the run.
+        let retries = RetryConfig::new(Duration::from_secs(3), 5)
+            .with_deadline(tokio::time::Instant::now() + TABLE_WAIT_TIMEOUT);
+        match crate::utils::retry_with_config(
             || ddb.create_table_if_not_exists(&table),
-            Duration::from_secs(3),
-            5,
+            &retries,
+            crate::utils::is_transient_error,
         )
         .await
         {
@@ -103,88 +224,173 @@ async fn setup_test_table(ddb: &DynamoDb) -> Result<Table<'static>> {
         info!("Table already exists");
     }
 
-    // Wait for the table to become active
-    let mut attempts = 0;
-    while attempts < 10 {
-        match ddb.describe_table(TEST_TABLE_NAME).await {
-            Ok(description) => {
-                if let Some(table_description) = description.table() {
-                    if table_description.table_status()
-                        == Some(&aws_sdk_dynamodb::types::TableStatus::Active)
-                    {
-                        info!("Table is active");
-                        break;
-                    }
-                }
-            }
-            Err(e) => {
-                error!("Error describing table: {e:?}");
-            }
-        }
-        tokio::time::sleep(Duration::from_secs(5)).await;
-        attempts += 1;
-    }
-
-    if attempts == 10 {
-        return Err(anyhow::anyhow!(
-            "Table did not become active within the expected time"
-        ));
-    }
+    ddb.wait_for_table_active(TEST_TABLE_NAME, TABLE_WAIT_TIMEOUT)
+        .await?;
 
     info!("Test table setup completed in {:?}", start.elapsed());
     Ok(table)
 }
 
-async fn clean_up_testing_table(ddb: &DynamoDb) -> Result<()> {
-    let items = ddb.scan_table(TEST_TABLE_NAME).await?;
-    for item in items {
-        let key = Item::new()
-            .set_string(
-                CATEGORY_PARTITION_KEY,
-                item.get(CATEGORY_PARTITION_KEY)
-                    .and_then(|attr| attr.as_s().ok())
-                    .ok_or_else(|| anyhow::anyhow!("Missing or invalid partition key"))?,
-            )
-            .set_string(
-                PRODUCT_NAME_SORT_KEY,
-                item.get(PRODUCT_NAME_SORT_KEY)
-                    .and_then(|attr| attr.as_s().ok())
-                    .ok_or_else(|| anyhow::anyhow!("Missing or invalid sort key"))?,
-            );
-        ddb.delete_item(TEST_TABLE_NAME, key).await?;
-    }
-    Ok(())
-}
-
 #[cfg(test)]
+#[allow(clippy::module_inception)]
 mod tests {
     use super::*;
+    use crate::aws::RetryMode;
     use anyhow::Context;
-    use aws_config::load_from_env;
+    use aws_sdk_dynamodb::config::http::HttpResponse;
+    use aws_sdk_dynamodb::config::{Credentials, SharedCredentialsProvider};
+    use aws_sdk_dynamodb::error::{ProvideErrorMetadata, SdkError};
+    use aws_sdk_dynamodb::operation::delete_item::DeleteItemError;
+    use aws_sdk_dynamodb::operation::describe_table::DescribeTableError;
+    use aws_sdk_dynamodb::operation::list_tables::ListTablesError;
+    use aws_sdk_sts::operation::get_caller_identity::GetCallerIdentityError;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+
+    /// Builds a client for tests that only inspect requests and never send them.
+    fn offline_client() -> DynamoDb {
+        DynamoDb::new(
+            &aws_config::SdkConfig::builder()
+                .behavior_version(aws_config::BehaviorVersion::latest())
+                .build(),
+        )
+    }
+
+    /// Builds a client whose every request is answered with `error_type`,
+    /// served from a throwaway local endpoint with retries disabled.
+    async fn failing_client(error_type: &'static str) -> Result<DynamoDb> {
+        let (endpoint, _) = fake_endpoint(move |_| dynamodb_error(400, error_type)).await?;
+        Ok(fake_client(endpoint).without_retries())
+    }
+
+    /// A DynamoDB error response with status `status`.
+    fn dynamodb_error(status: u16, error_type: &str) -> (u16, String) {
+        (
+            status,
+            format!(
+                r#"{{"__type":"com.amazonaws.dynamodb.v20120810#{error_type}","message":"{error_type}"}}"#
+            ),
+        )
+    }
+
+    /// Serves a throwaway local endpoint that answers each request with the
+    /// status and JSON body `respond` returns for it, given how many
+    /// requests came before. Returns the endpoint and a count of requests.
+    async fn fake_endpoint(
+        respond: impl Fn(usize) -> (u16, String) + Send + 'static,
+    ) -> Result<(String, Arc<AtomicUsize>)> {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let endpoint = format!("http://{}", listener.local_addr()?);
+        let requests = Arc::new(AtomicUsize::new(0));
+        let counter = requests.clone();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                read_request(&mut stream).await;
+                let (status, body) = respond(counter.fetch_add(1, Ordering::SeqCst));
+                write_response(&mut stream, status, &body).await;
+            }
+        });
+        Ok((endpoint, requests))
+    }
+
+    /// Reads a whole request from `stream`, so the client doesn't see a
+    /// reset connection when it's answered, and returns its body.
+    async fn read_request(stream: &mut tokio::net::TcpStream) -> String {
+        use tokio::io::AsyncReadExt;
+
+        let mut request = Vec::new();
+        let mut buf = [0; 4096];
+        while let Ok(n @ 1..) = stream.read(&mut buf).await {
+            request.extend_from_slice(&buf[..n]);
+            let text = String::from_utf8_lossy(&request);
+            if let Some((head, body)) = text.split_once("\r\n\r\n") {
+                let content_length = head
+                    .lines()
+                    .find_map(|line| {
+                        let (name, value) = line.split_once(':')?;
+                        name.eq_ignore_ascii_case("content-length")
+                            .then(|| value.trim().parse::<usize>().ok())?
+                    })
+                    .unwrap_or(0);
+                if body.len() >= content_length {
+                    return body.to_string();
+                }
+            }
+        }
+        String::new()
+    }
+
+    /// Answers a request read by [`read_request`] with `status` and a JSON
+    /// `body`.
+    async fn write_response(stream: &mut tokio::net::TcpStream, status: u16, body: &str) {
+        use tokio::io::AsyncWriteExt;
 
-    async fn setup() -> Result<DynamoDb> {
-        dotenv().ok();
-        let sdk_config = load_from_env().await;
-        Ok(DynamoDb::new(&sdk_config))
+        let response = format!(
+            "HTTP/1.1 {status} Status\r\nContent-Type: application/x-amz-json-1.0\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            body.len()
+        );
+        let _ = stream.write_all(response.as_bytes()).await;
     }
 
+    /// A client for `endpoint` with test credentials and the SDK's default
+    /// retries.
+    fn fake_client(endpoint: String) -> DynamoDb {
+        DynamoDb::new(
+            &aws_config::SdkConfig::builder()
+                .behavior_version(aws_config::BehaviorVersion::latest())
+                .endpoint_url(endpoint)
+                .region(aws_config::Region::new("us-east-1"))
+                .credentials_provider(SharedCredentialsProvider::new(Credentials::for_tests()))
+                .build(),
+        )
+    }
+
+    /// Runs an AWS-tier test against the configured endpoint; skipped when
+    /// [`requires_aws`] finds no credentials.
     async fn run_test<F, Fut>(test_name: &str, test_fn: F) -> Result<()>
     where
-        F: FnOnce(DynamoDb) -> Fut,
+        F: FnOnce(TestTableGuard) -> Fut,
         Fut: std::future::Future<Output = Result<()>>,
     {
-        let start = Instant::now();
         info!("Starting test: {}", test_name);
 
-        let ddb = setup().await.context("Failed to setup DynamoDB client")?;
-        let _table = setup_test_table(&ddb)
+        let Some(ddb) = requires_aws(test_name).await? else {
+            return Ok(());
+        };
+        run_with_client(test_name, ddb, test_fn).await
+    }
+
+    /// Like [`run_test`], but against a DynamoDB Local started for the test
+    /// (see [`requires_local`]); skipped when Docker isn't available.
+    async fn run_local_test<F, Fut>(test_name: &str, test_fn: F) -> Result<()>
+    where
+        F: FnOnce(TestTableGuard) -> Fut,
+        Fut: std::future::Future<Output = Result<()>>,
+    {
+        let Some(DynamoDbLocal {
+            ddb,
+            container: _container,
+            ..
+        }) = requires_local(test_name).await?
+        else {
+            return Ok(());
+        };
+        run_with_client(test_name, ddb, test_fn).await
+    }
+
+    async fn run_with_client<F, Fut>(test_name: &str, ddb: DynamoDb, test_fn: F) -> Result<()>
+    where
+        F: FnOnce(TestTableGuard) -> Fut,
+        Fut: std::future::Future<Output = Result<()>>,
+    {
+        let start = Instant::now();
+        let table = setup_test_table(&ddb)
             .await
             .context("Failed to setup test table")?;
 
-        // Add a delay to ensure the table is fully created
-        tokio::time::sleep(Duration::from_secs(5)).await;
-
-        let result = test_fn(ddb).await;
+        // The guard empties the table once the test is done with it, even
+        // if it panics.
+        let result = test_fn(TestTableGuard::new(ddb, table)).await;
 
         match &result {
             Ok(_) => info!("Test '{}' passed in {:?}", test_name, start.elapsed()),
@@ -194,18 +400,74 @@ mod tests {
         result
     }
 
+    #[tokio::test]
+    async fn test_guard_cleans_up_after_panic() -> Result<()> {
+        const GUARDED_TABLE_NAME: &str = "testing-guard";
+        let Some(ddb) = requires_aws("guard_cleans_up_after_panic").await? else {
+            return Ok(());
+        };
+        let key = Item::new()
+            .set_string(CATEGORY_PARTITION_KEY, "Guarded")
+            .set_string(PRODUCT_NAME_SORT_KEY, "Leftover");
+
+        // The futures aren't `Send`, so catch the panics on local tasks.
+        let written = key.clone();
+        let failed = tokio::task::LocalSet::new()
+            .run_until(async move {
+                tokio::task::spawn_local(async move {
+                    run_test("guard_cleans_up_after_panic", |ddb| async move {
+                        ddb.put_item(TEST_TABLE_NAME, written.clone()).await?;
+                        let found = ddb.get_item(TEST_TABLE_NAME, written).await?;
+                        assert!(found.is_none(), "Deliberately failing with a row written");
+                        Ok(())
+                    })
+                    .await
+                })
+                .await
+            })
+            .await;
+        assert!(failed.unwrap_err().is_panic());
+
+        assert!(
+            ddb.get_item(TEST_TABLE_NAME, key).await?.is_none(),
+            "The failed test's row was left behind"
+        );
+
+        // A guard on a table the test made for itself deletes it.
+        let table = Table::new(GUARDED_TABLE_NAME, "id", None);
+        ddb.create_table_if_not_exists(&table).await?;
+        ddb.wait_for_table_active(GUARDED_TABLE_NAME, TABLE_WAIT_TIMEOUT)
+            .await?;
+        let guarded = ddb.with_new_connections();
+        let failed = tokio::task::LocalSet::new()
+            .run_until(async move {
+                tokio::task::spawn_local(async move {
+                    let guard = TestTableGuard::new(guarded, table).delete_on_drop();
+                    guard
+                        .put_item(GUARDED_TABLE_NAME, Item::new().set_string("id", "a"))
+                        .await?;
+                    assert!(
+                        !guard.table_exists(GUARDED_TABLE_NAME).await?,
+                        "Deliberately failing"
+                    );
+                    Ok::<_, anyhow::Error>(())
+                })
+                .await
+            })
+            .await;
+        assert!(failed.unwrap_err().is_panic());
+        assert!(!ddb.table_exists(GUARDED_TABLE_NAME).await?);
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_table_creation_and_deletion() -> Result<()> {
         run_test("table_creation_and_deletion", |ddb| async move {
             assert!(ddb.table_exists(TEST_TABLE_NAME).await?);
 
-            // Add a delay to ensure the table is fully created
-            tokio::time::sleep(Duration::from_secs(5)).await;
-
             ddb.delete_table(TEST_TABLE_NAME).await?;
-
-            // Add a delay to ensure the table is fully deleted
-            tokio::time::sleep(Duration::from_secs(5)).await;
+            ddb.wait_for_table_deleted(TEST_TABLE_NAME, TABLE_WAIT_TIMEOUT)
+                .await?;
 
             assert!(!ddb.table_exists(TEST_TABLE_NAME).await?);
             Ok(())
@@ -214,170 +476,8360 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn test_basic_crud_operations() -> Result<()> {
-        run_test("basic_crud_operations", |ddb| async move {
-            // Test put_item
-            let item = Item::new()
-                .set_string(CATEGORY_PARTITION_KEY, "Electronics")
-                .set_string(PRODUCT_NAME_SORT_KEY, "Smartphone")
-                .set_number(PRICE_ATTRIBUTE, 599.99);
-            ddb.put_item(TEST_TABLE_NAME, item)
-                .await
-                .context("Failed to put item")?;
-
-            // Test get_item
-            let key = Item::new()
-                .set_string(CATEGORY_PARTITION_KEY, "Electronics")
-                .set_string(PRODUCT_NAME_SORT_KEY, "Smartphone");
-            let retrieved_item = ddb.get_item(TEST_TABLE_NAME, key.clone()).await?;
-            let retrieved_item = retrieved_item.ok_or_else(|| anyhow::anyhow!("Item not found"))?;
-            assert_eq!(
-                retrieved_item.get_number(PRICE_ATTRIBUTE),
-                Some(599.99),
-                "Unexpected price value"
-            );
-
-            // Test update_item
-            let updates = Item::new().set_number(PRICE_ATTRIBUTE, 649.99);
-            ddb.update_item(TEST_TABLE_NAME, key.clone(), updates)
-                .await
-                .context("Failed to update item")?;
-            let updated_item = ddb.get_item(TEST_TABLE_NAME, key.clone()).await?;
-            let updated_item =
-                updated_item.ok_or_else(|| anyhow::anyhow!("Updated item not found"))?;
-            assert_eq!(
-                updated_item.get_number(PRICE_ATTRIBUTE),
-                Some(649.99),
-                "Unexpected updated price value"
+    async fn test_table_waiters() -> Result<()> {
+        run_test("table_waiters", |ddb| async move {
+            let start = Instant::now();
+            ddb.wait_for_table_active(TEST_TABLE_NAME, TABLE_WAIT_TIMEOUT)
+                .await?;
+            assert!(
+                start.elapsed() < Duration::from_secs(2),
+                "Waiting on an active table took {:?}",
+                start.elapsed()
             );
 
-            // Test delete_item
-            ddb.delete_item(TEST_TABLE_NAME, key.clone()).await?;
-            let deleted_item = ddb.get_item(TEST_TABLE_NAME, key.clone()).await?;
-            assert!(deleted_item.is_none(), "Item was not deleted");
+            let missing = "testing-missing-table";
+            ddb.wait_for_table_deleted(missing, TABLE_WAIT_TIMEOUT)
+                .await?;
 
-            clean_up_testing_table(&ddb)
+            let start = Instant::now();
+            let error = ddb
+                .wait_for_table_active(missing, Duration::from_secs(1))
                 .await
-                .context("Failed to clean up testing table")?;
+                .expect_err("Waiting on a missing table should time out");
+            assert!(start.elapsed() >= Duration::from_secs(1));
+            assert!(
+                error.to_string().contains("Timed out") && error.to_string().contains(missing),
+                "Unexpected error: {error}"
+            );
             Ok(())
         })
         .await
     }
 
     #[tokio::test]
-    async fn test_query_operations() -> Result<()> {
-        run_test("query_operations", |ddb| async move {
-            // Ensure table is created and wait for it to be active
-            let _table = setup_test_table(&ddb).await?;
+    async fn test_table_exists_propagates_service_errors() -> Result<()> {
+        let ddb = failing_client("ThrottlingException").await?;
+        let error = ddb
+            .table_exists(TEST_TABLE_NAME)
+            .await
+            .expect_err("A throttled request must not report a missing table");
+        let code = error
+            .downcast_ref::<SdkError<DescribeTableError>>()
+            .and_then(|e| e.as_service_error())
+            .and_then(|e| e.code());
+        assert_eq!(
+            code,
+            Some("ThrottlingException"),
+            "Unexpected error: {error:?}"
+        );
 
-            // Add test items
-            for i in 1..=5 {
-                let item = Item::new()
-                    .set_string(CATEGORY_PARTITION_KEY, "Electronics")
-                    .set_string(PRODUCT_NAME_SORT_KEY, format!("Product{}", i))
-                    .set_number(PRICE_ATTRIBUTE, (i as f64) * 100.0);
-                ddb.put_item(TEST_TABLE_NAME, item).await?;
+        let ddb = failing_client("ResourceNotFoundException").await?;
+        assert!(!ddb.table_exists(TEST_TABLE_NAME).await?);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_retry_with_backoff_if() -> Result<()> {
+        use crate::utils::{is_transient_error, retry_with_backoff, retry_with_backoff_if};
+
+        let delay = Duration::from_millis(1);
+        let attempts = AtomicUsize::new(0);
+        let failing = |error: &'static str| {
+            attempts.store(0, Ordering::SeqCst);
+            let attempts = &attempts;
+            move || async move {
+                attempts.fetch_add(1, Ordering::SeqCst);
+                Err::<(), _>(error)
             }
+        };
+        let transient = |e: &&str| *e == "transient";
 
-            // Add a delay to ensure items are fully added
-            tokio::time::sleep(Duration::from_secs(2)).await;
+        // Permanent errors stop after the first attempt...
+        assert!(
+            retry_with_backoff_if(failing("permanent"), delay, 3, transient)
+                .await
+                .is_err()
+        );
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+        // ...transient ones use every retry...
+        assert!(
+            retry_with_backoff_if(failing("transient"), delay, 3, transient)
+                .await
+                .is_err()
+        );
+        assert_eq!(attempts.load(Ordering::SeqCst), 4);
+        // ...and the unconditional version still retries everything.
+        assert!(retry_with_backoff(failing("permanent"), delay, 3)
+            .await
+            .is_err());
+        assert_eq!(attempts.load(Ordering::SeqCst), 4);
 
-            // Test query_items
-            let partition_key = (
-                CATEGORY_PARTITION_KEY,
-                AttributeValue::S("Electronics".to_string()),
+        // Client errors are retried only when the request might succeed
+        // next time.
+        let key = || Item::new().set_string("id", "a");
+        for (status, error_type, expected_requests) in [
+            (400, "ValidationException", 1),
+            (400, "ResourceNotFoundException", 1),
+            (400, "ThrottlingException", 3),
+            (400, "ProvisionedThroughputExceededException", 3),
+            (500, "InternalServerError", 3),
+        ] {
+            let (endpoint, requests) =
+                fake_endpoint(move |_| dynamodb_error(status, error_type)).await?;
+            let ddb = fake_client(endpoint).without_retries();
+            let error = retry_with_backoff_if(
+                || ddb.put_item("retried", key()),
+                delay,
+                2,
+                is_transient_error,
+            )
+            .await
+            .unwrap_err();
+            assert_eq!(
+                requests.load(Ordering::SeqCst),
+                expected_requests,
+                "{error_type}: {error:?}"
             );
-            let items = ddb
-                .query_simple(
-                    TEST_TABLE_NAME,
-                    partition_key.clone(),
-                    None,
-                    None,
-                    None,
-                    None,
-                )
-                .await?;
-            assert_eq!(items.len(), 5);
+        }
+        assert!(!is_transient_error(&anyhow::anyhow!("Not a request")));
+        Ok(())
+    }
 
-            // Test query_simple
-            let sort_key_condition = Some((
-                PRODUCT_NAME_SORT_KEY,
-                ">".to_string(),
-                AttributeValue::S("Product2".to_string()),
-            ));
-            let filter_expression = Some("price > :min_price");
-            let mut expression_attribute_values = HashMap::new();
-            expression_attribute_values.insert(
-                ":min_price".to_string(),
-                AttributeValue::N("200".to_string()),
+    #[tokio::test]
+    async fn test_retry_delays_are_capped_and_jittered() -> Result<()> {
+        use crate::utils::{retry_with_sleep, JitterMode, RetryConfig};
+        use std::cell::RefCell;
+
+        let ms = Duration::from_millis;
+        let delays_for = |config: RetryConfig| async move {
+            let delays = RefCell::new(Vec::new());
+            let result = retry_with_sleep(
+                || async { Err::<(), _>("failed") },
+                &config,
+                |_| true,
+                |delay| {
+                    delays.borrow_mut().push(delay);
+                    std::future::ready(())
+                },
+            )
+            .await;
+            assert!(result.is_err());
+            delays.into_inner()
+        };
+        let config = RetryConfig::new(ms(100), 7).with_max_delay(ms(500));
+
+        // Fibonacci from the initial delay, capped.
+        let capped = vec![
+            ms(100),
+            ms(100),
+            ms(200),
+            ms(300),
+            ms(500),
+            ms(500),
+            ms(500),
+        ];
+        assert_eq!(delays_for(config).await, capped);
+        // Without a cap the delays keep growing.
+        assert_eq!(
+            delays_for(RetryConfig::new(ms(100), 7)).await.last(),
+            Some(&ms(1300))
+        );
+
+        for _ in 0..20 {
+            let full = delays_for(config.with_jitter(JitterMode::Full)).await;
+            let equal = delays_for(config.with_jitter(JitterMode::Equal)).await;
+            for ((full, equal), computed) in full.iter().zip(&equal).zip(&capped) {
+                assert!(full <= computed, "{full:?} > {computed:?}");
+                assert!(
+                    *computed / 2 <= *equal && equal <= computed,
+                    "{equal:?} outside {:?}..={computed:?}",
+                    *computed / 2
+                );
+            }
+        }
+        // Full jitter actually varies the delays.
+        let mut samples = std::collections::HashSet::new();
+        for _ in 0..5 {
+            samples.insert(delays_for(config.with_jitter(JitterMode::Full)).await);
+        }
+        assert!(samples.len() > 1, "{samples:?}");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_timeouts_and_deadlines() -> Result<()> {
+        use crate::utils::{
+            retry_with_config, with_deadline, with_timeout, DeadlineExceeded, TimedOut,
+        };
+        use tokio::time::Instant;
+
+        let ms = Duration::from_millis;
+        let roughly = |elapsed: Duration, expected: Duration| {
+            assert!(
+                elapsed >= expected && elapsed < expected + ms(500),
+                "Took {elapsed:?}, expected about {expected:?}"
             );
-            let queried_items = ddb
-                .query_simple(
-                    TEST_TABLE_NAME,
-                    partition_key,
-                    sort_key_condition,
-                    filter_expression,
-                    Some(3),
-                    Some(expression_attribute_values),
-                )
-                .await?;
-            assert_eq!(queried_items.len(), 3);
+        };
 
-            clean_up_testing_table(&ddb)
-                .await
-                .context("Failed to clean up testing table")?;
-            Ok(())
-        })
+        let start = Instant::now();
+        let error = with_timeout(ms(200), std::future::pending::<()>())
+            .await
+            .unwrap_err();
+        roughly(start.elapsed(), ms(200));
+        assert_eq!(error, TimedOut { after: ms(200) });
+        assert_eq!(error.to_string(), "Timed out after 200ms");
+        assert_eq!(with_timeout(ms(200), async { 7 }).await, Ok(7));
+
+        let start = Instant::now();
+        let error = with_deadline(start + ms(200), std::future::pending::<()>())
+            .await
+            .unwrap_err();
+        roughly(start.elapsed(), ms(200));
+        roughly(error.after, ms(200));
+
+        // A retry loop with a deadline stops early with the last error.
+        let attempts = AtomicUsize::new(0);
+        let start = Instant::now();
+        let error = retry_with_config(
+            || async {
+                let attempt = attempts.fetch_add(1, Ordering::SeqCst) + 1;
+                Err::<(), _>(anyhow::anyhow!("Attempt {attempt} failed"))
+            },
+            &RetryConfig::new(ms(50), 100).with_deadline(start + ms(300)),
+            |_| true,
+        )
         .await
+        .unwrap_err();
+        assert!(start.elapsed() < ms(300), "Took {:?}", start.elapsed());
+        let attempts = attempts.load(Ordering::SeqCst);
+        assert!((2..100).contains(&attempts), "{attempts} attempts");
+        assert_eq!(
+            error.downcast_ref::<DeadlineExceeded>(),
+            Some(&DeadlineExceeded { attempts })
+        );
+        assert_eq!(
+            format!("{error:#}"),
+            format!("Deadline exceeded after {attempts} attempts: Attempt {attempts} failed")
+        );
+        Ok(())
     }
 
-    #[test]
-    fn test_item_schema_and_table_operations() {
-        // Test Item operations
-        let item = Item::new()
-            .set_string("key1", "value1")
-            .set_number("key2", 42.0);
-        assert_eq!(item.get_string("key1"), Some(&"value1".to_string()));
-        assert_eq!(item.get_number("key2"), Some(42.0));
+    #[tokio::test]
+    async fn test_retry_policy() -> Result<()> {
+        let key = || Item::new().set_string("id", "a");
+        let with_policy = |ddb: DynamoDb| {
+            ddb.with_retry_policy(3, Duration::from_millis(1), Duration::from_millis(5))
+        };
+        let throttled = || dynamodb_error(400, "ProvisionedThroughputExceededException");
 
-        // Test Schema operations
-        let schema = Schema::new()
-            .add_field("field1", FieldType::String)
-            .add_field("field2", FieldType::Number);
-        let fields = schema.fields();
-        assert_eq!(fields.len(), 2);
-        assert!(matches!(fields.get("field1"), Some(FieldType::String)));
-        assert!(matches!(fields.get("field2"), Some(FieldType::Number)));
+        // Throttled twice, then accepted
+        let (endpoint, requests) = fake_endpoint(move |n| match n {
+            0 | 1 => throttled(),
+            _ => (200, "{}".to_string()),
+        })
+        .await?;
+        with_policy(fake_client(endpoint))
+            .put_item("retried", key())
+            .await?;
+        assert_eq!(requests.load(Ordering::SeqCst), 3);
 
-        // Test Table operations
-        let table = Table::new("test_table", "partition_key", Some("sort_key"));
-        assert_eq!(table.name(), "test_table");
-        assert_eq!(table.partition_key(), "partition_key");
-        assert_eq!(table.sort_key(), Some("sort_key"));
+        // Server errors are retried too
+        let (endpoint, requests) = fake_endpoint(|n| match n {
+            0 | 1 => dynamodb_error(500, "InternalServerError"),
+            _ => (200, "{}".to_string()),
+        })
+        .await?;
+        let item = with_policy(fake_client(endpoint))
+            .get_item("retried", key())
+            .await?;
+        assert!(item.is_none());
+        assert_eq!(requests.load(Ordering::SeqCst), 3);
+
+        // Gives up after max_attempts, keeping the last error
+        let (endpoint, requests) = fake_endpoint(move |_| throttled()).await?;
+        let error = with_policy(fake_client(endpoint))
+            .put_item("retried", key())
+            .await
+            .unwrap_err();
+        assert_eq!(requests.load(Ordering::SeqCst), 3);
+        assert!(
+            format!("{error:?}").contains("ProvisionedThroughputExceededException"),
+            "{error:?}"
+        );
+
+        // Errors another attempt wouldn't fix aren't retried
+        for error_type in ["ValidationException", "ConditionalCheckFailedException"] {
+            let (endpoint, requests) =
+                fake_endpoint(move |_| dynamodb_error(400, error_type)).await?;
+            assert!(with_policy(fake_client(endpoint))
+                .put_item("retried", key())
+                .await
+                .is_err());
+            assert_eq!(requests.load(Ordering::SeqCst), 1, "{error_type}");
+        }
+
+        let (endpoint, requests) = fake_endpoint(move |_| throttled()).await?;
+        assert!(fake_client(endpoint)
+            .without_retries()
+            .put_item("retried", key())
+            .await
+            .is_err());
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+        Ok(())
     }
 
     #[tokio::test]
-    async fn test_auth_and_describe_table() -> Result<()> {
-        run_test("auth_and_describe_table", |ddb| async move {
-            // Test check_auth
-            ddb.check_auth().await?;
-
-            // Test describe_table
-            let description = ddb.describe_table(TEST_TABLE_NAME).await?;
-            let table = description
-                .table()
-                .ok_or_else(|| anyhow::anyhow!("Table description not found"))?;
-            assert_eq!(
-                table.table_name(),
-                Some(TEST_TABLE_NAME),
-                "Unexpected table name"
-            );
+    async fn test_missing_table_is_typed() -> Result<()> {
+        let key = || Item::new().set_string("id", "a");
+        let not_found = |error: &anyhow::Error| {
+            error
+                .downcast_ref::<TableNotFound>()
+                .map(|e| e.table_name.clone())
+        };
 
-            clean_up_testing_table(&ddb)
+        let ddb = failing_client("ResourceNotFoundException").await?;
+        assert!(ddb.try_describe_table("missing").await?.is_none());
+        assert!(!ddb.table_exists("missing").await?);
+        for error in [
+            ddb.table_info("missing").await.unwrap_err(),
+            ddb.describe_table("missing").await.unwrap_err(),
+            ddb.delete_item("missing", key()).await.unwrap_err(),
+            ddb.update_item("missing", key(), Item::new().set_number("n", 1.0))
                 .await
-                .context("Failed to clean up testing table")?;
-            Ok(())
+                .unwrap_err(),
+            ddb.get_item("missing", key()).await.unwrap_err(),
+            ddb.scan_table("missing").await.unwrap_err(),
+        ] {
+            assert_eq!(not_found(&error).as_deref(), Some("missing"), "{error:?}");
+        }
+        // The SDK error is kept underneath.
+        let error = ddb.delete_item("missing", key()).await.unwrap_err();
+        assert!(error.chain().any(|e| e
+            .downcast_ref::<SdkError<DeleteItemError, HttpResponse>>()
+            .is_some()));
+
+        // Backup operations use another error code for the same thing.
+        let ddb = failing_client("TableNotFoundException").await?;
+        let error = ddb.create_backup("missing", "backup").await.unwrap_err();
+        assert_eq!(not_found(&error).as_deref(), Some("missing"), "{error:?}");
+
+        // Other errors stay as they were.
+        let ddb = failing_client("ValidationException").await?;
+        let error = ddb.delete_item("missing", key()).await.unwrap_err();
+        assert!(not_found(&error).is_none(), "{error:?}");
+
+        let ddb = InMemoryDynamoDb::new();
+        assert!(ddb.try_describe_table("missing").await?.is_none());
+        let error = ddb.delete_item("missing", key()).await.unwrap_err();
+        assert_eq!(not_found(&error).as_deref(), Some("missing"), "{error:?}");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_syntax_errors_report_expressions() -> Result<()> {
+        run_test("syntax_errors_report_expressions", |ddb| async move {
+            let rejected = |error: &anyhow::Error| {
+                error
+                    .downcast_ref::<ExpressionRejected>()
+                    .cloned()
+                    .unwrap_or_else(|| panic!("Expected ExpressionRejected, got {error:?}"))
+            };
+
+            let error = ddb
+                .query_flexible(QueryFlexibleParams {
+                    table_name: TEST_TABLE_NAME,
+                    key_condition_expression: "begins_with(#pk, :pkval",
+                    expression_attribute_names: Some(HashMap::from([(
+                        "#pk".to_string(),
+                        CATEGORY_PARTITION_KEY.to_string(),
+                    )])),
+                    expression_attribute_values: Some(HashMap::from([(
+                        ":pkval".to_string(),
+                        AttributeValue::S("Secret".to_string()),
+                    )])),
+                    ..Default::default()
+                })
+                .await
+                .unwrap_err();
+            let message = rejected(&error).to_string();
+            assert!(
+                message.contains("key condition `begins_with(#pk, :pkval`"),
+                "{message}"
+            );
+            assert!(message.contains(CATEGORY_PARTITION_KEY), "{message}");
+            // Values are reduced to their type and length.
+            assert!(message.contains("S (6 chars)"), "{message}");
+            assert!(!message.contains("Secret"), "{message}");
+
+            let error = ddb
+                .scan_paginated(
+                    ScanParams {
+                        table_name: TEST_TABLE_NAME,
+                        filter_expression: Some("attribute_exists("),
+                        ..Default::default()
+                    },
+                    None,
+                )
+                .await
+                .unwrap_err();
+            let rejected_scan = rejected(&error);
+            assert_eq!(
+                rejected_scan.filter_expression.as_deref(),
+                Some("attribute_exists(")
+            );
+            assert!(rejected_scan.to_string().contains("attribute_exists("));
+            Ok(())
+        })
+        .await?;
+
+        // Update expressions are built, so they can't be malformed, and
+        // moto answers a malformed query filter with a 500; check those
+        // against a stubbed ValidationException instead.
+        let ddb = failing_client("ValidationException").await?;
+        let error = ddb
+            .query_simple(
+                TEST_TABLE_NAME,
+                (
+                    CATEGORY_PARTITION_KEY,
+                    AttributeValue::S("Books".to_string()),
+                ),
+                None,
+                Some("price >"),
+                None,
+                None,
+                None,
+            )
+            .await
+            .unwrap_err();
+        let message = error
+            .downcast_ref::<ExpressionRejected>()
+            .unwrap_or_else(|| panic!("Expected ExpressionRejected, got {error:?}"))
+            .to_string();
+        assert!(message.contains("filter `price >`"), "{message}");
+
+        let error = ddb
+            .update_item(
+                TEST_TABLE_NAME,
+                Item::new().set_string("id", "a"),
+                Item::new().set_string("name", "Secret"),
+            )
+            .await
+            .unwrap_err();
+        let rejected = error
+            .downcast_ref::<ExpressionRejected>()
+            .unwrap_or_else(|| panic!("Expected ExpressionRejected, got {error:?}"));
+        assert!(rejected.update_expression.is_some());
+        let message = rejected.to_string();
+        assert!(
+            message.contains(rejected.update_expression.as_deref().unwrap()),
+            "{message}"
+        );
+        assert!(message.contains("\"name\""), "{message}");
+        assert!(!message.contains("Secret"), "{message}");
+        // The SDK error, with DynamoDB's message, stays in the chain.
+        assert!(error
+            .chain()
+            .any(|e| e.to_string().contains("ValidationException")));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_condition_failures() -> Result<()> {
+        run_test("condition_failures", |ddb| async move {
+            let key = |name: &str| {
+                Item::new()
+                    .set_string(CATEGORY_PARTITION_KEY, "Conditions")
+                    .set_string(PRODUCT_NAME_SORT_KEY, name)
+            };
+            let priced = |price: f64| {
+                Filter::eq(PRICE_ATTRIBUTE, AttributeValue::N(price.to_string())).build()
+            };
+            ddb.put_item(
+                TEST_TABLE_NAME,
+                key("lamp").set_number(PRICE_ATTRIBUTE, 10.0),
+            )
+            .await?;
+
+            // A single write whose condition is false.
+            let error = ddb
+                .put_item_if(
+                    TEST_TABLE_NAME,
+                    key("lamp").set_number(PRICE_ATTRIBUTE, 12.0),
+                    &Filter::attribute_not_exists(CATEGORY_PARTITION_KEY).build(),
+                    true,
+                )
+                .await
+                .unwrap_err();
+            assert!(error.is_condition_failure(), "{error:?}");
+            assert!(error.failed_conditions().is_empty());
+            assert_eq!(
+                error
+                    .condition_failure_item()
+                    .and_then(|item| item.get_number(PRICE_ATTRIBUTE)),
+                Some(10.0)
+            );
+            assert_eq!(
+                error
+                    .downcast_ref::<ConditionFailed>()
+                    .map(|e| e.table_name.as_str()),
+                Some(TEST_TABLE_NAME)
+            );
+
+            let error = ddb
+                .delete_item_if(TEST_TABLE_NAME, key("lamp"), &priced(99.0), false)
+                .await
+                .unwrap_err();
+            assert!(error.is_condition_failure(), "{error:?}");
+            assert!(error.condition_failure_item().is_none());
+            ddb.delete_item_if(TEST_TABLE_NAME, key("lamp"), &priced(10.0), false)
+                .await?;
+            assert!(ddb.get_item(TEST_TABLE_NAME, key("lamp")).await?.is_none());
+
+            // A transaction whose second of three operations fails its check.
+            ddb.put_item(
+                TEST_TABLE_NAME,
+                key("desk").set_number(PRICE_ATTRIBUTE, 80.0),
+            )
+            .await?;
+            let put = |name: &str| -> Result<TransactWriteItem> {
+                Ok(TransactWriteItem::builder()
+                    .put(
+                        Put::builder()
+                            .table_name(TEST_TABLE_NAME)
+                            .set_item(Some(key(name).attributes))
+                            .build()?,
+                    )
+                    .build())
+            };
+            let condition = priced(99.0);
+            let check = TransactWriteItem::builder()
+                .condition_check(
+                    ConditionCheck::builder()
+                        .table_name(TEST_TABLE_NAME)
+                        .set_key(Some(key("desk").attributes))
+                        .condition_expression(condition.expression)
+                        .set_expression_attribute_names(Some(condition.names))
+                        .set_expression_attribute_values(Some(condition.values))
+                        .return_values_on_condition_check_failure(
+                            ReturnValuesOnConditionCheckFailure::AllOld,
+                        )
+                        .build()?,
+                )
+                .build();
+            let error = ddb
+                .transact_write(vec![put("chair")?, check, put("shelf")?])
+                .await
+                .unwrap_err();
+            assert!(error.is_condition_failure(), "{error:?}");
+            assert_eq!(error.failed_conditions(), vec![1]);
+            assert_eq!(
+                error
+                    .condition_failure_item()
+                    .and_then(|item| item.get_number(PRICE_ATTRIBUTE)),
+                Some(80.0)
+            );
+            // Nothing was written.
+            assert!(ddb.get_item(TEST_TABLE_NAME, key("chair")).await?.is_none());
+
+            ddb.transact_write(vec![put("chair")?, put("shelf")?])
+                .await?;
+            assert!(ddb.get_item(TEST_TABLE_NAME, key("shelf")).await?.is_some());
+            Ok(())
+        })
+        .await?;
+
+        // Other failures aren't condition failures.
+        let ddb = failing_client("ValidationException").await?;
+        let error = ddb
+            .put_item_if(
+                TEST_TABLE_NAME,
+                Item::new().set_string("id", "a"),
+                &Filter::attribute_not_exists("id").build(),
+                false,
+            )
+            .await
+            .unwrap_err();
+        assert!(!error.is_condition_failure(), "{error:?}");
+        assert!(error.failed_conditions().is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_update_if_returns_current_item() -> Result<()> {
+        run_local_test("update_if_returns_current_item", |ddb| async move {
+            let key = Item::new()
+                .set_string(CATEGORY_PARTITION_KEY, "Conditions")
+                .set_string(PRODUCT_NAME_SORT_KEY, "lamp");
+            let priced = |price: f64| {
+                Filter::eq(PRICE_ATTRIBUTE, AttributeValue::N(price.to_string())).build()
+            };
+            ddb.put_item(
+                TEST_TABLE_NAME,
+                key.clone().set_number(PRICE_ATTRIBUTE, 10.0),
+            )
+            .await?;
+
+            let error = ddb
+                .update_item_if(
+                    TEST_TABLE_NAME,
+                    key.clone(),
+                    Item::new().set_number(PRICE_ATTRIBUTE, 12.0),
+                    &priced(99.0),
+                    true,
+                )
+                .await
+                .unwrap_err();
+            assert!(error.is_condition_failure(), "{error:?}");
+            let failed = error
+                .downcast_ref::<ConditionFailed>()
+                .unwrap_or_else(|| panic!("Expected ConditionFailed, got {error:?}"));
+            assert_eq!(failed.table_name, TEST_TABLE_NAME);
+            assert_eq!(
+                failed
+                    .item
+                    .as_ref()
+                    .and_then(|item| item.get_number(PRICE_ATTRIBUTE)),
+                Some(10.0)
+            );
+
+            // Without `return_item`, the failure carries no item.
+            let error = ddb
+                .update_with_if(
+                    TEST_TABLE_NAME,
+                    key.clone(),
+                    UpdateBuilder::new().remove(PRICE_ATTRIBUTE),
+                    &priced(99.0),
+                    false,
+                )
+                .await
+                .unwrap_err();
+            assert!(error.is_condition_failure(), "{error:?}");
+            assert!(error.condition_failure_item().is_none());
+
+            ddb.update_with_if(
+                TEST_TABLE_NAME,
+                key.clone(),
+                UpdateBuilder::new().set(PRICE_ATTRIBUTE, AttributeValue::N("12".to_string())),
+                &priced(10.0),
+                true,
+            )
+            .await?;
+            let item = ddb
+                .get_item(TEST_TABLE_NAME, key)
+                .await?
+                .context("Item not found")?;
+            assert_eq!(item.get_number(PRICE_ATTRIBUTE), Some(12.0));
+            Ok(())
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_errors_name_operation_and_table() -> Result<()> {
+        run_test("errors_name_operation_and_table", |ddb| async move {
+            let missing = "testing-no-such-table";
+            let request = |error: &anyhow::Error| {
+                error
+                    .downcast_ref::<RequestFailed>()
+                    .cloned()
+                    .unwrap_or_else(|| panic!("Expected RequestFailed, got {error:?}"))
+            };
+
+            let error = ddb
+                .put_item(missing, Item::new().set_string("id", "a"))
+                .await
+                .unwrap_err();
+            assert!(error.to_string().contains(missing), "{error}");
+            let failed = request(&error);
+            assert_eq!(failed.operation, "PutItem");
+            assert_eq!(failed.table_name.as_deref(), Some(missing));
+            assert!(
+                format!("{error:#}").contains(&failed.to_string()),
+                "{error:#}"
+            );
+
+            let error = ddb
+                .query_flexible(QueryFlexibleParams {
+                    table_name: missing,
+                    key_condition_expression: "#pk = :pkval",
+                    expression_attribute_names: Some(HashMap::from([(
+                        "#pk".to_string(),
+                        "id".to_string(),
+                    )])),
+                    expression_attribute_values: Some(HashMap::from([(
+                        ":pkval".to_string(),
+                        AttributeValue::S("a".to_string()),
+                    )])),
+                    ..Default::default()
+                })
+                .await
+                .unwrap_err();
+            assert!(error.to_string().contains(missing), "{error}");
+            assert_eq!(request(&error).operation, "Query");
+
+            // Single-item requests name the key, with its values redacted.
+            let error = ddb
+                .get_item(missing, Item::new().set_string("id", "secret"))
+                .await
+                .unwrap_err();
+            let failed = request(&error);
+            assert_eq!(failed.operation, "GetItem");
+            let message = failed.to_string();
+            assert!(message.contains(r#""id": "S (6 chars)""#), "{message}");
+            assert!(!message.contains("secret"), "{message}");
+            Ok(())
+        })
+        .await?;
+
+        // Requests on no table in particular still name the operation.
+        let ddb = failing_client("AccessDeniedException").await?;
+        let error = ddb.list_tables().await.unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "ListTables failed with AccessDeniedException"
+        );
+        assert_eq!(
+            error
+                .downcast_ref::<RequestFailed>()
+                .map(|e| e.table_name.clone()),
+            Some(None)
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_table_exists_and_list_tables() -> Result<()> {
+        run_test("table_exists_and_list_tables", |ddb| async move {
+            assert!(ddb.table_exists(TEST_TABLE_NAME).await?);
+            assert!(!ddb.table_exists("testing-missing-table").await?);
+            assert!(ddb
+                .try_describe_table("testing-missing-table")
+                .await?
+                .is_none());
+            assert_eq!(
+                ddb.try_describe_table(TEST_TABLE_NAME)
+                    .await?
+                    .map(|info| info.name),
+                Some(TEST_TABLE_NAME.to_string())
+            );
+            let error = ddb
+                .delete_item(
+                    "testing-missing-table",
+                    Item::new().set_string(CATEGORY_PARTITION_KEY, "Missing"),
+                )
+                .await
+                .unwrap_err();
+            assert!(error.downcast_ref::<TableNotFound>().is_some(), "{error:?}");
+            assert!(ddb
+                .list_tables()
+                .await?
+                .contains(&TEST_TABLE_NAME.to_string()));
+            Ok(())
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_list_tables_with_prefix() -> Result<()> {
+        run_test("list_tables_with_prefix", |ddb| async move {
+            const PREFIX: &str = "testing-listed-";
+            let names = ["alpha", "beta", "gamma"].map(|suffix| format!("{PREFIX}{suffix}"));
+            for name in &names {
+                let table = Table::new(name, CATEGORY_PARTITION_KEY, None);
+                ddb.create_table_if_not_exists_and_wait(&table, TABLE_WAIT_TIMEOUT)
+                    .await?;
+            }
+
+            let mut listed = ddb.list_tables_with_prefix(PREFIX).await?;
+            listed.sort();
+            assert_eq!(listed, names, "Expected only the prefixed tables");
+            assert!(ddb
+                .list_tables_with_prefix("testing-listed-b")
+                .await?
+                .iter()
+                .eq([&names[1]]));
+            assert!(ddb
+                .list_tables_with_prefix("no-such-prefix-")
+                .await?
+                .is_empty());
+
+            let all = ddb.list_tables().await?;
+            assert!(names.iter().all(|name| all.contains(name)));
+            assert!(all.contains(&TEST_TABLE_NAME.to_string()));
+
+            for name in &names {
+                ddb.delete_table(name).await?;
+            }
+            Ok(())
+        })
+        .await
+    }
+
+    #[cfg(feature = "cli")]
+    #[tokio::test]
+    async fn test_table_info() -> Result<()> {
+        const INFO_TABLE_NAME: &str = "testing-table-info";
+        const BRAND_INDEX: &str = "brand-index";
+
+        run_test("table_info", |ddb| async move {
+            let table = Table::new(
+                INFO_TABLE_NAME,
+                CATEGORY_PARTITION_KEY,
+                Some(PRODUCT_NAME_SORT_KEY),
+            )
+            .with_global_secondary_index(BRAND_INDEX, "brand", Some(PRICE_ATTRIBUTE))
+            .with_schema(Schema::new().add_field(PRICE_ATTRIBUTE, FieldType::Number));
+            ddb.create_table_if_not_exists_and_wait(&table, TABLE_WAIT_TIMEOUT)
+                .await?;
+
+            let info = ddb.table_info(INFO_TABLE_NAME).await?;
+            assert_eq!(info.name, INFO_TABLE_NAME);
+            assert_eq!(info.status, TableStatus::Active);
+            assert_eq!(info.partition_key, CATEGORY_PARTITION_KEY);
+            assert_eq!(info.sort_key.as_deref(), Some(PRODUCT_NAME_SORT_KEY));
+            assert_eq!(info.billing_mode, BillingMode::PayPerRequest);
+            assert_eq!(info.item_count, 0);
+            assert_eq!(info.stream_arn, None);
+            assert!(info.created_at <= std::time::SystemTime::now());
+
+            assert_eq!(info.global_secondary_indexes.len(), 1);
+            let index = &info.global_secondary_indexes[0];
+            assert_eq!(index.name, BRAND_INDEX);
+            assert_eq!(index.partition_key, "brand");
+            assert_eq!(index.sort_key.as_deref(), Some(PRICE_ATTRIBUTE));
+            assert_eq!(index.status, IndexStatus::Active);
+            assert_eq!(index.projection_type, ProjectionType::All);
+            assert_eq!(index.capacity, Capacity::OnDemand);
+            assert!(info.local_secondary_indexes.is_empty());
+
+            let rendered = command_line::render_info(&info, table.schema())?;
+            assert!(rendered.contains("Global Secondary Indexes:"), "{rendered}");
+            assert!(
+                rendered.contains(&format!(
+                    "  {BRAND_INDEX} (brand, {PRICE_ATTRIBUTE}) - ACTIVE"
+                )),
+                "{rendered}"
+            );
+            assert!(rendered.contains("    Projection: ALL"), "{rendered}");
+            assert!(rendered.contains("    Capacity: on-demand"), "{rendered}");
+            assert!(!rendered.contains("Local Secondary Indexes:"), "{rendered}");
+
+            let missing = ddb.table_info("testing-missing-table").await;
+            assert!(missing.is_err(), "Expected an error for a missing table");
+
+            ddb.delete_table(INFO_TABLE_NAME).await?;
+            Ok(())
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_delete_table_if_exists() -> Result<()> {
+        const RECREATED_TABLE_NAME: &str = "testing-recreated";
+
+        run_test("delete_table_if_exists", |ddb| async move {
+            let table = Table::new(RECREATED_TABLE_NAME, CATEGORY_PARTITION_KEY, None);
+            ddb.create_table_if_not_exists_and_wait(&table, TABLE_WAIT_TIMEOUT)
+                .await?;
+
+            assert!(
+                ddb.delete_table_if_exists_and_wait(RECREATED_TABLE_NAME, TABLE_WAIT_TIMEOUT)
+                    .await?
+            );
+            assert!(
+                !ddb.delete_table_if_exists(RECREATED_TABLE_NAME).await?,
+                "Deleting a missing table should report false"
+            );
+
+            // The name is free again as soon as the wait returns.
+            let created = ddb
+                .create_table_if_not_exists_and_wait(&table, TABLE_WAIT_TIMEOUT)
+                .await?;
+            assert!(created.is_some(), "Expected the table to be created again");
+
+            assert!(
+                ddb.delete_table_if_exists_and_wait(RECREATED_TABLE_NAME, TABLE_WAIT_TIMEOUT)
+                    .await?
+            );
+            Ok(())
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_create_and_delete_gsi() -> Result<()> {
+        const GSI_TABLE_NAME: &str = "testing-products-added-index";
+        const BRAND_INDEX: &str = "brand-index";
+
+        run_test("create_and_delete_gsi", |ddb| async move {
+            let table = Table::new(
+                GSI_TABLE_NAME,
+                CATEGORY_PARTITION_KEY,
+                Some(PRODUCT_NAME_SORT_KEY),
+            );
+            ddb.delete_table_if_exists_and_wait(GSI_TABLE_NAME, TABLE_WAIT_TIMEOUT)
+                .await?;
+            ddb.create_table_if_not_exists_and_wait(&table, TABLE_WAIT_TIMEOUT)
+                .await?;
+
+            for (category, name, brand) in [
+                ("Electronics", "Laptop", "Acme"),
+                ("Appliances", "Toaster", "Acme"),
+                ("Electronics", "Phone", "Globex"),
+            ] {
+                let item = Item::new()
+                    .set_string(CATEGORY_PARTITION_KEY, category)
+                    .set_string(PRODUCT_NAME_SORT_KEY, name)
+                    .set_string("brand", brand);
+                ddb.put_item(GSI_TABLE_NAME, item).await?;
+            }
+
+            let index =
+                GlobalSecondaryIndex::new(BRAND_INDEX, "brand", Some(PRODUCT_NAME_SORT_KEY));
+            ddb.create_gsi(&table, &index).await?;
+            ddb.wait_for_index_active(GSI_TABLE_NAME, BRAND_INDEX, TABLE_WAIT_TIMEOUT)
+                .await?;
+
+            let info = ddb.table_info(GSI_TABLE_NAME).await?;
+            let described = info
+                .global_secondary_indexes
+                .iter()
+                .find(|index| index.name == BRAND_INDEX)
+                .context("Index missing from the table description")?;
+            assert_eq!(described.partition_key, "brand");
+            assert_eq!(described.sort_key.as_deref(), Some(PRODUCT_NAME_SORT_KEY));
+
+            let items = ddb
+                .query_index(
+                    GSI_TABLE_NAME,
+                    BRAND_INDEX,
+                    ("brand", AttributeValue::S("Acme".to_string())),
+                    None,
+                    QueryOptions::default(),
+                )
+                .await?;
+            let names: Vec<_> = items
+                .iter()
+                .filter_map(|item| item.get_string(PRODUCT_NAME_SORT_KEY).cloned())
+                .collect();
+            assert_eq!(
+                names,
+                ["Laptop", "Toaster"],
+                "Expected the backfilled items"
+            );
+
+            ddb.delete_gsi(GSI_TABLE_NAME, BRAND_INDEX).await?;
+            let info = ddb.table_info(GSI_TABLE_NAME).await?;
+            assert!(
+                info.global_secondary_indexes
+                    .iter()
+                    .all(|index| index.name != BRAND_INDEX
+                        || index.status == IndexStatus::Deleting),
+                "Expected the index to be gone or deleting"
+            );
+
+            let error = ddb
+                .wait_for_index_active(GSI_TABLE_NAME, "missing-index", Duration::from_millis(500))
+                .await
+                .expect_err("Waiting on a missing index should time out");
+            assert!(error.to_string().contains("missing-index"));
+
+            ddb.delete_table_if_exists_and_wait(GSI_TABLE_NAME, TABLE_WAIT_TIMEOUT)
+                .await?;
+            Ok(())
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_basic_crud_operations() -> Result<()> {
+        run_local_test("basic_crud_operations", |ddb| async move {
+            // Test put_item
+            let item = Item::new()
+                .set_string(CATEGORY_PARTITION_KEY, "Electronics")
+                .set_string(PRODUCT_NAME_SORT_KEY, "Smartphone")
+                .set_number(PRICE_ATTRIBUTE, 599.99);
+            ddb.put_item(TEST_TABLE_NAME, item)
+                .await
+                .context("Failed to put item")?;
+
+            // Test get_item
+            let key = Item::new()
+                .set_string(CATEGORY_PARTITION_KEY, "Electronics")
+                .set_string(PRODUCT_NAME_SORT_KEY, "Smartphone");
+            let retrieved_item = ddb.get_item(TEST_TABLE_NAME, key.clone()).await?;
+            let retrieved_item = retrieved_item.ok_or_else(|| anyhow::anyhow!("Item not found"))?;
+            assert_eq!(
+                retrieved_item.get_number(PRICE_ATTRIBUTE),
+                Some(599.99),
+                "Unexpected price value"
+            );
+
+            // Test update_item
+            let updates = Item::new().set_number(PRICE_ATTRIBUTE, 649.99);
+            ddb.update_item(TEST_TABLE_NAME, key.clone(), updates)
+                .await
+                .context("Failed to update item")?;
+            let updated_item = ddb.get_item(TEST_TABLE_NAME, key.clone()).await?;
+            let updated_item =
+                updated_item.ok_or_else(|| anyhow::anyhow!("Updated item not found"))?;
+            assert_eq!(
+                updated_item.get_number(PRICE_ATTRIBUTE),
+                Some(649.99),
+                "Unexpected updated price value"
+            );
+
+            // Test delete_item
+            ddb.delete_item(TEST_TABLE_NAME, key.clone()).await?;
+            let deleted_item = ddb.get_item(TEST_TABLE_NAME, key.clone()).await?;
+            assert!(deleted_item.is_none(), "Item was not deleted");
+
+            Ok(())
+        })
+        .await
+    }
+
+    /// The table the recorded CRUD flow uses, so recording it doesn't touch
+    /// other tests' tables.
+    const CASSETTE_TABLE_NAME: &str = "testing-cassette";
+
+    /// Set to re-record cassettes against DynamoDB Local before replaying.
+    const RECORD_CASSETTES_VAR: &str = "RECORD_CASSETTES";
+
+    /// The CRUD flow recorded in `fixtures/cassettes/basic_crud.json`.
+    /// Changing it means re-recording the cassette.
+    async fn basic_crud_flow(ddb: &DynamoDb) -> Result<()> {
+        let table = Table::new(
+            CASSETTE_TABLE_NAME,
+            CATEGORY_PARTITION_KEY,
+            Some(PRODUCT_NAME_SORT_KEY),
+        );
+        ddb.delete_table_if_exists_and_wait(CASSETTE_TABLE_NAME, TABLE_WAIT_TIMEOUT)
+            .await?;
+        ddb.create_table_if_not_exists(&table).await?;
+        ddb.wait_for_table_active(CASSETTE_TABLE_NAME, TABLE_WAIT_TIMEOUT)
+            .await?;
+
+        let key = Item::new()
+            .set_string(CATEGORY_PARTITION_KEY, "Electronics")
+            .set_string(PRODUCT_NAME_SORT_KEY, "Smartphone");
+        assert!(ddb
+            .get_item(CASSETTE_TABLE_NAME, key.clone())
+            .await?
+            .is_none());
+
+        ddb.put_item(
+            CASSETTE_TABLE_NAME,
+            key.clone().set_number(PRICE_ATTRIBUTE, 599.99),
+        )
+        .await?;
+        let item = ddb.get_item(CASSETTE_TABLE_NAME, key.clone()).await?;
+        assert_eq!(
+            item.and_then(|i| i.get_number(PRICE_ATTRIBUTE)),
+            Some(599.99)
+        );
+
+        ddb.update_item(
+            CASSETTE_TABLE_NAME,
+            key.clone(),
+            Item::new().set_number(PRICE_ATTRIBUTE, 649.99),
+        )
+        .await?;
+        let item = ddb.get_item(CASSETTE_TABLE_NAME, key.clone()).await?;
+        assert_eq!(
+            item.and_then(|i| i.get_number(PRICE_ATTRIBUTE)),
+            Some(649.99)
+        );
+
+        ddb.delete_item(CASSETTE_TABLE_NAME, key.clone()).await?;
+        assert!(ddb.get_item(CASSETTE_TABLE_NAME, key).await?.is_none());
+
+        ddb.delete_table_if_exists_and_wait(CASSETTE_TABLE_NAME, TABLE_WAIT_TIMEOUT)
+            .await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_replay_basic_crud() -> Result<()> {
+        let cassette = fixture_path("cassettes/basic_crud.json");
+        if std::env::var_os(RECORD_CASSETTES_VAR).is_some() {
+            let Some(local) = requires_local("replay_basic_crud").await? else {
+                return Ok(());
+            };
+            let ddb = DynamoDb::new_local(&local.endpoint).with_recording(&cassette)?;
+            basic_crud_flow(&ddb).await?;
+        }
+
+        // Nothing listens on the discard port, so a request that reached
+        // the network would fail.
+        let ddb = DynamoDb::new_local("http://127.0.0.1:9").with_replay(&cassette)?;
+        basic_crud_flow(&ddb).await?;
+
+        let unrecorded = ddb
+            .get_item(
+                CASSETTE_TABLE_NAME,
+                Item::new()
+                    .set_string(CATEGORY_PARTITION_KEY, "Electronics")
+                    .set_string(PRODUCT_NAME_SORT_KEY, "Unrecorded"),
+            )
+            .await
+            .unwrap_err();
+        let message = format!("{unrecorded:?}");
+        assert!(message.contains("No response recorded"), "{message}");
+        assert!(DynamoDb::new_local(LOCAL_ENDPOINT)
+            .with_replay(fixture_path("cassettes/missing.json"))
+            .is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_query_operations() -> Result<()> {
+        run_local_test("query_operations", |ddb| async move {
+            // Ensure table is created and wait for it to be active
+            let table = setup_test_table(&ddb).await?;
+
+            // Product1..Product5, priced 100..500
+            assert_eq!(load_fixture(&*ddb, &table, "electronics.jsonl").await?, 5);
+
+            // Test query_items
+            let partition_key = (
+                CATEGORY_PARTITION_KEY,
+                AttributeValue::S("Electronics".to_string()),
+            );
+            let items = ddb
+                .query_simple(
+                    TEST_TABLE_NAME,
+                    partition_key.clone(),
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                )
+                .await?;
+            assert_eq!(items.len(), 5);
+            let items = ddb
+                .query_items(TEST_TABLE_NAME, partition_key.clone(), None)
+                .await?;
+            assert_eq!(items.len(), 5);
+            let items = ddb
+                .query_items(
+                    TEST_TABLE_NAME,
+                    partition_key.clone(),
+                    Some((
+                        PRODUCT_NAME_SORT_KEY,
+                        "<=".to_string(),
+                        AttributeValue::S("Product2".to_string()).into(),
+                    )),
+                )
+                .await?;
+            assert_eq!(items.len(), 2);
+
+            // Test query_simple
+            let sort_key_condition = Some((
+                PRODUCT_NAME_SORT_KEY,
+                ">".to_string(),
+                AttributeValue::S("Product2".to_string()).into(),
+            ));
+            let filter_expression = Some("price > :min_price");
+            let mut expression_attribute_values = HashMap::new();
+            expression_attribute_values.insert(
+                ":min_price".to_string(),
+                AttributeValue::N("200".to_string()),
+            );
+            let queried_items = ddb
+                .query_simple(
+                    TEST_TABLE_NAME,
+                    partition_key,
+                    sort_key_condition,
+                    filter_expression,
+                    Some(3),
+                    Some(expression_attribute_values),
+                    None,
+                )
+                .await?;
+            assert_eq!(queried_items.len(), 3);
+
+            Ok(())
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_list_append_and_set_element() -> Result<()> {
+        run_test("list_append_and_set_element", |ddb| async move {
+            let key = Item::new()
+                .set_string(CATEGORY_PARTITION_KEY, "Lists")
+                .set_string(PRODUCT_NAME_SORT_KEY, "Tagged");
+            ddb.put_item(TEST_TABLE_NAME, key.clone()).await?;
+
+            // Appending to a missing attribute creates the list
+            ddb.append_to_list(
+                TEST_TABLE_NAME,
+                key.clone(),
+                "tags",
+                vec![
+                    AttributeValue::S("a".to_string()),
+                    AttributeValue::S("b".to_string()),
+                ],
+            )
+            .await?;
+            ddb.append_to_list(
+                TEST_TABLE_NAME,
+                key.clone(),
+                "tags",
+                vec![AttributeValue::S("c".to_string())],
+            )
+            .await?;
+
+            let item = ddb
+                .get_item(TEST_TABLE_NAME, key.clone())
+                .await?
+                .ok_or_else(|| anyhow::anyhow!("Item not found"))?;
+            let tags: Vec<_> = item
+                .get_list("tags")
+                .ok_or_else(|| anyhow::anyhow!("Missing tags list"))?
+                .iter()
+                .filter_map(|tag| tag.as_s().ok().cloned())
+                .collect();
+            assert_eq!(tags, vec!["a", "b", "c"], "Unexpected tag order");
+
+            ddb.set_list_element(
+                TEST_TABLE_NAME,
+                key.clone(),
+                "tags",
+                0,
+                AttributeValue::S("z".to_string()),
+            )
+            .await?;
+
+            let item = ddb
+                .get_item(TEST_TABLE_NAME, key.clone())
+                .await?
+                .ok_or_else(|| anyhow::anyhow!("Item not found"))?;
+            let first = item
+                .get_list("tags")
+                .and_then(|tags| tags.first())
+                .and_then(|tag| tag.as_s().ok());
+            assert_eq!(first, Some(&"z".to_string()), "Element 0 not replaced");
+
+            Ok(())
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_set_if_not_exists() -> Result<()> {
+        run_test("set_if_not_exists", |ddb| async move {
+            let key = Item::new()
+                .set_string(CATEGORY_PARTITION_KEY, "Defaults")
+                .set_string(PRODUCT_NAME_SORT_KEY, "Timestamped");
+            ddb.put_item(TEST_TABLE_NAME, key.clone()).await?;
+
+            for (created_at, updated_at) in [("first", "1"), ("second", "2")] {
+                let updates = UpdateBuilder::new()
+                    .set_if_not_exists("created_at", AttributeValue::S(created_at.to_string()))
+                    .set("updated_at", AttributeValue::S(updated_at.to_string()));
+                ddb.update_with(TEST_TABLE_NAME, key.clone(), updates)
+                    .await
+                    .context("Failed to update item")?;
+            }
+
+            // The direct helper must not overwrite the existing value either
+            ddb.set_if_not_exists(
+                TEST_TABLE_NAME,
+                key.clone(),
+                "created_at",
+                AttributeValue::S("third".to_string()),
+            )
+            .await?;
+
+            let item = ddb
+                .get_item(TEST_TABLE_NAME, key.clone())
+                .await?
+                .ok_or_else(|| anyhow::anyhow!("Item not found"))?;
+            assert_eq!(
+                item.get_string("created_at"),
+                Some(&"first".to_string()),
+                "created_at was overwritten"
+            );
+            assert_eq!(
+                item.get_string("updated_at"),
+                Some(&"2".to_string()),
+                "updated_at was not bumped"
+            );
+
+            Ok(())
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_update_rejects_key_attributes() -> Result<()> {
+        run_test("update_rejects_key_attributes", |ddb| async move {
+            let table = setup_test_table(&ddb).await?;
+            let key = Item::new()
+                .set_string(CATEGORY_PARTITION_KEY, "Keys")
+                .set_string(PRODUCT_NAME_SORT_KEY, "Guarded");
+            ddb.put_item(
+                TEST_TABLE_NAME,
+                key.clone().set_number(PRICE_ATTRIBUTE, 1.0),
+            )
+            .await?;
+
+            for key_attribute in [CATEGORY_PARTITION_KEY, PRODUCT_NAME_SORT_KEY] {
+                let updates = Item::new()
+                    .set_number(PRICE_ATTRIBUTE, 2.0)
+                    .set_string(key_attribute, "changed");
+                let err = ddb
+                    .update_table_item(&table, key.clone(), updates)
+                    .await
+                    .expect_err("key attribute update should be rejected");
+                assert!(
+                    err.to_string().contains(key_attribute),
+                    "Error should name '{key_attribute}': {err}"
+                );
+            }
+
+            // Nothing was written by the rejected updates
+            let item = ddb
+                .get_item(TEST_TABLE_NAME, key.clone())
+                .await?
+                .ok_or_else(|| anyhow::anyhow!("Item not found"))?;
+            assert_eq!(item.get_number(PRICE_ATTRIBUTE), Some(1.0));
+
+            // Non-key updates still go through
+            ddb.update_table_item(
+                &table,
+                key.clone(),
+                Item::new().set_number(PRICE_ATTRIBUTE, 3.0),
+            )
+            .await?;
+            let item = ddb
+                .get_item(TEST_TABLE_NAME, key.clone())
+                .await?
+                .ok_or_else(|| anyhow::anyhow!("Item not found"))?;
+            assert_eq!(item.get_number(PRICE_ATTRIBUTE), Some(3.0));
+
+            Ok(())
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_query_all_follows_pages() -> Result<()> {
+        run_local_test("query_all_follows_pages", |ddb| async move {
+            // Twelve ~100 KB items exceed the 1 MB page size
+            let payload = "x".repeat(100_000);
+            for i in 0..12 {
+                let item = Item::new()
+                    .set_string(CATEGORY_PARTITION_KEY, "Large")
+                    .set_string(PRODUCT_NAME_SORT_KEY, format!("Product{:02}", i))
+                    .set_string("payload", payload.as_str());
+                ddb.put_item(TEST_TABLE_NAME, item).await?;
+            }
+
+            let params = || QueryFlexibleParams {
+                table_name: TEST_TABLE_NAME,
+                key_condition_expression: "#pk = :pkval",
+                expression_attribute_names: Some(HashMap::from([(
+                    "#pk".to_string(),
+                    CATEGORY_PARTITION_KEY.to_string(),
+                )])),
+                expression_attribute_values: Some(HashMap::from([(
+                    ":pkval".to_string(),
+                    AttributeValue::S("Large".to_string()),
+                )])),
+                filter_expression: None,
+                projection_expression: None,
+                limit: None,
+                scan_index_forward: None,
+                index_name: None,
+                ..Default::default()
+            };
+
+            let first_page = ddb.query_flexible(params()).await?;
+            assert!(first_page.len() < 12, "Expected a truncated first page");
+
+            let items = ddb.query_all(params(), None).await?;
+            assert_eq!(items.len(), 12);
+
+            let capped = ddb.query_all(params(), Some(5)).await?;
+            assert_eq!(capped.len(), 5);
+
+            Ok(())
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_query_begins_with_timestamp_prefix() -> Result<()> {
+        run_test("query_begins_with_timestamp_prefix", |ddb| async move {
+            let table = Table::new(
+                TEST_TABLE_NAME,
+                CATEGORY_PARTITION_KEY,
+                Some(PRODUCT_NAME_SORT_KEY),
+            );
+            for sort_key in [
+                "2024-01-15T10:00:00#Order1",
+                "2024-01-31T23:59:59#Order2",
+                "2024-02-01T00:00:00#Order3",
+                "2023-12-31T08:30:00#Order4",
+            ] {
+                let item = Item::new()
+                    .set_string(CATEGORY_PARTITION_KEY, "Orders")
+                    .set_string(PRODUCT_NAME_SORT_KEY, sort_key);
+                ddb.put_item(TEST_TABLE_NAME, item).await?;
+            }
+
+            let january = ddb
+                .query_begins_with(&table, AttributeValue::S("Orders".to_string()), "2024-01")
+                .await?;
+            let names: Vec<_> = january
+                .iter()
+                .filter_map(|item| item.get_string(PRODUCT_NAME_SORT_KEY).cloned())
+                .collect();
+            assert_eq!(
+                names,
+                vec!["2024-01-15T10:00:00#Order1", "2024-01-31T23:59:59#Order2"]
+            );
+
+            Ok(())
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_query_between_numeric_sort_key() -> Result<()> {
+        const EVENTS_TABLE_NAME: &str = "testing-events";
+
+        run_local_test("query_between_numeric_sort_key", |ddb| async move {
+            let table = Table::new(EVENTS_TABLE_NAME, "device", Some("timestamp")).with_schema(
+                Schema::new()
+                    .add_field("device", FieldType::String)
+                    .add_field("timestamp", FieldType::Number),
+            );
+            ddb.create_table_if_not_exists(&table).await?;
+            ddb.wait_for_table_active(EVENTS_TABLE_NAME, TABLE_WAIT_TIMEOUT)
+                .await?;
+
+            // Timestamps 50..500; 50 would sort between 200 and 400 if the
+            // key were a string.
+            load_fixture(&*ddb, &table, "sensor_events.json").await?;
+            assert_table_matches_fixture(&*ddb, &table, "sensor_events.json").await?;
+
+            let low = AttributeValue::N("200".to_string());
+            let high = AttributeValue::N("400".to_string());
+            let items = ddb
+                .query_between(
+                    &table,
+                    AttributeValue::S("sensor-1".to_string()),
+                    low.clone(),
+                    high.clone(),
+                )
+                .await?;
+            let timestamps: Vec<_> = items
+                .iter()
+                .filter_map(|item| item.get_number("timestamp"))
+                .collect();
+            assert_eq!(timestamps, vec![200.0, 300.0, 400.0]);
+
+            let pk = ("device", AttributeValue::S("sensor-1".to_string()));
+            let items = ddb
+                .query_simple(
+                    EVENTS_TABLE_NAME,
+                    pk.clone(),
+                    Some((
+                        "timestamp",
+                        "BETWEEN".to_string(),
+                        SortKeyValue::Between(low.clone(), high),
+                    )),
+                    None,
+                    None,
+                    None,
+                    None,
+                )
+                .await?;
+            assert_eq!(items.len(), 3);
+
+            let err = ddb
+                .query_simple(
+                    EVENTS_TABLE_NAME,
+                    pk,
+                    Some(("timestamp", "BETWEEN".to_string(), low.into())),
+                    None,
+                    None,
+                    None,
+                    None,
+                )
+                .await
+                .unwrap_err();
+            assert!(err.to_string().contains("BETWEEN"));
+
+            ddb.delete_table(EVENTS_TABLE_NAME).await?;
+            Ok(())
+        })
+        .await
+    }
+
+    #[test]
+    fn test_key_condition_sort_key_operators() {
+        let key_condition = |condition: &str, value: SortKeyValue| -> Result<_> {
+            KeyCondition::partition(
+                CATEGORY_PARTITION_KEY,
+                AttributeValue::S("Books".to_string()),
+            )
+            .and_sort(
+                PRODUCT_NAME_SORT_KEY,
+                SortKeyCondition::parse(condition, value)?,
+            )
+            .build()
+        };
+        let sk = || AttributeValue::S("M".to_string());
+
+        for (condition, expected) in [
+            ("=", "#pk = :pkval AND #sk = :skval"),
+            ("<", "#pk = :pkval AND #sk < :skval"),
+            ("<=", "#pk = :pkval AND #sk <= :skval"),
+            (">", "#pk = :pkval AND #sk > :skval"),
+            (">=", "#pk = :pkval AND #sk >= :skval"),
+            ("begins_with", "#pk = :pkval AND begins_with(#sk, :skval)"),
+        ] {
+            let key_condition = key_condition(condition, sk().into()).unwrap();
+            assert_eq!(key_condition.expression, expected);
+            assert_eq!(key_condition.names["#pk"], CATEGORY_PARTITION_KEY);
+            assert_eq!(key_condition.names["#sk"], PRODUCT_NAME_SORT_KEY);
+            assert_eq!(key_condition.values.len(), 2);
+        }
+
+        let between = key_condition(
+            "between",
+            SortKeyValue::Between(sk(), AttributeValue::S("T".to_string())),
+        )
+        .unwrap();
+        assert_eq!(
+            between.expression,
+            "#pk = :pkval AND #sk BETWEEN :lo AND :hi"
+        );
+        assert_eq!(between.values.len(), 3);
+
+        for condition in ["<>", "contains", "= :skval OR #pk", ""] {
+            assert!(key_condition(condition, sk().into()).is_err());
+        }
+        assert!(key_condition("BETWEEN", sk().into()).is_err());
+        assert!(key_condition("=", SortKeyValue::Between(sk(), sk())).is_err());
+    }
+
+    #[test]
+    fn test_key_condition_builder() {
+        let n = |n: &str| AttributeValue::N(n.to_string());
+        // Both key names are DynamoDB reserved words.
+        let partition = || KeyCondition::partition("name", AttributeValue::S("sensor".to_string()));
+
+        let cases = [
+            (partition().and_sort_eq("timestamp", n("5")), "#sk = :skval"),
+            (partition().and_sort_lt("timestamp", n("5")), "#sk < :skval"),
+            (
+                partition().and_sort_lt_eq("timestamp", n("5")),
+                "#sk <= :skval",
+            ),
+            (partition().and_sort_gt("timestamp", n("5")), "#sk > :skval"),
+            (
+                partition().and_sort_gt_eq("timestamp", n("5")),
+                "#sk >= :skval",
+            ),
+            (
+                partition().and_sort_begins_with("timestamp", "2024"),
+                "begins_with(#sk, :skval)",
+            ),
+        ];
+        for (builder, sort_clause) in cases {
+            let key_condition = builder.build().unwrap();
+            assert_eq!(
+                key_condition.expression,
+                format!("#pk = :pkval AND {sort_clause}")
+            );
+            assert_eq!(key_condition.names["#pk"], "name");
+            assert_eq!(key_condition.names["#sk"], "timestamp");
+            assert!(key_condition.values.contains_key(":skval"));
+        }
+
+        let between = partition()
+            .and_sort_between("timestamp", n("1"), n("9"))
+            .build()
+            .unwrap();
+        assert_eq!(
+            between.expression,
+            "#pk = :pkval AND #sk BETWEEN :lo AND :hi"
+        );
+        assert_eq!(between.values[":lo"], n("1"));
+        assert_eq!(between.values[":hi"], n("9"));
+
+        let partition_only = partition().build().unwrap();
+        assert_eq!(partition_only.expression, "#pk = :pkval");
+        assert_eq!(partition_only.names.len(), 1);
+
+        let params = QueryFlexibleParams::from_key_condition(TEST_TABLE_NAME, &between);
+        assert_eq!(params.key_condition_expression, between.expression);
+        assert_eq!(
+            params.expression_attribute_names.as_ref(),
+            Some(&between.names)
+        );
+        assert_eq!(
+            params.expression_attribute_values.as_ref(),
+            Some(&between.values)
+        );
+
+        let err = partition()
+            .and_sort_gt("timestamp", n("1"))
+            .and_sort_lt("timestamp", n("9"))
+            .build()
+            .unwrap_err();
+        assert!(err.to_string().contains("only once"));
+    }
+
+    #[test]
+    fn test_filter_rendering() {
+        let n = |n: &str| AttributeValue::N(n.to_string());
+        let s = |s: &str| AttributeValue::S(s.to_string());
+
+        let cases = [
+            (Filter::eq("price", n("1")), "#f0 = :f0"),
+            (Filter::ne("price", n("1")), "#f0 <> :f0"),
+            (Filter::lt("price", n("1")), "#f0 < :f0"),
+            (Filter::gt("price", n("1")), "#f0 > :f0"),
+            (
+                Filter::between("price", n("1"), n("9")),
+                "#f0 BETWEEN :f0 AND :f1",
+            ),
+            (Filter::begins_with("name", "Sm"), "begins_with(#f0, :f0)"),
+            (Filter::contains("tags", s("sale")), "contains(#f0, :f0)"),
+            (
+                Filter::is_in("category", vec![s("a"), s("b"), s("c")]),
+                "#f0 IN (:f0, :f1, :f2)",
+            ),
+            (
+                Filter::attribute_exists("discount"),
+                "attribute_exists(#f0)",
+            ),
+            (
+                Filter::attribute_not_exists("discount"),
+                "attribute_not_exists(#f0)",
+            ),
+            (Filter::size("tags").gt(2), "size(#f0) > :f0"),
+        ];
+        for (filter, expected) in cases {
+            assert_eq!(filter.build().expression, expected);
+        }
+
+        let filter = Filter::gt("price", n("100"))
+            .and(Filter::attribute_exists("discount").or(!Filter::contains("tags", s("sale"))))
+            .and(Filter::lt("price", n("500")))
+            .build();
+        assert_eq!(
+            filter.expression,
+            "((#f0 > :f0 AND (attribute_exists(#f1) OR NOT (contains(#f2, :f1)))) AND #f0 < :f2)"
+        );
+        assert_eq!(filter.names.len(), 3, "price should reuse one placeholder");
+        assert_eq!(filter.names["#f0"], "price");
+        assert_eq!(filter.values[":f2"], n("500"));
+    }
+
+    #[test]
+    fn test_filter_merges_with_key_condition() {
+        let key_condition = KeyCondition::partition(
+            CATEGORY_PARTITION_KEY,
+            AttributeValue::S("Electronics".to_string()),
+        )
+        .and_sort_begins_with(PRODUCT_NAME_SORT_KEY, "Smart")
+        .build()
+        .unwrap();
+        // Filtering on the sort key attribute still gets its own placeholder.
+        let filter = Filter::gt(PRICE_ATTRIBUTE, AttributeValue::N("100".to_string()))
+            .and(Filter::ne(
+                PRODUCT_NAME_SORT_KEY,
+                AttributeValue::S("Smartwatch".to_string()),
+            ))
+            .build();
+
+        let params = QueryFlexibleParams::from_key_condition(TEST_TABLE_NAME, &key_condition)
+            .with_filter(&filter)
+            .unwrap();
+        let names = params.expression_attribute_names.as_ref().unwrap();
+        let values = params.expression_attribute_values.as_ref().unwrap();
+        assert_eq!(names.len(), key_condition.names.len() + filter.names.len());
+        assert_eq!(
+            values.len(),
+            key_condition.values.len() + filter.values.len()
+        );
+        assert_eq!(params.filter_expression, Some(filter.expression.as_str()));
+        assert_eq!(names["#sk"], PRODUCT_NAME_SORT_KEY);
+        assert_eq!(names["#f1"], PRODUCT_NAME_SORT_KEY);
+
+        // A hand-written placeholder that clashes with the filter is rejected.
+        let clashing = ScanParams {
+            table_name: TEST_TABLE_NAME,
+            expression_attribute_values: Some(HashMap::from([(
+                ":f0".to_string(),
+                AttributeValue::N("1".to_string()),
+            )])),
+            ..Default::default()
+        };
+        assert!(clashing.with_filter(&filter).is_err());
+
+        let already_filtered = ScanParams {
+            table_name: TEST_TABLE_NAME,
+            filter_expression: Some("attribute_exists(price)"),
+            ..Default::default()
+        };
+        assert!(already_filtered.with_filter(&filter).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_query_index_rejects_clashing_placeholders() {
+        // Rejected before any request is sent, so nothing needs to listen.
+        let ddb = DynamoDb::new_local("http://127.0.0.1:9");
+        let query = |names: Option<HashMap<String, String>>,
+                     values: Option<HashMap<String, AttributeValue>>| {
+            ddb.query_index(
+                TEST_TABLE_NAME,
+                "brand-index",
+                ("brand", AttributeValue::S("Acme".to_string())),
+                None,
+                QueryOptions {
+                    filter_expression: Some("#pk = :pkval"),
+                    expression_attribute_names: names,
+                    expression_attribute_values: values,
+                    ..Default::default()
+                },
+            )
+        };
+
+        let error = query(
+            Some(HashMap::from([(
+                "#pk".to_string(),
+                CATEGORY_PARTITION_KEY.to_string(),
+            )])),
+            None,
+        )
+        .await
+        .expect_err("Clashing name placeholder was accepted");
+        assert!(error.to_string().contains("'#pk'"), "{error}");
+
+        let error = query(
+            None,
+            Some(HashMap::from([(
+                ":pkval".to_string(),
+                AttributeValue::S("Electronics".to_string()),
+            )])),
+        )
+        .await
+        .expect_err("Clashing value placeholder was accepted");
+        assert!(error.to_string().contains("':pkval'"), "{error}");
+    }
+
+    #[tokio::test]
+    async fn test_filter_in_queries_and_scans() -> Result<()> {
+        run_test("filter_in_queries_and_scans", |ddb| async move {
+            for (name, price, tagged) in [
+                ("Alpha", 50.0, true),
+                ("Bravo", 150.0, true),
+                ("Charlie", 250.0, false),
+                ("Delta", 350.0, true),
+            ] {
+                let mut item = Item::new()
+                    .set_string(CATEGORY_PARTITION_KEY, "Filtered")
+                    .set_string(PRODUCT_NAME_SORT_KEY, name)
+                    .set_number(PRICE_ATTRIBUTE, price);
+                if tagged {
+                    item = item.set_string("tag", "filter-test");
+                }
+                ddb.put_item(TEST_TABLE_NAME, item).await?;
+            }
+
+            let filter = Filter::gt(PRICE_ATTRIBUTE, AttributeValue::N("100".to_string()))
+                .and(Filter::eq(
+                    "tag",
+                    AttributeValue::S("filter-test".to_string()),
+                ))
+                .build();
+            let names = |items: Vec<Item>| -> Vec<String> {
+                let mut names: Vec<_> = items
+                    .iter()
+                    .filter_map(|item| item.get_string(PRODUCT_NAME_SORT_KEY).cloned())
+                    .collect();
+                names.sort();
+                names
+            };
+            let expected = vec!["Bravo".to_string(), "Delta".to_string()];
+
+            let key_condition = KeyCondition::partition(
+                CATEGORY_PARTITION_KEY,
+                AttributeValue::S("Filtered".to_string()),
+            )
+            .build()?;
+            let params = QueryFlexibleParams::from_key_condition(TEST_TABLE_NAME, &key_condition)
+                .with_filter(&filter)?;
+            assert_eq!(names(ddb.query_flexible(params).await?), expected);
+
+            let items = ddb
+                .query_simple(
+                    TEST_TABLE_NAME,
+                    (
+                        CATEGORY_PARTITION_KEY,
+                        AttributeValue::S("Filtered".to_string()),
+                    ),
+                    None,
+                    Some("price < :max"),
+                    None,
+                    Some(HashMap::from([(
+                        ":max".to_string(),
+                        AttributeValue::N("300".to_string()),
+                    )])),
+                    Some(&filter),
+                )
+                .await?;
+            assert_eq!(names(items), vec!["Bravo".to_string()]);
+
+            let scan_params = || {
+                ScanParams {
+                    table_name: TEST_TABLE_NAME,
+                    ..Default::default()
+                }
+                .with_filter(&filter)
+            };
+            assert_eq!(names(ddb.scan(scan_params()?).await?), expected);
+            let (items, _) = ddb.scan_paginated(scan_params()?, None).await?;
+            assert_eq!(names(items), expected);
+
+            Ok(())
+        })
+        .await
+    }
+
+    #[test]
+    fn test_projection_aliases_every_attribute() {
+        let projection =
+            ProjectionExpression::new(&["name", "status", "address.city", "tags[0]", "name"])
+                .unwrap();
+        assert_eq!(projection.expression, "#p0, #p1, #p2.#p3, #p4[0], #p0");
+        assert_eq!(projection.names.len(), 5);
+        assert_eq!(projection.names["#p0"], "name");
+        assert_eq!(projection.names["#p2"], "address");
+        assert_eq!(projection.names["#p3"], "city");
+        assert_eq!(projection.names["#p4"], "tags");
+        // Projecting nothing isn't a projection DynamoDB would accept.
+        assert!(ProjectionExpression::new(&[]).is_none());
+
+        let key_condition = KeyCondition::partition(
+            CATEGORY_PARTITION_KEY,
+            AttributeValue::S("Electronics".to_string()),
+        )
+        .build()
+        .unwrap();
+        let params = QueryFlexibleParams::from_key_condition(TEST_TABLE_NAME, &key_condition)
+            .with_projection(&projection)
+            .unwrap();
+        assert_eq!(
+            params.expression_attribute_names.as_ref().map(HashMap::len),
+            Some(6)
+        );
+        assert!(params.with_projection(&projection).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_projection_of_reserved_words() -> Result<()> {
+        run_test("projection_of_reserved_words", |ddb| async move {
+            let key = || {
+                Item::new()
+                    .set_string(CATEGORY_PARTITION_KEY, "Reserved")
+                    .set_string(PRODUCT_NAME_SORT_KEY, "Widget")
+            };
+            ddb.put_item(
+                TEST_TABLE_NAME,
+                key()
+                    .set_string("name", "Blue widget")
+                    .set_string("status", "active")
+                    .set_number(PRICE_ATTRIBUTE, 10.0),
+            )
+            .await?;
+
+            let assert_projected = |item: &Item| {
+                assert_eq!(
+                    item.get_string("name").map(String::as_str),
+                    Some("Blue widget")
+                );
+                assert_eq!(
+                    item.get_string("status").map(String::as_str),
+                    Some("active")
+                );
+                assert!(item.get_number(PRICE_ATTRIBUTE).is_none());
+            };
+            let projection = ProjectionExpression::new(&["name", "status"]).unwrap();
+
+            let item = ddb
+                .get_item_projected(TEST_TABLE_NAME, key(), &["name", "status"])
+                .await?
+                .expect("item should exist");
+            assert_projected(&item);
+            // No attributes means no projection, rather than a rejected request.
+            let item = ddb
+                .get_item_projected(TEST_TABLE_NAME, key(), &[])
+                .await?
+                .expect("item should exist");
+            assert_eq!(item.get_number(PRICE_ATTRIBUTE), Some(10.0));
+
+            let key_condition = KeyCondition::partition(
+                CATEGORY_PARTITION_KEY,
+                AttributeValue::S("Reserved".to_string()),
+            )
+            .build()?;
+            let items = ddb
+                .query_flexible(
+                    QueryFlexibleParams::from_key_condition(TEST_TABLE_NAME, &key_condition)
+                        .with_projection(&projection)?,
+                )
+                .await?;
+            assert_eq!(items.len(), 1);
+            assert_projected(&items[0]);
+
+            let filter = Filter::eq("status", AttributeValue::S("active".to_string())).build();
+            let params = ScanParams {
+                table_name: TEST_TABLE_NAME,
+                ..Default::default()
+            }
+            .with_projection(&projection)?
+            .with_filter(&filter)?;
+            let (items, _) = ddb.scan_paginated(params, None).await?;
+            assert_eq!(items.len(), 1);
+            assert_projected(&items[0]);
+
+            Ok(())
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_scan_where_in_and_contains() -> Result<()> {
+        run_test("scan_where_in_and_contains", |ddb| async move {
+            let s = |s: &str| AttributeValue::S(s.to_string());
+            for (category, name, price, tags) in [
+                ("InGarden", "Rake", 20.0, vec!["outdoor", "sale"]),
+                ("InGarden", "Hose", 40.0, vec!["outdoor"]),
+                ("InKitchen", "Pan", 60.0, vec!["sale"]),
+                ("InOffice", "Stapler", 5.0, vec!["sale"]),
+                ("InToys", "Kite", 15.0, vec!["outdoor", "sale"]),
+            ] {
+                let mut item = Item::new()
+                    .set_string(CATEGORY_PARTITION_KEY, category)
+                    .set_string(PRODUCT_NAME_SORT_KEY, name)
+                    .set_number(PRICE_ATTRIBUTE, price);
+                item.attributes.insert(
+                    "tags".to_string(),
+                    AttributeValue::L(tags.into_iter().map(s).collect()),
+                );
+                ddb.put_item(TEST_TABLE_NAME, item).await?;
+            }
+            let names = |items: Vec<Item>| -> Vec<String> {
+                let mut names: Vec<_> = items
+                    .iter()
+                    .filter_map(|item| item.get_string(PRODUCT_NAME_SORT_KEY).cloned())
+                    .collect();
+                names.sort();
+                names
+            };
+
+            let selected = ddb
+                .scan_where_in(
+                    TEST_TABLE_NAME,
+                    CATEGORY_PARTITION_KEY,
+                    vec![s("InGarden"), s("InKitchen")],
+                    None,
+                )
+                .await?;
+            assert_eq!(names(selected), vec!["Hose", "Pan", "Rake"]);
+
+            let cheap = Filter::lt(PRICE_ATTRIBUTE, AttributeValue::N("50".to_string()));
+            let selected = ddb
+                .scan_where_in(
+                    TEST_TABLE_NAME,
+                    CATEGORY_PARTITION_KEY,
+                    vec![s("InGarden"), s("InKitchen"), s("InToys")],
+                    Some(cheap),
+                )
+                .await?;
+            assert_eq!(names(selected), vec!["Hose", "Kite", "Rake"]);
+
+            let on_sale_outdoors = ddb
+                .scan_where_contains(
+                    TEST_TABLE_NAME,
+                    "tags",
+                    s("sale"),
+                    Some(Filter::contains("tags", s("outdoor"))),
+                )
+                .await?;
+            assert_eq!(names(on_sale_outdoors), vec!["Kite", "Rake"]);
+
+            assert!(ddb
+                .scan_where_in(TEST_TABLE_NAME, CATEGORY_PARTITION_KEY, vec![], None)
+                .await
+                .is_err());
+
+            Ok(())
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_query_with_sort_key_condition() -> Result<()> {
+        run_local_test("query_with_sort_key_condition", |ddb| async move {
+            for (category, name) in [
+                ("Books", "Dune"),
+                ("Books", "Emma"),
+                ("Books", "Ulysses"),
+                ("Games", "Go"),
+            ] {
+                let item = Item::new()
+                    .set_string(CATEGORY_PARTITION_KEY, category)
+                    .set_string(PRODUCT_NAME_SORT_KEY, name);
+                ddb.put_item(TEST_TABLE_NAME, item).await?;
+            }
+
+            // The partition key clause used to be dropped once a sort key
+            // condition was added, letting "Go" from another partition through.
+            let key_condition = KeyCondition::partition(
+                CATEGORY_PARTITION_KEY,
+                AttributeValue::S("Books".to_string()),
+            )
+            .and_sort_gt_eq(PRODUCT_NAME_SORT_KEY, AttributeValue::S("E".to_string()))
+            .build()?;
+            let items = ddb
+                .query_all(
+                    QueryFlexibleParams::from_key_condition(TEST_TABLE_NAME, &key_condition),
+                    None,
+                )
+                .await?;
+            let names: Vec<_> = items
+                .iter()
+                .filter_map(|item| item.get_string(PRODUCT_NAME_SORT_KEY).cloned())
+                .collect();
+            assert_eq!(names, vec!["Emma", "Ulysses"]);
+
+            Ok(())
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_max_items_across_filtered_pages() -> Result<()> {
+        run_test("max_items_across_filtered_pages", |ddb| async move {
+            // Only every tenth product passes the filter, so each two-item page
+            // usually yields nothing and max_items has to span many pages. The
+            // marker attribute keeps the scan from matching other tests' items.
+            for i in 0..40 {
+                let mut item = Item::new()
+                    .set_string(CATEGORY_PARTITION_KEY, "Sparse")
+                    .set_string(PRODUCT_NAME_SORT_KEY, format!("Product{:02}", i));
+                if i % 10 == 0 {
+                    item = item.set_string("marker", "max-items");
+                }
+                ddb.put_item(TEST_TABLE_NAME, item).await?;
+            }
+
+            let marker = || {
+                (
+                    ":marker".to_string(),
+                    AttributeValue::S("max-items".to_string()),
+                )
+            };
+            let query_params = || QueryFlexibleParams {
+                table_name: TEST_TABLE_NAME,
+                key_condition_expression: "#pk = :pkval",
+                expression_attribute_names: Some(HashMap::from([(
+                    "#pk".to_string(),
+                    CATEGORY_PARTITION_KEY.to_string(),
+                )])),
+                expression_attribute_values: Some(HashMap::from([
+                    (
+                        ":pkval".to_string(),
+                        AttributeValue::S("Sparse".to_string()),
+                    ),
+                    marker(),
+                ])),
+                filter_expression: Some("marker = :marker"),
+                projection_expression: None,
+                limit: Some(2),
+                scan_index_forward: None,
+                index_name: None,
+                ..Default::default()
+            };
+            assert_eq!(ddb.query_all(query_params(), Some(3)).await?.len(), 3);
+            assert_eq!(ddb.query_all(query_params(), Some(10)).await?.len(), 4);
+
+            let scan_params = || ScanParams {
+                table_name: TEST_TABLE_NAME,
+                filter_expression: Some("marker = :marker"),
+                expression_attribute_values: Some(HashMap::from([marker()])),
+                limit: Some(2),
+                ..Default::default()
+            };
+            assert_eq!(ddb.scan_all(scan_params(), Some(3), None).await?.len(), 3);
+            assert_eq!(ddb.scan_all(scan_params(), Some(10), None).await?.len(), 4);
+
+            // Progress is reported once per page, with running totals.
+            let mut progress = Vec::new();
+            let items = ddb
+                .scan_all(
+                    scan_params(),
+                    None,
+                    Some(&mut |p: &Progress| progress.push(*p)),
+                )
+                .await?;
+            assert_eq!(items.len(), 4);
+            assert!(progress.len() >= 2);
+            for (page, p) in progress.iter().enumerate() {
+                assert_eq!(p.pages, page + 1);
+                assert!(p.items <= 2 * p.pages);
+            }
+            assert!(progress.windows(2).all(|pair| {
+                pair[0].items <= pair[1].items && pair[0].elapsed <= pair[1].elapsed
+            }));
+            assert_eq!(progress.last().map(|p| p.items), Some(4));
+
+            Ok(())
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_query_latest() -> Result<()> {
+        run_test("query_latest", |ddb| async move {
+            let table = Table::new(
+                TEST_TABLE_NAME,
+                CATEGORY_PARTITION_KEY,
+                Some(PRODUCT_NAME_SORT_KEY),
+            );
+            for i in 1..=8 {
+                let item = Item::new()
+                    .set_string(CATEGORY_PARTITION_KEY, "Events")
+                    .set_string(PRODUCT_NAME_SORT_KEY, format!("2024-01-0{i}T00:00:00"));
+                ddb.put_item(TEST_TABLE_NAME, item).await?;
+            }
+
+            let pk_value = || AttributeValue::S("Events".to_string());
+            let latest = ddb.query_latest(&table, pk_value(), 3).await?;
+            let sort_keys: Vec<_> = latest
+                .iter()
+                .filter_map(|item| item.get_string(PRODUCT_NAME_SORT_KEY).cloned())
+                .collect();
+            assert_eq!(
+                sort_keys,
+                vec![
+                    "2024-01-08T00:00:00",
+                    "2024-01-07T00:00:00",
+                    "2024-01-06T00:00:00"
+                ]
+            );
+
+            assert_eq!(ddb.query_latest(&table, pk_value(), 20).await?.len(), 8);
+            assert!(ddb.query_latest(&table, pk_value(), 0).await.is_err());
+
+            Ok(())
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_query_index() -> Result<()> {
+        const INDEXED_TABLE_NAME: &str = "testing-products-query-index";
+        const BRAND_INDEX: &str = "brand-index";
+
+        run_test("query_index", |ddb| async move {
+            let table = Table::new(
+                INDEXED_TABLE_NAME,
+                CATEGORY_PARTITION_KEY,
+                Some(PRODUCT_NAME_SORT_KEY),
+            )
+            .with_global_secondary_index(
+                BRAND_INDEX,
+                "brand",
+                Some(PRODUCT_NAME_SORT_KEY),
+            );
+            ddb.create_table_if_not_exists(&table).await?;
+            ddb.wait_for_table_active(INDEXED_TABLE_NAME, TABLE_WAIT_TIMEOUT)
+                .await?;
+
+            for (category, name, brand) in [
+                ("Electronics", "Laptop", "Acme"),
+                ("Appliances", "Toaster", "Acme"),
+                ("Electronics", "Phone", "Globex"),
+            ] {
+                let item = Item::new()
+                    .set_string(CATEGORY_PARTITION_KEY, category)
+                    .set_string(PRODUCT_NAME_SORT_KEY, name)
+                    .set_string("brand", brand);
+                ddb.put_item(INDEXED_TABLE_NAME, item).await?;
+            }
+
+            let acme = ("brand", AttributeValue::S("Acme".to_string()));
+            let items = ddb
+                .query_index(
+                    INDEXED_TABLE_NAME,
+                    BRAND_INDEX,
+                    acme.clone(),
+                    None,
+                    QueryOptions::default(),
+                )
+                .await?;
+            let names: Vec<_> = items
+                .iter()
+                .filter_map(|item| item.get_string(PRODUCT_NAME_SORT_KEY).cloned())
+                .collect();
+            assert_eq!(names, vec!["Laptop", "Toaster"]);
+
+            let items = ddb
+                .query_index(
+                    INDEXED_TABLE_NAME,
+                    BRAND_INDEX,
+                    acme,
+                    Some((
+                        PRODUCT_NAME_SORT_KEY,
+                        "begins_with".to_string(),
+                        AttributeValue::S("Toa".to_string()).into(),
+                    )),
+                    QueryOptions {
+                        filter_expression: Some("#category = :category"),
+                        expression_attribute_names: Some(HashMap::from([(
+                            "#category".to_string(),
+                            CATEGORY_PARTITION_KEY.to_string(),
+                        )])),
+                        expression_attribute_values: Some(HashMap::from([(
+                            ":category".to_string(),
+                            AttributeValue::S("Appliances".to_string()),
+                        )])),
+                        ..Default::default()
+                    },
+                )
+                .await?;
+            assert_eq!(items.len(), 1);
+            assert_eq!(
+                items[0]
+                    .get_string(PRODUCT_NAME_SORT_KEY)
+                    .map(String::as_str),
+                Some("Toaster")
+            );
+
+            ddb.delete_table(INDEXED_TABLE_NAME).await?;
+            Ok(())
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_query_paginated() -> Result<()> {
+        run_test("query_paginated", |ddb| async move {
+            for i in 0..5 {
+                let item = Item::new()
+                    .set_string(CATEGORY_PARTITION_KEY, "Paged")
+                    .set_string(PRODUCT_NAME_SORT_KEY, format!("Product{}", i))
+                    .set_number(PRICE_ATTRIBUTE, i as f64);
+                ddb.put_item(TEST_TABLE_NAME, item).await?;
+            }
+
+            let mut seen = Vec::new();
+            let mut pages = 0;
+            let mut exclusive_start_key = None;
+            loop {
+                let params = QueryFlexibleParams {
+                    table_name: TEST_TABLE_NAME,
+                    key_condition_expression: "#pk = :pkval",
+                    expression_attribute_names: Some(HashMap::from([(
+                        "#pk".to_string(),
+                        CATEGORY_PARTITION_KEY.to_string(),
+                    )])),
+                    expression_attribute_values: Some(HashMap::from([(
+                        ":pkval".to_string(),
+                        AttributeValue::S("Paged".to_string()),
+                    )])),
+                    filter_expression: None,
+                    projection_expression: None,
+                    limit: Some(2),
+                    scan_index_forward: None,
+                    index_name: None,
+                    ..Default::default()
+                };
+                let (items, last_evaluated_key) =
+                    ddb.query_paginated(params, exclusive_start_key).await?;
+                pages += 1;
+                seen.extend(
+                    items
+                        .iter()
+                        .filter_map(|item| item.get_string(PRODUCT_NAME_SORT_KEY).cloned()),
+                );
+
+                exclusive_start_key = last_evaluated_key;
+                if exclusive_start_key.is_none() {
+                    break;
+                }
+            }
+
+            assert_eq!(pages, 3, "Expected three pages of two items");
+            let expected: Vec<_> = (0..5).map(|i| format!("Product{}", i)).collect();
+            assert_eq!(seen, expected, "Items were repeated or skipped");
+
+            Ok(())
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_paginate_with_page_tokens() -> Result<()> {
+        run_test("paginate_with_page_tokens", |ddb| async move {
+            const SIGNING_KEY: &[u8] = b"test signing key";
+
+            for i in 0..5 {
+                let item = Item::new()
+                    .set_string(CATEGORY_PARTITION_KEY, "Tokened")
+                    .set_string(PRODUCT_NAME_SORT_KEY, format!("Product{}", i))
+                    .set_number(PRICE_ATTRIBUTE, i as f64);
+                ddb.put_item(TEST_TABLE_NAME, item).await?;
+            }
+
+            let key_condition = KeyCondition::partition(
+                CATEGORY_PARTITION_KEY,
+                AttributeValue::S("Tokened".to_string()),
+            )
+            .build()?;
+
+            let mut seen = Vec::new();
+            let mut tokens = Vec::new();
+            let mut page_token: Option<String> = None;
+            loop {
+                let params = QueryFlexibleParams {
+                    limit: Some(2),
+                    ..QueryFlexibleParams::from_key_condition(TEST_TABLE_NAME, &key_condition)
+                };
+                let (items, next_token) = ddb
+                    .query_paginated_with_token(params, page_token.as_deref(), Some(SIGNING_KEY))
+                    .await?;
+                seen.extend(
+                    items
+                        .iter()
+                        .filter_map(|item| item.get_string(PRODUCT_NAME_SORT_KEY).cloned()),
+                );
+
+                match next_token {
+                    Some(token) => {
+                        tokens.push(token.clone());
+                        page_token = Some(token);
+                    }
+                    None => break,
+                }
+            }
+
+            assert_eq!(tokens.len(), 2, "Expected three pages of two items");
+            let expected: Vec<_> = (0..5).map(|i| format!("Product{}", i)).collect();
+            assert_eq!(seen, expected, "Items were repeated or skipped");
+
+            // A token signed with a different key is rejected before any request is made.
+            let params = QueryFlexibleParams::from_key_condition(TEST_TABLE_NAME, &key_condition);
+            let result = ddb
+                .query_paginated_with_token(params, Some(&tokens[0]), Some(b"another key"))
+                .await;
+            assert!(
+                result.is_err(),
+                "Token signed with another key was accepted"
+            );
+
+            // Scans page through the same tokens.
+            let filter = Filter::eq(
+                CATEGORY_PARTITION_KEY,
+                AttributeValue::S("Tokened".to_string()),
+            )
+            .build();
+            let mut scanned = 0;
+            let mut page_token: Option<String> = None;
+            loop {
+                let params = ScanParams {
+                    table_name: TEST_TABLE_NAME,
+                    limit: Some(2),
+                    ..Default::default()
+                }
+                .with_filter(&filter)?;
+                let (items, next_token) = ddb
+                    .scan_paginated_with_token(params, page_token.as_deref(), None)
+                    .await?;
+                scanned += items.len();
+                page_token = next_token;
+                if page_token.is_none() {
+                    break;
+                }
+            }
+            assert_eq!(scanned, 5, "Scan did not return every item");
+
+            Ok(())
+        })
+        .await
+    }
+
+    #[test]
+    fn test_page_token_round_trip_and_tampering() -> Result<()> {
+        let key = HashMap::from([
+            (
+                CATEGORY_PARTITION_KEY.to_string(),
+                AttributeValue::S("Electronics".to_string()),
+            ),
+            ("timestamp".to_string(), AttributeValue::N("42".to_string())),
+            (
+                "digest".to_string(),
+                AttributeValue::B(Blob::new(vec![0, 159, 255])),
+            ),
+        ]);
+
+        let token = PageToken::encode(&key)?;
+        assert!(
+            token
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'),
+            "Token is not URL-safe: {token}"
+        );
+        assert_eq!(PageToken::decode(&token)?, key);
+
+        let signed = PageToken::encode_signed(&key, b"secret")?;
+        assert_eq!(PageToken::decode_signed(&signed, b"secret")?, key);
+        assert!(PageToken::decode_signed(&signed, b"wrong").is_err());
+        assert!(PageToken::decode_signed(&token, b"secret").is_err());
+        assert!(PageToken::decode(&signed).is_err());
+
+        // Swap in a different payload while keeping the original signature.
+        let (_, signature) = signed.split_once('.').unwrap();
+        let forged_key = HashMap::from([(
+            CATEGORY_PARTITION_KEY.to_string(),
+            AttributeValue::S("Books".to_string()),
+        )]);
+        let forged = format!("{}.{signature}", PageToken::encode(&forged_key)?);
+        assert!(PageToken::decode_signed(&forged, b"secret").is_err());
+
+        assert!(PageToken::decode("not a token!").is_err());
+        assert!(PageToken::encode(&HashMap::from([(
+            "flag".to_string(),
+            AttributeValue::Bool(true)
+        )]))
+        .is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_query_flexible_detailed_counts() -> Result<()> {
+        run_test("query_flexible_detailed_counts", |ddb| async move {
+            for i in 0..10 {
+                let item = Item::new()
+                    .set_string(CATEGORY_PARTITION_KEY, "Diagnosed")
+                    .set_string(PRODUCT_NAME_SORT_KEY, format!("Product{}", i))
+                    .set_number(PRICE_ATTRIBUTE, i as f64);
+                ddb.put_item(TEST_TABLE_NAME, item).await?;
+            }
+
+            let key_condition = KeyCondition::partition(
+                CATEGORY_PARTITION_KEY,
+                AttributeValue::S("Diagnosed".to_string()),
+            )
+            .build()?;
+            let filter = Filter::eq(PRICE_ATTRIBUTE, AttributeValue::N("3".to_string())).build();
+            let params = QueryFlexibleParams::from_key_condition(TEST_TABLE_NAME, &key_condition)
+                .with_filter(&filter)?;
+
+            let result = ddb.query_flexible_detailed(params, None).await?;
+            assert_eq!(result.count, 1, "Expected the filter to keep one item");
+            assert_eq!(result.items.len(), 1);
+            assert_eq!(result.scanned_count, 10, "Expected every item to be read");
+            assert!(result.scanned_count > result.count);
+            assert!(result.last_evaluated_key.is_none());
+
+            let params = QueryFlexibleParams {
+                limit: Some(4),
+                ..QueryFlexibleParams::from_key_condition(TEST_TABLE_NAME, &key_condition)
+            };
+            let result = ddb.query_flexible_detailed(params, None).await?;
+            assert_eq!(result.count, 4);
+            assert!(
+                result.last_evaluated_key.is_some(),
+                "Expected a key to resume from"
+            );
+
+            Ok(())
+        })
+        .await
+    }
+
+    #[test]
+    fn test_scan_rate_limit_delay() -> Result<()> {
+        let stats = ScanStats {
+            pages: 1,
+            items: 100,
+            elapsed: Duration::from_millis(500),
+            consumed_capacity: 10.0,
+        };
+        // 10 units at 5 RCU/s need two seconds; half a second has passed.
+        assert_eq!(stats.delay_for_target(5.0)?, Duration::from_millis(1500));
+        assert_eq!(stats.average_rcu_per_sec(), 20.0);
+
+        // Already under budget, so no pause.
+        assert_eq!(stats.delay_for_target(100.0)?, Duration::ZERO);
+
+        let idle = ScanStats::default();
+        assert_eq!(idle.delay_for_target(1.0)?, Duration::ZERO);
+        assert_eq!(idle.average_rcu_per_sec(), 0.0);
+
+        // Targets that can't pace a scan are errors rather than panics.
+        for target in [0.0, -1.0, f64::NAN, f64::INFINITY] {
+            let error = stats.delay_for_target(target).unwrap_err();
+            assert!(error.to_string().contains("positive number"), "{error}");
+        }
+        let error = stats.delay_for_target(f64::MIN_POSITIVE).unwrap_err();
+        assert!(error.to_string().contains("too small"), "{error}");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_scan_rate_limited() -> Result<()> {
+        run_test("scan_rate_limited", |ddb| async move {
+            for i in 0..6 {
+                let item = Item::new()
+                    .set_string(CATEGORY_PARTITION_KEY, "Throttled")
+                    .set_string(PRODUCT_NAME_SORT_KEY, format!("Product{}", i))
+                    .set_number(PRICE_ATTRIBUTE, i as f64);
+                ddb.put_item(TEST_TABLE_NAME, item).await?;
+            }
+
+            let filter = Filter::eq(
+                CATEGORY_PARTITION_KEY,
+                AttributeValue::S("Throttled".to_string()),
+            )
+            .build();
+            let params = ScanParams {
+                table_name: TEST_TABLE_NAME,
+                limit: Some(2),
+                ..Default::default()
+            }
+            .with_filter(&filter)?;
+
+            let mut progress = Vec::new();
+            let (items, stats) = ddb
+                .scan_rate_limited(params, 1000.0, |stats| progress.push(*stats))
+                .await?;
+
+            assert_eq!(items.len(), 6, "Expected every matching item");
+            assert_eq!(
+                progress.len(),
+                stats.pages,
+                "Expected one callback per page"
+            );
+            assert_eq!(progress.last(), Some(&stats));
+            assert!(stats.pages >= 3, "Expected the page size to apply");
+            assert!(
+                stats.average_rcu_per_sec() <= 1000.0,
+                "Average consumption exceeded the target"
+            );
+
+            let params = ScanParams {
+                table_name: TEST_TABLE_NAME,
+                ..Default::default()
+            };
+            assert!(ddb.scan_rate_limited(params, 0.0, |_| {}).await.is_err());
+
+            Ok(())
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_query_and_scan_count() -> Result<()> {
+        run_test("query_and_scan_count", |ddb| async move {
+            for i in 0..30 {
+                let item = Item::new()
+                    .set_string(CATEGORY_PARTITION_KEY, "Counted")
+                    .set_string(PRODUCT_NAME_SORT_KEY, format!("Product{:02}", i))
+                    .set_number(PRICE_ATTRIBUTE, i as f64);
+                ddb.put_item(TEST_TABLE_NAME, item).await?;
+            }
+
+            let names = HashMap::from([("#pk".to_string(), CATEGORY_PARTITION_KEY.to_string())]);
+            let values = HashMap::from([(
+                ":pkval".to_string(),
+                AttributeValue::S("Counted".to_string()),
+            )]);
+
+            // Small pages force the count to be summed across several requests
+            let total = ddb
+                .query_count(QueryFlexibleParams {
+                    table_name: TEST_TABLE_NAME,
+                    key_condition_expression: "#pk = :pkval",
+                    expression_attribute_names: Some(names.clone()),
+                    expression_attribute_values: Some(values.clone()),
+                    filter_expression: None,
+                    projection_expression: None,
+                    limit: Some(7),
+                    scan_index_forward: None,
+                    index_name: None,
+                    ..Default::default()
+                })
+                .await?;
+            assert_eq!(
+                total,
+                ItemCount {
+                    count: 30,
+                    scanned_count: 30
+                }
+            );
+
+            let mut filtered_values = values.clone();
+            filtered_values.insert(
+                ":min_price".to_string(),
+                AttributeValue::N("19".to_string()),
+            );
+            let filtered = ddb
+                .query_count(QueryFlexibleParams {
+                    table_name: TEST_TABLE_NAME,
+                    key_condition_expression: "#pk = :pkval",
+                    expression_attribute_names: Some(names.clone()),
+                    expression_attribute_values: Some(filtered_values),
+                    filter_expression: Some("price > :min_price"),
+                    projection_expression: None,
+                    limit: Some(7),
+                    scan_index_forward: None,
+                    index_name: None,
+                    ..Default::default()
+                })
+                .await?;
+            assert_eq!(
+                filtered,
+                ItemCount {
+                    count: 10,
+                    scanned_count: 30
+                }
+            );
+
+            let scanned = ddb
+                .scan_count(
+                    TEST_TABLE_NAME,
+                    Some("#pk = :pkval"),
+                    Some(names),
+                    Some(values),
+                )
+                .await?;
+            assert_eq!(scanned.count, 30);
+
+            Ok(())
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_item_exists() -> Result<()> {
+        run_test("item_exists", |ddb| async move {
+            let table = setup_test_table(&ddb).await?;
+            let key = Item::new()
+                .set_string(CATEGORY_PARTITION_KEY, "Exists")
+                .set_string(PRODUCT_NAME_SORT_KEY, "Seeded");
+            ddb.put_item(
+                TEST_TABLE_NAME,
+                key.clone().set_number(PRICE_ATTRIBUTE, 1.0),
+            )
+            .await?;
+
+            assert!(ddb.item_exists(&table, key.clone(), None).await?);
+            assert!(ddb.item_exists(&table, key.clone(), Some(true)).await?);
+
+            let missing = Item::new()
+                .set_string(CATEGORY_PARTITION_KEY, "Exists")
+                .set_string(PRODUCT_NAME_SORT_KEY, "Missing");
+            assert!(!ddb.item_exists(&table, missing, Some(true)).await?);
+
+            Ok(())
+        })
+        .await
+    }
+
+    #[cfg(feature = "serde")]
+    #[tokio::test]
+    async fn test_typed_put_and_get() -> Result<()> {
+        run_test("typed_put_and_get", |ddb| async move {
+            let products = [
+                Product {
+                    category: "Typed".to_string(),
+                    product_name: "Discounted".to_string(),
+                    price: 19.5,
+                    discount: Some(0.25),
+                },
+                Product {
+                    category: "Typed".to_string(),
+                    product_name: "FullPrice".to_string(),
+                    price: 42.0,
+                    discount: None,
+                },
+            ];
+
+            for product in &products {
+                ddb.put_item_typed(TEST_TABLE_NAME, product).await?;
+
+                let key = Item::new()
+                    .set_string(CATEGORY_PARTITION_KEY, &product.category)
+                    .set_string(PRODUCT_NAME_SORT_KEY, &product.product_name);
+                let fetched: Option<Product> = ddb.get_item_typed(TEST_TABLE_NAME, key).await?;
+                assert_eq!(fetched.as_ref(), Some(product), "Round trip mismatch");
+            }
+
+            let missing = Item::new()
+                .set_string(CATEGORY_PARTITION_KEY, "Typed")
+                .set_string(PRODUCT_NAME_SORT_KEY, "Missing");
+            let fetched: Option<Product> = ddb.get_item_typed(TEST_TABLE_NAME, missing).await?;
+            assert!(fetched.is_none());
+
+            Ok(())
+        })
+        .await
+    }
+
+    #[cfg(feature = "serde")]
+    #[tokio::test]
+    async fn test_entity_lifecycle() -> Result<()> {
+        run_test("entity_lifecycle", |ddb| async move {
+            let product = |category: &str, name: &str, price: f64| Product {
+                category: category.to_string(),
+                product_name: name.to_string(),
+                price,
+                discount: None,
+            };
+            let key = |category: &str, name: &str| {
+                Product::key_of(
+                    AttributeValue::S(category.to_string()),
+                    Some(AttributeValue::S(name.to_string())),
+                )
+            };
+            assert_eq!(Product::table().name(), TEST_TABLE_NAME);
+
+            let mut lamp = product("Entities", "Lamp", 25.0);
+            for saved in [
+                &lamp,
+                &product("Entities", "Desk", 120.0),
+                &product("OtherEntities", "Chair", 60.0),
+            ] {
+                ddb.save(saved).await?;
+            }
+
+            let loaded = ddb.load::<Product>(key("Entities", "Lamp")).await?;
+            assert_eq!(loaded.as_ref(), Some(&lamp));
+            let missing = ddb.load::<Product>(key("Entities", "Sofa")).await?;
+            assert!(missing.is_none());
+
+            // Saving again replaces the item.
+            lamp.price = 20.0;
+            lamp.discount = Some(0.2);
+            ddb.save(&lamp).await?;
+            assert_eq!(ddb.load::<Product>(lamp.key()).await?, Some(lamp.clone()));
+
+            let names: Vec<_> = ddb
+                .query_partition::<Product>(AttributeValue::S("Entities".to_string()))
+                .await?
+                .into_iter()
+                .map(|product| product.product_name)
+                .collect();
+            assert_eq!(names, ["Desk", "Lamp"]);
+
+            ddb.delete_entity::<Product>(lamp.key()).await?;
+            assert!(ddb.load::<Product>(lamp.key()).await?.is_none());
+            let remaining = ddb
+                .query_partition::<Product>(AttributeValue::S("Entities".to_string()))
+                .await?;
+            assert_eq!(remaining, [product("Entities", "Desk", 120.0)]);
+            Ok(())
+        })
+        .await
+    }
+
+    #[cfg(feature = "serde")]
+    #[tokio::test]
+    async fn test_derived_entity() -> Result<()> {
+        assert_eq!(DerivedProduct::table_name(), TEST_TABLE_NAME);
+        assert_eq!(DerivedProduct::partition_key_name(), CATEGORY_PARTITION_KEY);
+        assert_eq!(DerivedProduct::sort_key_name(), Some(PRODUCT_NAME_SORT_KEY));
+        let schema = DerivedProduct::schema();
+        let mut fields: Vec<_> = schema
+            .fields()
+            .iter()
+            .map(|(name, field_type)| format!("{name}: {field_type:?}"))
+            .collect();
+        fields.sort();
+        // The Option is left out: None would be written as NULL.
+        assert_eq!(
+            fields,
+            [
+                "category: String",
+                "price: Number",
+                "product_name: String",
+                "tags: StringList"
+            ]
+        );
+
+        run_local_test("derived_entity", |ddb| async move {
+            let product = |name: &str, price: f64| DerivedProduct {
+                category: "Derived".to_string(),
+                name: name.to_string(),
+                price,
+                tags: vec!["new".to_string()],
+                discount: None,
+            };
+            let lamp = DerivedProduct {
+                discount: Some(0.1),
+                ..product("Lamp", 25.0)
+            };
+            ddb.save(&lamp).await?;
+            ddb.save(&product("Desk", 120.0)).await?;
+
+            // Stored under the renamed attribute, like any other row.
+            let raw = ddb
+                .get_item(
+                    TEST_TABLE_NAME,
+                    Item::new()
+                        .set_string(CATEGORY_PARTITION_KEY, "Derived")
+                        .set_string(PRODUCT_NAME_SORT_KEY, "Lamp"),
+                )
+                .await?;
+            assert!(raw.is_some());
+
+            assert_eq!(
+                ddb.load::<DerivedProduct>(lamp.key()).await?,
+                Some(lamp.clone())
+            );
+            let missing = product("Sofa", 1.0).key();
+            assert!(ddb.load::<DerivedProduct>(missing).await?.is_none());
+
+            let names: Vec<_> = ddb
+                .query_partition::<DerivedProduct>(AttributeValue::S("Derived".to_string()))
+                .await?
+                .into_iter()
+                .map(|product| product.name)
+                .collect();
+            assert_eq!(names, ["Desk", "Lamp"]);
+            Ok(())
+        })
+        .await
+    }
+
+    #[cfg(feature = "serde")]
+    #[tokio::test]
+    async fn test_serde_dynamo_interop() -> Result<()> {
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        struct Reading {
+            category: String,
+            product_name: String,
+            /// More digits than an f64 holds.
+            serial: u64,
+            price: f64,
+            tags: Vec<String>,
+        }
+
+        run_local_test("serde_dynamo_interop", |ddb| async move {
+            let reading = Reading {
+                category: "Interop".to_string(),
+                product_name: "Sensor".to_string(),
+                serial: u64::MAX,
+                price: 19.5,
+                tags: vec!["a".to_string(), "b".to_string()],
+            };
+            let mut written: serde_dynamo::Item = serde_dynamo::to_item(&reading)?;
+            let payload = vec![0, 159, 146, 150, 255];
+            written.insert(
+                "payload".to_string(),
+                serde_dynamo::AttributeValue::B(payload.clone()),
+            );
+
+            // Passed to put_item as it is.
+            ddb.put_item(TEST_TABLE_NAME, written.clone()).await?;
+
+            let key = Item::new()
+                .set_string(CATEGORY_PARTITION_KEY, "Interop")
+                .set_string(PRODUCT_NAME_SORT_KEY, "Sensor");
+            let read = ddb
+                .get_item(TEST_TABLE_NAME, key)
+                .await?
+                .expect("the item was just written");
+            let read = serde_dynamo::Item::from(read);
+            assert_eq!(read, written);
+            assert_eq!(
+                read.get("serial"),
+                Some(&serde_dynamo::AttributeValue::N(u64::MAX.to_string()))
+            );
+            assert_eq!(
+                read.get("payload"),
+                Some(&serde_dynamo::AttributeValue::B(payload))
+            );
+            assert_eq!(serde_dynamo::from_item::<_, Reading>(read)?, reading);
+
+            // Map values convert too, and nothing else does.
+            let value: serde_dynamo::AttributeValue = serde_dynamo::to_attribute_value(&reading)?;
+            let item = Item::try_from(value.clone())?;
+            assert_eq!(serde_dynamo::AttributeValue::from(item), value);
+            assert!(Item::try_from(serde_dynamo::AttributeValue::S("x".to_string())).is_err());
+            Ok(())
+        })
+        .await
+    }
+
+    #[cfg(feature = "serde")]
+    #[tokio::test]
+    async fn test_query_and_scan_typed() -> Result<()> {
+        run_test("query_and_scan_typed", |ddb| async move {
+            for (name, price) in [("Alpha", 10.0), ("Gamma", 30.0)] {
+                let product = Product {
+                    category: "TypedBatch".to_string(),
+                    product_name: name.to_string(),
+                    price,
+                    discount: None,
+                };
+                ddb.put_item_typed(TEST_TABLE_NAME, &product).await?;
+            }
+            let malformed = Item::new()
+                .set_string(CATEGORY_PARTITION_KEY, "TypedBatch")
+                .set_string(PRODUCT_NAME_SORT_KEY, "Beta")
+                .set_string(PRICE_ATTRIBUTE, "not a number");
+            ddb.put_item(TEST_TABLE_NAME, malformed).await?;
+
+            let key_condition = KeyCondition::partition(
+                CATEGORY_PARTITION_KEY,
+                AttributeValue::S("TypedBatch".to_string()),
+            )
+            .build()?;
+            let params =
+                || QueryFlexibleParams::from_key_condition(TEST_TABLE_NAME, &key_condition);
+
+            let result = ddb.query_typed_lenient::<Product>(params()).await?;
+            let names: Vec<_> = result
+                .values
+                .iter()
+                .map(|product| product.product_name.as_str())
+                .collect();
+            assert_eq!(names, ["Alpha", "Gamma"], "Expected the good rows");
+            assert_eq!(result.errors.len(), 1, "Expected one conversion error");
+            let error = &result.errors[0];
+            assert_eq!(
+                error.key,
+                HashMap::from([
+                    (
+                        CATEGORY_PARTITION_KEY.to_string(),
+                        AttributeValue::S("TypedBatch".to_string())
+                    ),
+                    (
+                        PRODUCT_NAME_SORT_KEY.to_string(),
+                        AttributeValue::S("Beta".to_string())
+                    ),
+                ])
+            );
+            assert!(
+                error.to_string().contains("Beta"),
+                "Unexpected error: {error}"
+            );
+            assert!(error.message.contains(PRICE_ATTRIBUTE));
+
+            let strict = ddb.query_typed::<Product>(params()).await;
+            let message = strict
+                .expect_err("Strict mode accepted a malformed item")
+                .to_string();
+            assert!(message.contains("Beta"), "Unexpected error: {message}");
+
+            let filter = Filter::eq(
+                CATEGORY_PARTITION_KEY,
+                AttributeValue::S("TypedBatch".to_string()),
+            );
+            let result = ddb
+                .scan_typed_lenient::<Product>(TEST_TABLE_NAME, Some(filter.clone()))
+                .await?;
+            assert_eq!(result.values.len(), 2);
+            assert_eq!(result.errors.len(), 1);
+            assert!(ddb
+                .scan_typed::<Product>(TEST_TABLE_NAME, Some(filter))
+                .await
+                .is_err());
+
+            Ok(())
+        })
+        .await
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_typed_conversion_error_names_attribute() {
+        let item = Item::new()
+            .set_string(CATEGORY_PARTITION_KEY, "Typed")
+            .set_string(PRODUCT_NAME_SORT_KEY, "Broken")
+            .set_string(PRICE_ATTRIBUTE, "not a number");
+        let err = item
+            .into_typed::<Product>()
+            .expect_err("string price should not convert");
+        assert!(
+            err.to_string().contains(PRICE_ATTRIBUTE),
+            "Error should name the attribute: {err}"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_consumed_capacity() -> Result<()> {
+        run_test("consumed_capacity", |ddb| async move {
+            let item = Item::new()
+                .set_string(CATEGORY_PARTITION_KEY, "Capacity")
+                .set_string(PRODUCT_NAME_SORT_KEY, "Measured")
+                .set_number(PRICE_ATTRIBUTE, 1.0);
+            let key = Item::new()
+                .set_string(CATEGORY_PARTITION_KEY, "Capacity")
+                .set_string(PRODUCT_NAME_SORT_KEY, "Measured");
+
+            // DynamoDB Local may omit capacity, so only check values that come back
+            let write = ddb.put_item_with_capacity(TEST_TABLE_NAME, item).await?;
+            assert!(write.consumed_capacity.is_none_or(|units| units > 0.0));
+
+            let read = ddb.get_item_with_capacity(TEST_TABLE_NAME, key).await?;
+            assert!(read.value.is_some(), "Item not found");
+            assert!(read.consumed_capacity.is_none_or(|units| units > 0.0));
+
+            let query = ddb
+                .query_flexible_with_capacity(QueryFlexibleParams {
+                    table_name: TEST_TABLE_NAME,
+                    key_condition_expression: "#pk = :pkval",
+                    expression_attribute_names: Some(HashMap::from([(
+                        "#pk".to_string(),
+                        CATEGORY_PARTITION_KEY.to_string(),
+                    )])),
+                    expression_attribute_values: Some(HashMap::from([(
+                        ":pkval".to_string(),
+                        AttributeValue::S("Capacity".to_string()),
+                    )])),
+                    filter_expression: None,
+                    projection_expression: None,
+                    limit: None,
+                    scan_index_forward: None,
+                    index_name: None,
+                    ..Default::default()
+                })
+                .await?;
+            assert_eq!(query.value.len(), 1);
+            assert!(query.consumed_capacity.is_none_or(|units| units > 0.0));
+
+            let scan = ddb
+                .scan_with_capacity(ScanParams {
+                    table_name: TEST_TABLE_NAME,
+                    ..Default::default()
+                })
+                .await?;
+            assert!(!scan.value.is_empty());
+            assert!(scan.consumed_capacity.is_none_or(|units| units > 0.0));
+
+            Ok(())
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_scan_index_and_consistent_read() -> Result<()> {
+        const INDEXED_TABLE_NAME: &str = "testing-products-indexed";
+        const BRAND_INDEX: &str = "brand-index";
+
+        run_test("scan_index_and_consistent_read", |ddb| async move {
+            let table = Table::new(
+                INDEXED_TABLE_NAME,
+                CATEGORY_PARTITION_KEY,
+                Some(PRODUCT_NAME_SORT_KEY),
+            )
+            .with_global_secondary_index(BRAND_INDEX, "brand", None);
+            ddb.create_table_if_not_exists(&table).await?;
+            ddb.wait_for_table_active(INDEXED_TABLE_NAME, TABLE_WAIT_TIMEOUT)
+                .await?;
+
+            // Only branded products appear in the sparse index.
+            for (name, brand) in [
+                ("Laptop", Some("Acme")),
+                ("Phone", Some("Globex")),
+                ("Cable", None),
+            ] {
+                let mut item = Item::new()
+                    .set_string(CATEGORY_PARTITION_KEY, "Electronics")
+                    .set_string(PRODUCT_NAME_SORT_KEY, name);
+                if let Some(brand) = brand {
+                    item = item.set_string("brand", brand);
+                }
+                ddb.put_item(INDEXED_TABLE_NAME, item).await?;
+            }
+
+            let indexed = ddb
+                .scan(ScanParams {
+                    table_name: INDEXED_TABLE_NAME,
+                    index_name: Some(BRAND_INDEX),
+                    ..Default::default()
+                })
+                .await?;
+            assert_eq!(indexed.len(), 2);
+            assert!(indexed
+                .iter()
+                .all(|item| item.get_string("brand").is_some()));
+
+            let consistent = ddb
+                .scan(ScanParams {
+                    table_name: INDEXED_TABLE_NAME,
+                    consistent_read: Some(true),
+                    ..Default::default()
+                })
+                .await?;
+            assert_eq!(consistent.len(), 3);
+
+            let err = ddb
+                .scan_paginated(
+                    ScanParams {
+                        table_name: INDEXED_TABLE_NAME,
+                        index_name: Some(BRAND_INDEX),
+                        consistent_read: Some(true),
+                        ..Default::default()
+                    },
+                    None,
+                )
+                .await
+                .unwrap_err();
+            assert!(err.to_string().contains(BRAND_INDEX));
+
+            ddb.delete_table(INDEXED_TABLE_NAME).await?;
+            Ok(())
+        })
+        .await
+    }
+
+    #[test]
+    fn test_consumed_capacity_request_parameter() {
+        let ddb = offline_client();
+        let key = || Item::new().set_string(CATEGORY_PARTITION_KEY, "Capacity");
+        let params = QueryFlexibleParams {
+            table_name: TEST_TABLE_NAME,
+            key_condition_expression: "#pk = :pkval",
+            expression_attribute_names: None,
+            expression_attribute_values: None,
+            filter_expression: None,
+            projection_expression: None,
+            limit: None,
+            scan_index_forward: None,
+            index_name: None,
+            ..Default::default()
+        };
+        let scan_params = ScanParams {
+            table_name: TEST_TABLE_NAME,
+            ..Default::default()
+        };
+        let total = Some(ReturnConsumedCapacity::Total);
+
+        for (enabled, expected) in [(true, &total), (false, &None)] {
+            assert_eq!(
+                ddb.put_item_request(TEST_TABLE_NAME, key(), enabled)
+                    .get_return_consumed_capacity(),
+                expected
+            );
+            assert_eq!(
+                ddb.get_item_request(TEST_TABLE_NAME, key(), enabled)
+                    .get_return_consumed_capacity(),
+                expected
+            );
+            assert_eq!(
+                ddb.query_request(&params, enabled)
+                    .get_return_consumed_capacity(),
+                expected
+            );
+            assert_eq!(
+                ddb.scan_request(&scan_params, enabled)
+                    .get_return_consumed_capacity(),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn test_backup_requests() {
+        let ddb = offline_client();
+        let arn = BackupArn::new("arn:aws:dynamodb:us-east-1:123456789012:table/t/backup/b");
+
+        let create = ddb.create_backup_request(TEST_TABLE_NAME, "before-migration");
+        assert_eq!(create.get_table_name().as_deref(), Some(TEST_TABLE_NAME));
+        assert_eq!(
+            create.get_backup_name().as_deref(),
+            Some("before-migration")
+        );
+
+        let list = ddb.list_backups_request(TEST_TABLE_NAME);
+        assert_eq!(list.get_table_name().as_deref(), Some(TEST_TABLE_NAME));
+        assert_eq!(list.get_backup_type(), &Some(BackupTypeFilter::User));
+        assert_eq!(list.get_exclusive_start_backup_arn(), &None);
+
+        let restore = ddb.restore_table_request(&arn, "testing-restored");
+        assert_eq!(restore.get_backup_arn().as_deref(), Some(arn.as_str()));
+        assert_eq!(
+            restore.get_target_table_name().as_deref(),
+            Some("testing-restored")
+        );
+        assert_eq!(arn.to_string(), arn.as_str());
+    }
+
+    /// Backups aren't supported by every local DynamoDB, so this only runs
+    /// when `DYNAMODB_BACKUP_TESTS` is set.
+    #[tokio::test]
+    async fn test_backup_and_restore() -> Result<()> {
+        const RESTORED_TABLE_NAME: &str = "testing-products-restored";
+
+        if std::env::var_os("DYNAMODB_BACKUP_TESTS").is_none() {
+            info!("Skipping backup_and_restore; set DYNAMODB_BACKUP_TESTS to run it");
+            return Ok(());
+        }
+
+        run_test("backup_and_restore", |ddb| async move {
+            let item = Item::new()
+                .set_string(CATEGORY_PARTITION_KEY, "BackedUp")
+                .set_string(PRODUCT_NAME_SORT_KEY, "Original")
+                .set_number(PRICE_ATTRIBUTE, 1.0);
+            ddb.put_item(TEST_TABLE_NAME, item).await?;
+
+            let arn = ddb.create_backup(TEST_TABLE_NAME, "testing-backup").await?;
+            let backups = ddb.list_backups(TEST_TABLE_NAME).await?;
+            let backup = backups
+                .iter()
+                .find(|backup| backup.arn == arn)
+                .context("New backup missing from list_backups")?;
+            assert_eq!(backup.name, "testing-backup");
+            assert_eq!(backup.table_name, TEST_TABLE_NAME);
+
+            ddb.delete_table_if_exists_and_wait(RESTORED_TABLE_NAME, TABLE_WAIT_TIMEOUT)
+                .await?;
+            ddb.restore_table_from_backup_and_wait(&arn, RESTORED_TABLE_NAME, TABLE_WAIT_TIMEOUT)
+                .await?;
+            let key = Item::new()
+                .set_string(CATEGORY_PARTITION_KEY, "BackedUp")
+                .set_string(PRODUCT_NAME_SORT_KEY, "Original");
+            assert!(
+                ddb.get_item(RESTORED_TABLE_NAME, key).await?.is_some(),
+                "Restored table is missing the backed up item"
+            );
+
+            ddb.delete_backup(&arn).await?;
+            assert!(ddb
+                .list_backups(TEST_TABLE_NAME)
+                .await?
+                .iter()
+                .all(|backup| backup.arn != arn || backup.status == BackupStatus::Deleted));
+            ddb.delete_table_if_exists_and_wait(RESTORED_TABLE_NAME, TABLE_WAIT_TIMEOUT)
+                .await?;
+            Ok(())
+        })
+        .await
+    }
+
+    #[test]
+    fn test_point_in_time_recovery_precondition() -> Result<()> {
+        let backups = |status| {
+            ContinuousBackupsDescription::builder()
+                .continuous_backups_status(ContinuousBackupsStatus::Enabled)
+                .point_in_time_recovery_description(
+                    PointInTimeRecoveryDescription::builder()
+                        .point_in_time_recovery_status(status)
+                        .build(),
+                )
+                .build()
+        };
+
+        PointInTimeRecoveryDisabled::check(
+            TEST_TABLE_NAME,
+            Some(&backups(PointInTimeRecoveryStatus::Enabled)?),
+        )?;
+
+        for description in [None, Some(backups(PointInTimeRecoveryStatus::Disabled)?)] {
+            let error = PointInTimeRecoveryDisabled::check(TEST_TABLE_NAME, description.as_ref())
+                .expect_err("Export allowed without point-in-time recovery");
+            assert_eq!(
+                error.downcast_ref::<PointInTimeRecoveryDisabled>(),
+                Some(&PointInTimeRecoveryDisabled {
+                    table_name: TEST_TABLE_NAME.to_string()
+                })
+            );
+            assert!(error.to_string().contains("enable it"));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_export_and_import_requests() -> Result<()> {
+        let ddb = offline_client();
+        let table_arn = "arn:aws:dynamodb:us-east-1:123456789012:table/testing-products";
+
+        let export = ddb.export_request(
+            table_arn,
+            "analytics",
+            "exports/products",
+            ExportFormat::DynamodbJson,
+        );
+        assert_eq!(export.get_table_arn().as_deref(), Some(table_arn));
+        assert_eq!(export.get_s3_bucket().as_deref(), Some("analytics"));
+        assert_eq!(export.get_s3_prefix().as_deref(), Some("exports/products"));
+        assert_eq!(
+            export.get_export_format(),
+            &Some(ExportFormat::DynamodbJson)
+        );
+
+        let table = Table::new(
+            "testing-imported",
+            CATEGORY_PARTITION_KEY,
+            Some(PRODUCT_NAME_SORT_KEY),
+        )
+        .with_schema(
+            Schema::new()
+                .add_field(CATEGORY_PARTITION_KEY, FieldType::String)
+                .add_field(PRODUCT_NAME_SORT_KEY, FieldType::String)
+                .add_field(PRICE_ATTRIBUTE, FieldType::Number),
+        )
+        .with_global_secondary_index(
+            "price-index",
+            CATEGORY_PARTITION_KEY,
+            Some(PRICE_ATTRIBUTE),
+        );
+        let import = ddb.import_request(
+            &table,
+            "analytics",
+            "exports/products/data/",
+            InputFormat::DynamodbJson,
+        )?;
+        assert_eq!(import.get_input_format(), &Some(InputFormat::DynamodbJson));
+
+        let source = import
+            .get_s3_bucket_source()
+            .as_ref()
+            .context("No S3 source")?;
+        assert_eq!(source.s3_bucket(), "analytics");
+        assert_eq!(source.s3_key_prefix(), Some("exports/products/data/"));
+
+        let parameters = import
+            .get_table_creation_parameters()
+            .as_ref()
+            .context("No table creation parameters")?;
+        assert_eq!(parameters.table_name(), "testing-imported");
+        assert_eq!(parameters.billing_mode(), Some(&BillingMode::PayPerRequest));
+        assert_eq!(parameters.key_schema().len(), 2);
+        let mut attributes: Vec<_> = parameters
+            .attribute_definitions()
+            .iter()
+            .map(|definition| definition.attribute_name())
+            .collect();
+        attributes.sort();
+        assert_eq!(
+            attributes,
+            [
+                CATEGORY_PARTITION_KEY,
+                PRICE_ATTRIBUTE,
+                PRODUCT_NAME_SORT_KEY
+            ]
+        );
+        assert_eq!(parameters.global_secondary_indexes().len(), 1);
+        Ok(())
+    }
+
+    /// Exports need a real S3 bucket and point-in-time recovery on the test
+    /// table, so this only runs when `DYNAMODB_S3_TEST_BUCKET` names the
+    /// bucket to use. Both the export and the import take several minutes.
+    #[tokio::test]
+    async fn test_export_and_import_s3() -> Result<()> {
+        const IMPORTED_TABLE_NAME: &str = "testing-products-imported";
+        const S3_TRANSFER_TIMEOUT: Duration = Duration::from_secs(1800);
+
+        let Some(bucket) = std::env::var("DYNAMODB_S3_TEST_BUCKET").ok() else {
+            info!("Skipping export_and_import_s3; set DYNAMODB_S3_TEST_BUCKET to run it");
+            return Ok(());
+        };
+
+        run_test("export_and_import_s3", |ddb| async move {
+            let item = Item::new()
+                .set_string(CATEGORY_PARTITION_KEY, "Exported")
+                .set_string(PRODUCT_NAME_SORT_KEY, "Original")
+                .set_number(PRICE_ATTRIBUTE, 1.0);
+            ddb.put_item(TEST_TABLE_NAME, item).await?;
+
+            let prefix = "testing-exports";
+            let export = ddb
+                .export_to_s3(TEST_TABLE_NAME, &bucket, prefix, ExportFormat::DynamodbJson)
+                .await
+                .context("Export failed; is point-in-time recovery enabled on the test table?")?;
+            let deadline = Instant::now() + S3_TRANSFER_TIMEOUT;
+            let export = loop {
+                let progress = ddb.describe_export(&export.arn).await?;
+                if progress.status != ExportStatus::InProgress || Instant::now() > deadline {
+                    break progress;
+                }
+                tokio::time::sleep(Duration::from_secs(10)).await;
+            };
+            assert_eq!(export.status, ExportStatus::Completed, "{export:?}");
+
+            // Exports land under `{prefix}/AWSDynamoDB/{export id}/data/`.
+            let export_id = export
+                .arn
+                .rsplit('/')
+                .next()
+                .context("Malformed export ARN")?;
+            let table = Table::new(
+                IMPORTED_TABLE_NAME,
+                CATEGORY_PARTITION_KEY,
+                Some(PRODUCT_NAME_SORT_KEY),
+            );
+            ddb.delete_table_if_exists_and_wait(IMPORTED_TABLE_NAME, TABLE_WAIT_TIMEOUT)
+                .await?;
+            let import = ddb
+                .import_from_s3(
+                    &table,
+                    &bucket,
+                    &format!("{prefix}/AWSDynamoDB/{export_id}/data/"),
+                    InputFormat::DynamodbJson,
+                )
+                .await?;
+            ddb.wait_for_table_active(IMPORTED_TABLE_NAME, S3_TRANSFER_TIMEOUT)
+                .await?;
+            let import = ddb.describe_import(&import.arn).await?;
+            assert_eq!(import.status, ImportStatus::Completed, "{import:?}");
+
+            let key = Item::new()
+                .set_string(CATEGORY_PARTITION_KEY, "Exported")
+                .set_string(PRODUCT_NAME_SORT_KEY, "Original");
+            assert!(
+                ddb.get_item(IMPORTED_TABLE_NAME, key).await?.is_some(),
+                "Imported table is missing the exported item"
+            );
+            ddb.delete_table_if_exists_and_wait(IMPORTED_TABLE_NAME, TABLE_WAIT_TIMEOUT)
+                .await?;
+            Ok(())
+        })
+        .await
+    }
+
+    #[test]
+    fn test_statement_parsing() -> Result<()> {
+        let statement = Statement::parse(
+            "  SELECT * FROM \"testing-products\"\n WHERE category = ? AND price > ?;\n",
+        )?;
+        assert_eq!(statement.kind(), StatementKind::Select);
+        assert_eq!(
+            statement.text(),
+            "SELECT * FROM \"testing-products\"\n WHERE category = ? AND price > ?"
+        );
+        assert_eq!(statement.parameter_count(), 2);
+        assert!(!statement.kind().is_write());
+        assert!(!statement.is_unbounded_write());
+
+        // Placeholders, keywords and semicolons inside quotes don't count.
+        let statement =
+            Statement::parse("update \"where?\" SET note = 'is it; where?' WHERE category = ?")?;
+        assert_eq!(statement.kind(), StatementKind::Update);
+        assert_eq!(statement.parameter_count(), 1);
+        assert!(!statement.is_unbounded_write());
+
+        let statement = Statement::parse("DELETE FROM \"WHERE\"")?;
+        assert!(statement.kind().is_write());
+        assert!(statement.is_unbounded_write());
+        let statement = Statement::parse("UPDATE \"testing-products\" SET wherever = 1;")?;
+        assert!(statement.is_unbounded_write());
+        let statement = Statement::parse("INSERT INTO \"testing-products\" VALUE {'category': ?}")?;
+        assert_eq!(statement.kind(), StatementKind::Insert);
+        assert!(!statement.is_unbounded_write());
+
+        for invalid in [
+            "",
+            " ; ",
+            "DROP TABLE products",
+            "SELECT * FROM t; DELETE FROM t",
+            "SELECT * FROM t WHERE name = 'open",
+        ] {
+            assert!(
+                Statement::parse(invalid).is_err(),
+                "Parsed invalid statement {invalid:?}"
+            );
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "cli")]
+    #[test]
+    fn test_sql_statement_and_parameter_prompts() -> Result<()> {
+        let mut lines = [
+            "SELECT * FROM \"testing-products\"",
+            "WHERE category = ?",
+            "  AND price > ? ;",
+        ]
+        .into_iter()
+        .map(String::from);
+        let statement = command_line::read_statement(|_| lines.next().context("Ran out of lines"))?
+            .context("No statement read")?;
+        assert_eq!(statement.parameter_count(), 2);
+        assert!(statement.text().ends_with("AND price > ?"));
+        assert_eq!(lines.next(), None);
+
+        assert_eq!(command_line::read_statement(|_| Ok(String::new()))?, None);
+
+        let mut answers = ["S", "Electronics", "N", "9.5"]
+            .into_iter()
+            .map(String::from);
+        let parameters = command_line::read_parameters(statement.parameter_count(), |_, _| {
+            answers.next().context("Ran out of answers")
+        })?;
+        assert_eq!(
+            parameters,
+            [
+                AttributeValue::S("Electronics".to_string()),
+                AttributeValue::N("9.5".to_string())
+            ]
+        );
+
+        let mut answers = ["X", "value"].into_iter().map(String::from);
+        assert!(command_line::read_parameters(1, |_, _| {
+            answers.next().context("Ran out of answers")
+        })
+        .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_execute_statement_request() -> Result<()> {
+        let ddb = offline_client();
+        let statement = Statement::parse("SELECT * FROM \"testing-products\" WHERE category = ?")?;
+
+        let request =
+            ddb.execute_statement_request(&statement, vec![AttributeValue::S("Books".to_string())]);
+        assert_eq!(request.get_statement().as_deref(), Some(statement.text()));
+        assert_eq!(
+            request.get_parameters(),
+            &Some(vec![AttributeValue::S("Books".to_string())])
+        );
+
+        let request = ddb.execute_statement_request(&statement, Vec::new());
+        assert_eq!(request.get_parameters(), &None);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_execute_statement() -> Result<()> {
+        run_test("execute_statement", |ddb| async move {
+            let item = Item::new()
+                .set_string(CATEGORY_PARTITION_KEY, "PartiQL")
+                .set_string(PRODUCT_NAME_SORT_KEY, "Selected")
+                .set_number(PRICE_ATTRIBUTE, 4.5);
+            ddb.put_item(TEST_TABLE_NAME, item).await?;
+
+            let select = Statement::parse(&format!(
+                "SELECT * FROM \"{TEST_TABLE_NAME}\" WHERE {CATEGORY_PARTITION_KEY} = ?;"
+            ))?;
+            assert!(ddb
+                .execute_statement(&select, Vec::new(), None)
+                .await
+                .is_err());
+
+            let result = ddb
+                .execute_statement(
+                    &select,
+                    vec![AttributeValue::S("PartiQL".to_string())],
+                    None,
+                )
+                .await?;
+            assert_eq!(result.items.len(), 1);
+            assert_eq!(
+                result.items[0].attributes.get(PRODUCT_NAME_SORT_KEY),
+                Some(&AttributeValue::S("Selected".to_string()))
+            );
+            assert_eq!(result.next_token, None);
+
+            let key = Item::new()
+                .set_string(CATEGORY_PARTITION_KEY, "PartiQL")
+                .set_string(PRODUCT_NAME_SORT_KEY, "Selected");
+            ddb.delete_item(TEST_TABLE_NAME, key).await?;
+            Ok(())
+        })
+        .await
+    }
+
+    #[test]
+    fn test_stream_event_types_and_request() -> Result<()> {
+        for event_type in [
+            StreamEventType::Insert,
+            StreamEventType::Modify,
+            StreamEventType::Remove,
+        ] {
+            assert_eq!(StreamEventType::parse(&event_type.to_string())?, event_type);
+        }
+        assert!(StreamEventType::parse("insert").is_err());
+
+        let request = offline_client()
+            .enable_stream_request(TEST_TABLE_NAME, StreamViewType::NewAndOldImages)?;
+        assert_eq!(request.get_table_name().as_deref(), Some(TEST_TABLE_NAME));
+        let specification = request
+            .get_stream_specification()
+            .as_ref()
+            .context("No stream specification")?;
+        assert!(specification.stream_enabled());
+        assert_eq!(
+            specification.stream_view_type(),
+            Some(&StreamViewType::NewAndOldImages)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_in_memory_checkpoints() -> Result<()> {
+        let checkpoints = InMemoryCheckpoints::default();
+        assert_eq!(checkpoints.load("shard-1")?, None);
+
+        checkpoints.save("shard-1", "100")?;
+        checkpoints.save("shard-2", "200")?;
+        checkpoints.save("shard-1", "101")?;
+        assert_eq!(checkpoints.load("shard-1")?.as_deref(), Some("101"));
+        assert_eq!(checkpoints.load("shard-2")?.as_deref(), Some("200"));
+        Ok(())
+    }
+
+    #[cfg(feature = "cli")]
+    #[test]
+    fn test_render_stream_record_diffs() {
+        let keys = Item::new()
+            .set_string(CATEGORY_PARTITION_KEY, "Books")
+            .set_string(PRODUCT_NAME_SORT_KEY, "Dune");
+        let old_image = keys
+            .clone()
+            .set_number(PRICE_ATTRIBUTE, 9.5)
+            .set_string("author", "Herbert")
+            .set_string("edition", "First");
+        let new_image = keys
+            .clone()
+            .set_number(PRICE_ATTRIBUTE, 12.0)
+            .set_string("author", "Herbert")
+            .set_number("discount", 0.1);
+        let record = |event_type, old_image, new_image| StreamRecord {
+            event_type,
+            keys: keys.clone(),
+            old_image,
+            new_image,
+            sequence_number: "100".to_string(),
+        };
+
+        assert_eq!(
+            command_line::render_change(&record(
+                StreamEventType::Modify,
+                Some(old_image.clone()),
+                Some(new_image.clone())
+            )),
+            "MODIFY {category: S(\"Books\"), product_name: S(\"Dune\")}\n  \
+             + discount: N(\"0.1\")\n  \
+             - edition: S(\"First\")\n  \
+             ~ price: N(\"9.5\") -> N(\"12\")"
+        );
+        assert_eq!(
+            command_line::render_change(&record(StreamEventType::Insert, None, Some(new_image))),
+            "INSERT {category: S(\"Books\"), product_name: S(\"Dune\")}\n  \
+             + author: S(\"Herbert\")\n  \
+             + discount: N(\"0.1\")\n  \
+             + price: N(\"12\")"
+        );
+        assert_eq!(
+            command_line::render_change(&record(StreamEventType::Remove, None, None)),
+            "REMOVE {category: S(\"Books\"), product_name: S(\"Dune\")}"
+        );
+    }
+
+    #[cfg(feature = "cli")]
+    #[test]
+    fn test_render_items_as_table() {
+        let keys = [CATEGORY_PARTITION_KEY, PRODUCT_NAME_SORT_KEY];
+        let mut dune = Item::new()
+            .set_string(CATEGORY_PARTITION_KEY, "Books")
+            .set_string(PRODUCT_NAME_SORT_KEY, "Dune")
+            .set_number(PRICE_ATTRIBUTE, 9.5);
+        dune.attributes
+            .insert("in_stock".to_string(), AttributeValue::Bool(true));
+        dune.attributes.insert(
+            "tags".to_string(),
+            AttributeValue::Ss(vec!["classic".to_string(), "sci-fi".to_string()]),
+        );
+        let mut lamp = Item::new()
+            .set_string(CATEGORY_PARTITION_KEY, "Home")
+            .set_string(
+                PRODUCT_NAME_SORT_KEY,
+                "A very long product name\nwith a newline",
+            );
+        lamp.attributes
+            .insert("in_stock".to_string(), AttributeValue::Null(true));
+        lamp.attributes.insert(
+            "dimensions".to_string(),
+            AttributeValue::L(vec![AttributeValue::N("10".to_string())]),
+        );
+
+        assert_eq!(
+            command_line::render_items(&[dune.clone(), lamp], &keys, 20),
+            "category | product_name         | dimensions | in_stock | price | tags\n\
+             ---------+----------------------+------------+----------+-------+------------------\n\
+             Books    | Dune                 |            | true     | 9.5   | {classic, sci-fi}\n\
+             Home     | A very long product… | [10]       | NULL\n\
+             (2 items)\n"
+        );
+        assert_eq!(
+            command_line::render_items(&[dune], &[], 30),
+            "category | in_stock | price | product_name | tags\n\
+             ---------+----------+-------+--------------+------------------\n\
+             Books    | true     | 9.5   | Dune         | {classic, sci-fi}\n\
+             (1 item)\n"
+        );
+        assert_eq!(command_line::render_items(&[], &keys, 30), "(no items)\n");
+    }
+
+    /// An `ItemEditor` for sessions that never reach the `edit` command.
+    #[cfg(feature = "cli")]
+    fn no_edits(_: &str) -> Result<Option<String>> {
+        Err(anyhow::anyhow!("Unexpected edit"))
+    }
+
+    /// Answers `read_table_definition`'s prompts in order.
+    #[cfg(feature = "cli")]
+    fn scripted_answers<'a>(
+        answers: &'a [&'a str],
+    ) -> impl FnMut(&str, Option<&str>) -> Result<String> + 'a {
+        let mut answers = answers.iter();
+        move |_, _| {
+            answers
+                .next()
+                .map(|answer| answer.to_string())
+                .context("Ran out of answers")
+        }
+    }
+
+    #[cfg(feature = "cli")]
+    #[test]
+    fn test_read_table_definition() -> Result<()> {
+        let table = command_line::read_table_definition(scripted_answers(&[
+            "orders",
+            "customer",
+            "S",
+            "order_date",
+            "N",
+            "provisioned",
+            "5",
+            "10",
+            "total",
+            "number",
+            "status",
+            "s",
+            "",
+        ]))?;
+        assert_eq!(table.name(), "orders");
+        assert_eq!(table.partition_key(), "customer");
+        assert_eq!(table.sort_key(), Some("order_date"));
+        assert_eq!(
+            table.capacity(),
+            Capacity::Provisioned {
+                read_capacity_units: 5,
+                write_capacity_units: 10
+            }
+        );
+        let fields = table.schema().context("Table has no schema")?.fields();
+        assert_eq!(fields.len(), 4);
+        assert!(matches!(fields.get("customer"), Some(FieldType::String)));
+        assert!(matches!(fields.get("order_date"), Some(FieldType::Number)));
+        assert!(matches!(fields.get("total"), Some(FieldType::Number)));
+        assert!(matches!(fields.get("status"), Some(FieldType::String)));
+
+        let table = command_line::read_table_definition(scripted_answers(&[
+            "events", "id", "S", "", "", "",
+        ]))?;
+        assert_eq!(table.sort_key(), None);
+        assert_eq!(table.capacity(), Capacity::OnDemand);
+
+        for answers in [
+            &["", "id", "S", "", "", ""][..],
+            &["events", "id", "X", "", "", ""],
+            &["events", "id", "S", "", "reserved"],
+            &["events", "id", "S", "", "provisioned", "0", "5"],
+            &["events", "id", "S", "", "", "price", ""],
+        ] {
+            assert!(
+                command_line::read_table_definition(scripted_answers(answers)).is_err(),
+                "{answers:?}"
+            );
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "cli")]
+    #[test]
+    fn test_create_table_request_capacity() -> Result<()> {
+        let table = command_line::read_table_definition(scripted_answers(&[
+            "orders",
+            "customer",
+            "S",
+            "",
+            "provisioned",
+            "3",
+            "4",
+            "",
+        ]))?
+        .with_global_secondary_index("by_status", "status", None);
+        let request = offline_client().create_table_request(&table)?;
+        assert_eq!(request.get_billing_mode(), &Some(BillingMode::Provisioned));
+        let throughput = request
+            .get_provisioned_throughput()
+            .as_ref()
+            .context("No provisioned throughput")?;
+        assert_eq!(throughput.read_capacity_units(), 3);
+        assert_eq!(throughput.write_capacity_units(), 4);
+        let indexes = request
+            .get_global_secondary_indexes()
+            .as_ref()
+            .context("No indexes")?;
+        assert_eq!(indexes[0].provisioned_throughput(), Some(throughput));
+
+        let request = offline_client().create_table_request(&Table::new("events", "id", None))?;
+        assert_eq!(
+            request.get_billing_mode(),
+            &Some(BillingMode::PayPerRequest)
+        );
+        assert_eq!(request.get_provisioned_throughput(), &None);
+        Ok(())
+    }
+
+    #[cfg(feature = "cli")]
+    #[test]
+    fn test_parse_command() {
+        assert_eq!(command_line::parse_command("info"), ("info", None));
+        assert_eq!(
+            command_line::parse_command("  use   testing-orders "),
+            ("use", Some("testing-orders"))
+        );
+        assert_eq!(command_line::parse_command(""), ("", None));
+    }
+
+    #[test]
+    fn test_infer_schema() {
+        let mut first = Item::new()
+            .set_string("id", "a")
+            .set_number("price", 1.5)
+            .set_string("mixed", "text");
+        first.attributes.insert(
+            "tags".to_string(),
+            AttributeValue::Ss(vec!["x".to_string()]),
+        );
+        let second = Item::new()
+            .set_string("id", "b")
+            .set_number("mixed", 2)
+            .set_number("quantity", 3);
+
+        let schema = Schema::infer(&[first, second]);
+        let mut fields: Vec<_> = schema.fields().iter().collect();
+        fields.sort_by_key(|(name, _)| name.as_str());
+        assert!(matches!(
+            fields[..],
+            [
+                (id, FieldType::String),
+                (price, FieldType::Number),
+                (quantity, FieldType::Number)
+            ] if id == "id" && price == "price" && quantity == "quantity"
+        ));
+        assert!(Schema::infer(&[]).fields().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_switch_between_tables() -> Result<()> {
+        const ORDERS_TABLE_NAME: &str = "testing-use-orders";
+        const USERS_TABLE_NAME: &str = "testing-use-users";
+
+        run_test("switch_between_tables", |ddb| async move {
+            let orders = Table::new(ORDERS_TABLE_NAME, "customer", Some("order_id"))
+                .with_schema(Schema::new().add_field("order_id", FieldType::Number));
+            let users = Table::new(USERS_TABLE_NAME, "user_id", None);
+            for table in [&orders, &users] {
+                ddb.delete_table_if_exists_and_wait(table.name(), TABLE_WAIT_TIMEOUT)
+                    .await?;
+                ddb.create_table_if_not_exists_and_wait(table, TABLE_WAIT_TIMEOUT)
+                    .await?;
+            }
+            ddb.put_item(
+                ORDERS_TABLE_NAME,
+                Item::new()
+                    .set_string("customer", "acme")
+                    .set_number("order_id", 1)
+                    .set_number("total", 9.5),
+            )
+            .await?;
+
+            let active = ddb.load_table(ORDERS_TABLE_NAME, Some(10)).await?;
+            assert_eq!(active.partition_key(), "customer");
+            assert_eq!(active.sort_key(), Some("order_id"));
+            let fields = active.schema().context("No schema loaded")?.fields();
+            assert!(matches!(fields.get("order_id"), Some(FieldType::Number)));
+            assert!(matches!(fields.get("total"), Some(FieldType::Number)));
+            let order = Item::new()
+                .set_string("customer", "acme")
+                .set_number("order_id", 2);
+            active.validate_item(&order)?;
+            ddb.put_item(active.name(), order.clone()).await?;
+
+            let active = ddb.load_table(USERS_TABLE_NAME, None).await?;
+            assert_eq!(active.partition_key(), "user_id");
+            assert_eq!(active.sort_key(), None);
+            assert!(active.validate_item(&order).is_err());
+            let user = Item::new().set_string("user_id", "u1");
+            ddb.put_item(active.name(), user.clone()).await?;
+
+            assert!(ddb.get_item(ORDERS_TABLE_NAME, order).await?.is_some());
+            assert!(ddb
+                .get_item(USERS_TABLE_NAME, user.clone())
+                .await?
+                .is_some());
+            assert_eq!(ddb.scan_table(ORDERS_TABLE_NAME).await?.len(), 2);
+            assert_eq!(ddb.scan_table(USERS_TABLE_NAME).await?.len(), 1);
+
+            let error = ddb
+                .load_table("testing-use-missing", None)
+                .await
+                .expect_err("Loaded a missing table");
+            assert_eq!(
+                error
+                    .downcast_ref::<TableNotFound>()
+                    .map(|e| e.table_name.as_str()),
+                Some("testing-use-missing"),
+                "{error}"
+            );
+
+            for table in [ORDERS_TABLE_NAME, USERS_TABLE_NAME] {
+                ddb.delete_table_if_exists_and_wait(table, TABLE_WAIT_TIMEOUT)
+                    .await?;
+            }
+            Ok(())
+        })
+        .await
+    }
+
+    /// Stands in for the terminal when driving `command_line::run`: `None`
+    /// lines act like Ctrl-C, and running out of lines like Ctrl-D.
+    #[cfg(feature = "cli")]
+    struct ScriptedInput {
+        lines: VecDeque<Option<&'static str>>,
+        prompts: Vec<String>,
+        field_names: Vec<Vec<String>>,
+    }
+
+    #[cfg(feature = "cli")]
+    impl ScriptedInput {
+        fn new(lines: &[Option<&'static str>]) -> Self {
+            Self {
+                lines: lines.iter().copied().collect(),
+                prompts: Vec::new(),
+                field_names: Vec::new(),
+            }
+        }
+    }
+
+    #[cfg(feature = "cli")]
+    impl LineReader for ScriptedInput {
+        fn read_line(&mut self, prompt: &str) -> Result<String> {
+            self.prompts.push(prompt.to_string());
+            match self.lines.pop_front() {
+                Some(Some(line)) => Ok(line.to_string()),
+                Some(None) => Err(PromptCancelled.into()),
+                None => Err(InputClosed.into()),
+            }
+        }
+
+        fn set_field_names(&mut self, field_names: Vec<String>) {
+            self.field_names.push(field_names);
+        }
+    }
+
+    #[cfg(feature = "cli")]
+    #[test]
+    fn test_prompt_completer() {
+        let mut completer = PromptCompleter::default();
+        completer.set_commands(&["query", "query_simple", "put", "use", "update"]);
+        completer.set_table_names(vec!["testing-orders".to_string(), "users".to_string()]);
+        completer.set_field_names(vec![
+            "category".to_string(),
+            "price".to_string(),
+            "product_name".to_string(),
+        ]);
+
+        completer.set_at_command_prompt(true);
+        assert_eq!(
+            completer.candidates("qu", 2),
+            (0, vec!["query".to_string(), "query_simple".to_string()])
+        );
+        assert_eq!(
+            completer.candidates("u", 1),
+            (0, vec!["update".to_string(), "use".to_string()])
+        );
+        assert_eq!(
+            completer.candidates("use te", 6),
+            (4, vec!["testing-orders".to_string()])
+        );
+        assert_eq!(completer.candidates("put pr", 6), (4, vec![]));
+
+        completer.set_at_command_prompt(false);
+        assert_eq!(
+            completer.candidates("pr", 2),
+            (0, vec!["price".to_string(), "product_name".to_string()])
+        );
+        assert_eq!(
+            completer.candidates("attribute_exists(ca", 19),
+            (17, vec!["category".to_string()])
+        );
+        assert_eq!(completer.candidates("qu", 2), (0, vec![]));
+
+        completer.set_field_names(vec!["quantity".to_string()]);
+        assert_eq!(
+            completer.candidates("qu", 2),
+            (0, vec!["quantity".to_string()])
+        );
+    }
+
+    #[cfg(feature = "cli")]
+    #[test]
+    fn test_parse_get_args() -> Result<()> {
+        let projection = |attributes: &[&str]| {
+            Some(
+                attributes
+                    .iter()
+                    .map(|attribute| attribute.to_string())
+                    .collect(),
+            )
+        };
+
+        assert_eq!(command_line::parse_get_args("")?, GetArgs::default());
+        assert_eq!(
+            command_line::parse_get_args("--consistent")?,
+            GetArgs {
+                consistent_read: true,
+                projection: None,
+            }
+        );
+        assert_eq!(
+            command_line::parse_get_args("--projection name, price --consistent")?,
+            GetArgs {
+                consistent_read: true,
+                projection: projection(&["name", "price"]),
+            }
+        );
+        assert_eq!(
+            command_line::parse_get_args("--projection=address.city,tags[0]")?,
+            GetArgs {
+                consistent_read: false,
+                projection: projection(&["address.city", "tags[0]"]),
+            }
+        );
+
+        for (argument, error) in [
+            ("--projection", "--projection needs a list of attributes"),
+            ("--projection= --consistent", "--projection needs a list of attributes"),
+            (
+                "--consistent=yes",
+                "Unknown option '--consistent=yes' for get, expected --consistent or --projection <attributes>",
+            ),
+            (
+                "k1",
+                "Unknown option 'k1' for get, expected --consistent or --projection <attributes>",
+            ),
+        ] {
+            assert_eq!(
+                command_line::parse_get_args(argument)
+                    .unwrap_err()
+                    .to_string(),
+                error
+            );
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "cli")]
+    #[tokio::test]
+    async fn test_consistent_projected_get() -> Result<()> {
+        const GET_TABLE_NAME: &str = "testing-projected-get";
+
+        run_test("consistent_projected_get", |ddb| async move {
+            let table = Table::new(GET_TABLE_NAME, "id", None);
+            ddb.delete_table_if_exists_and_wait(GET_TABLE_NAME, TABLE_WAIT_TIMEOUT)
+                .await?;
+            ddb.create_table_if_not_exists_and_wait(&table, TABLE_WAIT_TIMEOUT)
+                .await?;
+            let key = || Item::new().set_string("id", "k1");
+            ddb.put_item(
+                GET_TABLE_NAME,
+                key()
+                    .set_string("name", "Widget")
+                    .set_string("status", "active")
+                    .set_number("price", 5),
+            )
+            .await?;
+
+            // `name` and `status` are reserved words, so the projection
+            // has to alias them.
+            let projection = ProjectionExpression::new(&["name", "status"]).unwrap();
+            let item = ddb
+                .get_item_with(
+                    GET_TABLE_NAME,
+                    key(),
+                    GetItemOptions {
+                        consistent_read: Some(true),
+                        projection: Some(&projection),
+                    },
+                )
+                .await?
+                .context("Item not found")?;
+            let mut attributes: Vec<_> = item.attributes.keys().cloned().collect();
+            attributes.sort();
+            assert_eq!(attributes, ["name", "status"]);
+            assert_eq!(item.get_string("name").map(String::as_str), Some("Widget"));
+
+            // Flags skip the option prompts; without them they're asked for.
+            let mut input = ScriptedInput::new(&[
+                Some("get --consistent --projection name, status"),
+                Some("k1"),
+                Some("get"),
+                Some("k1"),
+                Some("y"),
+                Some("price"),
+                Some("get --bogus"),
+            ]);
+            command_line::run(
+                &*ddb,
+                &mut input,
+                &mut no_edits,
+                &Interrupt::default(),
+                &Config::default(),
+                Some(&table),
+            )
+            .await?;
+            assert!(input.lines.is_empty());
+            assert!(input.prompts[1].starts_with("Enter id"));
+            assert!(input.prompts[2].contains("Enter command"));
+            assert!(input.prompts[4].starts_with("Use a strongly consistent read?"));
+            assert!(input.prompts[5].starts_with("Enter attributes to project"));
+            // An unknown flag is reported without prompting for the key.
+            assert!(input.prompts[7].contains("Enter command"));
+            assert_eq!(input.prompts.len(), 8);
+
+            ddb.delete_table_if_exists_and_wait(GET_TABLE_NAME, TABLE_WAIT_TIMEOUT)
+                .await?;
+            Ok(())
+        })
+        .await
+    }
+
+    #[cfg(feature = "cli")]
+    #[tokio::test]
+    async fn test_run_with_scripted_input() -> Result<()> {
+        const SESSION_TABLE_NAME: &str = "testing-cli-session";
+
+        run_test("run_with_scripted_input", |ddb| async move {
+            let table = Table::new(SESSION_TABLE_NAME, "id", None);
+            ddb.delete_table_if_exists_and_wait(SESSION_TABLE_NAME, TABLE_WAIT_TIMEOUT)
+                .await?;
+            ddb.create_table_if_not_exists_and_wait(&table, TABLE_WAIT_TIMEOUT)
+                .await?;
+            let item = Item::new().set_string("id", "k1");
+            ddb.put_item(SESSION_TABLE_NAME, item.clone()).await?;
+
+            let mut input = ScriptedInput::new(&[
+                Some("get"),
+                Some("use testing-cli-session"),
+                Some("n"),
+                Some("delete"),
+                None,
+                Some("get"),
+                Some("k1"),
+                Some("n"),
+                Some(""),
+                Some("bogus"),
+                Some("delete"),
+                Some("k1"),
+            ]);
+            command_line::run(
+                &*ddb,
+                &mut input,
+                &mut no_edits,
+                &Interrupt::default(),
+                &Config::default(),
+                None,
+            )
+            .await?;
+
+            assert!(input.lines.is_empty());
+            assert_eq!(input.prompts.len(), 13);
+            assert!(input.prompts[0].starts_with("[no table] Enter command"));
+            assert!(input.prompts[4].starts_with("Enter id"));
+            // Ctrl-C went back to the command prompt with the table still active.
+            assert!(input.prompts[5].starts_with("[testing-cli-session] Enter command"));
+            assert!(input.prompts[12].starts_with("[testing-cli-session] Enter command"));
+            assert!(ddb.get_item(SESSION_TABLE_NAME, item).await?.is_none());
+            // Completion switched to the table's fields once it became active.
+            assert_eq!(input.field_names, [vec!["id".to_string()]]);
+
+            ddb.delete_table_if_exists_and_wait(SESSION_TABLE_NAME, TABLE_WAIT_TIMEOUT)
+                .await?;
+            Ok(())
+        })
+        .await
+    }
+
+    #[cfg(feature = "cli")]
+    #[test]
+    fn test_read_json_item() -> Result<()> {
+        let table = import_table();
+        let convert = |json| {
+            let item = Item::from_json(&json)?;
+            table.validate_item(&item)?;
+            Ok(item)
+        };
+        let read = |lines: &'static [&'static str]| {
+            let mut lines = lines.iter();
+            move |_: &str| {
+                lines
+                    .next()
+                    .map(|line| line.to_string())
+                    .context("Ran out of lines")
+            }
+        };
+
+        let item = command_line::read_json_item(
+            r#"{"category": "Books", "product_name": "Dune","#.to_string(),
+            read(&[
+                r#""details": {"tags": ["sci-fi", 1965], "signed": false},"#,
+                r#""note": "closing } in a string"}"#,
+            ]),
+            convert,
+        )?
+        .context("JSON entry was cancelled")?;
+        assert_eq!(
+            item.get_string(CATEGORY_PARTITION_KEY).map(String::as_str),
+            Some("Books")
+        );
+        assert_eq!(
+            item.attributes.get("details"),
+            Some(&AttributeValue::M(HashMap::from([
+                (
+                    "tags".to_string(),
+                    AttributeValue::L(vec![
+                        AttributeValue::S("sci-fi".to_string()),
+                        AttributeValue::N("1965".to_string())
+                    ])
+                ),
+                ("signed".to_string(), AttributeValue::Bool(false)),
+            ])))
+        );
+        assert_eq!(
+            item.get_string("note").map(String::as_str),
+            Some("closing } in a string")
+        );
+
+        // A syntax error, then an item missing its sort key, then a fix.
+        let item = command_line::read_json_item(
+            r#"{"category": "Books" "product_name": "Dune"}"#.to_string(),
+            read(&[
+                r#"{"category": "Books"}"#,
+                r#"{"category": "Books", "product_name": "Dune", "price": 9.5}"#,
+            ]),
+            convert,
+        )?
+        .context("JSON entry was cancelled")?;
+        assert_eq!(item.get_number(PRICE_ATTRIBUTE), Some(9.5));
+
+        assert!(command_line::read_json_item(
+            r#"{"category": 1, "product_name": "Dune"}"#.to_string(),
+            read(&[""]),
+            convert,
+        )?
+        .is_none());
+        Ok(())
+    }
+
+    #[cfg(feature = "cli")]
+    #[tokio::test]
+    async fn test_put_and_update_with_json() -> Result<()> {
+        const JSON_TABLE_NAME: &str = "testing-json-entry";
+
+        run_test("put_and_update_with_json", |ddb| async move {
+            let table = Table::new(JSON_TABLE_NAME, "id", None).with_schema(
+                Schema::new()
+                    .add_field("id", FieldType::String)
+                    .add_field("count", FieldType::Number),
+            );
+            ddb.delete_table_if_exists_and_wait(JSON_TABLE_NAME, TABLE_WAIT_TIMEOUT)
+                .await?;
+            ddb.create_table_if_not_exists_and_wait(&table, TABLE_WAIT_TIMEOUT)
+                .await?;
+
+            let mut input = ScriptedInput::new(&[
+                Some("put"),
+                Some(r#"{"id": "k1", "count": 1,"#),
+                Some(r#""details": {"tags": ["a", 2], "active": true}}"#),
+                Some("update"),
+                Some("k1"),
+                Some(r#"{"count": "two"}"#),
+                Some(r#"{"count": 2, "note": "hi"}"#),
+            ]);
+            command_line::run(
+                &*ddb,
+                &mut input,
+                &mut no_edits,
+                &Interrupt::default(),
+                &Config::default(),
+                Some(&table),
+            )
+            .await?;
+            assert!(input.lines.is_empty());
+
+            let item = ddb
+                .get_item(JSON_TABLE_NAME, Item::new().set_string("id", "k1"))
+                .await?
+                .context("Item wasn't put")?;
+            assert_eq!(item.get_number("count"), Some(2.0));
+            assert_eq!(item.get_string("note").map(String::as_str), Some("hi"));
+            assert_eq!(
+                item.attributes.get("details"),
+                Some(&AttributeValue::M(HashMap::from([
+                    (
+                        "tags".to_string(),
+                        AttributeValue::L(vec![
+                            AttributeValue::S("a".to_string()),
+                            AttributeValue::N("2".to_string())
+                        ])
+                    ),
+                    ("active".to_string(), AttributeValue::Bool(true)),
+                ])))
+            );
+
+            ddb.delete_table_if_exists_and_wait(JSON_TABLE_NAME, TABLE_WAIT_TIMEOUT)
+                .await?;
+            Ok(())
+        })
+        .await
+    }
+
+    #[cfg(feature = "cli")]
+    #[test]
+    fn test_coerce_field_values() -> Result<()> {
+        assert_eq!(
+            command_line::coerce(&FieldType::String, " spaced ")?,
+            AttributeValue::S(" spaced ".to_string())
+        );
+        assert_eq!(
+            command_line::coerce(&FieldType::String, "")?,
+            AttributeValue::S(String::new())
+        );
+        assert_eq!(
+            command_line::coerce(&FieldType::Number, " 12.50 ")?,
+            AttributeValue::N("12.50".to_string())
+        );
+        assert_eq!(
+            command_line::coerce(&FieldType::Number, "-3e2")?,
+            AttributeValue::N("-3e2".to_string())
+        );
+
+        for bad in ["abc", "", "1,000", "NaN", "inf"] {
+            let error = command_line::coerce(&FieldType::Number, bad).unwrap_err();
+            assert_eq!(error.to_string(), format!("'{bad}' is not a number"));
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "cli")]
+    #[tokio::test]
+    async fn test_copy_item() -> Result<()> {
+        const COPY_TABLE_NAME: &str = "testing-copy-item";
+
+        run_test("copy_item", |ddb| async move {
+            let table = Table::new(COPY_TABLE_NAME, "category", Some("title")).with_schema(
+                Schema::new()
+                    .add_field("category", FieldType::String)
+                    .add_field("title", FieldType::String)
+                    .add_field("color", FieldType::String)
+                    .add_field("price", FieldType::Number),
+            );
+            ddb.delete_table_if_exists_and_wait(COPY_TABLE_NAME, TABLE_WAIT_TIMEOUT)
+                .await?;
+            ddb.create_table_if_not_exists_and_wait(&table, TABLE_WAIT_TIMEOUT)
+                .await?;
+            let key = |category: &str| {
+                Item::new()
+                    .set_string("category", category)
+                    .set_string("title", "Dune")
+            };
+            let source = key("Books")
+                .set_string("color", "blue")
+                .set_number("price", 9.5);
+            ddb.put_item(COPY_TABLE_NAME, source.clone()).await?;
+
+            let mut input = ScriptedInput::new(&[
+                // A plain copy carries every other attribute.
+                Some("copy"),
+                Some("Books"),
+                Some("Dune"),
+                Some("Films"),
+                Some("Dune"),
+                Some("n"),
+                // Fields can be changed on the copy.
+                Some("copy"),
+                Some("Books"),
+                Some("Dune"),
+                Some("Games"),
+                Some("Dune"),
+                Some("y"),
+                Some("u"),
+                Some("red"),
+                Some("s"),
+                // Copying onto the same key is refused...
+                Some("copy"),
+                Some("Books"),
+                Some("Dune"),
+                Some("Books"),
+                Some("Dune"),
+                // ...and so is copying onto an existing item.
+                Some("copy"),
+                Some("Books"),
+                Some("Dune"),
+                Some("Games"),
+                Some("Dune"),
+                Some("n"),
+            ]);
+            command_line::run(
+                &*ddb,
+                &mut input,
+                &mut no_edits,
+                &Interrupt::default(),
+                &Config::default(),
+                Some(&table),
+            )
+            .await?;
+            assert!(input.lines.is_empty());
+
+            let get = |category: &'static str| {
+                let ddb = &ddb;
+                async move {
+                    ddb.get_item(COPY_TABLE_NAME, key(category))
+                        .await?
+                        .with_context(|| format!("No item in {category}"))
+                }
+            };
+            assert_eq!(get("Books").await?.attributes, source.attributes);
+
+            let films = get("Films").await?;
+            assert_eq!(films.get_string("color").map(String::as_str), Some("blue"));
+            assert_eq!(films.get_number("price"), Some(9.5));
+
+            let games = get("Games").await?;
+            assert_eq!(games.get_string("color").map(String::as_str), Some("red"));
+            assert_eq!(games.get_number("price"), Some(9.5));
+
+            assert_eq!(
+                ddb.scan_all(
+                    ScanParams {
+                        table_name: COPY_TABLE_NAME,
+                        ..Default::default()
+                    },
+                    None,
+                    None
+                )
+                .await?
+                .len(),
+                3
+            );
+
+            ddb.delete_table_if_exists_and_wait(COPY_TABLE_NAME, TABLE_WAIT_TIMEOUT)
+                .await?;
+            Ok(())
+        })
+        .await
+    }
+
+    #[cfg(feature = "cli")]
+    #[test]
+    fn test_coerce_complex_values() -> Result<()> {
+        let coerce = command_line::coerce;
+        let strings = |values: &[&str]| -> Vec<String> {
+            values.iter().map(|value| value.to_string()).collect()
+        };
+
+        for yes in ["y", "Yes", " true "] {
+            assert_eq!(
+                coerce(&FieldType::Boolean, yes)?,
+                AttributeValue::Bool(true)
+            );
+        }
+        for no in ["n", "NO", "false"] {
+            assert_eq!(
+                coerce(&FieldType::Boolean, no)?,
+                AttributeValue::Bool(false)
+            );
+        }
+        assert_eq!(
+            coerce(&FieldType::Boolean, "maybe")
+                .unwrap_err()
+                .to_string(),
+            "'maybe' is not y or n"
+        );
+
+        // Sets are comma-separated, or a JSON array as export_csv writes them.
+        assert_eq!(
+            coerce(&FieldType::StringSet, "red, green,, red")?,
+            AttributeValue::Ss(strings(&["red", "green"]))
+        );
+        assert_eq!(
+            coerce(&FieldType::StringSet, r#"["a, b", 1]"#)?,
+            AttributeValue::Ss(strings(&["a, b", "1"]))
+        );
+        assert_eq!(
+            coerce(&FieldType::StringSet, " ").unwrap_err().to_string(),
+            "A set needs at least one value"
+        );
+        assert_eq!(
+            coerce(&FieldType::NumberSet, "1, 2.5")?,
+            AttributeValue::Ns(strings(&["1", "2.5"]))
+        );
+        assert_eq!(
+            coerce(&FieldType::NumberSet, "1, x")
+                .unwrap_err()
+                .to_string(),
+            "'x' is not a number"
+        );
+
+        assert_eq!(
+            coerce(&FieldType::StringList, "a, b, a")?,
+            AttributeValue::L(vec![
+                AttributeValue::S("a".to_string()),
+                AttributeValue::S("b".to_string()),
+                AttributeValue::S("a".to_string()),
+            ])
+        );
+        assert_eq!(
+            coerce(&FieldType::StringList, "")?,
+            AttributeValue::L(Vec::new())
+        );
+
+        assert_eq!(
+            coerce(&FieldType::List, r#"[1, "two", {"three": 3}]"#)?,
+            AttributeValue::L(vec![
+                AttributeValue::N("1".to_string()),
+                AttributeValue::S("two".to_string()),
+                AttributeValue::M(HashMap::from([(
+                    "three".to_string(),
+                    AttributeValue::N("3".to_string()),
+                )])),
+            ])
+        );
+        assert_eq!(
+            coerce(&FieldType::List, r#"{"a": 1}"#)
+                .unwrap_err()
+                .to_string(),
+            r#"Expected a JSON array, found {"a":1}"#
+        );
+
+        assert_eq!(
+            coerce(&FieldType::Map, r#"{"color": "red", "dims": [1, 2]}"#)?,
+            AttributeValue::M(HashMap::from([
+                ("color".to_string(), AttributeValue::S("red".to_string())),
+                (
+                    "dims".to_string(),
+                    AttributeValue::L(vec![
+                        AttributeValue::N("1".to_string()),
+                        AttributeValue::N("2".to_string()),
+                    ]),
+                ),
+            ]))
+        );
+        let error = coerce(&FieldType::Map, r#"{"color": "#)
+            .unwrap_err()
+            .to_string();
+        assert!(
+            error.starts_with(r#"'{"color": ' is not valid JSON"#),
+            "{error}"
+        );
+        assert_eq!(
+            coerce(&FieldType::Map, "[1]").unwrap_err().to_string(),
+            "Expected a JSON object, found [1]"
+        );
+
+        assert!(matches!(
+            command_line::field_type("SS")?,
+            FieldType::StringSet
+        ));
+        assert!(matches!(command_line::field_type("map")?, FieldType::Map));
+        assert!(command_line::field_type("binary").is_err());
+        Ok(())
+    }
+
+    #[cfg(feature = "cli")]
+    #[tokio::test]
+    async fn test_put_complex_values() -> Result<()> {
+        const COMPLEX_TABLE_NAME: &str = "testing-put-complex";
+
+        run_test("put_complex_values", |ddb| async move {
+            let table = Table::new(COMPLEX_TABLE_NAME, "id", None).with_schema(
+                Schema::new()
+                    .add_field("id", FieldType::String)
+                    .add_field("active", FieldType::Boolean)
+                    .add_field("attrs", FieldType::Map)
+                    .add_field("colors", FieldType::StringSet)
+                    .add_field("parts", FieldType::List)
+                    .add_field("sizes", FieldType::NumberSet)
+                    .add_field("tags", FieldType::StringList),
+            );
+            ddb.delete_table_if_exists_and_wait(COMPLEX_TABLE_NAME, TABLE_WAIT_TIMEOUT)
+                .await?;
+            ddb.create_table_if_not_exists_and_wait(&table, TABLE_WAIT_TIMEOUT)
+                .await?;
+
+            // Fields are asked for key first, then by name; each invalid
+            // value is asked for again.
+            let mut input = ScriptedInput::new(&[
+                Some("put"),
+                Some("a1"),
+                Some("maybe"),
+                Some("y"),
+                Some(r#"{"color": "#),
+                Some(r#"{"color": "red", "dims": [1, 2]}"#),
+                Some("red, green, red"),
+                Some(r#"[1, "two"]"#),
+                Some("1, x"),
+                Some("1, 2.5"),
+                Some("a, b"),
+            ]);
+            command_line::run(
+                &*ddb,
+                &mut input,
+                &mut no_edits,
+                &Interrupt::default(),
+                &Config::default(),
+                Some(&table),
+            )
+            .await?;
+            assert!(input.lines.is_empty());
+
+            let item = ddb
+                .get_item(COMPLEX_TABLE_NAME, Item::new().set_string("id", "a1"))
+                .await?
+                .context("Item wasn't put")?;
+            assert_eq!(item.attributes["active"], AttributeValue::Bool(true));
+            assert_eq!(
+                item.get_json("attrs"),
+                Some(serde_json::json!({"color": "red", "dims": [1, 2]}))
+            );
+            let mut colors = item.attributes["colors"].as_ss().unwrap().clone();
+            colors.sort();
+            assert_eq!(colors, ["green", "red"]);
+            assert_eq!(item.get_json("parts"), Some(serde_json::json!([1, "two"])));
+            let mut sizes = item.attributes["sizes"].as_ns().unwrap().clone();
+            sizes.sort();
+            assert_eq!(sizes, ["1", "2.5"]);
+            assert_eq!(item.get_json("tags"), Some(serde_json::json!(["a", "b"])));
+
+            ddb.delete_table_if_exists_and_wait(COMPLEX_TABLE_NAME, TABLE_WAIT_TIMEOUT)
+                .await?;
+            Ok(())
+        })
+        .await
+    }
+
+    #[cfg(feature = "cli")]
+    #[tokio::test]
+    async fn test_put_reprompts_invalid_numbers() -> Result<()> {
+        const NUMBERS_TABLE_NAME: &str = "testing-put-numbers";
+
+        run_test("put_reprompts_invalid_numbers", |ddb| async move {
+            let table = Table::new(NUMBERS_TABLE_NAME, "count", None)
+                .with_schema(Schema::new().add_field("count", FieldType::Number));
+            ddb.delete_table_if_exists_and_wait(NUMBERS_TABLE_NAME, TABLE_WAIT_TIMEOUT)
+                .await?;
+            ddb.create_table_if_not_exists_and_wait(&table, TABLE_WAIT_TIMEOUT)
+                .await?;
+
+            // A bad number is asked for again; an empty answer then abandons
+            // the item and the session carries on.
+            let mut input = ScriptedInput::new(&[
+                Some("put"),
+                Some("abc"),
+                Some("7"),
+                Some("put"),
+                Some("seven"),
+                Some(""),
+                Some("scan_paginated"),
+                Some(""),
+                Some(""),
+                Some(""),
+                Some(""),
+                Some("ten"),
+                Some(""),
+            ]);
+            command_line::run(
+                &*ddb,
+                &mut input,
+                &mut no_edits,
+                &Interrupt::default(),
+                &Config::default(),
+                Some(&table),
+            )
+            .await?;
+            assert!(input.lines.is_empty());
+
+            let items = ddb
+                .scan(ScanParams {
+                    table_name: NUMBERS_TABLE_NAME,
+                    ..Default::default()
+                })
+                .await?;
+            assert_eq!(items.len(), 1);
+            assert_eq!(items[0].get_number("count"), Some(7.0));
+
+            ddb.delete_table_if_exists_and_wait(NUMBERS_TABLE_NAME, TABLE_WAIT_TIMEOUT)
+                .await?;
+            Ok(())
+        })
+        .await
+    }
+
+    /// Queries with a key condition read from `answers`, returning the
+    /// `version` of each item found.
+    #[cfg(feature = "cli")]
+    async fn query_versions(
+        ddb: &DynamoDb,
+        table: &Table<'_>,
+        answers: &[Option<&'static str>],
+    ) -> Result<Vec<f64>> {
+        let mut input = ScriptedInput::new(answers);
+        let key_condition =
+            command_line::read_key_condition(&mut input, table, "account", Some("version"))?
+                .build()?;
+        let items = ddb
+            .query_all(
+                QueryFlexibleParams {
+                    table_name: table.name(),
+                    key_condition_expression: &key_condition.expression,
+                    expression_attribute_names: Some(key_condition.names),
+                    expression_attribute_values: Some(key_condition.values),
+                    ..Default::default()
+                },
+                None,
+            )
+            .await?;
+        Ok(items
+            .iter()
+            .filter_map(|item| item.get_number("version"))
+            .collect())
+    }
+
+    #[cfg(feature = "cli")]
+    #[tokio::test]
+    async fn test_numeric_sort_key_prompts() -> Result<()> {
+        const VERSIONS_TABLE_NAME: &str = "testing-numeric-sort-key";
+
+        run_test("numeric_sort_key_prompts", |ddb| async move {
+            let table = Table::new(VERSIONS_TABLE_NAME, "account", Some("version")).with_schema(
+                Schema::new()
+                    .add_field("account", FieldType::String)
+                    .add_field("version", FieldType::Number),
+            );
+            ddb.delete_table_if_exists_and_wait(VERSIONS_TABLE_NAME, TABLE_WAIT_TIMEOUT)
+                .await?;
+            ddb.create_table_if_not_exists_and_wait(&table, TABLE_WAIT_TIMEOUT)
+                .await?;
+            for version in [1, 2, 10] {
+                ddb.put_item(
+                    VERSIONS_TABLE_NAME,
+                    Item::new()
+                        .set_string("account", "acme")
+                        .set_number("version", version),
+                )
+                .await?;
+            }
+
+            let mut input = ScriptedInput::new(&[Some("acme"), Some("two"), Some("2")]);
+            let key = command_line::create_key_item(&mut input, &table)?;
+            assert_eq!(
+                key.attributes.get("version"),
+                Some(&AttributeValue::N("2".to_string()))
+            );
+            assert!(ddb.get_item(VERSIONS_TABLE_NAME, key).await?.is_some());
+
+            // Numbers compare numerically, so 10 sorts after 2.
+            assert_eq!(
+                query_versions(&ddb, &table, &[Some("acme"), Some(">"), Some("1")]).await?,
+                vec![2.0, 10.0]
+            );
+            assert_eq!(
+                query_versions(
+                    &ddb,
+                    &table,
+                    &[Some("acme"), Some("BETWEEN"), Some("2"), Some("10")]
+                )
+                .await?,
+                vec![2.0, 10.0]
+            );
+
+            // Giving up on a bad key value cancels the command.
+            let mut input = ScriptedInput::new(&[Some("acme"), Some("latest"), Some("")]);
+            let error = command_line::create_key_item(&mut input, &table).unwrap_err();
+            assert!(error.is::<PromptCancelled>());
+
+            let mut input = ScriptedInput::new(&[Some("delete"), Some("acme"), Some("10")]);
+            command_line::run(
+                &*ddb,
+                &mut input,
+                &mut no_edits,
+                &Interrupt::default(),
+                &Config::default(),
+                Some(&table),
+            )
+            .await?;
+            let remaining = ddb
+                .scan(ScanParams {
+                    table_name: VERSIONS_TABLE_NAME,
+                    ..Default::default()
+                })
+                .await?;
+            assert_eq!(remaining.len(), 2);
+            assert!(remaining
+                .iter()
+                .all(|item| item.get_number("version") != Some(10.0)));
+
+            ddb.delete_table_if_exists_and_wait(VERSIONS_TABLE_NAME, TABLE_WAIT_TIMEOUT)
+                .await?;
+            Ok(())
+        })
+        .await
+    }
+
+    #[cfg(feature = "cli")]
+    #[tokio::test]
+    async fn test_update_sets_and_removes_fields() -> Result<()> {
+        const REMOVAL_TABLE_NAME: &str = "testing-update-removal";
+
+        run_test("update_sets_and_removes_fields", |ddb| async move {
+            let table = Table::new(REMOVAL_TABLE_NAME, "id", None).with_schema(
+                Schema::new()
+                    .add_field("id", FieldType::String)
+                    .add_field("discount", FieldType::Number)
+                    .add_field("note", FieldType::String)
+                    .add_field("price", FieldType::Number),
+            );
+            ddb.delete_table_if_exists_and_wait(REMOVAL_TABLE_NAME, TABLE_WAIT_TIMEOUT)
+                .await?;
+            ddb.create_table_if_not_exists_and_wait(&table, TABLE_WAIT_TIMEOUT)
+                .await?;
+            let key = || Item::new().set_string("id", "k1");
+            ddb.put_item(
+                REMOVAL_TABLE_NAME,
+                key()
+                    .set_number("discount", 2)
+                    .set_string("note", "sale")
+                    .set_number("price", 10),
+            )
+            .await?;
+
+            // Fields are offered in name order: discount, note, price. The
+            // note's removal is declined at the schema warning.
+            let mut input = ScriptedInput::new(&[
+                Some("update"),
+                Some("k1"),
+                Some("r"),
+                Some("y"),
+                Some("remove"),
+                Some("n"),
+                Some("u"),
+                Some("12"),
+            ]);
+            command_line::run(
+                &*ddb,
+                &mut input,
+                &mut no_edits,
+                &Interrupt::default(),
+                &Config::default(),
+                Some(&table),
+            )
+            .await?;
+            assert!(input.lines.is_empty());
+
+            let item = ddb
+                .get_item(REMOVAL_TABLE_NAME, key())
+                .await?
+                .context("Item disappeared")?;
+            assert_eq!(item.get_number("price"), Some(12.0));
+            assert_eq!(item.attributes.get("discount"), None);
+            assert_eq!(item.get_string("note").map(String::as_str), Some("sale"));
+
+            // A removal on its own sends no expression values.
+            let mut input = ScriptedInput::new(&[Some("s"), Some("r"), Some("y"), Some("s")]);
+            let changes = command_line::create_update_item(&mut input, &table)?;
+            assert_eq!(changes.remove, vec!["note".to_string()]);
+            ddb.update_with(REMOVAL_TABLE_NAME, key(), changes.into())
+                .await?;
+            let item = ddb.get_item(REMOVAL_TABLE_NAME, key()).await?.unwrap();
+            assert_eq!(item.attributes.get("note"), None);
+
+            ddb.delete_table_if_exists_and_wait(REMOVAL_TABLE_NAME, TABLE_WAIT_TIMEOUT)
+                .await?;
+            Ok(())
+        })
+        .await
+    }
+
+    #[cfg(feature = "cli")]
+    #[test]
+    fn test_diff_edited_item_json() -> Result<()> {
+        let table = Table::new("testing-edits", "id", None)
+            .with_schema(Schema::new().add_field("price", FieldType::Number));
+        let original = Item::new()
+            .set_string("id", "k1")
+            .set_string("name", "Lamp")
+            .set_string("discount", "10%");
+        let mut original = original.to_json(JsonFormat::Plain);
+        // Stored as "12.50", shown as 12.5: unchanged unless edited.
+        original["price"] = serde_json::json!(12.5);
+
+        let edited = serde_json::json!({
+            "id": "k1",
+            "name": "Desk lamp",
+            "price": 12.5,
+            "tags": ["new"],
+        });
+        let changes = command_line::diff_item_json(&original, &edited, &table)?;
+        assert_eq!(changes.remove, vec!["discount".to_string()]);
+        let mut set: Vec<_> = changes.set.attributes.keys().cloned().collect();
+        set.sort();
+        assert_eq!(set, vec!["name".to_string(), "tags".to_string()]);
+        assert_eq!(
+            command_line::render_item_changes(&changes),
+            "SET name = \"Desk lamp\"\nSET tags = [\"new\"]\nREMOVE discount\n"
+        );
+
+        assert!(command_line::diff_item_json(&original, &original, &table)?.is_empty());
+
+        let rekeyed = serde_json::json!({"id": "k2", "name": "Lamp"});
+        let error = command_line::diff_item_json(&original, &rekeyed, &table).unwrap_err();
+        assert_eq!(error.to_string(), "Key attribute 'id' can't be edited");
+
+        let mistyped = serde_json::json!({"id": "k1", "price": "cheap"});
+        assert!(command_line::diff_item_json(&original, &mistyped, &table).is_err());
+        assert!(command_line::diff_item_json(&original, &serde_json::json!([1]), &table).is_err());
+        Ok(())
+    }
+
+    #[cfg(feature = "cli")]
+    #[tokio::test]
+    async fn test_edit_item_in_editor() -> Result<()> {
+        const EDIT_TABLE_NAME: &str = "testing-edit-item";
+
+        run_test("edit_item_in_editor", |ddb| async move {
+            let table = Table::new(EDIT_TABLE_NAME, "id", None)
+                .with_schema(Schema::new().add_field("price", FieldType::Number));
+            ddb.delete_table_if_exists_and_wait(EDIT_TABLE_NAME, TABLE_WAIT_TIMEOUT)
+                .await?;
+            ddb.create_table_if_not_exists_and_wait(&table, TABLE_WAIT_TIMEOUT)
+                .await?;
+            let key = || Item::new().set_string("id", "k1");
+            let original = key().set_number("price", 10).set_string("note", "sale");
+            ddb.put_item(EDIT_TABLE_NAME, original.clone()).await?;
+
+            // Aborting the editor, saving invalid JSON or declining the
+            // summary leaves the item as it was.
+            let edited = r#"{"id": "k1", "price": 12}"#;
+            let mut aborted = |_: &str| Ok(None);
+            let mut invalid = |_: &str| Ok(Some("{\"id\": \"k1\",".to_string()));
+            let mut declined = |_: &str| Ok(Some(edited.to_string()));
+            let sessions: [(&mut dyn ItemEditor, &[Option<&'static str>]); 3] = [
+                (&mut aborted, &[Some("edit"), Some("k1")]),
+                (&mut invalid, &[Some("edit"), Some("k1")]),
+                (&mut declined, &[Some("edit"), Some("k1"), Some("n")]),
+            ];
+            for (editor, answers) in sessions {
+                let mut input = ScriptedInput::new(answers);
+                command_line::run(
+                    &*ddb,
+                    &mut input,
+                    editor,
+                    &Interrupt::default(),
+                    &Config::default(),
+                    Some(&table),
+                )
+                .await?;
+                let item = ddb.get_item(EDIT_TABLE_NAME, key()).await?.unwrap();
+                assert_eq!(
+                    item.to_json(JsonFormat::Plain),
+                    original.to_json(JsonFormat::Plain)
+                );
+            }
+
+            let mut shown = None;
+            let mut editor = |contents: &str| {
+                shown = Some(serde_json::from_str::<serde_json::Value>(contents)?);
+                Ok(Some(edited.to_string()))
+            };
+            let mut input = ScriptedInput::new(&[Some("edit"), Some("k1"), Some("y")]);
+            command_line::run(
+                &*ddb,
+                &mut input,
+                &mut editor,
+                &Interrupt::default(),
+                &Config::default(),
+                Some(&table),
+            )
+            .await?;
+            assert_eq!(shown, Some(original.to_json(JsonFormat::Plain)));
+
+            let item = ddb.get_item(EDIT_TABLE_NAME, key()).await?.unwrap();
+            assert_eq!(item.get_number("price"), Some(12.0));
+            assert_eq!(item.attributes.get("note"), None);
+
+            ddb.delete_table_if_exists_and_wait(EDIT_TABLE_NAME, TABLE_WAIT_TIMEOUT)
+                .await?;
+            Ok(())
+        })
+        .await
+    }
+
+    #[cfg(feature = "cli")]
+    #[tokio::test]
+    async fn test_create_table_from_prompts() -> Result<()> {
+        const CREATED_TABLE_NAME: &str = "testing-created";
+
+        run_test("create_table_from_prompts", |ddb| async move {
+            ddb.delete_table_if_exists_and_wait(CREATED_TABLE_NAME, TABLE_WAIT_TIMEOUT)
+                .await?;
+            let table = command_line::read_table_definition(scripted_answers(&[
+                CREATED_TABLE_NAME,
+                "account",
+                "S",
+                "version",
+                "N",
+                "provisioned",
+                "2",
+                "2",
+                "",
+            ]))?;
+            ddb.create_table_if_not_exists_and_wait(&table, TABLE_WAIT_TIMEOUT)
+                .await?;
+
+            let info = ddb.table_info(CREATED_TABLE_NAME).await?;
+            assert_eq!(info.partition_key, "account");
+            assert_eq!(info.sort_key.as_deref(), Some("version"));
+            assert_eq!(info.billing_mode, BillingMode::Provisioned);
+
+            let item = Item::new()
+                .set_string("account", "acme")
+                .set_number("version", 1);
+            ddb.put_item(CREATED_TABLE_NAME, item.clone()).await?;
+            assert!(ddb.get_item(CREATED_TABLE_NAME, item).await?.is_some());
+
+            ddb.delete_table_if_exists_and_wait(CREATED_TABLE_NAME, TABLE_WAIT_TIMEOUT)
+                .await?;
+            Ok(())
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_enable_stream() -> Result<()> {
+        const STREAM_TABLE_NAME: &str = "testing-streams";
+
+        run_test("enable_stream", |ddb| async move {
+            let table = Table::new(STREAM_TABLE_NAME, CATEGORY_PARTITION_KEY, None);
+            ddb.delete_table_if_exists_and_wait(STREAM_TABLE_NAME, TABLE_WAIT_TIMEOUT)
+                .await?;
+            ddb.create_table_if_not_exists_and_wait(&table, TABLE_WAIT_TIMEOUT)
+                .await?;
+
+            let error = ddb
+                .get_stream_arn(STREAM_TABLE_NAME)
+                .await
+                .expect_err("New table unexpectedly has a stream");
+            assert_eq!(
+                error.downcast_ref::<StreamsDisabled>(),
+                Some(&StreamsDisabled {
+                    table_name: STREAM_TABLE_NAME.to_string()
+                })
+            );
+
+            ddb.enable_stream(STREAM_TABLE_NAME, StreamViewType::NewAndOldImages)
+                .await?;
+            ddb.wait_for_table_active(STREAM_TABLE_NAME, TABLE_WAIT_TIMEOUT)
+                .await?;
+            let stream_arn = ddb.get_stream_arn(STREAM_TABLE_NAME).await?;
+            assert!(stream_arn.contains(STREAM_TABLE_NAME), "{stream_arn}");
+
+            ddb.delete_table_if_exists_and_wait(STREAM_TABLE_NAME, TABLE_WAIT_TIMEOUT)
+                .await?;
+            Ok(())
+        })
+        .await
+    }
+
+    /// Polls `reader` until it has returned `count` records, or fails
+    /// after a few seconds.
+    async fn poll_until(reader: &mut StreamReader, count: usize) -> Result<Vec<StreamRecord>> {
+        let deadline = Instant::now() + Duration::from_secs(10);
+        let mut records = Vec::new();
+        while records.len() < count {
+            if Instant::now() > deadline {
+                anyhow::bail!("Expected {count} stream records, got {records:?}");
+            }
+            records.extend(reader.poll_records().await?);
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        Ok(records)
+    }
+
+    #[tokio::test]
+    async fn test_stream_reader_reacquires_expired_iterators() -> Result<()> {
+        const STREAM_ARN: &str = "arn:aws:dynamodb:us-east-1:000000000000:table/events/stream/1";
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let endpoint = format!("http://{}", listener.local_addr()?);
+        let iterator_requests = Arc::new(Mutex::new(Vec::new()));
+        let reads = Arc::new(AtomicUsize::new(0));
+        {
+            let (iterator_requests, reads) = (iterator_requests.clone(), reads.clone());
+            tokio::spawn(async move {
+                while let Ok((mut stream, _)) = listener.accept().await {
+                    let body = read_request(&mut stream).await;
+                    let (status, response) = if body.contains("ShardIteratorType") {
+                        let mut requests = iterator_requests.lock().unwrap();
+                        requests.push(body);
+                        (
+                            200,
+                            format!(r#"{{"ShardIterator":"iterator-{}"}}"#, requests.len()),
+                        )
+                    } else if body.contains("ShardIterator") {
+                        // Every other read finds its iterator expired.
+                        match reads.fetch_add(1, Ordering::SeqCst) {
+                            0 | 2 => dynamodb_error(400, "ExpiredIteratorException"),
+                            _ => (
+                                200,
+                                r#"{"NextShardIterator":"next","Records":[{"eventName":"INSERT",
+                                    "dynamodb":{"Keys":{"id":{"S":"a"}},"NewImage":{"id":{"S":"a"}},
+                                    "SequenceNumber":"100"}}]}"#
+                                    .to_string(),
+                            ),
+                        }
+                    } else if body.contains("StreamArn") {
+                        (
+                            200,
+                            r#"{"StreamDescription":{"Shards":[{"ShardId":"shard-1",
+                                "SequenceNumberRange":{"StartingSequenceNumber":"1"}}]}}"#
+                                .to_string(),
+                        )
+                    } else {
+                        (
+                            200,
+                            format!(
+                                r#"{{"Table":{{"TableName":"events","TableStatus":"ACTIVE",
+                                    "CreationDateTime":0,"KeySchema":[{{"AttributeName":"id",
+                                    "KeyType":"HASH"}}],"LatestStreamArn":"{STREAM_ARN}"}}}}"#
+                            ),
+                        )
+                    };
+                    write_response(&mut stream, status, &response).await;
+                }
+            });
+        }
+
+        let ddb = fake_client(endpoint);
+        let mut reader = ddb.stream_reader("events").await?;
+        assert_eq!(reader.stream_arn(), STREAM_ARN);
+        let records = reader.poll_records().await?;
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].sequence_number, "100");
+        assert_eq!(
+            records[0].keys.attributes,
+            Item::new().set_string("id", "a").attributes
+        );
+        // Read after the record, though the next iterator expired too.
+        assert_eq!(reader.poll_records().await?.len(), 1);
+
+        let requests = iterator_requests.lock().unwrap();
+        assert_eq!(requests.len(), 3, "{requests:?}");
+        // Opening the reader, then the first expiry, before any record.
+        assert!(requests[0].contains("LATEST"), "{}", requests[0]);
+        assert!(requests[1].contains("LATEST"), "{}", requests[1]);
+        assert!(
+            requests[2].contains("AFTER_SEQUENCE_NUMBER") && requests[2].contains("\"100\""),
+            "{}",
+            requests[2]
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_stream_reader_keeps_records_when_a_shard_fails() -> Result<()> {
+        const STREAM_ARN: &str = "arn:aws:dynamodb:us-east-1:000000000000:table/events/stream/1";
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let endpoint = format!("http://{}", listener.local_addr()?);
+        let second_shard_reads = Arc::new(AtomicUsize::new(0));
+        {
+            let second_shard_reads = second_shard_reads.clone();
+            tokio::spawn(async move {
+                while let Ok((mut stream, _)) = listener.accept().await {
+                    let body = read_request(&mut stream).await;
+                    let (status, response) = if body.contains("ShardIteratorType") {
+                        let shard = if body.contains("shard-1") { 1 } else { 2 };
+                        (200, format!(r#"{{"ShardIterator":"iterator-{shard}"}}"#))
+                    } else if body.contains("iterator-1") {
+                        (
+                            200,
+                            r#"{"NextShardIterator":"next-1","Records":[{"eventName":"INSERT",
+                                "dynamodb":{"Keys":{"id":{"S":"a"}},"SequenceNumber":"100"}}]}"#
+                                .to_string(),
+                        )
+                    } else if body.contains("next-1") {
+                        (
+                            200,
+                            r#"{"NextShardIterator":"next-1","Records":[]}"#.to_string(),
+                        )
+                    } else if body.contains("ShardIterator") {
+                        // The first read of the second shard by each reader
+                        // fails, after the first shard was read.
+                        match second_shard_reads.fetch_add(1, Ordering::SeqCst) {
+                            0 | 3 => dynamodb_error(400, "AccessDeniedException"),
+                            _ => (
+                                200,
+                                r#"{"NextShardIterator":"next-2","Records":[]}"#.to_string(),
+                            ),
+                        }
+                    } else if body.contains("StreamArn") {
+                        (
+                            200,
+                            r#"{"StreamDescription":{"Shards":[
+                                {"ShardId":"shard-1","SequenceNumberRange":{"StartingSequenceNumber":"1"}},
+                                {"ShardId":"shard-2","SequenceNumberRange":{"StartingSequenceNumber":"1"}}]}}"#
+                                .to_string(),
+                        )
+                    } else {
+                        (
+                            200,
+                            format!(
+                                r#"{{"Table":{{"TableName":"events","TableStatus":"ACTIVE",
+                                    "CreationDateTime":0,"KeySchema":[{{"AttributeName":"id",
+                                    "KeyType":"HASH"}}],"LatestStreamArn":"{STREAM_ARN}"}}}}"#
+                            ),
+                        )
+                    };
+                    write_response(&mut stream, status, &response).await;
+                }
+            });
+        }
+
+        let ddb = fake_client(endpoint);
+        let mut reader = ddb.stream_reader("events").await?;
+        let error = reader.poll_records().await.unwrap_err();
+        assert!(format!("{error:#}").contains("AccessDenied"), "{error:#}");
+        // The first shard's record was read before the failure, and the
+        // next poll returns it.
+        let records = reader.poll_records().await?;
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].sequence_number, "100");
+        assert!(reader.poll_records().await?.is_empty());
+
+        // A subscription hands the record to its handler before ending with
+        // the error, so its checkpoint is saved.
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let checkpoints = Arc::new(InMemoryCheckpoints::default());
+        let subscription = ddb
+            .subscribe_changes(
+                "events",
+                checkpoints.clone(),
+                CancellationToken::new(),
+                Box::new({
+                    let seen = seen.clone();
+                    move |record: StreamRecord| seen.lock().unwrap().push(record.sequence_number)
+                }),
+            )
+            .await?;
+        assert!(subscription.await?.is_err());
+        assert_eq!(*seen.lock().unwrap(), ["100"]);
+        assert_eq!(checkpoints.load("shard-1")?.as_deref(), Some("100"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_stream_reader_sees_writes() -> Result<()> {
+        const STREAM_TABLE_NAME: &str = "testing-stream-reader";
+
+        run_test("stream_reader_sees_writes", |ddb| async move {
+            let table = Table::new(STREAM_TABLE_NAME, CATEGORY_PARTITION_KEY, None);
+            ddb.delete_table_if_exists_and_wait(STREAM_TABLE_NAME, TABLE_WAIT_TIMEOUT)
+                .await?;
+            ddb.create_table_if_not_exists_and_wait(&table, TABLE_WAIT_TIMEOUT)
+                .await?;
+
+            let error = ddb
+                .stream_reader(STREAM_TABLE_NAME)
+                .await
+                .expect_err("Read a stream that isn't enabled");
+            assert!(
+                error.downcast_ref::<StreamsDisabled>().is_some(),
+                "{error:?}"
+            );
+
+            ddb.enable_stream(STREAM_TABLE_NAME, StreamViewType::NewAndOldImages)
+                .await?;
+            ddb.wait_for_table_active(STREAM_TABLE_NAME, TABLE_WAIT_TIMEOUT)
+                .await?;
+            let mut reader = ddb.stream_reader(STREAM_TABLE_NAME).await?;
+            assert!(reader.poll_records().await?.is_empty());
+
+            let key = Item::new().set_string(CATEGORY_PARTITION_KEY, "Streamed");
+            ddb.put_item(
+                STREAM_TABLE_NAME,
+                key.clone().set_number(PRICE_ATTRIBUTE, 1.0),
+            )
+            .await?;
+            ddb.put_item(
+                STREAM_TABLE_NAME,
+                key.clone().set_number(PRICE_ATTRIBUTE, 2.0),
+            )
+            .await?;
+            ddb.delete_item(STREAM_TABLE_NAME, key.clone()).await?;
+
+            let records = poll_until(&mut reader, 3).await?;
+            let event_types: Vec<_> = records.iter().map(|record| record.event_type).collect();
+            assert_eq!(
+                event_types,
+                [
+                    StreamEventType::Insert,
+                    StreamEventType::Modify,
+                    StreamEventType::Remove
+                ]
+            );
+            for record in &records {
+                assert_eq!(record.keys.attributes, key.attributes);
+            }
+            let price = |image: &Option<Item>| {
+                image
+                    .as_ref()
+                    .and_then(|image| image.get_number(PRICE_ATTRIBUTE))
+            };
+            assert_eq!(price(&records[0].old_image), None);
+            assert_eq!(price(&records[0].new_image), Some(1.0));
+            assert_eq!(price(&records[1].old_image), Some(1.0));
+            assert_eq!(price(&records[1].new_image), Some(2.0));
+            assert_eq!(price(&records[2].old_image), Some(2.0));
+            assert!(records[2].new_image.is_none());
+            assert!(reader.poll_records().await?.is_empty());
+
+            ddb.delete_table_if_exists_and_wait(STREAM_TABLE_NAME, TABLE_WAIT_TIMEOUT)
+                .await?;
+            Ok(())
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_subscribe_changes_delivers_in_order() -> Result<()> {
+        const SUBSCRIBE_TABLE_NAME: &str = "testing-subscribe-changes";
+
+        run_test("subscribe_changes_delivers_in_order", |ddb| async move {
+            let table = Table::new(SUBSCRIBE_TABLE_NAME, CATEGORY_PARTITION_KEY, None);
+            ddb.delete_table_if_exists_and_wait(SUBSCRIBE_TABLE_NAME, TABLE_WAIT_TIMEOUT)
+                .await?;
+            ddb.create_table_if_not_exists_and_wait(&table, TABLE_WAIT_TIMEOUT)
+                .await?;
+            ddb.enable_stream(SUBSCRIBE_TABLE_NAME, StreamViewType::NewAndOldImages)
+                .await?;
+            ddb.wait_for_table_active(SUBSCRIBE_TABLE_NAME, TABLE_WAIT_TIMEOUT)
+                .await?;
+
+            let checkpoints = Arc::new(InMemoryCheckpoints::default());
+            let received = Arc::new(Mutex::new(Vec::new()));
+            let cancel = CancellationToken::new();
+            let subscription = ddb
+                .subscribe_changes(SUBSCRIBE_TABLE_NAME, checkpoints.clone(), cancel.clone(), {
+                    let received = received.clone();
+                    move |record| received.lock().unwrap().push(record)
+                })
+                .await?;
+
+            let key = Item::new().set_string(CATEGORY_PARTITION_KEY, "Subscribed");
+            for price in [1.0, 2.0, 3.0] {
+                ddb.put_item(
+                    SUBSCRIBE_TABLE_NAME,
+                    key.clone().set_number(PRICE_ATTRIBUTE, price),
+                )
+                .await?;
+            }
+
+            let deadline = Instant::now() + Duration::from_secs(10);
+            while received.lock().unwrap().len() < 3 {
+                assert!(Instant::now() < deadline, "Timed out waiting for changes");
+                tokio::time::sleep(Duration::from_millis(100)).await;
+            }
+            cancel.cancel();
+            subscription.await??;
+
+            let prices: Vec<_> = received
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|record| {
+                    record
+                        .new_image
+                        .as_ref()
+                        .and_then(|image| image.get_number(PRICE_ATTRIBUTE))
+                })
+                .collect();
+            assert_eq!(prices, [Some(1.0), Some(2.0), Some(3.0)]);
+
+            // A change made while nobody is subscribed is picked up by the
+            // next subscription from the saved checkpoints, and nothing older.
+            ddb.delete_item(SUBSCRIBE_TABLE_NAME, key.clone()).await?;
+            let resumed = Arc::new(Mutex::new(Vec::new()));
+            let cancel = CancellationToken::new();
+            let subscription = ddb
+                .subscribe_changes(SUBSCRIBE_TABLE_NAME, checkpoints, cancel.clone(), {
+                    let resumed = resumed.clone();
+                    move |record| resumed.lock().unwrap().push(record.event_type)
+                })
+                .await?;
+            while resumed.lock().unwrap().is_empty() {
+                assert!(Instant::now() < deadline, "Timed out waiting for changes");
+                tokio::time::sleep(Duration::from_millis(100)).await;
+            }
+            cancel.cancel();
+            subscription.await??;
+            assert_eq!(*resumed.lock().unwrap(), [StreamEventType::Remove]);
+
+            ddb.delete_table_if_exists_and_wait(SUBSCRIBE_TABLE_NAME, TABLE_WAIT_TIMEOUT)
+                .await?;
+            Ok(())
+        })
+        .await
+    }
+
+    #[cfg(feature = "cli")]
+    #[tokio::test]
+    async fn test_watch_offers_to_enable_streams() -> Result<()> {
+        const WATCH_TABLE_NAME: &str = "testing-cli-watch";
+
+        run_local_test("watch_offers_to_enable_streams", |ddb| async move {
+            let table = Table::new(WATCH_TABLE_NAME, CATEGORY_PARTITION_KEY, None);
+            ddb.delete_table_if_exists_and_wait(WATCH_TABLE_NAME, TABLE_WAIT_TIMEOUT)
+                .await?;
+            ddb.create_table_if_not_exists_and_wait(&table, TABLE_WAIT_TIMEOUT)
+                .await?;
+
+            // Declining leaves streams off; accepting turns them on and
+            // watches until Enter.
+            let mut input =
+                ScriptedInput::new(&[Some("watch"), Some("n"), Some("watch"), Some("y"), Some("")]);
+            command_line::run(
+                &*ddb,
+                &mut input,
+                &mut no_edits,
+                &Interrupt::default(),
+                &Config::default(),
+                Some(&table),
+            )
+            .await?;
+            assert!(input.lines.is_empty());
+            assert!(input.prompts[1].starts_with("Enable a stream"));
+            assert!(input.prompts[2].contains("Enter command"));
+            assert!(input.prompts[4].is_empty());
+            assert!(input.prompts[5].contains("Enter command"));
+            ddb.get_stream_arn(WATCH_TABLE_NAME).await?;
+
+            // With the stream on, `watch` starts straight away; Ctrl-C stops
+            // it and the session carries on.
+            let mut input = ScriptedInput::new(&[Some("watch"), None]);
+            command_line::run(
+                &*ddb,
+                &mut input,
+                &mut no_edits,
+                &Interrupt::default(),
+                &Config::default(),
+                Some(&table),
+            )
+            .await?;
+            assert_eq!(input.prompts.len(), 3);
+            assert!(input.prompts[1].is_empty());
+            assert!(input.prompts[2].contains("Enter command"));
+
+            ddb.delete_table_if_exists_and_wait(WATCH_TABLE_NAME, TABLE_WAIT_TIMEOUT)
+                .await?;
+            Ok(())
+        })
+        .await
+    }
+
+    fn import_table() -> Table<'static> {
+        Table::new(
+            TEST_TABLE_NAME,
+            CATEGORY_PARTITION_KEY,
+            Some(PRODUCT_NAME_SORT_KEY),
+        )
+        .with_schema(
+            Schema::new()
+                .add_field(CATEGORY_PARTITION_KEY, FieldType::String)
+                .add_field(PRODUCT_NAME_SORT_KEY, FieldType::String)
+                .add_field(PRICE_ATTRIBUTE, FieldType::Number),
+        )
+    }
+
+    #[cfg(feature = "cli")]
+    #[test]
+    fn test_parse_plain_json_import() -> Result<()> {
+        let import = command_line::parse_json_import(
+            r#"[
+                {"category": "Books", "product_name": "Dune", "price": 9.5, "tags": ["scifi"]},
+                {"category": "Books", "product_name": "Emma", "price": 7, "in_stock": true}
+            ]"#,
+            &import_table(),
+        )?;
+        assert!(import.rejected.is_empty(), "{:?}", import.rejected);
+        assert_eq!(import.items.len(), 2);
+        assert_eq!(import.items[0].get_number(PRICE_ATTRIBUTE), Some(9.5));
+        assert_eq!(
+            import.items[0].get_list("tags"),
+            Some(&vec![AttributeValue::S("scifi".to_string())])
+        );
+        assert_eq!(
+            import.items[1].attributes.get("in_stock"),
+            Some(&AttributeValue::Bool(true))
+        );
+        Ok(())
+    }
+
+    #[cfg(feature = "cli")]
+    #[test]
+    fn test_parse_dynamodb_json_import() -> Result<()> {
+        let import = command_line::parse_json_import(
+            r#"[{
+                "category": {"S": "Books"},
+                "product_name": {"S": "Dune"},
+                "price": {"N": "9.50"},
+                "formats": {"SS": ["paperback", "ebook"]},
+                "details": {"M": {"pages": {"N": "412"}, "signed": {"BOOL": false}}},
+                "cover": {"B": "aGk="},
+                "sequel": {"NULL": true}
+            }]"#,
+            &import_table(),
+        )?;
+        assert!(import.rejected.is_empty(), "{:?}", import.rejected);
+        let item = &import.items[0].attributes;
+        assert_eq!(item[PRICE_ATTRIBUTE], AttributeValue::N("9.50".to_string()));
+        assert_eq!(
+            item["formats"],
+            AttributeValue::Ss(vec!["paperback".to_string(), "ebook".to_string()])
+        );
+        assert_eq!(
+            item["details"],
+            AttributeValue::M(HashMap::from([
+                ("pages".to_string(), AttributeValue::N("412".to_string())),
+                ("signed".to_string(), AttributeValue::Bool(false)),
+            ]))
+        );
+        assert_eq!(item["cover"], AttributeValue::B(Blob::new("hi")));
+        assert_eq!(item["sequel"], AttributeValue::Null(true));
+        Ok(())
+    }
+
+    #[cfg(feature = "cli")]
+    #[test]
+    fn test_parse_partially_invalid_json_import() -> Result<()> {
+        let import = command_line::parse_json_import(
+            r#"[
+                {"category": "Books", "product_name": "Dune", "price": 9.5},
+                {"category": "Books", "price": 1},
+                {"category": "Books", "product_name": "Emma", "price": "cheap"},
+                "not an object",
+                {"category": "Games", "product_name": "Go"}
+            ]"#,
+            &import_table(),
+        )?;
+        assert_eq!(import.items.len(), 2);
+        let rejected: Vec<_> = import
+            .rejected
+            .iter()
+            .map(|rejected| rejected.record)
+            .collect();
+        assert_eq!(rejected, [2, 3, 4]);
+        assert!(import.rejected[0].reason.contains(PRODUCT_NAME_SORT_KEY));
+        assert!(import.rejected[1].reason.contains(PRICE_ATTRIBUTE));
+
+        let import = command_line::parse_json_import(
+            r#"[{"category": {"S": "Books"}, "product_name": {"S": "Dune"}},
+                {"category": {"S": "Books"}, "product_name": {"S": "Emma"}, "price": {"N": "abc"}}]"#,
+            &import_table(),
+        )?;
+        assert_eq!(import.items.len(), 1);
+        assert_eq!(import.rejected[0].record, 2);
+
+        for malformed in [
+            r#"[{"category": "Books""#,
+            "{\"category\": \"Books\"}\n{\"category\": ",
+        ] {
+            assert!(command_line::parse_json_import(malformed, &import_table()).is_err());
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "cli")]
+    #[test]
+    fn test_parse_csv_import() -> Result<()> {
+        let csv = "category,product_name,price,note\n\
+                   Books,\"Dune, Deluxe\",9.50,\"says \"\"hi\"\"\nover two lines\"\n\
+                   Books,Emma,cheap,\n\
+                   Games,Go,,\n\
+                   ,Orphan,1,\n\
+                   Games,Chess\n";
+        let import = command_line::parse_csv_import(csv.as_bytes(), &import_table(), false)?;
+
+        assert_eq!(import.items.len(), 2);
+        let dune = &import.items[0];
+        assert_eq!(
+            dune.get_string(PRODUCT_NAME_SORT_KEY).map(String::as_str),
+            Some("Dune, Deluxe")
+        );
+        assert_eq!(
+            dune.attributes[PRICE_ATTRIBUTE],
+            AttributeValue::N("9.50".to_string())
+        );
+        assert_eq!(
+            dune.get_string("note").map(String::as_str),
+            Some("says \"hi\"\nover two lines")
+        );
+        let go = &import.items[1];
+        assert!(!go.attributes.contains_key(PRICE_ATTRIBUTE));
+        assert!(!go.attributes.contains_key("note"));
+
+        let rows: Vec<_> = import
+            .errors
+            .iter()
+            .map(|error| (error.row, error.column.as_deref()))
+            .collect();
+        assert_eq!(
+            rows,
+            [
+                (4, Some(PRICE_ATTRIBUTE)),
+                (6, Some(CATEGORY_PARTITION_KEY)),
+                (7, None)
+            ]
+        );
+        assert!(import.errors[0].reason.contains("'cheap' is not a number"));
+        assert_eq!(
+            import.errors[0].to_string(),
+            "Row 4, column 'price': 'cheap' is not a number"
+        );
+        Ok(())
+    }
+
+    #[cfg(feature = "cli")]
+    #[test]
+    fn test_csv_import_aborts_on_bad_header() {
+        let missing_key = "category,price\nBooks,1\n";
+        let error = command_line::parse_csv_import(missing_key.as_bytes(), &import_table(), false)
+            .expect_err("Imported without a sort key column");
+        assert!(error.to_string().contains(PRODUCT_NAME_SORT_KEY));
+
+        let unknown_column = "category,product_name,color\nBooks,Dune,blue\n";
+        assert!(
+            command_line::parse_csv_import(unknown_column.as_bytes(), &import_table(), false)
+                .is_ok()
+        );
+        let error =
+            command_line::parse_csv_import(unknown_column.as_bytes(), &import_table(), true)
+                .expect_err("Strict import accepted an unknown column");
+        assert!(error.to_string().contains("color"));
+    }
+
+    #[test]
+    fn test_item_to_json() -> Result<()> {
+        let item = Item::new()
+            .set_string("name", "Dune")
+            .set_number("price", 9.5)
+            .set_number("stock", 12);
+        let mut item = item;
+        item.attributes.insert(
+            "formats".to_string(),
+            AttributeValue::Ss(vec!["paperback".to_string()]),
+        );
+        item.attributes
+            .insert("cover".to_string(), AttributeValue::B(Blob::new("hi")));
+
+        assert_eq!(
+            item.to_json(JsonFormat::Plain),
+            serde_json::json!({
+                "name": "Dune",
+                "price": 9.5,
+                "stock": 12,
+                "formats": ["paperback"],
+                "cover": "aGk="
+            })
+        );
+        let dynamodb_json = item.to_json(JsonFormat::DynamoDb);
+        assert_eq!(dynamodb_json["stock"], serde_json::json!({ "N": "12" }));
+        assert_eq!(
+            Item::from_dynamodb_json(dynamodb_json)?.attributes,
+            item.attributes
+        );
+
+        assert_eq!("json".parse::<JsonFormat>()?, JsonFormat::Plain);
+        assert_eq!("DynamoDB".parse::<JsonFormat>()?, JsonFormat::DynamoDb);
+        assert!("csv".parse::<JsonFormat>().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_item_equality() {
+        let number = |n: &str| Item {
+            attributes: HashMap::from([("n".to_string(), AttributeValue::N(n.to_string()))]),
+        };
+        for (a, b) in [
+            ("1.50", "1.5"),
+            ("1e2", "100"),
+            ("-0", "0.000"),
+            ("15E-1", "+1.5"),
+        ] {
+            assert_eq!(number(a), number(b), "{a} vs {b}");
+        }
+        // Digits past f64 precision still count.
+        assert_ne!(
+            number("12345678901234567890123456789012345678"),
+            number("12345678901234567890123456789012345679")
+        );
+        assert_ne!(number("-1"), number("1"));
+        assert_ne!(number("1"), Item::new().set_string("n", "1"));
+
+        let set = |values: &[&str]| Item {
+            attributes: HashMap::from([(
+                "s".to_string(),
+                AttributeValue::Ns(values.iter().map(|n| n.to_string()).collect()),
+            )]),
+        };
+        assert_eq!(set(&["1", "2.0"]), set(&["2", "1.0"]));
+        assert_ne!(set(&["1", "2"]), set(&["1", "3"]));
+
+        let nested = |n: &str| Item {
+            attributes: HashMap::from([(
+                "m".to_string(),
+                AttributeValue::M(HashMap::from([(
+                    "l".to_string(),
+                    AttributeValue::L(vec![AttributeValue::N(n.to_string())]),
+                )])),
+            )]),
+        };
+        assert_eq!(nested("7"), nested("7.0"));
+        assert_ne!(nested("7"), nested("8"));
+        assert_ne!(nested("7"), Item::new());
+    }
+
+    #[cfg(feature = "cli")]
+    #[tokio::test]
+    async fn test_export_and_reimport() -> Result<()> {
+        run_test("export_and_reimport", |ddb| async move {
+            let items: Vec<_> = [("Whole", "12"), ("Fraction", "9.5"), ("Negative", "-0.25")]
+                .into_iter()
+                .map(|(name, price)| {
+                    let mut item = Item::new()
+                        .set_string(CATEGORY_PARTITION_KEY, "Exported")
+                        .set_string(PRODUCT_NAME_SORT_KEY, name);
+                    item.attributes.insert(
+                        PRICE_ATTRIBUTE.to_string(),
+                        AttributeValue::N(price.to_string()),
+                    );
+                    item.attributes.insert(
+                        "tags".to_string(),
+                        AttributeValue::L(vec![AttributeValue::S("a, \"b\"".to_string())]),
+                    );
+                    item
+                })
+                .collect();
+            ddb.batch_put_items(TEST_TABLE_NAME, items.clone(), None)
+                .await?;
+
+            for format in [JsonFormat::Plain, JsonFormat::DynamoDb] {
+                let mut output = Vec::new();
+                let count = command_line::write_export(
+                    &*ddb,
+                    ScanParams {
+                        table_name: TEST_TABLE_NAME,
+                        filter_expression: Some("#c = :c"),
+                        expression_attribute_names: Some(HashMap::from([(
+                            "#c".to_string(),
+                            CATEGORY_PARTITION_KEY.to_string(),
+                        )])),
+                        expression_attribute_values: Some(HashMap::from([(
+                            ":c".to_string(),
+                            AttributeValue::S("Exported".to_string()),
+                        )])),
+                        limit: Some(2),
+                        ..Default::default()
+                    },
+                    format,
+                    &mut output,
+                    None,
+                    &CancellationToken::new(),
+                )
+                .await?
+                .value;
+                assert_eq!(count, 3);
+
+                let contents = String::from_utf8(output)?;
+                assert_eq!(contents.lines().count(), 3);
+                let import = command_line::parse_json_import(&contents, &import_table())?;
+                assert!(import.rejected.is_empty(), "{:?}", import.rejected);
+                for expected in &items {
+                    let name = expected.get_string(PRODUCT_NAME_SORT_KEY);
+                    let imported = import
+                        .items
+                        .iter()
+                        .find(|item| item.get_string(PRODUCT_NAME_SORT_KEY) == name)
+                        .with_context(|| format!("{name:?} missing from {format:?} export"))?;
+                    assert_eq!(imported.attributes, expected.attributes, "{format:?}");
+                }
+            }
+            Ok(())
+        })
+        .await
+    }
+
+    #[cfg(feature = "cli")]
+    #[test]
+    fn test_csv_export_columns_and_quoting() -> Result<()> {
+        let mut dune = Item::new()
+            .set_string(CATEGORY_PARTITION_KEY, "Books")
+            .set_string(PRODUCT_NAME_SORT_KEY, "Dune, Deluxe \"Signed\"")
+            .set_number(PRICE_ATTRIBUTE, 9.5);
+        dune.attributes.insert(
+            "tags".to_string(),
+            AttributeValue::L(vec![
+                AttributeValue::S("scifi".to_string()),
+                AttributeValue::N("1".to_string()),
+            ]),
+        );
+        let emma = Item::new()
+            .set_string(CATEGORY_PARTITION_KEY, "Books")
+            .set_string(PRODUCT_NAME_SORT_KEY, "Emma")
+            .set_string("author", "Austen");
+        let items = [dune, emma];
+
+        assert_eq!(
+            command_line::csv_columns(&import_table(), &items),
+            [
+                CATEGORY_PARTITION_KEY,
+                PRODUCT_NAME_SORT_KEY,
+                PRICE_ATTRIBUTE
+            ]
+        );
+        let schemaless = Table::new(
+            TEST_TABLE_NAME,
+            CATEGORY_PARTITION_KEY,
+            Some(PRODUCT_NAME_SORT_KEY),
+        );
+        let columns = command_line::csv_columns(&schemaless, &items);
+        assert_eq!(
+            columns,
+            [
+                CATEGORY_PARTITION_KEY,
+                PRODUCT_NAME_SORT_KEY,
+                "author",
+                PRICE_ATTRIBUTE,
+                "tags"
+            ]
+        );
+
+        let mut writer = csv::Writer::from_writer(Vec::new());
+        writer.write_record(&columns)?;
+        for item in &items {
+            writer.write_record(command_line::csv_row(item, &columns))?;
+        }
+        let output = String::from_utf8(writer.into_inner()?)?;
+        assert_eq!(
+            output,
+            "category,product_name,author,price,tags\n\
+             Books,\"Dune, Deluxe \"\"Signed\"\"\",,9.5,\"[\"\"scifi\"\",1]\"\n\
+             Books,Emma,Austen,,\n"
+        );
+        Ok(())
+    }
+
+    #[cfg(feature = "cli")]
+    #[tokio::test]
+    async fn test_cancel_bulk_operations() -> Result<()> {
+        const CANCEL_TABLE_NAME: &str = "testing-cancel-bulk";
+
+        run_test("cancel_bulk_operations", |ddb| async move {
+            let table = Table::new(CANCEL_TABLE_NAME, "id", None);
+            ddb.delete_table_if_exists_and_wait(CANCEL_TABLE_NAME, TABLE_WAIT_TIMEOUT)
+                .await?;
+            ddb.create_table_if_not_exists_and_wait(&table, TABLE_WAIT_TIMEOUT)
+                .await?;
+            let items: Vec<_> = (0..30)
+                .map(|i| Item::new().set_string("id", format!("item-{i:02}")))
+                .collect();
+
+            // Cancelling during the first batch stops before the second.
+            let cancel = CancellationToken::new();
+            let written = ddb
+                .batch_put_items_cancellable(
+                    CANCEL_TABLE_NAME,
+                    items.clone(),
+                    Some(&mut |_: &Progress| cancel.cancel()),
+                    &cancel,
+                )
+                .await?;
+            assert_eq!(written.status, BulkStatus::Cancelled);
+            assert_eq!(written.value, 25);
+
+            let written = ddb
+                .batch_put_items_cancellable(
+                    CANCEL_TABLE_NAME,
+                    items,
+                    None,
+                    &CancellationToken::new(),
+                )
+                .await?;
+            assert_eq!(written.status, BulkStatus::Complete);
+            assert_eq!(written.value, 30);
+
+            // Cancelling during the first page returns just that page.
+            let params = || ScanParams {
+                table_name: CANCEL_TABLE_NAME,
+                limit: Some(10),
+                ..Default::default()
+            };
+            let cancel = CancellationToken::new();
+            let mut pages = Vec::new();
+            let scan = ddb
+                .scan_all_cancellable(
+                    params(),
+                    None,
+                    Some(&mut |p: &Progress| {
+                        pages.push(p.pages);
+                        cancel.cancel();
+                    }),
+                    &cancel,
+                )
+                .await?;
+            assert_eq!(scan.status, BulkStatus::Cancelled);
+            assert_eq!(scan.value.len(), 10);
+            assert_eq!(pages, [1]);
+
+            let scan = ddb
+                .scan_all_cancellable(params(), None, None, &CancellationToken::new())
+                .await?;
+            assert_eq!(scan.status, BulkStatus::Complete);
+            assert_eq!(scan.value.len(), 30);
+
+            // The JSON export stops the same way.
+            let cancel = CancellationToken::new();
+            let mut output = Vec::new();
+            let export = command_line::write_export(
+                &*ddb,
+                params(),
+                JsonFormat::Plain,
+                &mut output,
+                Some(&mut |_: &Progress| cancel.cancel()),
+                &cancel,
+            )
+            .await?;
+            assert_eq!(export.status, BulkStatus::Cancelled);
+            assert_eq!(export.value, 10);
+            assert_eq!(String::from_utf8(output)?.lines().count(), 10);
+
+            ddb.delete_table_if_exists_and_wait(CANCEL_TABLE_NAME, TABLE_WAIT_TIMEOUT)
+                .await?;
+            Ok(())
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_batch_put_items() -> Result<()> {
+        run_test("batch_put_items", |ddb| async move {
+            let table = import_table();
+            let items = ItemGenerator::new(table.schema().unwrap().clone(), &table)
+                .with_seed(60)
+                .with_partition_values(vec!["Batched"])
+                .generate(60);
+
+            let mut progress = Vec::new();
+            let written = ddb
+                .batch_put_items(
+                    TEST_TABLE_NAME,
+                    items,
+                    Some(&mut |p: &Progress| progress.push((p.pages, p.items))),
+                )
+                .await?;
+            assert_eq!(written, 60);
+            assert_eq!(progress, [(1, 25), (2, 50), (3, 60)]);
+
+            let count = ddb
+                .query_count(QueryFlexibleParams {
+                    table_name: TEST_TABLE_NAME,
+                    key_condition_expression: "#pk = :pk",
+                    expression_attribute_names: Some(HashMap::from([(
+                        "#pk".to_string(),
+                        CATEGORY_PARTITION_KEY.to_string(),
+                    )])),
+                    expression_attribute_values: Some(HashMap::from([(
+                        ":pk".to_string(),
+                        AttributeValue::S("Batched".to_string()),
+                    )])),
+                    ..Default::default()
+                })
+                .await?;
+            assert_eq!(count.count, 60);
+
+            assert_eq!(
+                ddb.batch_put_items(TEST_TABLE_NAME, Vec::new(), None)
+                    .await?,
+                0
+            );
+            Ok(())
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_for_each_item_stops_early() -> Result<()> {
+        run_local_test("for_each_item_stops_early", |ddb| async move {
+            let items: Vec<Item> = (0..100)
+                .map(|i| {
+                    Item::new()
+                        .set_string(CATEGORY_PARTITION_KEY, "Streamed")
+                        .set_string(PRODUCT_NAME_SORT_KEY, format!("Product{i:03}"))
+                })
+                .collect();
+            ddb.batch_put_items(TEST_TABLE_NAME, items, None).await?;
+            let params = || ScanParams {
+                table_name: TEST_TABLE_NAME,
+                limit: Some(10),
+                ..Default::default()
+            };
+
+            let mut seen = Vec::new();
+            let summary = ddb
+                .for_each_item(params(), |item| {
+                    seen.push(item);
+                    Ok(if seen.len() == 10 {
+                        ControlFlow::Break(())
+                    } else {
+                        ControlFlow::Continue(())
+                    })
+                })
+                .await?;
+            assert_eq!(seen.len(), 10);
+            assert_eq!(summary.items, 10);
+            assert!(summary.stopped_early);
+            assert!((1..=2).contains(&summary.pages), "{summary:?}");
+
+            let mut count = 0;
+            let summary = ddb
+                .for_each_item(params(), |_| {
+                    count += 1;
+                    Ok(ControlFlow::Continue(()))
+                })
+                .await?;
+            assert_eq!(count, 100);
+            assert_eq!(summary.items, 100);
+            assert!(!summary.stopped_early);
+            assert!(summary.pages >= 10);
+
+            let failed = ddb
+                .for_each_item(params(), |_| Err(anyhow::anyhow!("handler failed")))
+                .await;
+            assert_eq!(failed.unwrap_err().to_string(), "handler failed");
+            Ok(())
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_query_all_shards() -> Result<()> {
+        run_local_test("query_all_shards", |ddb| async move {
+            let key = ShardedKey::new("Sharded", 8);
+            let items: Vec<Item> = (0..100)
+                .map(|i| {
+                    let name = format!("Product{i:03}");
+                    // Half hash to their shard, half land anywhere.
+                    let discriminator = (i % 2 == 0).then_some(name.as_str());
+                    Item::new()
+                        .set_sharded_partition_key(CATEGORY_PARTITION_KEY, &key, discriminator)
+                        .set_string(PRODUCT_NAME_SORT_KEY, name.as_str())
+                })
+                .collect();
+            ddb.batch_put_items(TEST_TABLE_NAME, items, None).await?;
+            // Not a shard, so never queried.
+            ddb.put_item(
+                TEST_TABLE_NAME,
+                Item::new()
+                    .set_string(CATEGORY_PARTITION_KEY, "Sharded")
+                    .set_string(PRODUCT_NAME_SORT_KEY, "Product000"),
+            )
+            .await?;
+
+            let merged = ddb
+                .query_all_shards(TEST_TABLE_NAME, CATEGORY_PARTITION_KEY, &key, None)
+                .await?;
+            assert_eq!(merged.len(), 100);
+            let mut names: Vec<_> = merged
+                .iter()
+                .map(|item| item.get_string(PRODUCT_NAME_SORT_KEY).unwrap().clone())
+                .collect();
+            names.sort();
+            names.dedup();
+            assert_eq!(names.len(), 100);
+            assert!(merged.iter().all(|item| {
+                let shard = item.get_string(CATEGORY_PARTITION_KEY).unwrap();
+                key.shards().any(|value| &value == shard)
+            }));
+
+            let narrowed = ddb
+                .query_all_shards(
+                    TEST_TABLE_NAME,
+                    CATEGORY_PARTITION_KEY,
+                    &key,
+                    Some((
+                        PRODUCT_NAME_SORT_KEY,
+                        SortKeyCondition::BeginsWith(AttributeValue::S("Product09".to_string())),
+                    )),
+                )
+                .await?;
+            assert_eq!(narrowed.len(), 10);
+            Ok(())
+        })
+        .await
+    }
+
+    #[test]
+    fn test_sharded_key() {
+        let key = ShardedKey::new("global", 8);
+        assert_eq!(
+            key.shards().collect::<Vec<_>>(),
+            (0..8).map(|n| format!("global#{n}")).collect::<Vec<_>>()
+        );
+        assert_eq!(key.shard(9), "global#1");
+
+        // The hash is fixed, so shards survive restarts and upgrades.
+        assert_eq!(key.shard_for("page-views"), key.shard_for("page-views"));
+        assert_eq!(ShardedKey::new("global", 8).shard_for(""), "global#5");
+        let used: std::collections::HashSet<_> = (0..100)
+            .map(|i| key.shard_for(&format!("item{i}")))
+            .collect();
+        assert_eq!(used.len(), 8);
+
+        for _ in 0..50 {
+            let shard = key.random_shard();
+            assert!(key.shards().any(|value| value == shard), "{shard}");
+        }
+        let item = Item::new().set_sharded_partition_key("pk", &key, Some("page-views"));
+        assert_eq!(item.get_string("pk"), Some(&key.shard_for("page-views")));
+        assert_eq!(ShardedKey::new("solo", 1).random_shard(), "solo#0");
+    }
+
+    #[tokio::test]
+    async fn test_put_items_concurrent() -> Result<()> {
+        run_local_test("put_items_concurrent", |ddb| async move {
+            let items: Vec<Item> = (0..3000)
+                .map(|i| {
+                    Item::new()
+                        .set_string(CATEGORY_PARTITION_KEY, format!("Concurrent{}", i % 7))
+                        .set_string(PRODUCT_NAME_SORT_KEY, format!("Product{i:04}"))
+                        .set_number("price", i)
+                })
+                .collect();
+
+            let stats = ddb.put_items_concurrent(TEST_TABLE_NAME, items, 8).await?;
+            assert_eq!(stats.written, 3000);
+            assert!(stats.failed.is_empty(), "{:?}", stats.failed);
+            assert!(stats.requests >= 120);
+
+            let count = ddb.scan_count(TEST_TABLE_NAME, None, None, None).await?;
+            assert_eq!(count.count, 3000);
+
+            assert!(ddb
+                .put_items_concurrent(TEST_TABLE_NAME, Vec::new(), 0)
+                .await
+                .is_err());
+            Ok(())
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_put_items_concurrent_bounds_in_flight_requests() -> Result<()> {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let endpoint = format!("http://{}", listener.local_addr()?);
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        let most_in_flight = Arc::new(AtomicUsize::new(0));
+        let puts = Arc::new(AtomicUsize::new(0));
+        {
+            let (in_flight, most_in_flight, puts) =
+                (in_flight.clone(), most_in_flight.clone(), puts.clone());
+            tokio::spawn(async move {
+                while let Ok((mut stream, _)) = listener.accept().await {
+                    let (in_flight, most_in_flight, puts) =
+                        (in_flight.clone(), most_in_flight.clone(), puts.clone());
+                    tokio::spawn(async move {
+                        let body = read_request(&mut stream).await;
+                        let now = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                        most_in_flight.fetch_max(now, Ordering::SeqCst);
+                        puts.fetch_add(body.matches("PutRequest").count(), Ordering::SeqCst);
+                        tokio::time::sleep(Duration::from_millis(50)).await;
+                        in_flight.fetch_sub(1, Ordering::SeqCst);
+                        write_response(&mut stream, 200, r#"{"UnprocessedItems":{}}"#).await;
+                    });
+                }
+            });
+        }
+
+        let items: Vec<Item> = (0..400)
+            .map(|i| Item::new().set_string("id", format!("item{i}")))
+            .collect();
+        let stats = fake_client(endpoint)
+            .put_items_concurrent("table", items, 3)
+            .await?;
+        assert_eq!(stats.written, 400);
+        assert_eq!(stats.requests, 16);
+        assert_eq!(puts.load(Ordering::SeqCst), 400);
+        assert_eq!(most_in_flight.load(Ordering::SeqCst), 3);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_put_items_concurrent_reports_failed_items() -> Result<()> {
+        let ddb = failing_client("ValidationException").await?;
+        let items: Vec<Item> = (0..60)
+            .map(|i| Item::new().set_string("id", format!("item{i}")))
+            .collect();
+        let stats = ddb.put_items_concurrent("table", items, 2).await?;
+        assert_eq!(stats.written, 0);
+        assert_eq!(stats.failed.len(), 3);
+        assert_eq!(stats.failed_items(), 60);
+        let mut ids: Vec<_> = stats
+            .failed
+            .iter()
+            .flat_map(|batch| &batch.items)
+            .map(|item| item.get_string("id").unwrap().to_string())
+            .collect();
+        ids.sort();
+        ids.dedup();
+        assert_eq!(ids.len(), 60);
+        Ok(())
+    }
+
+    #[test]
+    fn test_query_params_reach_request() {
+        let ddb = offline_client();
+        let params = QueryFlexibleParams {
+            table_name: TEST_TABLE_NAME,
+            key_condition_expression: "#pk = :pkval",
+            ..Default::default()
+        };
+
+        let request = ddb.query_request(&params, false);
+        assert_eq!(request.get_consistent_read(), &None);
+        assert_eq!(request.get_select(), &None);
+        assert_eq!(request.get_return_consumed_capacity(), &None);
+
+        let params = QueryFlexibleParams {
+            consistent_read: Some(true),
+            select: Some(Select::Count),
+            return_consumed_capacity: Some(ReturnConsumedCapacity::Indexes),
+            ..params
+        };
+        for return_capacity in [false, true] {
+            let request = ddb.query_request(&params, return_capacity);
+            assert_eq!(request.get_consistent_read(), &Some(true));
+            assert_eq!(request.get_select(), &Some(Select::Count));
+            assert_eq!(
+                request.get_return_consumed_capacity(),
+                &Some(ReturnConsumedCapacity::Indexes),
+                "An explicit capacity setting should win"
+            );
+        }
+
+        let params = QueryFlexibleParams {
+            select: Some(Select::AllProjectedAttributes),
+            index_name: Some("price-index"),
+            ..params
+        };
+        assert_eq!(
+            ddb.query_request(&params, false).get_select(),
+            &Some(Select::AllProjectedAttributes)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_query_flexible_asks_for_capacity_only_when_told() -> Result<()> {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let endpoint = format!("http://{}", listener.local_addr()?);
+        let bodies = Arc::new(Mutex::new(Vec::new()));
+        {
+            let bodies = bodies.clone();
+            tokio::spawn(async move {
+                while let Ok((mut stream, _)) = listener.accept().await {
+                    let body = read_request(&mut stream).await;
+                    bodies.lock().unwrap().push(body);
+                    write_response(
+                        &mut stream,
+                        200,
+                        r#"{"Count":0,"ScannedCount":0,"Items":[]}"#,
+                    )
+                    .await;
+                }
+            });
+        }
+
+        let ddb = fake_client(endpoint);
+        let params = QueryFlexibleParams {
+            table_name: TEST_TABLE_NAME,
+            key_condition_expression: "#pk = :pkval",
+            ..Default::default()
+        };
+        ddb.query_flexible(params.clone()).await?;
+        let result = ddb.query_flexible_detailed(params.clone(), None).await?;
+        assert_eq!(result.consumed_capacity, None);
+        ddb.query_flexible_detailed(
+            QueryFlexibleParams {
+                return_consumed_capacity: Some(ReturnConsumedCapacity::Total),
+                ..params
+            },
+            None,
+        )
+        .await?;
+
+        let bodies = bodies.lock().unwrap();
+        assert_eq!(bodies.len(), 3);
+        for body in &bodies[..2] {
+            assert!(!body.contains("ReturnConsumedCapacity"), "{body}");
+        }
+        assert!(
+            bodies[2].contains(r#""ReturnConsumedCapacity":"TOTAL""#),
+            "{}",
+            bodies[2]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_update_expression_building() {
+        // No attributes: nothing to send
+        assert!(UpdateBuilder::from(&Item::new()).build().is_none());
+
+        // One attribute
+        let update = UpdateBuilder::from(&Item::new().set_number(PRICE_ATTRIBUTE, 10.0))
+            .build()
+            .expect("expected an update expression");
+        assert_eq!(update.expression, "SET #attr0 = :val0");
+        assert_eq!(
+            update.names.get("#attr0"),
+            Some(&PRICE_ATTRIBUTE.to_string())
+        );
+        assert_eq!(
+            update.values.get(":val0"),
+            Some(&AttributeValue::N("10".to_string()))
+        );
+
+        // Several attributes each get their own placeholders
+        let update = UpdateBuilder::new()
+            .set("a", AttributeValue::S("1".to_string()))
+            .set("b", AttributeValue::S("2".to_string()))
+            .set_if_not_exists("c", AttributeValue::S("3".to_string()))
+            .build()
+            .expect("expected an update expression");
+        assert_eq!(
+            update.expression,
+            "SET #attr0 = :val0, #attr1 = :val1, #attr2 = if_not_exists(#attr2, :val2)"
+        );
+        assert_eq!(update.names.len(), 3);
+        assert_eq!(update.values.len(), 3);
+
+        // Removals follow the SET clause and need no value placeholders
+        let update = UpdateBuilder::new()
+            .remove("a")
+            .set("b", AttributeValue::S("2".to_string()))
+            .remove("c")
+            .build()
+            .expect("expected an update expression");
+        assert_eq!(
+            update.expression,
+            "SET #attr1 = :val1 REMOVE #attr0, #attr2"
+        );
+        assert_eq!(update.names.len(), 3);
+        assert_eq!(update.values.len(), 1);
+
+        let update = UpdateBuilder::new().remove("a").build().unwrap();
+        assert_eq!(update.expression, "REMOVE #attr0");
+        assert!(update.values.is_empty());
+    }
+
+    #[test]
+    fn test_item_schema_and_table_operations() {
+        // Test Item operations
+        let item = Item::new()
+            .set_string("key1", "value1")
+            .set_number("key2", 42.0);
+        assert_eq!(item.get_string("key1"), Some(&"value1".to_string()));
+        assert_eq!(item.get_number("key2"), Some(42.0));
+
+        // Test Schema operations
+        let schema = Schema::new()
+            .add_field("field1", FieldType::String)
+            .add_field("field2", FieldType::Number);
+        let fields = schema.fields();
+        assert_eq!(fields.len(), 2);
+        assert!(matches!(fields.get("field1"), Some(FieldType::String)));
+        assert!(matches!(fields.get("field2"), Some(FieldType::Number)));
+
+        // Test Table operations
+        let table = Table::new("test_table", "partition_key", Some("sort_key"));
+        assert_eq!(table.name(), "test_table");
+        assert_eq!(table.partition_key(), "partition_key");
+        assert_eq!(table.sort_key(), Some("sort_key"));
+        assert!(table.is_key_attribute("partition_key"));
+        assert!(table.is_key_attribute("sort_key"));
+        assert!(!table.is_key_attribute("other"));
+    }
+
+    #[tokio::test]
+    async fn test_auth_and_describe_table() -> Result<()> {
+        run_test("auth_and_describe_table", |ddb| async move {
+            // DynamoDB Local has no identity to report
+            let auth = ddb.check_auth().await?;
+            assert_eq!(auth.is_some(), !ddb.is_local());
+
+            // Test describe_table
+            let description = ddb.describe_table(TEST_TABLE_NAME).await?;
+            let table = description
+                .table()
+                .ok_or_else(|| anyhow::anyhow!("Table description not found"))?;
+            assert_eq!(
+                table.table_name(),
+                Some(TEST_TABLE_NAME),
+                "Unexpected table name"
+            );
+
+            Ok(())
+        })
+        .await
+    }
+
+    /// A client for AWS (not a local endpoint) with `access_key_id`, that
+    /// answers from a cassette instead of the network.
+    fn aws_replay_client(access_key_id: &str, cassette: &str) -> Result<DynamoDb> {
+        DynamoDb::new(
+            &aws_config::SdkConfig::builder()
+                .behavior_version(aws_config::BehaviorVersion::latest())
+                .region(aws_config::Region::new("us-east-1"))
+                .credentials_provider(SharedCredentialsProvider::new(Credentials::new(
+                    access_key_id,
+                    "secret",
+                    None,
+                    None,
+                    "test",
+                )))
+                .build(),
+        )
+        .with_replay(fixture_path(cassette))
+    }
+
+    #[tokio::test]
+    async fn test_check_auth_reports_identity() -> Result<()> {
+        let ddb = aws_replay_client("AKIDEXAMPLE", "cassettes/caller_identity.json")?;
+        let auth = ddb.check_auth().await?.context("Expected an identity")?;
+        assert_eq!(
+            auth,
+            AuthInfo {
+                account_id: "123456789012".to_string(),
+                arn: "arn:aws:iam::123456789012:user/tester".to_string(),
+                user_id: "AIDAEXAMPLEUSERID".to_string(),
+            }
+        );
+        assert_eq!(
+            auth.to_string(),
+            "arn:aws:iam::123456789012:user/tester (account 123456789012, user ID AIDAEXAMPLEUSERID)"
+        );
+
+        assert!(InMemoryDynamoDb::new().check_auth().await?.is_none());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_check_auth_keeps_sdk_error() -> Result<()> {
+        let ddb = aws_replay_client("AKIDBOGUS", "cassettes/bogus_credentials.json")?;
+        let error = ddb.check_auth().await.unwrap_err();
+        let sdk_error = error
+            .chain()
+            .find_map(|e| e.downcast_ref::<SdkError<GetCallerIdentityError, HttpResponse>>())
+            .with_context(|| format!("No SDK error in {error:?}"))?;
+        assert_eq!(sdk_error.code(), Some("InvalidClientTokenId"));
+        assert!(format!("{error:#}").starts_with("Authentication failed"));
+
+        // Against a local endpoint the ListTables error is kept instead.
+        let ddb = failing_client("UnrecognizedClientException").await?;
+        let error = ddb.check_auth().await.unwrap_err();
+        let sdk_error = error
+            .chain()
+            .find_map(|e| e.downcast_ref::<SdkError<ListTablesError, HttpResponse>>())
+            .with_context(|| format!("No SDK error in {error:?}"))?;
+        assert_eq!(sdk_error.code(), Some("UnrecognizedClientException"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_local_endpoint_detection() {
+        for endpoint in [
+            LOCAL_ENDPOINT,
+            "http://LOCALHOST:8000/",
+            "http://127.0.0.1:8000",
+            "https://[::1]:8000/shell",
+            "localhost:8000",
+        ] {
+            assert!(crate::dynamodb::is_local_endpoint(endpoint), "{endpoint}");
+        }
+        for endpoint in [
+            "https://dynamodb.us-east-1.amazonaws.com",
+            "http://localhost.example.com:8000",
+            "http://10.0.0.5:8000",
+            "http://[2001:db8::1]:8000",
+        ] {
+            assert!(!crate::dynamodb::is_local_endpoint(endpoint), "{endpoint}");
+        }
+
+        let config = |endpoint: &str| {
+            aws_config::SdkConfig::builder()
+                .behavior_version(aws_config::BehaviorVersion::latest())
+                .endpoint_url(endpoint)
+                .build()
+        };
+        assert!(DynamoDb::new(&config("http://127.0.0.1:8000")).is_local());
+        assert!(!DynamoDb::new(&config("https://dynamodb.eu-west-1.amazonaws.com")).is_local());
+        assert!(!offline_client().is_local());
+        assert!(DynamoDb::new_local(LOCAL_ENDPOINT).is_local());
+    }
+
+    #[tokio::test]
+    async fn test_new_local_lists_tables() -> Result<()> {
+        let Some(DynamoDbLocal {
+            endpoint,
+            container: _container,
+            ..
+        }) = requires_local("new_local_lists_tables").await?
+        else {
+            return Ok(());
+        };
+
+        // No credentials or region from the environment are involved.
+        let ddb = DynamoDb::new_local(&endpoint);
+        assert!(ddb.is_local());
+        assert!(ddb.check_auth().await?.is_none());
+
+        let table = Table::new("testing-new-local", "id", None);
+        ddb.create_table_if_not_exists(&table).await?;
+        ddb.wait_for_table_active(table.name(), TABLE_WAIT_TIMEOUT)
+            .await?;
+        assert!(ddb
+            .list_tables()
+            .await?
+            .iter()
+            .any(|name| name == table.name()));
+        ddb.delete_table_if_exists_and_wait(table.name(), TABLE_WAIT_TIMEOUT)
+            .await?;
+
+        let unreachable = DynamoDb::new_local("http://127.0.0.1:9").check_auth().await;
+        let error = unreachable.unwrap_err().to_string();
+        assert!(
+            error.contains("DynamoDB Local at http://127.0.0.1:9"),
+            "{error}"
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_from_client_shares_the_sdk_client() -> Result<()> {
+        const WRAPPED_TABLE_NAME: &str = "testing-from-client";
+
+        let Some(DynamoDbLocal {
+            endpoint,
+            container: _container,
+            ..
+        }) = requires_local("from_client_shares_the_sdk_client").await?
+        else {
+            return Ok(());
+        };
+
+        let config = crate::aws::sdk::Config::builder()
+            .behavior_version(aws_config::BehaviorVersion::latest())
+            .endpoint_url(&endpoint)
+            .region(aws_config::Region::new("us-east-1"))
+            .credentials_provider(Credentials::for_tests())
+            .build();
+        let ddb = DynamoDb::from_client(crate::aws::Client::from_conf(config));
+        assert_eq!(
+            ddb.client().config().region().map(|region| region.as_ref()),
+            Some("us-east-1")
+        );
+        // The endpoint and credentials can't be read back from the client:
+        // it isn't known to be local, there's no STS to ask, so only the
+        // connection is checked.
+        assert!(!ddb.is_local());
+        assert!(ddb.check_auth().await?.is_none());
+
+        let table = Table::new(WRAPPED_TABLE_NAME, "id", None);
+        ddb.delete_table_if_exists_and_wait(table.name(), TABLE_WAIT_TIMEOUT)
+            .await?;
+        ddb.create_table_if_not_exists_and_wait(&table, TABLE_WAIT_TIMEOUT)
+            .await?;
+
+        // Written through the wrapper, read through the raw client...
+        ddb.put_item(WRAPPED_TABLE_NAME, Item::new().set_string("id", "high"))
+            .await?;
+        let raw = ddb
+            .client()
+            .get_item()
+            .table_name(WRAPPED_TABLE_NAME)
+            .key("id", AttributeValue::S("high".to_string()))
+            .send()
+            .await?;
+        assert!(raw.item().is_some());
+
+        // ...and the other way round.
+        ddb.client()
+            .put_item()
+            .table_name(WRAPPED_TABLE_NAME)
+            .item("id", AttributeValue::S("raw".to_string()))
+            .send()
+            .await?;
+        let key = Item::new().set_string("id", "raw");
+        assert!(ddb.get_item(WRAPPED_TABLE_NAME, key).await?.is_some());
+
+        ddb.delete_table_if_exists_and_wait(table.name(), TABLE_WAIT_TIMEOUT)
+            .await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_builder_clients_are_independent() -> Result<()> {
+        const BUILDER_TABLE_NAME: &str = "testing-builder";
+
+        let Some(DynamoDbLocal {
+            endpoint,
+            container: _container,
+            ..
+        }) = requires_local("builder_clients_are_independent").await?
+        else {
+            return Ok(());
+        };
+
+        // One client on DynamoDB Local, relying on the local fallbacks...
+        let local = DynamoDb::builder()
+            .endpoint_url(&endpoint)
+            .timeout(Duration::from_secs(30))
+            .build()
+            .await?;
+        // ...and one on a fake endpoint with everything spelled out.
+        let (fake, requests) =
+            fake_endpoint(|_| (200, r#"{"TableNames":["only-on-b"]}"#.into())).await?;
+        let other = DynamoDb::builder()
+            .endpoint_url(fake)
+            .region("eu-west-1")
+            .credentials("AKIDEXAMPLE", "secret", Some("token".to_string()))
+            .without_retries()
+            .build()
+            .await?;
+
+        let table = Table::new(BUILDER_TABLE_NAME, "id", None);
+        local
+            .delete_table_if_exists_and_wait(table.name(), TABLE_WAIT_TIMEOUT)
+            .await?;
+        local
+            .create_table_if_not_exists_and_wait(&table, TABLE_WAIT_TIMEOUT)
+            .await?;
+
+        let local_tables = local.list_tables().await?;
+        assert!(local_tables.iter().any(|name| name == BUILDER_TABLE_NAME));
+        assert!(!local_tables.iter().any(|name| name == "only-on-b"));
+        assert_eq!(other.list_tables().await?, vec!["only-on-b".to_string()]);
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+
+        local
+            .delete_table_if_exists_and_wait(table.name(), TABLE_WAIT_TIMEOUT)
+            .await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_key_schema_cache() -> Result<()> {
+        let (endpoint, requests) = fake_endpoint(|_| {
+            (
+                200,
+                r#"{"Table":{"TableName":"orders","KeySchema":[
+                    {"AttributeName":"customer","KeyType":"HASH"},
+                    {"AttributeName":"order_id","KeyType":"RANGE"}]}}"#
+                    .to_string(),
+            )
+        })
+        .await?;
+        let ddb = fake_client(endpoint);
+        let expected = KeySchema {
+            partition_key: "customer".to_string(),
+            sort_key: Some("order_id".to_string()),
+        };
+
+        // Lookups that all miss at once share one DescribeTable.
+        let mut lookups = tokio::task::JoinSet::new();
+        for _ in 0..10 {
+            let ddb = ddb.clone();
+            lookups.spawn(async move { ddb.key_schema("orders").await });
+        }
+        while let Some(schema) = lookups.join_next().await {
+            assert_eq!(schema??, expected);
+        }
+        for _ in 0..10 {
+            assert_eq!(ddb.key_schema("orders").await?, expected);
+        }
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+
+        ddb.invalidate_key_schema("orders");
+        assert_eq!(ddb.key_schema("orders").await?, expected);
+        assert_eq!(ddb.key_schema("orders").await?, expected);
+        assert_eq!(requests.load(Ordering::SeqCst), 2);
+
+        let uncached = ddb.with_key_schema_ttl(Duration::ZERO);
+        uncached.key_schema("orders").await?;
+        uncached.key_schema("orders").await?;
+        assert_eq!(requests.load(Ordering::SeqCst), 4);
+        assert_eq!(
+            expected.names().collect::<Vec<_>>(),
+            ["customer", "order_id"]
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_builder_sdk_tuning() -> Result<()> {
+        let ddb = DynamoDb::builder()
+            .endpoint_url(LOCAL_ENDPOINT)
+            .retry_mode(RetryMode::Adaptive)
+            .max_attempts(10)
+            .connect_timeout(Duration::from_millis(500))
+            .read_timeout(Duration::from_secs(2))
+            .timeout(Duration::from_secs(30))
+            .build()
+            .await?;
+        let config = ddb.client().config();
+        let retry = config.retry_config().unwrap();
+        assert_eq!(retry.mode(), RetryMode::Adaptive);
+        assert_eq!(retry.max_attempts(), 10);
+        let timeouts = config.timeout_config().unwrap();
+        assert_eq!(timeouts.connect_timeout(), Some(Duration::from_millis(500)));
+        assert_eq!(timeouts.read_timeout(), Some(Duration::from_secs(2)));
+        assert_eq!(timeouts.operation_timeout(), Some(Duration::from_secs(30)));
+
+        // The mode and attempts combine in either order, and with a policy.
+        let ddb = DynamoDb::builder()
+            .endpoint_url(LOCAL_ENDPOINT)
+            .max_attempts(0)
+            .retry_policy(4, Duration::from_millis(10), Duration::from_secs(1))
+            .retry_mode(RetryMode::Adaptive)
+            .build()
+            .await?;
+        let retry = ddb.client().config().retry_config().unwrap();
+        assert_eq!(retry.mode(), RetryMode::Adaptive);
+        assert_eq!(retry.max_attempts(), 4);
+        assert_eq!(retry.initial_backoff(), Duration::from_millis(10));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_builder_operation_timeout() -> Result<()> {
+        // Accepts connections but never answers them.
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let endpoint = format!("http://{}", listener.local_addr()?);
+        tokio::spawn(async move {
+            let mut open = Vec::new();
+            while let Ok((stream, _)) = listener.accept().await {
+                open.push(stream);
+            }
+        });
+
+        let ddb = DynamoDb::builder()
+            .endpoint_url(endpoint)
+            .max_attempts(5)
+            .timeout(Duration::from_millis(300))
+            .build()
+            .await?;
+        let start = Instant::now();
+        let err = ddb.list_tables().await.unwrap_err();
+        let elapsed = start.elapsed();
+        assert!(elapsed >= Duration::from_millis(300), "{elapsed:?}");
+        assert!(elapsed < Duration::from_secs(2), "{elapsed:?}");
+        assert!(format!("{err:?}").contains("timeout"), "{err:?}");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_builder_needs_credentials_and_region() -> Result<()> {
+        let remote = "https://dynamodb.example.com";
+        let err = DynamoDb::builder()
+            .endpoint_url(remote)
+            .region("eu-west-1")
+            .build()
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("No credentials"), "{err}");
+
+        let err = DynamoDb::builder()
+            .endpoint_url(remote)
+            .credentials("AKIDEXAMPLE", "secret", None)
+            .build()
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("No region"), "{err}");
+
+        DynamoDb::builder()
+            .endpoint_url(remote)
+            .region("eu-west-1")
+            .credentials("AKIDEXAMPLE", "secret", None)
+            .build()
+            .await?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_blocking_crud() -> Result<()> {
+        const BLOCKING_TABLE_NAME: &str = "testing-blocking";
+
+        // Only finding DynamoDB Local needs a runtime; the client brings its own.
+        let Some(DynamoDbLocal {
+            endpoint,
+            container: _container,
+            ..
+        }) = tokio::runtime::Runtime::new()?.block_on(requires_local("blocking_crud"))?
+        else {
+            return Ok(());
+        };
+
+        let ddb = BlockingDynamoDb::new_local(&endpoint)?;
+        let table = Table::new(BLOCKING_TABLE_NAME, "id", None);
+        ddb.delete_table_if_exists_and_wait(table.name(), TABLE_WAIT_TIMEOUT)?;
+        ddb.create_table_if_not_exists_and_wait(&table, TABLE_WAIT_TIMEOUT)?;
+
+        let key = |id: &str| Item::new().set_string("id", id);
+        for n in 0..5 {
+            ddb.put_item(
+                BLOCKING_TABLE_NAME,
+                key(&format!("item-{n}")).set_number("count", n),
+            )?;
+        }
+        let item = ddb.get_item(BLOCKING_TABLE_NAME, key("item-1"))?;
+        assert_eq!(item.and_then(|item| item.get_number("count")), Some(1.0));
+
+        ddb.update_item(
+            BLOCKING_TABLE_NAME,
+            key("item-1"),
+            Item::new().set_number("count", 10),
+        )?;
+        let item = ddb.get_item(BLOCKING_TABLE_NAME, key("item-1"))?;
+        assert_eq!(item.and_then(|item| item.get_number("count")), Some(10.0));
+
+        ddb.delete_item(BLOCKING_TABLE_NAME, key("item-0"))?;
+        assert!(ddb.get_item(BLOCKING_TABLE_NAME, key("item-0"))?.is_none());
+
+        // The paginated helpers block once per page.
+        let mut seen = 0;
+        let mut start_key = None;
+        loop {
+            let params = ScanParams {
+                table_name: BLOCKING_TABLE_NAME,
+                limit: Some(2),
+                ..Default::default()
+            };
+            let (items, next) = ddb.scan_paginated(params, start_key)?;
+            seen += items.len();
+            match next {
+                Some(next) => start_key = Some(next),
+                None => break,
+            }
+        }
+        assert_eq!(seen, 4);
+
+        ddb.delete_table_if_exists_and_wait(table.name(), TABLE_WAIT_TIMEOUT)?;
+        Ok(())
+    }
+
+    #[test]
+    #[should_panic(expected = "can't be used from within an async runtime")]
+    fn test_blocking_inside_runtime_panics() {
+        let ddb = BlockingDynamoDb::new_local(LOCAL_ENDPOINT).unwrap();
+        tokio::runtime::Runtime::new()
+            .unwrap()
+            .block_on(async { ddb.list_tables() })
+            .unwrap();
+    }
+
+    #[cfg(feature = "cli")]
+    #[tokio::test]
+    async fn test_startup_against_local() -> Result<()> {
+        const STARTUP_TABLE_NAME: &str = "testing-startup";
+
+        let Some(DynamoDbLocal {
+            endpoint,
+            container: _container,
+            ..
+        }) = requires_local("startup_against_local").await?
+        else {
+            return Ok(());
+        };
+
+        // The same steps as main, minus the terminal.
+        let env = |name: &str| match name {
+            "AWS_ENDPOINT_URL" => Some(endpoint.clone()),
+            "DDB_TABLE" => Some(STARTUP_TABLE_NAME.to_string()),
+            _ => None,
+        };
+        let config = Config::resolve(None, env, &Flags::default())?;
+        let ddb = command_line::connect(&config).await?;
+        assert!(ddb.is_local());
+        command_line::print_identity(ddb.check_auth().await?.as_ref());
+
+        let table = config.table();
+        ddb.delete_table_if_exists_and_wait(table.name(), TABLE_WAIT_TIMEOUT)
+            .await?;
+        ddb.create_table_if_not_exists(&table).await?;
+        ddb.wait_for_table_active(table.name(), TABLE_WAIT_TIMEOUT)
+            .await?;
+
+        // Raw attribute maps still make items.
+        let item = Item::from(HashMap::from([
+            (
+                CATEGORY_PARTITION_KEY.to_string(),
+                AttributeValue::S("Books".to_string()),
+            ),
+            (
+                PRODUCT_NAME_SORT_KEY.to_string(),
+                AttributeValue::S("Dune".to_string()),
+            ),
+            (
+                PRICE_ATTRIBUTE.to_string(),
+                AttributeValue::N("9.99".to_string()),
+            ),
+        ]));
+        ddb.put_item(table.name(), item.clone()).await?;
+
+        let mut input = ScriptedInput::new(&[Some("config"), Some("stats")]);
+        command_line::run(
+            &ddb,
+            &mut input,
+            &mut no_edits,
+            &Interrupt::default(),
+            &config,
+            Some(&table),
+        )
+        .await?;
+        assert_eq!(input.prompts.len(), 3);
+        assert!(input.prompts[0].starts_with("[testing-startup] Enter command"));
+        let key = Item::new()
+            .set_string(CATEGORY_PARTITION_KEY, "Books")
+            .set_string(PRODUCT_NAME_SORT_KEY, "Dune");
+        assert_eq!(ddb.get_item(table.name(), key).await?, Some(item));
+        assert!(ddb.metrics().is_some());
+
+        ddb.delete_table_if_exists_and_wait(table.name(), TABLE_WAIT_TIMEOUT)
+            .await?;
+        Ok(())
+    }
+
+    #[cfg(feature = "cli")]
+    #[test]
+    fn test_config_precedence() -> Result<()> {
+        let path = std::path::Path::new("ddb.toml");
+        let file = ConfigFile::parse(
+            r#"
+            [table]
+            name = "file-table"
+            partition_key = "id"
+            sort_key = ""
+            prefix = "dev-"
+
+            [schema]
+            id = "string"
+            price = "number"
+
+            [output]
+            format = "json"
+            "#,
+            path,
+        )?;
+        let env = |name: &str| match name {
+            "DDB_TABLE" => Some("env-table".to_string()),
+            "DDB_OUTPUT" => Some("dynamodb".to_string()),
+            "AWS_ENDPOINT_URL" => Some("http://localhost:8000".to_string()),
+            _ => None,
+        };
+        let flags = Flags::parse(["--table=flag-table", "--output", "table"].map(String::from))?;
+
+        // Flag > env > file > default, setting by setting.
+        let config = Config::resolve(Some(&file), env, &flags)?;
+        assert_eq!(config.table_name.value, "flag-table");
+        assert_eq!(config.table_name.source, Source::Flag("--table"));
+        assert_eq!(config.output_format.value, OutputFormat::Table);
+        assert_eq!(
+            config.endpoint_url.value.as_deref(),
+            Some("http://localhost:8000")
+        );
+        assert_eq!(config.endpoint_url.source, Source::Env("AWS_ENDPOINT_URL"));
+        assert_eq!(config.partition_key.value, "id");
+        assert_eq!(config.partition_key.source, Source::File(path.into()));
+        assert_eq!(config.sort_key.value, None);
+        assert_eq!(config.schema.value.fields().len(), 2);
+        assert_eq!(config.full_table_name(), "dev-flag-table");
+
+        let config = Config::resolve(Some(&file), env, &Flags::default())?;
+        assert_eq!(config.table_name.value, "env-table");
+        assert_eq!(config.output_format.value, OutputFormat::DynamoDb);
+
+        let config = Config::resolve(Some(&file), |_| None, &Flags::default())?;
+        assert_eq!(config.table_name.value, "file-table");
+        assert_eq!(config.output_format.value, OutputFormat::Json);
+
+        let config = Config::resolve(None, |_| None, &Flags::default())?;
+        assert_eq!(config.table_name.source, Source::Default);
+        assert_eq!(
+            config.sort_key.value.as_deref(),
+            Some(PRODUCT_NAME_SORT_KEY)
+        );
+        assert_eq!(config.table().name(), config.table_name.value);
+
+        let rendered = Config::resolve(Some(&file), env, &flags)?.to_string();
+        assert!(
+            rendered.contains("table = \"flag-table\" (flag --table)"),
+            "{rendered}"
+        );
+        assert!(
+            rendered.contains("sort_key = (none) (file ddb.toml)"),
+            "{rendered}"
+        );
+
+        assert!(config.capacity.value);
+        let flags = Flags::parse(["--no-capacity".to_string()])?;
+        let config = Config::resolve(None, |_| None, &flags)?;
+        assert!(!config.capacity.value);
+        assert_eq!(config.capacity.source, Source::Flag("--no-capacity"));
+
+        assert!(Flags::parse(["--table".to_string()]).is_err());
+        Ok(())
+    }
+
+    #[cfg(feature = "cli")]
+    #[test]
+    fn test_config_env_overrides() -> Result<()> {
+        let env = |name: &str| match name {
+            "DDB_PARTITION_KEY" => Some("sku".to_string()),
+            "DDB_SORT_KEY" => Some(String::new()),
+            "DDB_TABLE_PREFIX" => Some("ci-".to_string()),
+            _ => None,
+        };
+        let config = Config::resolve(None, env, &Flags::default())?;
+        assert_eq!(config.table_name.value, TEST_TABLE_NAME);
+        assert_eq!(config.table_name.source, Source::Default);
+        assert_eq!(config.partition_key.value, "sku");
+        assert_eq!(
+            config.partition_key.source,
+            Source::Env("DDB_PARTITION_KEY")
+        );
+        // An empty sort key means the table has none.
+        assert_eq!(config.sort_key.value, None);
+        assert_eq!(config.sort_key.source, Source::Env("DDB_SORT_KEY"));
+        assert_eq!(config.full_table_name(), format!("ci-{TEST_TABLE_NAME}"));
+
+        let config = Config::resolve(None, |_| None, &Flags::default())?;
+        assert_eq!(config.partition_key.value, CATEGORY_PARTITION_KEY);
+        assert_eq!(config.full_table_name(), TEST_TABLE_NAME);
+
+        let env = |name: &str| (name == "DDB_OUTPUT").then(|| "yaml".to_string());
+        assert!(Config::resolve(None, env, &Flags::default()).is_err());
+        Ok(())
+    }
+
+    #[cfg(feature = "cli")]
+    #[test]
+    fn test_config_region_and_profile() -> Result<()> {
+        let path = std::path::Path::new("ddb.toml");
+        let file = ConfigFile::parse(
+            r#"
+            [connection]
+            region = "eu-west-1"
+            profile = "staging"
+            "#,
+            path,
+        )?;
+        let config = Config::resolve(Some(&file), |_| None, &Flags::default())?;
+        assert_eq!(config.region.value.as_deref(), Some("eu-west-1"));
+        assert_eq!(config.profile.value.as_deref(), Some("staging"));
+        assert_eq!(config.profile.source, Source::File(path.into()));
+
+        let flags = Flags::parse(["--region", "us-west-2", "--profile=prod"].map(String::from))?;
+        let config = Config::resolve(Some(&file), |_| None, &flags)?;
+        assert_eq!(config.region.value.as_deref(), Some("us-west-2"));
+        assert_eq!(config.region.source, Source::Flag("--region"));
+        assert_eq!(config.profile.value.as_deref(), Some("prod"));
+        let rendered = config.to_string();
+        assert!(
+            rendered.contains("profile = \"prod\" (flag --profile)"),
+            "{rendered}"
+        );
+
+        // The SDK reads AWS_REGION and AWS_PROFILE itself when neither is set.
+        let env = |name: &str| (name == "AWS_REGION").then(|| "ap-south-1".to_string());
+        let config = Config::resolve(None, env, &Flags::default())?;
+        assert_eq!(config.region.value, None);
+        assert_eq!(config.profile.value, None);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_clones_write_concurrently() -> Result<()> {
+        run_test("clones_write_concurrently", |ddb| async move {
+            const TASKS: usize = 10;
+            const ITEMS_PER_TASK: usize = 5;
+
+            let metrics = Arc::new(Metrics::new());
+            let shared = ddb.with_new_connections().with_metrics(metrics.clone());
+            let tasks: Vec<_> = (0..TASKS)
+                .map(|task| {
+                    let ddb = shared.clone();
+                    tokio::spawn(async move {
+                        for n in 0..ITEMS_PER_TASK {
+                            let item = Item::new()
+                                .set_string(CATEGORY_PARTITION_KEY, "Concurrent")
+                                .set_string(PRODUCT_NAME_SORT_KEY, format!("{task}-{n}"));
+                            ddb.put_item(TEST_TABLE_NAME, item).await?;
+                        }
+                        Ok::<_, anyhow::Error>(())
+                    })
+                })
+                .collect();
+            for task in tasks {
+                task.await??;
+            }
+
+            let items = ddb.scan_table(TEST_TABLE_NAME).await?;
+            assert_eq!(items.len(), TASKS * ITEMS_PER_TASK);
+            // Every clone counted into the same metrics.
+            let puts = metrics.snapshot().operations["PutItem"].calls;
+            assert_eq!(puts, (TASKS * ITEMS_PER_TASK) as u64);
+            Ok(())
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_metrics_count_operations() -> Result<()> {
+        run_test("metrics_count_operations", |ddb| async move {
+            let metrics = Arc::new(Metrics::new());
+            let ddb = ddb.with_new_connections().with_metrics(metrics.clone());
+            let item = |name: &str| {
+                Item::new()
+                    .set_string(CATEGORY_PARTITION_KEY, "Metrics")
+                    .set_string(PRODUCT_NAME_SORT_KEY, name)
+            };
+            ddb.put_item(TEST_TABLE_NAME, item("a")).await?;
+            ddb.put_item_with_capacity(TEST_TABLE_NAME, item("b"))
+                .await?;
+            assert!(ddb.get_item(TEST_TABLE_NAME, item("a")).await?.is_some());
+            assert!(ddb.get_item(TEST_TABLE_NAME, item("c")).await?.is_none());
+            assert_eq!(ddb.scan_table(TEST_TABLE_NAME).await?.len(), 2);
+            ddb.get_item("testing-no-such-table", item("a"))
+                .await
+                .unwrap_err();
+
+            let snapshot = metrics.snapshot();
+            let put = &snapshot.operations["PutItem"];
+            assert_eq!((put.calls, put.items, put.error_count()), (2, 2, 0));
+            let get = &snapshot.operations["GetItem"];
+            assert_eq!((get.calls, get.items), (3, 1));
+            assert_eq!(get.errors["ResourceNotFoundException"], 1);
+            assert_eq!(snapshot.operations["Scan"].items, 2);
+            for op in snapshot.operations.values() {
+                let bucketed: u64 = op.latency_buckets.iter().map(|b| b.count).sum();
+                assert_eq!(bucketed, op.calls);
+                assert!(op.max_latency_ms > 0.0);
+            }
+            assert_eq!(ddb.metrics().map(Metrics::snapshot), Some(snapshot.clone()));
+
+            let json = serde_json::to_value(&snapshot)?;
+            assert_eq!(json["operations"]["PutItem"]["calls"], 2);
+            assert!(snapshot.to_string().contains("GetItem"));
+            Ok(())
+        })
+        .await
+    }
+
+    #[test]
+    fn test_capacity_totals_by_table() {
+        use crate::dynamodb::CapacityKind;
+        use aws_sdk_dynamodb::types::ConsumedCapacity;
+
+        let consumed = |table: &str, units: f64| {
+            ConsumedCapacity::builder()
+                .table_name(table)
+                .capacity_units(units)
+                .build()
+        };
+        let metrics = Metrics::new();
+        metrics.record_capacity("GetItem", CapacityKind::Read, &[consumed("products", 0.5)]);
+        metrics.record_capacity("PutItem", CapacityKind::Write, &[consumed("products", 1.0)]);
+        metrics.record_capacity(
+            "BatchGetItem",
+            CapacityKind::Read,
+            &[consumed("products", 1.5), consumed("orders", 2.0)],
+        );
+        // A split reported by DynamoDB wins over the operation's kind.
+        metrics.record_capacity(
+            "TransactWriteItems",
+            CapacityKind::Write,
+            &[ConsumedCapacity::builder()
+                .table_name("orders")
+                .capacity_units(3.0)
+                .read_capacity_units(1.0)
+                .write_capacity_units(2.0)
+                .build()],
+        );
+        metrics.record_capacity("Scan", CapacityKind::Read, &[]);
+
+        let snapshot = metrics.snapshot();
+        assert_eq!(
+            snapshot.tables["products"],
+            TableCapacity {
+                read_units: 2.0,
+                write_units: 1.0
+            }
+        );
+        assert_eq!(
+            snapshot.tables["orders"],
+            TableCapacity {
+                read_units: 3.0,
+                write_units: 2.0
+            }
+        );
+        assert_eq!(snapshot.total_capacity().total(), 8.0);
+
+        // The batch get cost more than any other single request.
+        let costliest = snapshot.costliest.clone().unwrap();
+        assert_eq!(costliest.operation, "BatchGetItem");
+        assert_eq!(costliest.tables, ["products", "orders"]);
+        assert_eq!(costliest.capacity.to_string(), "3.5 RCU");
+
+        let before = snapshot.total_capacity();
+        metrics.record_capacity("PutItem", CapacityKind::Write, &[consumed("orders", 4.0)]);
+        let snapshot = metrics.snapshot();
+        assert_eq!((snapshot.total_capacity() - before).to_string(), "4.0 WCU");
+        assert_eq!(snapshot.costliest.unwrap().operation, "PutItem");
+    }
+
+    #[tokio::test]
+    async fn test_consumed_capacity_is_requested() -> Result<()> {
+        run_test("consumed_capacity_is_requested", |ddb| async move {
+            let metrics = Arc::new(Metrics::new());
+            let ddb = ddb
+                .with_new_connections()
+                .with_metrics(metrics.clone())
+                .with_consumed_capacity();
+            let item = Item::new()
+                .set_string(CATEGORY_PARTITION_KEY, "Capacity")
+                .set_string(PRODUCT_NAME_SORT_KEY, "Counted");
+            ddb.put_item(TEST_TABLE_NAME, item.clone()).await?;
+            assert!(ddb.get_item(TEST_TABLE_NAME, item).await?.is_some());
+
+            let table = metrics.snapshot().tables[TEST_TABLE_NAME];
+            assert!(table.read_units > 0.0, "{table:?}");
+            assert!(table.write_units > 0.0, "{table:?}");
+            Ok(())
+        })
+        .await
+    }
+
+    /// A span seen by [`SpanCapture`].
+    #[derive(Debug, Clone)]
+    struct CapturedSpan {
+        name: &'static str,
+        /// Index of the parent span in the captured list.
+        parent: Option<usize>,
+        fields: BTreeMap<String, String>,
+    }
+
+    /// A tracing layer that keeps every span with its parent and fields,
+    /// including those recorded after the span was created, and the fields
+    /// of every event.
+    #[derive(Clone, Default)]
+    struct SpanCapture {
+        spans: Arc<Mutex<Vec<CapturedSpan>>>,
+        events: Arc<Mutex<Vec<BTreeMap<String, String>>>>,
+        /// Where each live span id is in `spans`; ids are reused once a span
+        /// closes, so they can't index `spans` directly.
+        live: Arc<Mutex<HashMap<u64, usize>>>,
+    }
+
+    struct FieldRecorder<'a>(&'a mut BTreeMap<String, String>);
+
+    impl tracing::field::Visit for FieldRecorder<'_> {
+        fn record_str(&mut self, field: &tracing::field::Field, value: &str) {
+            self.0.insert(field.name().to_string(), value.to_string());
+        }
+
+        fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn std::fmt::Debug) {
+            self.0
+                .insert(field.name().to_string(), format!("{value:?}"));
+        }
+    }
+
+    impl<S> tracing_subscriber::Layer<S> for SpanCapture
+    where
+        S: tracing::Subscriber + for<'a> tracing_subscriber::registry::LookupSpan<'a>,
+    {
+        fn on_new_span(
+            &self,
+            attrs: &tracing::span::Attributes<'_>,
+            id: &tracing::span::Id,
+            ctx: tracing_subscriber::layer::Context<'_, S>,
+        ) {
+            let mut live = self.live.lock().unwrap();
+            let parent = ctx
+                .span(id)
+                .and_then(|span| span.parent())
+                .and_then(|parent| live.get(&parent.id().into_u64()).copied());
+            let mut fields = BTreeMap::new();
+            attrs.record(&mut FieldRecorder(&mut fields));
+            let mut spans = self.spans.lock().unwrap();
+            live.insert(id.into_u64(), spans.len());
+            spans.push(CapturedSpan {
+                name: attrs.metadata().name(),
+                parent,
+                fields,
+            });
+        }
+
+        fn on_record(
+            &self,
+            id: &tracing::span::Id,
+            values: &tracing::span::Record<'_>,
+            _ctx: tracing_subscriber::layer::Context<'_, S>,
+        ) {
+            if let Some(&index) = self.live.lock().unwrap().get(&id.into_u64()) {
+                values.record(&mut FieldRecorder(
+                    &mut self.spans.lock().unwrap()[index].fields,
+                ));
+            }
+        }
+
+        fn on_event(
+            &self,
+            event: &tracing::Event<'_>,
+            _ctx: tracing_subscriber::layer::Context<'_, S>,
+        ) {
+            let mut fields = BTreeMap::new();
+            event.record(&mut FieldRecorder(&mut fields));
+            self.events.lock().unwrap().push(fields);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_client_methods_open_spans() -> Result<()> {
+        use tracing_subscriber::layer::SubscriberExt;
+
+        run_test("client_methods_open_spans", |ddb| async move {
+            for name in ["First", "Second", "Third"] {
+                ddb.put_item(
+                    TEST_TABLE_NAME,
+                    Item::new()
+                        .set_string(CATEGORY_PARTITION_KEY, "Traced")
+                        .set_string(PRODUCT_NAME_SORT_KEY, name),
+                )
+                .await?;
+            }
+            let key_condition = KeyCondition::partition(
+                CATEGORY_PARTITION_KEY,
+                AttributeValue::S("Traced".to_string()),
+            )
+            .build()?;
+            let params =
+                || QueryFlexibleParams::from_key_condition(TEST_TABLE_NAME, &key_condition);
+
+            // The test runtime is single-threaded, so a thread-local
+            // subscriber sees every span the client opens.
+            let capture = SpanCapture::default();
+            let _default = tracing::subscriber::set_default(
+                tracing_subscriber::registry().with(capture.clone()),
+            );
+            assert_eq!(ddb.query_flexible(params()).await?.len(), 3);
+            let paged = QueryFlexibleParams {
+                limit: Some(2),
+                ..params()
+            };
+            assert_eq!(ddb.query_all(paged, None).await?.len(), 3);
+
+            let spans = capture.spans.lock().unwrap().clone();
+            let find = |name: &str| {
+                spans
+                    .iter()
+                    .position(|span| span.name == name)
+                    .unwrap_or_else(|| panic!("No {name} span in {spans:?}"))
+            };
+            let outer = find("query_flexible");
+            assert_eq!(spans[outer].parent, None);
+            assert_eq!(spans[outer].fields["table_name"], TEST_TABLE_NAME);
+            assert_eq!(spans[outer].fields["index_name"], "None");
+
+            // query_flexible delegates to query_flexible_detailed, whose
+            // span records the operation and how many items came back.
+            let inner = find("query_flexible_detailed");
+            assert_eq!(spans[inner].parent, Some(outer));
+            assert_eq!(spans[inner].fields["operation"], "Query");
+            assert_eq!(spans[inner].fields["table_name"], TEST_TABLE_NAME);
+            assert_eq!(spans[inner].fields["count"], "3");
+            assert!(
+                spans.iter().any(|span| span.parent == Some(inner)),
+                "The request made no spans under query_flexible_detailed"
+            );
+
+            // query_all opens a child span per page.
+            let all = find("query_all");
+            assert_eq!(spans[all].fields["count"], "3");
+            let pages: Vec<_> = spans
+                .iter()
+                .filter(|span| span.name == "page" && span.parent == Some(all))
+                .collect();
+            assert_eq!(pages[0].fields["number"], "1");
+            assert_eq!(pages[0].fields["count"], "2");
+            assert_eq!(pages[1].fields["number"], "2");
+            assert_eq!(pages[1].fields["count"], "1");
+            Ok(())
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_wire_logging_redacts_credentials() -> Result<()> {
+        use crate::dynamodb::WireLog;
+        use tracing_subscriber::layer::SubscriberExt;
+
+        let (endpoint, _) = fake_endpoint(|_| (200, "{}".to_string())).await?;
+        let client = DynamoDb::new(
+            &aws_config::SdkConfig::builder()
+                .behavior_version(aws_config::BehaviorVersion::latest())
+                .endpoint_url(endpoint)
+                .region(aws_config::Region::new("us-east-1"))
+                .credentials_provider(SharedCredentialsProvider::new(
+                    Credentials::for_tests_with_session_token(),
+                ))
+                .build(),
+        );
+        let item = Item::new()
+            .set_string(CATEGORY_PARTITION_KEY, "Logged")
+            .set_string(PRODUCT_NAME_SORT_KEY, "On the wire");
+        let logged_request = |ddb: DynamoDb| {
+            let item = item.clone();
+            async move {
+                let capture = SpanCapture::default();
+                let _default = tracing::subscriber::set_default(
+                    tracing_subscriber::registry().with(capture.clone()),
+                );
+                ddb.put_item(TEST_TABLE_NAME, item).await?;
+                let events = capture.events.lock().unwrap().clone();
+                anyhow::Ok(events)
+            }
+        };
+        let find = |events: &[BTreeMap<String, String>], message: &str| {
+            events
+                .iter()
+                .find(|event| event.get("message").is_some_and(|m| m == message))
+                .unwrap_or_else(|| panic!("No {message:?} event in {events:?}"))
+                .clone()
+        };
+
+        let events = logged_request(client.with_new_connections().with_wire_logging(true)).await?;
+        let request = find(&events, "DynamoDB request");
+        assert_eq!(request["method"], "POST");
+        assert_eq!(request["operation"], "DynamoDB_20120810.PutItem");
+        assert!(request["body"].contains(r#""TableName":"testing-products""#));
+        assert!(request["body"].contains(r#"{"S":"On the wire"}"#));
+        assert!(request["headers"].contains("authorization: REDACTED"));
+        assert!(request["headers"].contains("x-amz-security-token: REDACTED"));
+        assert!(!request["headers"].contains("Credential="));
+        assert!(!request["headers"].contains(
+            Credentials::for_tests_with_session_token()
+                .session_token()
+                .unwrap()
+        ));
+
+        let response = find(&events, "DynamoDB response");
+        assert_eq!(response["status"], "200");
+        assert_eq!(response["body"], "{}");
+
+        // Long attribute values can be cut short; names and keys stay whole.
+        let events =
+            logged_request(client.with_wire_log(WireLog::new().truncate_values(4))).await?;
+        let request = find(&events, "DynamoDB request");
+        assert!(request["body"].contains(r#"{"S":"On t... (11 chars)"}"#));
+        assert!(request["body"].contains(&format!(r#""{PRODUCT_NAME_SORT_KEY}":"#)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_wire_log_settings() {
+        use crate::dynamodb::WireLog;
+
+        let from = |vars: &[(&str, &str)]| {
+            WireLog::from_vars(|name| {
+                vars.iter()
+                    .find(|(var, _)| *var == name)
+                    .map(|(_, value)| value.to_string())
+            })
+        };
+        assert_eq!(from(&[]), None);
+        assert_eq!(from(&[("DDB_WIRE_LOG", "0")]), None);
+        assert_eq!(from(&[("DDB_WIRE_LOG", "off")]), None);
+        assert_eq!(from(&[("DDB_WIRE_LOG", "1")]), Some(WireLog::new()));
+        assert_eq!(
+            from(&[("DDB_WIRE_LOG", "true"), ("DDB_WIRE_LOG_MAX_VALUE", "8")]),
+            Some(WireLog::new().truncate_values(8))
+        );
+        // Truncation alone doesn't turn logging on.
+        assert_eq!(from(&[("DDB_WIRE_LOG_MAX_VALUE", "8")]), None);
+    }
+
+    /// Keeps every span exported to it, in place of an OTLP collector.
+    #[cfg(feature = "otel")]
+    #[derive(Debug, Clone, Default)]
+    struct SpanCollector(Arc<Mutex<Vec<opentelemetry_sdk::trace::SpanData>>>);
+
+    #[cfg(feature = "otel")]
+    impl opentelemetry_sdk::trace::SpanExporter for SpanCollector {
+        async fn export(
+            &self,
+            batch: Vec<opentelemetry_sdk::trace::SpanData>,
+        ) -> opentelemetry_sdk::error::OTelSdkResult {
+            self.0.lock().unwrap().extend(batch);
+            Ok(())
+        }
+    }
+
+    #[cfg(feature = "otel")]
+    #[tokio::test]
+    async fn test_spans_export_to_opentelemetry() -> Result<()> {
+        use tracing_subscriber::layer::SubscriberExt;
+
+        run_test("spans_export_to_opentelemetry", |ddb| async move {
+            let collector = SpanCollector::default();
+            let provider = opentelemetry_sdk::trace::SdkTracerProvider::builder()
+                .with_simple_exporter(collector.clone())
+                .build();
+            let key = Item::new()
+                .set_string(CATEGORY_PARTITION_KEY, "Exported")
+                .set_string(PRODUCT_NAME_SORT_KEY, "Span");
+            {
+                let _default = tracing::subscriber::set_default(
+                    tracing_subscriber::registry().with(crate::logging::telemetry_layer(&provider)),
+                );
+                ddb.put_item(TEST_TABLE_NAME, key.clone()).await?;
+                assert!(ddb.get_item(TEST_TABLE_NAME, key).await?.is_some());
+            }
+            provider.force_flush()?;
+
+            let spans = collector.0.lock().unwrap().clone();
+            let attribute = |name: &str, key: &str| {
+                let span = spans
+                    .iter()
+                    .find(|span| span.name == name)
+                    .unwrap_or_else(|| panic!("No {name} span was exported"));
+                span.attributes
+                    .iter()
+                    .find(|attribute| attribute.key.as_str() == key)
+                    .map(|attribute| attribute.value.as_str().into_owned())
+            };
+            assert_eq!(
+                attribute("put_item", "table_name").as_deref(),
+                Some(TEST_TABLE_NAME)
+            );
+            assert_eq!(
+                attribute("put_item", "operation").as_deref(),
+                Some("PutItem")
+            );
+            assert_eq!(
+                attribute("get_item", "table_name").as_deref(),
+                Some(TEST_TABLE_NAME)
+            );
+            // Fields recorded after the span opened are exported too.
+            assert_eq!(attribute("get_item", "found").as_deref(), Some("true"));
+            Ok(())
         })
         .await
     }
+
+    #[cfg(feature = "cli")]
+    #[test]
+    fn test_verbose_flags_stack() -> Result<()> {
+        let verbosity = |args: &[&str]| Flags::parse(args.iter().map(|arg| arg.to_string()));
+        assert_eq!(verbosity(&[])?.verbose, 0);
+        assert_eq!(verbosity(&["--verbose"])?.verbose, 1);
+        assert_eq!(verbosity(&["-v"])?.verbose, 1);
+        assert_eq!(verbosity(&["-vv"])?.verbose, 2);
+        assert_eq!(verbosity(&["-v", "--table", "t", "-vv"])?.verbose, 3);
+        assert!(verbosity(&["--verbose=2"]).is_err());
+        assert!(verbosity(&["-vx"]).is_err());
+        assert!(verbosity(&["-"]).is_err());
+        Ok(())
+    }
+
+    #[cfg(feature = "logging")]
+    #[test]
+    fn test_logging_init_twice() -> Result<()> {
+        // A silent subscriber stands in for one set earlier (and keeps log
+        // lines out of the test output); initializing must leave it be
+        // rather than fail.
+        let _ =
+            tracing::subscriber::set_global_default(tracing::subscriber::NoSubscriber::default());
+        crate::logging::init()?;
+        crate::logging::init()?;
+        crate::logging::init_with_level(tracing::Level::DEBUG)?;
+        crate::logging::init_with_verbosity(2)?;
+        Ok(())
+    }
+
+    #[cfg(feature = "cli")]
+    #[test]
+    fn test_malformed_config_file() {
+        let path = std::path::Path::new("broken.toml");
+
+        let error = ConfigFile::parse("[table]\nname = \"unterminated\n", path).unwrap_err();
+        let message = error.to_string();
+        assert!(message.contains("broken.toml"), "{message}");
+        assert!(message.contains("line 2"), "{message}");
+
+        let error = ConfigFile::parse("[table]\ntable_name = \"products\"\n", path).unwrap_err();
+        assert!(error.to_string().contains("table_name"), "{error}");
+
+        let file = ConfigFile::parse("[schema]\nprice = \"decimal\"\n", path).unwrap();
+        let error = Config::resolve(Some(&file), |_| None, &Flags::default()).unwrap_err();
+        assert!(format!("{error:#}").contains("'price'"), "{error:#}");
+    }
+
+    /// A table keyed by `category` and a numeric `rank`, with a few items.
+    async fn in_memory_products() -> Result<InMemoryDynamoDb> {
+        let ddb = InMemoryDynamoDb::new();
+        let table = Table::new("products", "category", Some("rank")).with_schema(
+            Schema::new()
+                .add_field("category", FieldType::String)
+                .add_field("rank", FieldType::Number),
+        );
+        assert!(ddb.create_table_if_not_exists(&table).await?.is_some());
+        assert!(ddb.create_table_if_not_exists(&table).await?.is_none());
+
+        for (category, rank, name, price) in [
+            ("tools", 1.0, "hammer", 12.5),
+            ("tools", 2.0, "saw", 20.0),
+            ("tools", 10.0, "drill", 80.0),
+            ("toys", 1.0, "kite", 15.0),
+        ] {
+            ddb.put_item(
+                "products",
+                Item::new()
+                    .set_string("category", category)
+                    .set_number("rank", rank)
+                    .set_string("name", name)
+                    .set_number("price", price),
+            )
+            .await?;
+        }
+        Ok(ddb)
+    }
+
+    fn names(items: &[Item]) -> Vec<&str> {
+        items
+            .iter()
+            .filter_map(|item| item.get_string("name").map(String::as_str))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_item_operations() -> Result<()> {
+        let ddb = in_memory_products().await?;
+        let key = |category: &str, rank: f64| {
+            Item::new()
+                .set_string("category", category)
+                .set_number("rank", rank)
+        };
+
+        let item = ddb.get_item("products", key("tools", 2.0)).await?;
+        assert_eq!(item.and_then(|item| item.get_number("price")), Some(20.0));
+        // Numbers are keys by value, not by how they're written.
+        let mut one = key("tools", 1.0);
+        one.attributes
+            .insert("rank".to_string(), AttributeValue::N("1.00".to_string()));
+        assert!(ddb.get_item("products", one).await?.is_some());
+        assert!(ddb.get_item("products", key("tools", 3.0)).await?.is_none());
+
+        let table = ddb.load_table("products", None).await?;
+        assert_eq!(table.sort_key(), Some("rank"));
+        assert!(
+            !ddb.put_item_if_not_exists(&table, key("tools", 1.0).set_string("name", "mallet"))
+                .await?
+        );
+        assert!(
+            ddb.put_item_if_not_exists(&table, key("tools", 3.0).set_string("name", "plane"))
+                .await?
+        );
+
+        ddb.update_with(
+            "products",
+            key("tools", 1.0),
+            UpdateBuilder::new()
+                .set("price", AttributeValue::N("14".to_string()))
+                .set_if_not_exists("name", AttributeValue::S("mallet".to_string()))
+                .set_if_not_exists("stock", AttributeValue::N("5".to_string())),
+        )
+        .await?;
+        let item = ddb
+            .get_item("products", key("tools", 1.0))
+            .await?
+            .context("Item was lost")?;
+        assert_eq!(item.get_number("price"), Some(14.0));
+        assert_eq!(item.get_string("name").map(String::as_str), Some("hammer"));
+        assert_eq!(item.get_number("stock"), Some(5.0));
+
+        ddb.update_with(
+            "products",
+            key("tools", 1.0),
+            UpdateBuilder::new().remove("stock"),
+        )
+        .await?;
+        let item = ddb.get_item("products", key("tools", 1.0)).await?.unwrap();
+        assert!(!item.attributes.contains_key("stock"));
+        // Updating a missing item creates it from the key.
+        ddb.update_with(
+            "products",
+            key("games", 1.0),
+            UpdateBuilder::new().set("name", AttributeValue::S("chess".to_string())),
+        )
+        .await?;
+        assert!(ddb.get_item("products", key("games", 1.0)).await?.is_some());
+        assert!(ddb
+            .update_with(
+                "products",
+                key("games", 1.0),
+                UpdateBuilder::new().set("category", AttributeValue::S("x".to_string())),
+            )
+            .await
+            .is_err());
+
+        let projection = ProjectionExpression::new(&["name"]).unwrap();
+        let projected = ddb
+            .get_item_with(
+                "products",
+                key("tools", 2.0),
+                GetItemOptions {
+                    projection: Some(&projection),
+                    ..Default::default()
+                },
+            )
+            .await?
+            .unwrap();
+        assert_eq!(projected.attributes.keys().collect::<Vec<_>>(), ["name"]);
+
+        ddb.delete_item("products", key("tools", 2.0)).await?;
+        assert!(ddb.get_item("products", key("tools", 2.0)).await?.is_none());
+        assert!(ddb
+            .put_item("products", Item::new().set_string("category", "tools"))
+            .await
+            .is_err());
+        assert!(ddb.get_item("missing", key("tools", 1.0)).await.is_err());
+
+        assert_eq!(ddb.table_info("products").await?.item_count, 5);
+        assert_eq!(ddb.list_tables_with_prefix("prod").await?, ["products"]);
+        assert!(
+            ddb.delete_table_if_exists_and_wait("products", TABLE_WAIT_TIMEOUT)
+                .await?
+        );
+        assert!(!ddb.table_exists("products").await?);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_query_and_scan() -> Result<()> {
+        let ddb = in_memory_products().await?;
+        let tools = ("category", AttributeValue::S("tools".to_string()));
+
+        // Sort keys order numerically, not as text.
+        let items = ddb
+            .query_simple("products", tools.clone(), None, None, None, None, None)
+            .await?;
+        assert_eq!(names(&items), ["hammer", "saw", "drill"]);
+        let items = ddb
+            .query_simple(
+                "products",
+                tools.clone(),
+                Some((
+                    "rank",
+                    ">=".to_string(),
+                    AttributeValue::N("2".to_string()).into(),
+                )),
+                None,
+                None,
+                None,
+                Some(&Filter::lt("price", AttributeValue::N("50".to_string())).build()),
+            )
+            .await?;
+        assert_eq!(names(&items), ["saw"]);
+
+        let key_condition = KeyCondition::partition("category", tools.1.clone()).build()?;
+        let params = QueryFlexibleParams {
+            table_name: "products",
+            key_condition_expression: &key_condition.expression,
+            expression_attribute_names: Some(key_condition.names.clone()),
+            expression_attribute_values: Some(key_condition.values.clone()),
+            scan_index_forward: Some(false),
+            limit: Some(2),
+            ..Default::default()
+        };
+        let first = ddb.query_flexible_detailed(params.clone(), None).await?;
+        assert_eq!(names(&first.items), ["drill", "saw"]);
+        assert_eq!(first.scanned_count, 2);
+        let second = ddb
+            .query_flexible_detailed(params.clone(), first.last_evaluated_key)
+            .await?;
+        assert_eq!(names(&second.items), ["hammer"]);
+        assert!(second.last_evaluated_key.is_none());
+        assert_eq!(
+            names(&ddb.query_all(params.clone(), Some(2)).await?),
+            ["drill", "saw"]
+        );
+        let count = ddb
+            .query_flexible_detailed(
+                QueryFlexibleParams {
+                    select: Some(Select::Count),
+                    limit: None,
+                    ..params
+                },
+                None,
+            )
+            .await?;
+        assert!(count.items.is_empty());
+        assert_eq!(count.count, 3);
+
+        // Scans follow every page and report progress per page.
+        let mut pages = Vec::new();
+        let items = ddb
+            .scan_all(
+                ScanParams {
+                    table_name: "products",
+                    limit: Some(3),
+                    ..Default::default()
+                },
+                None,
+                Some(&mut |progress: &Progress| pages.push(progress.items)),
+            )
+            .await?;
+        assert_eq!(items.len(), 4);
+        assert_eq!(pages, [3, 4]);
+
+        let (page, next) = ddb
+            .scan_paginated(
+                ScanParams {
+                    table_name: "products",
+                    filter_expression: Some("price > :p"),
+                    expression_attribute_values: Some(HashMap::from([(
+                        ":p".to_string(),
+                        AttributeValue::N("13".to_string()),
+                    )])),
+                    limit: Some(2),
+                    ..Default::default()
+                },
+                None,
+            )
+            .await?;
+        // The limit counts items evaluated before the filter.
+        assert_eq!(names(&page), ["saw"]);
+        assert!(next.is_some());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_filter_expressions() -> Result<()> {
+        let ddb = in_memory_products().await?;
+        let mut yoyo = Item::new()
+            .set_string("category", "toys")
+            .set_number("rank", 2.0)
+            .set_string("name", "yoyo");
+        yoyo.attributes.insert(
+            "tags".to_string(),
+            AttributeValue::L(vec![AttributeValue::S("classic".to_string())]),
+        );
+        yoyo.attributes.insert(
+            "details".to_string(),
+            AttributeValue::M(HashMap::from([(
+                "color".to_string(),
+                AttributeValue::S("red".to_string()),
+            )])),
+        );
+        ddb.put_item("products", yoyo).await?;
+
+        let scan = |expression: &'static str| {
+            let ddb = &ddb;
+            async move {
+                let items = ddb
+                    .scan_all(
+                        ScanParams {
+                            table_name: "products",
+                            filter_expression: Some(expression),
+                            expression_attribute_names: Some(HashMap::from([(
+                                "#c".to_string(),
+                                "category".to_string(),
+                            )])),
+                            expression_attribute_values: Some(HashMap::from([
+                                (":tools".to_string(), AttributeValue::S("tools".to_string())),
+                                (":toys".to_string(), AttributeValue::S("toys".to_string())),
+                                (":lo".to_string(), AttributeValue::N("13".to_string())),
+                                (":hi".to_string(), AttributeValue::N("20".to_string())),
+                                (":one".to_string(), AttributeValue::N("1".to_string())),
+                                (":red".to_string(), AttributeValue::S("red".to_string())),
+                                (":tag".to_string(), AttributeValue::S("classic".to_string())),
+                                (":h".to_string(), AttributeValue::S("h".to_string())),
+                                (":num".to_string(), AttributeValue::S("N".to_string())),
+                            ])),
+                            ..Default::default()
+                        },
+                        None,
+                        None,
+                    )
+                    .await?;
+                let mut names: Vec<String> = names(&items).into_iter().map(String::from).collect();
+                names.sort();
+                anyhow::Ok(names)
+            }
+        };
+
+        assert_eq!(scan("price BETWEEN :lo AND :hi").await?, ["kite", "saw"]);
+        assert_eq!(
+            scan("NOT (#c IN (:tools)) AND attribute_exists(price)").await?,
+            ["kite"]
+        );
+        assert_eq!(
+            scan("attribute_not_exists(price) OR begins_with(name, :h)").await?,
+            ["hammer", "yoyo"]
+        );
+        assert_eq!(scan("details.color = :red").await?, ["yoyo"]);
+        assert_eq!(
+            scan("tags[0] = :tag AND size(tags) = :one").await?,
+            ["yoyo"]
+        );
+        assert_eq!(scan("contains(tags, :tag)").await?, ["yoyo"]);
+        assert_eq!(
+            scan("attribute_type(price, :num) AND #c = :toys").await?,
+            ["kite"]
+        );
+        assert!(scan("price > :undefined").await.is_err());
+        assert!(scan("price >").await.is_err());
+        Ok(())
+    }
+
+    #[cfg(feature = "cli")]
+    #[tokio::test]
+    async fn test_failed_commands_return_to_the_prompt() -> Result<()> {
+        // The active table was never created, so every command on it fails.
+        let ddb = InMemoryDynamoDb::new();
+        let table = Table::new("notes", "id", None);
+
+        let mut input = ScriptedInput::new(&[
+            Some("info"),
+            Some("list"),
+            Some("backup"),
+            Some(""),
+            Some("exit"),
+            Some("info"),
+        ]);
+        command_line::run(
+            &ddb,
+            &mut input,
+            &mut no_edits,
+            &Interrupt::default(),
+            &Config::default(),
+            Some(&table),
+        )
+        .await?;
+        // Only `exit` ended the session.
+        assert_eq!(input.lines, [Some("info")]);
+        assert_eq!(input.prompts.len(), 5);
+        assert!(input.prompts[4].contains("Enter command"));
+        Ok(())
+    }
+
+    #[cfg(feature = "cli")]
+    #[tokio::test]
+    async fn test_use_missing_table_keeps_active_table() -> Result<()> {
+        let ddb = InMemoryDynamoDb::new();
+        let table = Table::new("notes", "id", None).with_schema(
+            Schema::new()
+                .add_field("id", FieldType::String)
+                .add_field("count", FieldType::Number),
+        );
+        ddb.create_table_if_not_exists(&table).await?;
+
+        let mut input = ScriptedInput::new(&[
+            Some("use missing"),
+            Some("n"),
+            Some("put"),
+            Some(r#"{"id": "k1", "count": 1}"#),
+        ]);
+        command_line::run(
+            &ddb,
+            &mut input,
+            &mut no_edits,
+            &Interrupt::default(),
+            &Config::default(),
+            Some(&table),
+        )
+        .await?;
+        assert!(input.lines.is_empty());
+        assert!(input.prompts[2].starts_with("[notes] Enter command"));
+        assert!(ddb
+            .get_item("notes", Item::new().set_string("id", "k1"))
+            .await?
+            .is_some());
+        Ok(())
+    }
+
+    #[cfg(feature = "cli")]
+    #[tokio::test]
+    async fn test_invalid_limits_are_asked_for_again() -> Result<()> {
+        let ddb = InMemoryDynamoDb::new();
+        let table = Table::new("notes", "id", None);
+        ddb.create_table_if_not_exists(&table).await?;
+
+        // A bad limit is asked for again; an empty answer then abandons the
+        // command and the session carries on.
+        let mut input = ScriptedInput::new(&[
+            Some("scan"),
+            Some(""),
+            Some("0"),
+            Some("2"),
+            Some("query_paginated"),
+            Some("id = :id"),
+            Some(""),
+            Some(""),
+            Some(":id"),
+            Some("S"),
+            Some("k1"),
+            Some(""),
+            Some("ten"),
+            Some(""),
+        ]);
+        command_line::run(
+            &ddb,
+            &mut input,
+            &mut no_edits,
+            &Interrupt::default(),
+            &Config::default(),
+            Some(&table),
+        )
+        .await?;
+        assert!(input.lines.is_empty());
+        assert!(input.prompts[2].starts_with("Enter limit"));
+        assert!(input.prompts[3].starts_with("Enter limit"));
+        assert!(input.prompts[4].contains("Enter command"));
+        assert!(input.prompts[12].starts_with("Enter page size"));
+        assert!(input.prompts[13].starts_with("Enter page size"));
+        assert!(input.prompts[14].contains("Enter command"));
+        Ok(())
+    }
+
+    #[cfg(feature = "cli")]
+    #[tokio::test]
+    async fn test_run_against_in_memory_dynamodb() -> Result<()> {
+        let ddb = InMemoryDynamoDb::new();
+        let table = Table::new("notes", "id", None).with_schema(
+            Schema::new()
+                .add_field("id", FieldType::String)
+                .add_field("count", FieldType::Number),
+        );
+        ddb.create_table_if_not_exists(&table).await?;
+
+        let mut input = ScriptedInput::new(&[
+            Some("whoami"),
+            Some("put"),
+            Some(r#"{"id": "k1", "count": 1, "note": "draft"}"#),
+            Some("update"),
+            Some("k1"),
+            Some(r#"{"count": 2}"#),
+            Some("copy"),
+            Some("k1"),
+            Some("k2"),
+            Some("n"),
+            Some("delete"),
+            Some("k1"),
+        ]);
+        command_line::run(
+            &ddb,
+            &mut input,
+            &mut no_edits,
+            &Interrupt::default(),
+            &Config::default(),
+            Some(&table),
+        )
+        .await?;
+        assert!(input.lines.is_empty());
+
+        assert!(ddb
+            .get_item("notes", Item::new().set_string("id", "k1"))
+            .await?
+            .is_none());
+        let copy = ddb
+            .get_item("notes", Item::new().set_string("id", "k2"))
+            .await?
+            .context("Item wasn't copied")?;
+        assert_eq!(copy.get_number("count"), Some(2.0));
+        assert_eq!(copy.get_string("note").map(String::as_str), Some("draft"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_fixture_loading() -> Result<()> {
+        let ddb = InMemoryDynamoDb::new();
+        let table = Table::new("testing-events", "device", Some("timestamp")).with_schema(
+            Schema::new()
+                .add_field("device", FieldType::String)
+                .add_field("timestamp", FieldType::Number),
+        );
+        ddb.create_table_if_not_exists(&table).await?;
+
+        assert_eq!(load_fixture(&ddb, &table, "sensor_events.json").await?, 6);
+        assert_table_matches_fixture(&ddb, &table, "sensor_events.json").await?;
+
+        // Numbers compare by value and attributes in any order.
+        let key = Item::new()
+            .set_string("device", "sensor-1")
+            .set_number("timestamp", 50.0);
+        let mut item = ddb.get_item("testing-events", key.clone()).await?.unwrap();
+        item.attributes.insert(
+            "timestamp".to_string(),
+            AttributeValue::N("50.0".to_string()),
+        );
+        ddb.put_item("testing-events", item).await?;
+        assert_table_matches_fixture(&ddb, &table, "sensor_events.json").await?;
+
+        // Records that don't fit the schema fail the load.
+        let strings = Table::new("testing-events", "device", Some("timestamp")).with_schema(
+            Schema::new()
+                .add_field("device", FieldType::Number)
+                .add_field("timestamp", FieldType::Number),
+        );
+        let error = read_fixture(&strings, "sensor_events.json").unwrap_err();
+        assert!(error.to_string().contains("record 1"), "{error}");
+        assert!(read_fixture(&table, "missing.json").is_err());
+
+        ddb.put_item("testing-events", key.set_string("note", "extra"))
+            .await?;
+        // The futures aren't `Send`, so catch the panic on a local task.
+        let mismatch = tokio::task::LocalSet::new()
+            .run_until(async move {
+                tokio::task::spawn_local(async move {
+                    assert_table_matches_fixture(&ddb, &table, "sensor_events.json").await
+                })
+                .await
+            })
+            .await;
+        let message = *mismatch
+            .unwrap_err()
+            .into_panic()
+            .downcast::<String>()
+            .unwrap();
+        assert!(message.contains("\"note\": String(\"extra\")"), "{message}");
+
+        Ok(())
+    }
+
+    /// A table with a field of every schema type, for the item generator.
+    fn generated_table() -> Table<'static> {
+        Table::new("generated", "category", Some("product_name")).with_schema(
+            Schema::new()
+                .add_field("category", FieldType::String)
+                .add_field("product_name", FieldType::String)
+                .add_field("price", FieldType::Number)
+                .add_field("in_stock", FieldType::Boolean)
+                .add_field("colour", FieldType::String)
+                .add_field("tags", FieldType::StringSet)
+                .add_field("sizes", FieldType::NumberSet)
+                .add_field("notes", FieldType::StringList)
+                .add_field("history", FieldType::List)
+                .add_field("details", FieldType::Map),
+        )
+    }
+
+    #[test]
+    fn test_item_generator_is_deterministic() {
+        let table = generated_table();
+        let generator = ItemGenerator::new(table.schema().unwrap().clone(), &table);
+
+        let generate = |seed, n| {
+            generator
+                .clone()
+                .with_seed(seed)
+                .generate(n)
+                .into_iter()
+                .map(|item| item.attributes)
+                .collect::<Vec<_>>()
+        };
+
+        let first = generate(7, 50);
+        assert_eq!(generate(7, 50), first);
+        // A shorter run is a prefix of a longer one.
+        assert_eq!(generate(7, 10), first[..10]);
+        assert_ne!(generate(8, 50), first);
+    }
+
+    #[test]
+    fn test_item_generator_conforms_to_schema() {
+        let table = generated_table();
+        let colours = WeightedPool::new([("red", 3), ("blue", 1), ("green", 0)]);
+        let items = ItemGenerator::new(table.schema().unwrap().clone(), &table)
+            .with_seed(1)
+            .with_string_pool("colour", colours.clone())
+            .with_number_range("price", 5, 9)
+            .with_number_range("sizes", -2, 2)
+            .generate(500);
+        assert_eq!(items.len(), 500);
+
+        let fields = table.schema().unwrap().fields();
+        let mut keys = std::collections::HashSet::new();
+        for item in &items {
+            table.validate_item(item).unwrap();
+            assert_eq!(item.attributes.len(), fields.len(), "{item:?}");
+            assert!(keys.insert((
+                item.get_string("category").cloned(),
+                item.get_string("product_name").cloned(),
+            )));
+
+            let category = item.get_string("category").unwrap();
+            assert!(CATEGORIES.iter().any(|(name, _)| name == category));
+            assert!(colours.contains(item.get_string("colour").unwrap()));
+            assert!((5.0..=9.0).contains(&item.get_number("price").unwrap()));
+            let Some(AttributeValue::Ns(sizes)) = item.attributes.get("sizes") else {
+                panic!("sizes isn't a number set: {item:?}");
+            };
+            assert!(!sizes.is_empty());
+            assert!(sizes
+                .iter()
+                .all(|size| (-2..=2).contains(&size.parse::<i64>().unwrap())));
+        }
+
+        // Weighted pools draw in proportion: red is three times as likely as
+        // blue, and green is never drawn.
+        let red = items
+            .iter()
+            .filter(|item| item.get_string("colour").unwrap() == "red")
+            .count();
+        assert!((300..450).contains(&red), "{red} red of 500");
+
+        // Without a sort key, partition keys are unique, so there can't be
+        // more items than partition values.
+        let devices = Table::new("devices", "device", None);
+        let items = ItemGenerator::new(Schema::new(), &devices)
+            .with_partition_values(vec!["a", "b", "c"])
+            .generate(3);
+        let mut names: Vec<_> = items
+            .iter()
+            .map(|item| item.get_string("device").unwrap().as_str())
+            .collect();
+        names.sort();
+        assert_eq!(names, ["a", "b", "c"]);
+        let too_many = std::panic::catch_unwind(|| {
+            ItemGenerator::new(Schema::new(), &devices)
+                .with_partition_values(vec!["a", "b", "c"])
+                .generate(4)
+        });
+        assert!(too_many.is_err());
+
+        // Numeric sort keys count up like timestamps.
+        let events = Table::new("events", "device", Some("timestamp"))
+            .with_schema(Schema::new().add_field("timestamp", FieldType::Number));
+        let items = ItemGenerator::new(events.schema().unwrap().clone(), &events)
+            .with_partition_values(vec!["sensor-1"])
+            .generate(20);
+        let timestamps: Vec<_> = items
+            .iter()
+            .map(|item| item.get_number("timestamp").unwrap())
+            .collect();
+        assert!(timestamps.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_queries_match_generated_items() -> Result<()> {
+        let table = generated_table();
+        let ddb = InMemoryDynamoDb::new();
+        ddb.create_table_if_not_exists(&table).await?;
+
+        for seed in 0..5 {
+            let items = ItemGenerator::new(table.schema().unwrap().clone(), &table)
+                .with_seed(seed)
+                .with_weighted_partition_values(WeightedPool::new(CATEGORIES.iter().copied()))
+                .with_number_range("price", 0, 100)
+                .generate(120);
+            ddb.batch_put_items_cancellable(
+                "generated",
+                items.clone(),
+                None,
+                &CancellationToken::new(),
+            )
+            .await?;
+
+            // Each category's query returns exactly its items, in sort key
+            // order, and a filter keeps exactly the matching ones.
+            for (category, _) in CATEGORIES {
+                let key_condition =
+                    KeyCondition::partition("category", AttributeValue::S(category.to_string()))
+                        .build()?;
+                let mut expression_attribute_values = key_condition.values.clone();
+                expression_attribute_values
+                    .insert(":max".to_string(), AttributeValue::N("50".to_string()));
+                let found = ddb
+                    .query_all(
+                        QueryFlexibleParams {
+                            table_name: "generated",
+                            key_condition_expression: &key_condition.expression,
+                            expression_attribute_names: Some(key_condition.names.clone()),
+                            expression_attribute_values: Some(expression_attribute_values),
+                            filter_expression: Some("price <= :max"),
+                            limit: Some(7),
+                            ..Default::default()
+                        },
+                        None,
+                    )
+                    .await?;
+
+                let mut expected: Vec<_> = items
+                    .iter()
+                    .filter(|item| item.get_string("category").unwrap() == *category)
+                    .filter(|item| item.get_number("price").unwrap() <= 50.0)
+                    .collect();
+                expected.sort_by_key(|item| item.get_string("product_name").cloned());
+                assert_eq!(
+                    found
+                        .iter()
+                        .map(|item| &item.attributes)
+                        .collect::<Vec<_>>(),
+                    expected
+                        .iter()
+                        .map(|item| &item.attributes)
+                        .collect::<Vec<_>>(),
+                    "seed {seed}"
+                );
+            }
+
+            ddb.delete_table_if_exists_and_wait("generated", TABLE_WAIT_TIMEOUT)
+                .await?;
+            ddb.create_table_if_not_exists(&table).await?;
+        }
+        Ok(())
+    }
 }
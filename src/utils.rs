@@ -1,6 +1,9 @@
+use std::fmt;
 use std::future::Future;
-use tokio::time::{sleep, Duration};
-use tracing::info;
+use tokio::time::{sleep, Duration, Instant};
+use tracing::{info, warn};
+
+use crate::dynamodb::RequestFailed;
 
 /// Retries an asynchronous operation with exponential backoff.
 ///
@@ -29,16 +32,16 @@ use tracing::info;
 /// # Examples
 ///
 /// ```
+/// use aws_dynamodb_testing::utils::retry_with_backoff;
 /// use tokio::time::Duration;
-/// use your_crate::utils::retry_with_backoff;
 ///
-/// async fn fallible_operation() -> Result<(), std::io::Error> {
+/// async fn fallible_operation() -> anyhow::Result<()> {
 ///     // Your operation logic here
 ///     Ok(())
 /// }
 ///
 /// #[tokio::main]
-/// async fn main() -> Result<(), std::io::Error> {
+/// async fn main() -> anyhow::Result<()> {
 ///     let result = retry_with_backoff(
 ///         || fallible_operation(),
 ///         Duration::from_secs(1),
@@ -47,7 +50,6 @@ use tracing::info;
 ///     Ok(())
 /// }
 /// ```
-#[allow(dead_code)]
 pub async fn retry_with_backoff<T, E, Fut, F>(
     operation: F,
     initial_delay: Duration,
@@ -56,27 +58,308 @@ pub async fn retry_with_backoff<T, E, Fut, F>(
 where
     F: Fn() -> Fut,
     Fut: Future<Output = Result<T, E>>,
-    E: std::fmt::Debug,
+    E: RetryError,
 {
+    retry_with_backoff_if(operation, initial_delay, max_retries, |_| true).await
+}
+
+/// Like [`retry_with_backoff`], but only retries errors `should_retry`
+/// accepts; any other error is returned straight away.
+///
+/// For client errors, [`is_transient_error`] retries only throttling, 5xx
+/// and timeouts, so a permanent error such as a `ValidationException`
+/// fails on the first attempt.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use aws_dynamodb_testing::dynamodb::DynamoDb;
+/// # use aws_dynamodb_testing::utils::{is_transient_error, retry_with_backoff_if};
+/// # use tokio::time::Duration;
+/// # async fn example(ddb: &DynamoDb) -> anyhow::Result<()> {
+/// let table = retry_with_backoff_if(
+///     || ddb.describe_table("products"),
+///     Duration::from_secs(1),
+///     3,
+///     is_transient_error,
+/// ).await?;
+/// # Ok(())
+/// # }
+/// ```
+pub async fn retry_with_backoff_if<T, E, Fut, F, P>(
+    operation: F,
+    initial_delay: Duration,
+    max_retries: usize,
+    should_retry: P,
+) -> Result<T, E>
+where
+    F: Fn() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+    E: RetryError,
+    P: Fn(&E) -> bool,
+{
+    retry_with_config(
+        operation,
+        &RetryConfig::new(initial_delay, max_retries),
+        should_retry,
+    )
+    .await
+}
+
+/// How [`retry_with_config`] spreads out its retries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum JitterMode {
+    /// Sleep for exactly the backoff delay.
+    #[default]
+    None,
+    /// Sleep for a random time between zero and the backoff delay.
+    Full,
+    /// Sleep for half the backoff delay plus a random time up to the other
+    /// half.
+    Equal,
+}
+
+/// The backoff schedule for [`retry_with_config`].
+///
+/// Delays follow a Fibonacci sequence from `initial_delay`, each capped at
+/// `max_delay`, and are then jittered so that clients retrying at the same
+/// time don't stay in step.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryConfig {
+    pub initial_delay: Duration,
+    pub max_delay: Duration,
+    pub max_retries: usize,
+    pub jitter: JitterMode,
+    /// When to stop retrying, however many retries are left.
+    pub deadline: Option<Instant>,
+}
+
+impl RetryConfig {
+    /// A schedule with no cap and no jitter, as [`retry_with_backoff`] uses.
+    pub fn new(initial_delay: Duration, max_retries: usize) -> Self {
+        Self {
+            initial_delay,
+            max_delay: Duration::MAX,
+            max_retries,
+            jitter: JitterMode::None,
+            deadline: None,
+        }
+    }
+
+    /// Caps every delay at `max_delay`.
+    pub fn with_max_delay(mut self, max_delay: Duration) -> Self {
+        self.max_delay = max_delay;
+        self
+    }
+
+    /// Sets how delays are jittered.
+    pub fn with_jitter(mut self, jitter: JitterMode) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Gives up once a retry would start after `deadline`, returning the
+    /// last error annotated with how many attempts were made.
+    pub fn with_deadline(mut self, deadline: Instant) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Jitters a capped backoff delay.
+    fn jittered(&self, delay: Duration) -> Duration {
+        match self.jitter {
+            JitterMode::None => delay,
+            JitterMode::Full => random_up_to(delay),
+            JitterMode::Equal => delay / 2 + random_up_to(delay - delay / 2),
+        }
+    }
+}
+
+/// A random duration in `0..=max`.
+///
+/// Jitter only needs to differ between callers, so the standard library's
+/// randomly keyed hasher stands in for a random number generator.
+fn random_up_to(max: Duration) -> Duration {
+    use std::hash::{BuildHasher, Hasher};
+
+    let random = std::collections::hash_map::RandomState::new()
+        .build_hasher()
+        .finish();
+    let nanos = u64::try_from(max.as_nanos()).unwrap_or(u64::MAX);
+    Duration::from_nanos(random % nanos.saturating_add(1))
+}
+
+/// Retries an operation on the schedule in `config`, for errors
+/// `should_retry` accepts.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use aws_dynamodb_testing::dynamodb::DynamoDb;
+/// # use aws_dynamodb_testing::utils::{
+/// #     is_transient_error, retry_with_config, JitterMode, RetryConfig,
+/// # };
+/// # use tokio::time::Duration;
+/// # async fn example(ddb: &DynamoDb) -> anyhow::Result<()> {
+/// let config = RetryConfig::new(Duration::from_millis(100), 8)
+///     .with_max_delay(Duration::from_secs(5))
+///     .with_jitter(JitterMode::Full);
+/// let items = retry_with_config(|| ddb.scan_table("products"), &config, is_transient_error).await?;
+/// # Ok(())
+/// # }
+/// ```
+pub async fn retry_with_config<T, E, Fut, F, P>(
+    operation: F,
+    config: &RetryConfig,
+    should_retry: P,
+) -> Result<T, E>
+where
+    F: Fn() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+    E: RetryError,
+    P: Fn(&E) -> bool,
+{
+    retry_with_sleep(operation, config, should_retry, sleep).await
+}
+
+/// [`retry_with_config`] with the sleep passed in, so tests can record
+/// the delays instead of waiting them out.
+pub(crate) async fn retry_with_sleep<T, E, Fut, F, P, S, SleepFut>(
+    operation: F,
+    config: &RetryConfig,
+    should_retry: P,
+    sleep: S,
+) -> Result<T, E>
+where
+    F: Fn() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+    E: RetryError,
+    P: Fn(&E) -> bool,
+    S: Fn(Duration) -> SleepFut,
+    SleepFut: Future<Output = ()>,
+{
+    let max_retries = config.max_retries;
     let mut retries = 0;
-    let mut fib = (initial_delay, initial_delay);
+    let mut fib = (config.initial_delay, config.initial_delay);
 
     loop {
         match operation().await {
             Ok(result) => return Ok(result),
-            Err(e) if retries < max_retries => {
+            Err(e) if retries < max_retries && should_retry(&e) => {
+                let delay = config.jittered(fib.0.min(config.max_delay));
+                if config
+                    .deadline
+                    .is_some_and(|deadline| Instant::now() + delay >= deadline)
+                {
+                    warn!("Deadline exceeded after {} attempts: {e:?}", retries + 1);
+                    return Err(e.deadline_exceeded(retries + 1));
+                }
                 info!(
                     "Operation failed: {:?}. Retrying in {:?} (attempt {}/{})",
                     e,
-                    fib.0,
+                    delay,
                     retries + 1,
                     max_retries
                 );
-                sleep(fib.0).await;
+                sleep(delay).await;
                 retries += 1;
-                fib = (fib.1, fib.0 + fib.1);
+                fib = (fib.1, fib.0.saturating_add(fib.1));
             }
             Err(e) => return Err(e),
         }
     }
 }
+
+/// Errors the retry functions can return.
+///
+/// `deadline_exceeded` lets an error say that retrying stopped at the
+/// [`RetryConfig`] deadline rather than because retries ran out.
+pub trait RetryError: fmt::Debug {
+    fn deadline_exceeded(self, attempts: usize) -> Self;
+}
+
+impl RetryError for anyhow::Error {
+    fn deadline_exceeded(self, attempts: usize) -> Self {
+        self.context(DeadlineExceeded { attempts })
+    }
+}
+
+/// Plain messages, as test doubles return, can't carry the annotation.
+impl RetryError for &str {
+    fn deadline_exceeded(self, _attempts: usize) -> Self {
+        self
+    }
+}
+
+/// Context on the last error of a retry loop that ran out of time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DeadlineExceeded {
+    pub attempts: usize,
+}
+
+impl fmt::Display for DeadlineExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Deadline exceeded after {} attempts", self.attempts)
+    }
+}
+
+impl std::error::Error for DeadlineExceeded {}
+
+/// Returned by [`with_timeout`] and [`with_deadline`] when the future
+/// didn't finish in time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimedOut {
+    pub after: Duration,
+}
+
+impl fmt::Display for TimedOut {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Timed out after {:?}", self.after)
+    }
+}
+
+impl std::error::Error for TimedOut {}
+
+/// Runs `future`, giving up with [`TimedOut`] if it takes longer than
+/// `duration`.
+///
+/// ```no_run
+/// # use aws_dynamodb_testing::dynamodb::DynamoDb;
+/// # use aws_dynamodb_testing::utils::with_timeout;
+/// # use tokio::time::Duration;
+/// # async fn example(ddb: &DynamoDb) -> anyhow::Result<()> {
+/// let tables = with_timeout(Duration::from_secs(10), ddb.list_tables()).await??;
+/// # Ok(())
+/// # }
+/// ```
+pub async fn with_timeout<F: Future>(duration: Duration, future: F) -> Result<F::Output, TimedOut> {
+    tokio::time::timeout(duration, future)
+        .await
+        .map_err(|_| TimedOut { after: duration })
+}
+
+/// Runs `future`, giving up with [`TimedOut`] if it hasn't finished by
+/// `deadline`.
+///
+/// Useful around a whole retry loop, so that attempts and backoff
+/// together stay within one time budget.
+pub async fn with_deadline<F: Future>(deadline: Instant, future: F) -> Result<F::Output, TimedOut> {
+    let start = Instant::now();
+    tokio::time::timeout_at(deadline, future)
+        .await
+        .map_err(|_| TimedOut {
+            after: start.elapsed(),
+        })
+}
+
+/// Whether an error from the DynamoDB client came from a request worth
+/// retrying, as judged by
+/// [`is_transient`](crate::dynamodb::is_transient) when the request failed.
+///
+/// Errors that didn't come from a request, such as a bad table definition
+/// caught before sending, are never retried.
+pub fn is_transient_error(error: &anyhow::Error) -> bool {
+    error
+        .downcast_ref::<RequestFailed>()
+        .is_some_and(|failed| failed.transient)
+}
@@ -0,0 +1,241 @@
+//! Property tests for converting items to and from JSON and typed values.
+//!
+//! The strategies generate arbitrary items: every attribute type, lists and
+//! maps nested up to three levels, and numbers written in the awkward ways
+//! DynamoDB accepts (`1.50`, `-0`, `1E+3`, 38 significant digits). When a
+//! property fails, proptest shrinks the item towards the fewest and
+//! simplest attributes that still fail and prints it.
+//!
+//! Failing cases are saved under `proptest-regressions/` and rerun first
+//! next time; commit them along with the fix.
+//!
+//! They only use the crate's public API, as a caller would. The ones for
+//! `testdata::ItemGenerator` need the `test-util` feature.
+#![cfg(feature = "client")]
+
+use aws_dynamodb_testing::aws::{AttributeValue, Blob};
+#[cfg(feature = "test-util")]
+use aws_dynamodb_testing::dynamodb::{FieldType, Schema, Table};
+use aws_dynamodb_testing::dynamodb::{Item, JsonFormat};
+#[cfg(feature = "test-util")]
+use aws_dynamodb_testing::testdata::ItemGenerator;
+use proptest::collection::{hash_map, hash_set, vec};
+use proptest::prelude::*;
+use proptest::test_runner::FileFailurePersistence;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+#[cfg(feature = "test-util")]
+use std::collections::HashSet;
+
+/// How deep lists and maps nest inside an attribute.
+const MAX_DEPTH: u32 = 3;
+
+fn attribute_name() -> impl Strategy<Value = String> {
+    prop_oneof!["[a-z_]{1,8}", "\\PC{1,8}"]
+}
+
+fn string() -> impl Strategy<Value = String> {
+    prop_oneof!["[a-zA-Z0-9 ]{0,12}", "\\PC{0,12}"]
+}
+
+/// Numbers as DynamoDB might hold them, including forms that only compare
+/// equal after normalisation and values beyond `f64` precision.
+fn number() -> impl Strategy<Value = String> {
+    prop_oneof![
+        plain_number(),
+        (any::<i64>(), 0..4usize).prop_map(|(i, zeros)| format!("{i}.{}", "0".repeat(zeros + 1))),
+        (-130i32..=125, 1..=9u8).prop_map(|(exponent, digit)| format!("{digit}E{exponent:+}")),
+        "-?[1-9][0-9]{37}",
+        "-?0\\.[0-9]{1,37}[1-9]",
+        Just("-0".to_string()),
+        Just("0.000".to_string()),
+    ]
+}
+
+/// Numbers plain JSON keeps exactly: any integer that fits an `i64`, and
+/// anything else an `f64` holds exactly.
+fn plain_number() -> impl Strategy<Value = String> {
+    prop_oneof![any::<i64>().prop_map(|i| i.to_string()), f64_number()]
+}
+
+/// Numbers that survive a trip through `f64`, in several spellings.
+fn f64_number() -> impl Strategy<Value = String> {
+    (finite_f64(), 0..3u8).prop_map(|(f, style)| match style {
+        0 => format!("{f}"),
+        1 => format!("{f:e}"),
+        _ if format!("{f}").contains('.') => format!("{f}00"),
+        _ => format!("{f}.00"),
+    })
+}
+
+fn binary() -> impl Strategy<Value = Blob> {
+    vec(any::<u8>(), 0..16).prop_map(Blob::new)
+}
+
+/// Any attribute value, with lists and maps nested up to `MAX_DEPTH` deep.
+fn attribute_value() -> impl Strategy<Value = AttributeValue> {
+    let leaf = prop_oneof![
+        string().prop_map(AttributeValue::S),
+        number().prop_map(AttributeValue::N),
+        binary().prop_map(AttributeValue::B),
+        any::<bool>().prop_map(AttributeValue::Bool),
+        Just(AttributeValue::Null(true)),
+        hash_set(string(), 1..4).prop_map(|set| AttributeValue::Ss(set.into_iter().collect())),
+        hash_set(number(), 1..4).prop_map(|set| AttributeValue::Ns(set.into_iter().collect())),
+        hash_set(vec(any::<u8>(), 0..8), 1..4)
+            .prop_map(|set| AttributeValue::Bs(set.into_iter().map(Blob::new).collect())),
+    ];
+    nested(leaf)
+}
+
+/// The values plain JSON keeps the type of: no sets or binary, and only
+/// numbers an `f64` holds exactly.
+fn plain_attribute_value() -> impl Strategy<Value = AttributeValue> {
+    let leaf = prop_oneof![
+        string().prop_map(AttributeValue::S),
+        plain_number().prop_map(AttributeValue::N),
+        any::<bool>().prop_map(AttributeValue::Bool),
+        Just(AttributeValue::Null(true)),
+    ];
+    nested(leaf)
+}
+
+fn nested(
+    leaf: impl Strategy<Value = AttributeValue> + 'static,
+) -> impl Strategy<Value = AttributeValue> {
+    leaf.prop_recursive(MAX_DEPTH, 32, 4, |inner| {
+        prop_oneof![
+            vec(inner.clone(), 0..4).prop_map(AttributeValue::L),
+            hash_map(attribute_name(), inner, 0..4).prop_map(AttributeValue::M),
+        ]
+    })
+}
+
+fn item(value: impl Strategy<Value = AttributeValue>) -> impl Strategy<Value = Item> {
+    hash_map(attribute_name(), value, 0..6).prop_map(Item::from)
+}
+
+#[cfg(feature = "serde")]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Order {
+    id: String,
+    quantity: u32,
+    total: f64,
+    discount: Option<i64>,
+    gift: bool,
+    lines: Vec<OrderLine>,
+    notes: HashMap<String, String>,
+}
+
+#[cfg(feature = "serde")]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct OrderLine {
+    sku: String,
+    price: f64,
+    tags: Vec<String>,
+}
+
+/// A table with a field of every schema type, for `ItemGenerator`.
+#[cfg(feature = "test-util")]
+fn generated_table(sort_key: Option<&'static str>) -> Table<'static> {
+    Table::new("generated", "id", sort_key).with_schema(
+        Schema::new()
+            .add_field("price", FieldType::Number)
+            .add_field("in_stock", FieldType::Boolean)
+            .add_field("tags", FieldType::StringSet)
+            .add_field("sizes", FieldType::NumberSet)
+            .add_field("notes", FieldType::StringList)
+            .add_field("history", FieldType::List)
+            .add_field("details", FieldType::Map),
+    )
+}
+
+fn finite_f64() -> impl Strategy<Value = f64> {
+    any::<f64>().prop_filter("finite", |f| f.is_finite())
+}
+
+#[cfg(feature = "serde")]
+fn order() -> impl Strategy<Value = Order> {
+    let line = (string(), finite_f64(), vec(string(), 0..3))
+        .prop_map(|(sku, price, tags)| OrderLine { sku, price, tags });
+    (
+        string(),
+        any::<u32>(),
+        finite_f64(),
+        any::<Option<i64>>(),
+        any::<bool>(),
+        vec(line, 0..3),
+        hash_map(attribute_name(), string(), 0..3),
+    )
+        .prop_map(
+            |(id, quantity, total, discount, gift, lines, notes)| Order {
+                id,
+                quantity,
+                total,
+                discount,
+                gift,
+                lines,
+                notes,
+            },
+        )
+}
+
+proptest! {
+    // Where they were saved while these tests lived in `src/`; proptest
+    // can't work that out for a file under `tests/`.
+    #![proptest_config(ProptestConfig {
+        failure_persistence: Some(Box::new(FileFailurePersistence::Direct(
+            "proptest-regressions/proptests.txt",
+        ))),
+        ..ProptestConfig::default()
+    })]
+
+    #[test]
+    fn dynamodb_json_round_trips(item in item(attribute_value())) {
+        let json = item.to_json(JsonFormat::DynamoDb);
+        prop_assert_eq!(Item::from_dynamodb_json(json).unwrap(), item);
+    }
+
+    #[test]
+    fn plain_json_round_trips(item in item(plain_attribute_value())) {
+        let json = item.to_json(JsonFormat::Plain);
+        prop_assert_eq!(Item::from_json(&json).unwrap(), item);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn typed_values_round_trip(order in order()) {
+        let item = Item::from_typed(&order).unwrap();
+        prop_assert_eq!(item.into_typed::<Order>().unwrap(), order);
+    }
+
+    #[cfg(feature = "test-util")]
+    #[test]
+    fn generated_items_fit_their_table(
+        seed in any::<u64>(),
+        n in 0..40usize,
+        sort_key in prop_oneof![Just(None), Just(Some("created_at"))],
+    ) {
+        let table = generated_table(sort_key);
+        let items = ItemGenerator::new(table.schema().unwrap().clone(), &table)
+            .with_seed(seed)
+            .generate(n);
+        prop_assert_eq!(items.len(), n);
+
+        let mut keys = HashSet::new();
+        for item in &items {
+            prop_assert!(table.validate_item(item).is_ok(), "{:?}", item);
+            let json = item.to_json(JsonFormat::DynamoDb);
+            let key = (json["id"].to_string(), sort_key.map(|name| json[name].to_string()));
+            prop_assert!(keys.insert(key), "Duplicate key in {:?}", item);
+            prop_assert_eq!(&Item::from_dynamodb_json(json).unwrap(), item);
+        }
+    }
+
+    #[test]
+    fn equality_ignores_number_spelling(n in f64_number()) {
+        let spelled = Item::from(HashMap::from([("n".to_string(), AttributeValue::N(n.clone()))]));
+        prop_assert_eq!(Item::new().set_number("n", n.parse::<f64>().unwrap()), spelled);
+    }
+}